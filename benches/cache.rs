@@ -0,0 +1,52 @@
+//! Metadata cache reads: hits in the in-memory cache and entries read back from disk.
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use lectern::cache::{cache_get_meta, cache_set_meta};
+use lectern::core::cache_utils::{CacheEntry, read_cache_entry};
+use std::hint::black_box;
+use std::time::Duration;
+
+/// Packagist metadata for one package with `count` releases
+fn metadata(count: usize) -> serde_json::Value {
+    let versions: Vec<serde_json::Value> = (0..count)
+        .map(|i| {
+            serde_json::json!({
+                "version": format!("{}.{}.{}", i / 100, i / 10 % 10, i % 10),
+                "require": {"php": ">=8.1", "psr/log": "^3.0"},
+                "dist": {"type": "zip", "url": format!("https://example.com/{i}.zip"), "reference": format!("{i:040x}")}
+            })
+        })
+        .collect();
+    serde_json::Value::Array(versions)
+}
+
+fn bench_cache(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let data = metadata(300);
+    let mut group = c.benchmark_group("cache_read");
+
+    let key = "p2:lectern-bench/cache";
+    runtime.block_on(cache_set_meta(key, data.clone()));
+    group.bench_function("memory", |b| {
+        b.iter(|| runtime.block_on(cache_get_meta(black_box(key))).is_some())
+    });
+
+    // Disk entries are read once per process before the memory cache takes over, so the
+    // file is read directly to measure that first lookup
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("entry.json");
+    let entry = serde_json::to_string(&CacheEntry::new(data, Duration::from_secs(3600))).unwrap();
+    std::fs::write(&path, &entry).unwrap();
+    group.throughput(Throughput::Bytes(entry.len() as u64));
+    group.bench_function("disk", |b| {
+        b.iter(|| {
+            runtime
+                .block_on(read_cache_entry(black_box(&path)))
+                .is_some()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_cache);
+criterion_main!(benches);
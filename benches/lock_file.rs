@@ -0,0 +1,58 @@
+//! Writing and reading composer.lock for projects of increasing size.
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use lectern::io::lock_to_string;
+use lectern::models::model::Lock;
+use std::hint::black_box;
+
+/// A lock of `count` packages carrying the fields Packagist fills in
+fn lock_of(count: usize) -> Lock {
+    let packages: Vec<serde_json::Value> = (0..count)
+        .map(|i| {
+            let name = format!("vendor{}/package{i}", i % 40);
+            serde_json::json!({
+                "name": name,
+                "version": format!("v{}.{}.{}", i % 7, i % 11, i % 13),
+                "source": {"type": "git", "url": format!("https://github.com/{name}.git"), "reference": format!("{i:040x}")},
+                "dist": {"type": "zip", "url": format!("https://api.github.com/repos/{name}/zipball/{i:040x}"), "reference": format!("{i:040x}"), "shasum": ""},
+                "require": {"php": ">=8.1", "psr/log": "^1.0 || ^2.0 || ^3.0"},
+                "type": "library",
+                "autoload": {"psr-4": {format!("Vendor{}\\Package{i}\\", i % 40): "src/"}},
+                "license": ["MIT"],
+                "description": "A package used to measure lock file serialization",
+                "time": "2024-05-01T12:00:00+00:00"
+            })
+        })
+        .collect();
+    serde_json::from_value(serde_json::json!({
+        "content-hash": "0123456789abcdef0123456789abcdef",
+        "packages": packages,
+        "packages-dev": []
+    }))
+    .unwrap()
+}
+
+fn bench_lock_file(c: &mut Criterion) {
+    let mut serialize = c.benchmark_group("lock_serialize");
+    for count in [50, 500] {
+        let lock = lock_of(count);
+        serialize.throughput(Throughput::Elements(count as u64));
+        serialize.bench_with_input(BenchmarkId::from_parameter(count), &lock, |b, lock| {
+            b.iter(|| lock_to_string(black_box(lock)).unwrap())
+        });
+    }
+    serialize.finish();
+
+    let mut parse = c.benchmark_group("lock_parse");
+    for count in [50, 500] {
+        let text = lock_to_string(&lock_of(count)).unwrap();
+        parse.throughput(Throughput::Bytes(text.len() as u64));
+        parse.bench_with_input(BenchmarkId::from_parameter(count), &text, |b, text| {
+            b.iter(|| serde_json::from_str::<Lock>(black_box(text)).unwrap())
+        });
+    }
+    parse.finish();
+}
+
+criterion_group!(benches, bench_lock_file);
+criterion_main!(benches);
@@ -0,0 +1,100 @@
+//! Classmap scanning over a vendor-sized tree. Set `LECTERN_BENCH_VENDOR` to a real
+//! `vendor` directory to scan it as well.
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use lectern::php_scanner::{find_classes, find_classes_in_file};
+use std::hint::black_box;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// A typical library file: docblocks, imports, strings, a heredoc and one class
+fn synthetic_file(package: usize, file: usize) -> String {
+    format!(
+        r#"<?php
+
+declare(strict_types=1);
+
+namespace Vendor{package}\Component\Sub{file};
+
+use Psr\Log\LoggerInterface;
+use Vendor{package}\Contracts\{{Service, Factory}};
+
+/**
+ * Builds things. The class keyword in this comment must be skipped: class Fake {{}}
+ */
+#[\Attribute(\Attribute::TARGET_CLASS)]
+final class Service{file} implements Service
+{{
+    private const TEMPLATE = <<<'SQL'
+        SELECT * FROM items WHERE class = 'x'
+        SQL;
+
+    public function __construct(private LoggerInterface $logger) {{}}
+
+    public function make(array $options = []): Factory
+    {{
+        $this->logger->debug("Making {{$options['name']}}", ['class' => self::class]);
+        return new class($options) implements Factory {{
+            public function __construct(private array $options) {{}}
+        }};
+    }}
+}}
+"#
+    )
+}
+
+fn php_files(dir: &Path) -> Vec<PathBuf> {
+    WalkDir::new(dir)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_file() && e.path().extension().is_some_and(|e| e == "php"))
+        .map(|e| e.into_path())
+        .collect()
+}
+
+fn bench_scanner(c: &mut Criterion) {
+    let source = synthetic_file(1, 1);
+    let mut group = c.benchmark_group("php_scanner");
+    group.throughput(Throughput::Bytes(source.len() as u64));
+    group.bench_function("find_classes", |b| {
+        b.iter(|| find_classes(black_box(source.as_bytes())))
+    });
+    group.finish();
+
+    // 200 packages of 25 files each, about the size of a framework application's vendor
+    let tree = tempfile::TempDir::new().unwrap();
+    for package in 0..200 {
+        let dir = tree.path().join(format!("vendor{package}/src"));
+        std::fs::create_dir_all(&dir).unwrap();
+        for file in 0..25 {
+            std::fs::write(
+                dir.join(format!("Service{file}.php")),
+                synthetic_file(package, file),
+            )
+            .unwrap();
+        }
+    }
+    let mut trees = vec![("synthetic".to_string(), tree.path().to_path_buf())];
+    if let Ok(vendor) = std::env::var("LECTERN_BENCH_VENDOR") {
+        trees.push(("vendor".to_string(), PathBuf::from(vendor)));
+    }
+
+    let mut group = c.benchmark_group("classmap_tree");
+    group.sample_size(10);
+    for (label, dir) in trees {
+        let files = php_files(&dir);
+        group.throughput(Throughput::Elements(files.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(label), &files, |b, files| {
+            b.iter(|| {
+                files
+                    .iter()
+                    .map(|file| find_classes_in_file(file).unwrap().len())
+                    .sum::<usize>()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_scanner);
+criterion_main!(benches);
@@ -0,0 +1,121 @@
+//! Constraint parsing, version selection and p2 metadata parsing, the inner loop of
+//! dependency resolution.
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use lectern::resolver::dependency::find_best_version;
+use lectern::resolver::packagist::{P2Version, parse_p2};
+use lectern::resolver::parse_constraint;
+use std::hint::black_box;
+
+/// Constraints as they appear in real composer.json files
+const CONSTRAINTS: &[&str] = &[
+    "^7.4",
+    "~2.1.0",
+    ">=1.0 <2.0",
+    "^5.4 || ^6.0 || ^7.0",
+    "1.2.*",
+    "v3.0.0",
+    ">=8.1",
+    "^1.0@dev",
+    "dev-main as 1.5.x-dev",
+];
+
+/// `count` releases across majors and minors, newest first like Packagist lists them,
+/// with a pre-release before every minor
+fn version_list(count: usize) -> Vec<P2Version> {
+    let mut versions = Vec::with_capacity(count);
+    'outer: for major in (0..20).rev() {
+        for minor in (0..10).rev() {
+            for patch in (0..10).rev() {
+                if versions.len() == count {
+                    break 'outer;
+                }
+                let version = if patch == 0 {
+                    format!("{major}.{minor}.0-RC1")
+                } else {
+                    format!("v{major}.{minor}.{patch}")
+                };
+                versions.push(
+                    serde_json::from_value(serde_json::json!({
+                        "version": version,
+                        "version_normalized": format!("{major}.{minor}.{patch}.0"),
+                        "require": {"php": ">=8.1"}
+                    }))
+                    .unwrap(),
+                );
+            }
+        }
+    }
+    versions
+}
+
+fn bench_parse_constraint(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_constraint");
+    for constraint in CONSTRAINTS {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(constraint),
+            constraint,
+            |b, constraint| b.iter(|| parse_constraint(black_box(constraint))),
+        );
+    }
+    group.finish();
+}
+
+fn bench_find_best_version(c: &mut Criterion) {
+    let mut group = c.benchmark_group("find_best_version");
+    let constraint = parse_constraint("^5.4 || ^6.0").unwrap();
+    for count in [100, 1_000, 2_000] {
+        let versions = version_list(count);
+        group.throughput(Throughput::Elements(count as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(count),
+            &versions,
+            |b, versions| b.iter(|| find_best_version(black_box(versions), &constraint).is_ok()),
+        );
+    }
+    group.finish();
+}
+
+/// A p2 metadata file for `acme/lib` with `count` versions, the later ones minified
+/// with `"__unset"` the way Packagist serves them
+fn p2_body(count: usize) -> Vec<u8> {
+    let versions: Vec<serde_json::Value> = (0..count)
+        .map(|i| {
+            serde_json::json!({
+                "name": "acme/lib",
+                "version": format!("{}.{}.{}", i / 100, i / 10 % 10, i % 10),
+                "version_normalized": format!("{}.{}.{}.0", i / 100, i / 10 % 10, i % 10),
+                "dist": {"type": "zip", "url": format!("https://example.com/{i}.zip"), "reference": format!("{i:040x}"), "shasum": ""},
+                "require": {"php": ">=8.1", "psr/log": if i % 3 == 0 { "__unset" } else { "^3.0" }},
+                "extra": if i % 2 == 0 { serde_json::json!("__unset") } else { serde_json::json!({"branch-alias": {"dev-main": "1.x-dev"}}) },
+                "license": ["MIT"],
+                "time": "2024-05-01T12:00:00+00:00"
+            })
+        })
+        .collect();
+    serde_json::to_vec(&serde_json::json!({
+        "minified": "composer/2.0",
+        "packages": {"acme/lib": versions}
+    }))
+    .unwrap()
+}
+
+fn bench_parse_p2(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_p2");
+    for count in [100, 1_000] {
+        let body = p2_body(count);
+        group.throughput(Throughput::Bytes(body.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(count), &body, |b, body| {
+            b.iter(|| parse_p2("acme/lib", black_box(body)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_parse_constraint,
+    bench_find_best_version,
+    bench_parse_p2
+);
+criterion_main!(benches);
@@ -13,13 +13,17 @@ pub struct Cli {
     pub working_dir: PathBuf,
 
     /// Verbosity level (-v, -vv, -vvv)
-    #[arg(short = 'v', action = clap::ArgAction::Count)]
+    #[arg(short = 'v', long = "verbose", action = clap::ArgAction::Count)]
     pub verbose: u8,
 
-    /// Suppress output
+    /// Suppress output except errors
     #[arg(short = 'q', long = "quiet")]
     pub quiet: bool,
 
+    /// How status messages are printed (console, json)
+    #[arg(long = "message-format", default_value = "console")]
+    pub message_format: String,
+
     /// Disable interaction
     #[arg(short = 'n', long = "no-interaction")]
     pub no_interaction: bool,
@@ -32,6 +36,10 @@ pub struct Cli {
     #[arg(long = "minimum-stability", default_value = "stable")]
     pub minimum_stability: String,
 
+    /// Display timing, memory and resolver statistics, per phase and for the slowest packages
+    #[arg(long = "profile")]
+    pub profile: bool,
+
     /// Memory limit in MB
     #[arg(long = "memory-limit", default_value = "512")]
     pub memory_limit: u32,
@@ -59,11 +67,11 @@ pub enum Commands {
     /// Initialize a new project
     Init(InitArgs),
     /// List outdated packages
-    Outdated,
+    Outdated(OutdatedArgs),
     /// List installed packages
     Status,
     /// Show licenses of dependencies
-    Licenses,
+    Licenses(LicensesArgs),
     /// Validate composer.json
     Validate(ValidateArgs),
     /// Create a new project from a package
@@ -81,15 +89,66 @@ pub enum Commands {
     /// Get and set configuration options
     Config(ConfigArgs),
     /// Show which packages depend on a given package
+    #[command(alias = "why")]
     Depends(DependsArgs),
     /// Show which packages prevent installing a given package
+    #[command(alias = "why-not")]
     Prohibits(ProhibitsArgs),
     /// Open package repository URL in browser
     Browse(BrowseArgs),
-    /// Show suggested packages
-    Suggests,
+    /// Show packages suggested by locked packages that aren't installed
+    Suggests(SuggestsArgs),
     /// Show funding information
-    Fund,
+    Fund(FundArgs),
+    /// Check that composer.lock is up to date with composer.json
+    CheckLock,
+    /// Remove vendor, autoload files, caches or composer.lock
+    Clean(CleanArgs),
+    /// Undo the last require/remove/update/install/bump
+    Rollback,
+    /// Write license and copyright notices of installed packages to a file
+    ThirdPartyFile(ThirdPartyFileArgs),
+    /// Check whether the locked packages support another PHP version
+    PhpCompat(PhpCompatArgs),
+    /// Print a hash of the locked dependencies for use as a CI cache key
+    Fingerprint(FingerprintArgs),
+    /// Raise composer.json constraints to the currently locked versions
+    Bump(BumpArgs),
+    /// Print Dockerfile and devcontainer snippets for caching dependencies
+    DockerCacheConfig(DockerCacheConfigArgs),
+    /// Delete packages from vendor and install them again from composer.lock
+    Reinstall(ReinstallArgs),
+    /// Export a software bill of materials (CycloneDX or SPDX) from composer.lock
+    Sbom(SbomArgs),
+    /// Reinstall changed packages, remove extraneous ones and regenerate autoload to match composer.lock
+    Sync(SyncArgs),
+    /// Start an interactive shell that keeps metadata and caches loaded between commands
+    Shell,
+    /// Run require, remove, update or show against the per-user global project
+    Global(GlobalArgs),
+    /// Update lectern to the latest release, or restore the previous binary
+    SelfUpdate(SelfUpdateArgs),
+    /// List commands and the scripts of composer.json
+    List,
+    /// Run a binary of an installed package, e.g. `lectern exec phpunit -- --filter Foo`
+    Exec(ExecArgs),
+    /// Export the dependency graph of composer.lock as Graphviz DOT, Mermaid or JSON
+    Graph(GraphArgs),
+    /// Resolve, install or inspect the members of a monorepo workspace together
+    Workspace(WorkspaceArgs),
+    /// Translate the manifest between composer.json and Lectern.toml
+    Convert(ConvertArgs),
+    /// A script of composer.json run by its name, like `lectern test`
+    #[command(external_subcommand)]
+    Script(Vec<String>),
+}
+
+/// One line typed into `lectern shell`: a command without the program name or global options
+#[derive(Parser, Debug)]
+#[command(name = "lectern", no_binary_name = true, disable_version_flag = true)]
+pub struct ShellLine {
+    #[command(subcommand)]
+    pub command: Commands,
 }
 
 #[derive(Args, Debug)]
@@ -117,6 +176,22 @@ pub struct InstallArgs {
     /// Optimize autoloader
     #[arg(long = "optimize-autoloader")]
     pub optimize_autoloader: bool,
+
+    /// Resolve for an environment declared in extra.lectern.platform-overrides
+    #[arg(long = "env")]
+    pub env: Option<String>,
+
+    /// Check the resulting lock against security advisories
+    #[arg(long = "audit")]
+    pub audit: bool,
+
+    /// Like --audit, but fail when an advisory affects a locked package
+    #[arg(long = "audit-strict")]
+    pub audit_strict: bool,
+
+    /// Fail instead of warning when an abandoned package would be installed
+    #[arg(long = "no-abandoned")]
+    pub no_abandoned: bool,
 }
 
 #[derive(Args, Debug)]
@@ -151,6 +226,38 @@ pub struct UpdateArgs {
     /// Optimize autoloader
     #[arg(long = "optimize-autoloader")]
     pub optimize_autoloader: bool,
+
+    /// Resolve for an environment declared in extra.lectern.platform-overrides
+    #[arg(long = "env")]
+    pub env: Option<String>,
+
+    /// Only use releases published before this date (YYYY-MM-DD)
+    #[arg(long = "until")]
+    pub until: Option<String>,
+
+    /// Only refresh composer.lock's hash and root settings, keeping locked versions
+    #[arg(long = "lock", conflicts_with_all = ["until", "packages"])]
+    pub lock: bool,
+
+    /// Write composer.lock without installing into vendor/
+    #[arg(long = "no-install")]
+    pub no_install: bool,
+
+    /// Check the resulting lock against security advisories
+    #[arg(long = "audit")]
+    pub audit: bool,
+
+    /// Like --audit, but fail when an advisory affects a locked package
+    #[arg(long = "audit-strict")]
+    pub audit_strict: bool,
+
+    /// Fail instead of warning when an abandoned package would be installed
+    #[arg(long = "no-abandoned")]
+    pub no_abandoned: bool,
+
+    /// Keep a package at its locked version; accepts globs like symfony/* and may repeat
+    #[arg(long = "ignore", value_name = "PACKAGE")]
+    pub ignore: Vec<String>,
 }
 
 #[derive(Args, Debug)]
@@ -170,10 +277,26 @@ pub struct RequireArgs {
     #[arg(long = "no-update")]
     pub no_update: bool,
 
-    /// Update with dependencies
-    #[arg(long = "update-with-dependencies")]
+    /// Write composer.json and composer.lock without installing into vendor/
+    #[arg(long = "no-install")]
+    pub no_install: bool,
+
+    /// Fail instead of warning when an abandoned package would be installed
+    #[arg(long = "no-abandoned")]
+    pub no_abandoned: bool,
+
+    /// Let the dependencies of the new packages update too, except root requirements
+    #[arg(long = "update-with-dependencies", short = 'w')]
     pub update_with_dependencies: bool,
 
+    /// Let the dependencies of the new packages update, root requirements included
+    #[arg(
+        long = "update-with-all-dependencies",
+        short = 'W',
+        conflicts_with = "update_with_dependencies"
+    )]
+    pub update_with_all_dependencies: bool,
+
     /// Ignore platform requirements
     #[arg(long = "ignore-platform-reqs")]
     pub ignore_platform_reqs: bool,
@@ -184,7 +307,7 @@ pub struct RemoveArgs {
     /// Packages to remove
     pub packages: Vec<String>,
 
-    /// Remove from dev dependencies
+    /// Only remove from dev dependencies
     #[arg(long = "dev")]
     pub dev: bool,
 
@@ -196,6 +319,14 @@ pub struct RemoveArgs {
     #[arg(long = "no-update")]
     pub no_update: bool,
 
+    /// Write composer.json and composer.lock without installing into vendor/
+    #[arg(long = "no-install")]
+    pub no_install: bool,
+
+    /// Don't install dev dependencies
+    #[arg(long = "no-dev")]
+    pub no_dev: bool,
+
     /// Update with dependencies
     #[arg(long = "update-with-dependencies")]
     pub update_with_dependencies: bool,
@@ -206,14 +337,21 @@ pub struct ShowArgs {
     /// Package name to show info for
     pub package: Option<String>,
 
+    /// Version of the package to show, installed or not
+    pub version: Option<String>,
+
     /// Show available versions
     #[arg(long = "available")]
     pub available: bool,
 
-    /// Include platform packages
+    /// List the platform packages (php, ext-*, lib-*) detected for the PHP on PATH
     #[arg(long = "platform")]
     pub platform: bool,
 
+    /// List the platform packages after the installed ones
+    #[arg(long = "all")]
+    pub all: bool,
+
     /// Show only direct dependencies
     #[arg(long = "direct")]
     pub direct: bool,
@@ -225,6 +363,10 @@ pub struct ShowArgs {
     /// Output format (table, json)
     #[arg(long = "format", default_value = "table")]
     pub format: String,
+
+    /// Aggregate lock, requirer, advisory, funding and vendor details for one package
+    #[arg(long = "full")]
+    pub full: bool,
 }
 
 #[derive(Args, Debug)]
@@ -351,13 +493,22 @@ pub struct RunScriptArgs {
     /// Script name to run
     pub script: String,
 
-    /// Additional arguments to pass to the script
+    /// Additional arguments to pass to the script, after `--`
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
     pub args: Vec<String>,
 
-    /// Run in dev mode
-    #[arg(long = "dev")]
+    /// Run in dev mode (the default)
+    #[arg(long = "dev", conflicts_with = "no_dev")]
     pub dev: bool,
 
+    /// Run with COMPOSER_DEV_MODE=0
+    #[arg(long = "no-dev")]
+    pub no_dev: bool,
+
+    /// Seconds each command may run, overriding `config.process-timeout` (0 for no limit)
+    #[arg(long = "timeout")]
+    pub timeout: Option<u64>,
+
     /// List available scripts
     #[arg(long = "list")]
     pub list: bool,
@@ -384,10 +535,62 @@ pub struct ArchiveArgs {
     pub format: String,
 }
 
+#[derive(Args, Debug)]
+pub struct OutdatedArgs {
+    /// Check every workspace member and show one aggregated matrix
+    #[arg(long = "workspace")]
+    pub workspace: bool,
+
+    /// Leave a package out of the report; accepts globs like symfony/* and may repeat
+    #[arg(long = "ignore", value_name = "PACKAGE")]
+    pub ignore: Vec<String>,
+}
+
 #[derive(Args, Debug)]
 pub struct ClearCacheArgs {
-    /// Clear specific cache type (repo, files, vcs, all)
+    /// Clear specific cache type (repo, files, vcs, dependency_resolution, all)
     pub cache_type: Option<String>,
+
+    /// Show how much each cache type holds without removing anything
+    #[arg(long = "dry-run")]
+    pub dry_run: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct CleanArgs {
+    /// What to remove (vendor, autoload, cache, lock); defaults to vendor
+    pub targets: Vec<String>,
+
+    /// Remove vendor, cache and composer.lock
+    #[arg(long = "all")]
+    pub all: bool,
+
+    /// List what would be removed without deleting anything
+    #[arg(long = "dry-run")]
+    pub dry_run: bool,
+
+    /// Remove composer.lock without asking
+    #[arg(long = "force", short = 'f')]
+    pub force: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ThirdPartyFileArgs {
+    /// File to write, relative to the working directory
+    #[arg(long = "output", short = 'o', default_value = "THIRD-PARTY-NOTICES")]
+    pub output: String,
+
+    /// Output format (text, markdown)
+    #[arg(long = "format", default_value = "text")]
+    pub format: String,
+
+    /// Per-package template with {name}, {version}, {license}, {authors}, {homepage}, {license_text}
+    #[arg(long = "template")]
+    pub template: Option<String>,
+
+    /// Include dev dependencies
+    #[arg(long = "dev")]
+    pub dev: bool,
 }
 
 #[derive(Args, Debug)]
@@ -458,3 +661,229 @@ pub struct BrowseArgs {
     #[arg(long = "show")]
     pub show: bool,
 }
+
+#[derive(Args, Debug)]
+pub struct PhpCompatArgs {
+    /// Target PHP version, e.g. 8.3
+    pub version: String,
+}
+
+#[derive(Args, Debug)]
+pub struct FingerprintArgs {
+    /// Print only the first 12 characters
+    #[arg(long = "short")]
+    pub short: bool,
+
+    /// Leave dev dependencies out, for jobs that install with --no-dev
+    #[arg(long = "no-dev")]
+    pub no_dev: bool,
+
+    /// Output format (text, json)
+    #[arg(long = "format", default_value = "text")]
+    pub format: String,
+}
+
+#[derive(Args, Debug)]
+pub struct BumpArgs {
+    /// Packages to bump (default: all)
+    pub packages: Vec<String>,
+
+    /// Only bump require-dev constraints
+    #[arg(long = "dev-only")]
+    pub dev_only: bool,
+
+    /// Only bump require constraints
+    #[arg(long = "no-dev-only")]
+    pub no_dev_only: bool,
+
+    /// Show the new constraints without writing composer.json
+    #[arg(long = "dry-run")]
+    pub dry_run: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct DockerCacheConfigArgs {
+    /// Print only the cache directory, for scripts that set up cache mounts
+    #[arg(long = "cache-dir-print")]
+    pub cache_dir_print: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ReinstallArgs {
+    /// Packages to reinstall; `*` wildcards are allowed, e.g. symfony/*
+    #[arg(required = true)]
+    pub packages: Vec<String>,
+
+    /// Also delete the cached archives, so they are downloaded again
+    #[arg(long = "no-cache")]
+    pub no_cache: bool,
+
+    /// Clone packages from their git source instead of downloading dist archives
+    #[arg(long = "prefer-source")]
+    pub prefer_source: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct LicensesArgs {
+    /// Leave dev dependencies out
+    #[arg(long = "no-dev")]
+    pub no_dev: bool,
+
+    /// Output format (text, json)
+    #[arg(long = "format", default_value = "text")]
+    pub format: String,
+}
+
+#[derive(Args, Debug)]
+pub struct SbomArgs {
+    /// Output format (cyclonedx, spdx-json)
+    #[arg(long = "format", default_value = "cyclonedx")]
+    pub format: String,
+
+    /// File to write, relative to the working directory (default: stdout)
+    #[arg(long = "output", short = 'o')]
+    pub output: Option<String>,
+
+    /// Leave dev dependencies out
+    #[arg(long = "no-dev")]
+    pub no_dev: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct SyncArgs {
+    /// Show what would change without changing anything
+    #[arg(long = "dry-run")]
+    pub dry_run: bool,
+
+    /// Leave dev dependencies out; installed dev packages count as extraneous
+    #[arg(long = "no-dev")]
+    pub no_dev: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct FundArgs {
+    /// Output format (text, json)
+    #[arg(long = "format", default_value = "text")]
+    pub format: String,
+}
+
+#[derive(Args, Debug)]
+pub struct GlobalArgs {
+    #[command(subcommand)]
+    pub command: GlobalCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum GlobalCommand {
+    /// Add packages to the global project and link their binaries
+    Require(RequireArgs),
+    /// Remove packages from the global project
+    Remove(RemoveArgs),
+    /// Update the global project's packages
+    Update(UpdateArgs),
+    /// Show the global project's packages
+    Show(ShowArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct WorkspaceArgs {
+    #[command(subcommand)]
+    pub command: WorkspaceCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum WorkspaceCommand {
+    /// Install every member from composer.workspace.lock, resolving it if missing or stale
+    Install(WorkspaceInstallArgs),
+    /// Resolve the requirements of every member together, then install them
+    Update(WorkspaceInstallArgs),
+    /// Show whether each member's lock and vendor dir are up to date
+    Status,
+}
+
+#[derive(Args, Debug)]
+pub struct WorkspaceInstallArgs {
+    /// Show what would change without writing locks or installing
+    #[arg(long = "dry-run")]
+    pub dry_run: bool,
+
+    /// Prefer source installs
+    #[arg(long = "prefer-source")]
+    pub prefer_source: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct SelfUpdateArgs {
+    /// Restore the binary that the last update replaced
+    #[arg(long = "rollback")]
+    pub rollback: bool,
+
+    /// Install a release checked only against its checksum when this build has no
+    /// release signing key
+    #[arg(long = "insecure")]
+    pub insecure: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ExecArgs {
+    /// Binary to run, from the bin directory or a package's `bin` entries
+    #[arg(required_unless_present = "list")]
+    pub binary: Option<String>,
+
+    /// Arguments for the binary, after `--`
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    pub args: Vec<String>,
+
+    /// List the available binaries
+    #[arg(short = 'l', long = "list")]
+    pub list: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct GraphArgs {
+    /// Output format (dot, mermaid, json)
+    #[arg(long = "format", default_value = "dot")]
+    pub format: String,
+
+    /// File to write, relative to the working directory (default: stdout)
+    #[arg(long = "output", short = 'o')]
+    pub output: Option<String>,
+
+    /// Leave dev dependencies out
+    #[arg(long = "no-dev")]
+    pub no_dev: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ConvertArgs {
+    /// Format to write (json, toml); defaults to the one the project doesn't use
+    #[arg(long = "to")]
+    pub to: Option<String>,
+
+    /// Keep the original file instead of removing it
+    #[arg(long = "keep")]
+    pub keep: bool,
+
+    /// Overwrite the target file if it already exists
+    #[arg(long = "force")]
+    pub force: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct SuggestsArgs {
+    /// Group suggestions by the package suggesting them (the default)
+    #[arg(long = "by-package")]
+    pub by_package: bool,
+
+    /// Group suggestions by the suggested package
+    #[arg(long = "by-suggestion")]
+    pub by_suggestion: bool,
+
+    /// Include suggestions of dev packages
+    #[arg(long = "all")]
+    pub all: bool,
+
+    /// Only print the suggested package names, one per line
+    #[arg(long = "list")]
+    pub list: bool,
+}
@@ -0,0 +1,482 @@
+use crate::commands::audit::{abandoned_packages, print_abandoned_warnings};
+use crate::commands::check_lock::{LockDrift, find_lock_drift};
+use crate::commands::outdated::{OutdatedPackage, outdated_packages};
+use crate::commands::suggests::print_new_suggestions;
+use crate::commands::{UpdateWith, add_requirement, resolve_required, resolve_requirement};
+use crate::environments::{environment_lock_path, select_environment};
+use crate::ignore_list::IgnoreList;
+use crate::installer::{InstallOptions, InstalledPackage, install_packages_with_options};
+use crate::io::{
+    manifest_path, read_composer_content_hash, read_composer_json, read_composer_json_raw,
+    read_lock, write_composer_json, write_lock, write_manifest_text,
+};
+use crate::journal::{begin_install_operation, commit_operation, discard_entry};
+use crate::lock_diff::{PackageChange, diff_locks, print_lock_diff};
+use crate::models::model::{Lock, LockedPackage};
+use crate::output::{Reporter, with_reporter, with_reporter_sync};
+use crate::resolver::dependency_utils::refresh_root_metadata;
+use crate::resolver::{ResolveOptions, solve_with_options};
+use crate::utils::{print_info, print_warning};
+use anyhow::{Result, anyhow};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Options for the operations of a `Lectern` project
+#[derive(Debug, Clone, Default)]
+pub struct LecternOptions {
+    /// Clone packages from their git source instead of downloading dist archives
+    pub prefer_source: bool,
+    /// Environment from `extra.lectern.environments` to resolve for, with its own lock file
+    pub env: Option<String>,
+    /// Only consider releases published before this date when updating (YYYY-MM-DD)
+    pub until: Option<String>,
+    /// Add required packages to `require-dev`
+    pub dev: bool,
+    /// Leave the `packages-dev` of the lock out of vendor/
+    pub no_dev: bool,
+    /// Change composer.json without resolving or installing
+    pub no_update: bool,
+    /// Resolve, but write and install nothing
+    pub dry_run: bool,
+    /// Write composer.json and composer.lock, but leave vendor/ alone
+    pub no_install: bool,
+    /// Fail instead of warning when the resolved packages include abandoned ones
+    pub no_abandoned: bool,
+    /// Packages (or `*` globs) kept at their locked versions by `update` and left out of
+    /// `outdated`, in addition to `extra.lectern.ignore`
+    pub ignore: Vec<String>,
+    /// Which locked packages besides the required ones `require` may update
+    pub update_with: UpdateWith,
+}
+
+/// The lock an install or update resolved and the packages it put in vendor/
+#[derive(Debug, Clone)]
+pub struct InstallReport {
+    pub lock: Lock,
+    /// How the lock differs from the one it replaces
+    pub changes: Vec<PackageChange>,
+    /// Empty for a dry run or with `no_install`
+    pub installed: Vec<InstalledPackage>,
+}
+
+/// What `require` added, and the resulting install unless `no_update` was set
+#[derive(Debug, Clone)]
+pub struct RequireReport {
+    /// Package names and the constraints they were required with
+    pub requirements: Vec<(String, String)>,
+    pub install: Option<InstallReport>,
+}
+
+/// Diff `lock` against the lock file at `lock_path`, if there is one, and print the changes
+fn lock_changes(lock_path: &Path, lock: &Lock) -> Vec<PackageChange> {
+    let previous = read_lock(lock_path).ok();
+    let changes = diff_locks(previous.as_ref(), lock);
+    print_lock_diff(&changes);
+    changes
+}
+
+/// The packages a change set installs for the first time
+fn installed_names(changes: &[PackageChange]) -> BTreeSet<&str> {
+    changes
+        .iter()
+        .filter(|change| matches!(change, PackageChange::Install { .. }))
+        .map(PackageChange::name)
+        .collect()
+}
+
+/// A Composer project driven from code instead of the command line:
+/// ```no_run
+/// # async fn example() -> anyhow::Result<()> {
+/// use lectern::{Lectern, LecternOptions};
+/// let project = Lectern::new("path/to/project").with_options(LecternOptions {
+///     prefer_source: true,
+///     ..LecternOptions::default()
+/// });
+/// let report = project.install().await?;
+/// println!("{} packages locked", report.lock.packages.len());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Lectern {
+    project_dir: PathBuf,
+    options: LecternOptions,
+    reporter: Option<Arc<dyn Reporter>>,
+}
+
+impl Lectern {
+    pub fn new(project_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            project_dir: project_dir.into(),
+            options: LecternOptions::default(),
+            reporter: None,
+        }
+    }
+
+    pub fn with_options(mut self, options: LecternOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Send status messages to `reporter` instead of the console while an operation runs
+    pub fn with_reporter(mut self, reporter: Arc<dyn Reporter>) -> Self {
+        self.reporter = Some(reporter);
+        self
+    }
+
+    pub fn project_dir(&self) -> &Path {
+        &self.project_dir
+    }
+
+    pub fn options(&self) -> &LecternOptions {
+        &self.options
+    }
+
+    /// Run an operation with its messages sent to the reporter of this project
+    async fn reporting<T>(&self, operation: impl Future<Output = T>) -> T {
+        match self.reporter.clone() {
+            Some(reporter) => with_reporter(reporter, operation).await,
+            None => operation.await,
+        }
+    }
+
+    fn reporting_sync<T>(&self, operation: impl FnOnce() -> T) -> T {
+        match self.reporter.clone() {
+            Some(reporter) => with_reporter_sync(reporter, operation),
+            None => operation(),
+        }
+    }
+
+    fn install_options(&self) -> InstallOptions {
+        InstallOptions {
+            prefer_source: self.options.prefer_source,
+        }
+    }
+
+    /// Warn about the abandoned packages in a resolved lock, or refuse the lock with
+    /// `no_abandoned`
+    fn check_abandoned(&self, lock: &Lock) -> Result<()> {
+        let abandoned = abandoned_packages(lock);
+        print_abandoned_warnings(&abandoned);
+        if self.options.no_abandoned && !abandoned.is_empty() {
+            let names: Vec<&str> = abandoned.iter().map(|(name, _)| name.as_str()).collect();
+            return Err(anyhow!(
+                "Abandoned packages are not allowed: {}; nothing was written",
+                names.join(", ")
+            ));
+        }
+        Ok(())
+    }
+
+    /// Install the packages of a freshly written lock, unless `no_install` leaves that to a
+    /// later `install`
+    async fn install_locked(&self, lock: &Lock) -> Result<Vec<InstalledPackage>> {
+        if self.options.no_install {
+            print_info("Skipped installing; run 'lectern install' to update vendor/");
+            return Ok(Vec::new());
+        }
+        let dev: &[LockedPackage] = if self.options.no_dev {
+            &[]
+        } else {
+            &lock.packages_dev
+        };
+        let pkgs: Vec<LockedPackage> = lock.packages.iter().chain(dev).cloned().collect();
+        install_packages_with_options(&pkgs, &self.project_dir, self.install_options()).await
+    }
+
+    /// Resolve composer.json with `resolve`, then write the lock and install it, recording
+    /// the change in the operation journal so it can be rolled back
+    async fn resolve_and_install(
+        &self,
+        operation: &str,
+        resolve: ResolveOptions,
+    ) -> Result<InstallReport> {
+        let composer_path = manifest_path(&self.project_dir);
+        let env = self.options.env.as_deref();
+        let composer = select_environment(read_composer_json(&composer_path)?, env)?;
+
+        let mut entry = (!self.options.dry_run)
+            .then(|| begin_install_operation(&self.project_dir, operation, self.options.no_dev))
+            .transpose()?;
+        let mut lock = solve_with_options(&composer, &resolve).await?;
+        lock.content_hash = read_composer_content_hash(&composer_path)?;
+        let lock_path = environment_lock_path(&self.project_dir, env);
+        let changes = lock_changes(&lock_path, &lock);
+        self.check_abandoned(&lock)?;
+        let Some(entry) = &mut entry else {
+            return Ok(InstallReport {
+                lock,
+                changes,
+                installed: Vec::new(),
+            });
+        };
+
+        write_lock(&lock_path, &lock)?;
+        let installed = self.install_locked(&lock).await?;
+        commit_operation(&self.project_dir, entry)?;
+        print_new_suggestions(&lock, Some(&composer), &installed_names(&changes));
+        Ok(InstallReport {
+            lock,
+            changes,
+            installed,
+        })
+    }
+
+    /// Resolve composer.json, write the lock file and install every locked package
+    /// # Errors
+    /// Returns an error if composer.json cannot be read, resolution fails or a package
+    /// cannot be installed
+    pub async fn install(&self) -> Result<InstallReport> {
+        self.reporting(async {
+            self.resolve_and_install("install", ResolveOptions::default())
+                .await
+        })
+        .await
+    }
+
+    /// Like `install`, honouring `until` to ignore newer releases and keeping ignored
+    /// packages at the versions in the current lock
+    /// # Errors
+    /// Returns an error if `until` is not a valid date, or for the reasons `install` fails
+    pub async fn update(&self) -> Result<InstallReport> {
+        self.reporting(async {
+            let mut resolve = match self.options.until.as_deref() {
+                Some(date) => {
+                    print_info(&format!(
+                        "📅 Only considering releases published before {date}"
+                    ));
+                    ResolveOptions::until(date)?
+                }
+                None => ResolveOptions::default(),
+            };
+            resolve.locked = self.ignored_locked_versions()?;
+            if !resolve.locked.is_empty() {
+                let held: Vec<String> = resolve
+                    .locked
+                    .iter()
+                    .map(|(name, version)| format!("{name} {version}"))
+                    .collect();
+                print_info(&format!(
+                    "📌 Keeping {} ignored package(s) at their locked versions: {}",
+                    held.len(),
+                    held.join(", ")
+                ));
+            }
+            self.resolve_and_install("update", resolve).await
+        })
+        .await
+    }
+
+    fn ignore_list(&self) -> Result<IgnoreList> {
+        IgnoreList::for_project(&self.project_dir, &self.options.ignore)
+    }
+
+    /// The locked versions of the packages on the ignore list, by lowercase name
+    fn ignored_locked_versions(&self) -> Result<BTreeMap<String, String>> {
+        let ignore = self.ignore_list()?;
+        let lock_path = environment_lock_path(&self.project_dir, self.options.env.as_deref());
+        if ignore.is_empty() || !lock_path.exists() {
+            return Ok(BTreeMap::new());
+        }
+        let lock = read_lock(&lock_path)?;
+        Ok(lock
+            .packages
+            .iter()
+            .chain(&lock.packages_dev)
+            .filter(|package| ignore.matches(&package.name))
+            .map(|package| (package.name.to_lowercase(), package.version.clone()))
+            .collect())
+    }
+
+    /// Refresh composer.lock's `content-hash` and the root settings it records (stability,
+    /// platform requirements) from composer.json, keeping every locked version; nothing is
+    /// resolved or installed. Like `update --lock` in Composer, for when composer.json
+    /// changed in a way that doesn't affect the locked packages.
+    /// # Errors
+    /// Returns an error if there is no lock file, or the locked packages no longer satisfy
+    /// the root requirements
+    pub fn update_lock(&self) -> Result<Lock> {
+        self.reporting_sync(|| {
+            let composer_path = manifest_path(&self.project_dir);
+            let env = self.options.env.as_deref();
+            let composer = select_environment(read_composer_json(&composer_path)?, env)?;
+            let lock_path = environment_lock_path(&self.project_dir, env);
+            if !lock_path.exists() {
+                return Err(anyhow!(
+                    "No composer.lock found. Run 'lectern update' first."
+                ));
+            }
+            let mut lock = read_lock(&lock_path)?;
+
+            let stale: Vec<String> = find_lock_drift(&composer, &lock)
+                .into_iter()
+                .filter_map(|drift| match drift {
+                    LockDrift::Added { name, constraint } => {
+                        Some(format!("{name} ({constraint}) is not locked"))
+                    }
+                    LockDrift::Changed {
+                        name,
+                        constraint,
+                        locked,
+                    } => Some(format!("{name} ({constraint}) is locked at {locked}")),
+                    LockDrift::Removed { .. } => None,
+                })
+                .collect();
+            if !stale.is_empty() {
+                return Err(anyhow!(
+                    "composer.lock doesn't satisfy composer.json: {}. \
+                     Run 'lectern update' instead.",
+                    stale.join(", ")
+                ));
+            }
+
+            refresh_root_metadata(&composer, &mut lock);
+            lock.content_hash = read_composer_content_hash(&composer_path)?;
+            if self.options.dry_run {
+                return Ok(lock);
+            }
+            let mut entry =
+                begin_install_operation(&self.project_dir, "update", self.options.no_dev)?;
+            write_lock(&lock_path, &lock)?;
+            commit_operation(&self.project_dir, &mut entry)?;
+            Ok(lock)
+        })
+    }
+
+    /// Add packages (`vendor/name` or `vendor/name:constraint`) to composer.json and install
+    /// them. Nothing is written unless the new requirements resolve; if installing fails,
+    /// composer.json and composer.lock are restored.
+    /// # Errors
+    /// Returns an error if a package doesn't exist, the requirements cannot be resolved or
+    /// the result cannot be written or installed
+    pub async fn require(&self, packages: &[String]) -> Result<RequireReport> {
+        self.reporting(async {
+            let composer_path = manifest_path(&self.project_dir);
+            let mut composer = read_composer_json_raw(&composer_path)?;
+            let dev = self.options.dev;
+
+            // Add packages to composer.json, checked against Packagist first
+            let custom_repositories = composer
+                .repositories
+                .as_ref()
+                .is_some_and(|repos| !repos.is_empty());
+            let mut requirements = Vec::new();
+            for package_spec in packages {
+                let (name, constraint) =
+                    resolve_requirement(package_spec, custom_repositories).await?;
+                print_info(&format!("Using version {constraint} for {name}"));
+
+                if add_requirement(&mut composer, &name, &constraint, dev).is_some() {
+                    let (from, to) = if dev {
+                        ("require", "require-dev")
+                    } else {
+                        ("require-dev", "require")
+                    };
+                    print_warning(&format!("Moving {name} from {from} to {to}"));
+                }
+                requirements.push((name, constraint));
+            }
+
+            // Resolve before writing anything, so a conflict leaves the project untouched.
+            // Locked packages keep their versions unless `update_with` lets them move.
+            let lock_path = self.project_dir.join("composer.lock");
+            let resolved = if self.options.no_update {
+                None
+            } else {
+                let current = read_lock(&lock_path).ok();
+                let names: Vec<String> =
+                    requirements.iter().map(|(name, _)| name.clone()).collect();
+                let update_with = self.options.update_with;
+                let resolved = resolve_required(&composer, current.as_ref(), &names, update_with);
+                Some(resolved.await.map_err(|e| {
+                    let hint = current
+                        .as_ref()
+                        .and(update_with.hint())
+                        .map(|hint| format!(". {hint}"))
+                        .unwrap_or_default();
+                    e.context(format!(
+                        "Could not add {}; composer.json and composer.lock were left \
+                         unchanged{hint}",
+                        packages.join(", ")
+                    ))
+                })?)
+            };
+
+            if let Some((_, lock)) = &resolved {
+                self.check_abandoned(lock)?;
+            }
+            if self.options.dry_run {
+                return Ok(RequireReport {
+                    requirements,
+                    install: resolved.map(|(_, lock)| InstallReport {
+                        changes: lock_changes(&lock_path, &lock),
+                        lock,
+                        installed: Vec::new(),
+                    }),
+                });
+            }
+
+            let dir = &self.project_dir;
+            let mut entry = begin_install_operation(dir, "require", self.options.no_dev)?;
+            let persisted = async {
+                match resolved {
+                    Some((composer_json, lock)) => {
+                        let changes = lock_changes(&lock_path, &lock);
+                        write_manifest_text(&composer_path, &composer_json)?;
+                        write_lock(&lock_path, &lock)?;
+                        let installed = self.install_locked(&lock).await?;
+                        Ok(Some(InstallReport {
+                            lock,
+                            changes,
+                            installed,
+                        }))
+                    }
+                    None => write_composer_json(&composer_path, &composer).map(|()| None),
+                }
+            }
+            .await;
+            let install = match persisted {
+                Ok(install) => install,
+                Err(e) => {
+                    entry.before.restore(dir)?;
+                    discard_entry(dir, &entry)?;
+                    print_info("Restored the previous composer.json and composer.lock");
+                    return Err(e);
+                }
+            };
+            commit_operation(dir, &mut entry)?;
+            if let Some(install) = &install {
+                print_new_suggestions(
+                    &install.lock,
+                    Some(&composer),
+                    &installed_names(&install.changes),
+                );
+            }
+            Ok(RequireReport {
+                requirements,
+                install,
+            })
+        })
+        .await
+    }
+
+    /// Locked packages with a newer stable release on Packagist, leaving out ignored ones
+    /// # Errors
+    /// Returns an error if there is no lock file or package metadata cannot be fetched
+    pub async fn outdated(&self) -> Result<Vec<OutdatedPackage>> {
+        self.reporting(async {
+            let lock_path = environment_lock_path(&self.project_dir, self.options.env.as_deref());
+            if !lock_path.exists() {
+                return Err(anyhow!(
+                    "No composer.lock found. Run 'lectern install' first."
+                ));
+            }
+            let ignore = self.ignore_list()?;
+            let mut outdated = outdated_packages(&read_lock(&lock_path)?).await?;
+            outdated.retain(|package| !ignore.matches(&package.name));
+            Ok(outdated)
+        })
+        .await
+    }
+}
@@ -0,0 +1,198 @@
+use crate::core::placeholders::expand_auth;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Environment variable holding auth.json contents, as in Composer
+pub const COMPOSER_AUTH_ENV: &str = "COMPOSER_AUTH";
+
+/// Username and password (or token) for an HTTPS remote
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpCredentials {
+    pub username: String,
+    pub password: String,
+}
+
+/// Credentials for private repositories, keyed by host like Composer's auth.json
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuthConfig {
+    pub github_oauth: BTreeMap<String, String>,
+    pub gitlab_oauth: BTreeMap<String, String>,
+    /// GitLab personal/deploy tokens, optionally with the username they belong to
+    pub gitlab_token: BTreeMap<String, (Option<String>, String)>,
+    /// Access tokens sent as `x-token-auth`, the Bitbucket convention
+    pub bearer: BTreeMap<String, String>,
+    pub http_basic: BTreeMap<String, HttpCredentials>,
+}
+
+/// Project whose composer.json and auth.json are consulted (set once from the CLI)
+static PROJECT_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Remember the project directory for project-level auth settings
+pub fn set_project_dir(dir: &Path) {
+    let _ = PROJECT_DIR.set(dir.to_path_buf());
+}
+
+/// The project directory set from the CLI, if any
+pub fn project_dir() -> Option<&'static Path> {
+    PROJECT_DIR.get().map(PathBuf::as_path)
+}
+
+/// Host of an `http(s)://` URL, without credentials or port
+pub fn url_host(url: &str) -> Option<String> {
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))?;
+    let authority = rest.split('/').next()?;
+    let host = authority.rsplit('@').next()?;
+    let host = host.split(':').next()?;
+    (!host.is_empty()).then(|| host.to_ascii_lowercase())
+}
+
+fn string_map(value: Option<&Value>) -> impl Iterator<Item = (String, String)> + '_ {
+    value
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .filter_map(|(host, v)| {
+            v.as_str()
+                .map(|s| (host.to_ascii_lowercase(), s.to_string()))
+        })
+}
+
+impl AuthConfig {
+    /// Merge an auth.json-shaped object; entries already present are replaced
+    pub fn merge_json(&mut self, auth: &Value) {
+        self.github_oauth
+            .extend(string_map(auth.get("github-oauth")));
+        self.gitlab_oauth
+            .extend(string_map(auth.get("gitlab-oauth")));
+        self.bearer.extend(string_map(auth.get("bearer")));
+
+        if let Some(tokens) = auth.get("gitlab-token").and_then(Value::as_object) {
+            for (host, value) in tokens {
+                let entry = match value {
+                    Value::String(token) => (None, token.clone()),
+                    Value::Object(obj) => match obj.get("token").and_then(Value::as_str) {
+                        Some(token) => (
+                            obj.get("username")
+                                .and_then(Value::as_str)
+                                .map(str::to_string),
+                            token.to_string(),
+                        ),
+                        None => continue,
+                    },
+                    _ => continue,
+                };
+                self.gitlab_token.insert(host.to_ascii_lowercase(), entry);
+            }
+        }
+
+        if let Some(basic) = auth.get("http-basic").and_then(Value::as_object) {
+            for (host, value) in basic {
+                if let (Some(username), Some(password)) = (
+                    value.get("username").and_then(Value::as_str),
+                    value.get("password").and_then(Value::as_str),
+                ) {
+                    self.http_basic.insert(
+                        host.to_ascii_lowercase(),
+                        HttpCredentials {
+                            username: username.to_string(),
+                            password: password.to_string(),
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    /// Merge credentials from a file, expanding `${VAR}` placeholders. A file that
+    /// references unset variables is skipped with a warning.
+    fn merge_file(&mut self, path: &Path, pointer: &str) {
+        if let Ok(content) = crate::io::read_manifest_text(path)
+            && let Ok(json) = serde_json::from_str::<Value>(&content)
+            && let Some(auth) = json.pointer(pointer)
+        {
+            let mut auth = auth.clone();
+            match expand_auth(&mut auth, &path.display().to_string()) {
+                Ok(()) => self.merge_json(&auth),
+                Err(e) => crate::core::utils::print_warning(&format!("Ignoring credentials: {e}")),
+            }
+        }
+    }
+
+    /// Collect credentials from the global auth.json, the project's composer.json `config`
+    /// and auth.json, then `COMPOSER_AUTH`, with later sources taking precedence
+    pub fn from_env_with<F>(get_env: F, home: Option<&Path>, project_dir: Option<&Path>) -> Self
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let mut auth = Self::default();
+
+        let global_dirs: Vec<PathBuf> = match get_env("COMPOSER_HOME") {
+            Some(dir) => vec![PathBuf::from(dir)],
+            None => home
+                .map(|home| vec![home.join(".config/composer"), home.join(".composer")])
+                .unwrap_or_default(),
+        };
+        if let Some(dir) = global_dirs.iter().find(|d| d.join("auth.json").is_file()) {
+            auth.merge_file(&dir.join("auth.json"), "");
+        }
+
+        if let Some(project) = project_dir {
+            auth.merge_file(&crate::io::manifest_path(project), "/config");
+            auth.merge_file(&project.join("auth.json"), "");
+        }
+
+        if let Some(value) = get_env(COMPOSER_AUTH_ENV)
+            && let Ok(json) = serde_json::from_str::<Value>(&value)
+        {
+            auth.merge_json(&json);
+        }
+
+        auth
+    }
+
+    /// Credentials from the process environment and the current project
+    pub fn from_env() -> Self {
+        Self::from_env_with(
+            |name| std::env::var(name).ok(),
+            dirs::home_dir().as_deref(),
+            PROJECT_DIR.get().map(PathBuf::as_path),
+        )
+    }
+
+    /// Credentials to send to an HTTPS git remote (GitHub, GitLab, Bitbucket or any
+    /// host configured with http-basic); never for plain `http://`, where they'd go in clear
+    pub fn credentials_for(&self, url: &str) -> Option<HttpCredentials> {
+        if !url
+            .get(..8)
+            .is_some_and(|scheme| scheme.eq_ignore_ascii_case("https://"))
+        {
+            return None;
+        }
+        let host = url_host(url)?;
+        let creds = |username: &str, password: &str| HttpCredentials {
+            username: username.to_string(),
+            password: password.to_string(),
+        };
+
+        if let Some(basic) = self.http_basic.get(&host) {
+            return Some(basic.clone());
+        }
+        if let Some(token) = self.github_oauth.get(&host) {
+            return Some(creds(token, "x-oauth-basic"));
+        }
+        if let Some(token) = self.gitlab_oauth.get(&host) {
+            return Some(creds("oauth2", token));
+        }
+        if let Some((username, token)) = self.gitlab_token.get(&host) {
+            return Some(creds(username.as_deref().unwrap_or("oauth2"), token));
+        }
+        if let Some(token) = self.bearer.get(&host) {
+            return Some(creds("x-token-auth", token));
+        }
+        None
+    }
+}
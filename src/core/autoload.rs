@@ -1,158 +1,581 @@
 use crate::installer::InstalledPackage;
-use crate::models::model::ComposerJson;
+use crate::io::write_file_atomic;
+use crate::models::model::{Autoload, ComposerJson, Lock, LockedPackage};
+use crate::php_scanner::find_classes_in_file;
+use crate::profiler::{Phase, record_phase};
+use crate::resolver::version::normalize_alias;
+use crate::utils::{print_info, print_warning};
 use anyhow::Result;
+use sha2::{Digest, Sha256};
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 use walkdir::WalkDir;
 
-/// Generate vendor/autoload.php, `autoload_psr4.php`, `autoload_classmap.php`
+/// How the autoloader is generated
+#[derive(Debug, Clone, Default)]
+pub struct AutoloadOptions {
+    /// Cache class lookups in `APCu` when the extension is enabled
+    pub apcu: bool,
+    /// Leave out the root's `autoload-dev`
+    pub no_dev: bool,
+    /// Installed packages only dev requirements need, recorded in `installed.php`
+    pub dev_packages: BTreeSet<String>,
+    /// Regenerate even when nothing the files depend on changed since the last run
+    pub force: bool,
+    /// The lock's `content-hash` and package references, so that a branch package moving
+    /// to a new commit regenerates the files even though its version stays the same
+    pub lock_state: String,
+}
+
+impl AutoloadOptions {
+    /// Options for the packages of `lock`, whose `packages-dev` are dev requirements
+    pub fn for_lock(lock: &Lock, no_dev: bool) -> Self {
+        Self {
+            no_dev,
+            dev_packages: lock.packages_dev.iter().map(|p| p.name.clone()).collect(),
+            lock_state: lock_state(lock),
+            ..Self::default()
+        }
+    }
+}
+
+fn lock_state(lock: &Lock) -> String {
+    let mut state = lock.content_hash.clone();
+    for pkg in lock.packages.iter().chain(&lock.packages_dev) {
+        let source = pkg.source.as_ref().map_or("", |s| s.reference.as_str());
+        let dist = pkg.dist.as_ref().map_or("", |d| d.reference.as_str());
+        let _ = write!(state, "\n{} {source} {dist}", pkg.name);
+    }
+    state
+}
+
+/// The packages of `lock` as installed in vendor, without `packages-dev` when `no_dev`
+pub fn lock_packages(lock: &Lock, no_dev: bool) -> Vec<InstalledPackage> {
+    let dev: &[LockedPackage] = if no_dev { &[] } else { &lock.packages_dev };
+    lock.packages
+        .iter()
+        .chain(dev)
+        .map(|pkg| InstalledPackage {
+            name: pkg.name.clone(),
+            version: pkg.version.clone(),
+            path: format!("vendor/{}", pkg.name).into(),
+        })
+        .collect()
+}
+
+/// Generate vendor/autoload.php, `autoload_psr4.php`, `autoload_classmap.php` and
+/// `autoload_static.php`
 /// # Errors
 /// Returns an error if the autoload files cannot be written
+pub async fn write_autoload_files(
+    project_dir: &Path,
+    composer: &ComposerJson,
+    installed: &[InstalledPackage],
+) -> Result<()> {
+    write_autoload_files_with_options(
+        project_dir,
+        composer,
+        installed,
+        &AutoloadOptions::default(),
+    )
+    .await
+}
+
+/// [`write_autoload_files`] with the `dump-autoload` options
+/// # Errors
+/// Returns an error if the autoload files cannot be written
+pub async fn write_autoload_files_with_options(
+    project_dir: &Path,
+    composer: &ComposerJson,
+    installed: &[InstalledPackage],
+    options: &AutoloadOptions,
+) -> Result<()> {
+    let started = Instant::now();
+    let written = dump_autoload(project_dir, composer, installed, options).await;
+    record_phase(Phase::AutoloadDump, started.elapsed());
+    written
+}
+
 #[allow(clippy::too_many_lines)]
 #[allow(clippy::cognitive_complexity)]
-pub async fn write_autoload_files(
+async fn dump_autoload(
     project_dir: &Path,
     composer: &ComposerJson,
-    installed: &Vec<InstalledPackage>,
+    installed: &[InstalledPackage],
+    options: &AutoloadOptions,
 ) -> Result<()> {
     let vendor = project_dir.join("vendor");
     let composer_dir = vendor.join("composer");
     tokio::fs::create_dir_all(&composer_dir).await?;
 
-    // generate autoload_psr4 from top-level composer.json + vendor packages' composer.json
-    let mut psr4_map: Vec<(String, String)> = Vec::new();
+    let stamp = autoload_stamp(composer, installed, options)?;
+    let stamp_path = composer_dir.join("autoload.stamp");
+    if !options.force
+        && vendor.join("autoload.php").exists()
+        && fs::read_to_string(&stamp_path).is_ok_and(|old| old == stamp)
+    {
+        print_info("✅ Autoload files are up to date");
+        return Ok(());
+    }
 
-    if let Some(a) = &composer.autoload {
+    // The root's autoload-dev only counts in dev mode; dev packages are left out of
+    // `installed` by the caller
+    let root_autoloads: Vec<&Autoload> = composer
+        .autoload
+        .iter()
+        .chain(composer.autoload_dev.iter().filter(|_| !options.no_dev))
+        .collect();
+    let manifests: Vec<Option<serde_json::Value>> = installed
+        .iter()
+        .map(|pkg| {
+            fs::read_to_string(pkg.path.as_std_path().join("composer.json"))
+                .ok()
+                .and_then(|s| serde_json::from_str(&s).ok())
+        })
+        .collect();
+
+    // generate autoload_psr4 from top-level composer.json + vendor packages' composer.json
+    let mut psr4_map: Vec<(String, PathBuf)> = Vec::new();
+    for a in &root_autoloads {
         for (k, v) in &a.psr4 {
-            psr4_map.push((k.clone(), v.clone()));
+            for dir in v.paths() {
+                psr4_map.push((k.clone(), project_dir.join(dir)));
+            }
         }
     }
-
     // scan installed packages for autoload psr-4 entries
-    for pkg in installed {
+    for (pkg, manifest) in installed.iter().zip(&manifests) {
         let pkg_path = pkg.path.as_std_path();
-        let cj = pkg_path.join("composer.json");
-        if cj.exists() {
-            if let Ok(s) = fs::read_to_string(&cj) {
-                if let Ok(v) = serde_json::from_str::<serde_json::Value>(&s) {
-                    if let Some(a) = v.get("autoload").and_then(|x| x.get("psr-4")) {
-                        if let Some(map) = a.as_object() {
-                            for (k, val) in map {
-                                if let Some(dir) = val.as_str() {
-                                    let base = pkg_path.join(dir);
-                                    psr4_map.push((k.clone(), base.to_string_lossy().into_owned()));
-                                }
-                            }
-                        }
+        if let Some(map) = manifest
+            .as_ref()
+            .and_then(|v| v.pointer("/autoload/psr-4"))
+            .and_then(|a| a.as_object())
+        {
+            for (k, val) in map {
+                let dirs: Vec<&str> = match val {
+                    serde_json::Value::Array(dirs) => {
+                        dirs.iter().filter_map(|dir| dir.as_str()).collect()
                     }
+                    dir => dir.as_str().into_iter().collect(),
+                };
+                for dir in dirs {
+                    psr4_map.push((k.clone(), pkg_path.join(dir)));
                 }
             }
         }
     }
 
-    // write autoload_psr4.php
-    let mut s = String::from("<?php\nreturn [\n");
-    for (ns, dir) in psr4_map {
-        use std::fmt::Write;
-        writeln!(
-            &mut s,
-            "  '{}' => '{}',",
-            ns.replace('\'', "\\'"),
-            dir.replace('\'', "\\'")
-        )
-        .unwrap();
-    }
-    s.push_str("];\n");
-    tokio::fs::write(composer_dir.join("autoload_psr4.php"), s).await?;
-
-    // classmap: top-level + vendor classmap directive
-    let mut classmap_entries: Vec<String> = Vec::new();
-    if let Some(a) = &composer.autoload {
+    // classmap: top-level + vendor classmap directive, minus what any package excludes
+    let mut classmap_roots: Vec<PathBuf> = Vec::new();
+    let mut excluded: Vec<(PathBuf, String)> = Vec::new();
+    let target_dir = composer.target_dir.as_deref();
+    for a in &root_autoloads {
         for entry in &a.classmap {
-            let p = project_dir.join(entry);
-            if p.exists() {
-                for e in WalkDir::new(&p)
-                    .into_iter()
-                    .filter_map(std::result::Result::ok)
-                {
-                    if e.file_type().is_file() && e.path().extension().is_some_and(|e| e == "php") {
-                        classmap_entries.push(e.path().to_string_lossy().to_string());
-                    }
-                }
-            }
+            classmap_roots.push(autoload_path(project_dir, entry, target_dir, true));
+        }
+        for pattern in &a.exclude_from_classmap {
+            excluded.push(exclusion(project_dir, pattern, target_dir, true));
         }
     }
     // vendor packages classmap
-    for pkg in installed {
+    for (pkg, manifest) in installed.iter().zip(&manifests) {
         let pkg_path = pkg.path.as_std_path();
-        let cj = pkg_path.join("composer.json");
-        if cj.exists() {
-            if let Ok(s) = fs::read_to_string(&cj) {
-                if let Ok(v) = serde_json::from_str::<serde_json::Value>(&s) {
-                    if let Some(cm) = v.get("autoload").and_then(|x| x.get("classmap")) {
-                        if let Some(arr) = cm.as_array() {
-                            for it in arr {
-                                if let Some(dir) = it.as_str() {
-                                    let root = pkg_path.join(dir);
-                                    if root.exists() {
-                                        for e in WalkDir::new(&root)
-                                            .into_iter()
-                                            .filter_map(std::result::Result::ok)
-                                        {
-                                            if e.file_type().is_file()
-                                                && e.path().extension().is_some_and(|e| e == "php")
-                                            {
-                                                classmap_entries
-                                                    .push(e.path().to_string_lossy().to_string());
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
+        if let Some(v) = manifest
+            && let Some(autoload) = v.get("autoload")
+        {
+            let target_dir = v.get("target-dir").and_then(|t| t.as_str());
+            let paths = |key: &str| {
+                autoload
+                    .get(key)
+                    .and_then(|x| x.as_array())
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|it| it.as_str())
+            };
+            for dir in paths("classmap") {
+                classmap_roots.push(autoload_path(pkg_path, dir, target_dir, false));
+            }
+            for pattern in paths("exclude-from-classmap") {
+                excluded.push(exclusion(pkg_path, pattern, target_dir, false));
+            }
+        }
+    }
+    // Classes sorted by name, each in the first file that declares it
+    let mut classmap_entries: BTreeMap<String, PathBuf> = BTreeMap::new();
+    for root in classmap_roots.iter().filter(|root| root.exists()) {
+        for e in WalkDir::new(root)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_map(std::result::Result::ok)
+        {
+            if !e.file_type().is_file()
+                || e.path().extension().is_none_or(|e| e != "php")
+                || is_excluded(&excluded, e.path())
+            {
+                continue;
+            }
+            for class in find_classes_in_file(e.path())? {
+                match classmap_entries.entry(class) {
+                    Entry::Vacant(entry) => {
+                        entry.insert(e.path().to_path_buf());
                     }
+                    Entry::Occupied(entry) if entry.get() != e.path() => print_warning(&format!(
+                        "⚠️  Ambiguous class resolution, \"{}\" was found in both \"{}\" and \"{}\", the first will be used.",
+                        entry.key(),
+                        entry.get().display(),
+                        e.path().display()
+                    )),
+                    Entry::Occupied(_) => {}
                 }
             }
         }
     }
 
-    // write classmap
-    let mut cm = String::from("<?php\nreturn [\n");
-    for p in classmap_entries {
-        use std::fmt::Write;
-        writeln!(
-            &mut cm,
-            "  '{}' => '{}',",
-            p.replace('\'', "\\'"),
-            p.replace('\'', "\\'")
-        )
-        .unwrap();
+    // Longer namespaces are tried first, as Composer orders them
+    let mut psr4: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (prefix, dir) in &psr4_map {
+        psr4.entry(prefix.clone())
+            .or_default()
+            .push(php_path(project_dir, dir));
+    }
+    let classmap: Vec<(String, String)> = classmap_entries
+        .iter()
+        .map(|(class, path)| (php_string(class), php_path(project_dir, path)))
+        .collect();
+
+    let mut s = String::from(GENERATED_HEADER);
+    s.push_str("return [\n");
+    for (prefix, dirs) in psr4.iter().rev() {
+        writeln!(s, "    {} => [{}],", php_string(prefix), dirs.join(", "))?;
+    }
+    s.push_str("];\n");
+    write_file_atomic(&composer_dir.join("autoload_psr4.php"), &s)?;
+
+    let mut cm = String::from(GENERATED_HEADER);
+    cm.push_str("return [\n");
+    for (class, path) in &classmap {
+        writeln!(cm, "    {class} => {path},")?;
     }
     cm.push_str("];\n");
-    tokio::fs::write(composer_dir.join("autoload_classmap.php"), cm).await?;
+    write_file_atomic(&composer_dir.join("autoload_classmap.php"), &cm)?;
 
-    // autoload.php shim
-    let autoload_php = r#"<?php
+    // Everything the loader needs in one file of constant arrays, which opcache keeps in
+    // shared memory instead of rebuilding them on every request
+    let mut st = String::from(GENERATED_HEADER);
+    st.push_str("return [\n    'prefixLengthsPsr4' => [\n");
+    let mut by_first: BTreeMap<u8, Vec<&String>> = BTreeMap::new();
+    for prefix in psr4.keys().rev().filter(|p| !p.is_empty()) {
+        by_first
+            .entry(prefix.as_bytes()[0])
+            .or_default()
+            .push(prefix);
+    }
+    for (first, prefixes) in by_first.iter().rev() {
+        writeln!(st, "        {} => [", php_byte(*first))?;
+        for prefix in prefixes {
+            writeln!(
+                st,
+                "            {} => {},",
+                php_string(prefix),
+                prefix.len()
+            )?;
+        }
+        st.push_str("        ],\n");
+    }
+    st.push_str("    ],\n    'prefixDirsPsr4' => [\n");
+    for (prefix, dirs) in psr4.iter().rev().filter(|(p, _)| !p.is_empty()) {
+        writeln!(
+            st,
+            "        {} => [{}],",
+            php_string(prefix),
+            dirs.join(", ")
+        )?;
+    }
+    let fallback = psr4.get("").map(|dirs| dirs.join(", ")).unwrap_or_default();
+    writeln!(st, "    ],\n    'fallbackDirsPsr4' => [{fallback}],")?;
+    st.push_str("    'classMap' => [\n");
+    for (class, path) in &classmap {
+        writeln!(st, "        {class} => {path},")?;
+    }
+    st.push_str("    ],\n];\n");
+    write_file_atomic(&composer_dir.join("autoload_static.php"), &st)?;
+
+    // What was installed and whether dev mode was dumped, as Composer records it for
+    // `Composer\InstalledVersions`
+    let mut ip = String::from(GENERATED_HEADER);
+    let root_type = composer.package_type.as_deref().unwrap_or("library");
+    let root_version = composer
+        .version
+        .as_deref()
+        .unwrap_or("1.0.0+no-version-set");
+    writeln!(
+        ip,
+        "return [\n    'root' => [\n        'name' => {},\n        'pretty_version' => {},\n        'version' => {},\n        'type' => {},\n        'install_path' => __DIR__ . '/../../',\n        'aliases' => [],\n        'dev' => {},\n    ],\n    'versions' => [",
+        php_string(composer.name.as_deref().unwrap_or("__root__")),
+        php_string(root_version),
+        php_string(&normalized_version(root_version)),
+        php_string(root_type),
+        !options.no_dev,
+    )?;
+    for (pkg, manifest) in installed.iter().zip(&manifests) {
+        let package_type = manifest
+            .as_ref()
+            .and_then(|v| v.get("type"))
+            .and_then(|t| t.as_str())
+            .unwrap_or("library");
+        writeln!(
+            ip,
+            "        {} => [\n            'pretty_version' => {},\n            'version' => {},\n            'type' => {},\n            'install_path' => {},\n            'aliases' => [],\n            'dev_requirement' => {},\n        ],",
+            php_string(&pkg.name),
+            php_string(&pkg.version),
+            php_string(&normalized_version(&pkg.version)),
+            php_string(package_type),
+            php_path(project_dir, pkg.path.as_std_path()),
+            options.dev_packages.contains(&pkg.name),
+        )?;
+    }
+    ip.push_str("    ],\n];\n");
+    write_file_atomic(&composer_dir.join("installed.php"), &ip)?;
+
+    // A prefix unique to these files, so cached lookups never outlive them
+    let apcu_prefix = if options.apcu {
+        let digest = Sha256::digest(format!("{project_dir:?}{psr4:?}{classmap:?}"));
+        php_string(&format!("lectern-{}", &hex::encode(digest)[..16]))
+    } else {
+        "null".to_string()
+    };
+    write_file_atomic(
+        &vendor.join("autoload.php"),
+        &AUTOLOAD_PHP.replace("{apcu_prefix}", &apcu_prefix),
+    )?;
+    // Written last, so an interrupted run is redone next time
+    write_file_atomic(&stamp_path, &stamp)?;
+    Ok(())
+}
+
+/// A hash of everything the generated files depend on besides the packages' own files:
+/// the root's autoload configuration, the installed packages (which follow composer.lock)
+/// and the options
+fn autoload_stamp(
+    composer: &ComposerJson,
+    installed: &[InstalledPackage],
+    options: &AutoloadOptions,
+) -> Result<String> {
+    let mut hasher = Sha256::new();
+    hasher.update(env!("CARGO_PKG_VERSION"));
+    hasher.update(serde_json::to_string(&(
+        &composer.name,
+        &composer.version,
+        &composer.package_type,
+        &composer.autoload,
+        &composer.autoload_dev,
+        &composer.target_dir,
+    ))?);
+    for pkg in installed {
+        hasher.update(format!("\n{} {} {}", pkg.name, pkg.version, pkg.path));
+    }
+    hasher.update(format!("\n{options:?}"));
+    Ok(hex::encode(hasher.finalize()))
+}
+
+const GENERATED_HEADER: &str = "<?php\n// Generated by Lectern\n\n";
+
+/// vendor/autoload.php: loads `autoload_static.php`, or else builds the same arrays from
+/// `autoload_psr4.php` and `autoload_classmap.php`, and registers a loader that looks classes
+/// up in the classmap and then the PSR-4 directories. With an `APCu` prefix, every lookup's
+/// result, including a miss, is cached under it.
+const AUTOLOAD_PHP: &str = r#"<?php
 // Generated by Lectern
-$loader = require __DIR__ . '/autoload_psr4.php';
-spl_autoload_register(function($class) use ($loader) {
-    foreach ($loader as $prefix => $baseDir) {
-        $len = strlen($prefix);
-        if (strncmp($prefix, $class, $len) !== 0) continue;
-        $relative = str_replace('\\', '/', substr($class, $len)) . '.php';
-        $file = rtrim($baseDir, '/').'/'.$relative;
-        if (file_exists($file)) { require $file; return true; }
-    }
-    $classmap = require __DIR__ . '/autoload_classmap.php';
-    if (isset($classmap[$class]) && file_exists($classmap[$class])) { require $classmap[$class]; return true; }
-    return false;
+
+$lecternAutoload = is_file(__DIR__ . '/composer/autoload_static.php')
+    ? require __DIR__ . '/composer/autoload_static.php'
+    : (static function () {
+        $prefixDirsPsr4 = require __DIR__ . '/composer/autoload_psr4.php';
+        $fallbackDirsPsr4 = $prefixDirsPsr4[''] ?? [];
+        unset($prefixDirsPsr4['']);
+        $prefixLengthsPsr4 = [];
+        foreach ($prefixDirsPsr4 as $prefix => $dirs) {
+            $prefixLengthsPsr4[$prefix[0]][$prefix] = strlen($prefix);
+        }
+        return [
+            'prefixLengthsPsr4' => $prefixLengthsPsr4,
+            'prefixDirsPsr4' => $prefixDirsPsr4,
+            'fallbackDirsPsr4' => $fallbackDirsPsr4,
+            'classMap' => require __DIR__ . '/composer/autoload_classmap.php',
+        ];
+    })();
+
+$apcuPrefix = {apcu_prefix};
+if ($apcuPrefix !== null && !(function_exists('apcu_fetch') && filter_var(ini_get('apc.enabled'), FILTER_VALIDATE_BOOLEAN))) {
+    $apcuPrefix = null;
+}
+
+spl_autoload_register(static function ($class) use ($lecternAutoload, $apcuPrefix) {
+    if ($apcuPrefix !== null) {
+        $file = apcu_fetch($apcuPrefix . $class, $hit);
+        if ($hit) {
+            if ($file !== false) {
+                (static function ($file) { require $file; })($file);
+            }
+            return;
+        }
+    }
+
+    $file = $lecternAutoload['classMap'][$class] ?? false;
+    if ($file === false) {
+        $logicalPath = strtr($class, '\\', '/') . '.php';
+        foreach ($lecternAutoload['prefixLengthsPsr4'][$class[0]] ?? [] as $prefix => $length) {
+            if (strncmp($prefix, $class, $length) !== 0) {
+                continue;
+            }
+            foreach ($lecternAutoload['prefixDirsPsr4'][$prefix] as $dir) {
+                if (is_file($candidate = $dir . '/' . substr($logicalPath, $length))) {
+                    $file = $candidate;
+                    break 2;
+                }
+            }
+        }
+    }
+    if ($file === false) {
+        foreach ($lecternAutoload['fallbackDirsPsr4'] as $dir) {
+            if (is_file($candidate = $dir . '/' . $logicalPath)) {
+                $file = $candidate;
+                break;
+            }
+        }
+    }
+
+    if ($apcuPrefix !== null) {
+        apcu_add($apcuPrefix . $class, $file);
+    }
+    if ($file !== false) {
+        (static function ($file) { require $file; })($file);
+    }
 });
-return $loader;
+
+return $lecternAutoload;
 "#;
-    tokio::fs::write(
-        project_dir.join("vendor").join("autoload.php"),
-        autoload_php,
+
+/// The version as Composer normalizes it: `v1.2` is `1.2.0.0`, `1.0.0-RC1` is `1.0.0.0-RC1`,
+/// and branches keep their name
+fn normalized_version(version: &str) -> String {
+    let version = version.trim();
+    if version.starts_with("dev-") {
+        return version.to_string();
+    }
+    match version.split_once('-') {
+        Some((numbers, suffix)) if suffix != "dev" => {
+            format!("{}-{suffix}", normalize_alias(numbers))
+        }
+        _ => normalize_alias(version),
+    }
+}
+
+/// A PHP single-quoted string literal
+fn php_string(s: &str) -> String {
+    format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// A one-byte PHP string, for the first byte of a namespace
+fn php_byte(byte: u8) -> String {
+    if byte.is_ascii() {
+        php_string(&char::from(byte).to_string())
+    } else {
+        format!("\"\\x{byte:02x}\"")
+    }
+}
+
+/// A PHP expression for `path` from vendor/composer, so the generated files keep working
+/// when the project is moved. Opcache folds the concatenation into a constant.
+fn php_path(project_dir: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(project_dir).unwrap_or(path);
+    if relative.is_absolute() {
+        return php_string(&relative.to_string_lossy().replace('\\', "/"));
+    }
+    let relative = relative
+        .components()
+        .filter(|c| !matches!(c, std::path::Component::CurDir))
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect::<Vec<_>>()
+        .join("/");
+    match relative.strip_prefix("vendor/") {
+        Some(in_vendor) => format!("__DIR__ . '/..' . {}", php_string(&format!("/{in_vendor}"))),
+        None if relative.is_empty() => "__DIR__ . '/../..'".to_string(),
+        None => format!(
+            "__DIR__ . '/../..' . {}",
+            php_string(&format!("/{relative}"))
+        ),
+    }
+}
+
+/// Where an autoload `path` of the package at `base` is. As in Composer, a path that doesn't
+/// exist is read with the legacy `target-dir` taken into account: removed from the front of
+/// the root package's paths, and put in front of a dependency's.
+fn autoload_path(base: &Path, path: &str, target_dir: Option<&str>, root: bool) -> PathBuf {
+    let direct = base.join(path);
+    let Some(target_dir) = target_dir.map(|t| t.trim_matches(['/', '\\'])) else {
+        return direct;
+    };
+    if direct.exists() || target_dir.is_empty() {
+        return direct;
+    }
+    if root {
+        let path = path.trim_start_matches(['/', '\\']);
+        let stripped = path.strip_prefix(target_dir).unwrap_or(path);
+        base.join(stripped.trim_start_matches(['/', '\\']))
+    } else {
+        base.join(target_dir).join(path)
+    }
+}
+
+/// An `exclude-from-classmap` entry: the directory it is relative to and its pattern
+fn exclusion(
+    base: &Path,
+    pattern: &str,
+    target_dir: Option<&str>,
+    root: bool,
+) -> (PathBuf, String) {
+    let pattern = pattern.replace('\\', "/");
+    let pattern = pattern.trim_matches('/');
+    let pattern = pattern.strip_prefix("./").unwrap_or(pattern);
+    // Wildcards never name a file on disk, so only the part before them is looked up
+    let fixed = pattern.find('*').map_or(pattern, |i| &pattern[..i]);
+    let located = autoload_path(base, fixed, target_dir, root);
+    let mut prefix = located
+        .strip_prefix(base)
+        .map(|p| p.to_string_lossy().replace('\\', "/"))
+        .unwrap_or_default();
+    if fixed.ends_with('/') && !prefix.is_empty() {
+        prefix.push('/');
+    }
+    (
+        base.to_path_buf(),
+        format!("{prefix}{}", &pattern[fixed.len()..]),
     )
-    .await?;
-    Ok(())
+}
+
+fn is_excluded(excluded: &[(PathBuf, String)], file: &Path) -> bool {
+    excluded.iter().any(|(base, pattern)| {
+        file.strip_prefix(base).is_ok_and(|relative| {
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            matches_path_prefix(pattern.as_bytes(), relative.as_bytes())
+        })
+    })
+}
+
+/// Whether `pattern` matches `path` or one of the directories it is in: `*` stands for
+/// one or more characters of a path segment and `**` for one or more of any
+fn matches_path_prefix(pattern: &[u8], path: &[u8]) -> bool {
+    match pattern {
+        [] => path.is_empty() || path[0] == b'/',
+        [b'*', b'*', rest @ ..] => (1..=path.len()).any(|i| matches_path_prefix(rest, &path[i..])),
+        [b'*', rest @ ..] => (1..=path.len())
+            .take_while(|&i| path[i - 1] != b'/')
+            .any(|i| matches_path_prefix(rest, &path[i..])),
+        [c, rest @ ..] => path.first() == Some(c) && matches_path_prefix(rest, &path[1..]),
+    }
 }
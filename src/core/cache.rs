@@ -24,7 +24,7 @@ static MEMORY_CACHE: MemoryCacheType = LazyLock::new(|| {
     )))
 });
 
-use crate::core::cache_utils::{CacheEntry, get_cache_dir, get_cache_file_path};
+use crate::core::cache_utils::{CacheEntry, get_cache_dir, get_cache_file_path, read_cache_entry};
 
 async fn load_from_cache(cache_type: &str, key: &str) -> Option<JsonValue> {
     let cache_key = format!("{cache_type}:{key}");
@@ -54,29 +54,23 @@ async fn load_from_cache(cache_type: &str, key: &str) -> Option<JsonValue> {
     // Fallback to disk cache
     let file_path = get_cache_file_path(cache_type, key);
 
-    match fs::read_to_string(&file_path).await {
-        Ok(content) => {
-            match serde_json::from_str::<CacheEntry>(&content) {
-                Ok(entry) => {
-                    if entry.is_expired() {
-                        // Remove expired cache file asynchronously
-                        tokio::spawn(async move {
-                            fs::remove_file(&file_path).await.ok();
-                        });
-                        None
-                    } else {
-                        // Store in memory cache for next time
-                        {
-                            let mut cache = MEMORY_CACHE.write().await;
-                            cache.put(cache_key, (entry.data.clone(), entry.timestamp));
-                        }
-                        Some(entry.data)
-                    }
-                }
-                Err(_) => None,
-            }
+    let entry = read_cache_entry(&file_path).await?;
+    if entry.is_expired() {
+        // Entries with HTTP validators are kept so they can be revalidated
+        if !entry.has_validators() {
+            // Remove expired cache file asynchronously
+            tokio::spawn(async move {
+                fs::remove_file(&file_path).await.ok();
+            });
         }
-        Err(_) => None,
+        None
+    } else {
+        // Store in memory cache for next time
+        {
+            let mut cache = MEMORY_CACHE.write().await;
+            cache.put(cache_key, (entry.data.clone(), entry.timestamp));
+        }
+        Some(entry.data)
     }
 }
 
@@ -85,6 +79,16 @@ async fn save_to_cache(
     key: &str,
     value: &JsonValue,
     ttl: Duration,
+) -> Result<()> {
+    save_entry_to_cache(cache_type, key, value, ttl, CacheValidators::default()).await
+}
+
+async fn save_entry_to_cache(
+    cache_type: &str,
+    key: &str,
+    value: &JsonValue,
+    ttl: Duration,
+    validators: CacheValidators,
 ) -> Result<()> {
     let cache_key = format!("{cache_type}:{key}");
     let timestamp = SystemTime::now()
@@ -101,7 +105,9 @@ async fn save_to_cache(
     // Asynchronously save to disk cache
     let cache_dir = get_cache_dir().join(cache_type);
     let file_path = get_cache_file_path(cache_type, key);
-    let entry = CacheEntry::new(value.clone(), ttl);
+    let mut entry = CacheEntry::new(value.clone(), ttl);
+    entry.etag = validators.etag;
+    entry.last_modified = validators.last_modified;
 
     tokio::spawn(async move {
         if let Err(e) = fs::create_dir_all(&cache_dir).await {
@@ -109,10 +115,10 @@ async fn save_to_cache(
             return;
         }
 
-        if let Ok(content) = serde_json::to_string(&entry) {
-            if let Err(e) = fs::write(&file_path, content).await {
-                eprintln!("Failed to write cache file: {e}");
-            }
+        if let Ok(content) = serde_json::to_string(&entry)
+            && let Err(e) = fs::write(&file_path, content).await
+        {
+            eprintln!("Failed to write cache file: {e}");
         }
     });
 
@@ -128,6 +134,121 @@ pub async fn cache_set_meta(key: &str, val: JsonValue) {
     save_to_cache("meta", key, &val, CACHE_TTL).await.ok();
 }
 
+/// `ETag` / `Last-Modified` values returned with a cached response
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheValidators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl CacheValidators {
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+/// Result of looking up metadata that supports conditional requests
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetaLookup {
+    /// Usable without contacting the server
+    Fresh(JsonValue),
+    /// Cached on disk; confirm with a conditional GET before use
+    Revalidate(JsonValue, CacheValidators),
+    Missing,
+}
+
+/// Look up metadata, preferring revalidation over TTL expiry for entries with validators.
+/// Anything already loaded in this process is treated as fresh.
+pub async fn cache_lookup_meta(key: &str) -> MetaLookup {
+    lookup_meta(key, false).await
+}
+
+/// [`cache_lookup_meta`] for entries a successful changes-feed sync vouches for: a changed
+/// package would have been invalidated, so an entry within its TTL is used without
+/// revalidation and only expired ones fall back to a conditional GET.
+pub async fn cache_lookup_meta_synced(key: &str) -> MetaLookup {
+    lookup_meta(key, true).await
+}
+
+async fn lookup_meta(key: &str, trust_unexpired: bool) -> MetaLookup {
+    let cache_key = format!("meta:{key}");
+    if let Some((value, _)) = MEMORY_CACHE.read().await.peek(&cache_key) {
+        return MetaLookup::Fresh(value.clone());
+    }
+
+    let Some(entry) = read_cache_entry(&get_cache_file_path("meta", key)).await else {
+        return MetaLookup::Missing;
+    };
+
+    if entry.has_validators() && (entry.is_expired() || !trust_unexpired) {
+        let validators = CacheValidators {
+            etag: entry.etag,
+            last_modified: entry.last_modified,
+        };
+        MetaLookup::Revalidate(entry.data, validators)
+    } else if entry.is_expired() {
+        MetaLookup::Missing
+    } else {
+        MEMORY_CACHE
+            .write()
+            .await
+            .put(cache_key, (entry.data.clone(), entry.timestamp));
+        MetaLookup::Fresh(entry.data)
+    }
+}
+
+/// Store metadata together with the validators needed to revalidate it later
+pub async fn cache_set_meta_validated(key: &str, val: JsonValue, validators: CacheValidators) {
+    save_entry_to_cache("meta", key, &val, CACHE_TTL, validators)
+        .await
+        .ok();
+}
+
+/// Drop a metadata entry from memory and disk so the next lookup refetches it
+pub async fn cache_invalidate_meta(key: &str) {
+    MEMORY_CACHE.write().await.pop(&format!("meta:{key}"));
+    fs::remove_file(get_cache_file_path("meta", key)).await.ok();
+}
+
+/// Drop every metadata entry from memory and disk
+/// # Errors
+/// Returns an error if the metadata cache directory cannot be removed
+pub async fn cache_invalidate_all_meta() -> Result<()> {
+    {
+        let mut cache = MEMORY_CACHE.write().await;
+        let keys: Vec<String> = cache
+            .iter()
+            .filter(|(k, _)| k.starts_with("meta:"))
+            .map(|(k, _)| k.clone())
+            .collect();
+        for key in keys {
+            cache.pop(&key);
+        }
+    }
+    clear_cache_type("meta").await
+}
+
+fn metadata_changes_path() -> std::path::PathBuf {
+    get_cache_dir().join("metadata-changes.json")
+}
+
+/// Packagist changes-feed timestamp the metadata cache was last synced to
+pub async fn cache_get_changes_timestamp() -> Option<u64> {
+    let content = fs::read_to_string(metadata_changes_path()).await.ok()?;
+    let value: JsonValue = serde_json::from_str(&content).ok()?;
+    value.get("timestamp")?.as_u64()
+}
+
+/// Record the Packagist changes-feed timestamp the metadata cache is synced to
+/// # Errors
+/// Returns an error if the timestamp file cannot be written
+pub async fn cache_set_changes_timestamp(timestamp: u64) -> Result<()> {
+    fs::create_dir_all(get_cache_dir()).await?;
+    let content = serde_json::json!({ "timestamp": timestamp }).to_string();
+    fs::write(metadata_changes_path(), content).await?;
+    Ok(())
+}
+
 // Package info specific cache
 pub async fn cache_get_package_info(key: &str) -> Option<JsonValue> {
     load_from_cache("package_info", key).await
@@ -223,38 +344,46 @@ pub async fn clear_cache_type(cache_type: &str) -> Result<()> {
     Ok(())
 }
 
-// Get cache statistics
-/// # Errors
-/// Returns an error if the cache directory cannot be read
-pub async fn get_cache_stats() -> Result<HashMap<String, usize>> {
-    let mut stats = HashMap::new();
-    let cache_dir = get_cache_dir();
-
-    if !cache_dir.exists() {
-        return Ok(stats);
-    }
+/// Cache types accepted by `clear-cache`, with the directories each one covers
+pub const CACHE_TYPES: &[(&str, &[&str])] = &[
+    ("repo", &["meta", "package_info", "search"]),
+    ("files", &["packages", "patches"]),
+    ("vcs", &["vcs"]),
+    ("dependency_resolution", &["dependency_resolution"]),
+];
+
+/// Number of files and bytes on disk held by a cache type
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheUsage {
+    pub entries: usize,
+    pub bytes: u64,
+}
 
-    let cache_types = ["meta", "package_info", "search"];
-
-    for cache_type in &cache_types {
-        let type_dir = cache_dir.join(cache_type);
-        if type_dir.exists() {
-            match fs::read_dir(&type_dir).await {
-                Ok(mut entries) => {
-                    let mut count = 0;
-                    while let Ok(Some(_)) = entries.next_entry().await {
-                        count += 1;
-                    }
-                    stats.insert((*cache_type).to_string(), count);
-                }
-                Err(_) => {
-                    stats.insert((*cache_type).to_string(), 0);
+/// Usage of every cache type under the given cache directory
+pub fn cache_usage_in(cache_dir: &std::path::Path) -> HashMap<String, CacheUsage> {
+    CACHE_TYPES
+        .iter()
+        .map(|(name, dirs)| {
+            let mut usage = CacheUsage::default();
+            for dir in *dirs {
+                for entry in walkdir::WalkDir::new(cache_dir.join(dir))
+                    .into_iter()
+                    .flatten()
+                    .filter(|e| e.file_type().is_file())
+                {
+                    usage.entries += 1;
+                    usage.bytes += entry.metadata().map(|m| m.len()).unwrap_or(0);
                 }
             }
-        } else {
-            stats.insert((*cache_type).to_string(), 0);
-        }
-    }
+            ((*name).to_string(), usage)
+        })
+        .collect()
+}
 
-    Ok(stats)
+// Get cache statistics
+/// # Errors
+/// Returns an error if the statistics task fails
+pub async fn get_cache_stats() -> Result<HashMap<String, CacheUsage>> {
+    let cache_dir = get_cache_dir();
+    Ok(tokio::task::spawn_blocking(move || cache_usage_in(&cache_dir)).await?)
 }
@@ -1,6 +1,6 @@
 use serde_json::Value as JsonValue;
 use sha2::{Digest, Sha256};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -8,6 +8,11 @@ pub struct CacheEntry {
     pub data: JsonValue,
     pub timestamp: u64,
     pub ttl: u64,
+    /// HTTP validators; entries that have them are revalidated instead of expiring
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
 }
 
 impl CacheEntry {
@@ -19,9 +24,15 @@ impl CacheEntry {
                 .unwrap()
                 .as_secs(),
             ttl: ttl.as_secs(),
+            etag: None,
+            last_modified: None,
         }
     }
 
+    pub fn has_validators(&self) -> bool {
+        self.etag.is_some() || self.last_modified.is_some()
+    }
+
     pub fn is_expired(&self) -> bool {
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -31,6 +42,12 @@ impl CacheEntry {
     }
 }
 
+/// The cache entry stored at `path`, expired or not; `None` if it is missing or unreadable
+pub async fn read_cache_entry(path: &Path) -> Option<CacheEntry> {
+    let content = tokio::fs::read_to_string(path).await.ok()?;
+    serde_json::from_str(&content).ok()
+}
+
 pub fn hash_key(key: &str) -> String {
     let mut hasher = Sha256::new();
     hasher.update(key.as_bytes());
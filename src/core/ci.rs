@@ -0,0 +1,82 @@
+use std::fmt;
+use std::path::Path;
+
+/// Environment variable that forces CI mode on (`1`/`true`) or off (`0`/`false`)
+pub const CI_OVERRIDE_ENV: &str = "LECTERN_CI";
+
+/// Known continuous integration providers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CiProvider {
+    GitHubActions,
+    GitLabCi,
+    Generic,
+}
+
+impl fmt::Display for CiProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CiProvider::GitHubActions => write!(f, "GitHub Actions"),
+            CiProvider::GitLabCi => write!(f, "GitLab CI"),
+            CiProvider::Generic => write!(f, "CI"),
+        }
+    }
+}
+
+fn is_truthy(value: &str) -> bool {
+    matches!(
+        value.trim().to_ascii_lowercase().as_str(),
+        "1" | "true" | "yes" | "on"
+    )
+}
+
+/// Detect a CI environment using the given variable lookup.
+/// `LECTERN_CI` takes precedence over provider detection.
+pub fn detect_ci_from<F>(get_env: F) -> Option<CiProvider>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    detect_ci_with(get_env, None)
+}
+
+/// Detect a CI environment using the given variable lookup and the project's
+/// `extra.lectern.ci` setting. `LECTERN_CI` wins over the setting, and both
+/// win over provider detection.
+pub fn detect_ci_with<F>(get_env: F, configured: Option<bool>) -> Option<CiProvider>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let forced = get_env(CI_OVERRIDE_ENV)
+        .map(|value| is_truthy(&value))
+        .or(configured);
+    if forced == Some(false) {
+        return None;
+    }
+
+    if get_env("GITHUB_ACTIONS").is_some_and(|v| is_truthy(&v)) {
+        return Some(CiProvider::GitHubActions);
+    }
+    if get_env("GITLAB_CI").is_some_and(|v| is_truthy(&v)) {
+        return Some(CiProvider::GitLabCi);
+    }
+    if get_env("CI").is_some_and(|v| is_truthy(&v)) || forced == Some(true) {
+        return Some(CiProvider::Generic);
+    }
+
+    None
+}
+
+/// The `extra.lectern.ci` setting in composer.json, if present
+pub fn configured_ci(project_dir: &Path) -> Option<bool> {
+    let composer = crate::io::read_manifest_value(project_dir)?;
+    match composer.pointer("/extra/lectern/ci")? {
+        serde_json::Value::Bool(enabled) => Some(*enabled),
+        serde_json::Value::String(value) => Some(is_truthy(value)),
+        serde_json::Value::Number(value) => Some(value.as_u64() != Some(0)),
+        _ => None,
+    }
+}
+
+/// Detect a CI environment from the process environment and the project's config
+pub fn detect_ci(project_dir: &Path) -> Option<CiProvider> {
+    detect_ci_with(|name| std::env::var(name).ok(), configured_ci(project_dir))
+}
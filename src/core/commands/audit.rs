@@ -0,0 +1,135 @@
+use crate::commands::outdated::checkable_package_names;
+use crate::models::model::{Abandoned, Lock};
+use crate::resolver::{SecurityAdvisory, fetch_security_advisories};
+use crate::utils::{print_success, print_text, print_warning};
+use anyhow::Result;
+use std::collections::BTreeMap;
+
+/// Packagist takes the package list in the query string, so large locks are asked in parts
+const ADVISORY_BATCH: usize = 100;
+
+/// A locked package version that a security advisory affects
+#[derive(Debug, Clone)]
+pub struct Vulnerability {
+    pub package: String,
+    pub version: String,
+    pub advisory: SecurityAdvisory,
+}
+
+/// The advisories that affect the versions in `lock`, by package name. Advisories whose
+/// ranges cannot be parsed are counted as affecting, so they aren't silently dropped.
+pub fn find_vulnerabilities(
+    lock: &Lock,
+    advisories: &BTreeMap<String, Vec<SecurityAdvisory>>,
+) -> Vec<Vulnerability> {
+    let mut found = Vec::new();
+    for pkg in lock.packages.iter().chain(&lock.packages_dev) {
+        for advisory in advisories.get(&pkg.name).into_iter().flatten() {
+            if advisory.affects(&pkg.version).unwrap_or(true) {
+                found.push(Vulnerability {
+                    package: pkg.name.clone(),
+                    version: pkg.version.clone(),
+                    advisory: advisory.clone(),
+                });
+            }
+        }
+    }
+    found.sort_by(|a, b| a.package.cmp(&b.package));
+    found
+}
+
+/// Locked packages marked abandoned, by name, with the replacement their maintainers suggest
+pub fn abandoned_packages(lock: &Lock) -> Vec<(String, Abandoned)> {
+    let mut abandoned: Vec<(String, Abandoned)> = lock
+        .packages
+        .iter()
+        .chain(&lock.packages_dev)
+        .filter_map(|pkg| Some((pkg.name.clone(), pkg.abandonment()?)))
+        .collect();
+    abandoned.sort_by(|a, b| a.0.cmp(&b.0));
+    abandoned
+}
+
+/// Warn about each abandoned package, the way Composer does after resolving
+pub fn print_abandoned_warnings(abandoned: &[(String, Abandoned)]) {
+    for (name, abandonment) in abandoned {
+        print_warning(&format!(
+            "⚠️  Package {name} is abandoned, you should avoid using it. {abandonment}"
+        ));
+    }
+}
+
+/// What an audit of a lock found
+#[derive(Debug, Clone, Default)]
+pub struct AuditReport {
+    pub vulnerabilities: Vec<Vulnerability>,
+    pub abandoned: Vec<(String, Abandoned)>,
+}
+
+/// Check every package in `lock` against the Packagist security advisories, and list the
+/// abandoned ones
+/// # Errors
+/// Returns an error if the advisories cannot be fetched
+pub async fn audit_lock(lock: &Lock) -> Result<AuditReport> {
+    let names = checkable_package_names(lock);
+    let mut advisories = BTreeMap::new();
+    for batch in names.chunks(ADVISORY_BATCH) {
+        advisories.extend(fetch_security_advisories(batch).await?);
+    }
+    Ok(AuditReport {
+        vulnerabilities: find_vulnerabilities(lock, &advisories),
+        abandoned: abandoned_packages(lock),
+    })
+}
+
+/// Print the audit result, one line per advisory and per abandoned package
+pub fn print_audit_summary(report: &AuditReport) {
+    if !report.abandoned.is_empty() {
+        let lines: Vec<String> = report
+            .abandoned
+            .iter()
+            .map(|(name, abandonment)| format!("  • {name}: {abandonment}"))
+            .collect();
+        print_warning(&format!(
+            "⚠️  {} abandoned package(s):",
+            report.abandoned.len()
+        ));
+        print_text(&lines.join("\n"));
+    }
+    let vulnerabilities = &report.vulnerabilities;
+    if vulnerabilities.is_empty() {
+        print_success("🛡️  No security vulnerability advisories found");
+        return;
+    }
+    print_warning(&format!(
+        "⚠️  Found {} security vulnerability {} affecting the locked packages:",
+        vulnerabilities.len(),
+        if vulnerabilities.len() == 1 {
+            "advisory"
+        } else {
+            "advisories"
+        }
+    ));
+    let lines: Vec<String> = vulnerabilities
+        .iter()
+        .map(|found| {
+            let advisory = &found.advisory;
+            let id = advisory.cve.as_deref().unwrap_or(&advisory.advisory_id);
+            let severity = advisory
+                .severity
+                .as_deref()
+                .map(|severity| format!(" [{severity}]"))
+                .unwrap_or_default();
+            let link = advisory
+                .link
+                .as_deref()
+                .map(|link| format!(" {link}"))
+                .unwrap_or_default();
+            format!(
+                "  • {} ({}){severity} {id}: {}{link}",
+                found.package, found.version, advisory.title
+            )
+        })
+        .collect();
+    print_text(&lines.join("\n"));
+}
@@ -12,7 +12,7 @@ pub async fn browse_package(args: &BrowseArgs) -> Result<()> {
     let url = if args.homepage {
         // Get homepage from package details
         if let Some(versions) = &package_info.package.versions {
-            versions.values().next().and_then(|_v| {
+            versions.values().next().and({
                 // Try to find homepage in version details
                 None::<String> // Placeholder - would need to parse from version details
             })
@@ -33,11 +33,11 @@ pub async fn browse_package(args: &BrowseArgs) -> Result<()> {
             let mut opened = false;
 
             for browser in &browsers {
-                if let Ok(status) = std::process::Command::new(browser).arg(&url).status() {
-                    if status.success() {
-                        opened = true;
-                        break;
-                    }
+                if let Ok(status) = std::process::Command::new(browser).arg(&url).status()
+                    && status.success()
+                {
+                    opened = true;
+                    break;
                 }
             }
 
@@ -0,0 +1,157 @@
+use crate::cli::BumpArgs;
+use crate::io::{
+    manifest_path, read_composer_content_hash, read_composer_json_raw, read_lock,
+    write_composer_json, write_lock,
+};
+use crate::journal::{begin_operation, commit_operation};
+use crate::models::model::{ComposerJson, Lock};
+use crate::resolver::is_platform_dependency;
+use crate::utils::{print_info, print_step, print_success, print_text};
+use anyhow::{Result, anyhow};
+use std::path::Path;
+
+/// A root constraint raised to the locked version
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bump {
+    pub name: String,
+    pub dev: bool,
+    pub from: String,
+    pub to: String,
+}
+
+/// Which sections of composer.json to bump
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BumpSections {
+    All,
+    DevOnly,
+    NoDevOnly,
+}
+
+/// `^<locked version>` for a constraint, or `None` when it should stay as written:
+/// dev branches and pre-releases can't serve as a floor, and exact versions are
+/// already as narrow as they get
+pub fn bumped_constraint(constraint: &str, locked_version: &str) -> Option<String> {
+    let version = locked_version.trim_start_matches('v');
+    let numeric = !version.is_empty()
+        && version
+            .split('.')
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()));
+    if !numeric {
+        return None;
+    }
+    let constraint = constraint.trim();
+    if constraint.trim_start_matches(['v', '=']) == version {
+        return None;
+    }
+    let bumped = format!("^{version}");
+    (constraint != bumped).then_some(bumped)
+}
+
+/// Raise root constraints to the locked versions. With `only`, just those packages are bumped.
+pub fn bump_requirements(
+    composer: &mut ComposerJson,
+    lock: &Lock,
+    only: &[String],
+    sections: BumpSections,
+) -> Vec<Bump> {
+    let locked_version = |name: &str| {
+        lock.packages
+            .iter()
+            .chain(lock.packages_dev.iter())
+            .find(|pkg| pkg.name.eq_ignore_ascii_case(name))
+            .map(|pkg| pkg.version.clone())
+    };
+
+    let mut bumps = Vec::new();
+    for (dev, requires) in [
+        (false, &mut composer.require),
+        (true, &mut composer.require_dev),
+    ] {
+        let skipped = match sections {
+            BumpSections::All => false,
+            BumpSections::DevOnly => !dev,
+            BumpSections::NoDevOnly => dev,
+        };
+        if skipped {
+            continue;
+        }
+        for (name, constraint) in requires.iter_mut() {
+            if is_platform_dependency(name)
+                || (!only.is_empty() && !only.iter().any(|p| p.eq_ignore_ascii_case(name)))
+            {
+                continue;
+            }
+            let Some(to) = locked_version(name).and_then(|v| bumped_constraint(constraint, &v))
+            else {
+                continue;
+            };
+            bumps.push(Bump {
+                name: name.clone(),
+                dev,
+                from: std::mem::replace(constraint, to.clone()),
+                to,
+            });
+        }
+    }
+    bumps
+}
+
+/// Rewrite composer.json constraints to the locked versions, like `composer bump`
+/// # Errors
+/// Returns an error if composer.json or composer.lock cannot be read or written
+pub fn bump(args: &BumpArgs, working_dir: &Path) -> Result<()> {
+    if args.dev_only && args.no_dev_only {
+        return Err(anyhow!("--dev-only and --no-dev-only cannot be combined"));
+    }
+    let composer_path = manifest_path(working_dir);
+    let lock_path = working_dir.join("composer.lock");
+    if !lock_path.exists() {
+        return Err(anyhow!(
+            "No composer.lock found. Run 'lectern update' first."
+        ));
+    }
+    print_step("⬆️  Bumping constraints to the locked versions...");
+
+    let mut composer = read_composer_json_raw(&composer_path)?;
+    let mut lock = read_lock(&lock_path)?;
+    let sections = if args.dev_only {
+        BumpSections::DevOnly
+    } else if args.no_dev_only {
+        BumpSections::NoDevOnly
+    } else {
+        BumpSections::All
+    };
+    let bumps = bump_requirements(&mut composer, &lock, &args.packages, sections);
+
+    if bumps.is_empty() {
+        print_success("✅ All constraints already match the locked versions");
+        return Ok(());
+    }
+    for bump in &bumps {
+        let section = if bump.dev { " (dev)" } else { "" };
+        print_text(&format!(
+            "  {}{section}: {} → {}",
+            bump.name, bump.from, bump.to
+        ));
+    }
+    if args.dry_run {
+        print_info(&format!(
+            "🔍 Dry run - {} constraint(s) would be bumped",
+            bumps.len()
+        ));
+        return Ok(());
+    }
+
+    let mut entry = begin_operation(working_dir, "bump")?;
+    // A lock that was in sync stays in sync: bumped floors are satisfied by what is locked
+    let lock_was_fresh = lock.content_hash == read_composer_content_hash(&composer_path)?;
+    write_composer_json(&composer_path, &composer)?;
+    if lock_was_fresh {
+        lock.content_hash = read_composer_content_hash(&composer_path)?;
+        write_lock(&lock_path, &lock)?;
+    }
+    commit_operation(working_dir, &mut entry)?;
+
+    print_success(&format!("✅ Bumped {} constraint(s)", bumps.len()));
+    Ok(())
+}
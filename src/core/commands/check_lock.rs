@@ -0,0 +1,132 @@
+use crate::io::{manifest_path, read_composer_content_hash, read_composer_json, read_lock};
+use crate::models::model::{ComposerJson, Lock};
+use crate::resolver::dependency_utils::locked_aliases;
+use crate::resolver::packagist::is_platform_dependency;
+use crate::resolver::satisfies;
+use crate::resolver::version::alias_satisfies;
+use crate::utils::{print_error, print_info, print_step, print_success, print_warning};
+use anyhow::Result;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// A root requirement that differs from what composer.lock was written for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LockDrift {
+    /// Required in composer.json but missing from the lock
+    Added { name: String, constraint: String },
+    /// Locked version no longer satisfies the root constraint
+    Changed {
+        name: String,
+        constraint: String,
+        locked: String,
+    },
+    /// Locked but no longer required by the root or any locked package
+    Removed { name: String },
+}
+
+/// Compare root requirements against the locked package set
+pub fn find_lock_drift(composer: &ComposerJson, lock: &Lock) -> Vec<LockDrift> {
+    let mut drift = Vec::new();
+    let locked = lock.packages.iter().chain(lock.packages_dev.iter());
+
+    for (name, constraint) in composer.require.iter().chain(composer.require_dev.iter()) {
+        if is_platform_dependency(name) {
+            continue;
+        }
+        let Some(pkg) = locked.clone().find(|p| &p.name == name) else {
+            drift.push(LockDrift::Added {
+                name: name.clone(),
+                constraint: constraint.clone(),
+            });
+            continue;
+        };
+        let aliased = locked_aliases(lock, pkg)
+            .iter()
+            .any(|alias| alias_satisfies(constraint, alias));
+        if !aliased && !locked_version_satisfies(&pkg.version, constraint) {
+            drift.push(LockDrift::Changed {
+                name: name.clone(),
+                constraint: constraint.clone(),
+                locked: pkg.version.clone(),
+            });
+        }
+    }
+
+    // Anything still needed is either a root requirement or required by another locked package
+    let mut needed: BTreeSet<&str> = composer
+        .require
+        .keys()
+        .chain(composer.require_dev.keys())
+        .map(String::as_str)
+        .collect();
+    for pkg in locked.clone() {
+        for deps in [&pkg.require, &pkg.require_dev].into_iter().flatten() {
+            needed.extend(deps.keys().map(String::as_str));
+        }
+    }
+    for pkg in locked {
+        if !needed.contains(pkg.name.as_str()) {
+            drift.push(LockDrift::Removed {
+                name: pkg.name.clone(),
+            });
+        }
+    }
+
+    drift
+}
+
+/// Returns true when the locked version matches the constraint, or cannot be compared
+fn locked_version_satisfies(version: &str, constraint: &str) -> bool {
+    satisfies(constraint, version).unwrap_or(true)
+}
+
+/// Check whether composer.lock is up to date with composer.json.
+/// Returns `Ok(false)` when the lock is missing or stale.
+/// # Errors
+/// Returns an error if composer.json or composer.lock cannot be parsed
+pub async fn check_lock(working_dir: &Path) -> Result<bool> {
+    print_step("🔍 Checking composer.lock freshness...");
+
+    let composer_path = manifest_path(working_dir);
+    let composer = read_composer_json(&composer_path)?;
+    let lock_path = working_dir.join("composer.lock");
+    if !lock_path.exists() {
+        print_error("❌ No composer.lock found. Run 'lectern install' first.");
+        return Ok(false);
+    }
+    let lock = read_lock(&lock_path)?;
+
+    let expected_hash = read_composer_content_hash(&composer_path)?;
+    if expected_hash == lock.content_hash {
+        print_success("✅ composer.lock is up to date");
+        return Ok(true);
+    }
+
+    print_warning("⚠️  composer.lock is out of date with composer.json");
+    let drift = find_lock_drift(&composer, &lock);
+    if drift.is_empty() {
+        print_info("Root requirements still match the locked packages (other settings changed)");
+    } else {
+        println!("\n📋 Changed root requirements:");
+        for entry in &drift {
+            match entry {
+                LockDrift::Added { name, constraint } => {
+                    println!("  + {name} ({constraint}) is not locked");
+                }
+                LockDrift::Changed {
+                    name,
+                    constraint,
+                    locked,
+                } => {
+                    println!("  ~ {name} ({constraint}) is locked at {locked}");
+                }
+                LockDrift::Removed { name } => {
+                    println!("  - {name} is no longer required");
+                }
+            }
+        }
+    }
+    println!("\nRun 'lectern update' to refresh the lock file.");
+
+    Ok(false)
+}
@@ -0,0 +1,190 @@
+use crate::cli::CleanArgs;
+use crate::core::cache_utils::get_cache_dir;
+use crate::installer::installer_links::remove_dir_all;
+use crate::utils::{interactive, print_info, print_step, print_success, print_text, print_warning};
+use anyhow::{Result, anyhow};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Something `lectern clean` can remove
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CleanTarget {
+    /// The whole vendor directory (includes the autoloader)
+    Vendor,
+    /// Generated autoload files only
+    Autoload,
+    /// The global lectern cache
+    Cache,
+    /// composer.lock (asks for confirmation)
+    Lock,
+}
+
+impl CleanTarget {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "vendor" => Some(Self::Vendor),
+            "autoload" => Some(Self::Autoload),
+            "cache" => Some(Self::Cache),
+            "lock" => Some(Self::Lock),
+            _ => None,
+        }
+    }
+}
+
+/// A path scheduled for removal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CleanEntry {
+    pub target: CleanTarget,
+    pub path: PathBuf,
+}
+
+/// Parse target names, defaulting to `vendor` when none are given
+/// # Errors
+/// Returns an error for unknown target names
+pub fn parse_clean_targets(names: &[String], all: bool) -> Result<Vec<CleanTarget>> {
+    if all {
+        return Ok(vec![
+            CleanTarget::Vendor,
+            CleanTarget::Cache,
+            CleanTarget::Lock,
+        ]);
+    }
+    if names.is_empty() {
+        return Ok(vec![CleanTarget::Vendor]);
+    }
+
+    let mut targets = Vec::new();
+    for name in names {
+        let target = CleanTarget::from_name(name).ok_or_else(|| {
+            anyhow!("Unknown clean target: {name}. Use: vendor, autoload, cache, or lock")
+        })?;
+        if !targets.contains(&target) {
+            targets.push(target);
+        }
+    }
+    Ok(targets)
+}
+
+/// Existing paths that would be removed for the given targets.
+/// Every path is checked to resolve inside the project or cache root.
+/// # Errors
+/// Returns an error if a path escapes its root (e.g. through a symlink)
+pub fn plan_clean(
+    working_dir: &Path,
+    cache_dir: &Path,
+    targets: &[CleanTarget],
+) -> Result<Vec<CleanEntry>> {
+    let vendor = working_dir.join("vendor");
+    let mut entries = Vec::new();
+
+    for &target in targets {
+        // Removing vendor already takes the autoloader with it
+        if target == CleanTarget::Autoload && targets.contains(&CleanTarget::Vendor) {
+            continue;
+        }
+
+        let (root, paths) = match target {
+            CleanTarget::Vendor => (working_dir, vec![vendor.clone()]),
+            CleanTarget::Autoload => (
+                working_dir,
+                vec![
+                    vendor.join("autoload.php"),
+                    vendor.join("composer").join("autoload_psr4.php"),
+                    vendor.join("composer").join("autoload_classmap.php"),
+                    vendor.join("composer").join("autoload_static.php"),
+                    vendor.join("composer").join("installed.php"),
+                    vendor.join("composer").join("autoload.stamp"),
+                ],
+            ),
+            CleanTarget::Cache => (cache_dir, vec![cache_dir.to_path_buf()]),
+            CleanTarget::Lock => (working_dir, vec![working_dir.join("composer.lock")]),
+        };
+
+        for path in paths {
+            if path.symlink_metadata().is_err() {
+                continue;
+            }
+            ensure_within_root(&path, root, target == CleanTarget::Cache)?;
+            entries.push(CleanEntry { target, path });
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Refuse paths that resolve outside `root`; `allow_root` permits removing the root itself
+fn ensure_within_root(path: &Path, root: &Path, allow_root: bool) -> Result<()> {
+    let root = root
+        .canonicalize()
+        .map_err(|e| anyhow!("Cannot resolve {}: {e}", root.display()))?;
+    // Resolve the parent so a symlinked entry is judged by where it lives, not where it points
+    let parent = path
+        .parent()
+        .ok_or_else(|| anyhow!("Refusing to delete {}", path.display()))?
+        .canonicalize()?;
+    let resolved = match path.file_name() {
+        Some(name) => parent.join(name),
+        None => return Err(anyhow!("Refusing to delete {}", path.display())),
+    };
+
+    let inside = resolved.starts_with(&root) && (allow_root || resolved != root);
+    if !inside || resolved.parent().is_none() {
+        return Err(anyhow!(
+            "Refusing to delete {} outside {}",
+            resolved.display(),
+            root.display()
+        ));
+    }
+    Ok(())
+}
+
+fn confirm(question: &str) -> Result<bool> {
+    print!("{question} [y/N] ");
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(
+        answer.trim().to_ascii_lowercase().as_str(),
+        "y" | "yes"
+    ))
+}
+
+/// Remove vendor, autoload files, caches and/or composer.lock
+/// # Errors
+/// Returns an error for unknown targets, unsafe paths, or failed removals
+pub async fn clean(args: &CleanArgs, working_dir: &Path) -> Result<()> {
+    let targets = parse_clean_targets(&args.targets, args.all)?;
+    let entries = plan_clean(working_dir, &get_cache_dir(), &targets)?;
+
+    if entries.is_empty() {
+        print_info("Nothing to clean");
+        return Ok(());
+    }
+
+    if args.dry_run {
+        print_info("🔍 Dry run mode - the following would be removed:");
+        for entry in &entries {
+            print_text(&format!("  - {}", entry.path.display()));
+        }
+        return Ok(());
+    }
+
+    print_step("🧹 Cleaning project...");
+    for entry in &entries {
+        if entry.target == CleanTarget::Lock && !args.force {
+            let confirmed = interactive()
+                && confirm("composer.lock pins your dependency versions. Remove it?")?;
+            if !confirmed {
+                print_warning(
+                    "⚠️  Keeping composer.lock (use --force to remove it without asking)",
+                );
+                continue;
+            }
+        }
+        remove_dir_all(&entry.path)?;
+        print_info(&format!("Removed {}", entry.path.display()));
+    }
+    print_success("✅ Clean completed");
+
+    Ok(())
+}
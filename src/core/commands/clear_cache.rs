@@ -1,47 +1,86 @@
+use crate::cache::{CACHE_TYPES, CacheUsage, clear_cache_type, get_cache_stats};
 use crate::cli::ClearCacheArgs;
 use crate::utils::{print_info, print_step, print_success};
 use anyhow::Result;
 
+/// Resolve a `clear-cache` type name to the cache types it covers
+/// # Errors
+/// Returns an error for unknown type names
+pub fn parse_cache_type(name: &str) -> Result<Vec<&'static str>> {
+    if name == "all" {
+        return Ok(CACHE_TYPES.iter().map(|(name, _)| *name).collect());
+    }
+    CACHE_TYPES
+        .iter()
+        .find(|(known, _)| *known == name)
+        .map(|(known, _)| vec![*known])
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Unknown cache type: {name}. Use: all, repo, files, vcs, or dependency_resolution"
+            )
+        })
+}
+
+/// Human readable size in megabytes
+pub fn format_megabytes(bytes: u64) -> String {
+    format!("{:.2} MB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+fn print_usage(name: &str, usage: CacheUsage) {
+    println!(
+        "  {name:<22} {:>7} entries {:>12}",
+        usage.entries,
+        format_megabytes(usage.bytes)
+    );
+}
+
 /// Clear Lectern caches
 pub async fn clear_cache(args: &ClearCacheArgs) -> Result<()> {
     let cache_dir = crate::core::cache_utils::get_cache_dir();
+    let cache_type = args.cache_type.as_deref().unwrap_or("all");
+    let types = parse_cache_type(cache_type)?;
 
     if !cache_dir.exists() {
         print_info("No cache directory found");
         return Ok(());
     }
 
-    let cache_type = args.cache_type.as_deref().unwrap_or("all");
+    let stats = get_cache_stats().await?;
+    let usage_of = |name: &str| stats.get(name).copied().unwrap_or_default();
 
-    match cache_type {
-        "all" => {
-            print_step("🗑️  Clearing all caches...");
-            std::fs::remove_dir_all(&cache_dir)?;
-            std::fs::create_dir_all(&cache_dir)?;
-            print_success("✅ All caches cleared");
+    if args.dry_run {
+        print_step(&format!("📊 Cache usage in {}", cache_dir.display()));
+        for name in &types {
+            print_usage(name, usage_of(name));
         }
-        "repo" => {
-            print_step("🗑️  Clearing repository cache...");
-            let repo_cache = cache_dir.join("meta");
-            if repo_cache.exists() {
-                std::fs::remove_dir_all(&repo_cache)?;
-            }
-            print_success("✅ Repository cache cleared");
-        }
-        "files" => {
-            print_step("🗑️  Clearing package files cache...");
-            let files_cache = cache_dir.join("files");
-            if files_cache.exists() {
-                std::fs::remove_dir_all(&files_cache)?;
+        return Ok(());
+    }
+
+    print_step(&format!("🗑️  Clearing {cache_type} cache..."));
+    let mut total = CacheUsage::default();
+    for name in &types {
+        if let Some((_, dirs)) = CACHE_TYPES.iter().find(|(known, _)| known == name) {
+            for dir in *dirs {
+                clear_cache_type(dir).await?;
             }
-            print_success("✅ Files cache cleared");
-        }
-        _ => {
-            return Err(anyhow::anyhow!(
-                "Unknown cache type: {cache_type}. Use: all, repo, or files"
-            ));
         }
+        let usage = usage_of(name);
+        total.entries += usage.entries;
+        total.bytes += usage.bytes;
+        print_usage(name, usage);
+    }
+
+    if cache_type == "all" {
+        // Also drop bookkeeping files that belong to no cache type
+        std::fs::remove_dir_all(&cache_dir)?;
+        std::fs::create_dir_all(&cache_dir)?;
     }
 
+    print_success(&format!(
+        "✅ Removed {} entries ({})",
+        total.entries,
+        format_megabytes(total.bytes)
+    ));
+
     Ok(())
 }
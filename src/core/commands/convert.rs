@@ -0,0 +1,59 @@
+use crate::cli::ConvertArgs;
+use crate::io::{read_composer_content_hash, read_manifest_text, write_manifest_text};
+use crate::models::lectern_toml::ManifestFormat;
+use crate::utils::{print_info, print_success};
+use anyhow::{Context, Result, anyhow};
+use std::path::Path;
+
+/// Write the project's manifest in the other format. The content hash of the new file is
+/// checked against the old one, so composer.lock stays up to date after the conversion.
+/// # Errors
+/// Returns an error if there is no manifest, the target exists without `--force`, or the
+/// converted manifest would not match composer.lock
+pub fn convert(args: &ConvertArgs, working_dir: &Path) -> Result<()> {
+    let from = ManifestFormat::detect(working_dir);
+    let source = working_dir.join(from.file_name());
+    if !source.exists() {
+        return Err(anyhow!("No composer.json or Lectern.toml found"));
+    }
+    let to = match args.to.as_deref() {
+        None => match from {
+            ManifestFormat::ComposerJson => ManifestFormat::LecternToml,
+            ManifestFormat::LecternToml => ManifestFormat::ComposerJson,
+        },
+        Some("json") => ManifestFormat::ComposerJson,
+        Some("toml") => ManifestFormat::LecternToml,
+        Some(other) => return Err(anyhow!("Unknown format: {other}. Use: json or toml")),
+    };
+    if to == from {
+        print_info(&format!("The manifest is already {}", from.file_name()));
+        return Ok(());
+    }
+    let target = working_dir.join(to.file_name());
+    if target.exists() && !args.force {
+        return Err(anyhow!(
+            "{} already exists; use --force to overwrite it",
+            to.file_name()
+        ));
+    }
+
+    let json = read_manifest_text(&source)?;
+    write_manifest_text(&target, &format!("{}\n", json.trim_end()))?;
+    let expected = read_composer_content_hash(&source)?;
+    if read_composer_content_hash(&target)? != expected {
+        std::fs::remove_file(&target).ok();
+        return Err(anyhow!(
+            "{} would not match composer.lock's content-hash; nothing was converted",
+            to.file_name()
+        ));
+    }
+    if !args.keep {
+        std::fs::remove_file(&source).with_context(|| format!("remove {source:?}"))?;
+    }
+    print_success(&format!(
+        "✅ Converted {} to {}",
+        from.file_name(),
+        to.file_name()
+    ));
+    Ok(())
+}
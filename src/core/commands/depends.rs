@@ -1,7 +1,7 @@
 use crate::cli::DependsArgs;
 use crate::io::read_lock;
-use crate::utils::{print_error, print_info, print_step};
-use anyhow::Result;
+use crate::utils::{print_info, print_step};
+use anyhow::{Result, anyhow};
 use std::path::Path;
 
 /// Show which packages depend on a given package
@@ -13,8 +13,9 @@ pub async fn show_depends(args: &DependsArgs, working_dir: &Path) -> Result<()>
 
     let lock_path = working_dir.join("composer.lock");
     if !lock_path.exists() {
-        print_error("❌ No composer.lock found. Run 'lectern install' first.");
-        return Ok(());
+        return Err(anyhow!(
+            "No composer.lock found. Run 'lectern install' first."
+        ));
     }
 
     let lock = read_lock(&lock_path)?;
@@ -22,11 +23,11 @@ pub async fn show_depends(args: &DependsArgs, working_dir: &Path) -> Result<()>
 
     // Check all packages
     for pkg in lock.packages.iter().chain(lock.packages_dev.iter()) {
-        if let Some(requires) = &pkg.require {
-            if requires.contains_key(&args.package) {
-                let constraint = requires.get(&args.package).unwrap();
-                dependents.push((pkg.name.clone(), constraint.clone(), false));
-            }
+        if let Some(requires) = &pkg.require
+            && requires.contains_key(&args.package)
+        {
+            let constraint = requires.get(&args.package).unwrap();
+            dependents.push((pkg.name.clone(), constraint.clone(), false));
         }
     }
 
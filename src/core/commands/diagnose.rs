@@ -1,7 +1,111 @@
-use crate::io::{read_composer_json, read_lock};
+use crate::commands::self_update::latest_release;
+use crate::http::{self, http_settings};
+use crate::io::{manifest_path, read_composer_json, read_lock};
+use crate::timeouts::RequestKind;
 use crate::utils::{print_info, print_step, print_success};
 use anyhow::Result;
+use serde_json::Value;
 use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// How long each network check may take, so diagnosing offline doesn't hang
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Hosts every install talks to
+const ENDPOINTS: [&str; 2] = [
+    "https://repo.packagist.org/packages.json",
+    "https://api.github.com/rate_limit",
+];
+
+const MB: f64 = 1024.0 * 1024.0;
+
+/// The core rate limit of the GitHub API
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimit {
+    pub limit: u64,
+    pub remaining: u64,
+    /// Unix time the limit resets at
+    pub reset: u64,
+}
+
+/// The core limit from a `/rate_limit` response
+pub fn parse_rate_limit(body: &Value) -> Option<RateLimit> {
+    let core = body
+        .pointer("/resources/core")
+        .or_else(|| body.get("rate"))?;
+    Some(RateLimit {
+        limit: core.get("limit")?.as_u64()?,
+        remaining: core.get("remaining")?.as_u64()?,
+        reset: core.get("reset").and_then(Value::as_u64).unwrap_or(0),
+    })
+}
+
+/// A problem with the rate limit: exhausted, or under a tenth left
+pub fn rate_limit_issue(rate: &RateLimit, authenticated: bool) -> Option<String> {
+    let hint = if authenticated {
+        ""
+    } else {
+        "; add a github-oauth token to auth.json for a higher limit"
+    };
+    if rate.remaining == 0 {
+        Some(format!(
+            "❌ GitHub API rate limit exhausted until {}{hint}",
+            rate.reset
+        ))
+    } else if rate.remaining * 10 < rate.limit {
+        Some(format!(
+            "⚠️  Only {} of {} GitHub API requests left{hint}",
+            rate.remaining, rate.limit
+        ))
+    } else {
+        None
+    }
+}
+
+/// A problem when the disk holding the project has less room than vendor/ takes, which a
+/// full reinstall needs again
+pub fn disk_space_issue(free: u64, vendor_size: u64) -> Option<String> {
+    (free < vendor_size).then(|| {
+        format!(
+            "⚠️  Only {:.0} MB free on disk, less than vendor/ takes ({:.0} MB)",
+            free as f64 / MB,
+            vendor_size as f64 / MB
+        )
+    })
+}
+
+/// Free bytes on the filesystem holding `path`
+#[cfg(unix)]
+fn free_space(path: &Path) -> Option<u64> {
+    use std::os::unix::ffi::OsStrExt;
+    let path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    // SAFETY: statvfs is plain old data, for which all zeroes is a valid value
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: `path` is NUL-terminated and `stat` is a valid statvfs to fill in
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    #[allow(clippy::useless_conversion)]
+    Some(u64::from(stat.f_bavail) * u64::from(stat.f_frsize))
+}
+
+#[cfg(not(unix))]
+fn free_space(_path: &Path) -> Option<u64> {
+    None
+}
+
+/// GET `url` with a short timeout, returning the status, the body and how long it took
+async fn probe(url: &str, token: Option<&str>) -> Result<(u16, String, Duration)> {
+    let started = Instant::now();
+    let mut request = http::get(url, RequestKind::Metadata).timeout(PROBE_TIMEOUT);
+    if let Some(token) = token {
+        request = request.header("Authorization", format!("token {token}"));
+    }
+    let response = request.send().await?;
+    let status = response.status().as_u16();
+    let body = response.text().await.unwrap_or_default();
+    Ok((status, body, started.elapsed()))
+}
 
 /// Diagnose the system to identify common problems
 pub async fn diagnose(working_dir: &Path) -> Result<()> {
@@ -12,7 +116,7 @@ pub async fn diagnose(working_dir: &Path) -> Result<()> {
 
     // Check composer.json
     print_info("Checking composer.json...");
-    let composer_path = working_dir.join("composer.json");
+    let composer_path = manifest_path(working_dir);
     if !composer_path.exists() {
         issues.push("❌ composer.json not found".to_string());
     } else {
@@ -63,10 +167,7 @@ pub async fn diagnose(working_dir: &Path) -> Result<()> {
 
         // Check cache size
         if let Ok(size) = get_dir_size(&cache_dir) {
-            println!(
-                "    Cache size: {:.2} MB",
-                size as f64 / 1024.0 / 1024.0
-            );
+            println!("    Cache size: {:.2} MB", size as f64 / 1024.0 / 1024.0);
         }
     } else {
         println!("  ℹ️  Cache directory will be created on first use");
@@ -74,10 +175,7 @@ pub async fn diagnose(working_dir: &Path) -> Result<()> {
 
     // Check PHP (if available)
     print_info("Checking PHP availability...");
-    if let Ok(output) = std::process::Command::new("php")
-        .arg("--version")
-        .output()
-    {
+    if let Ok(output) = std::process::Command::new("php").arg("--version").output() {
         if output.status.success() {
             checks_passed += 1;
             if let Ok(version) = String::from_utf8(output.stdout) {
@@ -89,6 +187,130 @@ pub async fn diagnose(working_dir: &Path) -> Result<()> {
         issues.push("⚠️  PHP not found in PATH".to_string());
     }
 
+    // Check git, which source installs fall back to
+    print_info("Checking git availability...");
+    match std::process::Command::new("git").arg("--version").output() {
+        Ok(output) if output.status.success() => {
+            checks_passed += 1;
+            println!("  ✓ {}", String::from_utf8_lossy(&output.stdout).trim());
+        }
+        _ => issues.push("⚠️  git not found in PATH (needed for source installs)".to_string()),
+    }
+
+    // Check the cache can be written
+    print_info("Checking cache writability...");
+    let probe_file = cache_dir.join(format!(".diagnose-{}", std::process::id()));
+    match std::fs::create_dir_all(&cache_dir).and_then(|()| std::fs::write(&probe_file, b"ok")) {
+        Ok(()) => {
+            let _ = std::fs::remove_file(&probe_file);
+            checks_passed += 1;
+            println!("  ✓ Cache directory is writable");
+        }
+        Err(e) => issues.push(format!(
+            "❌ Cache directory {} is not writable: {e}",
+            cache_dir.display()
+        )),
+    }
+
+    // Check disk space against what vendor/ takes
+    print_info("Checking disk space...");
+    match free_space(working_dir) {
+        Some(free) => {
+            let vendor_size = get_dir_size(&vendor_path).unwrap_or(0);
+            match disk_space_issue(free, vendor_size) {
+                Some(issue) => issues.push(issue),
+                None => {
+                    checks_passed += 1;
+                    println!(
+                        "  ✓ {:.0} MB free, vendor/ takes {:.0} MB",
+                        free as f64 / MB,
+                        vendor_size as f64 / MB
+                    );
+                }
+            }
+        }
+        None => println!("  ℹ️  Free disk space can't be determined on this platform"),
+    }
+
+    // Check connectivity to the hosts installs need
+    print_info("Checking network connectivity...");
+    for url in ENDPOINTS {
+        match probe(url, None).await {
+            Ok((status, _, elapsed)) if status < 500 => {
+                checks_passed += 1;
+                println!("  ✓ {url} ({status}, {} ms)", elapsed.as_millis());
+            }
+            Ok((status, _, _)) => issues.push(format!("❌ {url} answered with HTTP {status}")),
+            Err(e) => issues.push(format!("❌ Cannot reach {url}: {e:#}")),
+        }
+    }
+
+    // Check configured tokens, and the GitHub rate limit they get
+    print_info("Checking authentication and GitHub rate limit...");
+    let auth = &http_settings().auth;
+    let github_token = auth.github_oauth.get("github.com").map(String::as_str);
+    match probe("https://api.github.com/rate_limit", github_token).await {
+        Ok((401, _, _)) => issues
+            .push("❌ The github-oauth token for github.com is invalid or expired".to_string()),
+        Ok((_, body, _)) => {
+            if github_token.is_some() {
+                checks_passed += 1;
+                println!("  ✓ github-oauth token for github.com is valid");
+            }
+            match serde_json::from_str(&body)
+                .ok()
+                .as_ref()
+                .and_then(parse_rate_limit)
+            {
+                Some(rate) => match rate_limit_issue(&rate, github_token.is_some()) {
+                    Some(issue) => issues.push(issue),
+                    None => {
+                        checks_passed += 1;
+                        println!(
+                            "  ✓ GitHub API: {} of {} requests left",
+                            rate.remaining, rate.limit
+                        );
+                    }
+                },
+                None => println!("  ℹ️  GitHub rate limit not reported"),
+            }
+        }
+        Err(e) => println!("  ℹ️  GitHub rate limit unknown: {e:#}"),
+    }
+    let gitlab_hosts = auth.gitlab_oauth.keys().chain(auth.gitlab_token.keys());
+    for host in gitlab_hosts {
+        // Credentials are added for the host by the request itself
+        match probe(&format!("https://{host}/api/v4/user"), None).await {
+            Ok((401, _, _)) => issues.push(format!(
+                "❌ The GitLab token for {host} is invalid or expired"
+            )),
+            Ok((status, _, _)) if status < 400 => {
+                checks_passed += 1;
+                println!("  ✓ GitLab token for {host} is valid");
+            }
+            Ok((status, _, _)) => {
+                println!("  ℹ️  GitLab token for {host} unchecked (HTTP {status})")
+            }
+            Err(e) => issues.push(format!("❌ Cannot reach {host} to check its token: {e:#}")),
+        }
+    }
+
+    // Check whether lectern itself is up to date
+    print_info("Checking lectern version...");
+    let current = env!("CARGO_PKG_VERSION");
+    match latest_release().await.and_then(|release| release.version()) {
+        Ok(latest) if semver::Version::parse(current).is_ok_and(|current| latest > current) => {
+            issues.push(format!(
+                "⚠️  lectern {current} is outdated, {latest} is available (run 'lectern self-update')"
+            ));
+        }
+        Ok(_) => {
+            checks_passed += 1;
+            println!("  ✓ lectern {current} is the latest version");
+        }
+        Err(e) => println!("  ℹ️  Could not check for a newer lectern: {e:#}"),
+    }
+
     // Summary
     println!("\n📊 Diagnostic Summary:");
     println!("  Checks passed: {checks_passed}");
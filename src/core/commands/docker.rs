@@ -0,0 +1,95 @@
+use crate::cli::DockerCacheConfigArgs;
+use crate::core::cache_utils::get_cache_dir;
+use crate::installer::installer_utils::get_package_cache_dir;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+
+/// `XDG_CACHE_HOME` inside containers, so the cache lands in `/var/cache/lectern`
+pub const CONTAINER_CACHE_HOME: &str = "/var/cache";
+/// Project directory used in the Dockerfile snippet
+pub const CONTAINER_WORKDIR: &str = "/app";
+
+/// Where Lectern keeps its cache and installs packages for a project
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheLayout {
+    pub cache_dir: PathBuf,
+    pub packages_dir: PathBuf,
+    pub vendor_dir: PathBuf,
+    pub has_lock: bool,
+}
+
+impl CacheLayout {
+    pub fn for_project(working_dir: &Path) -> Self {
+        Self {
+            cache_dir: get_cache_dir(),
+            packages_dir: get_package_cache_dir(),
+            vendor_dir: working_dir.join("vendor"),
+            has_lock: working_dir.join("composer.lock").exists(),
+        }
+    }
+}
+
+fn container_cache_dir() -> String {
+    format!("{CONTAINER_CACHE_HOME}/lectern")
+}
+
+/// Dockerfile lines that install dependencies in their own layer with a cache mount
+pub fn dockerfile_snippet(layout: &CacheLayout) -> String {
+    let manifests = if layout.has_lock {
+        "composer.json composer.lock"
+    } else {
+        "composer.json"
+    };
+    let cache = container_cache_dir();
+    [
+        "# syntax=docker/dockerfile:1".to_string(),
+        format!("ENV XDG_CACHE_HOME={CONTAINER_CACHE_HOME}"),
+        format!("WORKDIR {CONTAINER_WORKDIR}"),
+        "# Dependency manifests first, so this layer is only rebuilt when they change".to_string(),
+        format!("COPY {manifests} ./"),
+        format!("RUN --mount=type=cache,target={cache} lectern install"),
+        "# Application code last".to_string(),
+        "COPY . .".to_string(),
+    ]
+    .join("\n")
+}
+
+/// devcontainer.json properties that share the host cache and keep vendor/ in a volume
+pub fn devcontainer_snippet(layout: &CacheLayout) -> String {
+    let snippet = json!({
+        "containerEnv": {"XDG_CACHE_HOME": CONTAINER_CACHE_HOME},
+        "mounts": [
+            format!(
+                "source={},target={},type=bind",
+                layout.cache_dir.display(),
+                container_cache_dir()
+            ),
+            "source=${localWorkspaceFolderBasename}-vendor,target=${containerWorkspaceFolder}/vendor,type=volume",
+        ],
+    });
+    serde_json::to_string_pretty(&snippet).unwrap_or_default()
+}
+
+/// Print Docker and devcontainer snippets for the project's cache and vendor paths
+pub fn docker_cache_config(args: &DockerCacheConfigArgs, working_dir: &Path) {
+    let layout = CacheLayout::for_project(working_dir);
+    if args.cache_dir_print {
+        println!("{}", layout.cache_dir.display());
+        return;
+    }
+
+    println!("# Host paths");
+    println!("#   cache:    {}", layout.cache_dir.display());
+    println!("#   archives: {}", layout.packages_dir.display());
+    println!("#   vendor:   {}", layout.vendor_dir.display());
+    println!();
+    println!("# Dockerfile (BuildKit)");
+    println!("{}", dockerfile_snippet(&layout));
+    println!();
+    println!("# .devcontainer/devcontainer.json (merge into your config)");
+    println!("{}", devcontainer_snippet(&layout));
+    println!();
+    println!("# .dockerignore");
+    println!("vendor/");
+    println!(".lectern/");
+}
@@ -0,0 +1,141 @@
+use crate::cli::ExecArgs;
+use crate::commands::script::{bin_dir, script_env};
+use crate::installer::installer_links::is_php_script;
+use crate::io::{manifest_path, read_composer_json, read_lock};
+use crate::models::model::ComposerJson;
+use crate::utils::{print_info, print_text};
+use anyhow::{Context, Result, anyhow};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Binaries the project can run by name: the bin directory's entries, then the `bin`
+/// entries of locked packages that aren't linked there
+pub fn available_binaries(
+    composer: &ComposerJson,
+    working_dir: &Path,
+) -> BTreeMap<String, PathBuf> {
+    let mut binaries = BTreeMap::new();
+    if let Ok(entries) = std::fs::read_dir(bin_dir(composer, working_dir)) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_bat = path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("bat"));
+            // On Windows `.bat` proxies stand in for the shell proxies next to them
+            let name = match (is_bat, cfg!(windows)) {
+                (false, _) => path.file_name(),
+                (true, true) => path.file_stem(),
+                (true, false) => continue,
+            };
+            let Some(name) = name.map(|name| name.to_string_lossy().to_string()) else {
+                continue;
+            };
+            if is_bat {
+                binaries.insert(name, path);
+            } else {
+                binaries.entry(name).or_insert(path);
+            }
+        }
+    }
+
+    let lock_path = working_dir.join("composer.lock");
+    if let Ok(lock) = read_lock(&lock_path) {
+        let vendor = working_dir.join(
+            composer
+                .config
+                .as_ref()
+                .and_then(|config| config.vendor_dir.as_deref())
+                .unwrap_or("vendor"),
+        );
+        for pkg in lock.packages.iter().chain(&lock.packages_dev) {
+            for bin in pkg.bin.iter().flatten() {
+                let path = vendor.join(&pkg.name).join(bin);
+                let name = bin.rsplit('/').next().unwrap_or(bin).to_string();
+                if path.is_file() {
+                    binaries.entry(name).or_insert(path);
+                }
+            }
+        }
+    }
+    binaries
+}
+
+/// The command that runs `binary`: PHP scripts with `php`, `.bat` proxies with cmd,
+/// shell scripts without an executable bit with sh, and everything else directly
+fn command_for(binary: &Path) -> std::process::Command {
+    if is_php_script(binary) {
+        let php = std::env::var("PHP_BINARY").unwrap_or_else(|_| "php".to_string());
+        let mut command = std::process::Command::new(php);
+        command.arg(binary);
+        return command;
+    }
+    if binary
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("bat"))
+    {
+        let mut command = std::process::Command::new("cmd");
+        command.arg("/C").arg(binary);
+        return command;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let executable =
+            std::fs::metadata(binary).is_ok_and(|meta| meta.permissions().mode() & 0o111 != 0);
+        if !executable {
+            let mut command = std::process::Command::new("sh");
+            command.arg(binary);
+            return command;
+        }
+    }
+    std::process::Command::new(binary)
+}
+
+/// Run a binary of an installed package with the environment scripts get, like
+/// `composer exec`. Returns the binary's exit code.
+/// # Errors
+/// Returns an error if composer.json cannot be read, there is no such binary, or it
+/// cannot be started
+pub fn exec(args: &ExecArgs, working_dir: &Path) -> Result<i32> {
+    let composer = read_composer_json(&manifest_path(working_dir))?;
+    let binaries = available_binaries(&composer, working_dir);
+
+    let Some(name) = args.binary.as_deref().filter(|_| !args.list) else {
+        if binaries.is_empty() {
+            print_info("No binaries found; install packages that provide `bin` entries first");
+        } else {
+            print_text("Available binaries:");
+            for name in binaries.keys() {
+                print_text(&format!("- {name}"));
+            }
+        }
+        return Ok(0);
+    };
+
+    let binary = binaries.get(name).ok_or_else(|| {
+        let known = if binaries.is_empty() {
+            "no binaries are installed".to_string()
+        } else {
+            format!(
+                "available: {}",
+                binaries.keys().cloned().collect::<Vec<_>>().join(", ")
+            )
+        };
+        anyhow!("Binary '{name}' not found in the bin directory or package bin entries ({known})")
+    })?;
+
+    let working_dir = &std::path::absolute(working_dir)?;
+    let lectern = std::env::current_exe().context("locate the running executable")?;
+    // Arguments may be given after `--`
+    let binary_args = match args.args.split_first() {
+        Some((first, rest)) if first == "--" => rest,
+        _ => &args.args,
+    };
+    let status = command_for(binary)
+        .args(binary_args)
+        .current_dir(working_dir)
+        .envs(script_env(&composer, working_dir, true, &lectern))
+        .status()
+        .with_context(|| format!("run {}", binary.display()))?;
+    Ok(status.code().unwrap_or(1))
+}
@@ -0,0 +1,127 @@
+use crate::cli::FingerprintArgs;
+use crate::io::read_lock;
+use crate::models::model::Lock;
+use anyhow::{Result, anyhow};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+/// Length of `--short` fingerprints
+pub const SHORT_FINGERPRINT_LEN: usize = 12;
+
+/// Hashes identifying an installed dependency set. Only hashes are exposed, so the
+/// fingerprint can be shared (e.g. as a CI cache key) without revealing package names.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Fingerprint {
+    pub fingerprint: String,
+    pub short: String,
+    pub components: FingerprintComponents,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FingerprintComponents {
+    pub lock: String,
+    pub php: String,
+    pub platform: String,
+}
+
+fn sha256_hex(input: &str) -> String {
+    format!("{:x}", Sha256::digest(input.as_bytes()))
+}
+
+/// Hash of the locked packages' names, versions and references. Unlike `content-hash`
+/// it ignores composer.json edits that don't change what gets installed.
+pub fn lock_hash(lock: &Lock, include_dev: bool) -> String {
+    let dev = if include_dev {
+        lock.packages_dev.as_slice()
+    } else {
+        &[]
+    };
+    let mut entries: Vec<String> = lock
+        .packages
+        .iter()
+        .map(|pkg| (pkg, ""))
+        .chain(dev.iter().map(|pkg| (pkg, " dev")))
+        .map(|(pkg, kind)| {
+            let reference = pkg
+                .dist
+                .as_ref()
+                .map(|dist| dist.reference.as_str())
+                .or_else(|| pkg.source.as_ref().map(|source| source.reference.as_str()))
+                .unwrap_or("");
+            format!("{}@{}#{reference}{kind}", pkg.name, pkg.version)
+        })
+        .collect();
+    entries.sort();
+    sha256_hex(&entries.join("\n"))
+}
+
+/// `MAJOR.MINOR` of the PHP version packages are installed for: the `config.platform`
+/// override recorded in the lock, otherwise the `php` binary on PATH
+pub fn php_version(lock: &Lock) -> Option<String> {
+    let version = match lock
+        .platform_overrides
+        .as_ref()
+        .and_then(|overrides| overrides.get("php"))
+    {
+        Some(version) => version.clone(),
+        None => {
+            let output = std::process::Command::new("php")
+                .args(["-r", "echo PHP_VERSION;"])
+                .output()
+                .ok()
+                .filter(|output| output.status.success())?;
+            String::from_utf8(output.stdout).ok()?
+        }
+    };
+    let mut parts = version.trim().split('.');
+    Some(format!("{}.{}", parts.next()?, parts.next().unwrap_or("0")))
+}
+
+/// Operating system and CPU architecture, e.g. `linux-x86_64`
+pub fn platform() -> String {
+    format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH)
+}
+
+impl Fingerprint {
+    pub fn compute(lock: &Lock, include_dev: bool, php: Option<&str>, platform: &str) -> Self {
+        let components = FingerprintComponents {
+            lock: lock_hash(lock, include_dev),
+            php: sha256_hex(php.unwrap_or("unknown")),
+            platform: sha256_hex(platform),
+        };
+        let fingerprint = sha256_hex(&format!(
+            "{}\n{}\n{}",
+            components.lock, components.php, components.platform
+        ));
+        Self {
+            short: fingerprint[..SHORT_FINGERPRINT_LEN].to_string(),
+            fingerprint,
+            components,
+        }
+    }
+}
+
+/// Print a fingerprint of composer.lock for use as a CI cache key
+/// # Errors
+/// Returns an error if composer.lock cannot be read or the format is unknown
+pub fn fingerprint(args: &FingerprintArgs, working_dir: &Path) -> Result<()> {
+    let lock_path = working_dir.join("composer.lock");
+    if !lock_path.exists() {
+        return Err(anyhow!(
+            "No composer.lock found. Run 'lectern update' first."
+        ));
+    }
+    let lock = read_lock(&lock_path)?;
+    let php = php_version(&lock);
+    let result = Fingerprint::compute(&lock, !args.no_dev, php.as_deref(), &platform());
+
+    // Printed bare so it can be captured with $(lectern fingerprint)
+    match args.format.as_str() {
+        "text" if args.short => println!("{}", result.short),
+        "text" => println!("{}", result.fingerprint),
+        "json" => println!("{}", serde_json::to_string_pretty(&result)?),
+        other => return Err(anyhow!("Unknown format: {other}. Use: text or json")),
+    }
+    Ok(())
+}
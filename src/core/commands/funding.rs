@@ -1,44 +1,215 @@
+use crate::cli::FundArgs;
 use crate::io::read_lock;
-use crate::utils::{print_error, print_info, print_step};
-use anyhow::Result;
+use crate::models::model::{Lock, LockedPackage};
+use crate::resolver::packagist::{P2Version, fetch_packagist_versions_cached};
+use crate::utils::{print_info, print_step, print_warning};
+use anyhow::{Result, anyhow};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
 use std::path::Path;
 
-/// Show funding information
-pub async fn show_funding(working_dir: &Path) -> Result<()> {
-    print_step("💰 Checking for funding information...");
+/// Display name of the platform a funding entry points to, from its type or URL
+pub fn funding_platform(fund_type: &str, url: &str) -> String {
+    let fund_type = fund_type.to_lowercase();
+    let host = url
+        .split("://")
+        .nth(1)
+        .unwrap_or(url)
+        .split('/')
+        .next()
+        .unwrap_or_default()
+        .trim_start_matches("www.")
+        .to_lowercase();
+    let known = [
+        ("github", "github.com", "GitHub Sponsors"),
+        ("opencollective", "opencollective.com", "OpenCollective"),
+        ("tidelift", "tidelift.com", "Tidelift"),
+        ("patreon", "patreon.com", "Patreon"),
+    ];
+    for (name, domain, label) in known {
+        if fund_type == name || host == domain {
+            return label.to_string();
+        }
+    }
+    if fund_type.is_empty() {
+        "Other".to_string()
+    } else {
+        fund_type
+    }
+}
 
-    let lock_path = working_dir.join("composer.lock");
-    if !lock_path.exists() {
-        print_error("❌ No composer.lock found. Run 'lectern install' first.");
-        return Ok(());
+/// One funding URL and the packages that list it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FundingLink {
+    pub url: String,
+    pub packages: Vec<String>,
+}
+
+/// Funding links of the locked packages grouped by platform, plus the packages without any
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct FundingReport {
+    pub platforms: BTreeMap<String, Vec<FundingLink>>,
+    pub unfunded: Vec<String>,
+}
+
+impl FundingReport {
+    /// Build the report from each package's `funding` entries; links shared by several
+    /// packages are listed once, most widely used first
+    pub fn from_entries(entries: &BTreeMap<String, Vec<Value>>) -> Self {
+        let mut platforms: BTreeMap<String, BTreeMap<String, Vec<String>>> = BTreeMap::new();
+        let mut unfunded = Vec::new();
+        for (package, funding) in entries {
+            let mut funded = false;
+            for fund in funding {
+                let Some(url) = fund.get("url").and_then(Value::as_str) else {
+                    continue;
+                };
+                let fund_type = fund.get("type").and_then(Value::as_str).unwrap_or_default();
+                let packages = platforms
+                    .entry(funding_platform(fund_type, url))
+                    .or_default()
+                    .entry(url.to_string())
+                    .or_default();
+                if !packages.contains(package) {
+                    packages.push(package.clone());
+                }
+                funded = true;
+            }
+            if !funded {
+                unfunded.push(package.clone());
+            }
+        }
+
+        let platforms = platforms
+            .into_iter()
+            .map(|(platform, links)| {
+                let mut links: Vec<FundingLink> = links
+                    .into_iter()
+                    .map(|(url, packages)| FundingLink { url, packages })
+                    .collect();
+                links.sort_by(|a, b| {
+                    b.packages
+                        .len()
+                        .cmp(&a.packages.len())
+                        .then(a.url.cmp(&b.url))
+                });
+                (platform, links)
+            })
+            .collect();
+        Self {
+            platforms,
+            unfunded,
+        }
     }
+}
 
-    let lock = read_lock(&lock_path)?;
-    let mut has_funding = false;
+fn non_empty(funding: Option<Vec<Value>>) -> Option<Vec<Value>> {
+    funding.filter(|funding| !funding.is_empty())
+}
+
+/// Funding entries known without going to the network: the lock, then the installed composer.json
+pub fn local_funding(pkg: &LockedPackage, vendor: &Path) -> Option<Vec<Value>> {
+    non_empty(pkg.funding.clone()).or_else(|| {
+        let manifest =
+            std::fs::read_to_string(vendor.join(&pkg.name).join("composer.json")).ok()?;
+        let manifest: Value = serde_json::from_str(&manifest).ok()?;
+        non_empty(serde_json::from_value(manifest.get("funding")?.clone()).ok())
+    })
+}
 
-    println!("\n💰 Packages with funding information:");
+/// Funding of `version` in Packagist p2 metadata. Minified metadata only repeats a field
+/// when it changes, so entries are carried down from newer versions.
+pub fn funding_for_version(versions: &[P2Version], version: &str) -> Option<Vec<Value>> {
+    let wanted = version.trim_start_matches('v');
+    let mut funding = None;
+    for candidate in versions {
+        if let Some(value) = candidate.other.get("funding") {
+            funding = serde_json::from_value::<Vec<Value>>(value.clone()).ok();
+        }
+        if candidate.version.trim_start_matches('v') == wanted {
+            return non_empty(funding);
+        }
+    }
+    None
+}
 
+/// Collect funding entries for every locked package, asking Packagist for packages whose
+/// lock entry and installed manifest have none. Also returns how many of those lookups failed.
+async fn collect_funding(lock: &Lock, vendor: &Path) -> (BTreeMap<String, Vec<Value>>, usize) {
+    let mut entries = BTreeMap::new();
+    let mut missing = Vec::new();
     for pkg in lock.packages.iter().chain(lock.packages_dev.iter()) {
-        if let Some(funding) = &pkg.funding {
-            if !funding.is_empty() {
-                has_funding = true;
-                println!("\n  📦 {}", pkg.name);
-                for fund in funding {
-                    if let Some(fund_type) = fund.get("type").and_then(|v| v.as_str()) {
-                        if let Some(url) = fund.get("url").and_then(|v| v.as_str()) {
-                            println!("    • {fund_type}: {url}");
-                        }
-                    }
-                }
+        match local_funding(pkg, vendor) {
+            Some(funding) => {
+                entries.insert(pkg.name.clone(), funding);
             }
+            None => missing.push(pkg),
         }
     }
 
-    if !has_funding {
+    let fetches = missing.iter().map(|pkg| async move {
+        let funding = fetch_packagist_versions_cached(&pkg.name)
+            .await
+            .map(|versions| funding_for_version(&versions, &pkg.version));
+        (pkg.name.clone(), funding)
+    });
+    let mut failed = 0;
+    for (name, funding) in futures::future::join_all(fetches).await {
+        let funding = funding.unwrap_or_else(|_| {
+            failed += 1;
+            None
+        });
+        entries.insert(name, funding.unwrap_or_default());
+    }
+    (entries, failed)
+}
+
+/// Show funding information of all locked packages grouped by platform
+/// # Errors
+/// Returns an error if the lock file cannot be read or the format is unknown
+pub async fn show_funding(args: &FundArgs, working_dir: &Path) -> Result<()> {
+    let json = match args.format.as_str() {
+        "text" => false,
+        "json" => true,
+        other => return Err(anyhow!("Unknown format: {other}. Use: text or json")),
+    };
+    if !json {
+        print_step("💰 Checking for funding information...");
+    }
+
+    let lock_path = working_dir.join("composer.lock");
+    if !lock_path.exists() {
+        return Err(anyhow!(
+            "No composer.lock found. Run 'lectern install' first."
+        ));
+    }
+
+    let lock = read_lock(&lock_path)?;
+    let (entries, failed) = collect_funding(&lock, &working_dir.join("vendor")).await;
+    let report = FundingReport::from_entries(&entries);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+    if failed > 0 {
+        print_warning(&format!(
+            "⚠️  Could not fetch funding information for {failed} package(s) from Packagist"
+        ));
+    }
+    if report.platforms.is_empty() {
         print_info("No funding information found in installed packages");
-    } else {
-        println!("\n💙 Consider supporting these packages!");
+        return Ok(());
     }
 
+    for (platform, links) in &report.platforms {
+        println!("\n💰 {platform}:");
+        for link in links {
+            println!("  • {}", link.url);
+            println!("    {}", link.packages.join(", "));
+        }
+    }
+    println!("\n💙 Consider supporting these packages!");
     Ok(())
 }
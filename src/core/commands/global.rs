@@ -0,0 +1,128 @@
+use crate::autoload::{AutoloadOptions, lock_packages, write_autoload_files_with_options};
+use crate::installer::installer_links::{remove_dir_all, write_bin_proxy};
+use crate::io::{read_composer_json, read_lock};
+use crate::utils::{print_info, print_success, print_warning};
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+/// Per-user Lectern configuration directory: `$XDG_CONFIG_HOME/lectern`, otherwise `~/.config/lectern`
+pub fn config_dir() -> PathBuf {
+    if let Ok(xdg) = std::env::var("XDG_CONFIG_HOME") {
+        return PathBuf::from(xdg).join("lectern");
+    }
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".config")
+        .join("lectern")
+}
+
+/// Project directory `lectern global` runs commands in
+pub fn global_dir() -> PathBuf {
+    config_dir().join("global")
+}
+
+/// Directory the binaries of globally installed packages are linked into
+pub fn global_bin_dir() -> PathBuf {
+    config_dir().join("bin")
+}
+
+/// Create the global project with an empty composer.json the first time it is used
+/// # Errors
+/// Returns an error if the directory or composer.json cannot be written
+pub fn ensure_global_project(dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir).with_context(|| format!("create {dir:?}"))?;
+    let composer_path = dir.join("composer.json");
+    if !composer_path.exists() {
+        std::fs::write(&composer_path, "{\n    \"require\": {}\n}\n")
+            .with_context(|| format!("write {composer_path:?}"))?;
+    }
+    Ok(())
+}
+
+/// Link the `bin` entries of the project's locked packages into `bin_dir`, replacing whatever
+/// it held before. When two packages ship a binary with the same name the first one wins.
+/// Returns the linked names.
+/// # Errors
+/// Returns an error if the lock file cannot be read or a link cannot be created
+pub fn link_binaries(project: &Path, bin_dir: &Path) -> Result<Vec<String>> {
+    let lock_path = project.join("composer.lock");
+    let packages = if lock_path.exists() {
+        read_lock(&lock_path)?.packages
+    } else {
+        Vec::new()
+    };
+
+    let mut binaries: BTreeMap<String, (String, PathBuf)> = BTreeMap::new();
+    for pkg in &packages {
+        for bin in pkg.bin.iter().flatten() {
+            let target = project.join("vendor").join(&pkg.name).join(bin);
+            let name = bin.rsplit('/').next().unwrap_or(bin).to_string();
+            if !target.is_file() {
+                continue;
+            }
+            if let Some((owner, _)) = binaries.get(&name) {
+                print_warning(&format!(
+                    "⚠️  {} also provides {name}, keeping the one from {owner}",
+                    pkg.name
+                ));
+                continue;
+            }
+            binaries.insert(name, (pkg.name.clone(), target));
+        }
+    }
+
+    // The directory is managed by Lectern, so links of removed packages can simply go
+    remove_dir_all(bin_dir)?;
+    std::fs::create_dir_all(bin_dir).with_context(|| format!("create {bin_dir:?}"))?;
+    for (name, (_, target)) in &binaries {
+        write_bin_proxy(target, &bin_dir.join(name))?;
+    }
+    Ok(binaries.into_keys().collect())
+}
+
+/// How to put `bin_dir` on the PATH, or nothing when `path` already contains it
+pub fn path_guidance(bin_dir: &Path, path: Option<&OsStr>) -> Option<String> {
+    if path.is_some_and(|path| std::env::split_paths(path).any(|dir| dir == bin_dir)) {
+        return None;
+    }
+    let bin = bin_dir.display();
+    Some(if cfg!(windows) {
+        format!("Add {bin} to your PATH to run globally installed tools")
+    } else {
+        format!(
+            "Add {bin} to your PATH to run globally installed tools, e.g. with\n    export PATH=\"{bin}:$PATH\"\nin ~/.bashrc or ~/.zshrc"
+        )
+    })
+}
+
+/// Bring the global project up to date after require, remove or update: regenerate its
+/// autoloader, which installed tools load, and relink binaries
+/// # Errors
+/// Returns an error if the autoloader or binary links cannot be written
+pub async fn finish_global_change(dir: &Path) -> Result<()> {
+    let lock_path = dir.join("composer.lock");
+    if lock_path.exists() {
+        let composer = read_composer_json(&dir.join("composer.json"))?;
+        // Global tools are installed without their dev requirements
+        let lock = read_lock(&lock_path)?;
+        let installed = lock_packages(&lock, true);
+        let options = AutoloadOptions::for_lock(&lock, true);
+        write_autoload_files_with_options(dir, &composer, &installed, &options).await?;
+    }
+
+    let bin_dir = global_bin_dir();
+    let linked = link_binaries(dir, &bin_dir)?;
+    if !linked.is_empty() {
+        print_success(&format!(
+            "🔗 Linked {} into {}",
+            linked.join(", "),
+            bin_dir.display()
+        ));
+        if let Some(guidance) = path_guidance(&bin_dir, std::env::var_os("PATH").as_deref()) {
+            print_info(&guidance);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,287 @@
+use crate::cli::GraphArgs;
+use crate::graph::DependencyGraph;
+use crate::io::{manifest_path, read_composer_json, read_lock};
+use crate::models::model::{ComposerJson, Lock};
+use crate::utils::print_success;
+use anyhow::{Context, Result, anyhow};
+use serde_json::{Map, Value, json};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// Name Composer gives a root package without one
+const UNNAMED_ROOT: &str = "__root__";
+
+/// A package in the exported graph
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphNode {
+    pub name: String,
+    /// The locked version; the root has none
+    pub version: Option<String>,
+    pub root: bool,
+    /// Only installed for development
+    pub dev: bool,
+}
+
+/// A requirement between two packages of the exported graph
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+    pub constraint: String,
+    /// From `require-dev` or a dev package
+    pub dev: bool,
+}
+
+/// The root package and the locked packages with the requirements between them, ready to
+/// render for Graphviz, Mermaid or as JSON
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GraphExport {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+impl GraphExport {
+    /// Build the graph from the lock, with the root's requirements from composer.json
+    /// when there is one. Dev packages and `require-dev` are left out unless `include_dev`.
+    pub fn from_lock(lock: &Lock, composer: Option<&ComposerJson>, include_dev: bool) -> Self {
+        let dev_packages: BTreeSet<&str> = lock
+            .packages_dev
+            .iter()
+            .map(|pkg| pkg.name.as_str())
+            .collect();
+        let packages = if include_dev {
+            lock.packages
+                .iter()
+                .chain(&lock.packages_dev)
+                .collect::<Vec<_>>()
+        } else {
+            lock.packages.iter().collect()
+        };
+        let graph = DependencyGraph::from_packages(packages.iter().copied());
+
+        let mut export = Self::default();
+        if let Some(composer) = composer {
+            let root = composer
+                .name
+                .clone()
+                .unwrap_or_else(|| UNNAMED_ROOT.to_string());
+            export.nodes.push(GraphNode {
+                name: root.clone(),
+                version: None,
+                root: true,
+                dev: false,
+            });
+            let dev_requires = if include_dev {
+                Some(&composer.require_dev)
+            } else {
+                None
+            };
+            let requires = composer
+                .require
+                .iter()
+                .map(|requirement| (requirement, false))
+                .chain(
+                    dev_requires
+                        .into_iter()
+                        .flatten()
+                        .map(|requirement| (requirement, true)),
+                );
+            for ((name, constraint), dev) in requires {
+                if graph.version(name).is_some() {
+                    export.edges.push(GraphEdge {
+                        from: root.clone(),
+                        to: name.clone(),
+                        constraint: constraint.clone(),
+                        dev,
+                    });
+                }
+            }
+        }
+
+        for pkg in &packages {
+            let dev = dev_packages.contains(pkg.name.as_str());
+            export.nodes.push(GraphNode {
+                name: pkg.name.clone(),
+                version: Some(pkg.version.clone()),
+                root: false,
+                dev,
+            });
+            for (dependency, constraint) in graph.dependencies(&pkg.name) {
+                export.edges.push(GraphEdge {
+                    from: pkg.name.clone(),
+                    to: dependency.clone(),
+                    constraint: constraint.clone(),
+                    dev,
+                });
+            }
+        }
+        export
+    }
+
+    /// Graphviz DOT: dev packages and requirements are dashed, the root is bold
+    pub fn to_dot(&self) -> String {
+        let escape = |text: &str| text.replace('\\', "\\\\").replace('"', "\\\"");
+        let quote = |text: &str| format!("\"{}\"", escape(text));
+        let mut lines = vec![
+            "digraph dependencies {".to_string(),
+            "    rankdir=LR;".to_string(),
+            "    node [shape=box];".to_string(),
+        ];
+        for node in &self.nodes {
+            // `\n` is DOT's line break, so the parts are escaped but not the label
+            let label = match &node.version {
+                Some(version) => format!("{}\\n{}", escape(&node.name), escape(version)),
+                None => escape(&node.name),
+            };
+            let style = if node.root {
+                ", style=bold"
+            } else if node.dev {
+                ", style=dashed"
+            } else {
+                ""
+            };
+            lines.push(format!(
+                "    {} [label=\"{label}\"{style}];",
+                quote(&node.name)
+            ));
+        }
+        for edge in &self.edges {
+            let style = if edge.dev { ", style=dashed" } else { "" };
+            lines.push(format!(
+                "    {} -> {} [label={}{style}];",
+                quote(&edge.from),
+                quote(&edge.to),
+                quote(&edge.constraint)
+            ));
+        }
+        lines.push("}".to_string());
+        lines.join("\n")
+    }
+
+    /// A Mermaid flowchart: dev requirements are dotted arrows and dev packages dashed boxes
+    pub fn to_mermaid(&self) -> String {
+        // Mermaid ids can't hold `/` or `-`, so nodes are numbered
+        let ids: BTreeMap<&str, String> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (node.name.as_str(), format!("n{index}")))
+            .collect();
+        let escape = |text: &str| text.replace('"', "#quot;").replace('|', "#124;");
+
+        let mut lines = vec!["graph LR".to_string()];
+        for node in &self.nodes {
+            let label = match &node.version {
+                Some(version) => format!("{} {version}", node.name),
+                None => node.name.clone(),
+            };
+            lines.push(format!(
+                "    {}[\"{}\"]",
+                ids[node.name.as_str()],
+                escape(&label)
+            ));
+        }
+        for edge in &self.edges {
+            let arrow = if edge.dev { "-.->" } else { "-->" };
+            lines.push(format!(
+                "    {} {arrow}|\"{}\"| {}",
+                ids[edge.from.as_str()],
+                escape(&edge.constraint),
+                ids[edge.to.as_str()]
+            ));
+        }
+        let class = |matches: fn(&GraphNode) -> bool| {
+            self.nodes
+                .iter()
+                .filter(|node| matches(node))
+                .map(|node| ids[node.name.as_str()].clone())
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        let roots = class(|node| node.root);
+        if !roots.is_empty() {
+            lines.push("    classDef root font-weight:bold".to_string());
+            lines.push(format!("    class {roots} root"));
+        }
+        let dev = class(|node| node.dev);
+        if !dev.is_empty() {
+            lines.push("    classDef dev stroke-dasharray: 5 5".to_string());
+            lines.push(format!("    class {dev} dev"));
+        }
+        lines.join("\n")
+    }
+
+    /// An adjacency list: every package with its version, root and dev markers and the
+    /// packages it requires, split into `require` and `require-dev`
+    pub fn to_json(&self) -> Value {
+        let mut packages = Map::new();
+        for node in &self.nodes {
+            let requires = |dev: bool| -> Map<String, Value> {
+                self.edges
+                    .iter()
+                    .filter(|edge| edge.from == node.name && edge.dev == dev)
+                    .map(|edge| (edge.to.clone(), Value::from(edge.constraint.as_str())))
+                    .collect()
+            };
+            // A dev package's requirements are all dev edges, but they are its `require`
+            let (require, require_dev) = if node.dev {
+                (requires(true), Map::new())
+            } else {
+                (requires(false), requires(true))
+            };
+            packages.insert(
+                node.name.clone(),
+                json!({
+                    "version": node.version,
+                    "root": node.root,
+                    "dev": node.dev,
+                    "require": require,
+                    "require-dev": require_dev,
+                }),
+            );
+        }
+        let root = self
+            .nodes
+            .iter()
+            .find(|node| node.root)
+            .map(|node| &node.name);
+        json!({ "root": root, "packages": packages })
+    }
+}
+
+/// Print or write the dependency graph of composer.lock as DOT, Mermaid or JSON
+/// # Errors
+/// Returns an error if there is no lock file, the format is unknown or the output file
+/// cannot be written
+pub fn export_graph(args: &GraphArgs, working_dir: &Path) -> Result<()> {
+    let lock_path = working_dir.join("composer.lock");
+    if !lock_path.exists() {
+        return Err(anyhow!(
+            "No composer.lock found. Run 'lectern install' first."
+        ));
+    }
+    let lock = read_lock(&lock_path)?;
+    let composer = read_composer_json(&manifest_path(working_dir)).ok();
+    let export = GraphExport::from_lock(&lock, composer.as_ref(), !args.no_dev);
+    let contents = match args.format.as_str() {
+        "dot" => export.to_dot(),
+        "mermaid" => export.to_mermaid(),
+        "json" => serde_json::to_string_pretty(&export.to_json())?,
+        other => {
+            return Err(anyhow!(
+                "Unknown format: {other}. Use: dot, mermaid or json"
+            ));
+        }
+    };
+
+    match &args.output {
+        Some(output) => {
+            let path = working_dir.join(output);
+            std::fs::write(&path, format!("{contents}\n"))
+                .with_context(|| format!("write {path:?}"))?;
+            print_success(&format!("✅ Wrote {} graph to {output}", args.format));
+        }
+        None => println!("{contents}"),
+    }
+    Ok(())
+}
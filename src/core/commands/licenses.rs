@@ -1,69 +1,214 @@
-use crate::io::read_lock;
+use crate::cli::LicensesArgs;
+use crate::io::{manifest_path, read_composer_json, read_lock};
+use crate::models::model::{ComposerJson, Lock};
 use crate::utils::{print_error, print_info, print_success};
-use anyhow::Result;
+use anyhow::{Result, anyhow};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
 use std::path::Path;
 
-/// Show licenses of all dependencies
-/// # Errors
-/// Returns an error if the lock file cannot be read
-pub async fn show_dependency_licenses(working_dir: &Path, quiet: bool) -> Result<()> {
-    if !quiet {
-        print_info("📜 Reading license information from lock file...");
-    }
+/// Label used for packages that declare no license
+const UNKNOWN_LICENSE: &str = "Unknown";
 
-    let lock_path = working_dir.join("composer.lock");
+/// Licenses a project accepts, from `extra.lectern.allowed-licenses` and
+/// `extra.lectern.denied-licenses` (SPDX identifiers, compared case-insensitively)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LicensePolicy {
+    pub allowed: Vec<String>,
+    pub denied: Vec<String>,
+}
 
-    if !lock_path.exists() {
-        print_error("❌ No composer.lock found. Run 'lectern install' first.");
-        return Ok(());
+impl LicensePolicy {
+    pub fn from_composer(composer: &ComposerJson) -> Self {
+        let list = |key: &str| -> Vec<String> {
+            composer
+                .extra
+                .as_ref()
+                .and_then(|extra| extra.pointer(&format!("/lectern/{key}")))
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        };
+        Self {
+            allowed: list("allowed-licenses"),
+            denied: list("denied-licenses"),
+        }
     }
 
-    let lock = read_lock(&lock_path)?;
+    pub fn is_empty(&self) -> bool {
+        self.allowed.is_empty() && self.denied.is_empty()
+    }
 
-    let total_packages = lock.packages.len() + lock.packages_dev.len();
-    if total_packages == 0 {
-        if !quiet {
-            print_info("📦 No packages installed.");
+    /// Whether a package may be used. Several licenses are alternatives (as in Composer),
+    /// so one that is allowed and not denied is enough. Packages without a license only
+    /// pass when nothing is allow-listed.
+    pub fn permits(&self, licenses: &[String]) -> bool {
+        let listed = |list: &[String], license: &str| {
+            list.iter().any(|entry| entry.eq_ignore_ascii_case(license))
+        };
+        if licenses.is_empty() {
+            return self.allowed.is_empty();
         }
-        return Ok(());
+        licenses.iter().any(|license| {
+            !listed(&self.denied, license)
+                && (self.allowed.is_empty() || listed(&self.allowed, license))
+        })
     }
+}
 
-    let mut table_rows = Vec::new();
+/// License information of one locked package
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PackageLicense {
+    pub version: String,
+    pub license: Vec<String>,
+    pub dev: bool,
+}
 
-    // Process regular packages
-    for pkg in &lock.packages {
-        let license_info = pkg
-            .license
-            .as_ref()
-            .map_or_else(|| "Unknown".to_string(), |licenses| licenses.join(", "));
+/// Licenses of the locked packages, how often each occurs and which packages the policy rejects
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct LicenseReport {
+    pub dependencies: BTreeMap<String, PackageLicense>,
+    pub summary: BTreeMap<String, usize>,
+    pub violations: Vec<String>,
+}
 
-        table_rows.push((pkg.name.clone(), pkg.version.clone(), license_info));
+impl LicenseReport {
+    pub fn from_lock(lock: &Lock, include_dev: bool, policy: &LicensePolicy) -> Self {
+        let mut report = Self::default();
+        let dev_packages = if include_dev {
+            lock.packages_dev.as_slice()
+        } else {
+            &[]
+        };
+        let packages = lock
+            .packages
+            .iter()
+            .map(|pkg| (pkg, false))
+            .chain(dev_packages.iter().map(|pkg| (pkg, true)));
+        for (pkg, dev) in packages {
+            let license = pkg.license.clone().unwrap_or_default();
+            if license.is_empty() {
+                *report
+                    .summary
+                    .entry(UNKNOWN_LICENSE.to_string())
+                    .or_default() += 1;
+            }
+            for name in &license {
+                *report.summary.entry(name.clone()).or_default() += 1;
+            }
+            if !policy.permits(&license) {
+                report.violations.push(pkg.name.clone());
+            }
+            report.dependencies.insert(
+                pkg.name.clone(),
+                PackageLicense {
+                    version: pkg.version.clone(),
+                    license,
+                    dev,
+                },
+            );
+        }
+        report.violations.sort();
+        report
     }
 
-    // Process dev packages
-    for pkg in &lock.packages_dev {
-        let license_info = pkg
-            .license
-            .as_ref()
-            .map_or_else(|| "Unknown".to_string(), |licenses| licenses.join(", "));
+    /// Licenses by how many packages use them, most common first
+    pub fn summary_by_count(&self) -> Vec<(&str, usize)> {
+        let mut summary: Vec<(&str, usize)> = self
+            .summary
+            .iter()
+            .map(|(license, count)| (license.as_str(), *count))
+            .collect();
+        summary.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        summary
+    }
+}
 
-        table_rows.push((pkg.name.clone(), pkg.version.clone(), license_info));
+fn license_label(license: &[String]) -> String {
+    if license.is_empty() {
+        UNKNOWN_LICENSE.to_string()
+    } else {
+        license.join(", ")
     }
+}
 
+/// Show licenses of all dependencies and check them against the project's license policy.
+/// Returns whether every package is allowed.
+/// # Errors
+/// Returns an error if the lock file cannot be read or the format is unknown
+pub async fn show_dependency_licenses(
+    args: &LicensesArgs,
+    working_dir: &Path,
+    quiet: bool,
+) -> Result<bool> {
+    let json = match args.format.as_str() {
+        "text" => false,
+        "json" => true,
+        other => return Err(anyhow!("Unknown format: {other}. Use: text or json")),
+    };
+    let quiet = quiet || json;
     if !quiet {
+        print_info("📜 Reading license information from lock file...");
+    }
+
+    let lock_path = working_dir.join("composer.lock");
+
+    if !lock_path.exists() {
+        print_error("❌ No composer.lock found. Run 'lectern install' first.");
+        return Ok(true);
+    }
+
+    let lock = read_lock(&lock_path)?;
+    let policy = read_composer_json(&manifest_path(working_dir))
+        .map(|composer| LicensePolicy::from_composer(&composer))
+        .unwrap_or_default();
+    let report = LicenseReport::from_lock(&lock, !args.no_dev, &policy);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else if report.dependencies.is_empty() {
+        if !quiet {
+            print_info("📦 No packages installed.");
+        }
+    } else if !quiet {
         println!("\n📜 Package Licenses:");
         println!("{:<40} {:<15} License", "Package", "Version");
         println!("{}", "-".repeat(80));
 
-        table_rows.sort_by(|a, b| a.0.cmp(&b.0));
-        let package_count = table_rows.len();
+        for (name, pkg) in &report.dependencies {
+            println!(
+                "{name:<40} {:<15} {}",
+                pkg.version,
+                license_label(&pkg.license)
+            );
+        }
 
-        for (name, version, license) in table_rows {
-            println!("{name:<40} {version:<15} {license}");
+        println!("\n📊 Summary by license:");
+        for (license, count) in report.summary_by_count() {
+            println!("  {license:<38} {count}");
         }
 
-        print_success(&format!("📊 Listed licenses for {package_count} packages"));
+        print_success(&format!(
+            "📊 Listed licenses for {} packages",
+            report.dependencies.len()
+        ));
     }
 
-    Ok(())
+    if policy.is_empty() {
+        return Ok(true);
+    }
+    for name in &report.violations {
+        let license = license_label(&report.dependencies[name].license);
+        print_error(&format!(
+            "❌ {name} is licensed under {license}, which the license policy does not allow"
+        ));
+    }
+    if report.violations.is_empty() && !quiet {
+        print_success("✅ All licenses are allowed by the license policy");
+    }
+    Ok(report.violations.is_empty())
 }
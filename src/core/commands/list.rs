@@ -0,0 +1,68 @@
+use crate::cli::Cli;
+use crate::io::{manifest_path, read_composer_json};
+use crate::models::model::ComposerJson;
+use anyhow::Result;
+use clap::CommandFactory;
+use std::path::Path;
+
+/// Built-in commands with their one-line descriptions, in the order `--help` shows them
+pub fn command_entries() -> Vec<(String, String)> {
+    Cli::command()
+        .get_subcommands()
+        .filter(|command| !command.is_hide_set())
+        .map(|command| {
+            let about = command.get_about().map(ToString::to_string);
+            (command.get_name().to_string(), about.unwrap_or_default())
+        })
+        .collect()
+}
+
+/// The scripts of composer.json with their `scripts-descriptions`, or Composer's default
+/// description for scripts without one
+pub fn script_entries(composer: &ComposerJson) -> Vec<(String, String)> {
+    let descriptions = composer.scripts_descriptions.as_ref();
+    composer
+        .scripts
+        .iter()
+        .flatten()
+        .map(|(name, _)| {
+            let description = descriptions
+                .and_then(|descriptions| descriptions.get(name))
+                .cloned()
+                .unwrap_or_else(|| format!("Runs the {name} script as defined in composer.json"));
+            (name.clone(), description)
+        })
+        .collect()
+}
+
+fn print_section(title: &str, entries: &[(String, String)], width: usize) {
+    println!("\n{title}:");
+    for (name, description) in entries {
+        println!("  {name:<width$}  {description}");
+    }
+}
+
+/// List the built-in commands, then the scripts of the project, which run as commands too
+/// # Errors
+/// Returns an error if the project's composer.json exists but cannot be read
+pub fn list(working_dir: &Path) -> Result<()> {
+    let commands = command_entries();
+    let composer_path = manifest_path(working_dir);
+    let scripts = if composer_path.exists() {
+        script_entries(&read_composer_json(&composer_path)?)
+    } else {
+        Vec::new()
+    };
+
+    let width = commands
+        .iter()
+        .chain(&scripts)
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(0);
+    print_section("Available commands", &commands, width);
+    if !scripts.is_empty() {
+        print_section("Available scripts", &scripts, width);
+    }
+    Ok(())
+}
@@ -1,31 +1,79 @@
 // Command modules
+pub mod audit;
 pub mod browse;
+pub mod bump;
+pub mod check_lock;
+pub mod clean;
 pub mod clear_cache;
+pub mod convert;
 pub mod depends;
 pub mod diagnose;
+pub mod docker;
+pub mod exec;
+pub mod fingerprint;
 pub mod funding;
+pub mod global;
+pub mod graph;
 pub mod licenses;
+pub mod list;
 pub mod outdated;
+pub mod php_compat;
 pub mod prohibits;
 pub mod project;
+pub mod reinstall;
+pub mod require;
+pub mod rollback;
+pub mod sbom;
 pub mod script;
 pub mod search;
+pub mod self_update;
+pub mod shell;
 pub mod show;
 pub mod status;
 pub mod suggests;
+pub mod sync;
+pub mod third_party;
+pub mod workspace;
 
 // Re-export command functions
+pub use audit::{audit_lock, print_audit_summary};
 pub use browse::browse_package;
+pub use bump::bump;
+pub use check_lock::check_lock;
+pub use clean::clean;
 pub use clear_cache::clear_cache;
+pub use convert::convert;
 pub use depends::show_depends;
 pub use diagnose::diagnose;
+pub use docker::docker_cache_config;
+pub use exec::exec;
+pub use fingerprint::fingerprint;
 pub use funding::show_funding;
+pub use global::{ensure_global_project, finish_global_change, global_dir};
+pub use graph::export_graph;
 pub use licenses::show_dependency_licenses;
-pub use outdated::check_outdated_packages;
+pub use list::list;
+pub use outdated::{check_outdated_packages, check_outdated_workspace, outdated_packages};
+pub use php_compat::php_compat;
 pub use prohibits::show_prohibits;
 pub use project::create_project;
-pub use script::run_script;
+pub use reinstall::reinstall;
+pub use require::{
+    UpdateWith, add_requirement, remove_requirement, resolve_prospective, resolve_required,
+    resolve_requirement,
+};
+pub use rollback::rollback;
+pub use sbom::sbom;
+pub use script::{run_custom_command, run_script};
 pub use search::search_packages;
-pub use show::show_package_details;
+pub use self_update::self_update;
+pub use shell::run_shell;
+pub use show::{
+    show_available_versions, show_dependency_tree, show_package_details, show_package_full,
+    show_platform, show_remote_version,
+};
 pub use status::show_dependency_status;
 pub use suggests::show_suggests;
+pub use sync::sync;
+pub use third_party::third_party_file;
+pub use workspace::{workspace_install, workspace_status};
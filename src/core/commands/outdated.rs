@@ -1,17 +1,157 @@
+use crate::ignore_list::IgnoreList;
 use crate::io::read_lock;
+use crate::models::model::{Abandoned, Lock};
 use crate::resolver::fetch_packagist_versions_bulk;
-use crate::utils::is_prerelease_version;
-use crate::utils::{print_error, print_info, print_success};
-use anyhow::Result;
-use semver::Version;
+use crate::resolver::packagist::P2Version;
+use crate::resolver::version::ComposerVersion;
+use crate::utils::{print_info, print_success};
+use crate::workspace::{member_label, workspace_members};
+use anyhow::{Result, anyhow};
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::Path;
 
-/// Check for outdated packages with incremental updates
+/// A locked package with a newer stable release available
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutdatedPackage {
+    pub name: String,
+    pub current: String,
+    pub latest: String,
+    pub description: String,
+}
+
+/// Packages in a lock file that can be checked against Packagist.
+/// Platform packages (php, ext-*, lib-*, etc.) can't be outdated.
+pub fn checkable_package_names(lock: &Lock) -> Vec<String> {
+    lock.packages
+        .iter()
+        .chain(lock.packages_dev.iter())
+        .filter(|pkg| {
+            !(pkg.name.starts_with("php")
+                || pkg.name.starts_with("ext-")
+                || pkg.name.starts_with("lib-")
+                || pkg.name == "hhvm")
+        })
+        .map(|pkg| pkg.name.clone())
+        .collect()
+}
+
+/// The newest stable release in a version list
+pub fn latest_stable_version(versions: &[P2Version]) -> Option<&P2Version> {
+    versions
+        .iter()
+        .filter_map(|v| ComposerVersion::parse(&v.version).map(|parsed| (parsed, v)))
+        .filter(|(parsed, _)| parsed.is_stable())
+        .max_by(|a, b| a.0.cmp(&b.0))
+        .map(|(_, v)| v)
+}
+
+/// Whether `current` is older than the stable release `latest`
+pub fn is_behind(current: &str, latest: &str) -> bool {
+    match (
+        ComposerVersion::parse(current),
+        ComposerVersion::parse(latest),
+    ) {
+        (Some(current), Some(latest)) => latest > current,
+        _ => false,
+    }
+}
+
+/// Compare locked packages against fetched version metadata
+pub fn find_outdated(
+    lock: &Lock,
+    versions_map: &BTreeMap<String, Vec<P2Version>>,
+) -> Vec<OutdatedPackage> {
+    let mut outdated = Vec::new();
+    for package_name in checkable_package_names(lock) {
+        // Look in both regular and dev packages
+        let locked_pkg = lock
+            .packages
+            .iter()
+            .find(|p| p.name == package_name)
+            .or_else(|| lock.packages_dev.iter().find(|p| p.name == package_name));
+
+        if let Some(locked_pkg) = locked_pkg
+            && let Some(versions) = versions_map.get(&package_name)
+            && let Some(latest) = latest_stable_version(versions)
+            && is_behind(&locked_pkg.version, &latest.version)
+        {
+            let description = latest
+                .other
+                .get("description")
+                .and_then(|d| d.as_str())
+                .unwrap_or("")
+                .to_string();
+
+            outdated.push(OutdatedPackage {
+                name: package_name,
+                current: locked_pkg.version.clone(),
+                latest: latest.version.clone(),
+                description,
+            });
+        }
+    }
+    outdated
+}
+
+/// Drop the ignored packages from an outdated report, returning how many were dropped
+pub fn remove_ignored(outdated: &mut Vec<OutdatedPackage>, ignore: &IgnoreList) -> usize {
+    let before = outdated.len();
+    outdated.retain(|pkg| !ignore.matches(&pkg.name));
+    before - outdated.len()
+}
+
+/// Note how many outdated packages the ignore list hid
+fn print_ignored(ignored: usize) {
+    if ignored > 0 {
+        print_info(&format!(
+            "🙈 {ignored} outdated package(s) ignored by extra.lectern.ignore or --ignore"
+        ));
+    }
+}
+
+/// Locked packages that are abandoned, by the latest metadata or else as recorded in the
+/// lock, with the replacement their maintainers suggest
+pub fn find_abandoned(
+    lock: &Lock,
+    versions_map: &BTreeMap<String, Vec<P2Version>>,
+) -> Vec<(String, Abandoned)> {
+    lock.packages
+        .iter()
+        .chain(&lock.packages_dev)
+        .filter_map(|pkg| {
+            let abandonment = match versions_map.get(&pkg.name).and_then(|v| v.first()) {
+                Some(latest) => latest.abandonment(),
+                None => pkg.abandonment(),
+            };
+            Some((pkg.name.clone(), abandonment?))
+        })
+        .collect::<BTreeMap<_, _>>()
+        .into_iter()
+        .collect()
+}
+
+/// Locked packages with a newer stable release on Packagist
+/// # Errors
+/// Returns an error if package metadata cannot be fetched
+pub async fn outdated_packages(lock: &Lock) -> Result<Vec<OutdatedPackage>> {
+    let package_names = checkable_package_names(lock);
+    if package_names.is_empty() {
+        return Ok(Vec::new());
+    }
+    // Use optimized bulk P2 API endpoint for much better performance
+    // This fetches only version metadata, not full package info
+    let versions_map = fetch_packagist_versions_bulk(&package_names).await?;
+    Ok(find_outdated(lock, &versions_map))
+}
+
+/// Check for outdated packages with incremental updates, leaving out ignored ones
 /// # Errors
 /// Returns an error if the lock file cannot be read or packages cannot be fetched
-/// # Panics
-/// May panic if version parsing fails unexpectedly
-pub async fn check_outdated_packages(working_dir: &Path, quiet: bool) -> Result<()> {
+pub async fn check_outdated_packages(
+    working_dir: &Path,
+    ignore: &IgnoreList,
+    quiet: bool,
+) -> Result<()> {
     if !quiet {
         print_info("🔍 Checking for outdated packages...");
     }
@@ -19,8 +159,9 @@ pub async fn check_outdated_packages(working_dir: &Path, quiet: bool) -> Result<
     let lock_path = working_dir.join("composer.lock");
 
     if !lock_path.exists() {
-        print_error("❌ No composer.lock found. Run 'lectern install' first.");
-        return Ok(());
+        return Err(anyhow!(
+            "No composer.lock found. Run 'lectern install' first."
+        ));
     }
 
     let lock = read_lock(&lock_path)?;
@@ -33,134 +174,162 @@ pub async fn check_outdated_packages(working_dir: &Path, quiet: bool) -> Result<
         return Ok(());
     }
 
-    // Collect only non-platform packages to check
-    let mut package_names: Vec<String> = Vec::new();
-    for pkg in lock.packages.iter().chain(lock.packages_dev.iter()) {
-        // Skip platform packages (php, ext-*, lib-*, etc.) - they can't be outdated
-        if pkg.name.starts_with("php")
-            || pkg.name.starts_with("ext-")
-            || pkg.name.starts_with("lib-")
-            || pkg.name == "hhvm"
-        {
-            continue;
+    let versions_map = fetch_packagist_versions_bulk(&checkable_package_names(&lock)).await?;
+    let mut outdated = find_outdated(&lock, &versions_map);
+    let ignored = remove_ignored(&mut outdated, ignore);
+    let abandoned = find_abandoned(&lock, &versions_map);
+    if !quiet && !abandoned.is_empty() {
+        println!("\n🏚️  Abandoned Packages ({} found):", abandoned.len());
+        for (name, abandonment) in &abandoned {
+            println!("  {name:<30} {abandonment}");
         }
-        package_names.push(pkg.name.clone());
     }
 
-    if package_names.is_empty() {
+    if !quiet {
+        print_ignored(ignored);
+    }
+    if outdated.is_empty() {
         if !quiet {
             print_success("✅ All packages are up to date!");
         }
-        return Ok(());
+    } else if !quiet {
+        println!("\n📊 Outdated Packages ({} found):", outdated.len());
+        println!(
+            "{:<30} {:<15} {:<15} Description",
+            "Package", "Current", "Latest"
+        );
+        println!("{}", "-".repeat(100));
+
+        for pkg in outdated {
+            let short_desc = if pkg.description.len() > 30 {
+                format!("{}...", &pkg.description[..27])
+            } else {
+                pkg.description
+            };
+            println!(
+                "{:<30} {:<15} {:<15} {short_desc}",
+                pkg.name, pkg.current, pkg.latest
+            );
+        }
+
+        println!("\nRun 'lectern update' to update packages.");
     }
 
-    // Use optimized bulk P2 API endpoint for much better performance
-    // This fetches only version metadata, not full package info
-    let versions_map = fetch_packagist_versions_bulk(&package_names).await?;
+    Ok(())
+}
 
-    let mut outdated_count = 0;
-    let mut table_rows = Vec::new();
+/// Check outdated packages across every workspace member with a single metadata fetch,
+/// leaving out ignored ones
+/// # Errors
+/// Returns an error if the workspace or a lock file cannot be read, or packages cannot be fetched
+pub async fn check_outdated_workspace(
+    working_dir: &Path,
+    ignore: &IgnoreList,
+    quiet: bool,
+) -> Result<()> {
+    if !quiet {
+        print_info("🔍 Checking for outdated packages across the workspace...");
+    }
 
-    for package_name in package_names.clone() {
-        // Look in both regular and dev packages
-        let locked_pkg = lock
-            .packages
-            .iter()
-            .find(|p| p.name == package_name)
-            .or_else(|| lock.packages_dev.iter().find(|p| p.name == package_name));
+    let mut members = Vec::new();
+    for member in workspace_members(working_dir)? {
+        let lock_path = member.join("composer.lock");
+        if lock_path.exists() {
+            members.push((member_label(working_dir, &member), read_lock(&lock_path)?));
+        } else if !quiet {
+            print_info(&format!(
+                "⏭️  Skipping {} (no composer.lock)",
+                member_label(working_dir, &member)
+            ));
+        }
+    }
+
+    if members.is_empty() {
+        return Err(anyhow!(
+            "No workspace member has a composer.lock. Run 'lectern install' first."
+        ));
+    }
 
-        if let Some(locked_pkg) = locked_pkg {
-            if let Some(versions) = versions_map.get(&package_name) {
-                // Find the latest stable version with early termination
-                let mut latest_version = None;
-                let mut latest_parsed: Option<Version> = None;
-
-                // Parse the current version
-                let current_version_str = locked_pkg.version.trim_start_matches('v');
-                let current_parsed = Version::parse(current_version_str).ok();
-
-                // Sort versions in descending order and stop at first stable version
-                let mut version_list: Vec<_> = versions.iter().collect();
-                version_list.sort_by(|a, b| {
-                    let a_clean = a.version.trim_start_matches('v');
-                    let b_clean = b.version.trim_start_matches('v');
-                    
-                    match (Version::parse(a_clean), Version::parse(b_clean)) {
-                        (Ok(va), Ok(vb)) => vb.cmp(&va), // Descending order
-                        _ => std::cmp::Ordering::Equal,
-                    }
-                });
-
-                // Find the latest stable version (early termination)
-                for version_data in version_list {
-                    let version_str = &version_data.version;
-                    
-                    // Skip dev versions and pre-releases for "latest" comparison
-                    if is_prerelease_version(version_str.as_str()) {
-                        continue;
-                    }
-
-                    // Try to parse the version
-                    let clean_version = version_str.trim_start_matches('v');
-                    if let Ok(parsed_version) = Version::parse(clean_version) {
-                        // Since we're sorted, this is the latest stable version
-                        latest_parsed = Some(parsed_version);
-                        latest_version = Some(version_str.clone());
-                        break; // Early termination - found latest stable
-                    }
-                }
-
-                // Check if the latest version is newer than current
-                if let (Some(current), Some(latest_ver), Some(latest_str)) =
-                    (current_parsed, latest_parsed, latest_version)
-                {
-                    if latest_ver > current {
-                        outdated_count += 1;
-                        
-                        // Get description from version data if available
-                        let description = versions
-                            .iter()
-                            .find(|v| v.version == latest_str)
-                            .and_then(|v| v.other.get("description"))
-                            .and_then(|d| d.as_str())
-                            .unwrap_or("")
-                            .to_string();
-                        
-                        table_rows.push((
-                            package_name.clone(),
-                            locked_pkg.version.clone(),
-                            latest_str,
-                            description,
-                        ));
-                    }
-                }
+    // One concurrent fetch over the union of every member's packages
+    let package_names: Vec<String> = members
+        .iter()
+        .flat_map(|(_, lock)| checkable_package_names(lock))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let versions_map = fetch_packagist_versions_bulk(&package_names).await?;
+
+    // package -> (latest, member -> locked version)
+    let mut matrix: BTreeMap<String, (String, BTreeMap<String, String>)> = BTreeMap::new();
+    let mut lagging_members = BTreeSet::new();
+    let mut ignored = BTreeSet::new();
+    for (label, lock) in &members {
+        for pkg in find_outdated(lock, &versions_map) {
+            if ignore.matches(&pkg.name) {
+                ignored.insert(pkg.name);
+                continue;
             }
+            let row = matrix
+                .entry(pkg.name)
+                .or_insert_with(|| (pkg.latest, BTreeMap::new()));
+            row.1.insert(label.clone(), pkg.current);
+            lagging_members.insert(label.clone());
         }
     }
 
-    if outdated_count == 0 {
+    if !quiet {
+        print_ignored(ignored.len());
+    }
+    if matrix.is_empty() {
         if !quiet {
-            print_success("✅ All packages are up to date!");
+            print_success("✅ All workspace members are up to date!");
         }
-    } else if !quiet {
-        println!("\n📊 Outdated Packages ({outdated_count} found):");
-        println!(
-            "{:<30} {:<15} {:<15} Description",
-            "Package", "Current", "Latest"
-        );
-        println!("{}", "-".repeat(100));
+        return Ok(());
+    }
+    if quiet {
+        return Ok(());
+    }
 
-        for (name, current, latest, desc) in table_rows {
-            let short_desc = if desc.len() > 30 {
-                format!("{}...", &desc[..27])
-            } else {
-                desc
+    // Show every member's locked version, marking the ones behind latest
+    let labels: Vec<&String> = members.iter().map(|(label, _)| label).collect();
+    let width = |s: &str| s.len().max(12);
+    print!("\n{:<30}", "Package");
+    for label in &labels {
+        print!(" {:<w$}", label, w = width(label));
+    }
+    println!(" Latest");
+    println!(
+        "{}",
+        "-".repeat(30 + labels.iter().map(|l| width(l) + 1).sum::<usize>() + 16)
+    );
+
+    for (name, (latest, behind)) in &matrix {
+        print!("{name:<30}");
+        for (label, lock) in &members {
+            let cell = match behind.get(label) {
+                Some(version) => format!("{version} *"),
+                None => lock
+                    .packages
+                    .iter()
+                    .chain(lock.packages_dev.iter())
+                    .find(|p| &p.name == name)
+                    .map_or_else(|| "-".to_string(), |p| p.version.clone()),
             };
-            println!("{name:<30} {current:<15} {latest:<15} {short_desc}");
+            print!(" {:<w$}", cell, w = width(label));
         }
+        println!(" {latest}");
+    }
 
-        println!("\nRun 'lectern update' to update packages.");
+    println!(
+        "\n📊 {} outdated package(s), {} of {} member(s) lagging (* = behind latest)",
+        matrix.len(),
+        lagging_members.len(),
+        members.len()
+    );
+    for (name, (_, behind)) in &matrix {
+        println!("  {name}: {} member(s) behind", behind.len());
     }
+    println!("\nRun 'lectern update' in each lagging member to update packages.");
 
     Ok(())
 }
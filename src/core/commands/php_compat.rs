@@ -0,0 +1,243 @@
+use crate::io::{manifest_path, read_composer_json, read_lock};
+use crate::models::model::{ComposerJson, Lock, LockedPackage};
+use crate::resolver::packagist::P2Version;
+use crate::resolver::version::ComposerVersion;
+use crate::resolver::{fetch_packagist_versions_bulk, is_platform_dependency, satisfies};
+use crate::utils::{print_error, print_info, print_step, print_success};
+use anyhow::{Result, anyhow};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::Path;
+
+/// A locked package whose `php` requirement excludes the target version
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhpBlocker {
+    pub name: String,
+    pub version: String,
+    pub constraint: String,
+    /// Shortest path of requirements from the root package down to this one
+    pub chain: Vec<String>,
+    /// Lowest newer stable release whose `php` requirement allows the target
+    pub fix: Option<String>,
+    /// Requirements (`package`, `constraint`) that rule out `fix`
+    pub held_back_by: Vec<(String, String)>,
+}
+
+fn locked_packages(lock: &Lock) -> impl Iterator<Item = &LockedPackage> {
+    lock.packages.iter().chain(lock.packages_dev.iter())
+}
+
+fn requires_php(requires: Option<&BTreeMap<String, String>>, target: &str) -> Option<bool> {
+    satisfies(requires?.get("php")?, target)
+}
+
+/// Shortest chain of requirements from the root to `name`
+fn requirement_chain(composer: &ComposerJson, lock: &Lock, name: &str) -> Vec<String> {
+    let root = composer.name.clone().unwrap_or_else(|| "root".to_string());
+    let requires: HashMap<&str, Vec<&str>> = locked_packages(lock)
+        .map(|pkg| {
+            let deps = pkg
+                .require
+                .iter()
+                .flat_map(|r| r.keys().map(String::as_str))
+                .collect();
+            (pkg.name.as_str(), deps)
+        })
+        .collect();
+
+    let mut parent: HashMap<&str, &str> = HashMap::new();
+    let mut queue: VecDeque<&str> = composer
+        .require
+        .keys()
+        .chain(composer.require_dev.keys())
+        .map(String::as_str)
+        .filter(|dep| !is_platform_dependency(dep))
+        .collect();
+    for dep in &queue {
+        parent.insert(dep, "");
+    }
+    while let Some(current) = queue.pop_front() {
+        if current == name {
+            let mut chain = vec![current.to_string()];
+            let mut node = current;
+            while let Some(&up) = parent.get(node).filter(|up| !up.is_empty()) {
+                chain.push(up.to_string());
+                node = up;
+            }
+            chain.push(root);
+            chain.reverse();
+            return chain;
+        }
+        for &dep in requires.get(current).into_iter().flatten() {
+            if !is_platform_dependency(dep) && !parent.contains_key(dep) {
+                parent.insert(dep, current);
+                queue.push_back(dep);
+            }
+        }
+    }
+    vec![root, name.to_string()]
+}
+
+/// Locked packages that cannot be installed on the target PHP version
+pub fn find_php_blockers(composer: &ComposerJson, lock: &Lock, target: &str) -> Vec<PhpBlocker> {
+    locked_packages(lock)
+        .filter(|pkg| requires_php(pkg.require.as_ref(), target) == Some(false))
+        .map(|pkg| PhpBlocker {
+            name: pkg.name.clone(),
+            version: pkg.version.clone(),
+            constraint: pkg
+                .require
+                .as_ref()
+                .and_then(|r| r.get("php"))
+                .cloned()
+                .unwrap_or_default(),
+            chain: requirement_chain(composer, lock, &pkg.name),
+            fix: None,
+            held_back_by: Vec::new(),
+        })
+        .collect()
+}
+
+fn parse_release(version: &str) -> Option<ComposerVersion> {
+    ComposerVersion::parse(version).filter(ComposerVersion::is_stable)
+}
+
+/// The lowest stable release newer than `current` that supports the target PHP version
+pub fn minimal_supporting_version<'a>(
+    versions: &'a [P2Version],
+    current: &str,
+    target: &str,
+) -> Option<&'a P2Version> {
+    let current = parse_release(current);
+    versions
+        .iter()
+        .filter_map(|v| parse_release(&v.version).map(|parsed| (parsed, v)))
+        .filter(|(parsed, _)| current.as_ref().is_none_or(|current| parsed > current))
+        .filter(|(_, v)| requires_php(v.require.as_ref(), target) != Some(false))
+        .min_by(|a, b| a.0.cmp(&b.0))
+        .map(|(_, v)| v)
+}
+
+/// Fill in the fix version for each blocker and which requirements would hold it back
+pub fn resolve_fixes(
+    blockers: &mut [PhpBlocker],
+    composer: &ComposerJson,
+    lock: &Lock,
+    versions_map: &BTreeMap<String, Vec<P2Version>>,
+    target: &str,
+) {
+    let root = composer.name.clone().unwrap_or_else(|| "root".to_string());
+    for blocker in blockers {
+        let Some(fix) = versions_map
+            .get(&blocker.name)
+            .and_then(|versions| minimal_supporting_version(versions, &blocker.version, target))
+        else {
+            continue;
+        };
+        blocker.fix = Some(fix.version.clone());
+
+        let root_requires = composer.require.iter().chain(composer.require_dev.iter());
+        let requirers = root_requires
+            .filter(|(dep, _)| **dep == blocker.name)
+            .map(|(_, constraint)| (root.clone(), constraint.clone()))
+            .chain(locked_packages(lock).filter_map(|pkg| {
+                pkg.require
+                    .as_ref()?
+                    .get(&blocker.name)
+                    .map(|constraint| (pkg.name.clone(), constraint.clone()))
+            }));
+        blocker.held_back_by = requirers
+            .filter(|(_, constraint)| satisfies(constraint, &fix.version) == Some(false))
+            .collect();
+    }
+}
+
+/// Check whether the locked dependencies can move to a PHP version.
+/// Returns `Ok(false)` when something blocks the upgrade.
+/// # Errors
+/// Returns an error if composer.json or composer.lock cannot be read
+pub async fn php_compat(target: &str, working_dir: &Path) -> Result<bool> {
+    if satisfies("*", target).is_none() {
+        return Err(anyhow!("Invalid PHP version '{target}'"));
+    }
+    print_step(&format!("🔍 Checking compatibility with PHP {target}..."));
+
+    let composer = read_composer_json(&manifest_path(working_dir))?;
+    let lock_path = working_dir.join("composer.lock");
+    if !lock_path.exists() {
+        print_error("❌ No composer.lock found. Run 'lectern install' first.");
+        return Ok(false);
+    }
+    let lock = read_lock(&lock_path)?;
+
+    let root_allows = requires_php(Some(&composer.require), target) != Some(false);
+    if !root_allows {
+        print_error(&format!(
+            "❌ composer.json requires php {}, which excludes PHP {target}",
+            composer.require["php"]
+        ));
+    }
+
+    let mut blockers = find_php_blockers(&composer, &lock, target);
+    if blockers.is_empty() {
+        if root_allows {
+            print_success(&format!("✅ All locked packages support PHP {target}"));
+        }
+        return Ok(root_allows);
+    }
+
+    let names: Vec<String> = blockers.iter().map(|b| b.name.clone()).collect();
+    match fetch_packagist_versions_bulk(&names).await {
+        Ok(versions_map) => {
+            resolve_fixes(&mut blockers, &composer, &lock, &versions_map, target);
+        }
+        Err(e) => print_info(&format!("Could not look up newer releases: {e}")),
+    }
+
+    println!(
+        "\n⚠️  {} locked package(s) do not support PHP {target}:",
+        blockers.len()
+    );
+    for blocker in &blockers {
+        println!(
+            "  • {} {} requires php {}",
+            blocker.name, blocker.version, blocker.constraint
+        );
+        if blocker.chain.len() > 2 {
+            println!("    via {}", blocker.chain.join(" → "));
+        }
+        match &blocker.fix {
+            Some(fix) if blocker.held_back_by.is_empty() => {
+                println!("    ✓ {fix} supports PHP {target}");
+            }
+            Some(fix) => {
+                let held: Vec<String> = blocker
+                    .held_back_by
+                    .iter()
+                    .map(|(name, constraint)| format!("{name} ({constraint})"))
+                    .collect();
+                println!(
+                    "    ✗ {fix} supports PHP {target} but is excluded by {}",
+                    held.join(", ")
+                );
+            }
+            None => println!("    ✗ no newer release supports PHP {target}"),
+        }
+    }
+
+    let path_exists = root_allows
+        && blockers
+            .iter()
+            .all(|b| b.fix.is_some() && b.held_back_by.is_empty());
+    println!();
+    if path_exists {
+        print_info(&format!(
+            "An upgrade path exists within the current constraints: run 'lectern update' on PHP {target}"
+        ));
+    } else {
+        print_info(
+            "No upgrade path within the current constraints; relax the requirements listed above",
+        );
+    }
+
+    Ok(false)
+}
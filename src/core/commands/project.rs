@@ -1,4 +1,5 @@
 use crate::cli::CreateProjectArgs;
+use crate::io::write_composer_json;
 use crate::utils::{print_info, print_step, print_success};
 use anyhow::{Result, anyhow};
 use std::collections::BTreeMap;
@@ -11,7 +12,7 @@ pub async fn create_project(args: &CreateProjectArgs, working_dir: &Path) -> Res
     let target_dir = if let Some(dir) = &args.directory {
         working_dir.join(dir)
     } else {
-        let pkg_name = args.package.split('/').last().unwrap_or(&args.package);
+        let pkg_name = args.package.split('/').next_back().unwrap_or(&args.package);
         working_dir.join(pkg_name)
     };
 
@@ -62,19 +63,24 @@ pub async fn create_project(args: &CreateProjectArgs, working_dir: &Path) -> Res
         repositories: None,
         config: None,
         scripts: None,
+        scripts_descriptions: None,
         extra: None,
         minimum_stability: None,
         prefer_stable: Some(true),
         bin: None,
     };
 
-    let composer_json = serde_json::to_string_pretty(&composer)?;
-    std::fs::write(target_dir.join("composer.json"), composer_json)?;
+    write_composer_json(&target_dir.join("composer.json"), &composer)?;
 
     print_success("✅ Project created successfully");
     print_info(&format!(
-        "Run 'cd {}' and 'lectern install' to set up dependencies",
-        target_dir.file_name().unwrap().to_string_lossy()
+        "Run 'cd {}' and 'lectern install{}' to set up dependencies",
+        target_dir.file_name().unwrap().to_string_lossy(),
+        if args.prefer_source {
+            " --prefer-source"
+        } else {
+            ""
+        }
     ));
 
     Ok(())
@@ -0,0 +1,91 @@
+use crate::cli::ReinstallArgs;
+use crate::installer::installer_links::{package_dir, remove_dir_all};
+use crate::installer::installer_utils::get_cached_package_path;
+use crate::installer::{InstallOptions, install_packages_with_options};
+use crate::io::read_lock;
+use crate::models::model::{Lock, LockedPackage};
+use crate::utils::{print_info, print_step, print_success};
+use crate::workspace::segment_matches;
+use anyhow::{Context, Result, anyhow};
+use std::path::Path;
+
+/// Locked packages matching the given names or `*` patterns (e.g. `symfony/*`)
+/// # Errors
+/// Returns an error if a name or pattern matches no locked package
+pub fn select_locked_packages<'a>(
+    lock: &'a Lock,
+    patterns: &[String],
+) -> Result<Vec<&'a LockedPackage>> {
+    let locked: Vec<&LockedPackage> = lock.packages.iter().chain(&lock.packages_dev).collect();
+    let mut selected: Vec<&LockedPackage> = Vec::new();
+    for pattern in patterns {
+        let pattern = pattern.to_lowercase();
+        let matches: Vec<&LockedPackage> = locked
+            .iter()
+            .copied()
+            .filter(|pkg| segment_matches(&pattern, &pkg.name.to_lowercase()))
+            .collect();
+        if matches.is_empty() {
+            return Err(anyhow!("{pattern} is not in composer.lock"));
+        }
+        for pkg in matches {
+            if !selected.iter().any(|s| s.name == pkg.name) {
+                selected.push(pkg);
+            }
+        }
+    }
+    Ok(selected)
+}
+
+/// Delete the selected packages from vendor and install them again from composer.lock
+/// # Errors
+/// Returns an error if composer.lock cannot be read, a package is not locked, or the
+/// install fails
+pub async fn reinstall(args: &ReinstallArgs, working_dir: &Path) -> Result<()> {
+    let lock_path = working_dir.join("composer.lock");
+    if !lock_path.exists() {
+        return Err(anyhow!(
+            "No composer.lock found. Run 'lectern install' first."
+        ));
+    }
+    let lock = read_lock(&lock_path)?;
+    let packages: Vec<LockedPackage> = select_locked_packages(&lock, &args.packages)?
+        .into_iter()
+        .cloned()
+        .collect();
+    print_step(&format!(
+        "♻️  Reinstalling {} package(s)...",
+        packages.len()
+    ));
+
+    let vendor = working_dir.join("vendor");
+    for pkg in &packages {
+        remove_dir_all(&package_dir(&vendor, &pkg.name))?;
+        if args.no_cache
+            && let Some(dist) = &pkg.dist
+        {
+            let archive = get_cached_package_path(&pkg.name, &pkg.version, &dist.url);
+            let mut dropped = false;
+            for path in [
+                archive.clone(),
+                archive.with_extension("tmp"),
+                archive.with_extension("validator"),
+            ] {
+                if path.exists() {
+                    std::fs::remove_file(&path).with_context(|| format!("remove {path:?}"))?;
+                    dropped = true;
+                }
+            }
+            if dropped {
+                print_info(&format!("🗑️  Dropped cached archive of {}", pkg.name));
+            }
+        }
+    }
+
+    let options = InstallOptions {
+        prefer_source: args.prefer_source,
+    };
+    install_packages_with_options(&packages, working_dir, options).await?;
+    print_success(&format!("✅ Reinstalled {} package(s)", packages.len()));
+    Ok(())
+}
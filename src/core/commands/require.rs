@@ -0,0 +1,286 @@
+use crate::commands::outdated::latest_stable_version;
+use crate::error::LecternError;
+use crate::io::parse_composer_json;
+use crate::models::model::{ComposerJson, Lock, LockedPackage};
+use crate::resolver::dependency_utils::{generate_content_hash, reachable};
+use crate::resolver::{
+    ResolveOptions, fetch_packagist_versions_cached, is_platform_dependency, search_packagist,
+    solve_with_options,
+};
+use crate::utils::print_warning;
+use anyhow::{Result, anyhow};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Most suggestions shown for a package that doesn't exist
+const MAX_SUGGESTIONS: usize = 5;
+
+/// Split `vendor/package:constraint` (or `vendor/package=constraint`) into its parts
+pub fn parse_package_spec(spec: &str) -> (String, Option<String>) {
+    match spec.find([':', '=']) {
+        Some(pos) => {
+            let constraint = spec[pos + 1..].trim();
+            (
+                spec[..pos].trim().to_string(),
+                (!constraint.is_empty()).then(|| constraint.to_string()),
+            )
+        }
+        None => (spec.trim().to_string(), None),
+    }
+}
+
+/// The constraint Composer recommends for a release: `^1.2` for `1.2.3`, and `^0.3.1`
+/// for `0.3.1` since minor 0.x releases may break compatibility
+pub fn recommended_constraint(version: &str) -> Option<String> {
+    let version = version.trim_start_matches('v');
+    let parts: Vec<&str> = version.split('.').collect();
+    if parts.len() < 3 || !parts.iter().all(|p| p.chars().all(|c| c.is_ascii_digit())) {
+        return None;
+    }
+    Some(if parts[0] == "0" {
+        format!("^{}.{}.{}", parts[0], parts[1], parts[2])
+    } else {
+        format!("^{}.{}", parts[0], parts[1])
+    })
+}
+
+async fn not_found_error(name: &str) -> anyhow::Error {
+    let suggestions: Vec<String> = search_packagist(&[name.to_string()])
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|result| result.name)
+        .filter(|candidate| candidate != name)
+        .take(MAX_SUGGESTIONS)
+        .collect();
+    let hint = if suggestions.is_empty() {
+        " on Packagist".to_string()
+    } else {
+        format!(
+            " on Packagist. Did you mean one of these?\n    {}",
+            suggestions.join("\n    ")
+        )
+    };
+    LecternError::PackageNotFound {
+        name: name.to_string(),
+        hint,
+    }
+    .into()
+}
+
+/// Check a `require` argument against Packagist and fill in the constraint when none was
+/// given. Packages that only exist in custom repositories can't be checked, so with
+/// `custom_repositories` an unknown package gets a warning and `*` instead of an error.
+/// # Errors
+/// Returns an error if the package does not exist on Packagist
+pub async fn resolve_requirement(
+    spec: &str,
+    custom_repositories: bool,
+) -> Result<(String, String)> {
+    let (name, constraint) = parse_package_spec(spec);
+    if is_platform_dependency(&name) {
+        return Ok((name, constraint.unwrap_or_else(|| "*".to_string())));
+    }
+    if !name.contains('/') {
+        return Err(anyhow!(
+            "Invalid package name '{name}', expected vendor/package"
+        ));
+    }
+
+    let versions = match fetch_packagist_versions_cached(&name).await {
+        Ok(versions) => versions,
+        Err(e) => {
+            let missing = e
+                .downcast_ref::<reqwest::Error>()
+                .and_then(reqwest::Error::status)
+                .is_some_and(|status| status == reqwest::StatusCode::NOT_FOUND);
+            if !missing {
+                print_warning(&format!("Could not check {name} on Packagist: {e}"));
+                return Ok((name, constraint.unwrap_or_else(|| "*".to_string())));
+            }
+            Vec::new()
+        }
+    };
+
+    if versions.is_empty() {
+        if custom_repositories {
+            print_warning(&format!(
+                "{name} is not on Packagist; assuming it comes from a custom repository"
+            ));
+            return Ok((name, constraint.unwrap_or_else(|| "*".to_string())));
+        }
+        return Err(not_found_error(&name).await);
+    }
+
+    if let Some(constraint) = constraint {
+        return Ok((name, constraint));
+    }
+    let recommended = latest_stable_version(&versions)
+        .and_then(|latest| recommended_constraint(&latest.version))
+        .unwrap_or_else(|| {
+            print_warning(&format!("{name} has no stable release; using *"));
+            "*".to_string()
+        });
+    Ok((name, recommended))
+}
+
+/// Resolve a composer.json that hasn't been written yet. Returns the contents to save
+/// together with the matching lock, so nothing touches disk unless resolution succeeds.
+/// # Errors
+/// Returns an error if the requirements cannot be resolved
+pub async fn resolve_prospective(composer: &ComposerJson) -> Result<(String, Lock)> {
+    resolve_prospective_with(composer, &ResolveOptions::default()).await
+}
+
+/// Like `resolve_prospective`, narrowed by `options`
+/// # Errors
+/// Returns an error if the requirements cannot be resolved
+pub async fn resolve_prospective_with(
+    composer: &ComposerJson,
+    options: &ResolveOptions,
+) -> Result<(String, Lock)> {
+    let contents = serde_json::to_string_pretty(composer)?;
+    // Resolve with `${VAR}` placeholders expanded, but save them as written
+    let mut lock = solve_with_options(&parse_composer_json(&contents)?, options).await?;
+    lock.content_hash = generate_content_hash(&contents);
+    Ok((contents, lock))
+}
+
+/// Which locked packages `require` may move to another version besides the required ones
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UpdateWith {
+    /// Only the required packages; everything else stays at its locked version
+    #[default]
+    Nothing,
+    /// The dependencies of the required packages, except root requirements (`-w`)
+    Dependencies,
+    /// The dependencies of the required packages, root requirements included (`-W`)
+    AllDependencies,
+}
+
+impl UpdateWith {
+    /// What to suggest when the required packages don't fit the locked ones
+    pub fn hint(self) -> Option<&'static str> {
+        match self {
+            Self::Nothing => Some(
+                "Run with -w to let their dependencies update too, or -W to also update root requirements",
+            ),
+            Self::Dependencies => Some("Run with -W to also update root requirements"),
+            Self::AllDependencies => None,
+        }
+    }
+}
+
+/// The locked versions to keep while adding `required`: every package in `lock` except
+/// the required ones and, with `update_with`, the packages they pull in according to
+/// `trial`, a resolution of the new composer.json that keeps nothing
+pub fn held_for_require(
+    composer: &ComposerJson,
+    lock: &Lock,
+    trial: Option<&Lock>,
+    required: &[String],
+    update_with: UpdateWith,
+) -> BTreeMap<String, String> {
+    let mut free: BTreeSet<String> = required.iter().map(|name| name.to_lowercase()).collect();
+    if let Some(trial) = trial.filter(|_| update_with != UpdateWith::Nothing) {
+        let is_root = |name: &str| {
+            !free.contains(&name.to_lowercase())
+                && (find_key(&composer.require, name).is_some()
+                    || find_key(&composer.require_dev, name).is_some())
+        };
+        // With -w, root requirements and what only they reach stay where they are
+        let candidates: Vec<LockedPackage> = trial
+            .packages
+            .iter()
+            .chain(&trial.packages_dev)
+            .filter(|package| update_with == UpdateWith::AllDependencies || !is_root(&package.name))
+            .cloned()
+            .collect();
+        let reached = reachable(required, &candidates);
+        free.extend(
+            candidates
+                .iter()
+                .zip(reached)
+                .filter(|(_, reached)| *reached)
+                .map(|(package, _)| package.name.to_lowercase()),
+        );
+    }
+    lock.packages
+        .iter()
+        .chain(&lock.packages_dev)
+        .filter(|package| !free.contains(&package.name.to_lowercase()))
+        .map(|package| (package.name.to_lowercase(), package.version.clone()))
+        .collect()
+}
+
+/// Resolve composer.json after `required` were added to it, keeping the packages of the
+/// current `lock` at their versions except for what `update_with` lets move. Without a
+/// lock, everything is resolved afresh.
+/// # Errors
+/// Returns an error if the requirements cannot be resolved with the packages held
+pub async fn resolve_required(
+    composer: &ComposerJson,
+    lock: Option<&Lock>,
+    required: &[String],
+    update_with: UpdateWith,
+) -> Result<(String, Lock)> {
+    let Some(lock) = lock else {
+        return resolve_prospective(composer).await;
+    };
+    let trial = match update_with {
+        UpdateWith::Nothing => None,
+        _ => Some(resolve_prospective(composer).await?.1),
+    };
+    let options = ResolveOptions {
+        locked: held_for_require(composer, lock, trial.as_ref(), required, update_with),
+        ..ResolveOptions::default()
+    };
+    resolve_prospective_with(composer, &options).await
+}
+
+fn find_key(section: &BTreeMap<String, String>, name: &str) -> Option<String> {
+    section
+        .keys()
+        .find(|key| key.eq_ignore_ascii_case(name))
+        .cloned()
+}
+
+/// Add a requirement to `require` (or `require-dev` with `dev`), taking it out of the other
+/// section so it is never listed twice. Returns the constraint it had there, if any.
+pub fn add_requirement(
+    composer: &mut ComposerJson,
+    name: &str,
+    constraint: &str,
+    dev: bool,
+) -> Option<String> {
+    let (target, other) = if dev {
+        (&mut composer.require_dev, &mut composer.require)
+    } else {
+        (&mut composer.require, &mut composer.require_dev)
+    };
+    let moved = find_key(other, name).and_then(|key| other.remove(&key));
+    if let Some(existing) = find_key(target, name) {
+        target.remove(&existing);
+    }
+    target.insert(name.to_string(), constraint.to_string());
+    moved
+}
+
+/// Remove a requirement from whichever section lists it, or only from `require-dev` with
+/// `dev_only`. Returns the sections it was removed from.
+pub fn remove_requirement(
+    composer: &mut ComposerJson,
+    name: &str,
+    dev_only: bool,
+) -> Vec<&'static str> {
+    let mut sections = vec![("require-dev", &mut composer.require_dev)];
+    if !dev_only {
+        sections.insert(0, ("require", &mut composer.require));
+    }
+    sections
+        .into_iter()
+        .filter_map(|(label, section)| {
+            let key = find_key(section, name)?;
+            section.remove(&key).map(|_| label)
+        })
+        .collect()
+}
@@ -0,0 +1,79 @@
+use crate::core::journal::{EntryStatus, discard_entry, list_entries};
+use crate::installer::install_packages;
+use crate::models::model::{Lock, LockedPackage};
+use crate::utils::{print_info, print_step, print_success};
+use anyhow::{Context, Result};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+fn locked_names(lock: Option<&Lock>) -> BTreeSet<String> {
+    lock.map(|l| {
+        l.packages
+            .iter()
+            .chain(l.packages_dev.iter())
+            .map(|p| p.name.clone())
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+/// Undo the most recent journaled operation and re-sync vendor with the restored lock
+/// # Errors
+/// Returns an error if the journal cannot be read or files cannot be restored
+pub async fn rollback(working_dir: &Path) -> Result<()> {
+    let entries = list_entries(working_dir)?;
+    let Some(entry) = entries.last() else {
+        print_info("Nothing to roll back");
+        return Ok(());
+    };
+
+    let state = match entry.status {
+        EntryStatus::Pending => "interrupted",
+        EntryStatus::Committed => "last",
+    };
+    print_step(&format!(
+        "⏪ Rolling back {state} '{}' operation...",
+        entry.operation
+    ));
+
+    let lock_path = working_dir.join("composer.lock");
+    let current_lock: Option<Lock> = std::fs::read_to_string(&lock_path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok());
+
+    entry.before.restore(working_dir)?;
+
+    let restored_lock: Option<Lock> = entry
+        .before
+        .composer_lock
+        .as_deref()
+        .map(serde_json::from_str)
+        .transpose()
+        .context("parse restored composer.lock")?;
+
+    // Drop packages that only the rolled back state had
+    let vendor = working_dir.join("vendor");
+    let keep = locked_names(restored_lock.as_ref());
+    for name in locked_names(current_lock.as_ref()).difference(&keep) {
+        let dir = vendor.join(name);
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir)?;
+            print_info(&format!("Removed {name}"));
+        }
+    }
+
+    if let Some(lock) = &restored_lock {
+        let dev: &[LockedPackage] = if entry.no_dev {
+            &[]
+        } else {
+            &lock.packages_dev
+        };
+        let pkgs: Vec<LockedPackage> = lock.packages.iter().chain(dev).cloned().collect();
+        install_packages(&pkgs, working_dir).await?;
+    }
+
+    discard_entry(working_dir, entry)?;
+    print_success("✅ Restored composer.json and composer.lock");
+
+    Ok(())
+}
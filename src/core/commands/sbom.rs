@@ -0,0 +1,409 @@
+use crate::cli::SbomArgs;
+use crate::commands::fingerprint::lock_hash;
+use crate::core::graph::DependencyGraph;
+use crate::io::{manifest_path, read_composer_json, read_lock};
+use crate::models::model::{ComposerJson, Lock, LockedPackage};
+use crate::utils::print_success;
+use anyhow::{Context, Result, anyhow};
+use serde_json::{Value, json};
+use std::path::Path;
+
+/// Environment variable for reproducible build timestamps (seconds since the epoch)
+pub const SOURCE_DATE_EPOCH_ENV: &str = "SOURCE_DATE_EPOCH";
+
+/// Package URL of a Composer package, e.g. `pkg:composer/psr/log@3.0.0`
+pub fn purl(name: &str, version: &str) -> String {
+    format!("pkg:composer/{name}@{}", urlencoding::encode(version))
+}
+
+/// `YYYY-MM-DDTHH:MM:SSZ` for a Unix timestamp
+pub fn rfc3339_utc(seconds: i64) -> String {
+    let days = seconds.div_euclid(86_400);
+    let time = seconds.rem_euclid(86_400);
+    // Inverse of the civil-from-days calculation in resolver::options
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+/// The locked packages in the SBOM, with whether each is a dev dependency
+struct Inventory<'a> {
+    root_name: String,
+    root_version: String,
+    root_license: Vec<String>,
+    packages: Vec<(&'a LockedPackage, bool)>,
+    graph: DependencyGraph,
+    /// Root requirements that are locked, with whether they come from require-dev
+    direct: Vec<(String, bool)>,
+    /// Stable identifier for this set of packages
+    id: String,
+}
+
+impl<'a> Inventory<'a> {
+    fn new(lock: &'a Lock, composer: Option<&ComposerJson>, include_dev: bool) -> Self {
+        let dev: &[LockedPackage] = if include_dev { &lock.packages_dev } else { &[] };
+        let packages: Vec<(&LockedPackage, bool)> = lock
+            .packages
+            .iter()
+            .map(|pkg| (pkg, false))
+            .chain(dev.iter().map(|pkg| (pkg, true)))
+            .collect();
+        let graph = DependencyGraph::from_packages(packages.iter().map(|(pkg, _)| *pkg));
+        let direct = composer
+            .map(|composer| {
+                let dev_requires = if include_dev {
+                    Some(&composer.require_dev)
+                } else {
+                    None
+                };
+                composer
+                    .require
+                    .keys()
+                    .map(|name| (name.clone(), false))
+                    .chain(
+                        dev_requires
+                            .into_iter()
+                            .flat_map(|requires| requires.keys().map(|name| (name.clone(), true))),
+                    )
+                    .filter(|(name, _)| graph.version(name).is_some())
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            root_name: composer
+                .and_then(|c| c.name.clone())
+                .unwrap_or_else(|| "__root__".to_string()),
+            root_version: composer
+                .and_then(|c| c.version.clone())
+                .unwrap_or_else(|| "dev-main".to_string()),
+            root_license: composer.and_then(|c| c.license.clone()).unwrap_or_default(),
+            packages,
+            graph,
+            direct,
+            id: lock_hash(lock, include_dev),
+        }
+    }
+
+    /// A UUID-shaped identifier derived from the locked packages, so the same lock gives
+    /// the same document
+    fn uuid(&self) -> String {
+        let hex = &self.id;
+        format!(
+            "{}-{}-5{}-8{}-{}",
+            &hex[..8],
+            &hex[8..12],
+            &hex[13..16],
+            &hex[17..20],
+            &hex[20..32]
+        )
+    }
+}
+
+/// CycloneDX license choice: one identifier, or an `OR` expression for dual licensing
+fn cyclonedx_licenses(license: &[String]) -> Value {
+    match license {
+        [] => json!([]),
+        [single] if single.eq_ignore_ascii_case("proprietary") => {
+            json!([{"license": {"name": single}}])
+        }
+        [single] => json!([{"license": {"id": single}}]),
+        several => json!([{"expression": several.join(" OR ")}]),
+    }
+}
+
+fn external_references(pkg: &LockedPackage) -> Vec<Value> {
+    let mut references = Vec::new();
+    if let Some(dist) = pkg.dist.as_ref().filter(|d| !d.url.is_empty()) {
+        references.push(json!({"type": "distribution", "url": dist.url}));
+    }
+    if let Some(source) = pkg.source.as_ref().filter(|s| !s.url.is_empty()) {
+        references.push(json!({"type": "vcs", "url": source.url}));
+    }
+    if let Some(homepage) = &pkg.homepage {
+        references.push(json!({"type": "website", "url": homepage}));
+    }
+    references
+}
+
+/// CycloneDX 1.5 JSON document for the locked packages
+pub fn cyclonedx(
+    lock: &Lock,
+    composer: Option<&ComposerJson>,
+    include_dev: bool,
+    timestamp: &str,
+) -> Value {
+    let inventory = Inventory::new(lock, composer, include_dev);
+    let root_ref = purl(&inventory.root_name, &inventory.root_version);
+    let bom_ref = |name: &str| purl(name, inventory.graph.version(name).unwrap_or_default());
+
+    let components: Vec<Value> = inventory
+        .packages
+        .iter()
+        .map(|(pkg, dev)| {
+            let (group, name) = pkg.name.split_once('/').unwrap_or(("", &pkg.name));
+            let mut component = json!({
+                "type": "library",
+                "bom-ref": purl(&pkg.name, &pkg.version),
+                "group": group,
+                "name": name,
+                "version": pkg.version,
+                "purl": purl(&pkg.name, &pkg.version),
+                "scope": if *dev { "optional" } else { "required" },
+                "licenses": cyclonedx_licenses(pkg.license.as_deref().unwrap_or_default()),
+                "externalReferences": external_references(pkg),
+            });
+            if let Some(description) = &pkg.description {
+                component["description"] = json!(description);
+            }
+            if let Some(dist) = pkg.dist.as_ref().filter(|d| !d.shasum.is_empty()) {
+                component["hashes"] = json!([{"alg": "SHA-1", "content": dist.shasum}]);
+            }
+            component
+        })
+        .collect();
+
+    let root_dependencies: Vec<String> = inventory
+        .direct
+        .iter()
+        .map(|(name, _)| bom_ref(name))
+        .collect();
+    let dependencies: Vec<Value> = std::iter::once(json!({
+        "ref": root_ref,
+        "dependsOn": root_dependencies,
+    }))
+    .chain(inventory.packages.iter().map(|(pkg, _)| {
+        let depends_on: Vec<String> = inventory
+            .graph
+            .dependencies(&pkg.name)
+            .iter()
+            .map(|(dep, _)| bom_ref(dep))
+            .collect();
+        json!({"ref": purl(&pkg.name, &pkg.version), "dependsOn": depends_on})
+    }))
+    .collect();
+
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.5",
+        "serialNumber": format!("urn:uuid:{}", inventory.uuid()),
+        "version": 1,
+        "metadata": {
+            "timestamp": timestamp,
+            "tools": {"components": [{
+                "type": "application",
+                "name": "lectern",
+                "version": env!("CARGO_PKG_VERSION"),
+            }]},
+            "component": {
+                "type": "application",
+                "bom-ref": root_ref,
+                "name": inventory.root_name,
+                "version": inventory.root_version,
+                "licenses": cyclonedx_licenses(&inventory.root_license),
+            },
+        },
+        "components": components,
+        "dependencies": dependencies,
+    })
+}
+
+/// SPDX identifiers may only hold letters, digits, `.` and `-`
+fn spdx_id(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("SPDXRef-Package-{name}")
+}
+
+fn spdx_license(license: &[String]) -> String {
+    match license {
+        [] => "NOASSERTION".to_string(),
+        [single] if single.eq_ignore_ascii_case("proprietary") => {
+            "LicenseRef-proprietary".to_string()
+        }
+        [single] => single.clone(),
+        several => format!("({})", several.join(" OR ")),
+    }
+}
+
+/// SPDX 2.3 JSON document for the locked packages
+pub fn spdx(
+    lock: &Lock,
+    composer: Option<&ComposerJson>,
+    include_dev: bool,
+    timestamp: &str,
+) -> Value {
+    let inventory = Inventory::new(lock, composer, include_dev);
+    let root_id = "SPDXRef-RootPackage";
+
+    let mut packages = vec![json!({
+        "name": inventory.root_name,
+        "SPDXID": root_id,
+        "versionInfo": inventory.root_version,
+        "downloadLocation": "NOASSERTION",
+        "filesAnalyzed": false,
+        "licenseConcluded": "NOASSERTION",
+        "licenseDeclared": spdx_license(&inventory.root_license),
+        "copyrightText": "NOASSERTION",
+    })];
+    for (pkg, _) in &inventory.packages {
+        let mut package = json!({
+            "name": pkg.name,
+            "SPDXID": spdx_id(&pkg.name),
+            "versionInfo": pkg.version,
+            "downloadLocation": pkg
+                .dist
+                .as_ref()
+                .map(|d| d.url.as_str())
+                .filter(|url| !url.is_empty())
+                .unwrap_or("NOASSERTION"),
+            "filesAnalyzed": false,
+            "licenseConcluded": "NOASSERTION",
+            "licenseDeclared": spdx_license(pkg.license.as_deref().unwrap_or_default()),
+            "copyrightText": "NOASSERTION",
+            "externalRefs": [{
+                "referenceCategory": "PACKAGE-MANAGER",
+                "referenceType": "purl",
+                "referenceLocator": purl(&pkg.name, &pkg.version),
+            }],
+        });
+        if let Some(dist) = pkg.dist.as_ref().filter(|d| !d.shasum.is_empty()) {
+            package["checksums"] = json!([{"algorithm": "SHA1", "checksumValue": dist.shasum}]);
+        }
+        if let Some(source) = pkg.source.as_ref().filter(|s| !s.url.is_empty()) {
+            package["sourceInfo"] = json!(format!(
+                "{} repository {} at {}",
+                source.source_type, source.url, source.reference
+            ));
+        }
+        if let Some(homepage) = &pkg.homepage {
+            package["homepage"] = json!(homepage);
+        }
+        if let Some(description) = &pkg.description {
+            package["description"] = json!(description);
+        }
+        packages.push(package);
+    }
+
+    let mut relationships = vec![json!({
+        "spdxElementId": "SPDXRef-DOCUMENT",
+        "relationshipType": "DESCRIBES",
+        "relatedSpdxElement": root_id,
+    })];
+    for (name, dev) in &inventory.direct {
+        relationships.push(if *dev {
+            json!({
+                "spdxElementId": spdx_id(name),
+                "relationshipType": "DEV_DEPENDENCY_OF",
+                "relatedSpdxElement": root_id,
+            })
+        } else {
+            json!({
+                "spdxElementId": root_id,
+                "relationshipType": "DEPENDS_ON",
+                "relatedSpdxElement": spdx_id(name),
+            })
+        });
+    }
+    for (pkg, _) in &inventory.packages {
+        for (dep, _) in inventory.graph.dependencies(&pkg.name) {
+            relationships.push(json!({
+                "spdxElementId": spdx_id(&pkg.name),
+                "relationshipType": "DEPENDS_ON",
+                "relatedSpdxElement": spdx_id(dep),
+            }));
+        }
+    }
+
+    json!({
+        "spdxVersion": "SPDX-2.3",
+        "dataLicense": "CC0-1.0",
+        "SPDXID": "SPDXRef-DOCUMENT",
+        "name": inventory.root_name,
+        "documentNamespace": format!(
+            "https://spdx.org/spdxdocs/{}-{}",
+            inventory.root_name.replace('/', "-"),
+            inventory.uuid()
+        ),
+        "creationInfo": {
+            "created": timestamp,
+            "creators": [format!("Tool: lectern-{}", env!("CARGO_PKG_VERSION"))],
+        },
+        "packages": packages,
+        "relationships": relationships,
+    })
+}
+
+/// Creation time for the document: `SOURCE_DATE_EPOCH` when set, otherwise now
+fn creation_timestamp() -> String {
+    let seconds = std::env::var(SOURCE_DATE_EPOCH_ENV)
+        .ok()
+        .and_then(|value| value.trim().parse::<i64>().ok())
+        .unwrap_or_else(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs() as i64)
+        });
+    rfc3339_utc(seconds)
+}
+
+/// Write a software bill of materials for composer.lock
+/// # Errors
+/// Returns an error if composer.lock cannot be read, the format is unknown, or the
+/// output file cannot be written
+pub fn sbom(args: &SbomArgs, working_dir: &Path) -> Result<()> {
+    let lock_path = working_dir.join("composer.lock");
+    if !lock_path.exists() {
+        return Err(anyhow!(
+            "No composer.lock found. Run 'lectern update' first."
+        ));
+    }
+    let lock = read_lock(&lock_path)?;
+    let composer = read_composer_json(&manifest_path(working_dir)).ok();
+    let timestamp = creation_timestamp();
+    let document = match args.format.as_str() {
+        "cyclonedx" => cyclonedx(&lock, composer.as_ref(), !args.no_dev, &timestamp),
+        "spdx-json" => spdx(&lock, composer.as_ref(), !args.no_dev, &timestamp),
+        other => {
+            return Err(anyhow!(
+                "Unknown format: {other}. Use: cyclonedx or spdx-json"
+            ));
+        }
+    };
+    let contents = serde_json::to_string_pretty(&document)?;
+
+    match &args.output {
+        Some(output) => {
+            let path = working_dir.join(output);
+            std::fs::write(&path, format!("{contents}\n"))
+                .with_context(|| format!("write {path:?}"))?;
+            print_success(&format!("✅ Wrote {} SBOM to {output}", args.format));
+        }
+        None => println!("{contents}"),
+    }
+    Ok(())
+}
@@ -1,12 +1,248 @@
 use crate::cli::RunScriptArgs;
-use crate::io::read_composer_json;
-use crate::utils::{print_info, print_step, print_success};
-use anyhow::{Result, anyhow};
-use std::path::Path;
+use crate::commands::list::command_entries;
+use crate::io::{manifest_path, read_composer_json};
+use crate::models::model::{ComposerJson, ScriptDefinition};
+use crate::utils::{print_info, print_step, print_success, similar_names};
+use anyhow::{Context, Result, anyhow};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Composer's default `config.process-timeout`, in seconds
+const DEFAULT_PROCESS_TIMEOUT: u64 = 300;
+
+/// What one entry of a script does once its `@` prefix is interpreted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptCommand {
+    /// A shell command line
+    Shell(String),
+    /// `@other-script`: run another script of composer.json
+    Script(String),
+    /// `@putenv NAME=value`: set a variable for the commands that follow
+    PutEnv(String, String),
+}
+
+/// Append `args` to `command`, or put them where it says `@additional_args`
+fn with_args(command: &str, args: &[String]) -> String {
+    let quoted = args
+        .iter()
+        .map(|arg| shlex::try_quote(arg).map_or_else(|_| arg.clone(), |arg| arg.into_owned()))
+        .collect::<Vec<_>>()
+        .join(" ");
+    if command.contains("@additional_args") {
+        command.replace("@additional_args", &quoted)
+    } else if quoted.is_empty() {
+        command.to_string()
+    } else {
+        format!("{command} {quoted}")
+    }
+}
+
+/// Interpret one script entry the way Composer does. `@php` runs `php` (or `PHP_BINARY`)
+/// and `@composer` runs `binary`, i.e. lectern; arguments are passed on to both and to
+/// plain commands.
+pub fn parse_script_command(command: &str, args: &[String], binary: &str) -> ScriptCommand {
+    let command = command.trim();
+    let Some(reference) = command.strip_prefix('@') else {
+        return ScriptCommand::Shell(with_args(command, args));
+    };
+    let (name, rest) = reference.split_once(' ').unwrap_or((reference, ""));
+    let rest = rest.trim();
+    match name {
+        "php" => {
+            let php = std::env::var("PHP_BINARY").unwrap_or_else(|_| "php".to_string());
+            ScriptCommand::Shell(with_args(&format!("{php} {rest}"), args))
+        }
+        "composer" => ScriptCommand::Shell(with_args(&format!("{binary} {rest}"), args)),
+        "putenv" => {
+            let (key, value) = rest.split_once('=').unwrap_or((rest, ""));
+            ScriptCommand::PutEnv(key.to_string(), value.to_string())
+        }
+        _ => ScriptCommand::Script(name.to_string()),
+    }
+}
+
+/// How long each command may run: `--timeout`, then `COMPOSER_PROCESS_TIMEOUT`, then
+/// `config.process-timeout`, 300 seconds otherwise. Zero means no limit.
+pub fn process_timeout(
+    composer: &ComposerJson,
+    cli: Option<u64>,
+    env: Option<&str>,
+) -> Option<Duration> {
+    let seconds = cli
+        .or_else(|| env.and_then(|value| value.trim().parse().ok()))
+        .or_else(|| {
+            composer
+                .config
+                .as_ref()
+                .and_then(|config| config.process_timeout)
+                .map(u64::from)
+        })
+        .unwrap_or(DEFAULT_PROCESS_TIMEOUT);
+    (seconds > 0).then(|| Duration::from_secs(seconds))
+}
+
+/// The directory `bin` proxies of installed packages live in: `config.bin-dir`, otherwise
+/// `bin` in the vendor directory
+pub fn bin_dir(composer: &ComposerJson, working_dir: &Path) -> PathBuf {
+    let config = composer.config.as_ref();
+    match config.and_then(|config| config.bin_dir.as_deref()) {
+        Some(dir) => working_dir.join(dir),
+        None => working_dir
+            .join(
+                config
+                    .and_then(|config| config.vendor_dir.as_deref())
+                    .unwrap_or("vendor"),
+            )
+            .join("bin"),
+    }
+}
+
+/// Variables scripts run with, as under Composer: the bin directory first on `PATH`,
+/// `COMPOSER_DEV_MODE`, `COMPOSER_BINARY` and the project and vendor directories
+pub fn script_env(
+    composer: &ComposerJson,
+    working_dir: &Path,
+    dev: bool,
+    binary: &Path,
+) -> BTreeMap<String, OsString> {
+    let bin_dir = bin_dir(composer, working_dir);
+    let vendor_dir = working_dir.join(
+        composer
+            .config
+            .as_ref()
+            .and_then(|config| config.vendor_dir.as_deref())
+            .unwrap_or("vendor"),
+    );
+    let mut paths = vec![bin_dir.clone()];
+    if let Some(path) = std::env::var_os("PATH") {
+        paths.extend(std::env::split_paths(&path));
+    }
+
+    let mut env = BTreeMap::new();
+    if let Ok(path) = std::env::join_paths(paths) {
+        env.insert("PATH".to_string(), path);
+    }
+    env.insert(
+        "COMPOSER_DEV_MODE".to_string(),
+        if dev { "1" } else { "0" }.into(),
+    );
+    env.insert("COMPOSER_BINARY".to_string(), binary.into());
+    env.insert("COMPOSER_ROOT_DIR".to_string(), working_dir.into());
+    env.insert("COMPOSER_VENDOR_DIR".to_string(), vendor_dir.into());
+    env.insert("COMPOSER_RUNTIME_BIN_DIR".to_string(), bin_dir.into());
+    env
+}
+
+/// Everything commands of a script run with
+struct ScriptRun<'a> {
+    composer: &'a ComposerJson,
+    working_dir: &'a Path,
+    args: &'a [String],
+    binary: String,
+    env: BTreeMap<String, OsString>,
+    timeout: Option<Duration>,
+}
+
+impl ScriptRun<'_> {
+    /// Run the commands of script `name` in order, stopping at the first failure.
+    /// `stack` holds the scripts being run, to catch scripts that reference themselves.
+    fn run(&mut self, name: &str, stack: &mut Vec<String>) -> Result<()> {
+        if stack.iter().any(|running| running == name) {
+            return Err(anyhow!(
+                "Script '{name}' references itself: {} -> {name}",
+                stack.join(" -> ")
+            ));
+        }
+        let script = self
+            .composer
+            .scripts
+            .as_ref()
+            .and_then(|scripts| scripts.get(name))
+            .ok_or_else(|| anyhow!("Script '{name}' not found in composer.json"))?;
+        let commands = match script {
+            ScriptDefinition::String(command) => vec![command.clone()],
+            ScriptDefinition::Array(commands) => commands.clone(),
+        };
+
+        stack.push(name.to_string());
+        for command in &commands {
+            match parse_script_command(command, self.args, &self.binary) {
+                ScriptCommand::Script(other) => self.run(&other, stack)?,
+                ScriptCommand::PutEnv(key, value) => {
+                    self.env.insert(key, value.into());
+                }
+                ScriptCommand::Shell(line) => {
+                    print_info(&format!("  > {line}"));
+                    self.shell(name, &line)?;
+                }
+            }
+        }
+        stack.pop();
+        Ok(())
+    }
+
+    fn shell(&self, name: &str, line: &str) -> Result<()> {
+        let mut command = if cfg!(windows) {
+            let mut command = std::process::Command::new("cmd");
+            command.arg("/C");
+            command
+        } else {
+            let mut command = std::process::Command::new("sh");
+            command.arg("-c");
+            command
+        };
+        // Its own process group, so a timeout also stops what the shell started. Interactive
+        // scripts stay in ours to keep reading from the terminal.
+        #[cfg(unix)]
+        if !std::io::IsTerminal::is_terminal(&std::io::stdin()) {
+            std::os::unix::process::CommandExt::process_group(&mut command, 0);
+        }
+        let mut child = command
+            .arg(line)
+            .current_dir(self.working_dir)
+            .envs(&self.env)
+            .spawn()
+            .with_context(|| format!("run {line}"))?;
+
+        let started = Instant::now();
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if self
+                .timeout
+                .is_some_and(|timeout| started.elapsed() >= timeout)
+            {
+                #[cfg(unix)]
+                if let Ok(pid) = i32::try_from(child.id()) {
+                    // SAFETY: signals the process group created for this command only
+                    unsafe { libc::kill(-pid, libc::SIGKILL) };
+                }
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(anyhow!(
+                    "Script '{name}' timed out after {}s running {line}; raise config.process-timeout or pass --timeout",
+                    self.timeout.unwrap_or_default().as_secs()
+                ));
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        };
+
+        if !status.success() {
+            return Err(anyhow!(
+                "Script '{name}' failed with exit code: {:?}",
+                status.code()
+            ));
+        }
+        Ok(())
+    }
+}
 
 /// Run a script defined in composer.json
 pub async fn run_script(args: &RunScriptArgs, working_dir: &Path) -> Result<()> {
-    let composer_path = working_dir.join("composer.json");
+    let composer_path = manifest_path(working_dir);
     let composer = read_composer_json(&composer_path)?;
 
     if args.list {
@@ -21,43 +257,79 @@ pub async fn run_script(args: &RunScriptArgs, working_dir: &Path) -> Result<()>
         return Ok(());
     }
 
-    if let Some(scripts) = &composer.scripts {
-        if let Some(script_value) = scripts.get(&args.script) {
-            print_step(&format!("🚀 Running script: {}", args.script));
-
-            // Scripts can be either a string or array of strings
-            let commands: Vec<String> = match script_value {
-                crate::models::model::ScriptDefinition::String(s) => vec![s.clone()],
-                crate::models::model::ScriptDefinition::Array(arr) => arr.clone(),
-            };
-
-            for cmd in commands {
-                print_info(&format!("  > {cmd}"));
-                let status = std::process::Command::new("sh")
-                    .arg("-c")
-                    .arg(&cmd)
-                    .current_dir(working_dir)
-                    .status()?;
-
-                if !status.success() {
-                    return Err(anyhow!(
-                        "Script '{}' failed with exit code: {:?}",
-                        args.script,
-                        status.code()
-                    ));
-                }
-            }
-
-            print_success("✅ Script completed successfully");
-        } else {
-            return Err(anyhow!(
-                "Script '{}' not found in composer.json",
-                args.script
-            ));
-        }
-    } else {
+    if composer.scripts.is_none() {
         return Err(anyhow!("No scripts defined in composer.json"));
     }
 
+    let binary = std::env::current_exe().context("locate the running executable")?;
+    let working_dir = &std::path::absolute(working_dir)?;
+    let mut run = ScriptRun {
+        composer: &composer,
+        working_dir,
+        args: &args.args,
+        binary: shlex::try_quote(&binary.to_string_lossy())
+            .map(|quoted| quoted.into_owned())
+            .unwrap_or_else(|_| binary.to_string_lossy().into_owned()),
+        env: script_env(&composer, working_dir, !args.no_dev, &binary),
+        timeout: process_timeout(
+            &composer,
+            args.timeout,
+            std::env::var("COMPOSER_PROCESS_TIMEOUT").ok().as_deref(),
+        ),
+    };
+
+    print_step(&format!("🚀 Running script: {}", args.script));
+    run.run(&args.script, &mut Vec::new())?;
+    print_success("✅ Script completed successfully");
     Ok(())
 }
+
+/// Run `lectern <name> [args...]` for a name that isn't a built-in command: the script of
+/// that name if composer.json defines one, like `composer test`
+/// # Errors
+/// Returns an error naming similar commands and scripts when there is no such script, or
+/// if the script fails
+pub async fn run_custom_command(words: &[String], working_dir: &Path) -> Result<()> {
+    let Some((name, rest)) = words.split_first() else {
+        return Err(anyhow!("No command given"));
+    };
+    let composer_path = manifest_path(working_dir);
+    let composer = composer_path
+        .exists()
+        .then(|| read_composer_json(&composer_path))
+        .transpose()?;
+    let scripts = composer
+        .as_ref()
+        .and_then(|composer| composer.scripts.as_ref());
+
+    if !scripts.is_some_and(|scripts| scripts.contains_key(name)) {
+        let commands = command_entries();
+        let candidates = commands.iter().map(|(command, _)| command.as_str()).chain(
+            scripts
+                .into_iter()
+                .flat_map(|scripts| scripts.keys().map(String::as_str)),
+        );
+        let similar = similar_names(name, candidates);
+        let hint = if similar.is_empty() {
+            "Run 'lectern list' to see the available commands and scripts".to_string()
+        } else {
+            format!("Did you mean one of these?\n    {}", similar.join("\n    "))
+        };
+        return Err(anyhow!("Command '{name}' is not defined. {hint}"));
+    }
+
+    // Arguments may be given after `--`, as for run-script
+    let args = match rest.split_first() {
+        Some((first, args)) if first == "--" => args,
+        _ => rest,
+    };
+    let args = RunScriptArgs {
+        script: name.clone(),
+        args: args.to_vec(),
+        dev: false,
+        no_dev: false,
+        timeout: None,
+        list: false,
+    };
+    run_script(&args, working_dir).await
+}
@@ -1,15 +1,14 @@
 use crate::resolver::search_packagist;
-use crate::utils::{print_error, print_info};
-use anyhow::Result;
+use crate::utils::print_info;
+use anyhow::{Result, anyhow};
 use std::path::Path;
 
 /// Search for packages on Packagist
 /// # Errors
-/// Returns an error if the search request fails
+/// Returns an error if no terms are given or the search request fails
 pub async fn search_packages(terms: &[String], _working_dir: &Path) -> Result<()> {
     if terms.is_empty() {
-        print_error("❌ Please provide search terms");
-        return Ok(());
+        return Err(anyhow!("Please provide search terms"));
     }
 
     print_info(&format!("🔍 Searching for: {}", terms.join(" ")));
@@ -0,0 +1,248 @@
+use crate::cli::SelfUpdateArgs;
+use crate::commands::global::config_dir;
+use crate::error::LecternError;
+use crate::http;
+use crate::timeouts::RequestKind;
+use crate::utils::{print_info, print_step, print_success, print_warning};
+use anyhow::{Context, Result, anyhow};
+use semver::Version;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+const RELEASES_URL: &str = "https://api.github.com/repos/zanderlewis/lectern/releases/latest";
+
+/// Hex-encoded ed25519 public key release binaries are signed with, embedded by release builds
+const RELEASE_PUBLIC_KEY: Option<&str> = option_env!("LECTERN_RELEASE_PUBLIC_KEY");
+
+/// The parts of a GitHub release self-update needs
+#[derive(Debug, Clone, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    #[serde(default)]
+    pub assets: Vec<ReleaseAsset>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReleaseAsset {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+impl Release {
+    pub fn version(&self) -> Result<Version> {
+        Version::parse(self.tag_name.trim_start_matches('v'))
+            .with_context(|| format!("parse release version {}", self.tag_name))
+    }
+
+    pub fn asset(&self, name: &str) -> Option<&ReleaseAsset> {
+        self.assets.iter().find(|asset| asset.name == name)
+    }
+}
+
+/// Name of the release asset built for this platform, e.g. `lectern-linux-x86_64`
+pub fn asset_name() -> String {
+    format!(
+        "lectern-{}-{}{}",
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        std::env::consts::EXE_SUFFIX
+    )
+}
+
+/// Check `bytes` against a `.sha256` file: a hex digest, optionally followed by a file name
+/// # Errors
+/// Returns an error if the file holds no digest or the digest doesn't match
+pub fn verify_checksum(bytes: &[u8], checksum: &str) -> Result<()> {
+    let expected = checksum
+        .split_whitespace()
+        .next()
+        .ok_or_else(|| anyhow!("Empty checksum file"))?;
+    let actual = hex::encode(Sha256::digest(bytes));
+    if !actual.eq_ignore_ascii_case(expected) {
+        return Err(LecternError::ChecksumMismatch {
+            expected: expected.to_string(),
+            actual,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Check a hex-encoded ed25519 signature of `bytes` made with the key `public_key` (hex)
+/// # Errors
+/// Returns an error if either value isn't valid hex or the signature doesn't verify
+pub fn verify_signature(bytes: &[u8], signature: &str, public_key: &str) -> Result<()> {
+    let signature = hex::decode(signature.trim()).context("decode signature")?;
+    let public_key = hex::decode(public_key.trim()).context("decode public key")?;
+    ring::signature::UnparsedPublicKey::new(&ring::signature::ED25519, public_key)
+        .verify(bytes, &signature)
+        .map_err(|_| anyhow!("Signature verification failed"))
+}
+
+/// Where copies of replaced binaries are kept for `--rollback`
+pub fn backup_dir() -> PathBuf {
+    config_dir().join("self-update")
+}
+
+fn backup_name(version: &str) -> String {
+    format!("lectern-{version}{}", std::env::consts::EXE_SUFFIX)
+}
+
+/// Write `bytes` next to `exe` and rename it over `exe`, so a crash never leaves a partial binary
+fn replace_executable(exe: &Path, bytes: &[u8]) -> Result<()> {
+    let file_name = exe
+        .file_name()
+        .ok_or_else(|| anyhow!("Invalid executable path {exe:?}"))?
+        .to_string_lossy();
+    let staged = exe.with_file_name(format!(".{file_name}.new"));
+    std::fs::write(&staged, bytes).with_context(|| format!("write {staged:?}"))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o755))?;
+    }
+    // A running executable can't be overwritten on Windows, but it can be moved aside
+    #[cfg(windows)]
+    std::fs::rename(exe, exe.with_file_name(format!(".{file_name}.old")))?;
+    std::fs::rename(&staged, exe).with_context(|| format!("replace {exe:?}"))
+}
+
+/// Keep a copy of `exe` as the backup of `current_version`, then replace it with `bytes`.
+/// Returns the backup path.
+/// # Errors
+/// Returns an error if the backup or the replacement cannot be written
+pub fn install_update(
+    exe: &Path,
+    bytes: &[u8],
+    backups: &Path,
+    current_version: &str,
+) -> Result<PathBuf> {
+    std::fs::create_dir_all(backups).with_context(|| format!("create {backups:?}"))?;
+    let backup = backups.join(backup_name(current_version));
+    std::fs::copy(exe, &backup).with_context(|| format!("back up {exe:?}"))?;
+    replace_executable(exe, bytes)?;
+    Ok(backup)
+}
+
+/// Put the most recently backed up binary back in place of `exe` and drop that backup, so
+/// rolling back again goes one version further. Returns the restored version.
+/// # Errors
+/// Returns an error if there is no backup or it cannot be restored
+pub fn restore_backup(exe: &Path, backups: &Path) -> Result<String> {
+    let mut candidates = Vec::new();
+    if let Ok(entries) = std::fs::read_dir(backups) {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let Some(version) = name
+                .strip_prefix("lectern-")
+                .map(|rest| rest.trim_end_matches(std::env::consts::EXE_SUFFIX))
+            else {
+                continue;
+            };
+            let modified = entry.metadata().and_then(|meta| meta.modified()).ok();
+            candidates.push((modified, version.to_string(), entry.path()));
+        }
+    }
+    candidates.sort();
+    let (_, version, backup) = candidates
+        .pop()
+        .ok_or_else(|| anyhow!("No previous version to roll back to in {backups:?}"))?;
+
+    let bytes = std::fs::read(&backup).with_context(|| format!("read {backup:?}"))?;
+    replace_executable(exe, &bytes)?;
+    std::fs::remove_file(&backup).with_context(|| format!("remove {backup:?}"))?;
+    Ok(version)
+}
+
+async fn download(url: &str) -> Result<Vec<u8>> {
+    // Release assets redirect to another host; the metadata client follows that over
+    // HTTP/1.1 or HTTP/2
+    let resp = http::get(url, RequestKind::Metadata)
+        .send()
+        .await
+        .with_context(|| format!("request {url}"))?
+        .error_for_status()?;
+    Ok(resp.bytes().await?.to_vec())
+}
+
+/// The latest lectern release, from GitHub or the mirror `LECTERN_SELF_UPDATE_URL` names
+/// # Errors
+/// Returns an error if the release information cannot be fetched or parsed
+pub async fn latest_release() -> Result<Release> {
+    let url = std::env::var("LECTERN_SELF_UPDATE_URL").unwrap_or_else(|_| RELEASES_URL.into());
+    serde_json::from_slice(&download(&url).await?).context("parse release information")
+}
+
+/// Replace the running binary with the latest release, or restore the previous one with
+/// `--rollback`. `LECTERN_SELF_UPDATE_URL` points the release lookup at a mirror.
+/// # Errors
+/// Returns an error if the release cannot be fetched, has no binary for this platform,
+/// fails verification, cannot be verified by signature without `--insecure`, or the
+/// executable cannot be replaced
+pub async fn self_update(args: &SelfUpdateArgs) -> Result<()> {
+    let exe = std::env::current_exe().context("locate the running executable")?;
+    let current = env!("CARGO_PKG_VERSION");
+
+    if args.rollback {
+        let version = restore_backup(&exe, &backup_dir())?;
+        print_success(&format!("✅ Rolled back from {current} to {version}"));
+        return Ok(());
+    }
+
+    // A checksum from the same release says nothing about who published it
+    if RELEASE_PUBLIC_KEY.is_none() && !args.insecure {
+        return Err(anyhow!(
+            "This build has no release signing key, so updates cannot be verified; reinstall lectern from a release, or pass --insecure to trust the checksum alone"
+        ));
+    }
+
+    print_step("🔍 Checking for a newer lectern release...");
+    let release = latest_release().await?;
+    let latest = release.version()?;
+    if latest <= Version::parse(current)? {
+        print_success(&format!("✅ lectern {current} is the latest version"));
+        return Ok(());
+    }
+
+    let name = asset_name();
+    let asset = release.asset(&name).ok_or_else(|| {
+        anyhow!(
+            "Release {} has no binary for this platform ({name})",
+            release.tag_name
+        )
+    })?;
+    let checksum = release.asset(&format!("{name}.sha256")).ok_or_else(|| {
+        anyhow!(
+            "Release {} publishes no checksum for {name}",
+            release.tag_name
+        )
+    })?;
+
+    print_info(&format!("📥 Downloading lectern {latest}..."));
+    let bytes = download(&asset.browser_download_url).await?;
+    let checksum = download(&checksum.browser_download_url).await?;
+    verify_checksum(&bytes, &String::from_utf8_lossy(&checksum))?;
+
+    match RELEASE_PUBLIC_KEY {
+        Some(public_key) => {
+            let signature = release.asset(&format!("{name}.sig")).ok_or_else(|| {
+                anyhow!(
+                    "Release {} publishes no signature for {name}",
+                    release.tag_name
+                )
+            })?;
+            let signature = download(&signature.browser_download_url).await?;
+            verify_signature(&bytes, &String::from_utf8_lossy(&signature), public_key)?;
+        }
+        None => print_warning(
+            "⚠️  --insecure: this build has no release signing key; only the checksum was verified",
+        ),
+    }
+
+    install_update(&exe, &bytes, &backup_dir(), current)?;
+    print_success(&format!(
+        "✅ Updated lectern from {current} to {latest}; run 'lectern self-update --rollback' to go back"
+    ));
+    Ok(())
+}
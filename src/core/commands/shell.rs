@@ -0,0 +1,307 @@
+use crate::cli::{Commands, ShellLine};
+use crate::io::{manifest_path, read_composer_json_raw, read_lock};
+use crate::resolver::fetch_packagist_versions_bulk;
+use crate::utils::{interactive, print_error, print_info};
+use anyhow::Result;
+use clap::{CommandFactory, Parser};
+use std::collections::BTreeSet;
+use std::io::{BufRead, IsTerminal, Read, Write};
+use std::path::Path;
+
+const PROMPT: &str = "lectern> ";
+const BUILTINS: [&str; 3] = ["exit", "help", "quit"];
+
+/// Package names the project knows about: locked packages and the requirements in composer.json
+pub fn project_packages(working_dir: &Path) -> Vec<String> {
+    let mut names = BTreeSet::new();
+    if let Ok(lock) = read_lock(&working_dir.join("composer.lock")) {
+        for pkg in lock.packages.iter().chain(lock.packages_dev.iter()) {
+            names.insert(pkg.name.clone());
+        }
+    }
+    if let Ok(composer) = read_composer_json_raw(&manifest_path(working_dir)) {
+        for name in composer.require.keys().chain(composer.require_dev.keys()) {
+            // Platform requirements (php, ext-*) have no vendor prefix
+            if name.contains('/') {
+                names.insert(name.clone());
+            }
+        }
+    }
+    names.into_iter().collect()
+}
+
+/// Candidates for tab completion: commands for the first word, package names after it
+#[derive(Debug, Clone, Default)]
+pub struct Completions {
+    pub commands: Vec<String>,
+    pub packages: Vec<String>,
+}
+
+impl Completions {
+    pub fn load(working_dir: &Path) -> Self {
+        let mut commands: Vec<String> = BUILTINS.iter().map(|name| name.to_string()).collect();
+        for command in ShellLine::command().get_subcommands() {
+            commands.push(command.get_name().to_string());
+            commands.extend(command.get_all_aliases().map(str::to_string));
+        }
+        commands.sort();
+        commands.dedup();
+        Self {
+            commands,
+            packages: project_packages(working_dir),
+        }
+    }
+
+    /// Where the word under the cursor starts in `line`, and the sorted candidates for it
+    pub fn complete(&self, line: &str) -> (usize, Vec<String>) {
+        let start = line
+            .rfind(char::is_whitespace)
+            .map(|index| index + 1)
+            .unwrap_or(0);
+        let word = &line[start..];
+        let pool = if line[..start].trim().is_empty() {
+            &self.commands
+        } else if word.starts_with('-') {
+            return (start, Vec::new());
+        } else {
+            &self.packages
+        };
+        let candidates = pool
+            .iter()
+            .filter(|candidate| candidate.starts_with(word))
+            .cloned()
+            .collect();
+        (start, candidates)
+    }
+}
+
+/// Longest prefix shared by all candidates
+fn common_prefix(candidates: &[String]) -> String {
+    let Some(first) = candidates.first() else {
+        return String::new();
+    };
+    let mut prefix = first.as_str();
+    for candidate in &candidates[1..] {
+        while !candidate.starts_with(prefix) {
+            let mut chars = prefix.chars();
+            chars.next_back();
+            prefix = chars.as_str();
+        }
+    }
+    prefix.to_string()
+}
+
+enum Input {
+    Line(String),
+    Interrupted,
+    Eof,
+}
+
+#[cfg(unix)]
+mod terminal {
+    /// Character-at-a-time input without echo or signals while a line is edited; the previous
+    /// settings come back on drop, so commands run with a normal terminal
+    pub struct RawMode {
+        original: libc::termios,
+    }
+
+    impl RawMode {
+        pub fn enable() -> Option<Self> {
+            // SAFETY: tcgetattr/tcsetattr only read and write the termios struct passed in
+            unsafe {
+                let mut original = std::mem::zeroed::<libc::termios>();
+                if libc::tcgetattr(libc::STDIN_FILENO, &mut original) != 0 {
+                    return None;
+                }
+                let mut raw = original;
+                raw.c_lflag &= !(libc::ICANON | libc::ECHO | libc::ISIG);
+                raw.c_cc[libc::VMIN] = 1;
+                raw.c_cc[libc::VTIME] = 0;
+                if libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw) != 0 {
+                    return None;
+                }
+                Some(Self { original })
+            }
+        }
+    }
+
+    impl Drop for RawMode {
+        fn drop(&mut self) {
+            // SAFETY: restores the settings read in `enable`
+            unsafe {
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.original);
+            }
+        }
+    }
+}
+
+fn read_byte(input: &mut impl Read) -> std::io::Result<Option<u8>> {
+    let mut byte = [0u8; 1];
+    match input.read(&mut byte)? {
+        0 => Ok(None),
+        _ => Ok(Some(byte[0])),
+    }
+}
+
+fn redraw(out: &mut impl Write, line: &str) -> std::io::Result<()> {
+    write!(out, "\r\x1b[K{PROMPT}{line}")?;
+    out.flush()
+}
+
+/// Read a line from the terminal, completing the word under the cursor on Tab
+fn read_line_interactive(completions: &Completions) -> std::io::Result<Input> {
+    #[cfg(unix)]
+    let _raw = terminal::RawMode::enable();
+    let mut input = std::io::stdin().lock();
+    let mut out = std::io::stdout();
+    let mut line = String::new();
+    redraw(&mut out, &line)?;
+
+    loop {
+        let Some(byte) = read_byte(&mut input)? else {
+            return Ok(Input::Eof);
+        };
+        match byte {
+            b'\r' | b'\n' => {
+                writeln!(out)?;
+                return Ok(Input::Line(line));
+            }
+            // Ctrl-C drops the line, Ctrl-D on an empty line leaves the shell
+            0x03 => {
+                writeln!(out, "^C")?;
+                return Ok(Input::Interrupted);
+            }
+            0x04 if line.is_empty() => {
+                writeln!(out)?;
+                return Ok(Input::Eof);
+            }
+            0x7f | 0x08 => {
+                line.pop();
+                redraw(&mut out, &line)?;
+            }
+            // Ctrl-U clears the line
+            0x15 => {
+                line.clear();
+                redraw(&mut out, &line)?;
+            }
+            b'\t' => {
+                let (start, candidates) = completions.complete(&line);
+                match candidates.as_slice() {
+                    [] => write!(out, "\x07")?,
+                    [only] => {
+                        line.truncate(start);
+                        line.push_str(only);
+                        line.push(' ');
+                    }
+                    _ => {
+                        let prefix = common_prefix(&candidates);
+                        if prefix.len() > line.len() - start {
+                            line.truncate(start);
+                            line.push_str(&prefix);
+                        } else {
+                            writeln!(out)?;
+                            writeln!(out, "{}", candidates.join("  "))?;
+                        }
+                    }
+                }
+                redraw(&mut out, &line)?;
+            }
+            // Arrow and function keys send escape sequences, which are skipped
+            0x1b => {
+                if let Some(b'[' | b'O') = read_byte(&mut input)? {
+                    while let Some(byte) = read_byte(&mut input)? {
+                        if (0x40..=0x7e).contains(&byte) {
+                            break;
+                        }
+                    }
+                }
+            }
+            byte if byte < 0x20 => {}
+            lead => {
+                let width = match lead {
+                    0xf0.. => 4,
+                    0xe0.. => 3,
+                    0xc0.. => 2,
+                    _ => 1,
+                };
+                let mut bytes = vec![lead];
+                for _ in 1..width {
+                    if let Some(byte) = read_byte(&mut input)? {
+                        bytes.push(byte);
+                    }
+                }
+                let text = String::from_utf8_lossy(&bytes);
+                line.push_str(&text);
+                write!(out, "{text}")?;
+                out.flush()?;
+            }
+        }
+    }
+}
+
+fn read_input(interactive: bool, completions: &Completions) -> std::io::Result<Input> {
+    if interactive {
+        return read_line_interactive(completions);
+    }
+    let mut line = String::new();
+    if std::io::stdin().lock().read_line(&mut line)? == 0 {
+        return Ok(Input::Eof);
+    }
+    Ok(Input::Line(line))
+}
+
+/// Run commands typed one per line until `exit` or end of input. Everything stays in this
+/// process, so the metadata cache, HTTP clients and throttling state are reused by each command
+/// `run` executes. Lines are read from the terminal with tab completion, or from piped stdin.
+/// # Errors
+/// Returns an error if reading input or printing help fails; command errors are reported and
+/// the shell continues
+pub async fn run_shell<F>(working_dir: &Path, mut run: F) -> Result<()>
+where
+    F: AsyncFnMut(Commands) -> Result<bool>,
+{
+    // `-n` keeps the line editor and its prompt out of the way
+    let interactive = std::io::stdin().is_terminal() && interactive();
+    let mut completions = Completions::load(working_dir);
+    if interactive {
+        print_info("🐚 Lectern shell - type 'help' for commands, Tab to complete, 'exit' to leave");
+        // Warm the metadata cache in the background so the first command doesn't pay for it
+        let packages = completions.packages.clone();
+        tokio::spawn(async move {
+            let _ = fetch_packagist_versions_bulk(&packages).await;
+        });
+    }
+
+    loop {
+        let line = match read_input(interactive, &completions)? {
+            Input::Line(line) => line,
+            Input::Interrupted => continue,
+            Input::Eof => break,
+        };
+        let Some(words) = shlex::split(&line) else {
+            print_error("❌ Unbalanced quotes");
+            continue;
+        };
+        match words.first().map(String::as_str) {
+            None => continue,
+            Some("exit" | "quit") => break,
+            Some("help") => {
+                ShellLine::command().print_help()?;
+                continue;
+            }
+            Some(_) => {}
+        }
+
+        match ShellLine::try_parse_from(&words) {
+            Ok(ShellLine { command }) => {
+                if let Err(e) = run(command).await {
+                    print_error(&format!("❌ {e:#}"));
+                }
+            }
+            Err(e) => e.print()?,
+        }
+        // Commands like require and remove change which packages can be completed
+        completions = Completions::load(working_dir);
+    }
+    Ok(())
+}
@@ -1,7 +1,17 @@
-use crate::resolver::fetch_package_info;
-use crate::utils::print_info;
-use anyhow::Result;
+use crate::core::graph::DependencyGraph;
+use crate::io::{manifest_path, read_composer_json, read_lock};
+use crate::models::model::{ComposerJson, Lock, LockedPackage};
+use crate::platform::{apply_platform_overrides, detect_platform};
+use crate::resolver::packagist::P2Version;
+use crate::resolver::version::ComposerVersion;
+use crate::resolver::{
+    fetch_package_info, fetch_packagist_versions_cached, fetch_security_advisories,
+};
+use crate::utils::{is_prerelease_version, print_info, print_warning};
+use anyhow::{Result, anyhow};
+use serde_json::Value;
 use std::path::Path;
+use walkdir::WalkDir;
 
 /// Show detailed information about a specific package
 /// # Errors
@@ -47,9 +57,8 @@ pub async fn show_package_details(package: &str, _working_dir: &Path) -> Result<
 
     if let Some(versions) = &package_info.package.versions {
         println!("📋 Recent Versions:");
-        let mut version_list: Vec<_> = versions.keys().collect();
-        version_list.sort();
-        version_list.reverse();
+        let mut version_list: Vec<&str> = versions.keys().map(String::as_str).collect();
+        sort_newest_first(&mut version_list);
 
         for version in version_list.iter().take(10) {
             if let Some(version_info) = versions.get(*version) {
@@ -61,3 +70,469 @@ pub async fn show_package_details(package: &str, _working_dir: &Path) -> Result<
 
     Ok(())
 }
+
+/// Sort version strings newest first by Composer's ordering; branches, which aren't
+/// versions, go last
+pub fn sort_newest_first(versions: &mut [&str]) {
+    versions.sort_by_cached_key(|version| std::cmp::Reverse(ComposerVersion::parse(version)));
+}
+
+/// Split versions from the p2 metadata into stable releases and pre-releases (alpha, beta,
+/// RC and dev branches), each newest first by Composer's ordering. Named branches such as
+/// `dev-main` aren't versions and keep their place after the numbered ones.
+pub fn group_versions(versions: &[P2Version]) -> (Vec<&str>, Vec<&str>) {
+    let mut sorted: Vec<&str> = versions
+        .iter()
+        .map(|version| version.version.as_str())
+        .collect();
+    sort_newest_first(&mut sorted);
+    sorted
+        .into_iter()
+        .partition(|version| !is_prerelease_version(version))
+}
+
+/// The entry for `version` in the p2 metadata, matched with or without a leading `v`
+pub fn find_version<'a>(versions: &'a [P2Version], version: &str) -> Option<&'a P2Version> {
+    fn bare(v: &str) -> &str {
+        v.strip_prefix('v').unwrap_or(v)
+    }
+    versions.iter().find(|candidate| {
+        bare(&candidate.version) == bare(version) || candidate.version_normalized == version
+    })
+}
+
+/// The lines `show <package> <version>` prints for one version of the p2 metadata
+pub fn describe_version(package: &str, version: &P2Version) -> Vec<String> {
+    let field = |name: &str| version.other.get(name);
+    let text = |name: &str| field(name).and_then(Value::as_str);
+
+    let mut lines = vec![format!("📦 Package: {package} {}", version.version)];
+    if let Some(description) = text("description") {
+        lines.push(format!("📝 Description: {description}"));
+    }
+    if let Some(package_type) = text("type") {
+        lines.push(format!("🏷️  Type: {package_type}"));
+    }
+    if let Some(time) = text("time") {
+        lines.push(format!("📅 Released: {time}"));
+    }
+    let licenses: Vec<&str> = field("license")
+        .and_then(Value::as_array)
+        .map(|licenses| licenses.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    if !licenses.is_empty() {
+        lines.push(format!("⚖️  License: {}", licenses.join(", ")));
+    }
+    if let Some(homepage) = text("homepage") {
+        lines.push(format!("🏠 Homepage: {homepage}"));
+    }
+
+    if let Some(authors) = field("authors").and_then(Value::as_array)
+        && !authors.is_empty()
+    {
+        lines.push(String::new());
+        lines.push("👥 Authors:".to_string());
+        for author in authors {
+            let name = author.get("name").and_then(Value::as_str).unwrap_or("?");
+            let email = author
+                .get("email")
+                .and_then(Value::as_str)
+                .map(|email| format!(" <{email}>"))
+                .unwrap_or_default();
+            lines.push(format!("   • {name}{email}"));
+        }
+    }
+
+    let requirement_sections = [
+        ("🔗 Requires:", version.require.clone()),
+        (
+            "🧪 Requires (dev):",
+            field("require-dev").and_then(|v| serde_json::from_value(v.clone()).ok()),
+        ),
+    ];
+    for (title, requires) in requirement_sections {
+        if let Some(requires) = requires
+            && !requires.is_empty()
+        {
+            lines.push(String::new());
+            lines.push(title.to_string());
+            for (name, constraint) in requires {
+                lines.push(format!("   • {name} {constraint}"));
+            }
+        }
+    }
+
+    if let Some(support) = field("support").and_then(Value::as_object)
+        && !support.is_empty()
+    {
+        lines.push(String::new());
+        lines.push("🛟 Support:".to_string());
+        for (kind, url) in support {
+            if let Some(url) = url.as_str() {
+                lines.push(format!("   • {kind}: {url}"));
+            }
+        }
+    }
+
+    if let Some(suggest) = field("suggest").and_then(Value::as_object)
+        && !suggest.is_empty()
+    {
+        lines.push(String::new());
+        lines.push("💡 Suggests:".to_string());
+        for (name, reason) in suggest {
+            lines.push(format!("   • {name}: {}", reason.as_str().unwrap_or("")));
+        }
+    }
+    lines
+}
+
+/// List every version of `package` Packagist knows, stable releases first
+/// # Errors
+/// Returns an error if the metadata cannot be fetched or the package has no versions
+pub async fn show_available_versions(package: &str) -> Result<()> {
+    let versions = fetch_packagist_versions_cached(package).await?;
+    if versions.is_empty() {
+        return Err(anyhow!("No versions of {package} found on Packagist"));
+    }
+    let (stable, prerelease) = group_versions(&versions);
+
+    println!("\n📦 Package: {package}");
+    for (title, versions) in [("📋 Stable:", stable), ("🧪 Pre-release:", prerelease)] {
+        if !versions.is_empty() {
+            println!("\n{title}");
+            for version in versions {
+                println!("   • {version}");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Show the metadata of one version of `package`, which doesn't need to be installed
+/// # Errors
+/// Returns an error if the metadata cannot be fetched or there is no such version
+pub async fn show_remote_version(package: &str, version: &str) -> Result<()> {
+    let versions = fetch_packagist_versions_cached(package).await?;
+    let Some(found) = find_version(&versions, version) else {
+        let (stable, _) = group_versions(&versions);
+        let recent = if stable.is_empty() {
+            String::new()
+        } else {
+            format!(
+                " (latest stable: {})",
+                stable
+                    .iter()
+                    .take(5)
+                    .copied()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        };
+        return Err(anyhow!(
+            "Version {version} of {package} not found{recent}; run 'lectern show --available {package}' to list all versions"
+        ));
+    };
+    println!();
+    for line in describe_version(package, found) {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+/// Everything lectern knows locally about one package
+#[derive(Debug, Default)]
+pub struct PackageReport {
+    pub locked: Option<LockedPackage>,
+    pub is_dev: bool,
+    /// (requirer, constraint) pairs, including the root project
+    pub required_by: Vec<(String, String)>,
+    /// Namespace prefixes, classmap and files entries from the package's autoload
+    pub autoload_roots: Vec<String>,
+    /// Number of files under vendor/<package>, if installed
+    pub installed_files: Option<usize>,
+}
+
+/// Gather lock, requirer and vendor information for `package`
+pub fn collect_package_report(
+    package: &str,
+    composer: Option<&ComposerJson>,
+    lock: Option<&Lock>,
+    working_dir: &Path,
+) -> PackageReport {
+    let mut report = PackageReport::default();
+
+    if let Some(composer) = composer {
+        let root = composer.name.clone().unwrap_or_else(|| "root".to_string());
+        for (requires, suffix) in [(&composer.require, ""), (&composer.require_dev, " (dev)")] {
+            if let Some(constraint) = requires.get(package) {
+                report
+                    .required_by
+                    .push((format!("{root}{suffix}"), constraint.clone()));
+            }
+        }
+    }
+
+    if let Some(lock) = lock {
+        report.locked = lock.packages.iter().find(|p| p.name == package).cloned();
+        if report.locked.is_none() {
+            report.locked = lock
+                .packages_dev
+                .iter()
+                .find(|p| p.name == package)
+                .cloned();
+            report.is_dev = report.locked.is_some();
+        }
+
+        for pkg in lock.packages.iter().chain(lock.packages_dev.iter()) {
+            if let Some(constraint) = pkg.require.as_ref().and_then(|r| r.get(package)) {
+                report
+                    .required_by
+                    .push((pkg.name.clone(), constraint.clone()));
+            }
+        }
+    }
+
+    if let Some(autoload) = report.locked.as_ref().and_then(|p| p.autoload.as_ref()) {
+        for (kind, map) in [("psr-4", &autoload.psr4), ("psr-0", &autoload.psr0)] {
+            report
+                .autoload_roots
+                .extend(map.keys().map(|ns| format!("{kind}: {ns}")));
+        }
+        for (kind, list) in [("classmap", &autoload.classmap), ("files", &autoload.files)] {
+            report
+                .autoload_roots
+                .extend(list.iter().map(|path| format!("{kind}: {path}")));
+        }
+    }
+
+    let package_dir = working_dir.join("vendor").join(package);
+    if package_dir.is_dir() {
+        report.installed_files = Some(
+            WalkDir::new(&package_dir)
+                .into_iter()
+                .filter_map(std::result::Result::ok)
+                .filter(|e| e.file_type().is_file())
+                .count(),
+        );
+    }
+
+    report
+}
+
+/// Show one aggregated view of a package: local lock data plus Packagist versions and advisories
+/// # Errors
+/// Returns an error if composer.json or composer.lock exist but cannot be parsed
+pub async fn show_package_full(package: &str, working_dir: &Path) -> Result<()> {
+    print_info(&format!("📦 Collecting everything known about: {package}"));
+
+    let composer_path = manifest_path(working_dir);
+    let composer = if composer_path.exists() {
+        Some(read_composer_json(&composer_path)?)
+    } else {
+        None
+    };
+    let lock_path = working_dir.join("composer.lock");
+    let lock = if lock_path.exists() {
+        Some(read_lock(&lock_path)?)
+    } else {
+        None
+    };
+
+    let report = collect_package_report(package, composer.as_ref(), lock.as_ref(), working_dir);
+
+    println!("\n📦 Package: {package}");
+    match &report.locked {
+        Some(locked) => {
+            let scope = if report.is_dev { " (dev)" } else { "" };
+            println!("🔒 Locked: {}{scope}", locked.version);
+            if let Some(desc) = &locked.description {
+                println!("📝 Description: {desc}");
+            }
+            if let Some(license) = &locked.license {
+                println!("⚖️  License: {}", license.join(", "));
+            }
+        }
+        None => println!("🔒 Locked: not in composer.lock"),
+    }
+
+    if !report.required_by.is_empty() {
+        println!("\n🔗 Required by:");
+        for (requirer, constraint) in &report.required_by {
+            println!("   • {requirer} ({constraint})");
+        }
+    }
+
+    if let Some(locked) = &report.locked {
+        println!("\n📥 Provenance:");
+        if let Some(dist) = &locked.dist {
+            println!(
+                "   • dist: {} {} ({})",
+                dist.dist_type, dist.url, dist.reference
+            );
+            if !dist.shasum.is_empty() {
+                println!("   • shasum: {}", dist.shasum);
+            }
+        }
+        if let Some(source) = &locked.source {
+            println!(
+                "   • source: {} {} ({})",
+                source.source_type, source.url, source.reference
+            );
+        }
+    }
+
+    match report.installed_files {
+        Some(count) => println!("\n📁 Installed files: {count}"),
+        None => println!("\n📁 Installed files: not installed"),
+    }
+
+    if !report.autoload_roots.is_empty() {
+        println!("\n🧭 Autoload:");
+        for root in &report.autoload_roots {
+            println!("   • {root}");
+        }
+    }
+
+    if let Some(suggest) = report.locked.as_ref().and_then(|p| p.suggest.as_ref())
+        && !suggest.is_empty()
+    {
+        println!("\n💡 Suggests:");
+        for (name, reason) in suggest {
+            println!("   • {name}: {reason}");
+        }
+    }
+
+    if let Some(funding) = report.locked.as_ref().and_then(|p| p.funding.as_ref())
+        && !funding.is_empty()
+    {
+        println!("\n💰 Funding:");
+        for fund in funding {
+            if let Some(fund_type) = fund.get("type").and_then(|v| v.as_str())
+                && let Some(url) = fund.get("url").and_then(|v| v.as_str())
+            {
+                println!("   • {fund_type}: {url}");
+            }
+        }
+    }
+
+    // Remote data is best effort so the local view still works offline
+    match fetch_security_advisories(&[package.to_string()]).await {
+        Ok(advisories) => {
+            let advisories = advisories.get(package).cloned().unwrap_or_default();
+            if advisories.is_empty() {
+                println!("\n🛡️  Advisories: none known");
+            } else {
+                println!("\n🛡️  Advisories:");
+                let locked_version = report.locked.as_ref().map(|p| p.version.as_str());
+                for advisory in &advisories {
+                    let affected = locked_version.and_then(|v| advisory.affects(v));
+                    let marker = if affected == Some(true) {
+                        " ⚠️  affects locked version"
+                    } else {
+                        ""
+                    };
+                    let id = advisory.cve.as_deref().unwrap_or(&advisory.advisory_id);
+                    println!(
+                        "   • {id}: {} ({}){marker}",
+                        advisory.title, advisory.affected_versions
+                    );
+                }
+            }
+        }
+        Err(e) => print_warning(&format!("⚠️  Could not fetch advisories: {e}")),
+    }
+
+    match fetch_package_info(package).await {
+        Ok(info) => {
+            if let Some(versions) = &info.package.versions {
+                println!("\n📋 Recent Versions:");
+                let mut version_list: Vec<&str> = versions.keys().map(String::as_str).collect();
+                sort_newest_first(&mut version_list);
+                for version in version_list.iter().take(10) {
+                    println!("   • {version}");
+                }
+            }
+        }
+        Err(e) => print_warning(&format!("⚠️  Could not fetch versions: {e}")),
+    }
+
+    Ok(())
+}
+
+/// List the platform packages of the PHP on PATH with `config.platform` applied, like
+/// `composer show --platform`
+/// # Errors
+/// Returns an error if composer.json exists but cannot be read
+pub fn show_platform(working_dir: &Path) -> Result<()> {
+    let composer_path = manifest_path(working_dir);
+    let overrides = if composer_path.exists() {
+        read_composer_json(&composer_path)?
+            .config
+            .and_then(|config| config.platform)
+            .unwrap_or_default()
+    } else {
+        Default::default()
+    };
+    let mut packages = detect_platform().unwrap_or_else(|e| {
+        print_warning(&format!("⚠️  Could not detect the PHP platform: {e}"));
+        Vec::new()
+    });
+    apply_platform_overrides(&mut packages, &overrides);
+
+    if packages.is_empty() {
+        print_info("No platform packages detected");
+        return Ok(());
+    }
+    println!("\n🐘 Platform Packages ({} total):", packages.len());
+    println!("{:<30} {:<15} Description", "Package", "Version");
+    println!("{}", "-".repeat(70));
+    for package in &packages {
+        println!(
+            "{:<30} {:<15} {}",
+            package.name, package.version, package.description
+        );
+    }
+    Ok(())
+}
+
+/// Print the locked dependency tree of one package, or of every root requirement
+/// # Errors
+/// Returns an error if composer.lock cannot be read or the package is not locked
+pub fn show_dependency_tree(package: Option<&str>, working_dir: &Path) -> Result<()> {
+    let lock_path = working_dir.join("composer.lock");
+    if !lock_path.exists() {
+        return Err(anyhow!(
+            "No composer.lock found. Run 'lectern install' first."
+        ));
+    }
+    let graph = DependencyGraph::from_lock(&read_lock(&lock_path)?);
+
+    let roots: Vec<String> = match package {
+        Some(package) => {
+            if graph.version(package).is_none() {
+                return Err(anyhow!("{package} is not in composer.lock"));
+            }
+            vec![package.to_string()]
+        }
+        None => {
+            let composer = read_composer_json(&manifest_path(working_dir))?;
+            composer
+                .require
+                .keys()
+                .chain(composer.require_dev.keys())
+                .filter(|name| graph.version(name).is_some())
+                .cloned()
+                .collect()
+        }
+    };
+    for root in &roots {
+        for line in graph.tree_lines(root) {
+            println!("{line}");
+        }
+    }
+
+    for cycle in graph.cycles() {
+        print_warning(&format!("⚠️  Circular dependency: {}", cycle.join(" → ")));
+    }
+    Ok(())
+}
@@ -1,19 +1,201 @@
+use crate::installer::InstallState;
+use crate::installer::installer_utils::{extract_archive_ultra_fast, get_cached_package_path};
 use crate::io::read_lock;
-use crate::utils::{print_error, print_info, print_success};
-use anyhow::Result;
-use std::path::Path;
+use crate::models::model::LockedPackage;
+use crate::utils::{print_info, print_success, print_warning};
+use anyhow::{Context, Result, anyhow};
+use sha1::Digest;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
-/// Show status of all dependencies
+/// Files in an installed package that differ from what was installed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LocalChanges {
+    pub modified: Vec<String>,
+    pub added: Vec<String>,
+    pub deleted: Vec<String>,
+    /// Commit a git checkout is on when it is not the locked reference
+    pub moved_to: Option<String>,
+}
+
+impl LocalChanges {
+    pub fn is_empty(&self) -> bool {
+        self.modified.is_empty()
+            && self.added.is_empty()
+            && self.deleted.is_empty()
+            && self.moved_to.is_none()
+    }
+
+    fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(commit) = &self.moved_to {
+            parts.push(format!("checked out at {commit}"));
+        }
+        for (count, label) in [
+            (self.modified.len(), "modified"),
+            (self.added.len(), "added"),
+            (self.deleted.len(), "deleted"),
+        ] {
+            if count > 0 {
+                parts.push(format!("{count} {label}"));
+            }
+        }
+        parts.join(", ")
+    }
+}
+
+/// How an installed package compares to its locked dist or source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackageStatus {
+    Unchanged,
+    Modified(LocalChanges),
+    NotInstalled,
+    /// Nothing to compare against, with the reason
+    Unknown(String),
+}
+
+/// Compare a package in vendor with its git reference (source checkouts) or with
+/// its cached dist archive, verified against the dist checksum
+/// # Errors
+/// Returns an error if the package directory, repository or archive cannot be read
+pub fn package_status(pkg: &LockedPackage, vendor: &Path) -> Result<PackageStatus> {
+    let dir = vendor.join(&pkg.name);
+    if !dir.exists() {
+        return Ok(PackageStatus::NotInstalled);
+    }
+    if !InstallState::load(vendor).patches_of(&pkg.name).is_empty() {
+        // The patched files would all count as local changes
+        return Ok(PackageStatus::Unknown(
+            "patched from extra.patches".to_string(),
+        ));
+    }
+    let changes = if dir.join(".git").exists() {
+        let reference = pkg.source.as_ref().map(|s| s.reference.as_str());
+        git_changes(&dir, reference.filter(|r| !r.is_empty()))?
+    } else {
+        let Some(dist) = pkg.dist.as_ref().filter(|d| d.dist_type != "path") else {
+            return Ok(PackageStatus::Unknown(
+                "not installed from a dist archive".to_string(),
+            ));
+        };
+        let archive = get_cached_package_path(&pkg.name, &pkg.version, &dist.url);
+        if !archive.exists() {
+            return Ok(PackageStatus::Unknown(
+                "dist archive is not in the cache".to_string(),
+            ));
+        }
+        if !dist.shasum.is_empty() {
+            let bytes = std::fs::read(&archive).with_context(|| format!("read {archive:?}"))?;
+            if !hex::encode(sha1::Sha1::digest(&bytes)).eq_ignore_ascii_case(&dist.shasum) {
+                return Ok(PackageStatus::Unknown(
+                    "cached archive does not match the dist checksum".to_string(),
+                ));
+            }
+        }
+        let pristine = tempfile::TempDir::new()?;
+        extract_archive_ultra_fast(&archive, pristine.path())
+            .with_context(|| format!("extract {archive:?}"))?;
+        tree_changes(pristine.path(), &dir)?
+    };
+    Ok(if changes.is_empty() {
+        PackageStatus::Unchanged
+    } else {
+        PackageStatus::Modified(changes)
+    })
+}
+
+fn git_changes(dir: &Path, reference: Option<&str>) -> Result<LocalChanges> {
+    let repo = git2::Repository::open(dir).with_context(|| format!("open {dir:?}"))?;
+    let mut changes = LocalChanges::default();
+    if let (Some(reference), Ok(head)) = (reference, repo.head())
+        && let Some(oid) = head.target()
+        && !oid.to_string().starts_with(&reference.to_lowercase())
+    {
+        changes.moved_to = Some(oid.to_string());
+    }
+
+    let mut options = git2::StatusOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .include_ignored(false);
+    for entry in repo.statuses(Some(&mut options))?.iter() {
+        let Some(path) = entry.path() else {
+            continue;
+        };
+        let status = entry.status();
+        let list = if status.intersects(git2::Status::WT_NEW | git2::Status::INDEX_NEW) {
+            &mut changes.added
+        } else if status.intersects(git2::Status::WT_DELETED | git2::Status::INDEX_DELETED) {
+            &mut changes.deleted
+        } else {
+            &mut changes.modified
+        };
+        list.push(path.to_string());
+    }
+    Ok(changes)
+}
+
+/// Files and symlinks under `root`, keyed by `/`-separated relative path
+fn tree_entries(root: &Path) -> BTreeMap<String, PathBuf> {
+    walkdir::WalkDir::new(root)
+        .follow_links(false)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| !entry.file_type().is_dir())
+        .filter_map(|entry| {
+            let relative = entry.path().strip_prefix(root).ok()?;
+            let key = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            Some((key, entry.path().to_path_buf()))
+        })
+        .collect()
+}
+
+fn same_entry(a: &Path, b: &Path) -> bool {
+    match (std::fs::read_link(a), std::fs::read_link(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        (Err(_), Err(_)) => std::fs::read(a).ok() == std::fs::read(b).ok(),
+        _ => false,
+    }
+}
+
+/// Differences between a freshly extracted package and the installed one
+fn tree_changes(pristine: &Path, installed: &Path) -> Result<LocalChanges> {
+    let expected = tree_entries(pristine);
+    let actual = tree_entries(installed);
+    let mut changes = LocalChanges::default();
+    for (path, original) in &expected {
+        match actual.get(path) {
+            Some(current) if !same_entry(original, current) => changes.modified.push(path.clone()),
+            Some(_) => {}
+            None => changes.deleted.push(path.clone()),
+        }
+    }
+    changes.added = actual
+        .keys()
+        .filter(|path| !expected.contains_key(*path))
+        .cloned()
+        .collect();
+    Ok(changes)
+}
+
+/// Show status of all dependencies, flagging installed packages with local changes.
+/// With `verbose`, changed files are listed.
 /// # Errors
 /// Returns an error if the lock file cannot be read
-pub async fn show_dependency_status(working_dir: &Path) -> Result<()> {
+pub async fn show_dependency_status(working_dir: &Path, verbose: bool) -> Result<()> {
     print_info("📊 Checking dependency status...");
 
     let lock_path = working_dir.join("composer.lock");
 
     if !lock_path.exists() {
-        print_error("❌ No composer.lock found. Run 'lectern install' first.");
-        return Ok(());
+        return Err(anyhow!(
+            "No composer.lock found. Run 'lectern install' first."
+        ));
     }
 
     let lock = read_lock(&lock_path)?;
@@ -39,5 +221,44 @@ pub async fn show_dependency_status(working_dir: &Path) -> Result<()> {
         print_info("📦 No packages installed.");
     }
 
+    let vendor = working_dir.join("vendor");
+    let mut modified = Vec::new();
+    for pkg in lock.packages.iter().chain(lock.packages_dev.iter()) {
+        match package_status(pkg, &vendor) {
+            Ok(PackageStatus::Modified(changes)) => modified.push((&pkg.name, changes)),
+            Ok(PackageStatus::Unknown(reason)) if verbose => {
+                print_info(&format!(
+                    "ℹ️  {}: cannot check for changes, {reason}",
+                    pkg.name
+                ));
+            }
+            Err(e) if verbose => {
+                print_info(&format!("ℹ️  {}: cannot check for changes: {e}", pkg.name));
+            }
+            _ => {}
+        }
+    }
+
+    if modified.is_empty() {
+        return Ok(());
+    }
+    print_warning(&format!(
+        "⚠️  {} package(s) have local modifications:",
+        modified.len()
+    ));
+    for (name, changes) in &modified {
+        println!("  {name} ({})", changes.summary());
+        if verbose {
+            for (marker, files) in [
+                ("M", &changes.modified),
+                ("A", &changes.added),
+                ("D", &changes.deleted),
+            ] {
+                for file in files {
+                    println!("    {marker} {file}");
+                }
+            }
+        }
+    }
     Ok(())
 }
@@ -1,46 +1,205 @@
-use crate::io::read_lock;
-use crate::utils::{print_error, print_info, print_step};
-use anyhow::Result;
-use std::collections::HashMap;
+use crate::cli::SuggestsArgs;
+use crate::io::{manifest_path, read_composer_json, read_lock};
+use crate::models::model::{ComposerJson, Lock};
+use crate::platform::detect_platform;
+use crate::utils::{print_info, print_step, print_text, verbosity};
+use anyhow::{Result, anyhow};
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::Path;
 
-/// Show suggested packages
-pub async fn show_suggests(working_dir: &Path) -> Result<()> {
-    print_step("🔍 Checking for suggested packages...");
+/// A package suggested by a locked package
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    /// The package whose `suggest` lists it
+    pub from: String,
+    pub package: String,
+    pub reason: String,
+}
 
-    let lock_path = working_dir.join("composer.lock");
-    if !lock_path.exists() {
-        print_error("❌ No composer.lock found. Run 'lectern install' first.");
-        return Ok(());
+/// Suggestions of the locked packages, with dev packages only when `include_dev`. Packages
+/// that are already there are left out: locked packages and what they provide or replace,
+/// root requirements, and the platform packages in `platform`.
+pub fn collect_suggestions(
+    lock: &Lock,
+    composer: Option<&ComposerJson>,
+    include_dev: bool,
+    platform: &BTreeSet<String>,
+) -> Vec<Suggestion> {
+    let all_packages = || lock.packages.iter().chain(&lock.packages_dev);
+    let mut installed: BTreeSet<&str> = platform.iter().map(String::as_str).collect();
+    for pkg in all_packages() {
+        installed.insert(&pkg.name);
+        for provided in [&pkg.provide, &pkg.replace].into_iter().flatten() {
+            installed.extend(provided.keys().map(String::as_str));
+        }
+    }
+    if let Some(composer) = composer {
+        installed.extend(composer.require.keys().map(String::as_str));
+        installed.extend(composer.require_dev.keys().map(String::as_str));
     }
 
-    let lock = read_lock(&lock_path)?;
-    let mut suggestions: HashMap<String, Vec<(String, String)>> = HashMap::new();
-
-    // Collect suggestions from all packages
-    for pkg in lock.packages.iter().chain(lock.packages_dev.iter()) {
-        if let Some(suggest) = &pkg.suggest {
-            for (suggested_pkg, reason) in suggest.iter() {
-                suggestions
-                    .entry(suggested_pkg.clone())
-                    .or_default()
-                    .push((pkg.name.clone(), reason.clone()));
+    let packages = if include_dev {
+        all_packages().collect::<Vec<_>>()
+    } else {
+        lock.packages.iter().collect()
+    };
+    let mut suggestions = Vec::new();
+    for pkg in packages {
+        for (package, reason) in pkg.suggest.iter().flatten() {
+            // Package names are case-insensitive
+            if !installed.contains(package.to_ascii_lowercase().as_str()) {
+                suggestions.push(Suggestion {
+                    from: pkg.name.clone(),
+                    package: package.clone(),
+                    reason: reason.clone(),
+                });
             }
         }
     }
+    suggestions
+}
 
-    if suggestions.is_empty() {
-        print_info("No package suggestions found");
-    } else {
-        println!("\n💡 Suggested packages:");
-        for (pkg, reasons) in suggestions.iter() {
-            println!("\n  📦 {pkg}");
-            for (from_pkg, reason) in reasons {
-                println!("    • From {from_pkg}: {reason}");
+/// Output lines for `suggestions`: grouped by suggesting package, by suggested package,
+/// or both; `list` prints just the suggested names
+pub fn suggestion_lines(
+    suggestions: &[Suggestion],
+    by_package: bool,
+    by_suggestion: bool,
+    list: bool,
+) -> Vec<String> {
+    if list {
+        let names: BTreeSet<&str> = suggestions.iter().map(|s| s.package.as_str()).collect();
+        return names.into_iter().map(str::to_string).collect();
+    }
+
+    let mut lines = Vec::new();
+    let reason = |text: &str| {
+        if text.is_empty() {
+            String::new()
+        } else {
+            format!(": {text}")
+        }
+    };
+    if by_package || !by_suggestion {
+        let mut grouped: BTreeMap<&str, Vec<&Suggestion>> = BTreeMap::new();
+        for suggestion in suggestions {
+            grouped
+                .entry(&suggestion.from)
+                .or_default()
+                .push(suggestion);
+        }
+        for (from, suggestions) in grouped {
+            lines.push(format!("📦 {from} suggests:"));
+            for suggestion in suggestions {
+                lines.push(format!(
+                    "    • {}{}",
+                    suggestion.package,
+                    reason(&suggestion.reason)
+                ));
             }
+            lines.push(String::new());
+        }
+    }
+    if by_suggestion {
+        let mut grouped: BTreeMap<&str, Vec<&Suggestion>> = BTreeMap::new();
+        for suggestion in suggestions {
+            grouped
+                .entry(&suggestion.package)
+                .or_default()
+                .push(suggestion);
+        }
+        for (package, suggestions) in grouped {
+            lines.push(format!("💡 {package} is suggested by:"));
+            for suggestion in suggestions {
+                lines.push(format!(
+                    "    • {}{}",
+                    suggestion.from,
+                    reason(&suggestion.reason)
+                ));
+            }
+            lines.push(String::new());
+        }
+    }
+    lines
+}
+
+/// After an install, say how many suggestions the packages in `added` bring, listing them
+/// with `-v` as Composer does
+pub fn print_new_suggestions(lock: &Lock, composer: Option<&ComposerJson>, added: &BTreeSet<&str>) {
+    let new_suggestions = |platform: &BTreeSet<String>| -> Vec<Suggestion> {
+        collect_suggestions(lock, composer, true, platform)
+            .into_iter()
+            .filter(|s| added.contains(s.from.as_str()))
+            .collect()
+    };
+    // PHP is only asked about its extensions when there is something to filter
+    if new_suggestions(&BTreeSet::new()).is_empty() {
+        return;
+    }
+    let platform = detect_platform()
+        .map(|packages| packages.into_iter().map(|package| package.name).collect())
+        .unwrap_or_default();
+    let suggestions = new_suggestions(&platform);
+    if suggestions.is_empty() {
+        return;
+    }
+    print_info(&format!(
+        "💡 {} package suggestion(s) were added by new dependencies, use 'lectern suggests' to see details",
+        suggestions.len()
+    ));
+    if verbosity() > 0 {
+        print_text(
+            suggestion_lines(&suggestions, true, false, false)
+                .join("\n")
+                .trim_end(),
+        );
+    }
+}
+
+/// Show the packages locked packages suggest that aren't installed yet
+/// # Errors
+/// Returns an error if there is no lock file or it cannot be read
+pub async fn show_suggests(args: &SuggestsArgs, working_dir: &Path) -> Result<()> {
+    if !args.list {
+        print_step("🔍 Checking for suggested packages...");
+    }
+    let lock_path = working_dir.join("composer.lock");
+    if !lock_path.exists() {
+        return Err(anyhow!(
+            "No composer.lock found. Run 'lectern install' first."
+        ));
+    }
+    let lock = read_lock(&lock_path)?;
+    let composer = read_composer_json(&manifest_path(working_dir)).ok();
+    // Without PHP every ext-* suggestion is shown, which is the safe side
+    let platform = detect_platform()
+        .map(|packages| packages.into_iter().map(|package| package.name).collect())
+        .unwrap_or_default();
+    let suggestions = collect_suggestions(&lock, composer.as_ref(), args.all, &platform);
+
+    let lines = suggestion_lines(&suggestions, args.by_package, args.by_suggestion, args.list);
+    if args.list {
+        for line in lines {
+            println!("{line}");
         }
-        println!("\nRun 'lectern require <package>' to install any of these packages.");
+        return Ok(());
     }
 
+    if suggestions.is_empty() {
+        print_info("No package suggestions found");
+        return Ok(());
+    }
+    println!();
+    for line in lines {
+        println!("{line}");
+    }
+    let count = suggestions
+        .iter()
+        .map(|s| s.package.as_str())
+        .collect::<BTreeSet<_>>()
+        .len();
+    println!(
+        "{count} additional suggestion(s). Run 'lectern require <package>' to install any of them."
+    );
     Ok(())
 }
@@ -0,0 +1,217 @@
+use crate::autoload::{AutoloadOptions, lock_packages, write_autoload_files_with_options};
+use crate::cli::SyncArgs;
+use crate::commands::status::{PackageStatus, package_status};
+use crate::installer::installer_links::{package_dir, remove_dir_all};
+use crate::installer::installer_state::package_reference;
+use crate::installer::{InstallOptions, InstallState, install_packages_with_options};
+use crate::io::{manifest_path, read_composer_json, read_lock};
+use crate::models::model::{Lock, LockedPackage};
+use crate::utils::{print_info, print_step, print_success, print_text};
+use anyhow::{Result, anyhow};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Why a locked package has to be installed again
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncReason {
+    Missing,
+    /// Installed at another version or reference
+    Outdated,
+    /// Files differ from the dist archive or git reference
+    Modified,
+}
+
+impl fmt::Display for SyncReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Missing => "missing from vendor",
+            Self::Outdated => "installed version differs from composer.lock",
+            Self::Modified => "locally modified",
+        })
+    }
+}
+
+/// What it takes to make vendor match composer.lock
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncPlan {
+    pub reinstall: Vec<(String, SyncReason)>,
+    /// Package directories under vendor that composer.lock doesn't list
+    pub remove: Vec<PathBuf>,
+    pub regenerate_autoload: bool,
+}
+
+impl SyncPlan {
+    pub fn is_empty(&self) -> bool {
+        self.reinstall.is_empty() && self.remove.is_empty() && !self.regenerate_autoload
+    }
+}
+
+/// Whether the install state records this exact package. Packages without a
+/// reference (plain path repositories) are matched on version alone.
+fn recorded(state: &InstallState, pkg: &LockedPackage) -> bool {
+    state.packages.get(&pkg.name).is_some_and(|record| {
+        record.version == pkg.version
+            && (package_reference(pkg).is_none()
+                || record.reference.as_deref() == package_reference(pkg))
+    })
+}
+
+/// `vendor/<vendor>/<name>` directories (or symlinks) that are not locked packages.
+/// `vendor/bin` holds proxies and files in `vendor/composer` belong to the autoloader.
+fn extraneous_dirs(vendor: &Path, locked: &BTreeSet<String>) -> Result<Vec<PathBuf>> {
+    let mut extraneous = Vec::new();
+    let Ok(namespaces) = std::fs::read_dir(vendor) else {
+        return Ok(extraneous);
+    };
+    for namespace in namespaces {
+        let namespace = namespace?;
+        let namespace_name = namespace.file_name().to_string_lossy().to_string();
+        if namespace_name == "bin" || !namespace.file_type()?.is_dir() {
+            continue;
+        }
+        for entry in std::fs::read_dir(namespace.path())? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            if !file_type.is_dir() && !file_type.is_symlink() {
+                continue;
+            }
+            let name = format!("{namespace_name}/{}", entry.file_name().to_string_lossy());
+            if !locked.contains(&name.to_lowercase()) {
+                extraneous.push(entry.path());
+            }
+        }
+    }
+    extraneous.sort();
+    Ok(extraneous)
+}
+
+/// Compare composer.lock with vendor: the install state says what was installed and the
+/// integrity check (see `status`) whether it was changed since
+/// # Errors
+/// Returns an error if vendor cannot be read
+pub fn plan_sync(lock: &Lock, working_dir: &Path, include_dev: bool) -> Result<SyncPlan> {
+    let vendor = working_dir.join("vendor");
+    let state = InstallState::load(&vendor);
+    let dev: &[LockedPackage] = if include_dev { &lock.packages_dev } else { &[] };
+
+    let mut plan = SyncPlan::default();
+    for pkg in lock.packages.iter().chain(dev) {
+        let reason = if !vendor.join(&pkg.name).exists() {
+            Some(SyncReason::Missing)
+        } else if !recorded(&state, pkg) {
+            Some(SyncReason::Outdated)
+        } else {
+            match package_status(pkg, &vendor)? {
+                PackageStatus::Modified(_) => Some(SyncReason::Modified),
+                PackageStatus::NotInstalled => Some(SyncReason::Missing),
+                PackageStatus::Unchanged | PackageStatus::Unknown(_) => None,
+            }
+        };
+        if let Some(reason) = reason {
+            plan.reinstall.push((pkg.name.clone(), reason));
+        }
+    }
+
+    let locked: BTreeSet<String> = lock
+        .packages
+        .iter()
+        .chain(dev)
+        .map(|pkg| pkg.name.to_lowercase())
+        .collect();
+    plan.remove = extraneous_dirs(&vendor, &locked)?;
+    plan.regenerate_autoload = !plan.reinstall.is_empty()
+        || !plan.remove.is_empty()
+        || !vendor.join("autoload.php").exists();
+    Ok(plan)
+}
+
+/// Reinstall missing, outdated or modified packages, remove directories composer.lock
+/// doesn't list and regenerate the autoloader, reporting each change
+/// # Errors
+/// Returns an error if composer.lock cannot be read or a change fails
+pub async fn sync(args: &SyncArgs, working_dir: &Path) -> Result<()> {
+    let lock_path = working_dir.join("composer.lock");
+    if !lock_path.exists() {
+        return Err(anyhow!(
+            "No composer.lock found. Run 'lectern update' first."
+        ));
+    }
+    let lock = read_lock(&lock_path)?;
+    print_step("🔄 Comparing vendor with composer.lock...");
+    let plan = plan_sync(&lock, working_dir, !args.no_dev)?;
+
+    if plan.is_empty() {
+        print_success("✅ vendor already matches composer.lock");
+        return Ok(());
+    }
+    let relative = |path: &Path| {
+        path.strip_prefix(working_dir)
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    };
+    for (name, reason) in &plan.reinstall {
+        print_text(&format!("  reinstall {name} ({reason})"));
+    }
+    for path in &plan.remove {
+        print_text(&format!(
+            "  remove {} (not in composer.lock)",
+            relative(path)
+        ));
+    }
+    if plan.regenerate_autoload {
+        print_text("  regenerate autoload files");
+    }
+    if args.dry_run {
+        print_info("🔍 Dry run - no changes were made");
+        return Ok(());
+    }
+
+    let vendor = working_dir.join("vendor");
+    for path in &plan.remove {
+        remove_dir_all(path)?;
+        // Drop the vendor namespace directory once its last package is gone
+        if let Some(namespace) = path.parent()
+            && std::fs::read_dir(namespace).is_ok_and(|mut entries| entries.next().is_none())
+        {
+            let _ = std::fs::remove_dir(namespace);
+        }
+    }
+
+    let dev: &[LockedPackage] = if args.no_dev { &[] } else { &lock.packages_dev };
+    let locked: Vec<&LockedPackage> = lock.packages.iter().chain(dev).collect();
+    let reinstall: Vec<LockedPackage> = locked
+        .iter()
+        .filter(|pkg| plan.reinstall.iter().any(|(name, _)| *name == pkg.name))
+        .map(|pkg| (*pkg).clone())
+        .collect();
+    for pkg in &reinstall {
+        remove_dir_all(&package_dir(&vendor, &pkg.name))?;
+    }
+    if reinstall.is_empty() {
+        // Forget state records of removed packages
+        InstallState::load(&vendor).save(&vendor)?;
+    } else {
+        install_packages_with_options(&reinstall, working_dir, InstallOptions::default()).await?;
+    }
+
+    if plan.regenerate_autoload {
+        let composer = read_composer_json(&manifest_path(working_dir))?;
+        let installed = lock_packages(&lock, args.no_dev);
+        let options = AutoloadOptions::for_lock(&lock, args.no_dev);
+        write_autoload_files_with_options(working_dir, &composer, &installed, &options).await?;
+    }
+
+    print_success(&format!(
+        "✅ Synced vendor: {} reinstalled, {} removed{}",
+        plan.reinstall.len(),
+        plan.remove.len(),
+        if plan.regenerate_autoload {
+            ", autoload regenerated"
+        } else {
+            ""
+        }
+    ));
+    Ok(())
+}
@@ -0,0 +1,256 @@
+use crate::cli::ThirdPartyFileArgs;
+use crate::io::{manifest_path, read_lock};
+use crate::models::model::{Author, Lock, LockedPackage};
+use crate::utils::{print_info, print_step, print_success, print_warning};
+use anyhow::{Context, Result, anyhow};
+use std::path::{Path, PathBuf};
+
+/// File name prefixes that hold license or copyright text
+const LICENSE_FILE_PREFIXES: &[&str] = &["LICENSE", "LICENCE", "COPYING", "COPYRIGHT", "NOTICE"];
+
+const TEXT_TEMPLATE: &str = "\
+================================================================================
+{name} {version}
+License: {license}
+Authors: {authors}
+Homepage: {homepage}
+================================================================================
+
+{license_text}
+";
+
+const MARKDOWN_TEMPLATE: &str = "\
+## {name} {version}
+
+- License: {license}
+- Authors: {authors}
+- Homepage: {homepage}
+
+```
+{license_text}
+```
+";
+
+/// Output layout of the notices file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoticeFormat {
+    Text,
+    Markdown,
+}
+
+impl NoticeFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "text" | "txt" => Some(Self::Text),
+            "markdown" | "md" => Some(Self::Markdown),
+            _ => None,
+        }
+    }
+
+    fn default_template(self) -> &'static str {
+        match self {
+            Self::Text => TEXT_TEMPLATE,
+            Self::Markdown => MARKDOWN_TEMPLATE,
+        }
+    }
+
+    fn header(self, project: &str) -> String {
+        let intro = format!(
+            "{project} includes the following third-party packages. Their license and copyright notices are reproduced below."
+        );
+        match self {
+            Self::Text => format!("THIRD-PARTY NOTICES\n\n{intro}\n\n"),
+            Self::Markdown => format!("# Third-party notices\n\n{intro}\n\n"),
+        }
+    }
+}
+
+/// License and attribution details of one installed package
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoticeEntry {
+    pub name: String,
+    pub version: String,
+    pub licenses: Vec<String>,
+    pub authors: Vec<String>,
+    pub homepage: Option<String>,
+    /// (file name, contents) of each license file found in the package
+    pub license_texts: Vec<(String, String)>,
+}
+
+/// License, copying and notice files at the top level of a package, sorted by name
+pub fn find_license_files(package_dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(package_dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|path| {
+            path.is_file()
+                && path.file_name().is_some_and(|name| {
+                    let upper = name.to_string_lossy().to_ascii_uppercase();
+                    LICENSE_FILE_PREFIXES.iter().any(|p| upper.starts_with(p))
+                })
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+fn format_authors(authors: Option<&Vec<Author>>) -> Vec<String> {
+    authors
+        .into_iter()
+        .flatten()
+        .filter(|author| !author.name.is_empty())
+        .map(|author| match &author.email {
+            Some(email) => format!("{} <{email}>", author.name),
+            None => author.name.clone(),
+        })
+        .collect()
+}
+
+fn notice_entry(pkg: &LockedPackage, vendor: &Path) -> NoticeEntry {
+    let license_texts = find_license_files(&vendor.join(&pkg.name))
+        .into_iter()
+        .filter_map(|path| {
+            let content = std::fs::read_to_string(&path).ok()?;
+            let file_name = path.file_name()?.to_string_lossy().to_string();
+            Some((file_name, content.trim_end().to_string()))
+        })
+        .collect();
+
+    NoticeEntry {
+        name: pkg.name.clone(),
+        version: pkg.version.clone(),
+        licenses: pkg.license.clone().unwrap_or_default(),
+        authors: format_authors(pkg.authors.as_ref()),
+        homepage: pkg.homepage.clone(),
+        license_texts,
+    }
+}
+
+/// Gather notice entries for the locked packages, sorted by name
+pub fn collect_notice_entries(
+    lock: &Lock,
+    working_dir: &Path,
+    include_dev: bool,
+) -> Vec<NoticeEntry> {
+    let vendor = working_dir.join("vendor");
+    let dev: &[LockedPackage] = if include_dev { &lock.packages_dev } else { &[] };
+    let mut entries: Vec<NoticeEntry> = lock
+        .packages
+        .iter()
+        .chain(dev)
+        .map(|pkg| notice_entry(pkg, &vendor))
+        .collect();
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    entries
+}
+
+fn or_unknown(values: &[String]) -> String {
+    if values.is_empty() {
+        "Unknown".to_string()
+    } else {
+        values.join(", ")
+    }
+}
+
+/// Fill a template's `{name}`, `{version}`, `{license}`, `{authors}`, `{homepage}` and `{license_text}`
+pub fn render_entry(entry: &NoticeEntry, template: &str) -> String {
+    let license_text = if entry.license_texts.is_empty() {
+        "(no license file found in package)".to_string()
+    } else {
+        entry
+            .license_texts
+            .iter()
+            .map(|(_, text)| text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    };
+
+    // `{license_text}` goes last so placeholders inside license files stay untouched
+    template
+        .replace("{name}", &entry.name)
+        .replace("{version}", &entry.version)
+        .replace("{license}", &or_unknown(&entry.licenses))
+        .replace("{authors}", &or_unknown(&entry.authors))
+        .replace("{homepage}", entry.homepage.as_deref().unwrap_or("-"))
+        .replace("{license_text}", &license_text)
+}
+
+/// Render the whole notices document
+pub fn render_notices(
+    entries: &[NoticeEntry],
+    project: &str,
+    format: NoticeFormat,
+    template: Option<&str>,
+) -> String {
+    let template = template.unwrap_or(format.default_template());
+    let mut out = format.header(project);
+    for entry in entries {
+        out.push_str(&render_entry(entry, template));
+        out.push('\n');
+    }
+    out
+}
+
+/// Write a third-party notices file for the installed packages
+/// # Errors
+/// Returns an error if the lock file, template or output file cannot be read or written
+pub async fn third_party_file(args: &ThirdPartyFileArgs, working_dir: &Path) -> Result<()> {
+    let format = NoticeFormat::from_name(&args.format)
+        .ok_or_else(|| anyhow!("Unknown format: {}. Use: text or markdown", args.format))?;
+
+    let lock_path = working_dir.join("composer.lock");
+    if !lock_path.exists() {
+        return Err(anyhow!(
+            "No composer.lock found. Run 'lectern install' first."
+        ));
+    }
+    let lock = read_lock(&lock_path)?;
+
+    let template = args
+        .template
+        .as_ref()
+        .map(|path| {
+            std::fs::read_to_string(working_dir.join(path))
+                .with_context(|| format!("read template {path}"))
+        })
+        .transpose()?;
+
+    print_step("📜 Collecting license and copyright notices...");
+    let entries = collect_notice_entries(&lock, working_dir, args.dev);
+
+    let missing: Vec<&str> = entries
+        .iter()
+        .filter(|e| e.license_texts.is_empty())
+        .map(|e| e.name.as_str())
+        .collect();
+    if !missing.is_empty() {
+        print_warning(&format!(
+            "⚠️  No license file found for {} package(s): {}",
+            missing.len(),
+            missing.join(", ")
+        ));
+        print_info("Run 'lectern install' if vendor is missing or out of date");
+    }
+
+    let project = crate::io::read_composer_json(&manifest_path(working_dir))
+        .ok()
+        .and_then(|c| c.name)
+        .unwrap_or_else(|| "This project".to_string());
+
+    let output = working_dir.join(&args.output);
+    std::fs::write(
+        &output,
+        render_notices(&entries, &project, format, template.as_deref()),
+    )
+    .with_context(|| format!("write {}", output.display()))?;
+
+    print_success(&format!(
+        "✅ Wrote notices for {} packages to {}",
+        entries.len(),
+        args.output
+    ));
+    Ok(())
+}
@@ -0,0 +1,279 @@
+use crate::autoload::{AutoloadOptions, lock_packages, write_autoload_files_with_options};
+use crate::cli::WorkspaceInstallArgs;
+use crate::installer::installer_links::{link_dir, package_dir, remove_dir_all};
+use crate::installer::{InstallOptions, install_packages_with_options};
+use crate::io::{
+    manifest_path, read_composer_content_hash, read_composer_json, read_lock, write_lock,
+};
+use crate::lock_diff::{diff_locks, print_lock_diff};
+use crate::models::model::{Lock, LockedPackage};
+use crate::resolver::dependency_utils::generate_content_hash_from_composer;
+use crate::resolver::solve;
+use crate::utils::{print_info, print_success, print_text, print_warning};
+use crate::workspace::{
+    WorkspaceMember, combined_manifest, is_member_package, load_members, member_lock,
+};
+use anyhow::{Result, anyhow};
+use camino::Utf8PathBuf;
+use std::fmt;
+use std::path::Path;
+
+/// The lock of the whole workspace, next to the root manifest
+pub const WORKSPACE_LOCK: &str = "composer.workspace.lock";
+
+fn members_of(root: &Path) -> Result<Vec<WorkspaceMember>> {
+    let members = load_members(root)?;
+    if members.is_empty() {
+        return Err(anyhow!(
+            "No workspace members found. Each directory matched by extra.lectern.workspace.members needs a composer.json"
+        ));
+    }
+    Ok(members)
+}
+
+/// Make the member at `target` available as `name` in `vendor`, replacing what was there
+fn link_member(vendor: &Path, name: &str, target: &Path) -> Result<()> {
+    let link = package_dir(vendor, name);
+    remove_dir_all(&link)?;
+    if let Some(parent) = link.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    link_dir(target, &link)?;
+    Ok(())
+}
+
+/// Write a member's share of the workspace lock, install its packages, link the members
+/// it requires and generate its autoloader
+async fn install_member(
+    member: &WorkspaceMember,
+    members: &[WorkspaceMember],
+    lock: &Lock,
+    options: InstallOptions,
+) -> Result<()> {
+    write_lock(&member.dir.join("composer.lock"), lock)?;
+    let (linked, packages): (Vec<&LockedPackage>, Vec<&LockedPackage>) = lock
+        .packages
+        .iter()
+        .chain(&lock.packages_dev)
+        .partition(|package| is_member_package(package, members));
+    let packages: Vec<LockedPackage> = packages.into_iter().cloned().collect();
+    install_packages_with_options(&packages, &member.dir, options).await?;
+
+    let vendor = member.dir.join("vendor");
+    for package in &linked {
+        let Some(other) = members
+            .iter()
+            .find(|other| other.composer.name.as_deref() == Some(package.name.as_str()))
+        else {
+            continue;
+        };
+        link_member(&vendor, &package.name, &other.dir)?;
+    }
+
+    // Paths from `lock_packages` are relative to the working directory, not the member
+    let mut installed = lock_packages(lock, false);
+    for package in &mut installed {
+        package.path = Utf8PathBuf::from_path_buf(member.dir.join(package.path.as_std_path()))
+            .map_err(|path| anyhow!("Non UTF-8 path {path:?}"))?;
+    }
+    let options = AutoloadOptions::for_lock(lock, false);
+    write_autoload_files_with_options(&member.dir, &member.composer, &installed, &options).await?;
+    print_success(&format!(
+        "✅ {}: {} packages installed, {} linked",
+        member.label,
+        packages.len(),
+        linked.len()
+    ));
+    Ok(())
+}
+
+/// Resolve the requirements of every member together and install each member from the
+/// result: the workspace lock is written next to the root manifest, each member gets a
+/// composer.lock with the packages it needs, and members required by others are linked
+/// into their vendor dirs. Without `update`, an up to date workspace lock is installed as is.
+/// # Errors
+/// Returns an error if a manifest cannot be read, the members' requirements cannot be
+/// resolved together, or a member cannot be installed
+pub async fn workspace_install(
+    args: &WorkspaceInstallArgs,
+    root: &Path,
+    update: bool,
+) -> Result<()> {
+    let root = &std::path::absolute(root)?;
+    let members = members_of(root)?;
+    let combined = combined_manifest(read_composer_json(&manifest_path(root))?, &members)?;
+    let content_hash = generate_content_hash_from_composer(&combined);
+
+    let lock_path = root.join(WORKSPACE_LOCK);
+    let old = read_lock(&lock_path).ok();
+    let current = old
+        .as_ref()
+        .filter(|lock| !update && lock.content_hash == content_hash);
+    let lock = if let Some(lock) = current {
+        print_info(&format!("📦 Installing from {WORKSPACE_LOCK}"));
+        lock.clone()
+    } else {
+        print_info(&format!(
+            "🔗 Resolving {} workspace members together",
+            members.len()
+        ));
+        let mut lock = solve(&combined).await?;
+        lock.content_hash = content_hash;
+        lock
+    };
+    print_lock_diff(&diff_locks(old.as_ref(), &lock));
+
+    let mut member_locks = Vec::new();
+    for member in &members {
+        let mut lock = member_lock(&lock, &members, member);
+        lock.content_hash = read_composer_content_hash(&manifest_path(&member.dir))?;
+        member_locks.push(lock);
+    }
+    if args.dry_run {
+        let lines: Vec<String> = members
+            .iter()
+            .zip(&member_locks)
+            .map(|(member, lock)| {
+                format!(
+                    "  • {}: {} packages",
+                    member.label,
+                    lock.packages.len() + lock.packages_dev.len()
+                )
+            })
+            .collect();
+        print_text(&lines.join("\n"));
+        return Ok(());
+    }
+
+    write_lock(&lock_path, &lock)?;
+    let options = InstallOptions {
+        prefer_source: args.prefer_source,
+    };
+    for (member, lock) in members.iter().zip(&member_locks) {
+        install_member(member, &members, lock, options).await?;
+    }
+    print_success(&format!("✅ Installed {} workspace members", members.len()));
+    Ok(())
+}
+
+/// How a lock file relates to the manifest it was written for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockState {
+    Missing,
+    Stale,
+    Current,
+}
+
+impl LockState {
+    fn of(lock_path: &Path, content_hash: &str) -> Self {
+        match read_lock(lock_path) {
+            Err(_) => Self::Missing,
+            Ok(lock) if lock.content_hash == content_hash => Self::Current,
+            Ok(_) => Self::Stale,
+        }
+    }
+}
+
+impl fmt::Display for LockState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Missing => "missing",
+            Self::Stale => "out of date",
+            Self::Current => "up to date",
+        })
+    }
+}
+
+/// The state of one workspace member
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemberStatus {
+    pub label: String,
+    pub name: Option<String>,
+    pub lock: LockState,
+    /// Whether vendor/autoload.php exists
+    pub installed: bool,
+    /// The other members it requires
+    pub links: Vec<String>,
+}
+
+/// The state of the workspace lock, then of each member
+/// # Errors
+/// Returns an error if a manifest cannot be read
+pub fn workspace_statuses(root: &Path) -> Result<(LockState, Vec<MemberStatus>)> {
+    let members = members_of(root)?;
+    let combined = combined_manifest(read_composer_json(&manifest_path(root))?, &members)?;
+    let workspace = LockState::of(
+        &root.join(WORKSPACE_LOCK),
+        &generate_content_hash_from_composer(&combined),
+    );
+    let mut statuses = Vec::new();
+    for member in &members {
+        let composer = &member.composer;
+        let links = members
+            .iter()
+            .filter_map(|other| other.composer.name.clone())
+            .filter(|name| {
+                composer.require.contains_key(name) || composer.require_dev.contains_key(name)
+            })
+            .collect();
+        statuses.push(MemberStatus {
+            label: member.label.clone(),
+            name: composer.name.clone(),
+            lock: LockState::of(
+                &member.dir.join("composer.lock"),
+                &read_composer_content_hash(&manifest_path(&member.dir))?,
+            ),
+            installed: member.dir.join("vendor").join("autoload.php").exists(),
+            links,
+        });
+    }
+    Ok((workspace, statuses))
+}
+
+/// Print the state of the workspace lock and every member
+/// # Errors
+/// Returns an error if a manifest cannot be read
+/// # Returns
+/// `true` when every lock is up to date and every member is installed
+pub fn workspace_status(root: &Path) -> Result<bool> {
+    let (workspace, statuses) = workspace_statuses(root)?;
+    print_info(&format!(
+        "📦 Workspace of {} members, {WORKSPACE_LOCK} {workspace}",
+        statuses.len()
+    ));
+    let lines: Vec<String> = statuses
+        .iter()
+        .map(|status| {
+            let name = status
+                .name
+                .as_deref()
+                .map(|name| format!(" ({name})"))
+                .unwrap_or_default();
+            let links = if status.links.is_empty() {
+                String::new()
+            } else {
+                format!(", links {}", status.links.join(", "))
+            };
+            format!(
+                "  • {}{name}: composer.lock {}, {}{links}",
+                status.label,
+                status.lock,
+                if status.installed {
+                    "installed"
+                } else {
+                    "not installed"
+                }
+            )
+        })
+        .collect();
+    print_text(&lines.join("\n"));
+
+    let ok = workspace == LockState::Current
+        && statuses
+            .iter()
+            .all(|status| status.lock == LockState::Current && status.installed);
+    if !ok {
+        print_warning("⚠️  Run 'lectern workspace install' to bring the workspace up to date");
+    }
+    Ok(ok)
+}
@@ -0,0 +1,97 @@
+use crate::models::model::{ComposerJson, Config};
+use anyhow::{Context, Result, anyhow};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Constraints that replace the root ones when resolving for a named environment,
+/// declared under `extra.lectern.platform-overrides.<env>`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct EnvironmentOverride {
+    #[serde(default)]
+    pub platform: BTreeMap<String, String>,
+    #[serde(default)]
+    pub require: BTreeMap<String, String>,
+    #[serde(default, rename = "require-dev")]
+    pub require_dev: BTreeMap<String, String>,
+}
+
+/// Environments declared in composer.json, by name
+/// # Errors
+/// Returns an error if `extra.lectern.platform-overrides` is malformed
+pub fn environments(composer: &ComposerJson) -> Result<BTreeMap<String, EnvironmentOverride>> {
+    let Some(overrides) = composer
+        .extra
+        .as_ref()
+        .and_then(|extra| extra.pointer("/lectern/platform-overrides"))
+    else {
+        return Ok(BTreeMap::new());
+    };
+    serde_json::from_value(overrides.clone())
+        .context("extra.lectern.platform-overrides must map environment names to overrides")
+}
+
+/// Apply the named environment's platform and constraint overrides to composer.json
+/// # Errors
+/// Returns an error if the environment is not declared
+pub fn apply_environment(mut composer: ComposerJson, env: &str) -> Result<ComposerJson> {
+    let mut declared = environments(&composer)?;
+    let Some(overrides) = declared.remove(env) else {
+        let names: Vec<String> = declared.into_keys().collect();
+        return Err(anyhow!(
+            "Unknown environment '{env}'. Declared environments: {}",
+            if names.is_empty() {
+                "none".to_string()
+            } else {
+                names.join(", ")
+            }
+        ));
+    };
+
+    composer.require.extend(overrides.require);
+    composer.require_dev.extend(overrides.require_dev);
+    if !overrides.platform.is_empty() {
+        composer
+            .config
+            .get_or_insert_with(Config::default)
+            .platform
+            .get_or_insert_with(BTreeMap::new)
+            .extend(overrides.platform);
+    }
+    Ok(composer)
+}
+
+/// Select an environment if one was requested, otherwise keep composer.json as is
+/// # Errors
+/// Returns an error if the environment is not declared
+pub fn select_environment(composer: ComposerJson, env: Option<&str>) -> Result<ComposerJson> {
+    match env {
+        Some(env) => apply_environment(composer, env),
+        None => Ok(composer),
+    }
+}
+
+/// Lock file for an environment: `composer.<env>.lock`, or `composer.lock` by default
+pub fn environment_lock_path(working_dir: &Path, env: Option<&str>) -> PathBuf {
+    match env {
+        Some(env) => working_dir.join(format!("composer.{env}.lock")),
+        None => working_dir.join("composer.lock"),
+    }
+}
+
+/// Resolve every declared environment and report which ones cannot be solved
+/// # Errors
+/// Returns an error if composer.json cannot be read
+pub async fn check_environments(composer_path: &Path) -> Result<Vec<(String, Result<()>)>> {
+    let composer = crate::io::read_composer_json(composer_path)?;
+    let mut results = Vec::new();
+    for env in environments(&composer)?.into_keys() {
+        let composer = crate::io::read_composer_json(composer_path)?;
+        let result = match apply_environment(composer, &env) {
+            Ok(composer) => crate::resolver::solve(&composer).await.map(|_| ()),
+            Err(e) => Err(e),
+        };
+        results.push((env, result));
+    }
+    Ok(results)
+}
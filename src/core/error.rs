@@ -0,0 +1,58 @@
+use thiserror::Error;
+
+/// Failures callers may want to tell apart. Functions still return `anyhow::Result`, with
+/// these as the root error (or anywhere in the context chain) of the failures they name;
+/// find them with `err.downcast_ref::<LecternError>()` or `find_lectern_error`.
+#[derive(Debug, Error)]
+pub enum LecternError {
+    /// No set of versions satisfies the requirements
+    #[error("{0}")]
+    ResolutionConflict(String),
+    #[error("Package {name} not found{hint}")]
+    PackageNotFound {
+        name: String,
+        /// Extra text after the message, e.g. suggestions for similar names
+        hint: String,
+    },
+    /// A request failed to send or the server answered with an error status
+    #[error("Request to {url} failed: {source}")]
+    NetworkError {
+        url: String,
+        #[source]
+        source: reqwest::Error,
+    },
+    #[error("Checksum mismatch: expected {expected}, downloaded file has {actual}")]
+    ChecksumMismatch { expected: String, actual: String },
+    #[error("Invalid composer.json: {0}")]
+    InvalidComposerJson(#[source] serde_json::Error),
+}
+
+impl LecternError {
+    pub fn network(url: &str, source: reqwest::Error) -> Self {
+        Self::NetworkError {
+            url: url.to_string(),
+            source,
+        }
+    }
+
+    /// Process exit code for this failure, matching Composer's: 2 when dependencies cannot
+    /// be resolved, 100 for transport errors and 1 for everything else
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::ResolutionConflict(_) | Self::PackageNotFound { .. } => 2,
+            Self::NetworkError { .. } => 100,
+            Self::ChecksumMismatch { .. } | Self::InvalidComposerJson(_) => 1,
+        }
+    }
+}
+
+/// The first `LecternError` in the chain of `err`, including errors wrapped with context
+pub fn find_lectern_error(err: &anyhow::Error) -> Option<&LecternError> {
+    err.chain()
+        .find_map(|cause| cause.downcast_ref::<LecternError>())
+}
+
+/// Exit code for a failed command: the code of its `LecternError`, or 1
+pub fn exit_code(err: &anyhow::Error) -> i32 {
+    find_lectern_error(err).map_or(1, LecternError::exit_code)
+}
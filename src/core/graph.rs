@@ -0,0 +1,200 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use crate::models::model::{Lock, LockedPackage};
+use crate::resolver::is_platform_dependency;
+
+/// Requirements between locked packages. Composer allows cycles (`a` requires `b`
+/// requires `a`), so every walk over this graph has to expect them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DependencyGraph {
+    versions: BTreeMap<String, String>,
+    /// `(dependency, constraint)` pairs, only for dependencies that are locked
+    edges: BTreeMap<String, Vec<(String, String)>>,
+}
+
+impl DependencyGraph {
+    pub fn from_packages<'a>(packages: impl IntoIterator<Item = &'a LockedPackage>) -> Self {
+        let packages: Vec<&LockedPackage> = packages.into_iter().collect();
+        let versions: BTreeMap<String, String> = packages
+            .iter()
+            .map(|pkg| (pkg.name.clone(), pkg.version.clone()))
+            .collect();
+        let edges = packages
+            .iter()
+            .map(|pkg| {
+                let deps = pkg
+                    .require
+                    .iter()
+                    .flatten()
+                    .filter(|(dep, _)| !is_platform_dependency(dep) && versions.contains_key(*dep))
+                    .map(|(dep, constraint)| (dep.clone(), constraint.clone()))
+                    .collect();
+                (pkg.name.clone(), deps)
+            })
+            .collect();
+        Self { versions, edges }
+    }
+
+    pub fn from_lock(lock: &Lock) -> Self {
+        Self::from_packages(lock.packages.iter().chain(lock.packages_dev.iter()))
+    }
+
+    pub fn version(&self, name: &str) -> Option<&str> {
+        self.versions.get(name).map(String::as_str)
+    }
+
+    pub fn dependencies(&self, name: &str) -> &[(String, String)] {
+        self.edges.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Strongly connected components among `names` (Tarjan), dependencies before
+    /// dependents. Packages on a cycle share a component; everything else is alone.
+    pub fn components_within(&self, names: &BTreeSet<&str>) -> Vec<Vec<String>> {
+        struct Tarjan<'g> {
+            graph: &'g DependencyGraph,
+            names: &'g BTreeSet<&'g str>,
+            index: BTreeMap<&'g str, usize>,
+            low: BTreeMap<&'g str, usize>,
+            stack: Vec<&'g str>,
+            on_stack: BTreeSet<&'g str>,
+            components: Vec<Vec<String>>,
+        }
+
+        impl<'g> Tarjan<'g> {
+            fn visit(&mut self, node: &'g str) {
+                let index = self.index.len();
+                self.index.insert(node, index);
+                self.low.insert(node, index);
+                self.stack.push(node);
+                self.on_stack.insert(node);
+
+                for (dep, _) in self.graph.dependencies(node) {
+                    let Some(&dep) = self.names.get(dep.as_str()) else {
+                        continue;
+                    };
+                    if !self.index.contains_key(dep) {
+                        self.visit(dep);
+                        let low = self.low[node].min(self.low[dep]);
+                        self.low.insert(node, low);
+                    } else if self.on_stack.contains(dep) {
+                        let low = self.low[node].min(self.index[dep]);
+                        self.low.insert(node, low);
+                    }
+                }
+
+                if self.low[node] == self.index[node] {
+                    let mut component = Vec::new();
+                    while let Some(member) = self.stack.pop() {
+                        self.on_stack.remove(member);
+                        component.push(member.to_string());
+                        if member == node {
+                            break;
+                        }
+                    }
+                    component.sort();
+                    self.components.push(component);
+                }
+            }
+        }
+
+        let mut tarjan = Tarjan {
+            graph: self,
+            names,
+            index: BTreeMap::new(),
+            low: BTreeMap::new(),
+            stack: Vec::new(),
+            on_stack: BTreeSet::new(),
+            components: Vec::new(),
+        };
+        for &name in names {
+            if !tarjan.index.contains_key(name) {
+                tarjan.visit(name);
+            }
+        }
+        tarjan.components
+    }
+
+    /// Every dependency cycle, each as a path that starts and ends at its alphabetically
+    /// first package, e.g. `[a, b, a]`
+    pub fn cycles(&self) -> Vec<Vec<String>> {
+        let names: BTreeSet<&str> = self.versions.keys().map(String::as_str).collect();
+        let mut cycles: Vec<Vec<String>> = self
+            .components_within(&names)
+            .into_iter()
+            .filter_map(|component| {
+                let start = component.first()?;
+                let members: BTreeSet<&str> = component.iter().map(String::as_str).collect();
+                self.cycle_through(start, &members)
+            })
+            .collect();
+        cycles.sort();
+        cycles
+    }
+
+    /// Shortest path from `start` back to itself that stays inside `members`
+    fn cycle_through(&self, start: &str, members: &BTreeSet<&str>) -> Option<Vec<String>> {
+        let mut parent: BTreeMap<&str, &str> = BTreeMap::new();
+        let mut queue: VecDeque<&str> = VecDeque::from([start]);
+        while let Some(node) = queue.pop_front() {
+            for (dep, _) in self.dependencies(node) {
+                let dep = dep.as_str();
+                if !members.contains(dep) {
+                    continue;
+                }
+                if dep == start {
+                    let mut path = vec![start.to_string(), node.to_string()];
+                    let mut current = node;
+                    while let Some(&up) = parent.get(current) {
+                        path.push(up.to_string());
+                        current = up;
+                    }
+                    path.reverse();
+                    return Some(path);
+                }
+                if !parent.contains_key(dep) {
+                    parent.insert(dep, node);
+                    queue.push_back(dep);
+                }
+            }
+        }
+        None
+    }
+
+    /// `show --tree` lines for a package and everything it requires. A requirement that
+    /// leads back into the current branch is marked instead of expanded.
+    pub fn tree_lines(&self, root: &str) -> Vec<String> {
+        let mut lines = vec![
+            format!("{root} {}", self.version(root).unwrap_or(""))
+                .trim_end()
+                .to_string(),
+        ];
+        let mut branch = vec![root.to_string()];
+        self.push_children(root, "", &mut branch, &mut lines);
+        lines
+    }
+
+    fn push_children(
+        &self,
+        name: &str,
+        prefix: &str,
+        branch: &mut Vec<String>,
+        lines: &mut Vec<String>,
+    ) {
+        let deps = self.dependencies(name);
+        for (i, (dep, constraint)) in deps.iter().enumerate() {
+            let last = i + 1 == deps.len();
+            let connector = if last { "└──" } else { "├──" };
+            if branch.contains(dep) {
+                lines.push(format!(
+                    "{prefix}{connector}{dep} {constraint} (circular dependency aborted here)"
+                ));
+                continue;
+            }
+            lines.push(format!("{prefix}{connector}{dep} {constraint}"));
+            branch.push(dep.clone());
+            let child_prefix = format!("{prefix}{}", if last { "   " } else { "│  " });
+            self.push_children(dep, &child_prefix, branch, lines);
+            branch.pop();
+        }
+    }
+}
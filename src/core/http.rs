@@ -0,0 +1,217 @@
+use anyhow::{Context, Result};
+use reqwest::{Certificate, Client, RequestBuilder};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex, OnceLock};
+use std::time::Duration;
+
+use crate::core::auth::AuthConfig;
+use crate::core::proxy::ProxySettings;
+use crate::core::timeouts::{RequestKind, Timeouts, http_timeouts};
+
+/// Extra CA certificates to trust, from Composer's `config.cafile` and `config.capath`
+#[derive(Debug, Clone, Default)]
+pub struct TlsSettings {
+    pub certificates: Vec<Certificate>,
+}
+
+impl TlsSettings {
+    /// Read the PEM bundle at `cafile` and every `.pem`/`.crt` file in `capath`; relative
+    /// paths are resolved against `project_dir`
+    /// # Errors
+    /// Returns an error if a configured file cannot be read or holds no valid certificate
+    pub fn from_config(config: Option<&Value>, project_dir: &Path) -> Result<Self> {
+        let path = |key: &str| {
+            config
+                .and_then(|config| config.get(key))
+                .and_then(Value::as_str)
+                .filter(|path| !path.is_empty())
+                .map(|path| project_dir.join(path))
+        };
+
+        let mut files: Vec<PathBuf> = path("cafile").into_iter().collect();
+        if let Some(dir) = path("capath") {
+            let mut entries: Vec<PathBuf> = std::fs::read_dir(&dir)
+                .with_context(|| format!("read capath {dir:?}"))?
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.extension()
+                        .is_some_and(|ext| ext == "pem" || ext == "crt")
+                })
+                .collect();
+            entries.sort();
+            files.extend(entries);
+        }
+
+        let mut certificates = Vec::new();
+        for file in files {
+            let pem = std::fs::read(&file).with_context(|| format!("read CA file {file:?}"))?;
+            let bundle = Certificate::from_pem_bundle(&pem)
+                .with_context(|| format!("parse CA file {file:?}"))?;
+            if bundle.is_empty() {
+                return Err(anyhow::anyhow!("No certificates found in {file:?}"));
+            }
+            certificates.extend(bundle);
+        }
+        Ok(Self { certificates })
+    }
+}
+
+/// What every client is built from and every request is authenticated with
+#[derive(Debug, Clone, Default)]
+pub struct HttpSettings {
+    pub proxy: ProxySettings,
+    pub tls: TlsSettings,
+    pub auth: AuthConfig,
+}
+
+impl HttpSettings {
+    /// Settings for a project: proxies, CA certificates and credentials from its
+    /// composer.json, auth.json and the process environment
+    /// # Errors
+    /// Returns an error if configured CA certificates cannot be loaded
+    pub fn load(project_dir: &Path) -> Result<Self> {
+        let composer = crate::io::read_manifest_value(project_dir);
+        Ok(Self {
+            proxy: ProxySettings::load(project_dir),
+            tls: TlsSettings::from_config(
+                composer.as_ref().and_then(|json| json.get("config")),
+                project_dir,
+            )?,
+            auth: AuthConfig::from_env_with(
+                |name| std::env::var(name).ok(),
+                dirs::home_dir().as_deref(),
+                Some(project_dir),
+            ),
+        })
+    }
+}
+
+static SETTINGS: OnceLock<HttpSettings> = OnceLock::new();
+
+/// Use these settings for the rest of the run (set once from the CLI, before any request)
+pub fn configure(settings: HttpSettings) {
+    let _ = SETTINGS.set(settings);
+}
+
+/// The process-wide settings, read from the environment if `configure` wasn't called
+pub fn http_settings() -> &'static HttpSettings {
+    SETTINGS.get_or_init(|| HttpSettings {
+        proxy: ProxySettings::from_env_with(|name| std::env::var(name).ok(), None),
+        tls: TlsSettings::default(),
+        auth: AuthConfig::from_env(),
+    })
+}
+
+/// Shared HTTP client with optimized connection pooling and settings
+/// This provides better performance for concurrent requests
+pub static HTTP_CLIENT: LazyLock<Client> = LazyLock::new(|| {
+    build_client(
+        RequestKind::Metadata,
+        http_timeouts().metadata,
+        http_settings(),
+    )
+});
+
+/// Clients for hosts with their own timeouts, one per kind and timeout pair
+static CLIENTS: LazyLock<Mutex<HashMap<(RequestKind, Timeouts), Client>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Build a client for one kind of request. Everything that talks HTTP goes through here,
+/// so pools, proxies and trusted certificates are the same for the resolver and installer.
+pub fn build_client(kind: RequestKind, timeouts: Timeouts, settings: &HttpSettings) -> Client {
+    // Proxies come from our settings only, which already include the proxy env vars
+    let proxy = settings.proxy.reqwest_proxy();
+    let proxied = proxy.is_some();
+    let mut builder = Client::builder().no_proxy();
+    if let Some(proxy) = proxy {
+        builder = builder.proxy(proxy);
+    }
+    for certificate in &settings.tls.certificates {
+        builder = builder.add_root_certificate(certificate.clone());
+    }
+    let builder = builder
+        .connect_timeout(timeouts.connect)
+        .read_timeout(timeouts.read)
+        .tcp_keepalive(Duration::from_secs(60))
+        .tcp_nodelay(true) // Disable Nagle's algorithm for lower latency
+        .http2_keep_alive_interval(Duration::from_secs(30));
+    let builder = match kind {
+        RequestKind::Metadata => builder
+            .pool_max_idle_per_host(100) // Increase connection pool size for better concurrency
+            .pool_idle_timeout(Duration::from_secs(90))
+            .http2_adaptive_window(true) // Use HTTP/2 when available, fallback to HTTP/1.1
+            .http2_keep_alive_timeout(Duration::from_secs(20))
+            .http2_keep_alive_while_idle(true)
+            .gzip(true)
+            .brotli(true)
+            .deflate(true)
+            .user_agent("Lectern/0.1.0 (Rust; async)"),
+        RequestKind::Download => {
+            let builder = builder
+                .user_agent("lectern/0.1")
+                .pool_idle_timeout(Duration::from_secs(300))
+                .pool_max_idle_per_host(num_cpus::get() * 8)
+                .connection_verbose(false);
+            // Proxies commonly only speak HTTP/1.1, so HTTP/2 is negotiated through them
+            if proxied {
+                builder
+            } else {
+                builder.http2_prior_knowledge() // Force HTTP/2 for better multiplexing
+            }
+        }
+    };
+    builder.build().expect("Failed to build HTTP client")
+}
+
+/// Get the shared HTTP client instance
+pub fn get_client() -> &'static Client {
+    &HTTP_CLIENT
+}
+
+/// Client for a request to `url`, honouring per-host timeout overrides
+pub fn client_for(url: &str, kind: RequestKind) -> Client {
+    let timeouts = http_timeouts().for_url(url, kind);
+    if kind == RequestKind::Metadata && timeouts == http_timeouts().metadata {
+        return get_client().clone();
+    }
+    let mut clients = CLIENTS.lock().unwrap_or_else(|e| e.into_inner());
+    clients
+        .entry((kind, timeouts))
+        .or_insert_with(|| build_client(kind, timeouts, http_settings()))
+        .clone()
+}
+
+/// Add the credentials `auth` holds for the host of `url`, the way Composer sends them:
+/// http-basic as basic auth, GitHub tokens as `token`, GitLab OAuth and bearer tokens as
+/// `Bearer`, and GitLab personal tokens in `PRIVATE-TOKEN`
+pub fn authenticate_with(auth: &AuthConfig, request: RequestBuilder, url: &str) -> RequestBuilder {
+    let Some(host) = crate::core::auth::url_host(url) else {
+        return request;
+    };
+    if let Some(basic) = auth.http_basic.get(&host) {
+        request.basic_auth(&basic.username, Some(&basic.password))
+    } else if let Some(token) = auth.github_oauth.get(&host) {
+        request.header("Authorization", format!("token {token}"))
+    } else if let Some(token) = auth.gitlab_oauth.get(&host) {
+        request.bearer_auth(token)
+    } else if let Some((_, token)) = auth.gitlab_token.get(&host) {
+        request.header("PRIVATE-TOKEN", token)
+    } else if let Some(token) = auth.bearer.get(&host) {
+        request.bearer_auth(token)
+    } else {
+        request
+    }
+}
+
+/// `authenticate_with` using the configured credentials
+pub fn authenticate(request: RequestBuilder, url: &str) -> RequestBuilder {
+    authenticate_with(&http_settings().auth, request, url)
+}
+
+/// An authenticated GET request for `url` on the client for its host and kind
+pub fn get(url: &str, kind: RequestKind) -> RequestBuilder {
+    authenticate(client_for(url, kind).get(url), url)
+}
@@ -0,0 +1,72 @@
+use crate::io::{manifest_path, read_composer_json};
+use crate::models::model::ComposerJson;
+use crate::workspace::segment_matches;
+use anyhow::Result;
+use std::path::Path;
+
+/// Packages held back from `outdated` reports and `update`, given as names or `*` globs
+/// such as `symfony/*`, from `extra.lectern.ignore` and `--ignore`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IgnoreList {
+    patterns: Vec<String>,
+}
+
+impl IgnoreList {
+    pub fn new<S: AsRef<str>>(patterns: &[S]) -> Self {
+        Self {
+            patterns: patterns
+                .iter()
+                .map(|pattern| pattern.as_ref().trim().to_lowercase())
+                .filter(|pattern| !pattern.is_empty())
+                .collect(),
+        }
+    }
+
+    /// The patterns of `extra.lectern.ignore`, followed by `extra_patterns`
+    pub fn from_composer(composer: &ComposerJson, extra_patterns: &[String]) -> Self {
+        let mut patterns: Vec<String> = composer
+            .extra
+            .as_ref()
+            .and_then(|extra| extra.pointer("/lectern/ignore"))
+            .and_then(|ignore| ignore.as_array())
+            .map(|ignore| {
+                ignore
+                    .iter()
+                    .filter_map(|p| p.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+        patterns.extend_from_slice(extra_patterns);
+        Self::new(&patterns)
+    }
+
+    /// The ignore list of the project in `dir`; without a manifest, only `extra_patterns`
+    /// # Errors
+    /// Returns an error if the manifest exists but cannot be read
+    pub fn for_project(dir: &Path, extra_patterns: &[String]) -> Result<Self> {
+        let path = manifest_path(dir);
+        if !path.exists() {
+            return Ok(Self::new(extra_patterns));
+        }
+        Ok(Self::from_composer(
+            &read_composer_json(&path)?,
+            extra_patterns,
+        ))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
+    /// Whether a package name is ignored; names are compared case-insensitively
+    pub fn matches(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        self.patterns
+            .iter()
+            .any(|pattern| segment_matches(pattern, &name))
+    }
+}
@@ -0,0 +1,105 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::core::installer::installer_utils::get_package_cache_dir;
+
+/// Size the package archive cache may grow to when `cache-files-maxsize` is not set
+pub const DEFAULT_CACHE_FILES_MAXSIZE: u64 = 2 * 1024 * 1024 * 1024;
+
+/// Archives removed by one eviction pass
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EvictionReport {
+    pub removed: Vec<PathBuf>,
+    pub freed_bytes: u64,
+}
+
+/// Parse a Composer size such as `300MiB`, `2G`, `512k` or `1048576` (units are binary)
+pub fn parse_cache_size(value: &str) -> Option<u64> {
+    let value = value.trim().to_ascii_lowercase();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let multiplier = match unit.trim() {
+        "" | "b" => 1u64,
+        "k" | "kb" | "kib" => 1024,
+        "m" | "mb" | "mib" => 1024 * 1024,
+        "g" | "gb" | "gib" => 1024 * 1024 * 1024,
+        _ => return None,
+    };
+    Some((number * multiplier as f64) as u64)
+}
+
+/// `config.cache-files-maxsize` from the project's composer.json, or the default
+pub fn cache_files_maxsize(project_dir: &Path) -> u64 {
+    crate::io::read_manifest_value(project_dir)
+        .and_then(|json| match json.pointer("/config/cache-files-maxsize")? {
+            serde_json::Value::String(s) => parse_cache_size(s),
+            serde_json::Value::Number(n) => n.as_u64(),
+            _ => None,
+        })
+        .unwrap_or(DEFAULT_CACHE_FILES_MAXSIZE)
+}
+
+/// Mark an archive as just used so eviction keeps it longest
+pub fn touch_archive(path: &Path) {
+    if let Ok(file) = std::fs::File::options().write(true).open(path) {
+        let _ = file.set_modified(SystemTime::now());
+    }
+}
+
+/// Remove least recently used archives until the cache fits in `max_bytes`.
+/// Partial downloads (`.tmp` and their `.validator`) and download locks are left alone.
+/// # Errors
+/// Returns an error if the cache directory cannot be read
+pub fn evict_archives(cache_dir: &Path, max_bytes: u64) -> Result<EvictionReport> {
+    let mut report = EvictionReport::default();
+    if !cache_dir.exists() {
+        return Ok(report);
+    }
+
+    let mut archives = Vec::new();
+    let mut total = 0u64;
+    for entry in std::fs::read_dir(cache_dir)?.flatten() {
+        let path = entry.path();
+        if path
+            .extension()
+            .is_some_and(|ext| ext == "tmp" || ext == "validator" || ext == "lock")
+        {
+            continue;
+        }
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        if !meta.is_file() {
+            continue;
+        }
+        let used = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        total += meta.len();
+        archives.push((used, meta.len(), path));
+    }
+
+    archives.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.2.cmp(&b.2)));
+    for (_, size, path) in archives {
+        if total <= max_bytes {
+            break;
+        }
+        if std::fs::remove_file(&path).is_ok() {
+            total -= size;
+            report.freed_bytes += size;
+            report.removed.push(path);
+        }
+    }
+
+    Ok(report)
+}
+
+/// Trim the package archive cache on a background thread
+pub fn spawn_archive_eviction(project_dir: &Path) {
+    let max_bytes = cache_files_maxsize(project_dir);
+    tokio::task::spawn_blocking(move || {
+        let _ = evict_archives(&get_package_cache_dir(), max_bytes);
+    });
+}
@@ -0,0 +1,172 @@
+use anyhow::{Result, anyhow};
+use reqwest::header::HeaderMap;
+use std::time::Duration;
+
+use crate::auth::url_host;
+use crate::core::error::LecternError;
+use crate::core::http::{authenticate, http_settings};
+use crate::core::installer::installer_urls::dist_url_rewrites;
+use crate::core::throttle::throttle;
+
+/// Retries of a rate limited download before giving up
+const MAX_RATE_LIMIT_RETRIES: usize = 3;
+/// Longest wait for a rate limit to reset; longer waits fail with a hint instead
+const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60);
+
+/// Hosts serving GitHub archives (API zipballs redirect to codeload)
+pub fn is_github_host(url: &str) -> bool {
+    matches!(
+        url_host(url).as_deref(),
+        Some("github.com" | "api.github.com" | "codeload.github.com")
+    )
+}
+
+/// `github-oauth` token for github.com from the run's HTTP settings
+fn github_token() -> Option<&'static str> {
+    http_settings()
+        .auth
+        .github_oauth
+        .get("github.com")
+        .map(String::as_str)
+}
+
+/// Rewrite a `codeload.github.com/<owner>/<repo>/legacy.zip/<ref>` URL to the API
+/// zipball, which counts against the token's rate limit rather than the IP's
+pub fn api_zipball_url(url: &str) -> Option<String> {
+    let path = url.strip_prefix("https://codeload.github.com/")?;
+    let mut parts = path.splitn(4, '/');
+    let (owner, repo, kind, reference) =
+        (parts.next()?, parts.next()?, parts.next()?, parts.next()?);
+    (kind == "legacy.zip" && !reference.is_empty())
+        .then(|| format!("https://api.github.com/repos/{owner}/{repo}/zipball/{reference}"))
+}
+
+/// How long to wait before retrying a rate limited response, from `Retry-After` or
+/// GitHub's `X-RateLimit-Reset`. `None` if the response is not a rate limit.
+pub fn rate_limit_wait(status: u16, headers: &HeaderMap, now: u64) -> Option<Duration> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    let retry_after = header("retry-after").and_then(|v| v.trim().parse::<u64>().ok());
+    let exhausted = header("x-ratelimit-remaining").is_some_and(|v| v.trim() == "0");
+
+    let limited = status == 429 || (status == 403 && (exhausted || retry_after.is_some()));
+    if !limited {
+        return None;
+    }
+    if let Some(seconds) = retry_after {
+        return Some(Duration::from_secs(seconds));
+    }
+    let reset = header("x-ratelimit-reset").and_then(|v| v.trim().parse::<u64>().ok());
+    Some(Duration::from_secs(
+        reset.map_or(60, |reset| reset.saturating_sub(now)),
+    ))
+}
+
+/// Byte ranges refer to the encoded body, so a resumed download asks for the archive as stored
+fn accept_encoding(offset: u64) -> &'static str {
+    if offset > 0 {
+        "identity"
+    } else {
+        "gzip, deflate, br, zstd"
+    }
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn rate_limit_error(url: &str, wait: Duration, authenticated: bool) -> anyhow::Error {
+    let hint = if authenticated || !is_github_host(url) {
+        String::new()
+    } else {
+        "\n   Add a GitHub token under \"github-oauth\" in auth.json (or COMPOSER_AUTH) to raise the limit."
+            .to_string()
+    };
+    anyhow!(
+        "Rate limit exceeded downloading {url} (resets in {}s){hint}",
+        wait.as_secs()
+    )
+}
+
+/// Request a dist archive, authenticating GitHub downloads with the configured token and
+/// waiting out rate limits that reset soon. Other downloads go through the dist URL
+/// rewrites, which send GitHub API archives to codeload.
+/// # Errors
+/// Returns an error if the request fails, the server answers with an error status, or
+/// the rate limit does not reset within a minute
+pub async fn send_download(client: &reqwest::Client, url: &str) -> Result<reqwest::Response> {
+    send_download_from(client, url, 0, None).await
+}
+
+/// Like `send_download`, asking for the bytes from `offset` on when it is non-zero. The
+/// range is sent with `If-Range: <validator>`, the ETag or Last-Modified of the response
+/// the partial file came from, so a changed archive comes back whole. The server may also
+/// ignore the range; check for `206 Partial Content`.
+/// # Errors
+/// Returns an error if the request fails, the server answers with an error status, or
+/// the rate limit does not reset within a minute
+pub async fn send_download_from(
+    client: &reqwest::Client,
+    url: &str,
+    offset: u64,
+    validator: Option<&str>,
+) -> Result<reqwest::Response> {
+    let mut offset = offset;
+    let token = is_github_host(url).then(github_token).flatten();
+    let request_url = match token {
+        Some(_) => api_zipball_url(url).unwrap_or_else(|| url.to_string()),
+        None => dist_url_rewrites()
+            .rewrite(url)
+            .unwrap_or_else(|| url.to_string()),
+    };
+
+    let mut attempt = 0;
+    loop {
+        let mut request = client
+            .get(&request_url)
+            .header("Accept-Encoding", accept_encoding(offset))
+            .header("Accept", "*/*")
+            .header("Connection", "keep-alive");
+        match token {
+            Some(token) => request = request.header("Authorization", format!("token {token}")),
+            // Private mirrors and dist hosts get their auth.json credentials
+            None => request = authenticate(request, &request_url),
+        }
+        if offset > 0 {
+            request = request.header("Range", format!("bytes={offset}-"));
+            if let Some(validator) = validator {
+                request = request.header("If-Range", validator);
+            }
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| LecternError::network(&request_url, e))?;
+        throttle().report_status(url, response.status().as_u16());
+
+        if offset > 0 && response.status() == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+            // The partial file doesn't match what the server has; start over
+            offset = 0;
+            continue;
+        }
+
+        let Some(wait) =
+            rate_limit_wait(response.status().as_u16(), response.headers(), unix_now())
+        else {
+            return Ok(response
+                .error_for_status()
+                .map_err(|e| LecternError::network(&request_url, e))?);
+        };
+        if attempt >= MAX_RATE_LIMIT_RETRIES || wait > MAX_RATE_LIMIT_WAIT {
+            return Err(rate_limit_error(url, wait, token.is_some()));
+        }
+        attempt += 1;
+        crate::core::utils::print_warning(&format!(
+            "Rate limited downloading {url}, retrying in {}s",
+            wait.as_secs()
+        ));
+        tokio::time::sleep(wait).await;
+    }
+}
@@ -1,13 +1,21 @@
 use anyhow::Result;
 use futures::StreamExt;
+use sha1::Digest;
 use std::path::Path;
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
 use tokio::sync::Semaphore;
 use tokio::task;
 
+use crate::core::error::LecternError;
+use crate::core::installer::installer_cache::touch_archive;
+use crate::core::installer::installer_github;
 use crate::core::installer::installer_utils as inst_utils;
+use crate::core::profiler::{Phase, record_package};
+use crate::core::throttle::throttle;
+use crate::models::model::DistInfo;
 
 const DOWNLOAD_CHUNK_SIZE: usize = 65536; // 64 KB
 const STREAMING_THRESHOLD: usize = 1024 * 1024; // 1 MB
@@ -16,8 +24,157 @@ pub fn get_cached_package_path(name: &str, version: &str, url: &str) -> std::pat
     inst_utils::get_cached_package_path(name, version, url)
 }
 
+/// The `.lock` file that marks an archive as being downloaded, removed when dropped so
+/// no early return leaves later installs waiting for a download that stopped
+struct DownloadLock(std::path::PathBuf);
+
+impl Drop for DownloadLock {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Write a downloaded archive to the cache atomically
+async fn persist_archive(bytes: impl AsRef<[u8]>, cache_path: std::path::PathBuf) -> Result<()> {
+    let temp_path = cache_path.with_extension("tmp");
+    fs::write(&temp_path, bytes.as_ref()).await?;
+    fs::rename(&temp_path, &cache_path).await?;
+    Ok(())
+}
+
+/// Start of the byte range in a `Content-Range: bytes <start>-<end>/<total>` header
+pub fn content_range_start(value: &str) -> Option<u64> {
+    let range = value.trim().strip_prefix("bytes ")?;
+    range.split('-').next()?.trim().parse().ok()
+}
+
+/// What identifies the archive a response carries, for `If-Range` on a later resume: a
+/// strong ETag, or else Last-Modified. Weak ETags can't be used with ranges.
+pub fn entity_validator(headers: &reqwest::header::HeaderMap) -> Option<String> {
+    let header = |name| headers.get(name).and_then(|v| v.to_str().ok());
+    header(reqwest::header::ETAG)
+        .filter(|etag| !etag.starts_with("W/"))
+        .or_else(|| header(reqwest::header::LAST_MODIFIED))
+        .map(str::to_string)
+}
+
+/// Check archive bytes against the lock's dist `shasum`, their SHA-1. An empty shasum,
+/// which Packagist sends for most GitHub archives, is not checked.
+fn check_shasum(bytes: &[u8], shasum: &str) -> Result<(), LecternError> {
+    if shasum.is_empty() {
+        return Ok(());
+    }
+    let actual = hex::encode(sha1::Sha1::digest(bytes));
+    if actual.eq_ignore_ascii_case(shasum) {
+        Ok(())
+    } else {
+        Err(LecternError::ChecksumMismatch {
+            expected: shasum.to_string(),
+            actual,
+        })
+    }
+}
+
+/// Check an archive file against the lock's dist `shasum`, like `check_shasum`
+/// # Errors
+/// Returns an error if the archive cannot be read, or `LecternError::ChecksumMismatch`
+pub async fn verify_archive_shasum(archive: &Path, shasum: &str) -> Result<()> {
+    if shasum.is_empty() {
+        return Ok(());
+    }
+    let bytes = fs::read(archive).await?;
+    Ok(check_shasum(&bytes, shasum)?)
+}
+
+/// Refuse an archive whose checksum differs, dropping what was extracted or prepared for
+/// it; callers also drop the cached copy so neither this install nor the next uses it
+async fn reject_archive(err: impl Into<anyhow::Error>, target: &Path) -> anyhow::Error {
+    let _ = fs::remove_dir_all(target).await;
+    err.into()
+}
+
+/// Offset the response body continues from: the partial length if the server honoured
+/// the range request, otherwise 0 (the whole archive is being sent)
+fn resumed_offset(response: &reqwest::Response, partial_len: u64) -> u64 {
+    let continues = partial_len > 0
+        && response.status() == reqwest::StatusCode::PARTIAL_CONTENT
+        && response
+            .headers()
+            .get(reqwest::header::CONTENT_RANGE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(content_range_start)
+            == Some(partial_len);
+    if continues { partial_len } else { 0 }
+}
+
+/// Stream a response body into `temp_path`, appending after `resume_from` bytes
+async fn stream_to_file(
+    response: reqwest::Response,
+    temp_path: &Path,
+    resume_from: u64,
+    total_size: Option<u64>,
+    package_name: &str,
+) -> Result<()> {
+    // Stream directly to cache with larger buffer for better throughput
+    let mut cache_file = if resume_from > 0 {
+        fs::OpenOptions::new().append(true).open(temp_path).await?
+    } else {
+        fs::File::create(temp_path).await?
+    };
+    let mut buffer = Vec::with_capacity(DOWNLOAD_CHUNK_SIZE);
+
+    let mut stream = response.bytes_stream();
+    let mut downloaded = resume_from;
+
+    while let Some(chunk_result) = stream.next().await {
+        let chunk = match chunk_result {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                // Whatever arrived is kept so the download can resume
+                cache_file.write_all(&buffer).await?;
+                cache_file.flush().await?;
+                return Err(e.into());
+            }
+        };
+        downloaded += chunk.len() as u64;
+
+        // Write with vectorized I/O for better performance
+        buffer.extend_from_slice(&chunk);
+
+        if buffer.len() >= DOWNLOAD_CHUNK_SIZE {
+            cache_file.write_all(&buffer).await?;
+            buffer.clear();
+        }
+
+        // Progress for large files (suppressed in plain/CI output for deterministic logs)
+        if let Some(total) = total_size
+            && total > STREAMING_THRESHOLD as u64
+            && !crate::core::utils::plain_output()
+        {
+            let percent = (downloaded as f64 / total as f64 * 100.0) as u32;
+            if downloaded.is_multiple_of((total / 10).max(1)) {
+                // Report every 10%
+                crate::core::utils::print_info(&format!("📥 {package_name}: {percent}%"));
+            }
+        }
+    }
+
+    // Write remaining buffer
+    if !buffer.is_empty() {
+        cache_file.write_all(&buffer).await?;
+    }
+
+    cache_file.flush().await?;
+    Ok(())
+}
+
+/// Download a dist archive into the cache, resuming an interrupted download, and extract
+/// it into `target` once its `shasum` checks out
+/// # Errors
+/// Returns an error if the download or extraction fails, or
+/// `LecternError::ChecksumMismatch` if the archive isn't the one the lock names
 pub async fn download_and_extract_streaming(
-    url: &str,
+    dist: &DistInfo,
     target: &Path,
     client: reqwest::Client,
     net_sem: Arc<Semaphore>,
@@ -25,6 +182,7 @@ pub async fn download_and_extract_streaming(
     package_name: &str,
     package_version: &str,
 ) -> Result<()> {
+    let url = dist.url.as_str();
     let cache_path = get_cached_package_path(package_name, package_version, url);
 
     // Create cache directory if it doesn't exist
@@ -39,85 +197,111 @@ pub async fn download_and_extract_streaming(
             .map(|m| m.len() > 0)
             .unwrap_or(false);
 
-    if !cache_exists {
+    // Set when this call finished an interrupted download
+    let mut resumed = false;
+
+    if cache_exists {
+        // Keep recently used archives out of reach of cache eviction
+        touch_archive(&cache_path);
+    } else {
         // Use a lock file to prevent concurrent downloads
         let lock_path = cache_path.with_extension("lock");
-        
+
         // Try to create lock file atomically
         let lock_created = tokio::fs::OpenOptions::new()
             .write(true)
             .create_new(true)
             .open(&lock_path)
             .await;
-        
+
         match lock_created {
             Ok(_lock_file) => {
                 // We got the lock, proceed with download
+                let _lock = DownloadLock(lock_path);
                 let _net_guard = net_sem.acquire_owned().await?;
+                let _host_guard = throttle().acquire(url).await;
 
                 // Double-check if file was created while we were waiting
                 if cache_path.exists() {
-                    let _ = fs::remove_file(&lock_path).await;
                     return Ok(());
                 }
+                let download_started = Instant::now();
 
-                // Ultra-optimized download with connection reuse and compression
-                let response = client
-                    .get(url)
-                    .header("Accept-Encoding", "gzip, deflate, br, zstd")
-                    .header("Accept", "*/*")
-                    .header("Connection", "keep-alive")
-                    .send()
-                    .await?
-                    .error_for_status()?;
-
-                let total_size = response.content_length();
-
-                // Stream directly to cache with larger buffer for better throughput
+                // A partial download left by an interrupted run is resumed where it stopped,
+                // as long as it is known which version of the archive it holds
                 let temp_path = cache_path.with_extension("tmp");
-                let mut cache_file = fs::File::create(&temp_path).await?;
-                let mut buffer = Vec::with_capacity(DOWNLOAD_CHUNK_SIZE);
+                let validator_path = cache_path.with_extension("validator");
+                let validator = fs::read_to_string(&validator_path).await.ok();
+                let partial_len = match validator {
+                    Some(_) => fs::metadata(&temp_path).await.map_or(0, |m| m.len()),
+                    None => 0,
+                };
 
-        let mut stream = response.bytes_stream();
-        let mut downloaded = 0u64;
+                let response = installer_github::send_download_from(
+                    &client,
+                    url,
+                    partial_len,
+                    validator.as_deref(),
+                )
+                .await?;
 
-        while let Some(chunk_result) = stream.next().await {
-            let chunk = chunk_result?;
-            downloaded += chunk.len() as u64;
+                let resume_from = resumed_offset(&response, partial_len);
+                if resume_from == 0 {
+                    // Starting over: remember which archive the new partial file belongs to
+                    match entity_validator(response.headers()) {
+                        Some(validator) => fs::write(&validator_path, validator).await.ok(),
+                        None => fs::remove_file(&validator_path).await.ok(),
+                    };
+                }
+                let total_size = response.content_length().map(|len| len + resume_from);
 
-            // Write with vectorized I/O for better performance
-            buffer.extend_from_slice(&chunk);
+                // Small archives are extracted straight from memory while the cache copy is written
+                if resume_from == 0
+                    && total_size.is_some_and(|size| size <= STREAMING_THRESHOLD as u64)
+                {
+                    let bytes = response.bytes().await?;
+                    drop(_net_guard);
+                    drop(_host_guard);
+                    record_package(package_name, Phase::Download, download_started.elapsed());
+                    let _ = fs::remove_file(&validator_path).await;
+                    if let Err(e) = check_shasum(&bytes, &dist.shasum) {
+                        return Err(reject_archive(e, target).await);
+                    }
+                    let persist = tokio::spawn(persist_archive(bytes.clone(), cache_path.clone()));
 
-            if buffer.len() >= DOWNLOAD_CHUNK_SIZE {
-                cache_file.write_all(&buffer).await?;
-                buffer.clear();
-            }
+                    let _extract_guard = extract_sem.acquire_owned().await?;
+                    let target = target.to_path_buf();
+                    let extract_started = Instant::now();
+                    let extracted = task::spawn_blocking(move || -> Result<()> {
+                        inst_utils::extract_archive_from_bytes(&bytes, &target)
+                    })
+                    .await?;
+                    record_package(package_name, Phase::Extraction, extract_started.elapsed());
 
-            // Progress for large files
-            if let Some(total) = total_size {
-                if total > STREAMING_THRESHOLD as u64 {
-                    let percent = (downloaded as f64 / total as f64 * 100.0) as u32;
-                    if downloaded % (total / 10).max(1) == 0 {
-                        // Report every 10%
-                        crate::core::utils::print_info(&format!("📥 {package_name}: {percent}%"));
+                    // The download lock is only released once the cache copy is complete
+                    let persisted = persist.await?;
+                    if extracted.is_err() {
+                        // Don't hand the archive that failed to extract to the next install
+                        let _ = fs::remove_file(&cache_path).await;
                     }
+                    extracted?;
+                    return persisted;
                 }
-            }
-        }
 
-        // Write remaining buffer
-        if !buffer.is_empty() {
-            cache_file.write_all(&buffer).await?;
-        }
-
-        cache_file.flush().await?;
-        drop(cache_file);
+                if resume_from > 0 {
+                    resumed = true;
+                    crate::core::utils::print_info(&format!(
+                        "📥 {package_name}: resuming download at {} KB",
+                        resume_from / 1024
+                    ));
+                }
+                // On failure the partial file is kept for the next attempt
+                stream_to_file(response, &temp_path, resume_from, total_size, package_name).await?;
 
-        // Atomic rename
-        fs::rename(&temp_path, &cache_path).await?;
-        
-        // Remove lock file
-        let _ = fs::remove_file(&lock_path).await;
+                // Atomic rename
+                fs::rename(&temp_path, &cache_path).await?;
+                let _ = fs::remove_file(&validator_path).await;
+                record_package(package_name, Phase::Download, download_started.elapsed());
             }
             Err(_) => {
                 // Another thread is downloading, wait for it to finish
@@ -129,21 +313,34 @@ pub async fn download_and_extract_streaming(
                 }
                 // If still not exists after waiting, return error
                 if !cache_path.exists() {
-                    return Err(anyhow::anyhow!("Failed to download package: timeout waiting for concurrent download"));
+                    return Err(anyhow::anyhow!(
+                        "Failed to download package: timeout waiting for concurrent download"
+                    ));
                 }
             }
         }
     }
 
+    if let Err(e) = verify_archive_shasum(&cache_path, &dist.shasum).await {
+        let _ = fs::remove_file(&cache_path).await;
+        return Err(reject_archive(e, target).await);
+    }
+
     // Parallel extraction with semaphore limiting
     let _extract_guard = extract_sem.acquire_owned().await?;
     let target = target.to_path_buf();
     let cache_path_clone = cache_path.clone();
 
-    task::spawn_blocking(move || -> Result<()> {
+    let extract_started = Instant::now();
+    let extracted = task::spawn_blocking(move || -> Result<()> {
         inst_utils::extract_archive_ultra_fast(&cache_path_clone, &target)
     })
-    .await??;
+    .await?;
+    record_package(package_name, Phase::Extraction, extract_started.elapsed());
+    if extracted.is_err() && resumed {
+        // The archive changed on the server since the partial download; start over next time
+        let _ = fs::remove_file(&cache_path).await;
+    }
 
-    Ok(())
+    extracted
 }
@@ -0,0 +1,259 @@
+// Filesystem operations that behave differently on Windows: links, bin proxies,
+// long paths and removing trees git left read-only
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+/// Paths at least this long need the `\\?\` prefix for the Win32 file APIs
+const MAX_PATH: usize = 260;
+
+/// How `link_entry` and `link_dir` made a path available
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    Symlink,
+    /// A directory junction, which unlike a symlink needs no privileges on Windows
+    Junction,
+    Copy,
+    /// A file holding the link target, for links whose target doesn't exist
+    Placeholder,
+}
+
+/// The `\\?\` form of an absolute Windows path: `C:\dir` becomes `\\?\C:\dir` and
+/// `\\server\share` becomes `\\?\UNC\server\share`. Returns nothing for paths that are
+/// relative or already verbatim.
+pub fn verbatim_path(path: &str) -> Option<String> {
+    if path.starts_with(r"\\?\") || path.starts_with(r"\\.\") {
+        return None;
+    }
+    // Verbatim paths skip normalisation, so separators have to be backslashes already
+    let path = path.replace('/', "\\");
+    if let Some(unc) = path.strip_prefix(r"\\") {
+        return Some(format!(r"\\?\UNC\{unc}"));
+    }
+    let bytes = path.as_bytes();
+    let is_drive_path =
+        bytes.len() >= 3 && bytes[0].is_ascii_alphabetic() && &bytes[1..3] == b":\\";
+    is_drive_path.then(|| format!(r"\\?\{path}"))
+}
+
+/// `path` in a form the OS accepts however long it is. On Windows long paths are made
+/// absolute and verbatim; everywhere else, and for short paths, `path` is returned as is.
+pub fn long_path(path: &Path) -> PathBuf {
+    if !cfg!(windows) || path.as_os_str().len() < MAX_PATH {
+        return path.to_path_buf();
+    }
+    let Ok(absolute) = std::path::absolute(path) else {
+        return path.to_path_buf();
+    };
+    verbatim_path(&absolute.to_string_lossy()).map_or(absolute, PathBuf::from)
+}
+
+/// Where package `name` is installed in `vendor`, in a form that works however deep
+/// `vendor` is
+pub fn package_dir(vendor: &Path, name: &str) -> PathBuf {
+    long_path(&vendor.join(name.replace('/', std::path::MAIN_SEPARATOR_STR)))
+}
+
+/// Recreate the archive symlink `link` pointing at `target` (relative to the link's
+/// directory). Where symlinks can't be created, as on Windows without developer mode,
+/// directories become junctions and files copies; a target that doesn't exist is written
+/// into a file at `link`, like git does.
+/// # Errors
+/// Returns an error if neither a link nor its fallback can be created
+pub fn link_entry(target: &Path, link: &Path) -> Result<LinkKind> {
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(target, link).with_context(|| format!("link {link:?}"))?;
+        Ok(LinkKind::Symlink)
+    }
+    #[cfg(not(unix))]
+    {
+        let resolved = link.parent().unwrap_or(Path::new("")).join(target);
+        if resolved.is_dir() {
+            link_dir(&resolved, link)
+        } else if resolved.is_file() {
+            #[cfg(windows)]
+            if std::os::windows::fs::symlink_file(target, link).is_ok() {
+                return Ok(LinkKind::Symlink);
+            }
+            std::fs::copy(long_path(&resolved), long_path(link))
+                .with_context(|| format!("copy {resolved:?} to {link:?}"))?;
+            Ok(LinkKind::Copy)
+        } else {
+            std::fs::write(long_path(link), target.to_string_lossy().as_bytes())
+                .with_context(|| format!("write {link:?}"))?;
+            Ok(LinkKind::Placeholder)
+        }
+    }
+}
+
+/// Make the directory `target` available at `link`: a symlink where possible, then a
+/// junction on Windows, and a copy as the last resort
+/// # Errors
+/// Returns an error if `target` can't be linked or copied
+pub fn link_dir(target: &Path, link: &Path) -> Result<LinkKind> {
+    #[cfg(unix)]
+    if std::os::unix::fs::symlink(target, link).is_ok() {
+        return Ok(LinkKind::Symlink);
+    }
+    #[cfg(windows)]
+    {
+        if std::os::windows::fs::symlink_dir(target, link).is_ok() {
+            return Ok(LinkKind::Symlink);
+        }
+        if create_junction(target, link).is_ok() {
+            return Ok(LinkKind::Junction);
+        }
+    }
+    let mut options = fs_extra::dir::CopyOptions::new();
+    options.copy_inside = true;
+    fs_extra::dir::copy(long_path(target), long_path(link), &options)
+        .map_err(|e| anyhow::anyhow!("copy {target:?} to {link:?}: {e}"))?;
+    Ok(LinkKind::Copy)
+}
+
+#[cfg(windows)]
+fn create_junction(target: &Path, link: &Path) -> Result<()> {
+    // Junctions need absolute targets and are created by cmd's builtin mklink
+    let target = std::path::absolute(target)?;
+    let status = std::process::Command::new("cmd")
+        .arg("/C")
+        .arg("mklink")
+        .arg("/J")
+        .arg(link)
+        .arg(&target)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .context("run mklink")?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("mklink /J {link:?} failed"));
+    }
+    Ok(())
+}
+
+/// Whether `path` is a PHP script, which proxies run with `php`, rather than a native
+/// executable or a shell script
+pub fn is_php_script(path: &Path) -> bool {
+    let mut head = [0u8; 128];
+    let Ok(read) =
+        std::fs::File::open(path).and_then(|mut file| std::io::Read::read(&mut file, &mut head))
+    else {
+        return false;
+    };
+    let head = String::from_utf8_lossy(&head[..read]);
+    let first_line = head.lines().next().unwrap_or("");
+    path.extension()
+        .is_some_and(|ext| ext == "php" || ext == "phar")
+        || first_line.starts_with("<?php")
+        || (first_line.starts_with("#!") && first_line.contains("php"))
+}
+
+/// A `.bat` proxy running `target` with php, or directly for other executables
+pub fn bat_proxy(target: &Path, php: bool) -> String {
+    let run = if php {
+        "php \"%BIN_TARGET%\" %*"
+    } else {
+        "\"%BIN_TARGET%\" %*"
+    };
+    format!(
+        "@ECHO OFF\r\nsetlocal DISABLEDELAYEDEXPANSION\r\nSET BIN_TARGET={}\r\n{run}\r\n",
+        target.display()
+    )
+}
+
+/// A shell proxy for `target`, for Git Bash and other POSIX shells on Windows
+pub fn sh_proxy(target: &Path, php: bool) -> String {
+    let target = target.to_string_lossy().replace('\\', "/");
+    let run = if php { "php " } else { "" };
+    format!("#!/usr/bin/env sh\nexec {run}\"{target}\" \"$@\"\n")
+}
+
+/// Make the binary `target` runnable as `link`: an executable symlink on Unix, and on
+/// Windows a `link.bat` proxy for cmd and PowerShell next to a shell proxy at `link`
+/// # Errors
+/// Returns an error if the link or proxies cannot be written
+pub fn write_bin_proxy(target: &Path, link: &Path) -> Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut permissions = std::fs::metadata(target)?.permissions();
+        permissions.set_mode(permissions.mode() | 0o111);
+        std::fs::set_permissions(target, permissions)?;
+        std::os::unix::fs::symlink(target, link).with_context(|| format!("link {link:?}"))
+    }
+    #[cfg(not(unix))]
+    {
+        let php = is_php_script(target);
+        // Append rather than replace the extension, so `tool.phar` gets `tool.phar.bat`
+        let mut bat = link.as_os_str().to_owned();
+        bat.push(".bat");
+        std::fs::write(&bat, bat_proxy(target, php)).with_context(|| format!("write {bat:?}"))?;
+        std::fs::write(link, sh_proxy(target, php)).with_context(|| format!("write {link:?}"))
+    }
+}
+
+/// Remove the directory tree at `path`. Links and junctions are removed without touching
+/// what they point at, and read-only files (git objects on Windows) are made writable
+/// when they block the removal. A missing `path` is not an error.
+/// # Errors
+/// Returns an error if the tree cannot be removed
+pub fn remove_dir_all(path: &Path) -> Result<()> {
+    let path = long_path(path);
+    let Ok(meta) = std::fs::symlink_metadata(&path) else {
+        return Ok(());
+    };
+    if meta.is_symlink() || is_junction(&meta) {
+        // Directory links are directories to Windows and files everywhere else
+        return std::fs::remove_file(&path)
+            .or_else(|_| std::fs::remove_dir(&path))
+            .with_context(|| format!("remove {path:?}"));
+    }
+    if !meta.is_dir() {
+        return std::fs::remove_file(&path).with_context(|| format!("remove {path:?}"));
+    }
+    match std::fs::remove_dir_all(&path) {
+        Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+            clear_readonly(&path);
+            std::fs::remove_dir_all(&path).with_context(|| format!("remove {path:?}"))
+        }
+        result => result.with_context(|| format!("remove {path:?}")),
+    }
+}
+
+/// `remove_dir_all` on the blocking pool
+/// # Errors
+/// Returns an error if the tree cannot be removed
+pub async fn remove_dir_all_async(path: &Path) -> Result<()> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || remove_dir_all(&path)).await?
+}
+
+#[cfg(windows)]
+fn is_junction(meta: &std::fs::Metadata) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    const FILE_ATTRIBUTE_REPARSE_POINT: u32 = 0x400;
+    meta.file_attributes() & FILE_ATTRIBUTE_REPARSE_POINT != 0
+}
+
+#[cfg(not(windows))]
+fn is_junction(_meta: &std::fs::Metadata) -> bool {
+    false
+}
+
+#[allow(clippy::permissions_set_readonly_false)]
+fn clear_readonly(dir: &Path) {
+    for entry in walkdir::WalkDir::new(dir)
+        .follow_links(false)
+        .into_iter()
+        .flatten()
+    {
+        if let Ok(meta) = entry.path().symlink_metadata()
+            && !meta.is_symlink()
+            && meta.permissions().readonly()
+        {
+            let mut permissions = meta.permissions();
+            permissions.set_readonly(false);
+            let _ = std::fs::set_permissions(entry.path(), permissions);
+        }
+    }
+}
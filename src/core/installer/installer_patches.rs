@@ -0,0 +1,483 @@
+//! Patches from `extra.patches`, in the format of cweagans/composer-patches: package names
+//! mapped to `{"description": "file or URL"}`, or to a list of `{"description", "url"}`
+//! objects. `extra.patches-file` names a JSON file holding the same `patches` map.
+//! Patches are unified diffs applied in order after a package is extracted; their hashes
+//! are kept in the install state, so a package is installed again when its patches change.
+//! Patches from URLs are downloaded once and cached by URL, so checking an installed
+//! package's patches doesn't go to the network.
+
+use crate::core::cache_utils::get_cache_dir;
+use crate::installer::InstallState;
+use crate::installer::installer_links::package_dir;
+use crate::models::model::LockedPackage;
+use crate::timeouts::RequestKind;
+use crate::utils;
+use anyhow::{Context, Result, anyhow};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+/// A patch for one package, as listed in composer.json
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Patch {
+    pub description: String,
+    /// A URL, or a path relative to the project
+    pub url: String,
+}
+
+/// A patch with its contents
+#[derive(Debug, Clone)]
+pub struct LoadedPatch {
+    pub patch: Patch,
+    pub contents: String,
+    /// sha256 of the contents, recorded once the patch is applied
+    pub hash: String,
+}
+
+fn parse_patch_list(package: &str, value: &Value) -> Result<Vec<Patch>> {
+    let invalid = || anyhow!("Invalid patches for {package} in extra.patches");
+    match value {
+        Value::Object(map) => map
+            .iter()
+            .map(|(description, url)| {
+                Ok(Patch {
+                    description: description.clone(),
+                    url: url.as_str().ok_or_else(invalid)?.to_string(),
+                })
+            })
+            .collect(),
+        Value::Array(list) => list
+            .iter()
+            .map(|entry| {
+                let url = entry
+                    .get("url")
+                    .and_then(Value::as_str)
+                    .ok_or_else(invalid)?;
+                let description = entry
+                    .get("description")
+                    .and_then(Value::as_str)
+                    .unwrap_or(url);
+                Ok(Patch {
+                    description: description.to_string(),
+                    url: url.to_string(),
+                })
+            })
+            .collect(),
+        _ => Err(invalid()),
+    }
+}
+
+fn parse_patch_map(value: &Value, patches: &mut BTreeMap<String, Vec<Patch>>) -> Result<()> {
+    let Some(map) = value.as_object() else {
+        return Err(anyhow!("extra.patches must map package names to patches"));
+    };
+    for (package, list) in map {
+        patches
+            .entry(package.to_ascii_lowercase())
+            .or_default()
+            .extend(parse_patch_list(package, list)?);
+    }
+    Ok(())
+}
+
+/// The patches of the root's `extra.patches` and `extra.patches-file`, by lowercased
+/// package name
+/// # Errors
+/// Returns an error if the patches are malformed or the patches file cannot be read
+pub fn patches_from_extra(
+    extra: Option<&Value>,
+    project_dir: &Path,
+) -> Result<BTreeMap<String, Vec<Patch>>> {
+    let mut patches = BTreeMap::new();
+    let Some(extra) = extra else {
+        return Ok(patches);
+    };
+    if let Some(map) = extra.get("patches") {
+        parse_patch_map(map, &mut patches)?;
+    }
+    if let Some(file) = extra.get("patches-file").and_then(Value::as_str) {
+        let path = project_dir.join(file);
+        let text = std::fs::read_to_string(&path).with_context(|| format!("read {path:?}"))?;
+        let value: Value =
+            serde_json::from_str(&text).with_context(|| format!("parse {path:?}"))?;
+        parse_patch_map(value.get("patches").unwrap_or(&Value::Null), &mut patches)?;
+    }
+    Ok(patches)
+}
+
+/// Where a patch downloaded from `url` is cached
+pub fn cached_patch_path(url: &str) -> PathBuf {
+    get_cache_dir().join("patches").join(format!(
+        "{}.patch",
+        hex::encode(Sha256::digest(url.as_bytes()))
+    ))
+}
+
+/// The patch at `url`, from the cache when it was downloaded before
+async fn download_patch(url: &str) -> Result<String> {
+    let cached = cached_patch_path(url);
+    if let Ok(contents) = std::fs::read_to_string(&cached) {
+        return Ok(contents);
+    }
+    let contents = crate::http::get(url, RequestKind::Download)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .with_context(|| format!("download patch {url}"))?
+        .text()
+        .await?;
+    // A cache that can't be written only costs a download next time
+    if let Some(parent) = cached.parent()
+        && std::fs::create_dir_all(parent).is_ok()
+    {
+        let _ = std::fs::write(&cached, &contents);
+    }
+    Ok(contents)
+}
+
+/// Read or download the contents of each patch
+/// # Errors
+/// Returns an error if a patch file cannot be read or downloaded
+pub async fn load_patches(patches: &[Patch], project_dir: &Path) -> Result<Vec<LoadedPatch>> {
+    let mut loaded = Vec::new();
+    for patch in patches {
+        let contents = if patch.url.starts_with("http://") || patch.url.starts_with("https://") {
+            download_patch(&patch.url).await?
+        } else {
+            let path = project_dir.join(&patch.url);
+            std::fs::read_to_string(&path).with_context(|| format!("read patch {path:?}"))?
+        };
+        loaded.push(LoadedPatch {
+            hash: hex::encode(Sha256::digest(contents.as_bytes())),
+            patch: patch.clone(),
+            contents,
+        });
+    }
+    Ok(loaded)
+}
+
+/// The loaded patches of `packages`, by package name; packages without patches are left out
+/// # Errors
+/// Returns an error if the patches are malformed or cannot be read or downloaded
+pub async fn package_patches(
+    extra: Option<&Value>,
+    project_dir: &Path,
+    packages: &[LockedPackage],
+) -> Result<BTreeMap<String, Vec<LoadedPatch>>> {
+    let patches = patches_from_extra(extra, project_dir)?;
+    let mut loaded = BTreeMap::new();
+    for package in packages {
+        if let Some(list) = patches.get(&package.name.to_ascii_lowercase()) {
+            loaded.insert(package.name.clone(), load_patches(list, project_dir).await?);
+        }
+    }
+    Ok(loaded)
+}
+
+/// The hashes recorded for a package installed with `patches`
+pub fn patch_hashes(patches: Option<&Vec<LoadedPatch>>) -> Vec<String> {
+    patches
+        .into_iter()
+        .flatten()
+        .map(|patch| patch.hash.clone())
+        .collect()
+}
+
+/// Apply the patches of each freshly installed package and record their hashes. A package
+/// whose patch fails is forgotten, so the next install extracts it again.
+/// # Errors
+/// Returns an error naming the first patch that doesn't apply
+pub fn apply_package_patches(
+    installed: &[&LockedPackage],
+    patches: &BTreeMap<String, Vec<LoadedPatch>>,
+    vendor: &Path,
+    state: &mut InstallState,
+) -> Result<()> {
+    for package in installed {
+        let Some(list) = patches.get(&package.name) else {
+            continue;
+        };
+        let dir = package_dir(vendor, &package.name);
+        for loaded in list {
+            if let Err(e) = apply_patch(&dir, &loaded.contents) {
+                state.packages.remove(&package.name);
+                return Err(e.context(format!(
+                    "Could not apply patch \"{}\" ({}) to {}",
+                    loaded.patch.description, loaded.patch.url, package.name
+                )));
+            }
+            utils::print_info(&format!(
+                "🩹 Applied patch to {}: {}",
+                package.name, loaded.patch.description
+            ));
+        }
+        state.record_patches(&package.name, patch_hashes(Some(list)));
+    }
+    Ok(())
+}
+
+#[derive(Debug)]
+enum HunkLine {
+    Context(String),
+    Remove(String),
+    Add(String),
+}
+
+#[derive(Debug)]
+struct Hunk {
+    /// First line of the old side, counted from 1
+    old_start: usize,
+    lines: Vec<HunkLine>,
+    /// `\ No newline at end of file` follows the last line of the new side
+    new_missing_newline: bool,
+}
+
+/// The changes to one file; `None` paths are `/dev/null`
+#[derive(Debug)]
+struct FilePatch {
+    old: Option<String>,
+    new: Option<String>,
+    hunks: Vec<Hunk>,
+}
+
+fn header_path(line: &str) -> Option<String> {
+    // A tab separates an optional timestamp
+    let path = line.split('\t').next().unwrap_or(line).trim_end();
+    (path != "/dev/null").then(|| path.to_string())
+}
+
+/// `-a,b` or `+a` of a hunk header as (start, count)
+fn hunk_range(range: &str) -> Option<(usize, usize)> {
+    let range = &range[1..];
+    Some(match range.split_once(',') {
+        Some((start, count)) => (start.parse().ok()?, count.parse().ok()?),
+        None => (range.parse().ok()?, 1),
+    })
+}
+
+fn parse_unified_diff(text: &str) -> Result<Vec<FilePatch>> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut files: Vec<FilePatch> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        if let Some(old) = line.strip_prefix("--- ")
+            && let Some(new) = lines.get(i + 1).and_then(|l| l.strip_prefix("+++ "))
+        {
+            files.push(FilePatch {
+                old: header_path(old),
+                new: header_path(new),
+                hunks: Vec::new(),
+            });
+            i += 2;
+            continue;
+        }
+        let Some(header) = line.strip_prefix("@@ ") else {
+            i += 1;
+            continue;
+        };
+        let file = files
+            .last_mut()
+            .ok_or_else(|| anyhow!("Hunk without a file header: {line}"))?;
+        let mut ranges = header.split_whitespace();
+        let (Some((old_start, mut old_left)), Some((_, mut new_left))) = (
+            ranges.next().and_then(hunk_range),
+            ranges.next().and_then(hunk_range),
+        ) else {
+            return Err(anyhow!("Invalid hunk header: {line}"));
+        };
+        let mut hunk = Hunk {
+            old_start,
+            lines: Vec::new(),
+            new_missing_newline: false,
+        };
+        i += 1;
+        while (old_left > 0 || new_left > 0) && i < lines.len() {
+            let line = lines[i];
+            let rest = line.get(1..).unwrap_or_default().to_string();
+            match line.as_bytes().first() {
+                // Some tools drop the space of empty context lines
+                Some(b' ') | None => {
+                    hunk.lines.push(HunkLine::Context(rest));
+                    old_left = old_left.saturating_sub(1);
+                    new_left = new_left.saturating_sub(1);
+                }
+                Some(b'-') => {
+                    hunk.lines.push(HunkLine::Remove(rest));
+                    old_left = old_left.saturating_sub(1);
+                }
+                Some(b'+') => {
+                    hunk.lines.push(HunkLine::Add(rest));
+                    new_left = new_left.saturating_sub(1);
+                }
+                Some(b'\\') => {}
+                _ => return Err(anyhow!("Unexpected line in hunk: {line}")),
+            }
+            i += 1;
+        }
+        if lines.get(i).is_some_and(|l| l.starts_with('\\'))
+            && !matches!(hunk.lines.last(), Some(HunkLine::Remove(_)))
+        {
+            hunk.new_missing_newline = true;
+            i += 1;
+        }
+        file.hunks.push(hunk);
+    }
+    if files.is_empty() {
+        return Err(anyhow!("No changes found; is it a unified diff?"));
+    }
+    Ok(files)
+}
+
+/// `path` without its first `strip` components, refusing to leave the package directory
+fn strip_path(path: &str, strip: usize) -> Option<PathBuf> {
+    let components: Vec<&str> = path.split('/').filter(|c| !c.is_empty()).collect();
+    let stripped: PathBuf = components.get(strip..)?.iter().collect();
+    let inside = stripped
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)));
+    (inside && !stripped.as_os_str().is_empty()).then_some(stripped)
+}
+
+fn same_line(file: &str, patch: &str) -> bool {
+    file.trim_end_matches('\r') == patch.trim_end_matches('\r')
+}
+
+/// Apply the hunks to `original`, each where its old lines are found nearest to where the
+/// header puts them
+fn apply_hunks(original: &str, hunks: &[Hunk]) -> Result<String> {
+    let crlf = original.contains("\r\n");
+    let mut ends_with_newline = original.is_empty() || original.ends_with('\n');
+    let mut lines: Vec<String> = original.lines().map(str::to_string).collect();
+    let mut offset: isize = 0;
+    for (index, hunk) in hunks.iter().enumerate() {
+        let old: Vec<&str> = hunk
+            .lines
+            .iter()
+            .filter_map(|line| match line {
+                HunkLine::Context(text) | HunkLine::Remove(text) => Some(text.as_str()),
+                HunkLine::Add(_) => None,
+            })
+            .collect();
+        let new: Vec<String> = hunk
+            .lines
+            .iter()
+            .filter_map(|line| match line {
+                HunkLine::Context(text) | HunkLine::Add(text) => Some(text.clone()),
+                HunkLine::Remove(_) => None,
+            })
+            .collect();
+        // An empty old side starts after line `old_start`
+        let start = if old.is_empty() {
+            hunk.old_start
+        } else {
+            hunk.old_start.saturating_sub(1)
+        };
+        let expected = start.saturating_add_signed(offset).min(lines.len());
+        let matches_at = |at: usize| {
+            at + old.len() <= lines.len()
+                && old
+                    .iter()
+                    .zip(&lines[at..])
+                    .all(|(patch, file)| same_line(file, patch))
+        };
+        let found = (0..=lines.len()).find_map(|distance| {
+            [expected.checked_sub(distance), Some(expected + distance)]
+                .into_iter()
+                .flatten()
+                .find(|&at| matches_at(at))
+        });
+        let Some(at) = found else {
+            return Err(anyhow!("hunk {} does not apply", index + 1));
+        };
+        let reaches_end = at + old.len() == lines.len();
+        lines.splice(at..at + old.len(), new.iter().cloned());
+        offset = at.cast_signed() - start.cast_signed() + new.len().cast_signed()
+            - old.len().cast_signed();
+        if reaches_end {
+            ends_with_newline = !hunk.new_missing_newline;
+        }
+    }
+    let newline = if crlf { "\r\n" } else { "\n" };
+    let mut patched = lines
+        .iter()
+        .map(|line| line.trim_end_matches('\r'))
+        .collect::<Vec<_>>()
+        .join(newline);
+    if ends_with_newline && !lines.is_empty() {
+        patched.push_str(newline);
+    }
+    Ok(patched)
+}
+
+/// The files a patch changes, with their new contents (`None` to delete), with paths
+/// stripped of `strip` leading components
+fn patched_files(
+    dir: &Path,
+    files: &[FilePatch],
+    strip: usize,
+) -> Result<Vec<(PathBuf, Option<String>)>> {
+    let mut results: Vec<(PathBuf, Option<String>)> = Vec::new();
+    for file in files {
+        let path = file
+            .new
+            .as_deref()
+            .or(file.old.as_deref())
+            .and_then(|path| strip_path(path, strip))
+            .ok_or_else(|| anyhow!("Invalid path in patch"))?;
+        let target = dir.join(&path);
+        let original = match results.iter().find(|(done, _)| *done == path) {
+            Some((_, contents)) => contents.clone().unwrap_or_default(),
+            None if file.old.is_none() => String::new(),
+            None => std::fs::read_to_string(&target)
+                .with_context(|| format!("{} doesn't exist", path.display()))?,
+        };
+        let contents = if file.new.is_none() {
+            None
+        } else {
+            Some(
+                apply_hunks(&original, &file.hunks)
+                    .with_context(|| format!("patch {}", path.display()))?,
+            )
+        };
+        results.retain(|(done, _)| *done != path);
+        results.push((path, contents));
+    }
+    Ok(results)
+}
+
+/// Apply a unified diff to the files under `dir`. Like composer-patches, paths are tried
+/// with one leading component stripped (`a/src/File.php`), then none, then two. Nothing is
+/// written unless every hunk applies.
+/// # Errors
+/// Returns an error if the patch cannot be parsed or doesn't apply
+pub fn apply_patch(dir: &Path, patch: &str) -> Result<()> {
+    let files = parse_unified_diff(patch)?;
+    let mut first_error = None;
+    for strip in [1, 0, 2] {
+        match patched_files(dir, &files, strip) {
+            Ok(results) => {
+                for (path, contents) in results {
+                    let target = dir.join(path);
+                    match contents {
+                        Some(contents) => {
+                            if let Some(parent) = target.parent() {
+                                std::fs::create_dir_all(parent)?;
+                            }
+                            std::fs::write(&target, contents)
+                                .with_context(|| format!("write {target:?}"))?;
+                        }
+                        None => std::fs::remove_file(&target)
+                            .with_context(|| format!("remove {target:?}"))?,
+                    }
+                }
+                return Ok(());
+            }
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    Err(first_error.unwrap_or_else(|| anyhow!("Patch does not apply")))
+}
@@ -0,0 +1,215 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::core::graph::DependencyGraph;
+use crate::models::model::{ComposerJson, LockedPackage, ScriptDefinition};
+
+/// Script events that run right after packages are installed
+const INSTALL_SCRIPT_EVENTS: &[&str] =
+    &["post-install-cmd", "post-update-cmd", "post-autoload-dump"];
+
+/// Order in which packages are queued for the install batches. The batches still run
+/// concurrently, so this decides which downloads start first, not which finish first.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InstallPlan {
+    /// Packages the root's install scripts call and packages with bin proxies, with every
+    /// dependency of theirs; queued first, dependencies before dependents
+    pub critical: Vec<String>,
+    /// Everything else, also in dependency order, with each of the root's direct
+    /// requirements right after what it needs
+    pub deferred: Vec<String>,
+}
+
+fn script_commands(composer: &ComposerJson) -> Vec<&str> {
+    let Some(scripts) = &composer.scripts else {
+        return Vec::new();
+    };
+    INSTALL_SCRIPT_EVENTS
+        .iter()
+        .filter_map(|event| scripts.get(*event))
+        .flat_map(|definition| match definition {
+            ScriptDefinition::String(command) => vec![command.as_str()],
+            ScriptDefinition::Array(commands) => commands.iter().map(String::as_str).collect(),
+        })
+        .collect()
+}
+
+/// PSR-4/PSR-0 namespace prefixes a package autoloads
+fn namespace_prefixes(pkg: &LockedPackage) -> Vec<String> {
+    let Some(autoload) = &pkg.autoload else {
+        return Vec::new();
+    };
+    autoload
+        .psr4
+        .keys()
+        .chain(autoload.psr0.keys())
+        .filter(|prefix| !prefix.is_empty())
+        .cloned()
+        .collect()
+}
+
+/// Whether a script command calls into the package, either through a
+/// `Vendor\Class::method` callback or one of its `vendor/bin` proxies
+fn script_uses_package(command: &str, pkg: &LockedPackage) -> bool {
+    if let Some((class, _)) = command.trim().split_once("::") {
+        let class = class.trim_start_matches('\\');
+        if namespace_prefixes(pkg)
+            .iter()
+            .any(|prefix| class.starts_with(prefix.as_str()))
+        {
+            return true;
+        }
+    }
+    pkg.bin.iter().flatten().any(|bin| {
+        let name = bin.rsplit('/').next().unwrap_or(bin);
+        command
+            .split_whitespace()
+            .any(|word| word == name || word.ends_with(&format!("/bin/{name}")))
+    })
+}
+
+/// Sort `names` so every package comes after the packages it requires. The packages of
+/// a dependency cycle can't be ordered, so they are kept together as one batch. Each
+/// package of `first` comes as early as that allows, right after what it needs; the rest
+/// follow in name order.
+fn dependency_order(
+    names: &BTreeSet<&str>,
+    graph: &DependencyGraph,
+    first: &[&str],
+) -> Vec<String> {
+    let components = graph.components_within(names);
+    let component_of: HashMap<&str, usize> = components
+        .iter()
+        .enumerate()
+        .flat_map(|(i, members)| members.iter().map(move |name| (name.as_str(), i)))
+        .collect();
+    let deps: Vec<BTreeSet<usize>> = components
+        .iter()
+        .enumerate()
+        .map(|(i, members)| {
+            members
+                .iter()
+                .flat_map(|name| graph.dependencies(name))
+                .filter_map(|(dep, _)| component_of.get(dep.as_str()).copied())
+                .filter(|dep| *dep != i)
+                .collect()
+        })
+        .collect();
+
+    let mut order = Vec::with_capacity(names.len());
+    let mut placed = vec![false; components.len()];
+    for name in first {
+        let component = names
+            .iter()
+            .find(|candidate| candidate.eq_ignore_ascii_case(name))
+            .and_then(|name| component_of.get(name));
+        if let Some(&component) = component {
+            place_after_deps(component, &deps, &mut placed, &mut |c| {
+                order.extend(components[c].iter().cloned());
+            });
+        }
+    }
+
+    // Batches without pending dependencies go in name order
+    let mut pending: BTreeMap<&str, (usize, BTreeSet<usize>)> = components
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !placed[*i])
+        .map(|(i, members)| {
+            let unplaced = deps[i]
+                .iter()
+                .copied()
+                .filter(|dep| !placed[*dep])
+                .collect();
+            (members[0].as_str(), (i, unplaced))
+        })
+        .collect();
+    while !pending.is_empty() {
+        let ready: Vec<(&str, usize)> = pending
+            .iter()
+            .filter(|(_, (_, deps))| deps.is_empty())
+            .map(|(first, (i, _))| (*first, *i))
+            .collect();
+        for (first, component) in ready {
+            pending.remove(first);
+            for (_, deps) in pending.values_mut() {
+                deps.remove(&component);
+            }
+            order.extend(components[component].iter().cloned());
+        }
+    }
+    order
+}
+
+/// Hand `component` to `place` after every component it depends on that isn't placed yet
+fn place_after_deps(
+    component: usize,
+    deps: &[BTreeSet<usize>],
+    placed: &mut [bool],
+    place: &mut impl FnMut(usize),
+) {
+    if placed[component] {
+        return;
+    }
+    placed[component] = true;
+    for &dep in &deps[component] {
+        place_after_deps(dep, deps, placed, place);
+    }
+    place(component);
+}
+
+/// Split locked packages into the ones to queue first and the long tail
+pub fn install_plan(pkgs: &[LockedPackage], composer: Option<&ComposerJson>) -> InstallPlan {
+    let by_name: BTreeMap<&str, &LockedPackage> =
+        pkgs.iter().map(|pkg| (pkg.name.as_str(), pkg)).collect();
+    let graph = DependencyGraph::from_packages(pkgs);
+    let commands = composer.map(script_commands).unwrap_or_default();
+    let mut seeds: Vec<&str> = pkgs
+        .iter()
+        .filter(|pkg| {
+            pkg.bin.as_ref().is_some_and(|bins| !bins.is_empty())
+                || commands.iter().any(|cmd| script_uses_package(cmd, pkg))
+        })
+        .map(|pkg| pkg.name.as_str())
+        .collect();
+
+    // Everything a critical package needs is critical too, so it is usable once installed
+    let mut critical: BTreeSet<&str> = BTreeSet::new();
+    while let Some(name) = seeds.pop() {
+        if by_name.contains_key(name) && critical.insert(name) {
+            seeds.extend(graph.dependencies(name).iter().map(|(dep, _)| dep.as_str()));
+        }
+    }
+    let deferred: BTreeSet<&str> = by_name
+        .keys()
+        .copied()
+        .filter(|name| !critical.contains(name))
+        .collect();
+
+    let direct: Vec<&str> = composer
+        .map(|composer| {
+            composer
+                .require
+                .keys()
+                .chain(composer.require_dev.keys())
+                .map(String::as_str)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    InstallPlan {
+        critical: dependency_order(&critical, &graph, &[]),
+        deferred: dependency_order(&deferred, &graph, &direct),
+    }
+}
+
+impl InstallPlan {
+    /// Position of each package in the plan
+    pub fn ranks(&self) -> HashMap<&str, usize> {
+        self.critical
+            .iter()
+            .chain(self.deferred.iter())
+            .enumerate()
+            .map(|(rank, name)| (name.as_str(), rank))
+            .collect()
+    }
+}
@@ -0,0 +1,208 @@
+use crate::core::auth::{AuthConfig, HttpCredentials};
+use crate::core::http::http_settings;
+use std::path::{Path, PathBuf};
+
+/// Environment variable with extra private key paths, separated like `PATH`
+pub const SSH_KEY_ENV: &str = "LECTERN_SSH_KEY";
+/// Environment variable selecting the host key policy (`strict` or `off`)
+pub const SSH_HOST_KEY_CHECK_ENV: &str = "LECTERN_SSH_HOST_KEY_CHECK";
+
+/// Keys looked up in `~/.ssh` when no agent identity is accepted
+const DEFAULT_KEY_NAMES: &[&str] = &["id_ed25519", "id_ecdsa", "id_rsa"];
+
+/// How unknown or changed SSH host keys are handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HostKeyPolicy {
+    /// Verify against `~/.ssh/known_hosts` and reject unknown hosts
+    #[default]
+    Strict,
+    /// Accept any host key (only for throwaway environments)
+    AcceptAll,
+}
+
+impl HostKeyPolicy {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "strict" | "yes" | "1" | "true" => Some(Self::Strict),
+            "off" | "no" | "0" | "false" => Some(Self::AcceptAll),
+            _ => None,
+        }
+    }
+
+    /// The answer to libgit2's certificate check, which covers HTTPS certificates as well
+    /// as SSH host keys: `AcceptAll` only waves host keys through, TLS is always verified
+    pub fn certificate_status(self, is_host_key: bool) -> git2::CertificateCheckStatus {
+        match self {
+            Self::AcceptAll if is_host_key => git2::CertificateCheckStatus::CertificateOk,
+            _ => git2::CertificateCheckStatus::CertificatePassthrough,
+        }
+    }
+}
+
+/// Credentials and verification settings for SSH git remotes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SshOptions {
+    /// Private keys to try after the agent, configured keys first
+    pub key_paths: Vec<PathBuf>,
+    pub use_agent: bool,
+    pub host_key_policy: HostKeyPolicy,
+    /// Tokens for private HTTPS remotes
+    pub http_auth: AuthConfig,
+}
+
+/// Whether a git URL is reached over SSH (`ssh://...` or scp-like `git@host:vendor/repo.git`)
+pub fn is_ssh_url(url: &str) -> bool {
+    if url.starts_with("ssh://") || url.starts_with("git+ssh://") {
+        return true;
+    }
+    if url.contains("://") {
+        return false;
+    }
+    // scp-like syntax: a colon before the first slash
+    match (url.find(':'), url.find('/')) {
+        (Some(colon), Some(slash)) => colon < slash,
+        (Some(_), None) => true,
+        _ => false,
+    }
+}
+
+/// Existing default identities in the given `.ssh` directory
+pub fn default_key_paths(ssh_dir: &Path) -> Vec<PathBuf> {
+    DEFAULT_KEY_NAMES
+        .iter()
+        .map(|name| ssh_dir.join(name))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+impl SshOptions {
+    /// Build options using the given variable lookup and home directory
+    pub fn from_env_with<F>(get_env: F, home: Option<&Path>) -> Self
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let mut key_paths: Vec<PathBuf> = get_env(SSH_KEY_ENV)
+            .map(|value| std::env::split_paths(&value).collect())
+            .unwrap_or_default();
+        if let Some(home) = home {
+            for path in default_key_paths(&home.join(".ssh")) {
+                if !key_paths.contains(&path) {
+                    key_paths.push(path);
+                }
+            }
+        }
+
+        let host_key_policy = get_env(SSH_HOST_KEY_CHECK_ENV)
+            .and_then(|value| HostKeyPolicy::from_name(&value))
+            .unwrap_or_default();
+
+        Self {
+            key_paths,
+            use_agent: get_env("SSH_AUTH_SOCK").is_some_and(|v| !v.is_empty()),
+            host_key_policy,
+            http_auth: AuthConfig::default(),
+        }
+    }
+
+    /// Build options from the process environment
+    pub fn from_env() -> Self {
+        Self {
+            http_auth: http_settings().auth.clone(),
+            ..Self::from_env_with(|name| std::env::var(name).ok(), dirs::home_dir().as_deref())
+        }
+    }
+
+    /// HTTPS credentials configured for a remote URL
+    pub fn http_credentials(&self, url: &str) -> Option<HttpCredentials> {
+        self.http_auth.credentials_for(url)
+    }
+
+    /// `GIT_SSH_COMMAND` for the system git fallback, if the defaults need overriding
+    pub fn git_ssh_command(&self) -> Option<String> {
+        if self.key_paths.is_empty() && self.host_key_policy == HostKeyPolicy::Strict {
+            return None;
+        }
+        let mut command = String::from("ssh");
+        for path in &self.key_paths {
+            // git runs the command through a shell
+            if let Ok(path) = shlex::try_quote(&path.to_string_lossy()) {
+                command.push_str(&format!(" -i {path}"));
+            }
+        }
+        let strict = match self.host_key_policy {
+            HostKeyPolicy::Strict => "yes",
+            HostKeyPolicy::AcceptAll => "no",
+        };
+        command.push_str(&format!(" -o StrictHostKeyChecking={strict}"));
+        if self.host_key_policy == HostKeyPolicy::AcceptAll {
+            command.push_str(" -o UserKnownHostsFile=/dev/null");
+        }
+        Some(command)
+    }
+
+    /// git2 callbacks that authenticate with the agent, then each key in turn
+    pub fn remote_callbacks(&self) -> git2::RemoteCallbacks<'static> {
+        let mut callbacks = git2::RemoteCallbacks::new();
+
+        let use_agent = self.use_agent;
+        let key_paths = self.key_paths.clone();
+        let http_auth = self.http_auth.clone();
+        // libgit2 calls back again after every rejected credential
+        let mut attempt = 0usize;
+        let mut sent_http_credentials = false;
+        callbacks.credentials(move |url, username, allowed| {
+            let user = username.unwrap_or("git");
+            if allowed.contains(git2::CredentialType::USERNAME) {
+                return git2::Cred::username(user);
+            }
+            if allowed.contains(git2::CredentialType::USER_PASS_PLAINTEXT) {
+                // A rejected token would otherwise be offered forever
+                if !sent_http_credentials && let Some(creds) = http_auth.credentials_for(url) {
+                    sent_http_credentials = true;
+                    return git2::Cred::userpass_plaintext(&creds.username, &creds.password);
+                }
+                return Err(git2::Error::from_str(&format!(
+                    "no accepted credentials for {url}; configure a token in auth.json or COMPOSER_AUTH"
+                )));
+            }
+            if allowed.contains(git2::CredentialType::SSH_KEY) {
+                loop {
+                    let current = attempt;
+                    attempt += 1;
+                    let key_index = if use_agent {
+                        if current == 0
+                            && let Ok(cred) = git2::Cred::ssh_key_from_agent(user)
+                        {
+                            return Ok(cred);
+                        }
+                        current.checked_sub(1)
+                    } else {
+                        Some(current)
+                    };
+                    match key_index.map(|i| key_paths.get(i)) {
+                        Some(Some(key)) => {
+                            if let Ok(cred) = git2::Cred::ssh_key(user, None, key, None) {
+                                return Ok(cred);
+                            }
+                        }
+                        Some(None) => break,
+                        None => {}
+                    }
+                }
+                return Err(git2::Error::from_str(&format!(
+                    "no SSH agent identity or key was accepted for {url}"
+                )));
+            }
+            git2::Cred::default()
+        });
+
+        let policy = self.host_key_policy;
+        if policy == HostKeyPolicy::AcceptAll {
+            callbacks.certificate_check(move |cert, _| {
+                Ok(policy.certificate_status(cert.as_hostkey().is_some()))
+            });
+        }
+
+        callbacks
+    }
+}
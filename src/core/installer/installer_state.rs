@@ -0,0 +1,160 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::core::installer::installer_links::package_dir;
+use crate::core::installer::installer_patches::LoadedPatch;
+use crate::models::model::LockedPackage;
+
+/// What was last installed for one package
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstalledRecord {
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    /// sha256 of each patch from `extra.patches` applied after installing, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub patches: Vec<String>,
+}
+
+/// Version and reference of every package Lectern installed into vendor,
+/// kept in `vendor/composer/lectern-state.json`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstallState {
+    #[serde(default)]
+    pub packages: BTreeMap<String, InstalledRecord>,
+}
+
+/// The reference a locked package is installed from (dist is preferred, like Composer)
+pub fn package_reference(pkg: &LockedPackage) -> Option<&str> {
+    pkg.dist
+        .as_ref()
+        .map(|d| d.reference.as_str())
+        .filter(|r| !r.is_empty())
+        .or_else(|| {
+            pkg.source
+                .as_ref()
+                .map(|s| s.reference.as_str())
+                .filter(|r| !r.is_empty())
+        })
+}
+
+impl InstallState {
+    pub fn path(vendor: &Path) -> PathBuf {
+        vendor.join("composer").join("lectern-state.json")
+    }
+
+    /// Load the state file; a missing or unreadable file means nothing is known to be installed
+    pub fn load(vendor: &Path) -> Self {
+        std::fs::read_to_string(Self::path(vendor))
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write the state file, dropping records whose package directory is gone
+    /// # Errors
+    /// Returns an error if the state file cannot be written
+    pub fn save(&mut self, vendor: &Path) -> Result<()> {
+        self.packages
+            .retain(|name, _| package_dir(vendor, name).exists());
+        let path = Self::path(vendor);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("write {path:?}"))
+    }
+
+    /// Whether the package is on disk at exactly the locked version and reference.
+    /// Packages without a reference (e.g. plain path repositories) are never considered current.
+    pub fn is_current(&self, pkg: &LockedPackage, vendor: &Path) -> bool {
+        let Some(reference) = package_reference(pkg) else {
+            return false;
+        };
+        self.packages.get(&pkg.name).is_some_and(|record| {
+            record.version == pkg.version && record.reference.as_deref() == Some(reference)
+        }) && package_dir(vendor, &pkg.name).exists()
+    }
+
+    pub fn record(&mut self, pkg: &LockedPackage) {
+        self.packages.insert(
+            pkg.name.clone(),
+            InstalledRecord {
+                version: pkg.version.clone(),
+                reference: package_reference(pkg).map(str::to_string),
+                patches: Vec::new(),
+            },
+        );
+    }
+
+    /// The hashes of the patches applied to an installed package
+    pub fn patches_of(&self, name: &str) -> &[String] {
+        self.packages
+            .get(name)
+            .map_or(&[], |record| record.patches.as_slice())
+    }
+
+    pub fn record_patches(&mut self, name: &str, hashes: Vec<String>) {
+        if let Some(record) = self.packages.get_mut(name) {
+            record.patches = hashes;
+        }
+    }
+}
+
+/// Write `vendor/composer/installed.json` in Composer's format: the locked entry of every
+/// package Lectern installed, with its install path and source. Patched packages list the
+/// applied patches, with their sha256, under `extra.patches_applied`.
+/// # Errors
+/// Returns an error if the file cannot be written
+pub fn write_installed_json(
+    vendor: &Path,
+    pkgs: &[LockedPackage],
+    state: &InstallState,
+    patches: &BTreeMap<String, Vec<LoadedPatch>>,
+) -> Result<()> {
+    let mut packages = Vec::new();
+    for pkg in pkgs {
+        let dir = package_dir(vendor, &pkg.name);
+        if !state.packages.contains_key(&pkg.name) || !dir.exists() {
+            continue;
+        }
+        let mut entry = serde_json::to_value(pkg)?;
+        let source = if pkg.dist.is_some() && !dir.join(".git").exists() {
+            "dist"
+        } else {
+            "source"
+        };
+        entry["installation-source"] = source.into();
+        entry["install-path"] = format!("../{}", pkg.name).into();
+
+        let applied = state.patches_of(&pkg.name);
+        if let Some(list) = patches.get(&pkg.name).filter(|_| !applied.is_empty()) {
+            let records: Vec<serde_json::Value> = list
+                .iter()
+                .zip(applied)
+                .map(|(loaded, hash)| {
+                    serde_json::json!({
+                        "description": loaded.patch.description,
+                        "url": loaded.patch.url,
+                        "sha256": hash,
+                    })
+                })
+                .collect();
+            if !entry.get("extra").is_some_and(serde_json::Value::is_object) {
+                entry["extra"] = serde_json::json!({});
+            }
+            entry["extra"]["patches_applied"] = records.into();
+        }
+        packages.push(entry);
+    }
+
+    let path = vendor.join("composer").join("installed.json");
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let installed = serde_json::json!({ "packages": packages });
+    std::fs::write(&path, serde_json::to_string_pretty(&installed)?)
+        .with_context(|| format!("write {path:?}"))
+}
@@ -0,0 +1,129 @@
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::ffi::OsStr;
+use std::path::Path;
+use tokio::task;
+
+use crate::core::http::http_settings;
+use crate::core::installer::installer_ssh::SshOptions;
+use crate::core::installer::installer_utils::run_system_git;
+use crate::workspace::segment_matches;
+
+/// Which source installs get their submodules initialized, from
+/// `extra.lectern.submodules` (`true`, or `{"packages": [...], "depth": n}`)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SubmoduleConfig {
+    pub enabled: bool,
+    /// Package name patterns (`*` wildcards); empty means every package
+    pub packages: Vec<String>,
+    /// Levels of nested submodules to initialize; `None` recurses fully
+    pub depth: Option<usize>,
+}
+
+impl SubmoduleConfig {
+    /// Parse the `extra.lectern.submodules` value
+    pub fn from_value(value: &Value) -> Self {
+        match value {
+            Value::Bool(enabled) => Self {
+                enabled: *enabled,
+                ..Self::default()
+            },
+            Value::Object(obj) => Self {
+                enabled: obj.get("enabled").and_then(Value::as_bool).unwrap_or(true),
+                packages: obj
+                    .get("packages")
+                    .and_then(Value::as_array)
+                    .map(|list| {
+                        list.iter()
+                            .filter_map(|p| p.as_str().map(str::to_string))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                depth: obj.get("depth").and_then(Value::as_u64).map(|d| d as usize),
+            },
+            _ => Self::default(),
+        }
+    }
+
+    /// Read the setting from the project's composer.json
+    pub fn from_project(project_dir: &Path) -> Self {
+        crate::io::read_manifest_value(project_dir)
+            .and_then(|json| {
+                json.pointer("/extra/lectern/submodules")
+                    .map(Self::from_value)
+            })
+            .unwrap_or_default()
+    }
+
+    /// Submodule depth to initialize for a package, or `None` to leave them alone
+    pub fn depth_for(&self, package: &str) -> Option<usize> {
+        let selected = self.enabled
+            && (self.packages.is_empty()
+                || self
+                    .packages
+                    .iter()
+                    .any(|pattern| segment_matches(pattern, package)));
+        selected.then(|| self.depth.unwrap_or(usize::MAX))
+    }
+}
+
+/// Initialize and check out the submodules of `repo`, descending `depth` levels
+fn update_submodules(repo: &git2::Repository, depth: usize, ssh: &SshOptions) -> Result<()> {
+    if depth == 0 {
+        return Ok(());
+    }
+    for mut submodule in repo.submodules()? {
+        let name = submodule.name().unwrap_or("submodule").to_string();
+        let mut fetch_options = git2::FetchOptions::new();
+        fetch_options.remote_callbacks(ssh.remote_callbacks());
+        if let Some(url) = submodule.url() {
+            fetch_options.proxy_options(http_settings().proxy.git_proxy_options(url));
+        }
+        let mut update_options = git2::SubmoduleUpdateOptions::new();
+        update_options.fetch(fetch_options);
+        submodule
+            .update(true, Some(&mut update_options))
+            .with_context(|| format!("update submodule {name}"))?;
+
+        let nested = submodule.open()?;
+        update_submodules(&nested, depth - 1, ssh)?;
+    }
+    Ok(())
+}
+
+/// Initialize submodules of a fresh source checkout, using the same SSH and token
+/// credentials as the clone. Falls back to the system git like clones do.
+pub async fn init_submodules(
+    target: &Path,
+    url: &str,
+    depth: usize,
+    cpu_sem: std::sync::Arc<tokio::sync::Semaphore>,
+) -> Result<()> {
+    let _cpu_guard = cpu_sem.acquire_owned().await?;
+    let target = target.to_path_buf();
+    let url = url.to_string();
+
+    task::spawn_blocking(move || -> Result<()> {
+        let ssh = SshOptions::from_env();
+        let repo = git2::Repository::open(&target)?;
+        if let Err(e) = update_submodules(&repo, depth, &ssh) {
+            let mut args: Vec<&OsStr> = vec![
+                "-C".as_ref(),
+                target.as_os_str(),
+                "submodule".as_ref(),
+                "update".as_ref(),
+                "--init".as_ref(),
+            ];
+            // The system git cannot stop part way down, so any depth past one recurses fully
+            if depth > 1 {
+                args.push("--recursive".as_ref());
+            }
+            run_system_git(&url, &ssh, &args)
+                .with_context(|| format!("submodule update in {} failed: {e}", target.display()))?;
+        }
+        Ok(())
+    })
+    .await??;
+
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use crate::core::installer::InstalledPackage;
+use crate::utils;
+use std::collections::BTreeMap;
+
+/// Above this many changed packages the summary collapses to per-vendor counts
+pub const SUMMARY_COLLAPSE_THRESHOLD: usize = 30;
+
+/// Group packages by vendor namespace (the part of the name before `/`)
+pub fn group_by_vendor(packages: &[InstalledPackage]) -> BTreeMap<String, Vec<&InstalledPackage>> {
+    let mut groups: BTreeMap<String, Vec<&InstalledPackage>> = BTreeMap::new();
+    for pkg in packages {
+        let vendor = pkg
+            .name
+            .split_once('/')
+            .map_or(pkg.name.as_str(), |(vendor, _)| vendor);
+        groups.entry(vendor.to_string()).or_default().push(pkg);
+    }
+    for list in groups.values_mut() {
+        list.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+    groups
+}
+
+/// Whether the summary should list individual packages rather than vendor counts
+pub fn should_expand_summary(changed_count: usize, verbosity: u8) -> bool {
+    verbosity > 0 || changed_count <= SUMMARY_COLLAPSE_THRESHOLD
+}
+
+/// Print the final install summary grouped by vendor.
+/// Only changed packages are shown by default; `--verbose` expands every vendor
+/// and also lists the packages that were already up to date.
+pub fn print_install_summary(changed: &[InstalledPackage], unchanged: &[InstalledPackage]) {
+    let verbosity = utils::verbosity();
+
+    utils::print_success(&format!(
+        "✅ {} packages installed, {} unchanged",
+        changed.len(),
+        unchanged.len()
+    ));
+
+    if !changed.is_empty() {
+        print_vendor_groups(
+            "Installed",
+            changed,
+            should_expand_summary(changed.len(), verbosity),
+        );
+        if !should_expand_summary(changed.len(), verbosity) {
+            utils::print_text("  Run with --verbose to list every package.");
+        }
+    }
+
+    if verbosity > 0 && !unchanged.is_empty() {
+        print_vendor_groups("Unchanged", unchanged, true);
+    }
+}
+
+fn print_vendor_groups(title: &str, packages: &[InstalledPackage], expand: bool) {
+    let groups = group_by_vendor(packages);
+    let mut lines = vec![format!(
+        "\n📦 {title} ({} packages, {} vendors):",
+        packages.len(),
+        groups.len()
+    )];
+    for (vendor, list) in &groups {
+        lines.push(format!("  {vendor} ({})", list.len()));
+        if expand {
+            for pkg in list {
+                lines.push(format!("    • {} ({})", pkg.name, pkg.version));
+            }
+        }
+    }
+    utils::print_text(&lines.join("\n"));
+}
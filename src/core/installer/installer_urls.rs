@@ -0,0 +1,170 @@
+use serde_json::Value;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Rewrites applied to anonymous downloads unless the project configures its own first:
+/// GitHub API archives count against the 60 requests/hour anonymous API quota, while
+/// codeload serves the same archives without it
+const DEFAULT_REWRITES: &[(&str, &str)] = &[
+    (
+        "https://api.github.com/repos/{owner}/{repo}/zipball/{ref}",
+        "https://codeload.github.com/{owner}/{repo}/legacy.zip/{ref}",
+    ),
+    (
+        "https://api.github.com/repos/{owner}/{repo}/tarball/{ref}",
+        "https://codeload.github.com/{owner}/{repo}/legacy.tar.gz/{ref}",
+    ),
+];
+
+/// One dist URL template pair. A `{name}` in `from` captures one path segment, or
+/// everything that is left when it ends the template; captures are percent-decoded
+/// into the same placeholders in `to`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DistUrlRewrite {
+    pub from: String,
+    pub to: String,
+}
+
+enum Token<'a> {
+    Literal(&'a str),
+    Placeholder(&'a str),
+}
+
+fn template_tokens(template: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = template;
+    while !rest.is_empty() {
+        match rest
+            .find('{')
+            .and_then(|open| Some((open, rest[open..].find('}')? + open)))
+        {
+            Some((0, close)) => {
+                tokens.push(Token::Placeholder(&rest[1..close]));
+                rest = &rest[close + 1..];
+            }
+            Some((open, _)) => {
+                tokens.push(Token::Literal(&rest[..open]));
+                rest = &rest[open..];
+            }
+            None => {
+                tokens.push(Token::Literal(rest));
+                rest = "";
+            }
+        }
+    }
+    tokens
+}
+
+impl DistUrlRewrite {
+    pub fn new(from: &str, to: &str) -> Self {
+        Self {
+            from: from.to_string(),
+            to: to.to_string(),
+        }
+    }
+
+    /// The rewritten URL, or `None` if `url` doesn't match `from`
+    pub fn apply(&self, url: &str) -> Option<String> {
+        let tokens = template_tokens(&self.from);
+        let mut captures: Vec<(&str, String)> = Vec::new();
+        let mut rest = url;
+        for (i, token) in tokens.iter().enumerate() {
+            match token {
+                Token::Literal(literal) => rest = rest.strip_prefix(literal)?,
+                Token::Placeholder(name) => {
+                    let value = match tokens.get(i + 1) {
+                        Some(Token::Literal(next)) => {
+                            let end = rest.find(next)?;
+                            let value = &rest[..end];
+                            if value.contains('/') {
+                                return None;
+                            }
+                            value
+                        }
+                        // Two placeholders in a row can't be told apart
+                        Some(Token::Placeholder(_)) => return None,
+                        None => rest,
+                    };
+                    if value.is_empty() {
+                        return None;
+                    }
+                    rest = &rest[value.len()..];
+                    captures.push((name, urlencoding::decode(value).ok()?.into_owned()));
+                }
+            }
+        }
+        if !rest.is_empty() {
+            return None;
+        }
+
+        let mut rewritten = String::new();
+        for token in template_tokens(&self.to) {
+            match token {
+                Token::Literal(literal) => rewritten.push_str(literal),
+                Token::Placeholder(name) => {
+                    let (_, value) = captures.iter().find(|(captured, _)| *captured == name)?;
+                    rewritten.push_str(value);
+                }
+            }
+        }
+        Some(rewritten)
+    }
+}
+
+/// Ordered dist URL rewrites: the project's `extra.lectern.dist-url-rewrites`
+/// (`[{"from": ..., "to": ...}]`), then the built-in ones
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DistUrlRewrites {
+    pub rules: Vec<DistUrlRewrite>,
+}
+
+impl Default for DistUrlRewrites {
+    fn default() -> Self {
+        Self::from_config(None)
+    }
+}
+
+impl DistUrlRewrites {
+    pub fn from_config(config: Option<&Value>) -> Self {
+        let configured = config
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|rule| {
+                let from = rule.get("from")?.as_str()?;
+                let to = rule.get("to")?.as_str()?;
+                Some(DistUrlRewrite::new(from, to))
+            });
+        let defaults = DEFAULT_REWRITES
+            .iter()
+            .map(|(from, to)| DistUrlRewrite::new(from, to));
+        Self {
+            rules: configured.chain(defaults).collect(),
+        }
+    }
+
+    /// Read the rewrites from the project's composer.json
+    pub fn from_project(project_dir: &Path) -> Self {
+        let composer = crate::io::read_manifest_value(project_dir);
+        Self::from_config(
+            composer
+                .as_ref()
+                .and_then(|json| json.pointer("/extra/lectern/dist-url-rewrites")),
+        )
+    }
+
+    /// URL to download instead of `url`, from the first rule that matches
+    pub fn rewrite(&self, url: &str) -> Option<String> {
+        self.rules.iter().find_map(|rule| rule.apply(url))
+    }
+}
+
+/// The rewrites for the current project, loaded once per run
+pub fn dist_url_rewrites() -> &'static DistUrlRewrites {
+    static REWRITES: OnceLock<DistUrlRewrites> = OnceLock::new();
+    REWRITES.get_or_init(|| {
+        crate::auth::project_dir()
+            .map(DistUrlRewrites::from_project)
+            .unwrap_or_default()
+    })
+}
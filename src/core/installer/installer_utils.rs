@@ -1,7 +1,11 @@
+use crate::core::auth::url_host;
 use crate::core::cache_utils::get_cache_dir;
-use anyhow::Result;
+use crate::core::http::http_settings;
+use crate::core::installer::installer_links::{link_entry, remove_dir_all};
+use crate::core::installer::installer_ssh::{SshOptions, is_ssh_url};
+use anyhow::{Context, Result};
 use sha2::Digest;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 use tokio::task;
 
 pub fn get_package_cache_dir() -> PathBuf {
@@ -19,47 +23,232 @@ pub fn get_cached_package_path(name: &str, version: &str, url: &str) -> PathBuf
 pub fn extract_archive_ultra_fast(archive: &Path, dest: &Path) -> Result<()> {
     // Implemented here to avoid circular private access
     let file = std::fs::File::open(archive)?;
-    let mut buffer = [0; 4];
+    let mut buffer = Vec::with_capacity(TAR_MAGIC_END);
 
     // Read magic bytes for format detection
     {
         use std::io::{Read, Seek, SeekFrom};
         let mut reader = file.try_clone()?;
-        reader.read_exact(&mut buffer)?;
+        (&mut reader)
+            .take(TAR_MAGIC_END as u64)
+            .read_to_end(&mut buffer)?;
         reader.seek(SeekFrom::Start(0))?;
     }
 
     // Fast format detection by magic bytes
-    match &buffer {
-        [0x50, 0x4B, 0x03, 0x04] | [0x50, 0x4B, 0x05, 0x06] | [0x50, 0x4B, 0x07, 0x08] => {
+    match buffer.get(..4) {
+        Some([0x50, 0x4B, 0x03, 0x04] | [0x50, 0x4B, 0x05, 0x06] | [0x50, 0x4B, 0x07, 0x08]) => {
             extract_zip_ultra_fast(archive, dest)
         }
-        [0x1F, 0x8B, _, _] => extract_tar_gz_ultra_fast(archive, dest),
+        Some([0x1F, 0x8B, _, _]) => extract_tar_gz_ultra_fast(archive, dest),
+        _ if is_plain_tar(&buffer) => extract_tar_from_reader(file, dest),
         _ => extract_zip_ultra_fast(archive, dest)
             .or_else(|_| extract_tar_gz_ultra_fast(archive, dest)),
     }
 }
 
+/// Extract an archive already held in memory (same format detection as on disk)
+pub fn extract_archive_from_bytes(bytes: &[u8], dest: &Path) -> Result<()> {
+    match bytes.get(..4) {
+        Some([0x50, 0x4B, 0x03, 0x04] | [0x50, 0x4B, 0x05, 0x06] | [0x50, 0x4B, 0x07, 0x08]) => {
+            extract_zip_from_reader(std::io::Cursor::new(bytes), dest)
+        }
+        Some([0x1F, 0x8B, _, _]) => extract_tar_gz_from_reader(bytes, dest),
+        _ if is_plain_tar(bytes) => extract_tar_from_reader(bytes, dest),
+        _ => extract_zip_from_reader(std::io::Cursor::new(bytes), dest)
+            .or_else(|_| extract_tar_gz_from_reader(bytes, dest)),
+    }
+}
+
+/// Uncompressed tarballs have no leading magic; POSIX headers carry `ustar` at offset 257
+const TAR_MAGIC_END: usize = 262;
+
+fn is_plain_tar(header: &[u8]) -> bool {
+    header.get(257..TAR_MAGIC_END) == Some(b"ustar".as_slice())
+}
+
 pub fn extract_zip_ultra_fast(archive: &Path, dest: &Path) -> Result<()> {
-    let file = std::fs::File::open(archive)?;
-    let mut zip = zip::ZipArchive::new(file)?;
+    extract_zip_from_reader(std::fs::File::open(archive)?, dest)
+}
+
+/// Archive entry name relative to the package root (top-level directory stripped).
+/// Returns `None` for the root itself and an error for absolute or `..` paths.
+/// # Errors
+/// Returns an error if the entry would land outside the destination
+pub fn sanitize_entry_path(name: &str) -> Result<Option<PathBuf>> {
+    // Windows separators must not smuggle `..` past the check
+    let name = name.replace('\\', "/");
+    if name.starts_with('/') || Path::new(&name).has_root() || name.get(1..2) == Some(":") {
+        return Err(anyhow::anyhow!(
+            "archive entry has an absolute path: {name}"
+        ));
+    }
+
+    let mut components = Path::new(&name).components();
+    // Packagist archives wrap everything in one top-level directory
+    components.next();
+
+    let mut sanitized = PathBuf::new();
+    for component in components {
+        match component {
+            Component::Normal(part) => sanitized.push(part),
+            Component::CurDir => {}
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "archive entry escapes the destination: {name}"
+                ));
+            }
+        }
+    }
+
+    Ok((!sanitized.as_os_str().is_empty()).then_some(sanitized))
+}
+
+/// Whether a symlink at `link` (relative to the package root) pointing to `target` stays inside it
+pub fn symlink_stays_inside(link: &Path, target: &Path) -> bool {
+    let mut depth: Vec<&std::ffi::OsStr> = link
+        .parent()
+        .map(|p| p.components().map(Component::as_os_str).collect())
+        .unwrap_or_default();
+    for component in target.components() {
+        match component {
+            Component::Normal(part) => depth.push(part),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if depth.pop().is_none() {
+                    return false;
+                }
+            }
+            Component::RootDir | Component::Prefix(_) => return false,
+        }
+    }
+    true
+}
+
+/// Create `dir` after checking that no existing symlink redirects it outside `root` (canonical)
+fn create_dir_inside(root: &Path, dir: &Path) -> Result<()> {
+    let mut existing = dir;
+    while !existing.exists()
+        && let Some(parent) = existing.parent()
+    {
+        existing = parent;
+    }
+    if !existing.canonicalize()?.starts_with(root) {
+        return Err(anyhow::anyhow!(
+            "archive entry escapes the destination through a symlink: {}",
+            dir.display()
+        ));
+    }
+    std::fs::create_dir_all(dir)?;
+    Ok(())
+}
+
+/// Whether `path` lands inside `root` (canonical) once every symlink on it is followed; a
+/// path that doesn't exist must have its deepest existing ancestor inside and not climb above it
+fn resolves_inside(root: &Path, path: &Path) -> bool {
+    let mut existing = path;
+    while !existing.exists()
+        && let Some(parent) = existing.parent()
+    {
+        existing = parent;
+    }
+    let climbs = path
+        .strip_prefix(existing)
+        .is_ok_and(|rest| rest.components().any(|c| c == Component::ParentDir));
+    !climbs
+        && existing
+            .canonicalize()
+            .is_ok_and(|real| real.starts_with(root))
+}
+
+/// Where the symlink at `link` points, from the directory it really sits in
+fn symlink_destination(link: &Path, target: &Path) -> Result<PathBuf> {
+    let parent = link.parent().unwrap_or(link).canonicalize()?;
+    Ok(parent.join(target))
+}
+
+/// Create the symlink `link -> target` inside `root` (canonical). Links extracted earlier
+/// may redirect the link's own directory or the target's path, so both are judged where
+/// they really resolve rather than by the names in the archive.
+fn create_symlink_inside(root: &Path, link: &Path, target: &Path) -> Result<()> {
+    if let Some(parent) = link.parent() {
+        create_dir_inside(root, parent)?;
+    }
+    let escapes = || {
+        anyhow::anyhow!(
+            "archive symlink {} points outside the package: {}",
+            link.display(),
+            target.display()
+        )
+    };
+    let parent = link.parent().unwrap_or(link).canonicalize()?;
+    let relative = parent
+        .strip_prefix(root)
+        .map_err(|_| escapes())?
+        .join(link.file_name().unwrap_or_default());
+    if !symlink_stays_inside(&relative, target) {
+        return Err(escapes());
+    }
+    link_entry(target, link)?;
+    if !resolves_inside(root, &symlink_destination(link, target)?) {
+        std::fs::remove_file(link).ok();
+        return Err(escapes());
+    }
+    Ok(())
+}
+
+/// Check every extracted symlink again once all exist, since a later link can redirect the
+/// path an earlier one points through
+fn check_symlinks_inside(root: &Path, links: &[(PathBuf, PathBuf)]) -> Result<()> {
+    for (link, target) in links {
+        if !resolves_inside(root, &symlink_destination(link, target)?) {
+            std::fs::remove_file(link).ok();
+            return Err(anyhow::anyhow!(
+                "archive symlink {} points outside the package: {}",
+                link.display(),
+                target.display()
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn extract_zip_from_reader<R: std::io::Read + std::io::Seek>(reader: R, dest: &Path) -> Result<()> {
+    let mut zip = zip::ZipArchive::new(reader)?;
+    std::fs::create_dir_all(dest)?;
+    let root = dest.canonicalize()?;
 
     // Pre-allocate collections for better memory performance
     let file_count = zip.len();
     let mut directories = Vec::with_capacity(file_count / 10); // Estimate 10% directories
     let mut files = Vec::with_capacity(file_count);
+    let mut symlinks = Vec::new();
 
     // Single pass to categorize entries
     for i in 0..file_count {
-        let entry = zip.by_index(i)?;
-        let stripped = crate::core::utils::strip_first_component(entry.name());
-        
-        // Skip if path becomes empty after stripping (root-level files with single component)
-        if stripped.as_os_str().is_empty() {
+        let mut entry = zip.by_index(i)?;
+        // Skip the root itself (root-level files with single component)
+        let Some(relative) = sanitize_entry_path(entry.name())? else {
+            continue;
+        };
+
+        let is_symlink = entry
+            .unix_mode()
+            .is_some_and(|mode| mode & 0o170000 == 0o120000);
+        if is_symlink {
+            let mut target = String::new();
+            std::io::Read::read_to_string(&mut entry, &mut target)?;
+            if !symlink_stays_inside(&relative, Path::new(&target)) {
+                return Err(anyhow::anyhow!(
+                    "archive symlink {} points outside the package: {target}",
+                    relative.display()
+                ));
+            }
+            symlinks.push((dest.join(relative), PathBuf::from(target)));
             continue;
         }
-        
-        let path = dest.join(stripped);
+
+        let path = dest.join(relative);
 
         if entry.is_dir() {
             directories.push(path);
@@ -70,7 +259,7 @@ pub fn extract_zip_ultra_fast(archive: &Path, dest: &Path) -> Result<()> {
 
     // Batch create all directories
     for dir in directories {
-        std::fs::create_dir_all(&dir)?;
+        create_dir_inside(&root, &dir)?;
     }
 
     // Sort files by size (extract small files first for better perceived performance)
@@ -81,7 +270,7 @@ pub fn extract_zip_ultra_fast(archive: &Path, dest: &Path) -> Result<()> {
         let mut entry = zip.by_index(index)?;
 
         if let Some(parent) = path.parent() {
-            std::fs::create_dir_all(parent)?;
+            create_dir_inside(&root, parent)?;
         }
 
         let mut output = std::fs::File::create(&path)?;
@@ -97,53 +286,191 @@ pub fn extract_zip_ultra_fast(archive: &Path, dest: &Path) -> Result<()> {
         }
     }
 
-    Ok(())
+    // Links last so no file above is written through one
+    for (link, target) in &symlinks {
+        create_symlink_inside(&root, link, target)?;
+    }
+    check_symlinks_inside(&root, &symlinks)
 }
 
 pub fn extract_tar_gz_ultra_fast(archive: &Path, dest: &Path) -> Result<()> {
-    let file = std::fs::File::open(archive)?;
-    let decompressor = flate2::read::GzDecoder::new(file);
-    let mut tar = tar::Archive::new(decompressor);
+    extract_tar_gz_from_reader(std::fs::File::open(archive)?, dest)
+}
+
+fn extract_tar_gz_from_reader<R: std::io::Read>(reader: R, dest: &Path) -> Result<()> {
+    extract_tar_from_reader(flate2::read::GzDecoder::new(reader), dest)
+}
+
+fn extract_tar_from_reader<R: std::io::Read>(reader: R, dest: &Path) -> Result<()> {
+    let mut tar = tar::Archive::new(reader);
+    std::fs::create_dir_all(dest)?;
+    let root = dest.canonicalize()?;
 
     // Set preserve permissions to false for faster extraction
     tar.set_preserve_permissions(false);
     tar.set_preserve_mtime(false);
 
+    let mut symlinks = Vec::new();
+
     // Extract entries manually to strip first component
     for entry_result in tar.entries()? {
         let mut entry = entry_result?;
-        let entry_path = entry.path()?;
-        
-        // Strip the first component from the path
-        let stripped = crate::core::utils::strip_first_component(
-            entry_path.to_str().unwrap_or("")
-        );
-        
+        let entry_path = entry.path()?.to_string_lossy().to_string();
+
         // Skip if path becomes empty after stripping
-        if stripped.as_os_str().is_empty() {
+        let Some(relative) = sanitize_entry_path(&entry_path)? else {
             continue;
-        }
-        
-        let target_path = dest.join(stripped);
-        
+        };
+        let target_path = dest.join(&relative);
+        let entry_type = entry.header().entry_type();
+
         // Handle directories
-        if entry.header().entry_type().is_dir() {
-            std::fs::create_dir_all(&target_path).ok(); // Ignore errors if already exists
+        if entry_type.is_dir() {
+            create_dir_inside(&root, &target_path)?;
             continue;
         }
-        
-        // Create parent directories if needed for files
+
         if let Some(parent) = target_path.parent() {
-            std::fs::create_dir_all(parent)?;
+            create_dir_inside(&root, parent)?;
+        }
+
+        if entry_type.is_symlink() {
+            let target = entry
+                .link_name()?
+                .ok_or_else(|| anyhow::anyhow!("symlink without target: {entry_path}"))?
+                .into_owned();
+            create_symlink_inside(&root, &target_path, &target)?;
+            symlinks.push((target_path, target));
+            continue;
         }
-        
+
+        if entry_type.is_hard_link() {
+            // Hard link names are archive paths, so they get the same treatment as entries
+            let link_name = entry
+                .link_name()?
+                .map(|l| l.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let source = sanitize_entry_path(&link_name)?
+                .ok_or_else(|| anyhow::anyhow!("invalid hard link target: {link_name}"))?;
+            std::fs::copy(dest.join(source), &target_path)?;
+            continue;
+        }
+
         // Extract the file
         entry.unpack(&target_path)?;
     }
 
+    check_symlinks_inside(&root, &symlinks)
+}
+
+/// Whether a reference is a full commit SHA rather than a tag or branch name
+pub fn is_commit_sha(reference: &str) -> bool {
+    reference.len() == 40 && reference.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Refspecs that fetch just the locked reference
+pub fn reference_refspecs(reference: &str) -> Vec<String> {
+    if is_commit_sha(reference) {
+        vec![reference.to_string()]
+    } else {
+        vec![
+            format!("+refs/tags/{reference}:refs/tags/{reference}"),
+            format!("+refs/heads/{reference}:refs/remotes/origin/{reference}"),
+        ]
+    }
+}
+
+const FULL_REFSPECS: [&str; 2] = [
+    "+refs/heads/*:refs/remotes/origin/*",
+    "+refs/tags/*:refs/tags/*",
+];
+
+/// Find the commit a locked reference (SHA, tag or branch) points at in a fetched repository
+pub fn resolve_reference(repo: &git2::Repository, reference: &str) -> Result<git2::Oid> {
+    if is_commit_sha(reference) {
+        let oid = git2::Oid::from_str(reference)?;
+        return Ok(repo.find_commit(oid)?.id());
+    }
+    for name in [
+        format!("refs/tags/{reference}"),
+        format!("refs/remotes/origin/{reference}"),
+    ] {
+        if let Ok(found) = repo.find_reference(&name) {
+            return Ok(found.peel_to_commit()?.id());
+        }
+    }
+    // Abbreviated SHAs and other revision expressions
+    Ok(repo.revparse_single(reference)?.peel_to_commit()?.id())
+}
+
+fn fetch_refspecs(
+    remote: &mut git2::Remote,
+    refspecs: &[String],
+    depth: i32,
+    ssh: &SshOptions,
+) -> Result<()> {
+    let mut fetch_options = git2::FetchOptions::new();
+    fetch_options.download_tags(git2::AutotagOption::None); // Tags are fetched explicitly
+    fetch_options.remote_callbacks(ssh.remote_callbacks());
+    if let Some(url) = remote.url() {
+        fetch_options.proxy_options(http_settings().proxy.git_proxy_options(url));
+    }
+    if depth > 0 {
+        fetch_options.depth(depth);
+    }
+    remote.fetch(refspecs, Some(&mut fetch_options), None)?;
     Ok(())
 }
 
+/// Fetch `reference` into a fresh repository at `target` and check it out on a detached HEAD
+fn clone_reference(url: &str, reference: &str, target: &Path, ssh: &SshOptions) -> Result<()> {
+    let repo = git2::Repository::init(target)?;
+    let mut remote = repo.remote("origin", url)?;
+
+    // Servers that allow it send only the locked commit; otherwise fetch everything
+    let shallow = fetch_refspecs(&mut remote, &reference_refspecs(reference), 1, ssh).is_ok()
+        && resolve_reference(&repo, reference).is_ok();
+    if !shallow {
+        let full: Vec<String> = FULL_REFSPECS.iter().map(|s| s.to_string()).collect();
+        fetch_refspecs(&mut remote, &full, 0, ssh)?;
+    }
+
+    let oid = resolve_reference(&repo, reference)
+        .with_context(|| format!("reference {reference} not found in {url}"))?;
+    let commit = repo.find_commit(oid)?;
+    repo.checkout_tree(
+        commit.as_object(),
+        Some(git2::build::CheckoutBuilder::new().force()),
+    )?;
+    repo.set_head_detached(oid)?;
+    Ok(())
+}
+
+/// The commit `branch` of the repository at `url` points at, like
+/// `git ls-remote url refs/heads/<branch>`
+/// # Errors
+/// Returns an error if the remote cannot be reached or has no such branch
+pub async fn branch_head(url: &str, branch: &str) -> Result<String> {
+    let url = url.to_string();
+    let wanted = format!("refs/heads/{branch}");
+    task::spawn_blocking(move || -> Result<String> {
+        let ssh = SshOptions::from_env();
+        let mut remote = git2::Remote::create_detached(url.as_str())?;
+        let connection = remote.connect_auth(
+            git2::Direction::Fetch,
+            Some(ssh.remote_callbacks()),
+            Some(http_settings().proxy.git_proxy_options(&url)),
+        )?;
+        let head = connection
+            .list()?
+            .iter()
+            .find(|head| head.name() == wanted)
+            .map(|head| head.oid().to_string());
+        head.ok_or_else(|| anyhow::anyhow!("{url} has no {wanted}"))
+    })
+    .await?
+}
+
 pub async fn clone_git_optimized(
     url: &str,
     reference: Option<&str>,
@@ -152,30 +479,32 @@ pub async fn clone_git_optimized(
 ) -> Result<()> {
     let _cpu_guard = cpu_sem.acquire_owned().await?;
     let url = url.to_string();
-    let reference = reference.map(|s| s.to_string());
+    let reference = reference.filter(|r| !r.is_empty()).map(|s| s.to_string());
     let target = target.to_path_buf();
 
     task::spawn_blocking(move || -> Result<()> {
-        let mut builder = git2::build::RepoBuilder::new();
-
-        // Optimize git clone for speed
-        builder.bare(false);
-        builder.branch(reference.as_deref().unwrap_or("main"));
-
-        // Configure for faster clones
-        let mut fetch_options = git2::FetchOptions::new();
-        fetch_options.download_tags(git2::AutotagOption::None); // Skip tags for speed
-
-        let mut remote_callbacks = git2::RemoteCallbacks::new();
-        remote_callbacks.update_tips(|_, _, _| true); // Skip tip updates
-
-        fetch_options.remote_callbacks(remote_callbacks);
-        builder.fetch_options(fetch_options);
-
-        // Shallow clone for maximum speed (depth=1)
-        builder.clone_local(git2::build::CloneLocal::Auto);
+        let ssh = SshOptions::from_env();
+
+        let result = match reference.as_deref() {
+            Some(reference) => clone_reference(&url, reference, &target, &ssh),
+            None => {
+                // Without a locked reference the remote's default branch is used
+                let mut fetch_options = git2::FetchOptions::new();
+                fetch_options.remote_callbacks(ssh.remote_callbacks());
+                fetch_options.proxy_options(http_settings().proxy.git_proxy_options(&url));
+                git2::build::RepoBuilder::new()
+                    .fetch_options(fetch_options)
+                    .clone(&url, &target)
+                    .map(|_| ())
+                    .map_err(Into::into)
+            }
+        };
 
-        builder.clone(&url, &target)?;
+        if let Err(e) = result {
+            // The system git may know credentials or SSH config that libgit2 does not
+            clone_with_system_git(&url, reference.as_deref(), &target, &ssh)
+                .with_context(|| format!("git clone {url} failed: {e}"))?;
+        }
         Ok(())
     })
     .await??;
@@ -183,6 +512,114 @@ pub async fn clone_git_optimized(
     Ok(())
 }
 
+/// Credential helper answering git's `get` request from the environment, only for HTTPS
+/// requests to `LECTERN_GIT_HOST`, so redirects and submodules on other hosts get nothing
+pub const GIT_CREDENTIAL_HELPER: &str = "credential.helper=!f() { \
+test \"$1\" = get || return 0; protocol=; host=; \
+while IFS== read -r key value; do case \"$key\" in \
+protocol) protocol=\"$value\" ;; host) host=$(printf %s \"${value%%:*}\" | tr A-Z a-z) ;; \
+esac; done; \
+if test \"$protocol\" = https && test \"$host\" = \"$LECTERN_GIT_HOST\"; then \
+echo username=\"$LECTERN_GIT_USERNAME\"; echo password=\"$LECTERN_GIT_PASSWORD\"; fi; }; f";
+
+/// Run the system git with the SSH settings and HTTPS token that apply to `url`
+pub fn run_system_git(url: &str, ssh: &SshOptions, args: &[&std::ffi::OsStr]) -> Result<()> {
+    let mut command = std::process::Command::new("git");
+    command.env("GIT_TERMINAL_PROMPT", "0");
+    if is_ssh_url(url)
+        && let Some(ssh_command) = ssh.git_ssh_command()
+    {
+        command.env("GIT_SSH_COMMAND", ssh_command);
+    }
+    if let Some(proxy) = http_settings().proxy.proxy_for(url) {
+        command.arg("-c").arg(format!("http.proxy={proxy}"));
+    }
+    if let Some(creds) = ssh.http_credentials(url) {
+        // Hand the token to git through a one-off credential helper instead of the URL,
+        // so it never ends up in .git/config or the process list
+        command
+            .args(["-c", "credential.helper=", "-c", GIT_CREDENTIAL_HELPER])
+            .env("LECTERN_GIT_HOST", url_host(url).unwrap_or_default())
+            .env("LECTERN_GIT_USERNAME", &creds.username)
+            .env("LECTERN_GIT_PASSWORD", &creds.password);
+    }
+    let output = command.args(args).output().context("run system git")?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "{}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+fn clone_with_system_git(
+    url: &str,
+    reference: Option<&str>,
+    target: &Path,
+    ssh: &SshOptions,
+) -> Result<()> {
+    if let Some(reference) = reference
+        && !is_git_reference(reference)
+    {
+        return Err(anyhow::anyhow!(
+            "invalid git reference '{reference}' for {url}"
+        ));
+    }
+    // Start from an empty directory after the failed libgit2 attempt
+    remove_dir_all(target)?;
+
+    // `--` keeps a URL from the lock such as `--upload-pack=...` from being read as an option
+    run_system_git(
+        url,
+        ssh,
+        &[
+            "clone".as_ref(),
+            "--quiet".as_ref(),
+            "--".as_ref(),
+            url.as_ref(),
+            target.as_os_str(),
+        ],
+    )?;
+    if let Some(reference) = reference {
+        let checkout = |rev: &str| {
+            run_system_git(
+                url,
+                ssh,
+                &[
+                    "-C".as_ref(),
+                    target.as_os_str(),
+                    "checkout".as_ref(),
+                    "--quiet".as_ref(),
+                    "--detach".as_ref(),
+                    rev.as_ref(),
+                ],
+            )
+        };
+        // Branches only exist as remote-tracking refs after a clone
+        checkout(reference).or_else(|_| checkout(&format!("origin/{reference}")))?;
+    }
+    Ok(())
+}
+
+/// Whether `reference` from a lock file can be handed to `git checkout`: a commit sha or a
+/// ref name as `git check-ref-format` allows it, never something git would take for an option
+pub fn is_git_reference(reference: &str) -> bool {
+    !reference.is_empty()
+        && !reference.starts_with(['-', '/', '.'])
+        && !reference.ends_with(['/', '.'])
+        && !reference.ends_with(".lock")
+        && !reference.contains("..")
+        && !reference.contains("//")
+        && !reference.contains("@{")
+        && !reference.contains("/.")
+        && reference.chars().all(|c| {
+            !c.is_control()
+                && !c.is_whitespace()
+                && !matches!(c, '~' | '^' | ':' | '?' | '*' | '[' | '\\')
+        })
+}
+
 pub async fn copy_local_path_optimized(src: &str, target: &Path) -> Result<()> {
     let src = std::path::PathBuf::from(src);
     let target = target.to_path_buf();
@@ -195,7 +632,8 @@ pub async fn copy_local_path_optimized(src: &str, target: &Path) -> Result<()> {
         let mut options = fs_extra::dir::CopyOptions::new();
         options.overwrite = true;
         options.copy_inside = true;
-        options.content_only = false;
+        // The target directory already exists; copy what's in `src`, not `src` itself
+        options.content_only = true;
 
         // Use optimized copying
         fs_extra::dir::copy(&src, &target, &options)
@@ -1,9 +1,23 @@
 // installer submodules grouped under src/core/installer/
+pub mod installer_cache;
+pub mod installer_github;
 pub mod installer_io;
+pub mod installer_links;
+pub mod installer_patches;
+pub mod installer_plan;
+pub mod installer_ssh;
+pub mod installer_state;
+pub mod installer_submodules;
+pub mod installer_summary;
+pub mod installer_urls;
 pub mod installer_utils;
 
 // Re-export commonly used items at crate::core::installer::*
 pub use installer_io::*;
+pub use installer_plan::{InstallPlan, install_plan};
+pub use installer_state::InstallState;
+pub use installer_submodules::SubmoduleConfig;
+pub use installer_summary::print_install_summary;
 pub use installer_utils as inst_utils;
 
 use anyhow::Result;
@@ -12,10 +26,16 @@ use futures::stream::{FuturesUnordered, StreamExt};
 // sha2::Digest moved to installer_utils when needed
 use std::path::Path;
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::fs;
 use tokio::sync::Semaphore;
 use tokio::task;
 
+use crate::core::http::client_for;
+use crate::core::output;
+use crate::core::profiler::{Phase, record_package};
+use crate::core::throttle::throttle;
+use crate::core::timeouts::RequestKind;
 use crate::models::model::LockedPackage;
 use crate::utils;
 
@@ -27,90 +47,144 @@ pub struct InstalledPackage {
     pub path: Utf8PathBuf,
 }
 
-const NETWORK_FACTOR: usize = 50;
 const CPU_FACTOR: usize = 24;
 const MAX_CONCURRENT_EXTRACTIONS: usize = 16;
 
+/// How packages should be installed
+#[derive(Clone, Copy, Debug, Default)]
+pub struct InstallOptions {
+    /// Clone packages from their git source instead of downloading dist archives
+    pub prefer_source: bool,
+}
+
+/// Whether `version` is a branch (`dev-main`, `2.x-dev`) rather than a release
+pub fn is_dev_version(version: &str) -> bool {
+    version.starts_with("dev-") || version.ends_with("-dev")
+}
+
+/// Whether `package` is cloned from its git source rather than downloaded as an archive.
+/// As with Composer's `preferred-install: auto`, branches are cloned so they can be worked
+/// on and updated, unless `preferred-install` (a method, or a map of package patterns to
+/// methods) asks for `dist`; `--prefer-source` clones everything.
+pub fn prefers_source(
+    package: &LockedPackage,
+    prefer_source: bool,
+    preferred_install: Option<&serde_json::Value>,
+) -> bool {
+    if !has_git_source(package) {
+        return false;
+    }
+    let preferred = match preferred_install {
+        Some(serde_json::Value::String(method)) => Some(method.as_str()),
+        Some(serde_json::Value::Object(patterns)) => patterns
+            .iter()
+            .find(|(pattern, _)| crate::workspace::segment_matches(pattern, &package.name))
+            .and_then(|(_, method)| method.as_str()),
+        _ => None,
+    };
+    match preferred {
+        _ if prefer_source => true,
+        Some("source") => true,
+        Some("dist") => false,
+        _ => is_dev_version(&package.version),
+    }
+}
+
 /// Install packages from locked package list
 /// # Errors
 /// Returns an error if packages cannot be downloaded or installed
+pub async fn install_packages(
+    pkgs: &[LockedPackage],
+    project_dir: &Path,
+) -> Result<Vec<InstalledPackage>> {
+    install_packages_with_options(pkgs, project_dir, InstallOptions::default()).await
+}
+
+/// Install packages from locked package list with explicit options
+/// # Errors
+/// Returns an error if packages cannot be downloaded or installed
 /// # Panics
 /// May panic if path conversion fails unexpectedly
 #[allow(clippy::too_many_lines)]
-pub async fn install_packages(
+pub async fn install_packages_with_options(
     pkgs: &[LockedPackage],
     project_dir: &Path,
+    options: InstallOptions,
 ) -> Result<Vec<InstalledPackage>> {
     let vendor = project_dir.join("vendor");
     fs::create_dir_all(&vendor).await?;
 
     let cores = num_cpus::get();
-    let net_sem = Arc::new(Semaphore::new(cores * NETWORK_FACTOR));
+    let max_parallel_http = throttle().limits().max_parallel;
+    let net_sem = Arc::new(Semaphore::new(max_parallel_http));
     let cpu_sem = Arc::new(Semaphore::new(cores * CPU_FACTOR));
     let extract_sem = Arc::new(Semaphore::new(MAX_CONCURRENT_EXTRACTIONS));
 
-    // Ultra-optimized HTTP client with connection pooling and keep-alive
-    let client = reqwest::Client::builder()
-        .user_agent("lectern/0.1")
-        .tcp_nodelay(true)
-        .tcp_keepalive(std::time::Duration::from_secs(60))
-        .pool_idle_timeout(std::time::Duration::from_secs(300))
-        .pool_max_idle_per_host(cores * 8) // Increased pool size
-        .http2_prior_knowledge() // Force HTTP/2 for better multiplexing
-        .http2_keep_alive_interval(std::time::Duration::from_secs(30))
-        .timeout(std::time::Duration::from_secs(60)) // Reduced timeout for faster failure
-        .connection_verbose(false)
-        .build()?;
-
     // Pre-filter packages to avoid unnecessary work
     let mut already_installed = Vec::new();
     let mut to_install = Vec::new();
 
+    let mut state = InstallState::load(&vendor);
+    let root = crate::io::read_composer_json_raw(&crate::io::manifest_path(project_dir)).ok();
+    let preferred_install = root
+        .as_ref()
+        .and_then(|root| root.config.as_ref())
+        .and_then(|config| config.preferred_install.as_ref());
+    let wants_source =
+        |p: &LockedPackage| prefers_source(p, options.prefer_source, preferred_install);
+
+    let extra = root.as_ref().and_then(|root| root.extra.as_ref());
+    let patches = installer_patches::package_patches(extra, project_dir, pkgs).await?;
+
     for p in pkgs {
-        let target = vendor.join(
-            p.name
-                .replace('/', std::path::MAIN_SEPARATOR.to_string().as_str()),
-        );
-
-        // Check if already installed with correct version
-        if target.exists() {
-            if let Ok(composer_path) = target.join("composer.json").canonicalize() {
-                if let Ok(content) = std::fs::read_to_string(&composer_path) {
-                    if let Ok(composer_json) = serde_json::from_str::<serde_json::Value>(&content) {
-                        if let Some(version) = composer_json.get("version").and_then(|v| v.as_str())
-                        {
-                            if version == p.version {
-                                already_installed.push(InstalledPackage {
-                                    name: p.name.clone(),
-                                    version: p.version.clone(),
-                                    path: Utf8PathBuf::from_path_buf(target).unwrap(),
-                                });
-                                continue;
-                            }
-                        }
-                    }
-                }
-            }
+        let target = installer_links::package_dir(&vendor, &p.name);
+        let wants_source = wants_source(p);
+        let patches_changed =
+            state.patches_of(&p.name) != installer_patches::patch_hashes(patches.get(&p.name));
+
+        // Skip packages installed at exactly the locked version, reference and patches,
+        // unless a source install was asked for and the package came from a dist archive
+        if state.is_current(p, &vendor)
+            && !patches_changed
+            && (!wants_source || target.join(".git").exists())
+        {
+            already_installed.push(InstalledPackage {
+                name: p.name.clone(),
+                version: p.version.clone(),
+                path: Utf8PathBuf::from_path_buf(target).unwrap(),
+            });
+            continue;
         }
 
+        if (wants_source && target.exists() && !target.join(".git").exists())
+            || patches_changed
+            || !state.patches_of(&p.name).is_empty()
+        {
+            // Replace the extracted archive with a checkout, or start over from pristine
+            // files before patching
+            installer_links::remove_dir_all_async(&target).await?;
+        }
         to_install.push(p);
     }
 
-    if !already_installed.is_empty() {
-        utils::print_info(&format!(
-            "✅ {} packages already installed",
-            already_installed.len()
-        ));
-    }
-
     if to_install.is_empty() {
+        installer_state::write_installed_json(&vendor, pkgs, &state, &patches)?;
+        print_install_summary(&[], &already_installed);
         return Ok(already_installed);
     }
 
+    // Packages that scripts and bin proxies need are queued first, then the root's direct
+    // requirements; the batches start their work in this order but run concurrently
+    let plan = install_plan(pkgs, root.as_ref());
+    let ranks = plan.ranks();
+    to_install.sort_by_key(|p| ranks.get(p.name.as_str()).copied().unwrap_or(usize::MAX));
+
+    // Collapsed in CI logs; the summary below stays visible
+    let install_group = output::group(&format!("Installing {} packages", to_install.len()));
     utils::print_info(&format!(
         "🚀 Installing {} packages with {}x network concurrency, {}x CPU concurrency",
         to_install.len(),
-        cores * NETWORK_FACTOR,
+        max_parallel_http,
         cores * CPU_FACTOR
     ));
 
@@ -120,7 +194,9 @@ pub async fn install_packages(
     let mut path_packages = Vec::new();
 
     for p in &to_install {
-        if p.dist.is_some() {
+        if wants_source(p) {
+            git_packages.push((*p).clone());
+        } else if p.dist.is_some() {
             dist_packages.push((*p).clone());
         } else if let Some(source) = &p.source {
             if source.source_type == "path" {
@@ -131,47 +207,61 @@ pub async fn install_packages(
         }
     }
 
-    let mut all_results = already_installed;
+    let mut all_results = Vec::new();
 
     // Process all package types in parallel for maximum throughput
     let mut batch_futures = Vec::new();
 
+    let downloads_archives = !dist_packages.is_empty();
+
+    // Source checkouts may need their submodules initialized
+    let submodules = Arc::new(SubmoduleConfig::from_project(project_dir));
+
     // Batch 1: Distribution packages (ZIP/TAR downloads) - highest priority
-    if !dist_packages.is_empty() {
-        let client_clone = client.clone();
+    if downloads_archives {
         let net_sem_clone = net_sem.clone();
         let extract_sem_clone = extract_sem.clone();
+        let cpu_sem_clone = cpu_sem.clone();
         let vendor_clone = vendor.clone();
+        let submodules_clone = submodules.clone();
 
-        batch_futures.push(task::spawn(async move {
+        batch_futures.push(task::spawn(output::in_current_scope(async move {
             install_dist_packages_batch(
                 &dist_packages,
                 &vendor_clone,
-                client_clone,
                 net_sem_clone,
                 extract_sem_clone,
+                cpu_sem_clone,
+                submodules_clone,
             )
             .await
-        }));
+        })));
     }
 
     // Batch 2: Git packages in parallel
     if !git_packages.is_empty() {
         let cpu_sem_clone = cpu_sem.clone();
         let vendor_clone = vendor.clone();
+        let submodules_clone = submodules.clone();
 
-        batch_futures.push(task::spawn(async move {
-            install_git_packages_batch(&git_packages, &vendor_clone, cpu_sem_clone).await
-        }));
+        batch_futures.push(task::spawn(output::in_current_scope(async move {
+            install_git_packages_batch(
+                &git_packages,
+                &vendor_clone,
+                cpu_sem_clone,
+                submodules_clone,
+            )
+            .await
+        })));
     }
 
     // Batch 3: Path packages (usually local, very fast)
     if !path_packages.is_empty() {
         let vendor_clone = vendor.clone();
 
-        batch_futures.push(task::spawn(async move {
+        batch_futures.push(task::spawn(output::in_current_scope(async move {
             install_path_packages_batch(&path_packages, &vendor_clone).await
-        }));
+        })));
     }
 
     // Wait for all batches to complete and collect results
@@ -191,10 +281,24 @@ pub async fn install_packages(
         }
     }
 
-    utils::print_info(&format!(
-        "✅ Successfully installed {} packages",
-        all_results.len()
-    ));
+    drop(install_group);
+
+    for p in &to_install {
+        state.record(p);
+    }
+    let patched =
+        installer_patches::apply_package_patches(&to_install, &patches, &vendor, &mut state);
+    state.save(&vendor)?;
+    installer_state::write_installed_json(&vendor, pkgs, &state, &patches)?;
+    patched?;
+
+    if downloads_archives {
+        installer_cache::spawn_archive_eviction(project_dir);
+    }
+
+    print_install_summary(&all_results, &already_installed);
+
+    all_results.extend(already_installed);
     Ok(all_results)
 }
 
@@ -202,9 +306,10 @@ pub async fn install_packages(
 async fn install_dist_packages_batch(
     packages: &[LockedPackage],
     vendor: &Path,
-    client: reqwest::Client,
     net_sem: Arc<Semaphore>,
     extract_sem: Arc<Semaphore>,
+    cpu_sem: Arc<Semaphore>,
+    submodules: Arc<SubmoduleConfig>,
 ) -> Result<Vec<InstalledPackage>> {
     utils::print_info(&format!(
         "🚀 Batch processing {} distribution packages",
@@ -215,60 +320,96 @@ async fn install_dist_packages_batch(
 
     for p in packages {
         if let Some(dist_info) = &p.dist {
-            let target = vendor.join(
-                p.name
-                    .replace('/', std::path::MAIN_SEPARATOR.to_string().as_str()),
-            );
+            let target = installer_links::package_dir(vendor, &p.name);
 
-            let client = client.clone();
+            let dist = dist_info.clone();
+            let url = dist.url.clone();
+            let client = client_for(&url, RequestKind::Download);
             let net_sem = net_sem.clone();
             let extract_sem = extract_sem.clone();
-            let url = dist_info.url.clone();
-            let name = p.name.clone();
-            let version = p.version.clone();
-
-            futures.push(tokio::spawn(async move {
-                // Create target directory
-                fs::create_dir_all(&target).await?;
-
-                // Download and extract with streaming for better memory usage
-                installer_io::download_and_extract_streaming(
-                    &url,
-                    &target,
-                    client,
-                    net_sem,
-                    extract_sem,
-                    &name,
-                    &version,
-                )
-                .await?;
-
-                Ok(InstalledPackage {
-                    name,
-                    version,
-                    path: Utf8PathBuf::from_path_buf(target).unwrap(),
-                })
-            }));
+            let package = p.clone();
+
+            let tag = package.name.clone();
+            futures.push(tokio::spawn(output::with_package(&tag, async move {
+                let name = package.name.clone();
+                let version = package.version.clone();
+                let result = async {
+                    utils::print_debug(&format!("Downloading {url}"));
+                    // Create target directory
+                    fs::create_dir_all(&target).await?;
+
+                    // Download and extract with streaming for better memory usage
+                    installer_io::download_and_extract_streaming(
+                        &dist,
+                        &target,
+                        client,
+                        net_sem,
+                        extract_sem,
+                        &name,
+                        &version,
+                    )
+                    .await?;
+
+                    Ok(InstalledPackage {
+                        name,
+                        version,
+                        path: Utf8PathBuf::from_path_buf(target).unwrap(),
+                    })
+                }
+                .await;
+                (package, result)
+            })));
         }
     }
 
     let mut results = Vec::new();
+    let mut fallback = Vec::new();
     while let Some(result) = futures.next().await {
         match result {
-            Ok(Ok(installed)) => results.push(installed),
-            Ok(Err(e)) => return Err(e),
+            Ok((_, Ok(installed))) => results.push(installed),
+            Ok((package, Err(e))) => {
+                // Like Composer, retry from the locked source before giving up
+                if !has_git_source(&package) {
+                    return Err(e);
+                }
+                utils::print_warning(&format!(
+                    "⚠️  Failed to download {} dist ({e}), falling back to source",
+                    package.name
+                ));
+                fallback.push(package);
+            }
             Err(e) => return Err(anyhow::anyhow!("Task failed: {}", e)),
         }
     }
 
+    if !fallback.is_empty() {
+        for package in &fallback {
+            // Drop whatever a partial extraction left behind so the clone starts clean
+            let target = installer_links::package_dir(vendor, &package.name);
+            let _ = installer_links::remove_dir_all_async(&target).await;
+        }
+        let mut cloned = install_git_packages_batch(&fallback, vendor, cpu_sem, submodules)
+            .await
+            .map_err(|e| e.context("dist download and source fallback both failed"))?;
+        results.append(&mut cloned);
+    }
+
     Ok(results)
 }
 
+/// Whether a package can be cloned from its locked source reference
+fn has_git_source(package: &LockedPackage) -> bool {
+    package.source.as_ref().is_some_and(|source| {
+        source.source_type != "path" && !source.url.is_empty() && !source.reference.is_empty()
+    })
+}
+
 // Ultra-fast batch processing for git packages
 async fn install_git_packages_batch(
     packages: &[LockedPackage],
     vendor: &Path,
     cpu_sem: Arc<Semaphore>,
+    submodules: Arc<SubmoduleConfig>,
 ) -> Result<Vec<InstalledPackage>> {
     utils::print_info(&format!(
         "🚀 Batch processing {} git packages",
@@ -279,28 +420,32 @@ async fn install_git_packages_batch(
 
     for p in packages {
         if let Some(source_info) = &p.source {
-            let target = vendor.join(
-                p.name
-                    .replace('/', std::path::MAIN_SEPARATOR.to_string().as_str()),
-            );
+            let target = installer_links::package_dir(vendor, &p.name);
 
             let cpu_sem = cpu_sem.clone();
             let url = source_info.url.clone();
             let reference = source_info.reference.clone();
             let name = p.name.clone();
             let version = p.version.clone();
+            let submodule_depth = submodules.depth_for(&p.name);
 
-            futures.push(tokio::spawn(async move {
+            futures.push(tokio::spawn(output::with_package(&p.name, async move {
                 fs::create_dir_all(&target).await?;
 
-                inst_utils::clone_git_optimized(&url, Some(&reference), &target, cpu_sem).await?;
+                let started = Instant::now();
+                inst_utils::clone_git_optimized(&url, Some(&reference), &target, cpu_sem.clone())
+                    .await?;
+                if let Some(depth) = submodule_depth {
+                    installer_submodules::init_submodules(&target, &url, depth, cpu_sem).await?;
+                }
+                record_package(&name, Phase::Download, started.elapsed());
 
                 Ok(InstalledPackage {
                     name,
                     version,
                     path: Utf8PathBuf::from_path_buf(target).unwrap(),
                 })
-            }));
+            })));
         }
     }
 
@@ -330,26 +475,25 @@ async fn install_path_packages_batch(
 
     for p in packages {
         if let Some(source_info) = &p.source {
-            let target = vendor.join(
-                p.name
-                    .replace('/', std::path::MAIN_SEPARATOR.to_string().as_str()),
-            );
+            let target = installer_links::package_dir(vendor, &p.name);
 
             let src_path = source_info.url.clone();
             let name = p.name.clone();
             let version = p.version.clone();
 
-            futures.push(tokio::spawn(async move {
+            futures.push(tokio::spawn(output::with_package(&p.name, async move {
                 fs::create_dir_all(&target).await?;
 
+                let started = Instant::now();
                 inst_utils::copy_local_path_optimized(&src_path, &target).await?;
+                record_package(&name, Phase::Extraction, started.elapsed());
 
                 Ok(InstalledPackage {
                     name,
                     version,
                     path: Utf8PathBuf::from_path_buf(target).unwrap(),
                 })
-            }));
+            })));
         }
     }
 
@@ -1,29 +1,142 @@
 use crate::core::cache_utils::get_cache_dir;
+use crate::core::error::LecternError;
+use crate::core::installer::installer_links::remove_dir_all_async;
+use crate::core::placeholders::expand_composer;
+use crate::models::lectern_toml::{ManifestFormat, json_to_toml, toml_to_json};
 use crate::models::model::{ComposerJson, Lock};
+use crate::resolver::dependency_utils::generate_content_hash;
 use anyhow::{Context, Result};
+use serde::Serialize;
 use serde_json;
 use std::collections::HashMap;
 use std::fs;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 // Composer JSON support
-pub fn read_composer_json(path: &Path) -> Result<ComposerJson> {
+/// The project's manifest: composer.json, or Lectern.toml when only that exists
+pub fn manifest_path(working_dir: &Path) -> PathBuf {
+    working_dir.join(ManifestFormat::detect(working_dir).file_name())
+}
+
+/// The manifest at `path` as composer.json text; Lectern.toml is converted
+/// # Errors
+/// Returns an error if the file cannot be read, or is not valid TOML
+pub fn read_manifest_text(path: &Path) -> Result<String> {
     let s = fs::read_to_string(path).with_context(|| format!("read {path:?}"))?;
-    let json: ComposerJson = serde_json::from_str(&s).context("parse composer.json")?;
+    match ManifestFormat::of_path(path) {
+        ManifestFormat::ComposerJson => Ok(s),
+        ManifestFormat::LecternToml => Ok(serde_json::to_string_pretty(&toml_to_json(&s)?)?),
+    }
+}
+
+/// The project's manifest as JSON, for settings read straight from it; `None` if it is
+/// missing or invalid
+pub fn read_manifest_value(working_dir: &Path) -> Option<serde_json::Value> {
+    let text = read_manifest_text(&manifest_path(working_dir)).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+/// Save composer.json text to the manifest at `path`, as TOML for Lectern.toml
+/// # Errors
+/// Returns an error if `json` is not a JSON object or the file cannot be written
+pub fn write_manifest_text(path: &Path, json: &str) -> Result<()> {
+    match ManifestFormat::of_path(path) {
+        ManifestFormat::ComposerJson => write_file_atomic(path, json),
+        ManifestFormat::LecternToml => {
+            let value: serde_json::Value =
+                serde_json::from_str(json).map_err(LecternError::InvalidComposerJson)?;
+            write_file_atomic(path, &json_to_toml(&value)?)
+        }
+    }
+}
+
+/// Read composer.json with `${VAR}` placeholders in repositories, config and scripts expanded
+pub fn read_composer_json(path: &Path) -> Result<ComposerJson> {
+    parse_composer_json(&read_manifest_text(path)?)
+}
+
+/// Parse composer.json contents, expanding `${VAR}` placeholders like `read_composer_json`
+pub fn parse_composer_json(s: &str) -> Result<ComposerJson> {
+    let mut value: serde_json::Value =
+        serde_json::from_str(s).map_err(LecternError::InvalidComposerJson)?;
+    expand_composer(&mut value)?;
+    let json: ComposerJson =
+        serde_json::from_value(value).map_err(LecternError::InvalidComposerJson)?;
+    Ok(json)
+}
+
+/// Read composer.json as written, keeping placeholders, for commands that save it back
+pub fn read_composer_json_raw(path: &Path) -> Result<ComposerJson> {
+    let s = read_manifest_text(path)?;
+    let json: ComposerJson = serde_json::from_str(&s).map_err(LecternError::InvalidComposerJson)?;
     Ok(json)
 }
 
+/// Composer-compatible lock `content-hash` of the composer.json file on disk. For
+/// Lectern.toml it is the hash of the equivalent composer.json.
+pub fn read_composer_content_hash(path: &Path) -> Result<String> {
+    let s = read_manifest_text(path)?;
+    Ok(generate_content_hash(&s))
+}
+
 pub fn write_composer_json(path: &Path, composer: &ComposerJson) -> Result<()> {
     let s = serde_json::to_string_pretty(composer)?;
-    let mut f = fs::File::create(path)?;
-    f.write_all(s.as_bytes())?;
+    write_manifest_text(path, &s)
+}
+
+/// Replace a file without ever leaving it half written: the contents go to a temp file in
+/// the same directory, are synced, and renamed over the original. The original's
+/// permissions and CRLF line endings are kept.
+/// # Errors
+/// Returns an error if the temp file cannot be written or renamed
+pub fn write_file_atomic(path: &Path, contents: &str) -> Result<()> {
+    let existing = fs::read(path).ok();
+    let crlf = existing
+        .as_ref()
+        .is_some_and(|old| old.windows(2).any(|pair| pair == b"\r\n"));
+    let contents = if crlf && !contents.contains("\r\n") {
+        contents.replace('\n', "\r\n")
+    } else {
+        contents.to_string()
+    };
+
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let temp_path = dir.join(format!(".{file_name}.{}.tmp", std::process::id()));
+
+    let written = (|| -> Result<()> {
+        let mut f = fs::File::create(&temp_path)?;
+        f.write_all(contents.as_bytes())?;
+        if existing.is_some() {
+            f.set_permissions(fs::metadata(path)?.permissions())?;
+        }
+        f.sync_all()?;
+        fs::rename(&temp_path, path)?;
+        Ok(())
+    })();
+    if written.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    written.with_context(|| format!("write {path:?}"))?;
+
+    // Make the rename itself durable
+    #[cfg(unix)]
+    if let Ok(dir) = fs::File::open(dir) {
+        let _ = dir.sync_all();
+    }
     Ok(())
 }
 
 // Read project configuration
 pub fn read_project_config(working_dir: &Path) -> Result<ComposerJson> {
-    let composer_path = working_dir.join("composer.json");
+    let composer_path = manifest_path(working_dir);
     if composer_path.exists() {
         read_composer_json(&composer_path)
     } else {
@@ -34,15 +147,45 @@ pub fn read_project_config(working_dir: &Path) -> Result<ComposerJson> {
 // Lock file operations (JSON format for Composer compatibility)
 pub fn read_lock(path: &Path) -> Result<Lock> {
     let s = fs::read_to_string(path).with_context(|| format!("read {path:?}"))?;
-    let lock: Lock = serde_json::from_str(&s).context("parse lock file")?;
+    let value: serde_json::Value = serde_json::from_str(&s).context("parse lock file")?;
+    let empty_list_keys = value
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter(|(_, v)| v.as_array().is_some_and(Vec::is_empty))
+        .map(|(key, _)| key.clone())
+        .collect();
+    let mut lock: Lock = serde_json::from_value(value).context("parse lock file")?;
+    lock.empty_list_keys = empty_list_keys;
     Ok(lock)
 }
 
+/// A lock in Composer's formatting (4-space indent, trailing newline), with empty maps the
+/// lock was read with as `[]` written that way again
+/// # Errors
+/// Returns an error if the lock cannot be serialized
+pub fn lock_to_string(lock: &Lock) -> Result<String> {
+    let mut value = serde_json::to_value(lock)?;
+    if let Some(object) = value.as_object_mut() {
+        for key in &lock.empty_list_keys {
+            if let Some(v) = object.get_mut(key)
+                && v.as_object().is_some_and(serde_json::Map::is_empty)
+            {
+                *v = serde_json::Value::Array(Vec::new());
+            }
+        }
+    }
+    let mut buf = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(b"    ");
+    let mut ser = serde_json::Serializer::with_formatter(&mut buf, formatter);
+    value.serialize(&mut ser)?;
+    buf.push(b'\n');
+    Ok(String::from_utf8(buf)?)
+}
+
+/// Write composer.lock with Composer's formatting
 pub fn write_lock(path: &Path, lock: &Lock) -> Result<()> {
-    let s = serde_json::to_string_pretty(lock)?;
-    let mut f = fs::File::create(path)?;
-    f.write_all(s.as_bytes())?;
-    Ok(())
+    write_file_atomic(path, &lock_to_string(lock)?)
 }
 
 /// Write cache data to a file
@@ -74,9 +217,7 @@ pub fn read_cache(path: &Path) -> Result<HashMap<String, String>> {
 
 pub async fn clean(dir: &Path) -> Result<()> {
     let vendor = dir.join("vendor");
-    if vendor.exists() {
-        tokio::fs::remove_dir_all(&vendor).await.ok();
-    }
+    remove_dir_all_async(&vendor).await.ok();
     // Clean lock file
     let composer_lock = dir.join("composer.lock");
     if composer_lock.exists() {
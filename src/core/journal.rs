@@ -0,0 +1,181 @@
+use crate::core::io::{manifest_path, write_file_atomic};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Journal entries older than the newest this many are pruned
+pub const JOURNAL_RETENTION: usize = 20;
+
+/// Contents of composer.json and composer.lock at one point in time (`None` = file absent)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub composer_json: Option<String>,
+    pub composer_lock: Option<String>,
+}
+
+impl Snapshot {
+    /// Capture the current project files
+    /// # Errors
+    /// Returns an error if an existing file cannot be read
+    pub fn capture(working_dir: &Path) -> Result<Self> {
+        Ok(Self {
+            composer_json: read_optional(&manifest_path(working_dir))?,
+            composer_lock: read_optional(&working_dir.join("composer.lock"))?,
+        })
+    }
+
+    /// Write these contents back, deleting files that did not exist
+    /// # Errors
+    /// Returns an error if a file cannot be written or removed
+    pub fn restore(&self, working_dir: &Path) -> Result<()> {
+        restore_file(&manifest_path(working_dir), self.composer_json.as_deref())?;
+        restore_file(
+            &working_dir.join("composer.lock"),
+            self.composer_lock.as_deref(),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryStatus {
+    /// Written before the operation started; it never finished if still pending
+    Pending,
+    Committed,
+}
+
+/// One mutating operation with the project state before and after it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub id: u64,
+    pub operation: String,
+    pub timestamp: u64,
+    pub status: EntryStatus,
+    pub before: Snapshot,
+    #[serde(default)]
+    pub after: Option<Snapshot>,
+    /// The operation left `packages-dev` out of vendor/, so a rollback does too
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub no_dev: bool,
+}
+
+/// Directory holding the journal of a project
+pub fn journal_dir(working_dir: &Path) -> PathBuf {
+    working_dir.join(".lectern").join("journal")
+}
+
+fn entry_path(working_dir: &Path, id: u64) -> PathBuf {
+    journal_dir(working_dir).join(format!("{id:08}.json"))
+}
+
+fn read_optional(path: &Path) -> Result<Option<String>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(path).with_context(|| format!("read {path:?}"))?;
+    Ok(Some(content))
+}
+
+fn restore_file(path: &Path, content: Option<&str>) -> Result<()> {
+    match content {
+        Some(content) => write_file_atomic(path, content),
+        None if path.exists() => fs::remove_file(path).with_context(|| format!("remove {path:?}")),
+        None => Ok(()),
+    }
+}
+
+fn write_entry(working_dir: &Path, entry: &JournalEntry) -> Result<()> {
+    let dir = journal_dir(working_dir);
+    fs::create_dir_all(&dir)?;
+    let mut f = fs::File::create(entry_path(working_dir, entry.id))?;
+    f.write_all(serde_json::to_string_pretty(entry)?.as_bytes())?;
+    // The entry must be durable before the operation touches any project file
+    f.sync_all()?;
+    Ok(())
+}
+
+/// All journal entries, oldest first
+/// # Errors
+/// Returns an error if the journal directory or an entry cannot be read
+pub fn list_entries(working_dir: &Path) -> Result<Vec<JournalEntry>> {
+    let dir = journal_dir(working_dir);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut entries = Vec::new();
+    for file in fs::read_dir(&dir)? {
+        let path = file?.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            let content = fs::read_to_string(&path)?;
+            let entry: JournalEntry = serde_json::from_str(&content)
+                .with_context(|| format!("parse journal entry {path:?}"))?;
+            entries.push(entry);
+        }
+    }
+    entries.sort_by_key(|e| e.id);
+    Ok(entries)
+}
+
+/// Record the state before a mutating operation (write-ahead)
+/// # Errors
+/// Returns an error if the snapshot or journal entry cannot be written
+pub fn begin_operation(working_dir: &Path, operation: &str) -> Result<JournalEntry> {
+    begin_install_operation(working_dir, operation, false)
+}
+
+/// Like `begin_operation`, also recording whether the operation installs without
+/// `packages-dev`
+/// # Errors
+/// Returns an error if the snapshot or journal entry cannot be written
+pub fn begin_install_operation(
+    working_dir: &Path,
+    operation: &str,
+    no_dev: bool,
+) -> Result<JournalEntry> {
+    let entries = list_entries(working_dir)?;
+    let entry = JournalEntry {
+        id: entries.last().map_or(1, |e| e.id + 1),
+        operation: operation.to_string(),
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default(),
+        status: EntryStatus::Pending,
+        before: Snapshot::capture(working_dir)?,
+        after: None,
+        no_dev,
+    };
+    write_entry(working_dir, &entry)?;
+
+    // Prune the oldest entries
+    let excess = (entries.len() + 1).saturating_sub(JOURNAL_RETENTION);
+    for old in entries.iter().take(excess) {
+        let _ = fs::remove_file(entry_path(working_dir, old.id));
+    }
+
+    Ok(entry)
+}
+
+/// Mark an operation as finished and record the resulting state
+/// # Errors
+/// Returns an error if the snapshot or journal entry cannot be written
+pub fn commit_operation(working_dir: &Path, entry: &mut JournalEntry) -> Result<()> {
+    entry.after = Some(Snapshot::capture(working_dir)?);
+    entry.status = EntryStatus::Committed;
+    write_entry(working_dir, entry)
+}
+
+/// Remove an entry once it has been rolled back
+/// # Errors
+/// Returns an error if the entry file cannot be removed
+pub fn discard_entry(working_dir: &Path, entry: &JournalEntry) -> Result<()> {
+    let path = entry_path(working_dir, entry.id);
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,157 @@
+//! What changes between two lock files, reported the way Composer lists its lock file
+//! operations: "Installing x (1.2.3)", "Upgrading y (1.0.0 => 1.1.0)", "Removing z (2.0.0)"
+
+use crate::installer::installer_state::package_reference;
+use crate::models::model::{Lock, LockedPackage};
+use crate::resolver::version::ComposerVersion;
+use crate::utils::{print_info, print_text};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A change to one package between two lock files
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackageChange {
+    Install {
+        name: String,
+        version: String,
+    },
+    Upgrade {
+        name: String,
+        from: String,
+        to: String,
+    },
+    Downgrade {
+        name: String,
+        from: String,
+        to: String,
+    },
+    Remove {
+        name: String,
+        version: String,
+    },
+}
+
+impl PackageChange {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Install { name, .. }
+            | Self::Upgrade { name, .. }
+            | Self::Downgrade { name, .. }
+            | Self::Remove { name, .. } => name,
+        }
+    }
+}
+
+impl fmt::Display for PackageChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Install { name, version } => write!(f, "Installing {name} ({version})"),
+            Self::Upgrade { name, from, to } => write!(f, "Upgrading {name} ({from} => {to})"),
+            Self::Downgrade { name, from, to } => {
+                write!(f, "Downgrading {name} ({from} => {to})")
+            }
+            Self::Remove { name, version } => write!(f, "Removing {name} ({version})"),
+        }
+    }
+}
+
+/// The version shown for a package: branches also show the commit they are locked at,
+/// so moving a branch to a new commit reads as a change
+fn display_version(pkg: &LockedPackage) -> String {
+    match package_reference(pkg) {
+        Some(reference) if crate::installer::is_dev_version(&pkg.version) => {
+            let short: String = reference.chars().take(7).collect();
+            format!("{} {short}", pkg.version)
+        }
+        _ => pkg.version.clone(),
+    }
+}
+
+fn locked(lock: Option<&Lock>) -> BTreeMap<String, &LockedPackage> {
+    lock.into_iter()
+        .flat_map(|lock| lock.packages.iter().chain(&lock.packages_dev))
+        .map(|pkg| (pkg.name.to_lowercase(), pkg))
+        .collect()
+}
+
+/// The changes that turn `old` (no lock file yet when `None`) into `new`: installs,
+/// upgrades, downgrades, then removals, each by package name. A package moving between
+/// `packages` and `packages-dev` isn't a change.
+pub fn diff_locks(old: Option<&Lock>, new: &Lock) -> Vec<PackageChange> {
+    let before = locked(old);
+    let after = locked(Some(new));
+
+    let mut changes = Vec::new();
+    for (key, pkg) in &after {
+        let Some(previous) = before.get(key) else {
+            changes.push(PackageChange::Install {
+                name: pkg.name.clone(),
+                version: display_version(pkg),
+            });
+            continue;
+        };
+        if previous.version == pkg.version && package_reference(previous) == package_reference(pkg)
+        {
+            continue;
+        }
+        let (from, to) = (display_version(previous), display_version(pkg));
+        let name = pkg.name.clone();
+        let older = match (
+            ComposerVersion::parse(&pkg.version),
+            ComposerVersion::parse(&previous.version),
+        ) {
+            (Some(new), Some(old)) => new < old,
+            _ => false,
+        };
+        changes.push(if older {
+            PackageChange::Downgrade { name, from, to }
+        } else {
+            PackageChange::Upgrade { name, from, to }
+        });
+    }
+    for (key, pkg) in &before {
+        if !after.contains_key(key) {
+            changes.push(PackageChange::Remove {
+                name: pkg.name.clone(),
+                version: display_version(pkg),
+            });
+        }
+    }
+    changes.sort_by_key(|change| match change {
+        PackageChange::Install { .. } => 0,
+        PackageChange::Upgrade { .. } => 1,
+        PackageChange::Downgrade { .. } => 2,
+        PackageChange::Remove { .. } => 3,
+    });
+    changes
+}
+
+/// Print the lock file operations, with a count per kind before the list
+pub fn print_lock_diff(changes: &[PackageChange]) {
+    if changes.is_empty() {
+        print_info("Nothing to install, update or remove");
+        return;
+    }
+    let count = |matches: fn(&PackageChange) -> bool| changes.iter().filter(|c| matches(c)).count();
+    let installs = count(|c| matches!(c, PackageChange::Install { .. }));
+    let updates = count(|c| {
+        matches!(
+            c,
+            PackageChange::Upgrade { .. } | PackageChange::Downgrade { .. }
+        )
+    });
+    let removals = count(|c| matches!(c, PackageChange::Remove { .. }));
+    let plural =
+        |n: usize, one: &str, many: &str| format!("{n} {}", if n == 1 { one } else { many });
+    print_info(&format!(
+        "Lock file operations: {}, {}, {}",
+        plural(installs, "install", "installs"),
+        plural(updates, "update", "updates"),
+        plural(removals, "removal", "removals")
+    ));
+    let lines: Vec<String> = changes
+        .iter()
+        .map(|change| format!("  - {change}"))
+        .collect();
+    print_text(&lines.join("\n"));
+}
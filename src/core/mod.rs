@@ -1,8 +1,27 @@
 // Core module grouping. Each submodule corresponds to a file under src/core/.
+pub mod api;
+pub mod auth;
 pub mod autoload;
 pub mod cache;
 pub mod cache_utils;
+pub mod ci;
 pub mod commands;
+pub mod environments;
+pub mod error;
+pub mod graph;
+pub mod http;
+pub mod ignore_list;
 pub mod installer;
 pub mod io;
+pub mod journal;
+pub mod lock_diff;
+pub mod output;
+pub mod php_scanner;
+pub mod placeholders;
+pub mod platform;
+pub mod profiler;
+pub mod proxy;
+pub mod throttle;
+pub mod timeouts;
 pub mod utils;
+pub mod workspace;
@@ -0,0 +1,325 @@
+use crate::core::ci::CiProvider;
+use colored::Colorize;
+use std::future::Future;
+use std::io::Write;
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock, RwLock, mpsc};
+
+/// Where a line of output goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+struct Line {
+    stream: Stream,
+    text: String,
+    written: mpsc::SyncSender<()>,
+}
+
+/// Every line is written by a single thread, so concurrent install tasks can't interleave
+/// partial lines. Senders wait until their line is written to stay ordered with plain `println!`.
+static WRITER: OnceLock<mpsc::Sender<Line>> = OnceLock::new();
+
+fn writer() -> Option<&'static mpsc::Sender<Line>> {
+    if let Some(sender) = WRITER.get() {
+        return Some(sender);
+    }
+    let (sender, receiver) = mpsc::channel::<Line>();
+    std::thread::Builder::new()
+        .name("lectern-output".to_string())
+        .spawn(move || {
+            for line in receiver {
+                let _ = match line.stream {
+                    Stream::Stdout => writeln!(std::io::stdout().lock(), "{}", line.text),
+                    Stream::Stderr => writeln!(std::io::stderr().lock(), "{}", line.text),
+                };
+                let _ = line.written.send(());
+            }
+        })
+        .ok()?;
+    // Another thread may have won the race; its writer is used and ours exits when dropped
+    Some(WRITER.get_or_init(|| sender))
+}
+
+/// Write one message (possibly several lines) as a unit
+pub fn emit(stream: Stream, text: String) {
+    let (written, done) = mpsc::sync_channel(1);
+    match writer() {
+        Some(sender) => match sender.send(Line {
+            stream,
+            text,
+            written,
+        }) {
+            Ok(()) => {
+                let _ = done.recv();
+            }
+            Err(mpsc::SendError(line)) => write_directly(line.stream, &line.text),
+        },
+        None => write_directly(stream, &text),
+    }
+}
+
+fn write_directly(stream: Stream, text: &str) {
+    match stream {
+        Stream::Stdout => println!("{text}"),
+        Stream::Stderr => eprintln!("{text}"),
+    }
+}
+
+/// What kind of message is being reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    Success,
+    Info,
+    Warning,
+    Error,
+    Step,
+    /// Untagged lines such as summaries and tables
+    Text,
+    /// Details for `-vv` and above
+    Debug,
+}
+
+impl Level {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+            Self::Step => "step",
+            Self::Text => "text",
+            Self::Debug => "debug",
+        }
+    }
+
+    /// Whether the level is shown with `quiet` (`-q`) and `verbosity` (`-v` count): quiet
+    /// keeps only errors, debug messages need `-vv`
+    pub fn shown(self, quiet: bool, verbosity: u8) -> bool {
+        match self {
+            Self::Error => true,
+            _ if quiet => false,
+            Self::Debug => verbosity >= 2,
+            _ => true,
+        }
+    }
+}
+
+/// Receives every status message Lectern prints. The default `ConsoleReporter` writes tagged
+/// lines to the terminal; embedders can capture or silence output with `with_reporter`.
+pub trait Reporter: Send + Sync {
+    /// `package` is the package the message is about, inside a `with_package` scope
+    fn report(&self, level: Level, package: Option<&str>, message: &str);
+}
+
+/// Tagged, colored lines on stdout, with warnings and errors on stderr
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConsoleReporter;
+
+impl Reporter for ConsoleReporter {
+    fn report(&self, level: Level, package: Option<&str>, message: &str) {
+        let (stream, label) = match level {
+            Level::Success => (Stream::Stdout, "[SUCCESS]".green().bold()),
+            Level::Info => (Stream::Stdout, "[INFO]".blue().bold()),
+            Level::Warning => (Stream::Stderr, "[WARNING]".yellow().bold()),
+            Level::Error => (Stream::Stderr, "[ERROR]".red().bold()),
+            Level::Step => (Stream::Stdout, "[STEP]".cyan().bold()),
+            Level::Debug => (Stream::Stderr, "[DEBUG]".dimmed()),
+            Level::Text => return emit(Stream::Stdout, message.to_string()),
+        };
+        let prefix = package
+            .map(|package| format!("[{package}] "))
+            .unwrap_or_default();
+        emit(stream, format!("{label} {prefix}{message}"));
+    }
+}
+
+/// One JSON object per message on stdout, for tools that drive Lectern:
+/// `{"level":"success","package":null,"message":"Resolved 3 packages"}`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonReporter;
+
+impl Reporter for JsonReporter {
+    fn report(&self, level: Level, package: Option<&str>, message: &str) {
+        let line = serde_json::json!({
+            "level": level.as_str(),
+            "package": package,
+            "message": strip_symbols(message),
+        });
+        emit(Stream::Stdout, line.to_string());
+    }
+}
+
+/// `message` without the emoji and spacing it starts with
+pub fn strip_symbols(message: &str) -> &str {
+    message
+        .trim_start_matches(|c: char| c.is_whitespace() || (!c.is_ascii() && !c.is_alphanumeric()))
+}
+
+/// Drops every message
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SilentReporter;
+
+impl Reporter for SilentReporter {
+    fn report(&self, _level: Level, _package: Option<&str>, _message: &str) {}
+}
+
+/// The reporter of the CLI run, for messages outside any `with_reporter` scope; `None`
+/// means the console
+static DEFAULT_REPORTER: RwLock<Option<Arc<dyn Reporter>>> = RwLock::new(None);
+
+tokio::task_local! {
+    static REPORTER: Option<Arc<dyn Reporter>>;
+    static PACKAGE: String;
+}
+
+/// Send messages outside any `with_reporter` scope to `reporter` for the rest of the run
+pub fn set_reporter(reporter: Arc<dyn Reporter>) {
+    *DEFAULT_REPORTER.write().unwrap_or_else(|e| e.into_inner()) = Some(reporter);
+}
+
+/// Run a future with its messages sent to `reporter`. The scope belongs to the task, so
+/// concurrent operations with different reporters don't see each other's messages; tasks
+/// spawned from it keep the reporter through `with_package` or `in_current_scope`.
+pub fn with_reporter<F: Future>(
+    reporter: Arc<dyn Reporter>,
+    future: F,
+) -> impl Future<Output = F::Output> + use<F> {
+    REPORTER.scope(Some(reporter), future)
+}
+
+/// Like `with_reporter`, for synchronous work
+pub fn with_reporter_sync<R>(reporter: Arc<dyn Reporter>, f: impl FnOnce() -> R) -> R {
+    REPORTER.sync_scope(Some(reporter), f)
+}
+
+/// Keep the reporter of the current scope in a future that is about to be spawned
+pub fn in_current_scope<F: Future>(future: F) -> impl Future<Output = F::Output> + use<F> {
+    let reporter = REPORTER.try_with(Clone::clone).ok().flatten();
+    REPORTER.scope(reporter, future)
+}
+
+fn current_reporter() -> Option<Arc<dyn Reporter>> {
+    REPORTER.try_with(Clone::clone).ok().flatten().or_else(|| {
+        DEFAULT_REPORTER
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    })
+}
+
+/// Pass a message to the current reporter, tagged with the current package, unless `-q` or
+/// the verbosity hides its level
+pub fn report(level: Level, message: &str) {
+    if !level.shown(crate::utils::quiet(), crate::utils::verbosity()) {
+        return;
+    }
+    let reporter = current_reporter();
+    let package = current_package();
+    match reporter {
+        Some(reporter) => reporter.report(level, package.as_deref(), message),
+        None => ConsoleReporter.report(level, package.as_deref(), message),
+    }
+}
+
+/// Run a future with its output tagged with the package it belongs to, keeping the reporter
+/// of the current scope
+pub fn with_package<F: Future>(
+    package: &str,
+    future: F,
+) -> impl Future<Output = F::Output> + use<F> {
+    in_current_scope(PACKAGE.scope(package.to_string(), future))
+}
+
+/// The package of the enclosing `with_package` scope
+pub fn current_package() -> Option<String> {
+    PACKAGE.try_with(String::clone).ok()
+}
+
+/// `[vendor/package] ` inside a `with_package` scope, empty otherwise
+pub fn package_prefix() -> String {
+    current_package()
+        .map(|package| format!("[{package}] "))
+        .unwrap_or_default()
+}
+
+const GROUPS_PLAIN: u8 = 0;
+const GROUPS_GITHUB: u8 = 1;
+const GROUPS_GITLAB: u8 = 2;
+
+static GROUP_STYLE: AtomicU8 = AtomicU8::new(GROUPS_PLAIN);
+static NEXT_GROUP_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Use the collapsible log sections of the detected CI provider
+pub fn set_ci_provider(provider: Option<CiProvider>) {
+    let style = match provider {
+        Some(CiProvider::GitHubActions) => GROUPS_GITHUB,
+        Some(CiProvider::GitLabCi) => GROUPS_GITLAB,
+        _ => GROUPS_PLAIN,
+    };
+    GROUP_STYLE.store(style, Ordering::Relaxed);
+}
+
+/// Marker that opens a collapsible section, if the provider supports them
+pub fn group_start_marker(
+    provider: Option<CiProvider>,
+    id: &str,
+    title: &str,
+    timestamp: u64,
+) -> Option<String> {
+    match provider {
+        Some(CiProvider::GitHubActions) => Some(format!("::group::{title}")),
+        Some(CiProvider::GitLabCi) => Some(format!(
+            "\x1b[0Ksection_start:{timestamp}:{id}[collapsed=true]\r\x1b[0K{title}"
+        )),
+        _ => None,
+    }
+}
+
+/// Marker that closes a collapsible section, if the provider supports them
+pub fn group_end_marker(provider: Option<CiProvider>, id: &str, timestamp: u64) -> Option<String> {
+    match provider {
+        Some(CiProvider::GitHubActions) => Some("::endgroup::".to_string()),
+        Some(CiProvider::GitLabCi) => Some(format!("\x1b[0Ksection_end:{timestamp}:{id}\r\x1b[0K")),
+        _ => None,
+    }
+}
+
+fn group_provider() -> Option<CiProvider> {
+    match GROUP_STYLE.load(Ordering::Relaxed) {
+        GROUPS_GITHUB => Some(CiProvider::GitHubActions),
+        GROUPS_GITLAB => Some(CiProvider::GitLabCi),
+        _ => None,
+    }
+}
+
+fn unix_timestamp() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// A collapsible section of CI log output, closed when dropped
+pub struct OutputGroup {
+    id: String,
+}
+
+/// Start a collapsible section in CI logs; outside CI nothing is printed
+pub fn group(title: &str) -> OutputGroup {
+    let id = format!("lectern_{}", NEXT_GROUP_ID.fetch_add(1, Ordering::Relaxed));
+    if let Some(marker) = group_start_marker(group_provider(), &id, title, unix_timestamp()) {
+        emit(Stream::Stdout, marker);
+    }
+    OutputGroup { id }
+}
+
+impl Drop for OutputGroup {
+    fn drop(&mut self) {
+        if let Some(marker) = group_end_marker(group_provider(), &self.id, unix_timestamp()) {
+            emit(Stream::Stdout, marker);
+        }
+    }
+}
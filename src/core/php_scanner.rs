@@ -0,0 +1,272 @@
+//! Finds the classes, interfaces, traits and enums a PHP file declares, for the classmap,
+//! without running PHP. The tokenizer only tells code from strings, comments and inline
+//! HTML, which is all it takes to find declarations; like Composer it lists declarations
+//! inside `if` blocks too, and stops quietly where a file is cut off mid-string or comment.
+
+use anyhow::Result;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token<'a> {
+    /// A name, keyword or qualified name such as `Foo\Bar`
+    Name(&'a [u8]),
+    /// Punctuation; `::` and `->` are kept whole so member access can be told apart
+    Symbol(&'static str),
+    /// Variables, numbers, strings and anything else that can't start a declaration
+    Other,
+}
+
+/// The fully qualified names declared in a PHP file
+/// # Errors
+/// Returns an error if the file cannot be read
+pub fn find_classes_in_file(path: &Path) -> Result<Vec<String>> {
+    Ok(find_classes(&std::fs::read(path)?))
+}
+
+/// The fully qualified names of the classes, interfaces, traits and enums declared in
+/// `source`, in order
+pub fn find_classes(source: &[u8]) -> Vec<String> {
+    let tokens = tokenize(source);
+    let mut classes = Vec::new();
+    let mut namespace = String::new();
+    for (i, token) in tokens.iter().enumerate() {
+        let Token::Name(keyword) = *token else {
+            continue;
+        };
+        let previous = i.checked_sub(1).and_then(|p| tokens.get(p));
+        if matches!(previous, Some(Token::Symbol("::" | "->")))
+            || ["new", "function", "const"]
+                .iter()
+                .any(|word| is_keyword(previous, word))
+        {
+            continue;
+        }
+        let next = tokens.get(i + 1);
+        if keyword.eq_ignore_ascii_case(b"namespace") {
+            match next {
+                Some(Token::Name(name)) => {
+                    namespace = String::from_utf8_lossy(name)
+                        .trim_start_matches('\\')
+                        .to_string();
+                }
+                Some(Token::Symbol("{")) => namespace.clear(),
+                _ => {}
+            }
+            continue;
+        }
+        let Some(kind) = ["class", "interface", "trait", "enum"]
+            .into_iter()
+            .find(|kind| keyword.eq_ignore_ascii_case(kind.as_bytes()))
+        else {
+            continue;
+        };
+        let Some(Token::Name(name)) = next else {
+            continue;
+        };
+        // `new class extends Foo` is anonymous
+        if is_keyword(next, "extends") || is_keyword(next, "implements") || name.contains(&b'\\') {
+            continue;
+        }
+        // `enum` is also a valid name for functions and constants, so require what only an
+        // enum declaration has after its name
+        let after = tokens.get(i + 2);
+        if kind == "enum"
+            && !matches!(after, Some(Token::Symbol("{" | ":")))
+            && !is_keyword(after, "implements")
+        {
+            continue;
+        }
+        let name = String::from_utf8_lossy(name);
+        if namespace.is_empty() {
+            classes.push(name.into_owned());
+        } else {
+            classes.push(format!("{namespace}\\{name}"));
+        }
+    }
+    classes
+}
+
+fn is_keyword(token: Option<&Token>, keyword: &str) -> bool {
+    matches!(token, Some(Token::Name(name)) if name.eq_ignore_ascii_case(keyword.as_bytes()))
+}
+
+fn is_name_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_' || byte >= 0x80
+}
+
+/// The code tokens of `source`, leaving out inline HTML, strings and comments
+#[allow(clippy::too_many_lines)]
+fn tokenize(source: &[u8]) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let len = source.len();
+    let at = |i: usize, text: &[u8]| source.get(i..i + text.len()) == Some(text);
+    let mut i = 0;
+    // Inline HTML until the first open tag
+    let mut in_php = false;
+    while i < len {
+        if !in_php {
+            match find(source, i, b"<?") {
+                Some(open) => {
+                    i = open + 2;
+                    if source
+                        .get(i..i + 3)
+                        .is_some_and(|tag| tag.eq_ignore_ascii_case(b"php"))
+                    {
+                        i += 3;
+                    } else if at(i, b"=") {
+                        i += 1;
+                    }
+                    in_php = true;
+                }
+                None => break,
+            }
+            continue;
+        }
+
+        let byte = source[i];
+        match byte {
+            b if b.is_ascii_whitespace() => i += 1,
+            b'?' if at(i, b"?>") => {
+                in_php = false;
+                i += 2;
+            }
+            b'#' if !at(i, b"#[") => i = end_of_line_comment(source, i),
+            b'/' if at(i, b"//") => i = end_of_line_comment(source, i),
+            b'/' if at(i, b"/*") => match find(source, i + 2, b"*/") {
+                Some(end) => i = end + 2,
+                None => break,
+            },
+            b'\'' | b'"' | b'`' => match end_of_quoted(source, i) {
+                Some(end) => {
+                    tokens.push(Token::Other);
+                    i = end;
+                }
+                None => break,
+            },
+            b'<' if at(i, b"<<<") => match end_of_heredoc(source, i + 3) {
+                Some(end) => {
+                    tokens.push(Token::Other);
+                    i = end;
+                }
+                None => break,
+            },
+            b'$' => {
+                i += 1;
+                while i < len && is_name_byte(source[i]) {
+                    i += 1;
+                }
+                tokens.push(Token::Other);
+            }
+            b':' if at(i, b"::") => {
+                tokens.push(Token::Symbol("::"));
+                i += 2;
+            }
+            b'-' if at(i, b"->") => {
+                tokens.push(Token::Symbol("->"));
+                i += 2;
+            }
+            b'?' if at(i, b"?->") => {
+                tokens.push(Token::Symbol("->"));
+                i += 3;
+            }
+            b'{' | b'}' | b';' | b':' => {
+                tokens.push(Token::Symbol(match byte {
+                    b'{' => "{",
+                    b'}' => "}",
+                    b';' => ";",
+                    _ => ":",
+                }));
+                i += 1;
+            }
+            b if b.is_ascii_digit() => {
+                while i < len && is_name_byte(source[i]) {
+                    i += 1;
+                }
+                tokens.push(Token::Other);
+            }
+            b if is_name_byte(b) || b == b'\\' => {
+                let start = i;
+                while i < len && (is_name_byte(source[i]) || source[i] == b'\\') {
+                    i += 1;
+                }
+                tokens.push(Token::Name(&source[start..i]));
+            }
+            _ => {
+                tokens.push(Token::Other);
+                i += 1;
+            }
+        }
+    }
+    tokens
+}
+
+fn find(source: &[u8], from: usize, needle: &[u8]) -> Option<usize> {
+    source
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|pos| from + pos)
+}
+
+/// Where a `//` or `#` comment ends: at the end of the line, or before a `?>` that closes PHP
+fn end_of_line_comment(source: &[u8], mut i: usize) -> usize {
+    while i < source.len() && source[i] != b'\n' {
+        if source[i..].starts_with(b"?>") {
+            return i;
+        }
+        i += 1;
+    }
+    i
+}
+
+/// The position after the closing quote of the string starting at `start`
+fn end_of_quoted(source: &[u8], start: usize) -> Option<usize> {
+    let quote = source[start];
+    let mut i = start + 1;
+    while i < source.len() {
+        match source[i] {
+            b'\\' => i += 2,
+            b if b == quote => return Some(i + 1),
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+/// The position after the closing identifier of the heredoc or nowdoc whose `<<<` ends
+/// before `i`. As since PHP 7.3, the closing identifier may be indented and followed by
+/// more code on its line.
+fn end_of_heredoc(source: &[u8], mut i: usize) -> Option<usize> {
+    while matches!(source.get(i), Some(b' ' | b'\t')) {
+        i += 1;
+    }
+    let quoted = matches!(source.get(i), Some(b'\'' | b'"'));
+    if quoted {
+        i += 1;
+    }
+    let start = i;
+    while i < source.len() && is_name_byte(source[i]) {
+        i += 1;
+    }
+    let label = &source[start..i];
+    if label.is_empty() {
+        return Some(i);
+    }
+    if quoted {
+        i += 1;
+    }
+    loop {
+        i = find(source, i, b"\n")? + 1;
+        let mut line = i;
+        while matches!(source.get(line), Some(b' ' | b'\t')) {
+            line += 1;
+        }
+        if source[line..].starts_with(label)
+            && !source
+                .get(line + label.len())
+                .is_some_and(|&b| is_name_byte(b))
+        {
+            return Some(line + label.len());
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use anyhow::{Result, anyhow};
+use serde_json::Value;
+
+/// composer.json sections whose strings may contain `${VAR}` placeholders
+pub const EXPANDED_SECTIONS: &[&str] = &["repositories", "config", "scripts"];
+
+/// Expand `${VAR}` placeholders in a string. `$${VAR}` is kept as a literal `${VAR}`
+/// (e.g. for shell variables in scripts), and any other `$` is left untouched.
+/// Names of unset variables are appended to `missing`.
+pub fn expand_str<F>(input: &str, get_env: &F, missing: &mut Vec<String>) -> String
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(pos) = rest.find('$') {
+        out.push_str(&rest[..pos]);
+        let after = &rest[pos + 1..];
+
+        if let Some(escaped) = after.strip_prefix("${") {
+            out.push_str("${");
+            rest = escaped;
+            continue;
+        }
+
+        let placeholder = after
+            .strip_prefix('{')
+            .and_then(|inner| inner.find('}').map(|end| &inner[..end]))
+            .filter(|name| is_variable_name(name));
+        match placeholder {
+            Some(name) => {
+                match get_env(name) {
+                    Some(value) => out.push_str(&value),
+                    None => {
+                        if !missing.iter().any(|m| m == name) {
+                            missing.push(name.to_string());
+                        }
+                    }
+                }
+                rest = &after[name.len() + 2..];
+            }
+            None => {
+                out.push('$');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn is_variable_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Expand placeholders in every string (object keys excluded) below `value`
+pub fn expand_value<F>(value: &mut Value, get_env: &F, missing: &mut Vec<String>)
+where
+    F: Fn(&str) -> Option<String>,
+{
+    match value {
+        Value::String(s) if s.contains('$') => *s = expand_str(s, get_env, missing),
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|item| expand_value(item, get_env, missing)),
+        Value::Object(map) => map
+            .values_mut()
+            .for_each(|item| expand_value(item, get_env, missing)),
+        _ => {}
+    }
+}
+
+fn missing_error(missing: &[String], source: &str) -> anyhow::Error {
+    anyhow!(
+        "{source} references environment variable{} that {} not set: {}",
+        if missing.len() == 1 { "" } else { "s" },
+        if missing.len() == 1 { "is" } else { "are" },
+        missing.join(", ")
+    )
+}
+
+/// Expand placeholders in the repository, config (auth) and script sections of a parsed
+/// composer.json
+/// # Errors
+/// Returns an error naming every referenced variable that is not set
+pub fn expand_composer_with<F>(json: &mut Value, get_env: F) -> Result<()>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut missing = Vec::new();
+    for section in EXPANDED_SECTIONS {
+        if let Some(value) = json.get_mut(*section) {
+            expand_value(value, &get_env, &mut missing);
+        }
+    }
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(missing_error(&missing, "composer.json"))
+    }
+}
+
+/// Expand placeholders from the process environment
+/// # Errors
+/// Returns an error naming every referenced variable that is not set
+pub fn expand_composer(json: &mut Value) -> Result<()> {
+    expand_composer_with(json, |name| std::env::var(name).ok())
+}
+
+/// Expand placeholders in an auth.json-shaped value from the process environment
+/// # Errors
+/// Returns an error naming every referenced variable that is not set
+pub fn expand_auth(auth: &mut Value, source: &str) -> Result<()> {
+    let mut missing = Vec::new();
+    expand_value(auth, &|name| std::env::var(name).ok(), &mut missing);
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(missing_error(&missing, source))
+    }
+}
@@ -0,0 +1,134 @@
+// Platform packages (php, php-64bit, ext-*, lib-*) of the PHP installation on PATH, as
+// Composer reports them
+use anyhow::{Context, Result, anyhow};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// PHP code printing the version, integer size, loaded extensions and library versions as
+/// JSON; the raw strings are turned into package versions by `platform_packages_from`
+const PROBE: &str = r#"
+$libs = [];
+if (defined('OPENSSL_VERSION_TEXT')) { $libs['openssl'] = OPENSSL_VERSION_TEXT; }
+if (defined('PCRE_VERSION')) { $libs['pcre'] = PCRE_VERSION; }
+if (defined('INTL_ICU_VERSION')) { $libs['icu'] = INTL_ICU_VERSION; }
+if (defined('LIBXML_DOTTED_VERSION')) { $libs['libxml'] = LIBXML_DOTTED_VERSION; }
+if (function_exists('curl_version')) { $libs['curl'] = curl_version()['version']; }
+if (defined('ZLIB_VERSION')) { $libs['zlib'] = ZLIB_VERSION; }
+if (defined('ICONV_VERSION')) { $libs['iconv'] = ICONV_VERSION; }
+$extensions = [];
+foreach (get_loaded_extensions() as $name) { $extensions[$name] = (string) phpversion($name); }
+echo json_encode(['php' => PHP_VERSION, 'bits' => PHP_INT_SIZE * 8, 'extensions' => $extensions, 'libs' => $libs]);
+"#;
+
+/// A package the PHP installation provides
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlatformPackage {
+    pub name: String,
+    pub version: String,
+    pub description: String,
+}
+
+/// The first dotted number in `raw`, e.g. `3.0.2` from `OpenSSL 3.0.2 15 Mar 2022`, or
+/// `0` when there is none, like Composer does for extensions without a version
+pub fn platform_version(raw: &str) -> String {
+    let start = raw.find(|c: char| c.is_ascii_digit());
+    let Some(start) = start else {
+        return "0".to_string();
+    };
+    let rest = &raw[start..];
+    let end = rest
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(rest.len());
+    rest[..end].trim_end_matches('.').to_string()
+}
+
+/// Platform packages from the output of the PHP probe, sorted by name
+pub fn platform_packages_from(probe: &Value) -> Vec<PlatformPackage> {
+    let mut packages = BTreeMap::new();
+    let mut add = |name: String, version: String, description: String| {
+        packages.insert(
+            name.clone(),
+            PlatformPackage {
+                name,
+                version,
+                description,
+            },
+        );
+    };
+
+    if let Some(php) = probe.get("php").and_then(Value::as_str) {
+        let version = platform_version(php);
+        if probe.get("bits").and_then(Value::as_u64) == Some(64) {
+            add(
+                "php-64bit".to_string(),
+                version.clone(),
+                "The PHP interpreter, 64bit".to_string(),
+            );
+        }
+        add(
+            "php".to_string(),
+            version,
+            "The PHP interpreter".to_string(),
+        );
+    }
+    if let Some(extensions) = probe.get("extensions").and_then(Value::as_object) {
+        for (name, version) in extensions {
+            // `Zend OPcache` is ext-zend-opcache
+            let name = name.to_ascii_lowercase().replace(' ', "-");
+            add(
+                format!("ext-{name}"),
+                platform_version(version.as_str().unwrap_or("")),
+                format!("The {name} PHP extension"),
+            );
+        }
+    }
+    if let Some(libs) = probe.get("libs").and_then(Value::as_object) {
+        for (name, version) in libs {
+            add(
+                format!("lib-{name}"),
+                platform_version(version.as_str().unwrap_or("")),
+                format!("The {name} library"),
+            );
+        }
+    }
+    packages.into_values().collect()
+}
+
+/// Apply `config.platform`: listed packages get the configured version, and `false`
+/// removes a package, as in Composer
+pub fn apply_platform_overrides(
+    packages: &mut Vec<PlatformPackage>,
+    overrides: &BTreeMap<String, String>,
+) {
+    for (name, version) in overrides {
+        packages.retain(|package| &package.name != name);
+        if version != "false" {
+            packages.push(PlatformPackage {
+                name: name.clone(),
+                version: version.clone(),
+                description: "Package overridden via config.platform".to_string(),
+            });
+        }
+    }
+    packages.sort_by(|a, b| a.name.cmp(&b.name));
+}
+
+/// Detect the platform packages of `php` (or `PHP_BINARY`) on PATH
+/// # Errors
+/// Returns an error if PHP cannot be run or its output cannot be parsed
+pub fn detect_platform() -> Result<Vec<PlatformPackage>> {
+    let php = std::env::var("PHP_BINARY").unwrap_or_else(|_| "php".to_string());
+    let output = std::process::Command::new(&php)
+        .args(["-r", PROBE])
+        .output()
+        .with_context(|| format!("run {php}"))?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "{php} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let probe: Value =
+        serde_json::from_slice(&output.stdout).context("parse the PHP platform probe")?;
+    Ok(platform_packages_from(&probe))
+}
@@ -0,0 +1,182 @@
+use crate::utils::{print_info, print_text, profile};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How many of the slowest packages `--profile` lists
+const SLOWEST_PACKAGES: usize = 10;
+
+/// The phases of a run that `--profile` times
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Phase {
+    MetadataFetch,
+    Resolution,
+    /// Archive downloads and git clones
+    Download,
+    /// Archive extraction and copies of path packages
+    Extraction,
+    AutoloadDump,
+}
+
+impl Phase {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::MetadataFetch => "Metadata fetch",
+            Self::Resolution => "Resolution",
+            Self::Download => "Download",
+            Self::Extraction => "Extraction",
+            Self::AutoloadDump => "Autoload dump",
+        }
+    }
+}
+
+/// Time spent in one phase
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PhaseTiming {
+    /// Summed over every package for per-package phases, which run concurrently
+    pub time: Duration,
+    /// How often the phase ran: once per package for downloads and extractions
+    pub count: usize,
+    /// Peak resident memory of the process when the phase last ended
+    pub peak_memory: Option<u64>,
+}
+
+/// Timings gathered with `--profile`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Profile {
+    pub phases: BTreeMap<Phase, PhaseTiming>,
+    /// Time per package and phase
+    pub packages: BTreeMap<String, BTreeMap<Phase, Duration>>,
+}
+
+impl Profile {
+    pub const fn new() -> Self {
+        Self {
+            phases: BTreeMap::new(),
+            packages: BTreeMap::new(),
+        }
+    }
+
+    /// Add `elapsed` to `phase`, and to `package` when the phase was for one package
+    pub fn record(&mut self, phase: Phase, elapsed: Duration, package: Option<&str>) {
+        let timing = self.phases.entry(phase).or_default();
+        timing.time += elapsed;
+        timing.count += 1;
+        timing.peak_memory = peak_memory().or(timing.peak_memory);
+        if let Some(package) = package {
+            *self
+                .packages
+                .entry(package.to_string())
+                .or_default()
+                .entry(phase)
+                .or_default() += elapsed;
+        }
+    }
+
+    /// The `count` packages that took longest over all their phases, slowest first
+    pub fn slowest_packages(&self, count: usize) -> Vec<(&str, Duration)> {
+        let mut totals: Vec<(&str, Duration)> = self
+            .packages
+            .iter()
+            .map(|(name, phases)| (name.as_str(), phases.values().sum()))
+            .collect();
+        totals.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        totals.truncate(count);
+        totals
+    }
+
+    /// The report `--profile` prints: one line per phase, then the slowest packages
+    pub fn report_lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for (phase, timing) in &self.phases {
+            let runs = match phase {
+                Phase::Download | Phase::Extraction => {
+                    format!(" over {} package(s)", timing.count)
+                }
+                _ if timing.count > 1 => format!(" over {} runs", timing.count),
+                _ => String::new(),
+            };
+            let memory = timing
+                .peak_memory
+                .map(|bytes| format!(", peak memory {}", format_memory(bytes)))
+                .unwrap_or_default();
+            lines.push(format!(
+                "  {:<16} {:>10.2?}{runs}{memory}",
+                phase.label(),
+                timing.time
+            ));
+        }
+        let slowest = self.slowest_packages(SLOWEST_PACKAGES);
+        if !slowest.is_empty() {
+            lines.push("  Slowest packages:".to_string());
+        }
+        for (name, total) in slowest {
+            let phases: Vec<String> = self.packages[name]
+                .iter()
+                .map(|(phase, time)| format!("{} {time:.2?}", phase.label().to_lowercase()))
+                .collect();
+            lines.push(format!(
+                "    {name:<40} {total:>10.2?} ({})",
+                phases.join(", ")
+            ));
+        }
+        lines
+    }
+}
+
+static PROFILE: Mutex<Profile> = Mutex::new(Profile::new());
+
+fn with_profile(update: impl FnOnce(&mut Profile)) {
+    if !profile() {
+        return;
+    }
+    if let Ok(mut recorded) = PROFILE.lock() {
+        update(&mut recorded);
+    }
+}
+
+/// Record time spent in a phase of the whole run; does nothing without `--profile`
+pub fn record_phase(phase: Phase, elapsed: Duration) {
+    with_profile(|recorded| recorded.record(phase, elapsed, None));
+}
+
+/// Record time one package spent in a phase; does nothing without `--profile`
+pub fn record_package(package: &str, phase: Phase, elapsed: Duration) {
+    with_profile(|recorded| recorded.record(phase, elapsed, Some(package)));
+}
+
+/// What has been recorded so far
+pub fn recorded_profile() -> Profile {
+    PROFILE
+        .lock()
+        .map(|recorded| recorded.clone())
+        .unwrap_or_default()
+}
+
+/// Print the phase timings and slowest packages recorded during this run
+pub fn print_profile() {
+    let recorded = recorded_profile();
+    if recorded.phases.is_empty() {
+        return;
+    }
+    print_info("⏱️  Phase timings:");
+    print_text(&recorded.report_lines().join("\n"));
+}
+
+/// Peak resident memory of this process in bytes, where the platform reports it
+pub fn peak_memory() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kilobytes: u64 = line
+        .trim_start_matches("VmHWM:")
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()?;
+    Some(kilobytes * 1024)
+}
+
+fn format_memory(bytes: u64) -> String {
+    format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+}
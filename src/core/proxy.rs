@@ -0,0 +1,145 @@
+use serde_json::Value;
+use std::path::Path;
+
+use crate::auth::url_host;
+
+/// Proxies for HTTP and HTTPS requests and the hosts that bypass them
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProxySettings {
+    pub http: Option<String>,
+    pub https: Option<String>,
+    /// Lowercase host patterns: `*`, exact hosts, or domains matching their subdomains
+    pub no_proxy: Vec<String>,
+}
+
+impl ProxySettings {
+    /// Proxies from `extra.lectern.http.proxy` (`http`, `https` and `no-proxy`, the latter
+    /// a list or comma separated string). `HTTP_PROXY`, `HTTPS_PROXY`, `ALL_PROXY` and
+    /// `NO_PROXY`, in either case, override the config like they do for curl and git.
+    pub fn from_env_with<F>(get_env: F, proxy_config: Option<&Value>) -> Self
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let env = |name: &str| {
+            get_env(&name.to_lowercase())
+                .or_else(|| get_env(name))
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        let config = |key: &str| proxy_config.and_then(|config| config.get(key));
+        let config_str = |key: &str| {
+            config(key)
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .filter(|value| !value.is_empty())
+        };
+
+        let all = env("ALL_PROXY");
+        let http = env("HTTP_PROXY")
+            .or_else(|| all.clone())
+            .or_else(|| config_str("http"));
+        let https = env("HTTPS_PROXY").or(all).or_else(|| config_str("https"));
+
+        let no_proxy = match env("NO_PROXY") {
+            Some(list) => list.split(',').map(str::to_string).collect(),
+            None => match config("no-proxy") {
+                Some(Value::String(list)) => list.split(',').map(str::to_string).collect(),
+                Some(Value::Array(list)) => list
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(str::to_string)
+                    .collect(),
+                _ => Vec::new(),
+            },
+        };
+        let no_proxy = no_proxy
+            .iter()
+            .map(|pattern| {
+                let pattern = pattern.trim().to_ascii_lowercase();
+                // Ports are ignored; `.example.com` and `example.com` mean the same
+                let host = pattern
+                    .rsplit_once(':')
+                    .map_or(pattern.as_str(), |(host, _)| host);
+                host.trim_start_matches("*.")
+                    .trim_start_matches('.')
+                    .to_string()
+            })
+            .filter(|pattern| !pattern.is_empty())
+            .collect();
+
+        Self {
+            http,
+            https,
+            no_proxy,
+        }
+    }
+
+    /// Proxy settings for a project, from its composer.json and the process environment
+    pub fn load(project_dir: &Path) -> Self {
+        let composer = crate::io::read_manifest_value(project_dir);
+        Self::from_env_with(
+            |name| std::env::var(name).ok(),
+            composer
+                .as_ref()
+                .and_then(|json| json.pointer("/extra/lectern/http/proxy")),
+        )
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.http.is_none() && self.https.is_none()
+    }
+
+    /// Whether `host` is listed in `no_proxy`
+    pub fn bypasses(&self, host: &str) -> bool {
+        let host = host.to_ascii_lowercase();
+        self.no_proxy.iter().any(|pattern| {
+            pattern == "*"
+                || host == *pattern
+                || host
+                    .strip_suffix(pattern.as_str())
+                    .is_some_and(|rest| rest.ends_with('.'))
+        })
+    }
+
+    /// The proxy a request to `url` goes through, if any. Only `http(s)://` URLs are proxied.
+    pub fn proxy_for(&self, url: &str) -> Option<&str> {
+        let proxy = if url.starts_with("https://") {
+            self.https.as_deref()
+        } else if url.starts_with("http://") {
+            self.http.as_deref()
+        } else {
+            None
+        }?;
+        match url_host(url) {
+            Some(host) if self.bypasses(&host) => None,
+            _ => Some(proxy),
+        }
+    }
+
+    /// The settings as a reqwest proxy, or nothing when no proxy is configured
+    pub fn reqwest_proxy(&self) -> Option<reqwest::Proxy> {
+        if self.is_empty() {
+            return None;
+        }
+        let settings = self.clone();
+        Some(reqwest::Proxy::custom(move |url| {
+            settings.proxy_for(url.as_str()).map(str::to_string)
+        }))
+    }
+
+    /// libgit2 proxy options for fetching `url`. Without a proxy of ours libgit2 falls back to
+    /// git's own `http.proxy` config, except for hosts `NO_PROXY` exempts.
+    pub fn git_proxy_options(&self, url: &str) -> git2::ProxyOptions<'static> {
+        let mut options = git2::ProxyOptions::new();
+        match self.proxy_for(url) {
+            Some(proxy) => {
+                options.url(proxy);
+            }
+            None if url_host(url).is_some_and(|host| self.bypasses(&host)) => {}
+            None => {
+                options.auto();
+            }
+        }
+        options
+    }
+}
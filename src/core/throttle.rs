@@ -0,0 +1,191 @@
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::auth::url_host;
+
+/// Composer's variable for the number of parallel downloads
+pub const MAX_PARALLEL_HTTP_ENV: &str = "COMPOSER_MAX_PARALLEL_HTTP";
+/// Cap on simultaneous connections to a single host
+pub const MAX_HTTP_PER_HOST_ENV: &str = "LECTERN_MAX_HTTP_PER_HOST";
+
+const DEFAULT_MAX_PARALLEL_HTTP: usize = 12;
+const DEFAULT_MAX_HTTP_PER_HOST: usize = 8;
+
+/// How many HTTP requests may be in flight, overall and per host
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HttpLimits {
+    pub max_parallel: usize,
+    pub max_per_host: usize,
+}
+
+impl Default for HttpLimits {
+    fn default() -> Self {
+        Self {
+            max_parallel: DEFAULT_MAX_PARALLEL_HTTP,
+            max_per_host: DEFAULT_MAX_HTTP_PER_HOST,
+        }
+    }
+}
+
+fn positive(value: &str) -> Option<usize> {
+    value.trim().parse().ok().filter(|n| *n > 0)
+}
+
+impl HttpLimits {
+    /// Limits from `extra.lectern.http` (`max-parallel`, `max-per-host`), overridden by
+    /// `COMPOSER_MAX_PARALLEL_HTTP` and `LECTERN_MAX_HTTP_PER_HOST`
+    pub fn from_env_with<F>(get_env: F, http_config: Option<&Value>) -> Self
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let configured = |key: &str| {
+            http_config
+                .and_then(|config| config.get(key))
+                .and_then(Value::as_u64)
+                .map(|n| n as usize)
+                .filter(|n| *n > 0)
+        };
+        let defaults = Self::default();
+        let max_parallel = get_env(MAX_PARALLEL_HTTP_ENV)
+            .as_deref()
+            .and_then(positive)
+            .or_else(|| configured("max-parallel"))
+            .unwrap_or(defaults.max_parallel);
+        let max_per_host = get_env(MAX_HTTP_PER_HOST_ENV)
+            .as_deref()
+            .and_then(positive)
+            .or_else(|| configured("max-per-host"))
+            .unwrap_or(defaults.max_per_host);
+
+        Self {
+            max_parallel,
+            // A single host can never use more than the overall budget
+            max_per_host: max_per_host.min(max_parallel),
+        }
+    }
+
+    /// Limits for a project, from its composer.json and the process environment
+    pub fn load(project_dir: &Path) -> Self {
+        let composer = crate::io::read_manifest_value(project_dir);
+        Self::from_env_with(
+            |name| std::env::var(name).ok(),
+            composer
+                .as_ref()
+                .and_then(|json| json.pointer("/extra/lectern/http")),
+        )
+    }
+}
+
+struct HostLimit {
+    permits: Arc<Semaphore>,
+    limit: AtomicUsize,
+}
+
+/// Per-host connection caps that shrink when a host answers 429 or 503
+pub struct HttpThrottle {
+    limits: HttpLimits,
+    hosts: Mutex<HashMap<String, Arc<HostLimit>>>,
+}
+
+impl HttpThrottle {
+    pub fn new(limits: HttpLimits) -> Self {
+        Self {
+            limits,
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn limits(&self) -> HttpLimits {
+        self.limits
+    }
+
+    fn host(&self, url: &str) -> Option<Arc<HostLimit>> {
+        let host = url_host(url)?;
+        let mut hosts = self.hosts.lock().unwrap_or_else(|e| e.into_inner());
+        Some(
+            hosts
+                .entry(host)
+                .or_insert_with(|| {
+                    Arc::new(HostLimit {
+                        permits: Arc::new(Semaphore::new(self.limits.max_per_host)),
+                        limit: AtomicUsize::new(self.limits.max_per_host),
+                    })
+                })
+                .clone(),
+        )
+    }
+
+    /// Wait for a connection slot to the URL's host; hold the permit until the body is read
+    pub async fn acquire(&self, url: &str) -> Option<OwnedSemaphorePermit> {
+        self.host(url)?.permits.clone().acquire_owned().await.ok()
+    }
+
+    /// Current connection cap for the URL's host
+    pub fn host_limit(&self, url: &str) -> usize {
+        self.host(url).map_or(self.limits.max_per_host, |host| {
+            host.limit.load(Ordering::Relaxed)
+        })
+    }
+
+    /// Record a response status. On 429 or 503 the host's cap is halved (down to one
+    /// connection); returns whether it was lowered.
+    pub fn report_status(&self, url: &str, status: u16) -> bool {
+        if status != 429 && status != 503 {
+            return false;
+        }
+        let Some(host) = self.host(url) else {
+            return false;
+        };
+        let Ok(previous) = host
+            .limit
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |limit| {
+                (limit > 1).then_some(limit / 2)
+            })
+        else {
+            return false;
+        };
+
+        // Retire permits: free ones right away, the rest as in-flight requests finish
+        let reduce_by = previous - previous / 2;
+        let outstanding = reduce_by - host.permits.forget_permits(reduce_by);
+        if outstanding > 0 {
+            let permits = host.permits.clone();
+            let retire = async move {
+                if let Ok(permit) = permits.acquire_many_owned(outstanding as u32).await {
+                    permit.forget();
+                }
+            };
+            if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+                runtime.spawn(retire);
+            }
+        }
+
+        crate::core::utils::print_warning(&format!(
+            "{} is throttling requests, lowering concurrency to {}",
+            url_host(url).unwrap_or_default(),
+            previous / 2
+        ));
+        true
+    }
+}
+
+static THROTTLE: OnceLock<HttpThrottle> = OnceLock::new();
+
+/// Use these limits for the rest of the run (set once from the CLI)
+pub fn configure(limits: HttpLimits) {
+    let _ = THROTTLE.set(HttpThrottle::new(limits));
+}
+
+/// The process-wide throttle, configured from the environment if `configure` wasn't called
+pub fn throttle() -> &'static HttpThrottle {
+    THROTTLE.get_or_init(|| {
+        HttpThrottle::new(HttpLimits::from_env_with(
+            |name| std::env::var(name).ok(),
+            None,
+        ))
+    })
+}
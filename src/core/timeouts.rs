@@ -0,0 +1,162 @@
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::auth::url_host;
+
+/// Read timeout for metadata requests, in seconds
+pub const METADATA_TIMEOUT_ENV: &str = "LECTERN_METADATA_TIMEOUT";
+/// Read timeout for dist downloads, in seconds
+pub const DOWNLOAD_TIMEOUT_ENV: &str = "LECTERN_DOWNLOAD_TIMEOUT";
+
+/// What a request fetches, which decides how long it may take
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RequestKind {
+    Metadata,
+    Download,
+}
+
+/// How long to wait for a connection, and for the next bytes once connected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Timeouts {
+    pub connect: Duration,
+    pub read: Duration,
+}
+
+impl Timeouts {
+    /// Metadata is small, so a stalled server is given up on quickly
+    pub const METADATA: Self = Self {
+        connect: Duration::from_secs(5),
+        read: Duration::from_secs(30),
+    };
+    /// Archives may trickle in on slow links; only a connection that goes quiet is dropped
+    pub const DOWNLOAD: Self = Self {
+        connect: Duration::from_secs(10),
+        read: Duration::from_secs(300),
+    };
+
+    /// These timeouts with `connect`/`read` seconds from a config object replacing them
+    fn overridden_by(self, config: Option<&Value>) -> Self {
+        let seconds = |key: &str| {
+            config
+                .and_then(|config| config.get(key))
+                .and_then(Value::as_u64)
+                .filter(|n| *n > 0)
+                .map(Duration::from_secs)
+        };
+        Self {
+            connect: seconds("connect").unwrap_or(self.connect),
+            read: seconds("read").unwrap_or(self.read),
+        }
+    }
+}
+
+/// Timeouts for one host
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HostTimeouts {
+    pub metadata: Timeouts,
+    pub download: Timeouts,
+}
+
+/// Timeouts for metadata and dist requests, with overrides for specific hosts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpTimeouts {
+    pub metadata: Timeouts,
+    pub download: Timeouts,
+    /// Keyed by lowercase host
+    pub hosts: BTreeMap<String, HostTimeouts>,
+}
+
+impl Default for HttpTimeouts {
+    fn default() -> Self {
+        Self {
+            metadata: Timeouts::METADATA,
+            download: Timeouts::DOWNLOAD,
+            hosts: BTreeMap::new(),
+        }
+    }
+}
+
+impl HttpTimeouts {
+    /// Timeouts from `extra.lectern.http.timeouts` (`metadata`, `download` and `hosts`,
+    /// each kind holding `connect`/`read` seconds). `LECTERN_METADATA_TIMEOUT` and
+    /// `LECTERN_DOWNLOAD_TIMEOUT` override the read timeouts; host entries stay more specific.
+    pub fn from_env_with<F>(get_env: F, timeouts_config: Option<&Value>) -> Self
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let section = |key: &str| timeouts_config.and_then(|config| config.get(key));
+        let with_env = |timeouts: Timeouts, name: &str| Timeouts {
+            read: get_env(name)
+                .and_then(|value| value.trim().parse::<u64>().ok())
+                .filter(|n| *n > 0)
+                .map_or(timeouts.read, Duration::from_secs),
+            ..timeouts
+        };
+        let metadata = with_env(
+            Timeouts::METADATA.overridden_by(section("metadata")),
+            METADATA_TIMEOUT_ENV,
+        );
+        let download = with_env(
+            Timeouts::DOWNLOAD.overridden_by(section("download")),
+            DOWNLOAD_TIMEOUT_ENV,
+        );
+
+        let hosts = section("hosts")
+            .and_then(Value::as_object)
+            .into_iter()
+            .flatten()
+            .map(|(host, config)| {
+                (
+                    host.to_ascii_lowercase(),
+                    HostTimeouts {
+                        metadata: metadata.overridden_by(config.get("metadata")),
+                        download: download.overridden_by(config.get("download")),
+                    },
+                )
+            })
+            .collect();
+
+        Self {
+            metadata,
+            download,
+            hosts,
+        }
+    }
+
+    /// Timeouts for a project, from its composer.json and the process environment
+    pub fn load(project_dir: &Path) -> Self {
+        let composer = crate::io::read_manifest_value(project_dir);
+        Self::from_env_with(
+            |name| std::env::var(name).ok(),
+            composer
+                .as_ref()
+                .and_then(|json| json.pointer("/extra/lectern/http/timeouts")),
+        )
+    }
+
+    /// Timeouts for a request to `url`
+    pub fn for_url(&self, url: &str, kind: RequestKind) -> Timeouts {
+        let host = url_host(url).and_then(|host| self.hosts.get(&host));
+        match (kind, host) {
+            (RequestKind::Metadata, Some(host)) => host.metadata,
+            (RequestKind::Download, Some(host)) => host.download,
+            (RequestKind::Metadata, None) => self.metadata,
+            (RequestKind::Download, None) => self.download,
+        }
+    }
+}
+
+static TIMEOUTS: OnceLock<HttpTimeouts> = OnceLock::new();
+
+/// Use these timeouts for the rest of the run (set once from the CLI)
+pub fn configure(timeouts: HttpTimeouts) {
+    let _ = TIMEOUTS.set(timeouts);
+}
+
+/// The process-wide timeouts, read from the environment if `configure` wasn't called
+pub fn http_timeouts() -> &'static HttpTimeouts {
+    TIMEOUTS.get_or_init(|| HttpTimeouts::from_env_with(|name| std::env::var(name).ok(), None))
+}
@@ -1,6 +1,86 @@
+use crate::core::output::{Level, report};
+use crate::resolver::version::version_stability;
 use anyhow::Result;
-use colored::Colorize;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+/// Global verbosity level set from the `-v` flags (0 = normal)
+static VERBOSITY: AtomicU8 = AtomicU8::new(0);
+
+/// Set the global verbosity level
+pub fn set_verbosity(level: u8) {
+    VERBOSITY.store(level, Ordering::Relaxed);
+}
+
+/// Get the global verbosity level
+pub fn verbosity() -> u8 {
+    VERBOSITY.load(Ordering::Relaxed)
+}
+
+/// Set by `-q`: only errors are reported
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Enable or disable quiet mode
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+/// Whether quiet mode is enabled
+pub fn quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Cleared by `-n` and in CI, so nothing waits for an answer
+static INTERACTIVE: AtomicBool = AtomicBool::new(true);
+
+/// Allow or forbid prompts
+pub fn set_interactive(interactive: bool) {
+    INTERACTIVE.store(interactive, Ordering::Relaxed);
+}
+
+/// Whether commands may prompt for input
+pub fn interactive() -> bool {
+    INTERACTIVE.load(Ordering::Relaxed)
+}
+
+/// Set by `--profile` to report timings and resolver statistics
+static PROFILE: AtomicBool = AtomicBool::new(false);
+
+/// Enable or disable profiling output
+pub fn set_profile(enabled: bool) {
+    PROFILE.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether profiling output is enabled
+pub fn profile() -> bool {
+    PROFILE.load(Ordering::Relaxed)
+}
+
+/// Plain output: no colors and no in-place progress (enabled in CI)
+static PLAIN_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// Set once any error has been printed, so CI runs can fail strictly
+static ERROR_REPORTED: AtomicBool = AtomicBool::new(false);
+
+/// Enable or disable plain output
+pub fn set_plain_output(plain: bool) {
+    PLAIN_OUTPUT.store(plain, Ordering::Relaxed);
+    if plain {
+        colored::control::set_override(false);
+    } else {
+        colored::control::unset_override();
+    }
+}
+
+/// Whether plain output is enabled
+pub fn plain_output() -> bool {
+    PLAIN_OUTPUT.load(Ordering::Relaxed)
+}
+
+/// Whether any error has been printed during this run
+pub fn error_reported() -> bool {
+    ERROR_REPORTED.load(Ordering::Relaxed)
+}
 
 /// Normalize a repo path: absolute if relative
 pub fn normalize_path(s: &str) -> Result<PathBuf> {
@@ -20,33 +100,78 @@ pub fn strip_first_component(s: &str) -> std::path::PathBuf {
     comps.as_path().to_path_buf()
 }
 
-/// Print a success message in green
+/// The `candidates` within a few typos of `name`, closest first, for "did you mean" hints
+pub fn similar_names<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let name = name.to_lowercase();
+    let mut similar: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(&name, &candidate.to_lowercase()), candidate))
+        .filter(|(distance, candidate)| {
+            *distance <= name.len() / 3 + 1 || candidate.starts_with(name.as_str())
+        })
+        .collect();
+    similar.sort();
+    similar.dedup();
+    similar
+        .into_iter()
+        .map(|(_, candidate)| candidate.to_string())
+        .collect()
+}
+
+/// Levenshtein distance between two strings, by character
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Report a success message (green on the console)
 pub fn print_success(message: &str) {
-    println!("{} {}", "[SUCCESS]".green().bold(), message);
+    report(Level::Success, message);
 }
 
-/// Print an info message in blue
+/// Report an info message (blue on the console)
 pub fn print_info(message: &str) {
-    println!("{} {}", "[INFO]".blue().bold(), message);
+    report(Level::Info, message);
 }
 
-/// Print an error message in red
+/// Report an error message (red on the console)
 pub fn print_error(message: &str) {
-    eprintln!("{} {}", "[ERROR]".red().bold(), message);
+    ERROR_REPORTED.store(true, Ordering::Relaxed);
+    report(Level::Error, message);
 }
 
-/// Print a warning message in yellow
+/// Report a warning message (yellow on the console)
 pub fn print_warning(message: &str) {
-    println!("{} {}", "[WARNING]".yellow().bold(), message);
+    report(Level::Warning, message);
 }
 
-/// Print a step message (for showing progress)
+/// Report a step message (for showing progress)
 pub fn print_step(message: &str) {
-    println!("{} {}", "[STEP]".cyan().bold(), message);
+    report(Level::Step, message);
+}
+
+/// Report details shown with `-vv` and above
+pub fn print_debug(message: &str) {
+    report(Level::Debug, message);
+}
+
+/// Report untagged text, such as a summary or table
+pub fn print_text(message: &str) {
+    report(Level::Text, message);
 }
 
-/// Return true if the version string indicates a pre-release (alpha/beta/rc/dev)
+/// Return true if the version string indicates a pre-release (alpha/beta/RC/dev), read as
+/// Composer reads stability suffixes: `1.0.0-RC1` and `2.0.0b2` are pre-releases while a
+/// patch release such as `1.0.0-p1` is stable
 pub fn is_prerelease_version(version_str: &str) -> bool {
-    let s = version_str.to_ascii_lowercase();
-    s.contains("dev") || s.contains("alpha") || s.contains("beta") || s.contains("rc")
+    version_stability(version_str) > 0
 }
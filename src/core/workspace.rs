@@ -0,0 +1,299 @@
+use crate::models::model::{ComposerJson, Lock, LockedPackage, SourceInfo};
+use crate::resolver::dependency_utils::{
+    partition_dev_packages, prune_unreachable, refresh_root_metadata,
+};
+use anyhow::{Result, anyhow};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Component, Path, PathBuf};
+
+/// Member patterns from `extra.lectern.workspace.members` (e.g. `["packages/*"]`)
+pub fn workspace_member_patterns(composer: &ComposerJson) -> Vec<String> {
+    composer
+        .extra
+        .as_ref()
+        .and_then(|extra| extra.pointer("/lectern/workspace/members"))
+        .and_then(|members| members.as_array())
+        .map(|members| {
+            members
+                .iter()
+                .filter_map(|m| m.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Match a single path segment against a pattern where `*` stands for any run of characters
+pub fn segment_matches(pattern: &str, name: &str) -> bool {
+    let Some((first, rest)) = pattern.split_once('*') else {
+        return pattern == name;
+    };
+    let Some(mut remaining) = name.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = rest.split('*').collect();
+    let last = parts.pop().unwrap_or("");
+    for part in parts {
+        match remaining.find(part) {
+            Some(pos) => remaining = &remaining[pos + part.len()..],
+            None => return false,
+        }
+    }
+    remaining.len() >= last.len() && remaining.ends_with(last)
+}
+
+fn expand_pattern(root: &Path, pattern: &str) -> Vec<PathBuf> {
+    let mut matches = vec![root.to_path_buf()];
+    for segment in pattern.split('/').filter(|s| !s.is_empty() && *s != ".") {
+        let mut next = Vec::new();
+        for dir in &matches {
+            if segment.contains('*') {
+                let Ok(entries) = std::fs::read_dir(dir) else {
+                    continue;
+                };
+                for entry in entries.flatten() {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    if !name.starts_with('.')
+                        && entry.path().is_dir()
+                        && segment_matches(segment, &name)
+                    {
+                        next.push(entry.path());
+                    }
+                }
+            } else {
+                next.push(dir.join(segment));
+            }
+        }
+        matches = next;
+    }
+    matches
+}
+
+/// Member project directories of the workspace rooted at `root`, sorted.
+/// The root is included when it has a lock file of its own.
+/// # Errors
+/// Returns an error if composer.json cannot be read or declares no members
+pub fn workspace_members(root: &Path) -> Result<Vec<PathBuf>> {
+    let composer = crate::io::read_composer_json(&crate::io::manifest_path(root))?;
+    let patterns = workspace_member_patterns(&composer);
+    if patterns.is_empty() {
+        return Err(anyhow!(
+            "No workspace members configured. Add extra.lectern.workspace.members to composer.json"
+        ));
+    }
+
+    let mut members: Vec<PathBuf> = patterns
+        .iter()
+        .flat_map(|pattern| expand_pattern(root, pattern))
+        .filter(|dir| crate::io::manifest_path(dir).is_file())
+        .collect();
+    if root.join("composer.lock").is_file() {
+        members.push(root.to_path_buf());
+    }
+    members.sort();
+    members.dedup();
+    Ok(members)
+}
+
+/// Short label for a member: its path relative to the workspace root
+pub fn member_label(root: &Path, member: &Path) -> String {
+    match member.strip_prefix(root) {
+        Ok(rel) if rel.as_os_str().is_empty() => ".".to_string(),
+        Ok(rel) => rel.to_string_lossy().replace('\\', "/"),
+        Err(_) => member.display().to_string(),
+    }
+}
+
+/// A workspace member with its manifest
+#[derive(Debug)]
+pub struct WorkspaceMember {
+    pub dir: PathBuf,
+    pub label: String,
+    pub composer: ComposerJson,
+}
+
+/// The members matched by `extra.lectern.workspace.members`, with their manifests. Unlike
+/// [`workspace_members`], the root is never one of them.
+/// # Errors
+/// Returns an error if the root or a member manifest cannot be read
+pub fn load_members(root: &Path) -> Result<Vec<WorkspaceMember>> {
+    workspace_members(root)?
+        .into_iter()
+        .filter(|dir| dir != root)
+        .map(|dir| {
+            let composer = crate::io::read_composer_json(&crate::io::manifest_path(&dir))?;
+            Ok(WorkspaceMember {
+                label: member_label(root, &dir),
+                dir,
+                composer,
+            })
+        })
+        .collect()
+}
+
+/// Lowercased package names of the members
+fn member_names(members: &[WorkspaceMember]) -> BTreeSet<String> {
+    members
+        .iter()
+        .filter_map(|member| member.composer.name.as_deref())
+        .map(str::to_ascii_lowercase)
+        .collect()
+}
+
+/// The requirements of a package by every member that has one
+#[derive(Default)]
+struct Requirement {
+    name: String,
+    by: Vec<(String, String)>,
+    production: bool,
+}
+
+/// One constraint meeting every member's: the constraint they share, or all of them
+/// joined with `,`. Constraints with `||` can't be joined without changing their meaning.
+fn combine_constraints(requirement: &Requirement) -> Result<String> {
+    let constraints: BTreeSet<&str> = requirement
+        .by
+        .iter()
+        .map(|(constraint, _)| constraint.trim())
+        .collect();
+    if constraints.len() == 1 || !constraints.iter().any(|c| c.contains('|')) {
+        return Ok(constraints.into_iter().collect::<Vec<_>>().join(", "));
+    }
+    let by: Vec<String> = requirement
+        .by
+        .iter()
+        .map(|(constraint, label)| format!("{constraint} by {label}"))
+        .collect();
+    Err(anyhow!(
+        "{} is required as {}; use the same constraint in every member to resolve them together",
+        requirement.name,
+        by.join(" and ")
+    ))
+}
+
+/// One manifest with the requirements of every member, to resolve the whole workspace at
+/// once. Repositories, stability and config come from the root; requirements on members
+/// are left out, as the workspace provides them. A package any member needs outside of
+/// `require-dev` goes in `require`, the rest in `require-dev`.
+/// # Errors
+/// Returns an error if members require a package with constraints that can't be combined
+pub fn combined_manifest(
+    mut root: ComposerJson,
+    members: &[WorkspaceMember],
+) -> Result<ComposerJson> {
+    let provided = member_names(members);
+    let mut requirements: BTreeMap<String, Requirement> = BTreeMap::new();
+    for member in members {
+        let sections = [
+            (&member.composer.require, true),
+            (&member.composer.require_dev, false),
+        ];
+        for (section, production) in sections {
+            for (name, constraint) in section {
+                let key = name.to_ascii_lowercase();
+                if provided.contains(&key) {
+                    continue;
+                }
+                let requirement = requirements.entry(key).or_default();
+                requirement.name.clone_from(name);
+                requirement
+                    .by
+                    .push((constraint.clone(), member.label.clone()));
+                requirement.production |= production;
+            }
+        }
+    }
+
+    root.require.clear();
+    root.require_dev.clear();
+    for requirement in requirements.values() {
+        let constraint = combine_constraints(requirement)?;
+        let section = if requirement.production {
+            &mut root.require
+        } else {
+            &mut root.require_dev
+        };
+        section.insert(requirement.name.clone(), constraint);
+    }
+    Ok(root)
+}
+
+/// `to` relative to `from`, both directories of the workspace, e.g. `../b`
+pub fn relative_path(from: &Path, to: &Path) -> PathBuf {
+    let from: Vec<Component> = from.components().collect();
+    let to: Vec<Component> = to.components().collect();
+    let shared = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let mut path: PathBuf = from[shared..]
+        .iter()
+        .map(|_| Component::ParentDir)
+        .collect();
+    path.extend(&to[shared..]);
+    path
+}
+
+/// The lock entry for a member, installed by linking its directory into the vendor dir of
+/// the project in `from`. `None` for a member without a name, which nothing can require.
+pub fn member_package(member: &WorkspaceMember, from: &Path) -> Option<LockedPackage> {
+    let composer = &member.composer;
+    let url = relative_path(from, &member.dir)
+        .to_string_lossy()
+        .replace('\\', "/");
+    Some(LockedPackage {
+        name: composer.name.clone()?,
+        version: composer
+            .version
+            .clone()
+            .unwrap_or_else(|| "dev-main".to_string()),
+        source: Some(SourceInfo {
+            source_type: "path".to_string(),
+            url,
+            reference: "HEAD".to_string(),
+            mirrors: None,
+        }),
+        require: Some(composer.require.clone()).filter(|require| !require.is_empty()),
+        package_type: composer.package_type.clone(),
+        autoload: composer.autoload.clone(),
+        description: composer.description.clone(),
+        ..LockedPackage::default()
+    })
+}
+
+/// Whether a locked package is the workspace member `members` has under its name
+pub fn is_member_package(package: &LockedPackage, members: &[WorkspaceMember]) -> bool {
+    package
+        .source
+        .as_ref()
+        .is_some_and(|source| source.source_type == "path")
+        && member_names(members).contains(&package.name.to_ascii_lowercase())
+}
+
+/// The part of the workspace lock that `member` needs: the packages its requirements
+/// reach, other members included, split into `packages` and `packages-dev` for it. The
+/// content hash is left to the caller.
+pub fn member_lock(
+    workspace_lock: &Lock,
+    members: &[WorkspaceMember],
+    member: &WorkspaceMember,
+) -> Lock {
+    let own_name = member.composer.name.as_deref().map(str::to_ascii_lowercase);
+    let candidates: Vec<LockedPackage> = workspace_lock
+        .packages
+        .iter()
+        .chain(&workspace_lock.packages_dev)
+        .cloned()
+        .chain(
+            members
+                .iter()
+                .filter(|other| other.dir != member.dir)
+                .filter_map(|other| member_package(other, &member.dir)),
+        )
+        .filter(|package| own_name.as_deref() != Some(&package.name.to_ascii_lowercase()))
+        .collect();
+    let needed = prune_unreachable(&member.composer, candidates);
+    let (packages, packages_dev) = partition_dev_packages(&member.composer, needed);
+
+    let mut lock = workspace_lock.clone();
+    lock.packages = packages;
+    lock.packages_dev = packages_dev;
+    refresh_root_metadata(&member.composer, &mut lock);
+    lock
+}
@@ -5,4 +5,10 @@ pub mod resolver;
 
 // Re-export commonly used items
 pub use cli::*;
-pub use core::{autoload, cache, commands, installer, io, utils};
+pub use core::api::{InstallReport, Lectern, LecternOptions, RequireReport};
+pub use core::error::LecternError;
+pub use core::{
+    api, auth, autoload, cache, ci, commands, environments, error, graph, http, ignore_list,
+    installer, io, journal, lock_diff, output, php_scanner, placeholders, platform, profiler,
+    proxy, throttle, timeouts, utils, workspace,
+};
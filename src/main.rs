@@ -2,295 +2,582 @@ use anyhow::Result;
 use clap::Parser;
 use env_logger::Env;
 use lectern::{
-    autoload::write_autoload_files,
+    Lectern, LecternOptions,
+    autoload::{AutoloadOptions, lock_packages, write_autoload_files_with_options},
+    ci::detect_ci,
     cli::*,
     commands::{
-        browse_package, check_outdated_packages, clear_cache, create_project, diagnose, run_script,
-        search_packages, show_dependency_licenses, show_dependency_status, show_depends,
-        show_funding, show_package_details, show_prohibits, show_suggests,
+        UpdateWith, audit_lock, browse_package, bump, check_lock, check_outdated_packages,
+        check_outdated_workspace, clean, clear_cache, convert, create_project, diagnose,
+        docker_cache_config, ensure_global_project, exec, export_graph, fingerprint,
+        finish_global_change, global_dir, list, php_compat, print_audit_summary, reinstall,
+        remove_requirement, resolve_prospective, rollback, run_custom_command, run_script,
+        run_shell, sbom, search_packages, self_update, show_available_versions,
+        show_dependency_licenses, show_dependency_status, show_dependency_tree, show_depends,
+        show_funding, show_package_details, show_package_full, show_platform, show_prohibits,
+        show_remote_version, show_suggests, sync, third_party_file, workspace_install,
+        workspace_status,
     },
-    installer::{InstalledPackage, install_packages},
-    io::{read_composer_json, read_lock, write_lock},
+    environments::check_environments,
+    graph::DependencyGraph,
+    ignore_list::IgnoreList,
+    installer::install_packages,
+    io::{
+        manifest_path, read_composer_content_hash, read_composer_json, read_composer_json_raw,
+        read_lock, write_composer_json, write_lock,
+    },
+    journal::{begin_install_operation, commit_operation, discard_entry},
+    lock_diff::{diff_locks, print_lock_diff},
     models::model::*,
+    profiler::print_profile,
     resolver::solve,
     utils::*,
 };
 use std::collections::BTreeMap;
 
 #[tokio::main]
-async fn main() -> Result<()> {
-    // Initialize logger
-    env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
+async fn main() {
+    // Failures exit with Composer's codes: 2 for resolution problems, 100 for network errors
+    if let Err(e) = run().await {
+        print_error(&format!("❌ {e:#}"));
+        std::process::exit(lectern::error::exit_code(&e));
+    }
+}
 
+async fn run() -> Result<()> {
     // Parse CLI arguments
-    let cli = Cli::parse();
+    let mut cli = Cli::parse();
+
+    // -v, -vv and -vvv raise the log level; RUST_LOG still wins
+    let log_level = match cli.verbose {
+        0 => "warn",
+        1 => "info",
+        2 => "debug",
+        _ => "trace",
+    };
+    env_logger::Builder::from_env(Env::default().default_filter_or(log_level)).init();
+
+    set_verbosity(cli.verbose);
+    set_quiet(cli.quiet);
+    match cli.message_format.as_str() {
+        "console" => {}
+        "json" => lectern::output::set_reporter(std::sync::Arc::new(lectern::output::JsonReporter)),
+        other => {
+            return Err(anyhow::anyhow!(
+                "Unknown message format '{other}' (expected console or json)"
+            ));
+        }
+    }
+    set_profile(cli.profile);
+    let started = std::time::Instant::now();
 
-    // Set working directory
-    let working_dir = &cli.working_dir;
+    // Set working directory; `global` works on the per-user global project instead
+    let working_dir = &match cli.command {
+        Some(Commands::Global(_)) => global_dir(),
+        _ => cli.working_dir.clone(),
+    };
 
-    // Execute the requested command
-    match cli.command {
-        Some(command) => match command {
-            Commands::Install(args) => {
-                if args.dry_run {
-                    print_info("🔍 Dry run mode - no changes will be made");
-                }
+    // CI environments get non-interactive, plain output and strict exit codes
+    let ci_provider = detect_ci(working_dir);
+    lectern::output::set_ci_provider(ci_provider);
+    if let Some(provider) = ci_provider {
+        cli.no_interaction = true;
+        set_plain_output(true);
+        if cli.verbose > 0 {
+            print_info(&format!(
+                "Detected {provider} environment, running non-interactively"
+            ));
+        }
+    }
+    set_interactive(!cli.no_interaction);
 
-                let composer_path = working_dir.join("composer.json");
-                let composer = read_composer_json(&composer_path)?;
+    lectern::auth::set_project_dir(working_dir);
+    lectern::throttle::configure(lectern::throttle::HttpLimits::load(working_dir));
+    lectern::timeouts::configure(lectern::timeouts::HttpTimeouts::load(working_dir));
+    lectern::http::configure(lectern::http::HttpSettings::load(working_dir)?);
 
-                if !args.dry_run {
-                    let lock = solve(&composer).await?;
-                    let lock_path = working_dir.join("composer.lock");
-                    write_lock(&lock_path, &lock)?;
-                    install_packages(&lock.packages, working_dir).await?;
-                } else {
-                    print_success("✅ Dry run completed - dependencies would be installed");
-                }
+    // Execute the requested command
+    match cli.command.take() {
+        Some(Commands::Shell) => {
+            run_shell(&cli.working_dir, async |command| {
+                run_command(command, &cli, &cli.working_dir).await
+            })
+            .await?;
+        }
+        Some(command) => {
+            if !run_command(command, &cli, &cli.working_dir).await? {
+                std::process::exit(1);
             }
+        }
+        None => {
+            // No command provided, show help
+            use clap::CommandFactory;
+            Cli::command().print_help()?;
+        }
+    }
 
-            Commands::Update(args) => {
-                if args.dry_run {
-                    print_info("🔍 Dry run mode - no changes will be made");
-                }
+    if profile() {
+        print_profile();
+        print_info(&format!("⏱️  Finished in {:.2?}", started.elapsed()));
+    }
+
+    // In CI any reported error fails the run, even if the command recovered
+    if ci_provider.is_some() && error_reported() {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
 
-                let composer_path = working_dir.join("composer.json");
-                let composer = read_composer_json(&composer_path)?;
+/// Run one command, returning false when it reports a failure that should set the exit code
+async fn run_command(command: Commands, cli: &Cli, working_dir: &std::path::Path) -> Result<bool> {
+    match command {
+        Commands::Install(args) => {
+            if args.dry_run {
+                print_info("🔍 Dry run mode - no changes will be made");
+            }
+            let options = LecternOptions {
+                prefer_source: args.prefer_source,
+                env: args.env,
+                no_dev: args.no_dev,
+                dry_run: args.dry_run,
+                no_abandoned: args.no_abandoned,
+                ..LecternOptions::default()
+            };
+            let report = Lectern::new(working_dir)
+                .with_options(options)
+                .install()
+                .await?;
+            if args.dry_run {
+                print_success("✅ Dry run completed - the changes above would be made");
+            }
+            if (args.audit || args.audit_strict)
+                && !audit_installed(&report.lock, args.audit_strict).await?
+            {
+                return Ok(false);
+            }
+        }
 
+        Commands::Update(args) => {
+            if args.dry_run {
+                print_info("🔍 Dry run mode - no changes will be made");
+            }
+            let options = LecternOptions {
+                prefer_source: args.prefer_source,
+                env: args.env,
+                until: args.until,
+                no_dev: args.no_dev,
+                dry_run: args.dry_run,
+                no_install: args.no_install,
+                no_abandoned: args.no_abandoned,
+                ignore: args.ignore,
+                ..LecternOptions::default()
+            };
+            let project = Lectern::new(working_dir).with_options(options);
+            let lock = if args.lock {
+                let lock = project.update_lock()?;
                 if !args.dry_run {
-                    let lock = solve(&composer).await?;
-                    let lock_path = working_dir.join("composer.lock");
-                    write_lock(&lock_path, &lock)?;
-                    install_packages(&lock.packages, working_dir).await?;
-                } else {
-                    print_success("✅ Dry run completed - dependencies would be updated");
+                    print_success("✅ Refreshed the composer.lock hash, keeping locked versions");
                 }
+                lock
+            } else {
+                project.update().await?.lock
+            };
+            if args.dry_run {
+                print_success("✅ Dry run completed - the changes above would be made");
             }
+            if (args.audit || args.audit_strict)
+                && !audit_installed(&lock, args.audit_strict).await?
+            {
+                return Ok(false);
+            }
+        }
 
-            Commands::Require(args) => {
-                if args.dry_run {
-                    print_info("🔍 Dry run mode - no changes will be made");
-                }
+        Commands::Require(args) => {
+            if args.dry_run {
+                print_info("🔍 Dry run mode - no changes will be made");
+            }
+            let options = LecternOptions {
+                dev: args.dev,
+                no_update: args.no_update,
+                dry_run: args.dry_run,
+                no_install: args.no_install,
+                no_abandoned: args.no_abandoned,
+                update_with: if args.update_with_all_dependencies {
+                    UpdateWith::AllDependencies
+                } else if args.update_with_dependencies {
+                    UpdateWith::Dependencies
+                } else {
+                    UpdateWith::Nothing
+                },
+                ..LecternOptions::default()
+            };
+            Lectern::new(working_dir)
+                .with_options(options)
+                .require(&args.packages)
+                .await?;
+            if args.dry_run {
+                print_success("✅ Dry run completed - the changes above would be made");
+            }
+        }
 
-                let composer_path = working_dir.join("composer.json");
-                let mut composer = read_composer_json(&composer_path)?;
+        Commands::Remove(args) => {
+            if args.dry_run {
+                print_info("🔍 Dry run mode - no changes will be made");
+            }
 
-                // Add packages to composer.json
-                for package_spec in &args.packages {
-                    let (name, constraint) = if let Some(pos) = package_spec.find(':') {
-                        (
-                            package_spec[..pos].to_string(),
-                            package_spec[pos + 1..].to_string(),
-                        )
-                    } else {
-                        (package_spec.clone(), "*".to_string())
-                    };
+            let composer_path = manifest_path(working_dir);
+            let mut composer = read_composer_json_raw(&composer_path)?;
 
-                    if args.dev {
-                        composer.require_dev.insert(name, constraint);
+            // Remove packages from composer.json
+            for package_name in &args.packages {
+                if remove_requirement(&mut composer, package_name, args.dev).is_empty() {
+                    let section = if args.dev {
+                        "require-dev"
                     } else {
-                        composer.require.insert(name, constraint);
-                    }
+                        "composer.json"
+                    };
+                    print_warning(&format!("{package_name} is not required in {section}"));
                 }
+            }
 
-                if !args.dry_run {
+            if !args.dry_run {
+                let mut entry = begin_install_operation(working_dir, "remove", args.no_dev)?;
+                let removed = async {
                     // Write updated composer.json
-                    let composer_json = serde_json::to_string_pretty(&composer)?;
-                    std::fs::write(&composer_path, composer_json)?;
-
-                    if !args.no_update {
-                        let lock = solve(&composer).await?;
-                        let lock_path = working_dir.join("composer.lock");
-                        write_lock(&lock_path, &lock)?;
-                        install_packages(&lock.packages, working_dir).await?;
+                    write_composer_json(&composer_path, &composer)?;
+                    if args.no_update {
+                        return Ok(());
                     }
-                } else {
-                    print_success("✅ Dry run completed - packages would be added");
-                }
-            }
 
-            Commands::Remove(args) => {
-                if args.dry_run {
-                    print_info("🔍 Dry run mode - no changes will be made");
-                }
-
-                let composer_path = working_dir.join("composer.json");
-                let mut composer = read_composer_json(&composer_path)?;
-
-                // Remove packages from composer.json
-                for package_name in &args.packages {
-                    if args.dev {
-                        composer.require_dev.remove(package_name);
-                    } else {
-                        composer.require.remove(package_name);
+                    // Resolve with `${VAR}` placeholders expanded
+                    let composer = read_composer_json(&composer_path)?;
+                    let mut lock = solve(&composer).await?;
+                    lock.content_hash = read_composer_content_hash(&composer_path)?;
+                    let lock_path = working_dir.join("composer.lock");
+                    write_lock(&lock_path, &lock)?;
+                    if args.no_install {
+                        print_info("Skipped installing; run 'lectern install' to update vendor/");
+                        return Ok(());
                     }
+                    let dev: &[LockedPackage] = if args.no_dev { &[] } else { &lock.packages_dev };
+                    let pkgs: Vec<LockedPackage> =
+                        lock.packages.iter().chain(dev).cloned().collect();
+                    install_packages(&pkgs, working_dir).await.map(|_| ())
                 }
-
-                if !args.dry_run {
-                    // Write updated composer.json
-                    let composer_json = serde_json::to_string_pretty(&composer)?;
-                    std::fs::write(&composer_path, composer_json)?;
-
-                    if !args.no_update {
-                        let lock = solve(&composer).await?;
-                        let lock_path = working_dir.join("composer.lock");
-                        write_lock(&lock_path, &lock)?;
-                        install_packages(&lock.packages, working_dir).await?;
-                    }
-                } else {
-                    print_success("✅ Dry run completed - packages would be removed");
+                .await;
+                if let Err(e) = removed {
+                    entry.before.restore(working_dir)?;
+                    discard_entry(working_dir, &entry)?;
+                    print_info("Restored the previous composer.json and composer.lock");
+                    return Err(e);
+                }
+                commit_operation(working_dir, &mut entry)?;
+            } else {
+                // Resolve without the removed packages to show what would change
+                if !args.no_update {
+                    let (_, lock) = resolve_prospective(&composer).await?;
+                    let previous = read_lock(&working_dir.join("composer.lock")).ok();
+                    print_lock_diff(&diff_locks(previous.as_ref(), &lock));
                 }
+                print_success("✅ Dry run completed - the changes above would be made");
             }
+        }
 
-            Commands::Show(args) => {
-                if let Some(package) = &args.package {
-                    show_package_details(package, working_dir).await?;
+        Commands::Show(args) => {
+            if args.available && args.package.is_none() {
+                print_error(
+                    "❌ --available needs a package name, e.g. lectern show --available vendor/package",
+                );
+                return Ok(false);
+            }
+            if args.tree {
+                show_dependency_tree(args.package.as_deref(), working_dir)?;
+            } else if let Some(package) = &args.package {
+                if args.available {
+                    show_available_versions(package).await?;
+                } else if let Some(version) = &args.version {
+                    show_remote_version(package, version).await?;
+                } else if args.full {
+                    show_package_full(package, working_dir).await?;
                 } else {
-                    show_dependency_status(working_dir).await?;
+                    show_package_details(package, working_dir).await?;
+                }
+            } else if args.platform {
+                show_platform(working_dir)?;
+            } else {
+                show_dependency_status(working_dir, cli.verbose > 0).await?;
+                if args.all {
+                    show_platform(working_dir)?;
                 }
             }
+        }
 
-            Commands::Autoload(_args) => {
-                let composer_path = working_dir.join("composer.json");
-                let composer = read_composer_json(&composer_path)?;
+        Commands::Autoload(args) => {
+            let composer_path = manifest_path(working_dir);
+            let composer = read_composer_json(&composer_path)?;
 
-                // Read the lock file to get installed packages
-                let lock_path = working_dir.join("composer.lock");
-                if !lock_path.exists() {
-                    print_error("❌ No composer.lock found. Run 'lectern install' first.");
-                    return Ok(());
-                }
+            // Read the lock file to get installed packages
+            let lock_path = working_dir.join("composer.lock");
+            if !lock_path.exists() {
+                print_error("❌ No composer.lock found. Run 'lectern install' first.");
+                return Ok(false);
+            }
 
-                let lock = read_lock(&lock_path)?;
+            let lock = read_lock(&lock_path)?;
 
-                // Convert LockedPackage to InstalledPackage for autoload generation
-                let installed: Vec<InstalledPackage> = lock
-                    .packages
-                    .iter()
-                    .map(|pkg| InstalledPackage {
-                        name: pkg.name.clone(),
-                        version: pkg.version.clone(),
-                        path: format!("vendor/{}", pkg.name).into(),
-                    })
-                    .collect();
+            let installed = lock_packages(&lock, args.no_dev);
+            let options = AutoloadOptions {
+                apcu: args.apcu,
+                ..AutoloadOptions::for_lock(&lock, args.no_dev)
+            };
+            write_autoload_files_with_options(working_dir, &composer, &installed, &options).await?;
+        }
 
-                write_autoload_files(working_dir, &composer, &installed).await?;
-            }
+        Commands::Search(args) => {
+            search_packages(&args.terms, working_dir).await?;
+        }
 
-            Commands::Search(args) => {
-                search_packages(&args.terms, working_dir).await?;
+        Commands::Init(args) => {
+            if !init_project(working_dir, &args)? {
+                return Ok(false);
             }
+        }
 
-            Commands::Init(args) => {
-                init_project(working_dir, &args)?;
+        Commands::Outdated(args) => {
+            let ignore = IgnoreList::for_project(working_dir, &args.ignore)?;
+            if args.workspace {
+                check_outdated_workspace(working_dir, &ignore, cli.quiet).await?;
+            } else {
+                check_outdated_packages(working_dir, &ignore, cli.quiet).await?;
             }
+        }
 
-            Commands::Outdated => {
-                check_outdated_packages(working_dir, cli.quiet).await?;
-            }
+        Commands::Status => {
+            show_dependency_status(working_dir, cli.verbose > 0).await?;
+        }
 
-            Commands::Status => {
-                show_dependency_status(working_dir).await?;
+        Commands::Licenses(args) => {
+            if !show_dependency_licenses(&args, working_dir, cli.quiet).await? {
+                return Ok(false);
             }
+        }
 
-            Commands::Licenses => {
-                show_dependency_licenses(working_dir, cli.quiet).await?;
+        Commands::Validate(args) => {
+            if !validate_composer_json(working_dir, &args).await? {
+                return Ok(false);
             }
+        }
 
-            Commands::Validate(args) => {
-                validate_composer_json(working_dir, &args)?;
-            }
+        Commands::CreateProject(args) => {
+            create_project(&args, working_dir).await?;
+        }
 
-            Commands::CreateProject(args) => {
-                create_project(&args, working_dir).await?;
+        Commands::DumpAutoload(args) => {
+            let composer_path = manifest_path(working_dir);
+            let composer = read_composer_json(&composer_path)?;
+            let lock_path = working_dir.join("composer.lock");
+
+            if !lock_path.exists() {
+                print_error("❌ No composer.lock found. Run 'lectern install' first.");
+                return Ok(false);
             }
 
-            Commands::DumpAutoload(_) => {
-                let composer_path = working_dir.join("composer.json");
-                let composer = read_composer_json(&composer_path)?;
-                let lock_path = working_dir.join("composer.lock");
+            let lock = read_lock(&lock_path)?;
+            let installed = lock_packages(&lock, args.no_dev);
+            let options = AutoloadOptions {
+                apcu: args.apcu,
+                // Asked for explicitly, so files are rewritten even if nothing changed
+                force: true,
+                ..AutoloadOptions::for_lock(&lock, args.no_dev)
+            };
+            write_autoload_files_with_options(working_dir, &composer, &installed, &options).await?;
+            print_success("✅ Generated autoload files");
+        }
 
-                if !lock_path.exists() {
-                    print_error("❌ No composer.lock found. Run 'lectern install' first.");
-                    return Ok(());
-                }
+        Commands::RunScript(args) => {
+            run_script(&args, working_dir).await?;
+        }
 
-                let lock = read_lock(&lock_path)?;
-                let installed: Vec<InstalledPackage> = lock
-                    .packages
-                    .iter()
-                    .map(|pkg| InstalledPackage {
-                        name: pkg.name.clone(),
-                        version: pkg.version.clone(),
-                        path: format!("vendor/{}", pkg.name).into(),
-                    })
-                    .collect();
-
-                write_autoload_files(working_dir, &composer, &installed).await?;
-                print_success("✅ Generated autoload files");
-            }
+        Commands::Diagnose => {
+            diagnose(working_dir).await?;
+        }
 
-            Commands::RunScript(args) => {
-                run_script(&args, working_dir).await?;
-            }
+        Commands::Archive(_args) => {
+            print_info("📦 Archive command not yet fully implemented");
+            // TODO: Implement archive functionality
+        }
 
-            Commands::Diagnose => {
-                diagnose(working_dir).await?;
-            }
+        Commands::ClearCache(args) => {
+            clear_cache(&args).await?;
+        }
 
-            Commands::Archive(_args) => {
-                print_info("📦 Archive command not yet fully implemented");
-                // TODO: Implement archive functionality
-            }
+        Commands::Config(_args) => {
+            print_info("⚙️  Config command not yet fully implemented");
+            // TODO: Implement config management
+        }
 
-            Commands::ClearCache(args) => {
-                clear_cache(&args).await?;
-            }
+        Commands::Depends(args) => {
+            show_depends(&args, working_dir).await?;
+        }
 
-            Commands::Config(_args) => {
-                print_info("⚙️  Config command not yet fully implemented");
-                // TODO: Implement config management
-            }
+        Commands::Prohibits(args) => {
+            show_prohibits(&args, working_dir).await?;
+        }
+
+        Commands::Browse(args) => {
+            browse_package(&args).await?;
+        }
+
+        Commands::Suggests(args) => {
+            show_suggests(&args, working_dir).await?;
+        }
 
-            Commands::Depends(args) => {
-                show_depends(&args, working_dir).await?;
+        Commands::Fund(args) => {
+            show_funding(&args, working_dir).await?;
+        }
+
+        Commands::CheckLock => {
+            if !check_lock(working_dir).await? {
+                return Ok(false);
             }
+        }
+
+        Commands::Clean(args) => {
+            clean(&args, working_dir).await?;
+        }
+
+        Commands::Rollback => {
+            rollback(working_dir).await?;
+        }
+
+        Commands::ThirdPartyFile(args) => {
+            third_party_file(&args, working_dir).await?;
+        }
+
+        Commands::Bump(args) => {
+            bump(&args, working_dir)?;
+        }
+
+        Commands::DockerCacheConfig(args) => {
+            docker_cache_config(&args, working_dir);
+        }
+
+        Commands::Reinstall(args) => {
+            reinstall(&args, working_dir).await?;
+        }
 
-            Commands::Prohibits(args) => {
-                show_prohibits(&args, working_dir).await?;
+        Commands::Sbom(args) => {
+            sbom(&args, working_dir)?;
+        }
+
+        Commands::Sync(args) => {
+            sync(&args, working_dir).await?;
+        }
+
+        Commands::SelfUpdate(args) => {
+            self_update(&args).await?;
+        }
+
+        Commands::Fingerprint(args) => {
+            fingerprint(&args, working_dir)?;
+        }
+
+        Commands::PhpCompat(args) => {
+            if !php_compat(&args.version, working_dir).await? {
+                return Ok(false);
             }
+        }
+        Commands::Shell => {
+            print_error("❌ Already in a lectern shell");
+            return Ok(false);
+        }
 
-            Commands::Browse(args) => {
-                browse_package(&args).await?;
+        Commands::Global(args) => {
+            let dir = global_dir();
+            ensure_global_project(&dir)?;
+            print_info(&format!("Changed current directory to {}", dir.display()));
+            let (command, changes) = match args.command {
+                GlobalCommand::Require(args) => {
+                    let changes = !args.dry_run;
+                    (Commands::Require(args), changes)
+                }
+                GlobalCommand::Remove(args) => {
+                    let changes = !args.dry_run;
+                    (Commands::Remove(args), changes)
+                }
+                GlobalCommand::Update(args) => {
+                    let changes = !args.dry_run;
+                    (Commands::Update(args), changes)
+                }
+                GlobalCommand::Show(args) => (Commands::Show(args), false),
+            };
+            if !Box::pin(run_command(command, cli, &dir)).await? {
+                return Ok(false);
             }
+            if changes {
+                finish_global_change(&dir).await?;
+            }
+        }
 
-            Commands::Suggests => {
-                show_suggests(working_dir).await?;
+        Commands::List => {
+            list(working_dir)?;
+        }
+
+        Commands::Exec(args) => {
+            if exec(&args, working_dir)? != 0 {
+                return Ok(false);
             }
+        }
 
-            Commands::Fund => {
-                show_funding(working_dir).await?;
+        Commands::Workspace(args) => match args.command {
+            WorkspaceCommand::Install(args) => workspace_install(&args, working_dir, false).await?,
+            WorkspaceCommand::Update(args) => workspace_install(&args, working_dir, true).await?,
+            WorkspaceCommand::Status => {
+                if !workspace_status(working_dir)? {
+                    return Ok(false);
+                }
             }
         },
-        _ => {
-            // No command provided, show help
-            use clap::CommandFactory;
-            Cli::command().print_help()?;
+        Commands::Convert(args) => {
+            convert(&args, working_dir)?;
+        }
+        Commands::Graph(args) => {
+            export_graph(&args, working_dir)?;
+        }
+
+        Commands::Script(words) => {
+            run_custom_command(&words, working_dir).await?;
         }
     }
+    Ok(true)
+}
 
-    Ok(())
+/// Check the lock an install or update produced against the security advisories. Findings
+/// and fetch failures are reported without failing the command, unless `strict`.
+async fn audit_installed(lock: &Lock, strict: bool) -> Result<bool> {
+    match audit_lock(lock).await {
+        Ok(report) => {
+            print_audit_summary(&report);
+            Ok(!strict || report.vulnerabilities.is_empty())
+        }
+        Err(e) if !strict => {
+            print_warning(&format!("⚠️  Could not check security advisories: {e:#}"));
+            Ok(true)
+        }
+        Err(e) => Err(e),
+    }
 }
 
 /// Initialize a new project
-fn init_project(working_dir: &std::path::Path, args: &InitArgs) -> Result<()> {
+fn init_project(working_dir: &std::path::Path, args: &InitArgs) -> Result<bool> {
     print_step("📝 Initializing new project...");
 
-    let composer_path = working_dir.join("composer.json");
+    let composer_path = manifest_path(working_dir);
 
     if composer_path.exists() {
         print_error("❌ composer.json already exists");
-        return Ok(());
+        return Ok(false);
     }
 
     let composer = ComposerJson {
@@ -318,6 +605,7 @@ fn init_project(working_dir: &std::path::Path, args: &InitArgs) -> Result<()> {
         repositories: None,
         config: None,
         scripts: None,
+        scripts_descriptions: None,
         extra: None,
         minimum_stability: args.stability.clone(),
         prefer_stable: Some(true),
@@ -329,22 +617,24 @@ fn init_project(working_dir: &std::path::Path, args: &InitArgs) -> Result<()> {
         print_info("📦 Interactive package selection not yet implemented");
     }
 
-    let composer_json = serde_json::to_string_pretty(&composer)?;
-    std::fs::write(&composer_path, composer_json)?;
+    write_composer_json(&composer_path, &composer)?;
 
     print_success("✅ Created composer.json");
-    Ok(())
+    Ok(true)
 }
 
 /// Validate composer.json
-fn validate_composer_json(working_dir: &std::path::Path, _args: &ValidateArgs) -> Result<()> {
+async fn validate_composer_json(
+    working_dir: &std::path::Path,
+    _args: &ValidateArgs,
+) -> Result<bool> {
     print_step("🔍 Validating composer.json...");
 
-    let composer_path = working_dir.join("composer.json");
+    let composer_path = manifest_path(working_dir);
 
     if !composer_path.exists() {
         print_error("❌ composer.json not found");
-        return Ok(());
+        return Ok(false);
     }
 
     match read_composer_json(&composer_path) {
@@ -352,9 +642,36 @@ fn validate_composer_json(working_dir: &std::path::Path, _args: &ValidateArgs) -
             print_success("✅ composer.json is valid");
         }
         Err(e) => {
-            print_error(&format!("❌ composer.json is invalid: {e}"));
+            print_error(&format!("❌ composer.json is invalid: {e:#}"));
+            return Ok(false);
         }
     }
 
-    Ok(())
+    // Circular requirements are allowed but make install order and upgrades harder
+    let lock_path = working_dir.join("composer.lock");
+    if lock_path.exists() {
+        for cycle in DependencyGraph::from_lock(&read_lock(&lock_path)?).cycles() {
+            print_warning(&format!("⚠️  Circular dependency: {}", cycle.join(" → ")));
+        }
+    }
+
+    // Every declared environment must stay solvable
+    let mut unsolvable = 0;
+    for (env, result) in check_environments(&composer_path).await? {
+        match result {
+            Ok(()) => print_success(&format!("✅ Environment '{env}' is solvable")),
+            Err(e) => {
+                unsolvable += 1;
+                print_error(&format!("❌ Environment '{env}' cannot be resolved: {e}"));
+            }
+        }
+    }
+    if unsolvable > 0 {
+        return Err(lectern::LecternError::ResolutionConflict(format!(
+            "{unsolvable} environment(s) cannot be resolved"
+        ))
+        .into());
+    }
+
+    Ok(true)
 }
@@ -0,0 +1,124 @@
+//! `Lectern.toml`, composer.json written as TOML. The keys and their meaning are the same
+//! (`require`, `autoload.psr-4`, `config`, ...), so a project can use either file and
+//! `lectern convert` translates between them. The lock's `content-hash` is computed from
+//! the equivalent JSON, and the keys it covers keep their order through a conversion, so
+//! composer.lock stays valid for Composer.
+
+use crate::resolver::dependency_utils::CONTENT_HASH_KEYS;
+use anyhow::{Context, Result, anyhow};
+use std::path::Path;
+
+pub const COMPOSER_JSON: &str = "composer.json";
+pub const LECTERN_TOML: &str = "Lectern.toml";
+
+/// Which file a project's manifest lives in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManifestFormat {
+    ComposerJson,
+    LecternToml,
+}
+
+impl ManifestFormat {
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::ComposerJson => COMPOSER_JSON,
+            Self::LecternToml => LECTERN_TOML,
+        }
+    }
+
+    /// The format of a manifest file, by its extension
+    pub fn of_path(path: &Path) -> Self {
+        if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"))
+        {
+            Self::LecternToml
+        } else {
+            Self::ComposerJson
+        }
+    }
+
+    /// The format a project uses: composer.json when it exists, so Composer and lectern
+    /// agree, then Lectern.toml. Projects without either get composer.json.
+    pub fn detect(project_dir: &Path) -> Self {
+        if !project_dir.join(COMPOSER_JSON).exists() && project_dir.join(LECTERN_TOML).exists() {
+            Self::LecternToml
+        } else {
+            Self::ComposerJson
+        }
+    }
+}
+
+/// Parse Lectern.toml into the JSON document composer.json would hold
+/// # Errors
+/// Returns an error if `text` is not valid TOML
+pub fn toml_to_json(text: &str) -> Result<serde_json::Value> {
+    let value: toml::Table = toml::from_str(text).context("parse Lectern.toml")?;
+    Ok(serde_json::to_value(value)?)
+}
+
+/// Write a composer.json document as Lectern.toml. TOML has no `null`, so null entries of
+/// objects are left out, as Composer treats them as unset anyway.
+///
+/// TOML writes a table's plain values before its sub-tables, which would reorder keys
+/// that feed the lock's `content-hash`; those are written as inline tables instead.
+/// # Errors
+/// Returns an error if the document is not an object or holds a `null` inside a list
+pub fn json_to_toml(value: &serde_json::Value) -> Result<String> {
+    let mut value = value.clone();
+    drop_nulls(&mut value);
+    let serde_json::Value::Object(mut map) = value else {
+        return Err(anyhow!("A manifest must be an object"));
+    };
+    let mut inline = String::new();
+    for key in CONTENT_HASH_KEYS {
+        if map.get(key).is_some_and(|value| !keeps_key_order(value))
+            && let Some(value) = map.shift_remove(key)
+        {
+            let value: toml::Value =
+                serde_json::from_value(value).context("convert composer.json to TOML")?;
+            inline.push_str(&format!("{key} = {value}\n"));
+        }
+    }
+    let table: toml::Table = serde_json::from_value(serde_json::Value::Object(map))
+        .context("convert composer.json to TOML")?;
+    Ok(inline + &toml::to_string_pretty(&table)?)
+}
+
+/// Whether TOML can write `value` as tables without moving a key: every value that
+/// becomes a sub-table must already come after the plain values of its table
+fn keeps_key_order(value: &serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut seen_table = false;
+            map.values().all(|child| {
+                let is_table = becomes_table(child);
+                let in_order = is_table || !seen_table;
+                seen_table |= is_table;
+                in_order && keeps_key_order(child)
+            })
+        }
+        serde_json::Value::Array(list) => list.iter().all(keeps_key_order),
+        _ => true,
+    }
+}
+
+/// Whether TOML writes `value` under its own `[table]` or `[[table]]` header
+fn becomes_table(value: &serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::Object(_) => true,
+        serde_json::Value::Array(list) => !list.is_empty() && list.iter().all(|v| v.is_object()),
+        _ => false,
+    }
+}
+
+fn drop_nulls(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            map.retain(|_, v| !v.is_null());
+            map.values_mut().for_each(drop_nulls);
+        }
+        serde_json::Value::Array(list) => list.iter_mut().for_each(drop_nulls),
+        _ => {}
+    }
+}
@@ -1,2 +1,3 @@
-// Models module grouping. Submodule maps to `src/models/model.rs`.
+// Models module grouping. Submodules map to files under `src/models/`.
+pub mod lectern_toml;
 pub mod model;
@@ -45,12 +45,18 @@ pub struct ComposerJson {
     pub include_path: Option<Vec<String>>,
     #[serde(default, rename = "target-dir")]
     pub target_dir: Option<String>,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "deserialize_repositories")]
     pub repositories: Option<Vec<Repository>>,
     #[serde(default)]
     pub config: Option<Config>,
     #[serde(default)]
     pub scripts: Option<BTreeMap<String, ScriptDefinition>>,
+    #[serde(
+        default,
+        rename = "scripts-descriptions",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub scripts_descriptions: Option<BTreeMap<String, String>>,
     #[serde(default)]
     pub extra: Option<serde_json::Value>,
     #[serde(default, rename = "minimum-stability")]
@@ -63,38 +69,41 @@ pub struct ComposerJson {
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Author {
-    pub name: String,
     #[serde(default)]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub email: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub homepage: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub role: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Support {
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub email: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub issues: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub forum: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub wiki: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub irc: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub source: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub docs: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub rss: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub chat: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub security: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Default, Deserialize, Serialize)]
 pub struct Config {
     #[serde(default, rename = "vendor-dir")]
     pub vendor_dir: Option<String>,
@@ -143,12 +152,51 @@ pub enum ScriptDefinition {
 
 #[derive(Debug, Deserialize, Serialize, Default, Clone)]
 pub struct Autoload {
-    #[serde(default)]
-    pub psr4: BTreeMap<String, String>,
-    #[serde(default)]
+    /// Namespace prefixes and their directories; `psr4` is accepted from older files
+    #[serde(
+        default,
+        rename = "psr-4",
+        alias = "psr4",
+        skip_serializing_if = "BTreeMap::is_empty"
+    )]
+    pub psr4: BTreeMap<String, AutoloadPaths>,
+    /// Legacy PSR-0 prefixes; read from packages, not used by the generated autoloader
+    #[serde(default, rename = "psr-0", skip_serializing_if = "BTreeMap::is_empty")]
+    pub psr0: BTreeMap<String, AutoloadPaths>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub classmap: Vec<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub files: Vec<String>,
+    /// Paths left out of the classmap; `*` matches within a path segment, `**` across them
+    #[serde(
+        default,
+        rename = "exclude-from-classmap",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub exclude_from_classmap: Vec<String>,
+}
+
+/// One directory, or a list of them
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum AutoloadPaths {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl AutoloadPaths {
+    pub fn paths(&self) -> &[String] {
+        match self {
+            AutoloadPaths::One(path) => std::slice::from_ref(path),
+            AutoloadPaths::Many(paths) => paths,
+        }
+    }
+}
+
+impl From<&str> for AutoloadPaths {
+    fn from(path: &str) -> Self {
+        AutoloadPaths::One(path.to_string())
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -214,8 +262,22 @@ pub enum Repository {
         #[serde(default)]
         exclude: Option<Vec<String>>,
     },
+    /// `{"packagist.org": false}` turns off a default repository
+    #[serde(untagged)]
+    Disabled(BTreeMap<String, bool>),
+}
+
+impl Repository {
+    /// Whether this entry turns off Packagist
+    pub fn disables_packagist(&self) -> bool {
+        matches!(self, Repository::Disabled(names) if names
+            .iter()
+            .any(|(name, enabled)| !enabled && (name == "packagist.org" || name == "packagist")))
+    }
 }
 
+// Composer 2 lock file format. Fields are declared in the order Composer writes them,
+// and anything lectern does not model is kept in `other` so round-trips are lossless.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Lock {
     #[serde(default)]
@@ -229,86 +291,240 @@ pub struct Lock {
     pub aliases: Vec<serde_json::Value>,
     #[serde(default, rename = "minimum-stability")]
     pub minimum_stability: String,
-    #[serde(default, rename = "stability-flags")]
+    #[serde(
+        default,
+        rename = "stability-flags",
+        deserialize_with = "deserialize_map_or_empty_list"
+    )]
     pub stability_flags: BTreeMap<String, i32>,
     #[serde(default, rename = "prefer-stable")]
     pub prefer_stable: bool,
     #[serde(default, rename = "prefer-lowest")]
     pub prefer_lowest: bool,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "deserialize_map_or_empty_list")]
     pub platform: BTreeMap<String, String>,
-    #[serde(default, rename = "platform-dev")]
+    #[serde(
+        default,
+        rename = "platform-dev",
+        deserialize_with = "deserialize_map_or_empty_list"
+    )]
     pub platform_dev: BTreeMap<String, String>,
-    #[serde(default)]
+    #[serde(
+        default,
+        rename = "platform-overrides",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub platform_overrides: Option<BTreeMap<String, String>>,
+    #[serde(
+        default,
+        rename = "plugin-api-version",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub plugin_api_version: Option<String>,
+    #[serde(flatten)]
+    pub other: serde_json::Map<String, serde_json::Value>,
+    /// Top-level keys whose empty map the file held as `[]`, as Composer 2.2 and older
+    /// write them, so `write_lock` gives them back in the same shape
+    #[serde(skip)]
+    pub empty_list_keys: Vec<String>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct LockedPackage {
     pub name: String,
     pub version: String,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_normalized: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub source: Option<SourceInfo>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub dist: Option<DistInfo>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub require: Option<BTreeMap<String, String>>,
-    #[serde(default, rename = "require-dev")]
-    pub require_dev: Option<BTreeMap<String, String>>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub conflict: Option<BTreeMap<String, String>>,
-    #[serde(default)]
-    pub replace: Option<BTreeMap<String, String>>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub provide: Option<BTreeMap<String, String>>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replace: Option<BTreeMap<String, String>>,
+    #[serde(
+        default,
+        rename = "require-dev",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub require_dev: Option<BTreeMap<String, String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub suggest: Option<BTreeMap<String, String>>,
-    #[serde(default, rename = "type")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bin: Option<Vec<String>>,
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
     pub package_type: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub extra: Option<serde_json::Value>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub autoload: Option<Autoload>,
-    #[serde(default, rename = "autoload-dev")]
+    #[serde(
+        default,
+        rename = "autoload-dev",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub autoload_dev: Option<Autoload>,
-    #[serde(default, rename = "notification-url")]
+    #[serde(
+        default,
+        rename = "notification-url",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub notification_url: Option<String>,
-    #[serde(default)]
+    #[serde(
+        default,
+        rename = "include-path",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub include_path: Option<Vec<String>>,
+    #[serde(
+        default,
+        rename = "default-branch",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub default_branch: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scripts: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub license: Option<Vec<String>>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub authors: Option<Vec<Author>>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub homepage: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub keywords: Option<Vec<String>>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub support: Option<Support>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub funding: Option<Vec<serde_json::Value>>,
-    #[serde(default)]
+    /// `true` or the name of the suggested replacement package
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub abandoned: Option<serde_json::Value>,
+    // Fields lectern does not model (`transport-options`, `archive`, ...)
+    #[serde(flatten)]
+    pub other: serde_json::Map<String, serde_json::Value>,
+    // Composer always writes `time` last
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub time: Option<String>,
-    #[serde(default)]
-    pub bin: Option<Vec<String>>,
-    #[serde(default, rename = "include-path")]
-    pub include_path: Option<Vec<String>>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+impl LockedPackage {
+    /// Whether the package was marked abandoned when it was locked
+    pub fn abandonment(&self) -> Option<Abandoned> {
+        Abandoned::from_value(self.abandoned.as_ref())
+    }
+}
+
+/// A package its maintainers no longer work on, from the `abandoned` field of its
+/// metadata: `true`, or the name of the package to use instead
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Abandoned {
+    pub replacement: Option<String>,
+}
+
+impl Abandoned {
+    pub fn from_value(value: Option<&serde_json::Value>) -> Option<Self> {
+        match value? {
+            serde_json::Value::Bool(true) => Some(Self { replacement: None }),
+            serde_json::Value::String(replacement) => Some(Self {
+                replacement: Some(replacement.clone()).filter(|r| !r.is_empty()),
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Abandoned {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.replacement {
+            Some(replacement) => write!(f, "Use {replacement} instead."),
+            None => f.write_str("No replacement was suggested."),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct SourceInfo {
     #[serde(rename = "type")]
     pub source_type: String,
     pub url: String,
+    #[serde(default)]
     pub reference: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mirrors: Option<Vec<Mirror>>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct DistInfo {
     #[serde(rename = "type")]
     pub dist_type: String,
     pub url: String,
+    #[serde(default)]
     pub reference: String,
     #[serde(default)]
     pub shasum: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mirrors: Option<Vec<Mirror>>,
+}
+
+/// Alternative download location; `%package%`, `%version%` and `%reference%` are placeholders
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Mirror {
+    pub url: String,
+    #[serde(default)]
+    pub preferred: bool,
+}
+
+/// Repositories may be listed, or keyed by name in an object whose order is their priority:
+/// `{"acme": {"type": "composer", ...}, "packagist.org": false}`
+fn deserialize_repositories<'de, D>(deserializer: D) -> Result<Option<Vec<Repository>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let entries = match Option::<serde_json::Value>::deserialize(deserializer)? {
+        None | Some(serde_json::Value::Null) => return Ok(None),
+        Some(serde_json::Value::Array(entries)) => entries,
+        // `preserve_order` keeps the keys in the order they were written
+        Some(serde_json::Value::Object(named)) => named
+            .into_iter()
+            .map(|(name, entry)| match entry {
+                serde_json::Value::Bool(enabled) => serde_json::json!({ name: enabled }),
+                entry => entry,
+            })
+            .collect(),
+        Some(_) => return Err(serde::de::Error::custom("expected a list or an object")),
+    };
+    entries
+        .into_iter()
+        .map(|entry| Repository::deserialize(entry).map_err(serde::de::Error::custom))
+        .collect::<Result<Vec<_>, _>>()
+        .map(Some)
+}
+
+/// Composer writes empty maps as `[]` in older lock files
+fn deserialize_map_or_empty_list<'de, D, V>(
+    deserializer: D,
+) -> Result<BTreeMap<String, V>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    V: Deserialize<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum MapOrList<V> {
+        Map(BTreeMap<String, V>),
+        List(Vec<serde_json::Value>),
+    }
+
+    match MapOrList::deserialize(deserializer)? {
+        MapOrList::Map(map) => Ok(map),
+        MapOrList::List(list) if list.is_empty() => Ok(BTreeMap::new()),
+        MapOrList::List(_) => Err(serde::de::Error::custom("expected an object")),
+    }
 }
@@ -1,273 +1,695 @@
+use crate::core::error::{LecternError, find_lectern_error};
+use crate::core::graph::DependencyGraph;
+use crate::core::installer::installer_utils::branch_head;
+use crate::core::profiler::{Phase, record_phase};
 use crate::models::model::{ComposerJson, DistInfo, LockedPackage, SourceInfo};
 use crate::resolver::dependency_utils as utils_dep;
-use crate::resolver::dependency_utils::read_package_from_path;
+use crate::resolver::dependency_utils::{
+    branch_alias, find_best_version_with_stability, find_branch_version, is_branch_constraint,
+    minimum_stability, read_package_from_path, root_provided, root_stability_flags,
+};
 pub use crate::resolver::dependency_utils::{find_best_version, generate_content_hash};
+use crate::resolver::explain::Explainer;
+use crate::resolver::options::ResolveOptions;
 use crate::resolver::packagist::{
-    fetch_packagist_versions_bulk, fetch_packagist_versions_cached, is_platform_dependency,
+    P2Version, fetch_packagist_dev_versions_cached, fetch_packagist_versions_bulk,
+    fetch_packagist_versions_cached, is_platform_dependency,
+};
+use crate::resolver::repositories::Repositories;
+use crate::resolver::stats::{ResolverStats, print_resolver_stats};
+use crate::resolver::version::{
+    Constraint, actual_constraint, alias_satisfies, normalize_alias, parse_inline_alias, satisfies,
 };
-use crate::resolver::version::parse_constraint;
-use crate::utils::{print_error, print_info, print_step, print_success, print_warning};
-use anyhow::{Result, anyhow};
-use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use crate::utils::{
+    print_debug, print_info, print_step, print_success, print_warning, profile, verbosity,
+};
+use anyhow::Result;
+use futures::future::join_all;
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::Path;
+use std::time::Instant;
 
-/// Main dependency resolution function with batch processing optimization
+/// Main dependency resolution function with batch processing optimization.
+/// Statistics are printed with `--profile` or `-v`.
 pub async fn solve(composer: &ComposerJson) -> Result<crate::models::model::Lock> {
-    print_step("🔍 Resolving dependencies...");
+    solve_with_options(composer, &ResolveOptions::default()).await
+}
 
-    let mut locked_packages = Vec::new();
-    let mut processed = BTreeSet::new();
-    let mut queue = VecDeque::new();
-    let mut dev_package_names = BTreeSet::new();
+/// Resolve dependencies restricted by `options`
+pub async fn solve_with_options(
+    composer: &ComposerJson,
+    options: &ResolveOptions,
+) -> Result<crate::models::model::Lock> {
+    let (lock, stats) = resolve(composer, options).await?;
+    record_phase(Phase::MetadataFetch, stats.fetch_time);
+    record_phase(Phase::Resolution, stats.select_time);
+    if profile() || verbosity() > 0 {
+        print_resolver_stats(&stats);
+    }
+    Ok(lock)
+}
 
-    // Collect all dependencies first for batch processing
-    let mut all_deps = Vec::new();
+/// Count a requirement on an already selected package that neither its version nor one
+/// of its aliases satisfies; true if there is such a conflict
+fn record_conflict(
+    stats: &mut ResolverStats,
+    selected: &BTreeMap<String, P2Version>,
+    aliases: &BTreeMap<String, Vec<String>>,
+    name: &str,
+    constraint: &str,
+) -> bool {
+    if let Some(chosen) = selected.get(name)
+        && let Some(matches) = satisfies(constraint, &chosen.version)
+    {
+        stats.candidates_evaluated += 1;
+        let aliased = aliases
+            .get(name)
+            .is_some_and(|aliases| aliases.iter().any(|a| alias_satisfies(constraint, a)));
+        if !aliased && !matches {
+            stats.conflicts += 1;
+            return true;
+        }
+    }
+    false
+}
 
-    // Add all direct dependencies to the queue
-    for (name, constraint) in &composer.require {
+/// The constraint every `(requirer, constraint)` requirement on a package allows.
+/// Unparsable constraints are reported where they are met and don't narrow it.
+/// Why a root requirement could not be fetched: not found when Packagist has no such
+/// package, otherwise the failed request itself
+fn root_fetch_error(name: &str, err: anyhow::Error) -> anyhow::Error {
+    let missing = matches!(
+        find_lectern_error(&err),
+        Some(LecternError::NetworkError { source, .. })
+            if source.status() == Some(reqwest::StatusCode::NOT_FOUND)
+    );
+    if missing {
+        LecternError::PackageNotFound {
+            name: name.to_string(),
+            hint: " on Packagist".to_string(),
+        }
+        .into()
+    } else {
+        err.context(format!("Could not fetch versions for {name}"))
+    }
+}
+
+fn combined_constraint(requirements: &[(String, String)]) -> Option<Constraint> {
+    let specs: BTreeSet<&str> = requirements.iter().map(|(_, spec)| spec.as_str()).collect();
+    specs
+        .into_iter()
+        .filter_map(|spec| Constraint::parse(spec).ok())
+        .reduce(|all, next| all.intersect(&next))
+}
+
+/// Queue the requirements of `version` of `name` for the next level
+fn queue_requirements(level: &mut Vec<(String, String, String)>, name: &str, version: &P2Version) {
+    for (dep_name, dep_constraint) in version.require.iter().flatten() {
         // Skip platform dependencies
-        if is_platform_dependency(name) {
-            print_info(&format!("⏭️  Skipping platform dependency: {name}"));
-            continue;
+        if !is_platform_dependency(dep_name) {
+            level.push((dep_name.clone(), dep_constraint.clone(), name.to_string()));
         }
-        queue.push_back((name.clone(), constraint.clone(), false));
-        all_deps.push(name.clone());
     }
+}
+
+/// The lock entry for `version` of the package `name`, found on Packagist when `packagist`
+/// is set and otherwise in a repository composer.json lists
+fn lock_version(name: &str, version: &P2Version, packagist: bool) -> LockedPackage {
+    LockedPackage {
+        name: name.to_string(),
+        version: version.version.clone(),
+        source: version.source.as_ref().map(|s| SourceInfo {
+            source_type: s.stype.clone().unwrap_or_else(|| "git".to_string()),
+            url: s.url.clone().unwrap_or_default(),
+            reference: s.reference.clone().unwrap_or_default(),
+            mirrors: None,
+        }),
+        dist: version.dist.as_ref().map(|d| DistInfo {
+            dist_type: d.dtype.clone().unwrap_or_else(|| "zip".to_string()),
+            url: d.url.clone().unwrap_or_default(),
+            reference: d.reference.clone().unwrap_or_default(),
+            shasum: d.shasum.clone().unwrap_or_default(),
+            mirrors: None,
+        }),
+        require: version.require.clone(),
+        require_dev: version
+            .other
+            .get("require-dev")
+            .and_then(|v| serde_json::from_value(v.clone()).ok()),
+        conflict: version
+            .other
+            .get("conflict")
+            .and_then(|v| serde_json::from_value(v.clone()).ok()),
+        replace: version
+            .other
+            .get("replace")
+            .and_then(|v| serde_json::from_value(v.clone()).ok()),
+        provide: version
+            .other
+            .get("provide")
+            .and_then(|v| serde_json::from_value(v.clone()).ok()),
+        suggest: version
+            .other
+            .get("suggest")
+            .and_then(|v| serde_json::from_value(v.clone()).ok()),
+        package_type: version
+            .other
+            .get("type")
+            .and_then(|v| v.as_str().map(|s| s.to_string()))
+            .or_else(|| Some("library".to_string())),
+        extra: version.extra.clone(),
+        autoload: version
+            .other
+            .get("autoload")
+            .and_then(|v| serde_json::from_value(v.clone()).ok()),
+        autoload_dev: version
+            .other
+            .get("autoload-dev")
+            .and_then(|v| serde_json::from_value(v.clone()).ok()),
+        notification_url: packagist.then(|| "https://packagist.org/downloads/".to_string()),
+        license: version
+            .other
+            .get("license")
+            .and_then(|v| serde_json::from_value(v.clone()).ok()),
+        authors: version
+            .other
+            .get("authors")
+            .and_then(|v| serde_json::from_value(v.clone()).ok()),
+        description: version
+            .other
+            .get("description")
+            .and_then(|v| v.as_str().map(|s| s.to_string())),
+        homepage: version
+            .other
+            .get("homepage")
+            .and_then(|v| v.as_str().map(|s| s.to_string())),
+        keywords: version
+            .other
+            .get("keywords")
+            .and_then(|v| serde_json::from_value(v.clone()).ok()),
+        support: version
+            .other
+            .get("support")
+            .and_then(|v| serde_json::from_value(v.clone()).ok()),
+        funding: version
+            .other
+            .get("funding")
+            .and_then(|v| serde_json::from_value(v.clone()).ok()),
+        time: version
+            .other
+            .get("time")
+            .and_then(|v| v.as_str().map(|s| s.to_string())),
+        bin: version
+            .other
+            .get("bin")
+            .and_then(|v| serde_json::from_value(v.clone()).ok()),
+        include_path: version
+            .other
+            .get("include-path")
+            .and_then(|v| serde_json::from_value(v.clone()).ok()),
+        default_branch: version
+            .other
+            .get("default-branch")
+            .and_then(serde_json::Value::as_bool),
+        scripts: version.other.get("scripts").cloned(),
+        abandoned: version.other.get("abandoned").cloned(),
+        // Composer drops version_normalized when writing the lock
+        version_normalized: None,
+        other: serde_json::Map::new(),
+    }
+}
+
+/// The git branch a dev version stands for: `feature` for `dev-feature`, `2.x` for `2.x-dev`
+fn branch_name(version: &str) -> &str {
+    version
+        .strip_prefix("dev-")
+        .or_else(|| version.strip_suffix("-dev"))
+        .unwrap_or(version)
+}
 
-    for (name, constraint) in &composer.require_dev {
+/// Lock a branch at a commit: the one pinned with `dev-main#<ref>`, the one the metadata
+/// names, or else the head of the branch in the git source
+async fn pin_branch_reference(locked: &mut LockedPackage, constraint: &str) {
+    let is_commit =
+        |reference: &str| reference.len() == 40 && reference.chars().all(|c| c.is_ascii_hexdigit());
+    let reference = match constraint.split_once('#') {
+        Some((_, pinned)) => pinned.to_string(),
+        None => {
+            let Some(source) = &locked.source else {
+                return;
+            };
+            if is_commit(&source.reference) {
+                return;
+            }
+            match branch_head(&source.url, branch_name(&locked.version)).await {
+                Ok(head) => head,
+                Err(e) => {
+                    print_warning(&format!(
+                        "⚠️  Could not find the head of {} {}: {e}",
+                        locked.name, locked.version
+                    ));
+                    return;
+                }
+            }
+        }
+    };
+
+    if let Some(source) = &mut locked.source {
+        source.reference = reference.clone();
+    }
+    if let Some(dist) = &mut locked.dist {
+        // Archive URLs such as GitHub's zipballs name the commit they contain
+        if !dist.reference.is_empty() {
+            dist.url = dist.url.replace(&dist.reference, &reference);
+        }
+        dist.reference = reference;
+    }
+}
+
+/// Resolve dependencies and report what the resolver did
+pub async fn solve_with_stats(
+    composer: &ComposerJson,
+) -> Result<(crate::models::model::Lock, ResolverStats)> {
+    resolve(composer, &ResolveOptions::default()).await
+}
+
+async fn resolve(
+    composer: &ComposerJson,
+    options: &ResolveOptions,
+) -> Result<(crate::models::model::Lock, ResolverStats)> {
+    print_step("🔍 Resolving dependencies...");
+    let started = Instant::now();
+    let mut stats = ResolverStats::default();
+    let mut selected: BTreeMap<String, P2Version> = BTreeMap::new();
+    // Versions selected packages also count as: root inline aliases and branch aliases
+    let mut aliases: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut lock_aliases = Vec::new();
+
+    let mut locked_packages: Vec<LockedPackage> = Vec::new();
+    let mut processed = BTreeSet::new();
+    // Every `(requirer, constraint)` met per package, so a version is chosen for all of them
+    let mut requirements: BTreeMap<String, Vec<(String, String)>> = BTreeMap::new();
+    // The versions a package was selected from, to choose again when a later requirement
+    // rules the selection out
+    let mut available: BTreeMap<String, Vec<P2Version>> = BTreeMap::new();
+    // Repositories listed in composer.json are consulted before Packagist
+    let repositories = Repositories::from_composer(composer)?;
+    let mut outside_packagist = BTreeSet::new();
+    // Root requirements may allow less stable versions than minimum-stability
+    let stability_flags = root_stability_flags(composer);
+    let minimum = minimum_stability(composer);
+    let prefer_stable = composer.prefer_stable.unwrap_or(false);
+    let stability_of = |name: &str| {
+        stability_flags
+            .get(&name.to_ascii_lowercase())
+            .copied()
+            .unwrap_or(minimum)
+    };
+    let root = composer
+        .name
+        .clone()
+        .unwrap_or_else(|| "__root__".to_string());
+
+    // Direct dependencies form the first level of the resolution; which packages are dev
+    // only is decided once everything is resolved
+    let mut level: Vec<(String, String, String)> = Vec::new();
+    for (name, constraint) in composer.require.iter().chain(&composer.require_dev) {
         // Skip platform dependencies
         if is_platform_dependency(name) {
             print_info(&format!("⏭️  Skipping platform dependency: {name}"));
             continue;
         }
-        dev_package_names.insert(name.clone());
-        queue.push_back((name.clone(), constraint.clone(), true));
-        all_deps.push(name.clone());
+        level.push((name.clone(), constraint.clone(), root.clone()));
     }
 
-    // Pre-fetch all direct dependencies in bulk for better performance
-    if !all_deps.is_empty() {
-        print_info(&format!(
-            "📥 Pre-fetching {} dependencies in batch...",
-            all_deps.len()
-        ));
-        let _bulk_versions = fetch_packagist_versions_bulk(&all_deps)
+    // Resolve level by level so each level's metadata is fetched in one concurrent batch
+    let mut depth = 0;
+    let loop_started = Instant::now();
+    while !level.is_empty() {
+        let mut to_fetch: Vec<String> = level
+            .iter()
+            .map(|(name, _, _)| name.clone())
+            // Path repositories are read from disk instead
+            .filter(|name| {
+                !processed.contains(name)
+                    && root_provided(composer, name).is_none()
+                    && !Path::new(name).join("composer.json").exists()
+            })
+            .collect();
+        to_fetch.sort();
+        to_fetch.dedup();
+
+        if !to_fetch.is_empty() {
+            print_info(&format!(
+                "📥 Fetching metadata for {} packages (depth {depth})...",
+                to_fetch.len()
+            ));
+        }
+        let fetch_started = Instant::now();
+        let mut custom: BTreeMap<String, (Vec<P2Version>, bool)> = BTreeMap::new();
+        if !repositories.is_empty() {
+            let found = join_all(to_fetch.iter().map(|name| repositories.versions(name))).await;
+            custom = to_fetch.iter().cloned().zip(found).collect();
+        }
+        // Packagist is only asked for what no canonical repository has
+        to_fetch.retain(|name| {
+            repositories.packagist() && !custom.get(name).is_some_and(|(_, canonical)| *canonical)
+        });
+        let mut metadata = fetch_packagist_versions_bulk(&to_fetch)
             .await
             .unwrap_or_default();
-        print_success("✅ Batch pre-fetch completed");
-    }
+        stats.fetch_time += fetch_started.elapsed();
 
-    while let Some((pkg_name, constraint_str, is_dev)) = queue.pop_front() {
-        if processed.contains(&pkg_name) {
-            continue;
-        }
-        processed.insert(pkg_name.clone());
-
-        print_info(&format!("📦 Processing: {pkg_name} ({constraint_str})"));
-
-        // Handle repository paths
-        if let Some(path_pkg) = read_package_from_path(Path::new(&pkg_name))? {
-            let locked = LockedPackage {
-                name: path_pkg.0,
-                version: path_pkg.1.unwrap_or_else(|| "dev-main".to_string()),
-                source: Some(SourceInfo {
-                    source_type: "path".to_string(),
-                    url: pkg_name.clone(),
-                    reference: "HEAD".to_string(),
-                }),
-                dist: None,
-                require: None,
-                require_dev: None,
-                conflict: None,
-                replace: None,
-                provide: None,
-                suggest: None,
-                package_type: Some("library".to_string()),
-                extra: None,
-                autoload: None,
-                autoload_dev: None,
-                notification_url: None,
-                license: None,
-                authors: None,
-                description: None,
-                homepage: None,
-                keywords: None,
-                support: None,
-                funding: None,
-                time: None,
-                bin: None,
-                include_path: None,
-            };
-            locked_packages.push(locked);
-            continue;
+        // Record the whole level first, so a package required twice at the same depth is
+        // selected for both requirements
+        for (name, constraint, requirer) in &level {
+            requirements
+                .entry(name.clone())
+                .or_default()
+                .push((requirer.clone(), constraint.clone()));
         }
 
-        // Fetch available versions from Packagist
-        let versions = match fetch_packagist_versions_cached(&pkg_name).await {
-            Ok(v) => v,
-            Err(e) => {
-                print_warning(&format!("⚠️  Could not fetch versions for {pkg_name}: {e}"));
+        let mut next_level = Vec::new();
+        for (pkg_name, constraint_str, requirer) in level {
+            // Nothing to install for what the project itself replaces or provides
+            if let Some(provided) = root_provided(composer, &pkg_name) {
+                if provided.satisfies(&constraint_str) {
+                    if processed.insert(pkg_name.clone()) {
+                        print_info(&format!(
+                            "⏭️  Skipping {pkg_name}: provided by the root package"
+                        ));
+                    }
+                    continue;
+                }
+                if provided.replaced {
+                    let explainer = Explainer {
+                        root: &root,
+                        requirements: &requirements,
+                        selected: &selected,
+                    };
+                    return Err(LecternError::ResolutionConflict(format!(
+                        "{} -> but the root package replaces it with version {}",
+                        explainer.chain(&pkg_name, &requirer, &constraint_str),
+                        provided.version.unwrap_or_default()
+                    ))
+                    .into());
+                }
+            }
+            if processed.contains(&pkg_name) {
+                if !record_conflict(&mut stats, &selected, &aliases, &pkg_name, &constraint_str) {
+                    continue;
+                }
+                // Choose again with every requirement seen so far
+                let requirers = &requirements[&pkg_name];
+                let reselected = available.get(&pkg_name).and_then(|versions| {
+                    let constraint = combined_constraint(requirers)?;
+                    stats.candidates_evaluated += versions.len();
+                    find_best_version_with_stability(
+                        versions,
+                        &constraint,
+                        stability_of(&pkg_name),
+                        prefer_stable,
+                    )
+                    .ok()
+                    .cloned()
+                });
+                let Some(best_version) = reselected else {
+                    let explainer = Explainer {
+                        root: &root,
+                        requirements: &requirements,
+                        selected: &selected,
+                    };
+                    return Err(LecternError::ResolutionConflict(
+                        explainer.conflict(&pkg_name, options.locked_version(&pkg_name)),
+                    )
+                    .into());
+                };
+                stats.backtracks += 1;
+                print_info(&format!(
+                    "↩️  Reselecting {pkg_name} {} for {requirer} ({constraint_str})",
+                    best_version.version
+                ));
+                // The replaced version's requirements no longer apply
+                for on_package in requirements.values_mut() {
+                    on_package.retain(|(by, _)| *by != pkg_name);
+                }
+                if let Some(locked) = locked_packages.iter_mut().find(|p| p.name == pkg_name) {
+                    *locked = lock_version(
+                        &pkg_name,
+                        &best_version,
+                        !outside_packagist.contains(&pkg_name),
+                    );
+                }
+                queue_requirements(&mut next_level, &pkg_name, &best_version);
+                selected.insert(pkg_name, best_version);
                 continue;
             }
-        };
+            processed.insert(pkg_name.clone());
+            stats.packages_in_pool += 1;
 
-        if versions.is_empty() {
-            print_warning(&format!("⚠️  No versions found for package: {pkg_name}"));
-            continue;
-        }
+            print_info(&format!("📦 Processing: {pkg_name} ({constraint_str})"));
 
-        // Parse the constraint
-        let constraint = match parse_constraint(&constraint_str) {
-            Ok(c) => c,
-            Err(e) => {
-                print_error(&format!(
-                    "❌ Invalid constraint '{constraint_str}' for package {pkg_name}: {e}"
-                ));
+            // Handle repository paths
+            if let Some(path_pkg) = read_package_from_path(Path::new(&pkg_name))? {
+                let locked = LockedPackage {
+                    name: path_pkg.0,
+                    version: path_pkg.1.unwrap_or_else(|| "dev-main".to_string()),
+                    source: Some(SourceInfo {
+                        source_type: "path".to_string(),
+                        url: pkg_name.clone(),
+                        reference: "HEAD".to_string(),
+                        mirrors: None,
+                    }),
+                    dist: None,
+                    require: None,
+                    require_dev: None,
+                    conflict: None,
+                    replace: None,
+                    provide: None,
+                    suggest: None,
+                    package_type: Some("library".to_string()),
+                    extra: None,
+                    autoload: None,
+                    autoload_dev: None,
+                    notification_url: None,
+                    license: None,
+                    authors: None,
+                    description: None,
+                    homepage: None,
+                    keywords: None,
+                    support: None,
+                    funding: None,
+                    abandoned: None,
+                    time: None,
+                    bin: None,
+                    include_path: None,
+                    default_branch: None,
+                    scripts: None,
+                    version_normalized: None,
+                    other: serde_json::Map::new(),
+                };
+                locked_packages.push(locked);
                 continue;
             }
-        };
-
-        // Find the best matching version
-        let best_version = match find_best_version(&versions, &constraint) {
-            Ok(v) => v,
-            Err(e) => {
-                print_error(&format!(
-                    "❌ No version satisfies constraint '{constraint_str}' for package {pkg_name}: {e}"
-                ));
-                print_info(&format!(
-                    "Available versions for {pkg_name}: {}",
-                    versions
-                        .iter()
-                        .take(5)
-                        .map(|v| v.version.clone())
-                        .collect::<Vec<_>>()
-                        .join(", ")
-                ));
-                return Err(anyhow!(
-                    "No version satisfies constraint '{constraint_str}' for package {pkg_name}"
-                ));
+
+            // What the listed repositories have, then Packagist's batch result unless a canonical
+            // repository had the package; a single retry surfaces the error if the batch dropped it
+            let (mut versions, canonical) = custom.remove(&pkg_name).unwrap_or_default();
+            let from_packagist = !canonical && repositories.packagist();
+            if from_packagist {
+                match metadata.remove(&pkg_name) {
+                    Some(v) => versions.extend(v),
+                    None => {
+                        let fetch_started = Instant::now();
+                        let fetched = fetch_packagist_versions_cached(&pkg_name).await;
+                        stats.fetch_time += fetch_started.elapsed();
+                        match fetched {
+                            Ok(v) => versions.extend(v),
+                            // A root requirement that can't be fetched must not drop out of the lock
+                            Err(e) if depth == 0 && versions.is_empty() => {
+                                return Err(root_fetch_error(&pkg_name, e));
+                            }
+                            Err(e) => {
+                                print_warning(&format!(
+                                    "⚠️  Could not fetch versions for {pkg_name}: {e}"
+                                ));
+                                if versions.is_empty() {
+                                    continue;
+                                }
+                            }
+                        }
+                    }
+                }
+            } else if versions.is_empty() {
+                return Err(LecternError::PackageNotFound {
+                    name: pkg_name,
+                    hint: " in the repositories composer.json lists (packagist.org is disabled)"
+                        .to_string(),
+                }
+                .into());
+            } else {
+                outside_packagist.insert(pkg_name.clone());
             }
-        };
-
-        let locked = LockedPackage {
-            name: pkg_name.clone(),
-            version: best_version.version.clone(),
-            source: best_version.source.as_ref().map(|s| SourceInfo {
-                source_type: s.stype.clone().unwrap_or_else(|| "git".to_string()),
-                url: s.url.clone().unwrap_or_default(),
-                reference: s.reference.clone().unwrap_or_default(),
-            }),
-            dist: best_version.dist.as_ref().map(|d| DistInfo {
-                dist_type: d.dtype.clone().unwrap_or_else(|| "zip".to_string()),
-                url: d.url.clone().unwrap_or_default(),
-                reference: d.reference.clone().unwrap_or_default(),
-                shasum: d.shasum.clone().unwrap_or_default(),
-            }),
-            require: best_version.require.clone(),
-            require_dev: best_version
-                .other
-                .get("require-dev")
-                .and_then(|v| serde_json::from_value(v.clone()).ok()),
-            conflict: best_version
-                .other
-                .get("conflict")
-                .and_then(|v| serde_json::from_value(v.clone()).ok()),
-            replace: best_version
-                .other
-                .get("replace")
-                .and_then(|v| serde_json::from_value(v.clone()).ok()),
-            provide: best_version
-                .other
-                .get("provide")
-                .and_then(|v| serde_json::from_value(v.clone()).ok()),
-            suggest: best_version
-                .other
-                .get("suggest")
-                .and_then(|v| serde_json::from_value(v.clone()).ok()),
-            package_type: best_version
-                .other
-                .get("type")
-                .and_then(|v| v.as_str().map(|s| s.to_string()))
-                .or_else(|| Some("library".to_string())),
-            extra: best_version.extra.clone(),
-            autoload: best_version
-                .other
-                .get("autoload")
-                .and_then(|v| serde_json::from_value(v.clone()).ok()),
-            autoload_dev: best_version
-                .other
-                .get("autoload-dev")
-                .and_then(|v| serde_json::from_value(v.clone()).ok()),
-            notification_url: Some("https://packagist.org/downloads/".to_string()),
-            license: best_version
-                .other
-                .get("license")
-                .and_then(|v| serde_json::from_value(v.clone()).ok()),
-            authors: best_version
-                .other
-                .get("authors")
-                .and_then(|v| serde_json::from_value(v.clone()).ok()),
-            description: best_version
-                .other
-                .get("description")
-                .and_then(|v| v.as_str().map(|s| s.to_string())),
-            homepage: best_version
-                .other
-                .get("homepage")
-                .and_then(|v| v.as_str().map(|s| s.to_string())),
-            keywords: best_version
-                .other
-                .get("keywords")
-                .and_then(|v| serde_json::from_value(v.clone()).ok()),
-            support: best_version
-                .other
-                .get("support")
-                .and_then(|v| serde_json::from_value(v.clone()).ok()),
-            funding: best_version
-                .other
-                .get("funding")
-                .and_then(|v| serde_json::from_value(v.clone()).ok()),
-            time: best_version
-                .other
-                .get("time")
-                .and_then(|v| v.as_str().map(|s| s.to_string())),
-            bin: best_version
-                .other
-                .get("bin")
-                .and_then(|v| serde_json::from_value(v.clone()).ok()),
-            include_path: best_version
-                .other
-                .get("include-path")
-                .and_then(|v| serde_json::from_value(v.clone()).ok()),
-        };
-
-        // Add dependencies to the queue
-        if let Some(deps) = &best_version.require {
-            for (dep_name, dep_constraint) in deps {
-                // Skip platform dependencies
-                if is_platform_dependency(dep_name) {
-                    continue;
+            // Packagist keeps branches in a separate file, only fetched when one is required
+            let actual = actual_constraint(&constraint_str);
+            let branch = is_branch_constraint(&actual);
+            if branch && from_packagist && find_branch_version(&versions, &actual).is_none() {
+                match fetch_packagist_dev_versions_cached(&pkg_name).await {
+                    Ok(dev_versions) => versions.extend(dev_versions),
+                    Err(e) => {
+                        print_warning(&format!("⚠️  Could not fetch branches of {pkg_name}: {e}"))
+                    }
                 }
-                if !processed.contains(dep_name) {
-                    // Mark transitive dependencies of dev packages as dev too
-                    if is_dev {
-                        dev_package_names.insert(dep_name.clone());
+            }
+            stats.versions_fetched += versions.len();
+
+            if versions.is_empty() {
+                if depth == 0 {
+                    return Err(LecternError::PackageNotFound {
+                        name: pkg_name,
+                        hint: " in any repository".to_string(),
                     }
-                    queue.push_back((dep_name.clone(), dep_constraint.clone(), is_dev));
+                    .into());
                 }
+                print_warning(&format!("⚠️  No versions found for package: {pkg_name}"));
+                continue;
+            }
+
+            let versions: Vec<P2Version> = versions
+                .into_iter()
+                .filter(|v| options.allows_package(&pkg_name, v))
+                .collect();
+            if versions.is_empty() {
+                let reason = match options.locked_version(&pkg_name) {
+                    Some(locked) => format!(
+                        "{pkg_name} is ignored and held at {locked}, which is not available"
+                    ),
+                    None => {
+                        format!("No release of {pkg_name} was published before the --until date")
+                    }
+                };
+                return Err(LecternError::ResolutionConflict(reason).into());
+            }
+
+            stats.candidates_evaluated += versions.len();
+            let found = if branch {
+                find_branch_version(&versions, &actual).ok_or_else(|| {
+                    anyhow::anyhow!("no branch {actual} or branch aliased as {actual}")
+                })
+            } else {
+                // Every requirement met so far narrows the choice, not just this one
+                let constraint = match Constraint::parse(&actual) {
+                    Ok(c) => combined_constraint(&requirements[&pkg_name]).unwrap_or(c),
+                    Err(e) => {
+                        let explainer = Explainer {
+                            root: &root,
+                            requirements: &requirements,
+                            selected: &selected,
+                        };
+                        return Err(LecternError::ResolutionConflict(format!(
+                            "Invalid constraint '{constraint_str}' for package {pkg_name}: {e}\n  {}",
+                            explainer.chain(&pkg_name, &requirer, &constraint_str)
+                        ))
+                        .into());
+                    }
+                };
+                find_best_version_with_stability(
+                    &versions,
+                    &constraint,
+                    stability_of(&pkg_name),
+                    prefer_stable,
+                )
+            };
+
+            // Find the best matching version
+            let best_version = match found {
+                Ok(v) => v,
+                Err(e) => {
+                    print_debug(&format!("{pkg_name} {constraint_str}: {e}"));
+                    let explainer = Explainer {
+                        root: &root,
+                        requirements: &requirements,
+                        selected: &selected,
+                    };
+                    let locked = options.locked_version(&pkg_name);
+                    let explanation = if requirements[&pkg_name].len() > 1 {
+                        explainer.conflict(&pkg_name, locked)
+                    } else {
+                        explainer.no_match(&pkg_name, &requirer, &constraint_str, &versions, locked)
+                    };
+                    return Err(LecternError::ResolutionConflict(explanation).into());
+                }
+            };
+            print_debug(&format!(
+                "{pkg_name} {constraint_str}: selected {} of {} candidates",
+                best_version.version,
+                versions.len()
+            ));
+
+            // Only root requirements can alias, as in Composer
+            let mut package_aliases: Vec<String> = branch_alias(best_version)
+                .map(str::to_string)
+                .into_iter()
+                .collect();
+            if depth == 0
+                && let Some(inline_alias) = parse_inline_alias(&constraint_str)
+            {
+                print_info(&format!(
+                    "🔗 Aliasing {pkg_name} {} as {}",
+                    best_version.version, inline_alias.alias
+                ));
+                lock_aliases.push(serde_json::json!({
+                    "package": pkg_name,
+                    "version": best_version.version,
+                    "alias": inline_alias.alias,
+                    "alias_normalized": normalize_alias(&inline_alias.alias),
+                }));
+                package_aliases.push(inline_alias.alias);
+            }
+            if !package_aliases.is_empty() {
+                aliases.insert(pkg_name.clone(), package_aliases);
+            }
+
+            let mut locked = lock_version(&pkg_name, best_version, from_packagist);
+            if branch {
+                pin_branch_reference(&mut locked, &actual).await;
+            }
+
+            // Add dependencies to the queue; those already selected are checked there
+            queue_requirements(&mut next_level, &pkg_name, best_version);
+
+            selected.insert(pkg_name.clone(), best_version.clone());
+            locked_packages.push(locked);
+            // Branches and aliased versions were asked for by name and are not chosen again
+            if !branch && !aliases.contains_key(&pkg_name) {
+                available.insert(pkg_name, versions);
             }
         }
 
-        locked_packages.push(locked);
+        level = next_level;
+        depth += 1;
+    }
+    stats.depth = depth;
+    stats.select_time = loop_started.elapsed().saturating_sub(stats.fetch_time);
+
+    // Versions replaced while resolving may have left dependencies nothing requires now
+    if stats.backtracks > 0 {
+        locked_packages = utils_dep::prune_unreachable(composer, locked_packages);
     }
 
     // Sort packages by name for consistent output
     locked_packages.sort_by(|a, b| a.name.cmp(&b.name));
 
+    // Cycles are allowed, but worth knowing about
+    let cycles = DependencyGraph::from_packages(&locked_packages).cycles();
+    for cycle in &cycles {
+        print_info(&format!("🔁 Circular dependency: {}", cycle.join(" → ")));
+    }
+    stats.cycles = cycles.len();
+
     // Separate dev and regular packages
-    let (dev_packages, regular_packages): (Vec<_>, Vec<_>) = locked_packages
-        .into_iter()
-        .partition(|pkg| dev_package_names.contains(&pkg.name));
+    let (regular_packages, dev_packages) =
+        utils_dep::partition_dev_packages(composer, locked_packages);
 
     print_success(&format!(
         "✅ Resolved {} packages",
@@ -277,7 +699,7 @@ pub async fn solve(composer: &ComposerJson) -> Result<crate::models::model::Lock
     // Generate content hash for the lock file
     let content_hash = utils_dep::generate_content_hash_from_composer(composer);
 
-    Ok(crate::models::model::Lock {
+    let lock = crate::models::model::Lock {
         _readme: vec![
             "This file locks the dependencies of your project to a known state".to_string(),
             "Read more about it at https://getcomposer.org/doc/01-basic-usage.md#installing-dependencies".to_string(),
@@ -286,15 +708,26 @@ pub async fn solve(composer: &ComposerJson) -> Result<crate::models::model::Lock
         content_hash,
         packages: regular_packages,
         packages_dev: dev_packages,
-        aliases: vec![],
+        aliases: lock_aliases,
         minimum_stability: composer.minimum_stability.clone().unwrap_or_else(|| "stable".to_string()),
-        stability_flags: BTreeMap::new(),
-        prefer_stable: composer.prefer_stable.unwrap_or(false),
+        stability_flags,
+        prefer_stable,
         prefer_lowest: false,
-        platform: BTreeMap::new(),
-        platform_dev: BTreeMap::new(),
+        // Root platform requirements are recorded in the lock like Composer does
+        platform: utils_dep::root_platform_requirements(&composer.require),
+        platform_dev: utils_dep::root_platform_requirements(&composer.require_dev),
+        platform_overrides: composer
+            .config
+            .as_ref()
+            .and_then(|c| c.platform.clone())
+            .filter(|p| !p.is_empty()),
         plugin_api_version: Some("2.6.0".to_string()),
-    })
+        other: serde_json::Map::new(),
+        empty_list_keys: Vec::new(),
+    };
+
+    stats.total_time = started.elapsed();
+    Ok((lock, stats))
 }
 
 // Helper functions are in `dependency_utils.rs` and imported above
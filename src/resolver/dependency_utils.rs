@@ -1,33 +1,282 @@
-use crate::models::model::ComposerJson;
-use crate::resolver::packagist::P2Version;
+use crate::models::model::{ComposerJson, Lock, LockedPackage};
+use crate::resolver::packagist::{P2Version, is_platform_dependency};
+use crate::resolver::version::{
+    ComposerVersion, Constraint, actual_constraint, satisfies, split_stability_flag,
+    stability_value, version_stability,
+};
 use anyhow::{Context, Result, anyhow};
-use semver::Version;
-use sha2::{Digest, Sha256};
+use md5::{Digest, Md5};
+use std::collections::{BTreeMap, VecDeque};
 use std::path::Path;
 
-/// Generate content hash from composer.json content
+/// Top-level composer.json keys that feed into Composer's lock `content-hash`
+pub const CONTENT_HASH_KEYS: [&str; 11] = [
+    "name",
+    "version",
+    "require",
+    "require-dev",
+    "conflict",
+    "replace",
+    "provide",
+    "minimum-stability",
+    "prefer-stable",
+    "repositories",
+    "extra",
+];
+
+/// Generate content hash from composer.json content.
+/// Matches Composer's `Locker::getContentHash`: the md5 of the sorted relevant keys
+/// (plus `config.platform`) re-encoded the way PHP's `json_encode` would.
+/// Content that is not a JSON object hashes as an empty document.
 pub fn generate_content_hash(content: &str) -> String {
-    let mut hasher = Sha256::new();
-    hasher.update(content.as_bytes());
-    let result = hasher.finalize();
-    hex::encode(result)
+    let parsed: serde_json::Value =
+        serde_json::from_str(content).unwrap_or(serde_json::Value::Null);
+
+    let mut relevant: Vec<(&str, serde_json::Value)> = Vec::new();
+    if let Some(object) = parsed.as_object() {
+        for key in CONTENT_HASH_KEYS {
+            // Composer checks `isset()`, which is false for null keys
+            if let Some(value) = object.get(key).filter(|value| !value.is_null()) {
+                relevant.push((key, value.clone()));
+            }
+        }
+        if let Some(platform) = object
+            .get("config")
+            .and_then(|config| config.get("platform"))
+            .filter(|platform| !platform.is_null())
+        {
+            let mut config = serde_json::Map::new();
+            config.insert("platform".to_string(), platform.clone());
+            relevant.push(("config", serde_json::Value::Object(config)));
+        }
+    }
+    relevant.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut encoded = String::new();
+    if relevant.is_empty() {
+        encoded.push_str("[]");
+    } else {
+        encoded.push('{');
+        for (i, (key, value)) in relevant.iter().enumerate() {
+            if i > 0 {
+                encoded.push(',');
+            }
+            php_json_encode_str(key, &mut encoded);
+            encoded.push(':');
+            php_json_encode(value, &mut encoded);
+        }
+        encoded.push('}');
+    }
+
+    format!("{:x}", Md5::digest(encoded.as_bytes()))
 }
 
-/// Generate content hash from ComposerJson structure
+/// Generate content hash from ComposerJson structure.
+/// Equal to [`generate_content_hash`] of the file as lectern writes it; prefer hashing
+/// the raw composer.json when it is available, since hand-written files may omit keys.
 pub fn generate_content_hash_from_composer(composer: &ComposerJson) -> String {
-    let mut hasher = Sha256::new();
+    let content = serde_json::to_string(composer).unwrap_or_default();
+    generate_content_hash(&content)
+}
+
+/// Encode a JSON value like PHP's `json_encode($value, 0)` after `json_decode($json, true)`
+fn php_json_encode(value: &serde_json::Value, out: &mut String) {
+    match value {
+        serde_json::Value::Null => out.push_str("null"),
+        serde_json::Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        serde_json::Value::Number(n) => out.push_str(&n.to_string()),
+        serde_json::Value::String(s) => php_json_encode_str(s, out),
+        serde_json::Value::Array(items) => php_json_encode_list(items.iter(), out),
+        serde_json::Value::Object(map) => {
+            // PHP arrays with keys 0..n (including empty ones) are encoded as lists
+            if map.keys().enumerate().all(|(i, k)| *k == i.to_string()) {
+                php_json_encode_list(map.values(), out);
+                return;
+            }
+            out.push('{');
+            for (i, (key, item)) in map.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                php_json_encode_str(key, out);
+                out.push(':');
+                php_json_encode(item, out);
+            }
+            out.push('}');
+        }
+    }
+}
+
+fn php_json_encode_list<'a>(items: impl Iterator<Item = &'a serde_json::Value>, out: &mut String) {
+    out.push('[');
+    for (i, item) in items.enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        php_json_encode(item, out);
+    }
+    out.push(']');
+}
+
+/// PHP escapes `/` and every non-ASCII character (as UTF-16 `\uXXXX` units) by default
+fn php_json_encode_str(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '/' => out.push_str("\\/"),
+            '\u{08}' => out.push_str("\\b"),
+            '\u{0c}' => out.push_str("\\f"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 || !c.is_ascii() => {
+                let mut units = [0u16; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    out.push_str(&format!("\\u{unit:04x}"));
+                }
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Which of `packages` the requirements named by `roots` reach, directly or through other
+/// packages. A requirement met through `replace` or `provide` reaches the package declaring
+/// it, and a path package is also reached by its path.
+pub fn reachable<'a>(
+    roots: impl IntoIterator<Item = &'a String>,
+    packages: &[LockedPackage],
+) -> Vec<bool> {
+    // Package names are case-insensitive
+    let mut providers: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for (index, package) in packages.iter().enumerate() {
+        let path = package
+            .source
+            .as_ref()
+            .filter(|source| source.source_type == "path")
+            .map(|source| &source.url);
+        let names = std::iter::once(&package.name)
+            .chain(path)
+            .chain(package.replace.iter().flat_map(BTreeMap::keys))
+            .chain(package.provide.iter().flat_map(BTreeMap::keys));
+        for name in names {
+            providers
+                .entry(name.to_ascii_lowercase())
+                .or_default()
+                .push(index);
+        }
+    }
+    let reached_by = |names: &mut dyn Iterator<Item = &String>| -> Vec<usize> {
+        names
+            .filter_map(|name| providers.get(&name.to_ascii_lowercase()))
+            .flatten()
+            .copied()
+            .collect()
+    };
+
+    let mut reached = vec![false; packages.len()];
+    let mut queue: VecDeque<usize> = reached_by(&mut roots.into_iter()).into();
+    while let Some(index) = queue.pop_front() {
+        if std::mem::replace(&mut reached[index], true) {
+            continue;
+        }
+        queue.extend(reached_by(
+            &mut packages[index].require.iter().flat_map(BTreeMap::keys),
+        ));
+    }
+    reached
+}
+
+/// Split resolved packages into `packages` and `packages-dev` like Composer does: whatever
+/// the root `require` reaches is a production package, even when a dev package needs it
+/// too, and only the rest is dev.
+pub fn partition_dev_packages(
+    composer: &ComposerJson,
+    packages: Vec<LockedPackage>,
+) -> (Vec<LockedPackage>, Vec<LockedPackage>) {
+    let production = reachable(composer.require.keys(), &packages);
+    let (regular, dev): (Vec<_>, Vec<_>) = packages
+        .into_iter()
+        .zip(production)
+        .partition(|(_, production)| *production);
+    (
+        regular.into_iter().map(|(package, _)| package).collect(),
+        dev.into_iter().map(|(package, _)| package).collect(),
+    )
+}
+
+/// Drop packages no root requirement reaches any more, such as the dependencies of a
+/// version the resolver replaced
+pub fn prune_unreachable(
+    composer: &ComposerJson,
+    packages: Vec<LockedPackage>,
+) -> Vec<LockedPackage> {
+    let roots = composer.require.keys().chain(composer.require_dev.keys());
+    let reached = reachable(roots, &packages);
+    packages
+        .into_iter()
+        .zip(reached)
+        .filter_map(|(package, reached)| reached.then_some(package))
+        .collect()
+}
+
+/// A package the root project stands in for: its own name, or one its composer.json
+/// `replace`s or `provide`s
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RootProvided {
+    /// Replaced (or the root itself) rather than provided; only a provided package may
+    /// still come from elsewhere
+    pub replaced: bool,
+    /// The version it stands in with, `None` when that isn't a single version (`*`, or
+    /// `self.version` without a root `version`)
+    pub version: Option<String>,
+}
 
-    // Create a normalized representation for hashing
-    let mut content = String::new();
-    content.push_str(&serde_json::to_string(&composer.require).unwrap_or_default());
-    content.push_str(&serde_json::to_string(&composer.require_dev).unwrap_or_default());
+impl RootProvided {
+    /// Whether a requirement with `constraint` is met by the root
+    pub fn satisfies(&self, constraint: &str) -> bool {
+        self.version
+            .as_deref()
+            .is_none_or(|version| satisfies(constraint, version) != Some(false))
+    }
+}
 
-    hasher.update(content.as_bytes());
-    let result = hasher.finalize();
-    hex::encode(result)
+/// How the root project stands in for the package `name`, if it does
+pub fn root_provided(composer: &ComposerJson, name: &str) -> Option<RootProvided> {
+    let root_version = || composer.version.clone();
+    if composer
+        .name
+        .as_deref()
+        .is_some_and(|root| root.eq_ignore_ascii_case(name))
+    {
+        return Some(RootProvided {
+            replaced: true,
+            version: root_version(),
+        });
+    }
+    let find = |links: &Option<BTreeMap<String, String>>| {
+        links
+            .iter()
+            .flatten()
+            .find(|(link, _)| link.eq_ignore_ascii_case(name))
+            .map(|(_, constraint)| constraint.trim().to_string())
+    };
+    let (replaced, constraint) = find(&composer.replace)
+        .map(|constraint| (true, constraint))
+        .or_else(|| find(&composer.provide).map(|constraint| (false, constraint)))?;
+    let version = if constraint == "self.version" {
+        root_version()
+    } else {
+        ComposerVersion::parse(&constraint).map(|_| constraint)
+    };
+    Some(RootProvided { replaced, version })
 }
 
-/// Find the best version that satisfies the constraint
+/// Find the best version that satisfies the constraint. Versions are ordered as Composer
+/// orders them, so four-number releases such as `1.0.0.1` count too.
 pub fn find_best_version<'a>(
     versions: &'a [P2Version],
     constraint: &semver::VersionReq,
@@ -47,55 +296,219 @@ pub fn find_best_version<'a>(
             || version_string.starts_with("dev-")
             || version_string.ends_with("-dev")
         {
+            let newest = || ComposerVersion::parse("999.0.0-dev");
             // For dev versions, we'll be more lenient
             if constraint == &semver::VersionReq::STAR {
-                candidates.push((version, Version::parse("999.0.0-dev").unwrap()));
+                candidates.extend(newest().map(|newest| (version, newest)));
                 continue;
             }
             // Try to match dev versions with appropriate constraints
             if format!("{constraint}").contains("dev") {
-                candidates.push((version, Version::parse("999.0.0-dev").unwrap()));
+                candidates.extend(newest().map(|newest| (version, newest)));
                 continue;
             }
         }
 
-        // Try to normalize and parse the version
-        let normalized_version = match normalize_version_string(version_string) {
-            Ok(v) => v,
-            Err(_) => {
-                // Try some alternative normalization strategies
-                if let Ok(alt_version) = try_alternative_normalization(version_string) {
-                    alt_version
-                } else {
-                    continue; // Skip unparseable versions
-                }
-            }
-        };
-
-        if let Ok(semver_version) = Version::parse(&normalized_version) {
-            if constraint.matches(&semver_version) {
-                candidates.push((version, semver_version));
-            }
+        // Try the version as written, then some alternative normalization strategies
+        let parsed = ComposerVersion::parse(version_string)
+            .or_else(|| ComposerVersion::parse(&version.version))
+            .or_else(|| {
+                try_alternative_normalization(version_string)
+                    .ok()
+                    .and_then(|alt_version| ComposerVersion::parse(&alt_version))
+            });
+        if let Some(parsed) = parsed
+            && constraint.matches(&parsed.to_semver())
+        {
+            candidates.push((version, parsed));
         }
     }
 
-    if candidates.is_empty() {
-        return Err(anyhow!(
-            "No version satisfies constraint. Constraint: {}, Available versions: [{}]",
-            constraint,
+    // The highest version is the best one, the first listed among equals
+    candidates
+        .into_iter()
+        .min_by(|a, b| b.1.cmp(&a.1))
+        .map(|(version, _)| version)
+        .ok_or_else(|| {
+            anyhow!(
+                "No version satisfies constraint. Constraint: {}, Available versions: [{}]",
+                constraint,
+                versions
+                    .iter()
+                    .take(10)
+                    .map(|v| v.version.clone())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        })
+}
+
+/// Find the best version that satisfies the constraint among those at least as stable as
+/// `stability`, with Composer's ordering: RC over beta of the same release. With
+/// `prefer_stable` a stable match wins over a newer prerelease. Branches are only selected
+/// by name, see `find_branch_version`.
+pub fn find_best_version_with_stability<'a>(
+    versions: &'a [P2Version],
+    constraint: &Constraint,
+    stability: i32,
+    prefer_stable: bool,
+) -> Result<&'a P2Version> {
+    let best = versions
+        .iter()
+        .filter(|version| {
+            version_stability(&version.version) <= stability && !is_dev_branch(&version.version)
+        })
+        .filter_map(|version| {
+            let parsed = ComposerVersion::parse(&version.version)
+                .or_else(|| ComposerVersion::parse(&version.version_normalized))?;
+            constraint.matches_version(&parsed).then(|| {
+                (
+                    prefer_stable && version_stability(&version.version) == 0,
+                    parsed,
+                    version,
+                )
+            })
+        })
+        .max_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+    best.map(|(_, _, version)| version).ok_or_else(|| {
+        anyhow!(
+            "No version satisfies constraint. Available versions: [{}]",
             versions
                 .iter()
                 .take(10)
                 .map(|v| v.version.clone())
                 .collect::<Vec<_>>()
                 .join(", ")
-        ));
+        )
+    })
+}
+
+/// Whether `version` is a branch (`dev-main`, `2.x-dev`) rather than a tagged release
+fn is_dev_branch(version: &str) -> bool {
+    version.starts_with("dev-") || version.ends_with("-dev")
+}
+
+/// The stability each root requirement allows, as Composer records it in the lock's
+/// `stability-flags`: an explicit flag (`^1.0@beta`), or else the stability of a version
+/// the constraint names when it is below `minimum_stability` (`dev-main`, `2.0.0-RC1`)
+pub fn root_stability_flags(composer: &ComposerJson) -> BTreeMap<String, i32> {
+    let minimum = minimum_stability(composer);
+    let mut flags = BTreeMap::new();
+    for (name, constraint) in composer.require.iter().chain(&composer.require_dev) {
+        let flag = split_stability_flag(constraint).1.or_else(|| {
+            actual_constraint(constraint)
+                .split([' ', ',', '|'])
+                .map(|part| part.trim_start_matches(['^', '~', '<', '>', '=', '!']))
+                .filter(|part| !part.is_empty() && *part != "*")
+                .map(version_stability)
+                .max()
+                .filter(|implied| *implied > minimum)
+        });
+        if let Some(flag) = flag {
+            flags.insert(name.to_ascii_lowercase(), flag);
+        }
     }
+    flags
+}
+
+/// The platform packages (`php`, `ext-*`, ...) in a root requirement section, as the lock
+/// records them under `platform` and `platform-dev`
+pub fn root_platform_requirements(requires: &BTreeMap<String, String>) -> BTreeMap<String, String> {
+    requires
+        .iter()
+        .filter(|(name, _)| is_platform_dependency(name))
+        .map(|(name, constraint)| (name.clone(), constraint.clone()))
+        .collect()
+}
+
+/// Bring what the lock records about the root up to date with composer.json without
+/// touching the locked packages: stability settings and platform requirements. The
+/// `content-hash` is left to the caller, which knows the file as written.
+pub fn refresh_root_metadata(composer: &ComposerJson, lock: &mut Lock) {
+    lock.minimum_stability = composer
+        .minimum_stability
+        .clone()
+        .unwrap_or_else(|| "stable".to_string());
+    lock.stability_flags = root_stability_flags(composer);
+    lock.prefer_stable = composer.prefer_stable.unwrap_or(false);
+    lock.platform = root_platform_requirements(&composer.require);
+    lock.platform_dev = root_platform_requirements(&composer.require_dev);
+    lock.platform_overrides = composer
+        .config
+        .as_ref()
+        .and_then(|c| c.platform.clone())
+        .filter(|p| !p.is_empty());
+}
+
+/// The value of composer.json's `minimum-stability`, `stable` when unset or unknown
+pub fn minimum_stability(composer: &ComposerJson) -> i32 {
+    composer
+        .minimum_stability
+        .as_deref()
+        .and_then(stability_value)
+        .unwrap_or(0)
+}
 
-    // Sort by version (highest first) and return the best one
-    candidates.sort_by(|a, b| b.1.cmp(&a.1));
+/// Whether `constraint` names a branch rather than a range of releases: `dev-feature`, or
+/// a numbered branch like `2.x-dev`
+pub fn is_branch_constraint(constraint: &str) -> bool {
+    let constraint = constraint.trim();
+    constraint.starts_with("dev-")
+        || (constraint.ends_with("-dev")
+            && !constraint.contains(['^', '~', '>', '<', '=', '*', ' ', ',', '|', '@']))
+}
+
+/// The `extra.branch-alias` of a dev version, e.g. `2.x-dev` for `dev-main`
+pub fn branch_alias(version: &P2Version) -> Option<&str> {
+    version
+        .extra
+        .as_ref()?
+        .get("branch-alias")?
+        .get(&version.version)?
+        .as_str()
+}
+
+/// The version of the branch `constraint` names: the branch itself (`dev-feature`, or
+/// `2.x-dev` as Packagist lists numbered branches), otherwise the dev version whose
+/// `extra.branch-alias` is `constraint`. A `#ref` suffix is ignored.
+pub fn find_branch_version<'a>(
+    versions: &'a [P2Version],
+    constraint: &str,
+) -> Option<&'a P2Version> {
+    let constraint = constraint.split('#').next().unwrap_or(constraint).trim();
+    versions
+        .iter()
+        .find(|version| version.version == constraint)
+        .or_else(|| {
+            versions
+                .iter()
+                .find(|version| branch_alias(version) == Some(constraint))
+        })
+}
 
-    Ok(candidates[0].0)
+/// The versions a locked package also counts as: root inline aliases recorded in the
+/// lock's `aliases`, and the `extra.branch-alias` of its branch
+pub fn locked_aliases(lock: &Lock, package: &LockedPackage) -> Vec<String> {
+    let mut aliases: Vec<String> = lock
+        .aliases
+        .iter()
+        .filter(|alias| {
+            alias.get("package").and_then(|v| v.as_str()) == Some(package.name.as_str())
+                && alias.get("version").and_then(|v| v.as_str()) == Some(package.version.as_str())
+        })
+        .filter_map(|alias| alias.get("alias").and_then(|v| v.as_str()))
+        .map(str::to_string)
+        .collect();
+    if let Some(alias) = package
+        .extra
+        .as_ref()
+        .and_then(|extra| extra.get("branch-alias"))
+        .and_then(|aliases| aliases.get(&package.version))
+        .and_then(|alias| alias.as_str())
+    {
+        aliases.push(alias.to_string());
+    }
+    aliases
 }
 
 /// Try alternative normalization strategies for version strings
@@ -0,0 +1,102 @@
+use crate::resolver::packagist::P2Version;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Most available versions listed when none matches a constraint
+const MAX_LISTED_VERSIONS: usize = 5;
+
+/// What the resolver knows when it gives up, to explain the failure the way Composer
+/// does: each requirement on the package traced back to composer.json, e.g.
+/// `Root composer.json requires foo ^2 -> foo 2.1.0 requires bar ^3`
+#[derive(Debug, Clone, Copy)]
+pub struct Explainer<'a> {
+    /// The name the resolver records root requirements under
+    pub root: &'a str,
+    /// Every `(requirer, constraint)` met per package
+    pub requirements: &'a BTreeMap<String, Vec<(String, String)>>,
+    /// The versions selected so far
+    pub selected: &'a BTreeMap<String, P2Version>,
+}
+
+impl Explainer<'_> {
+    /// How `requirer` came to require `package` with `constraint`, starting from the root
+    pub fn chain(&self, package: &str, requirer: &str, constraint: &str) -> String {
+        let mut steps = Vec::new();
+        let mut seen = BTreeSet::from([package.to_string()]);
+        let (mut package, mut requirer, mut constraint) = (package, requirer, constraint);
+        loop {
+            if requirer == self.root {
+                steps.push(format!(
+                    "Root composer.json requires {package} {constraint}"
+                ));
+                break;
+            }
+            let version = self
+                .selected
+                .get(requirer)
+                .map(|v| format!(" {}", v.version))
+                .unwrap_or_default();
+            steps.push(format!(
+                "{requirer}{version} requires {package} {constraint}"
+            ));
+            // Follow the first requirement on the requirer that doesn't lead back in a cycle
+            let Some((by, by_constraint)) = self
+                .requirements
+                .get(requirer)
+                .into_iter()
+                .flatten()
+                .find(|(by, _)| !seen.contains(by))
+            else {
+                break;
+            };
+            seen.insert(requirer.to_string());
+            (package, requirer, constraint) = (requirer, by, by_constraint);
+        }
+        steps.reverse();
+        steps.join(" -> ")
+    }
+
+    /// Requirements on `package` that no version satisfies together, one chain per line
+    pub fn conflict(&self, package: &str, locked: Option<&str>) -> String {
+        let mut lines = vec![format!(
+            "No version of {package} satisfies every requirement:"
+        )];
+        for (requirer, constraint) in self.requirements.get(package).into_iter().flatten() {
+            lines.push(format!("  {}", self.chain(package, requirer, constraint)));
+        }
+        if let Some(locked) = locked {
+            lines.push(format!("  but {package} is locked at {locked}"));
+        }
+        lines.join("\n")
+    }
+
+    /// A requirement on `package` that none of `versions` matches
+    pub fn no_match(
+        &self,
+        package: &str,
+        requirer: &str,
+        constraint: &str,
+        versions: &[P2Version],
+        locked: Option<&str>,
+    ) -> String {
+        let reason = match locked {
+            Some(locked) => format!("but {package} is locked at {locked}"),
+            None => {
+                let listed: Vec<&str> = versions
+                    .iter()
+                    .take(MAX_LISTED_VERSIONS)
+                    .map(|v| v.version.as_str())
+                    .collect();
+                let more = if versions.len() > MAX_LISTED_VERSIONS {
+                    ", ..."
+                } else {
+                    ""
+                };
+                format!("but the available versions are {}{more}", listed.join(", "))
+            }
+        };
+        format!(
+            "No version satisfies constraint '{constraint}' for package {package}\n  {} -> {reason}",
+            self.chain(package, requirer, constraint)
+        )
+    }
+}
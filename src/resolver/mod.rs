@@ -1,14 +1,24 @@
 pub mod dependency;
 pub mod dependency_utils;
-pub mod http_client;
+pub mod explain;
+pub mod options;
+pub mod package_repository;
 pub mod packagist;
+pub mod repositories;
+pub mod stats;
 pub mod version;
 
 // Re-export commonly used items
-pub use dependency::solve;
-pub use http_client::get_client;
+pub use dependency::{solve, solve_with_options, solve_with_stats};
+pub use explain::Explainer;
+pub use options::ResolveOptions;
+pub use package_repository::inline_packages;
 pub use packagist::{
-    PackageInfo, SearchResult, fetch_multiple_package_info, fetch_package_info,
-    fetch_packagist_versions_bulk, search_packagist,
+    PackageInfo, SearchResult, SecurityAdvisory, fetch_multiple_package_info, fetch_package_info,
+    fetch_packagist_dev_versions_cached, fetch_packagist_versions_bulk,
+    fetch_packagist_versions_cached, fetch_security_advisories, is_platform_dependency,
+    search_packagist,
 };
-pub use version::parse_constraint;
+pub use repositories::Repositories;
+pub use stats::ResolverStats;
+pub use version::{Constraint, parse_constraint, satisfies};
@@ -0,0 +1,123 @@
+use crate::resolver::packagist::P2Version;
+use anyhow::{Result, anyhow};
+use std::collections::BTreeMap;
+
+/// Settings that narrow which versions the resolver may pick
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResolveOptions {
+    /// Only releases published before this Unix timestamp (`update --until`)
+    pub released_before: Option<i64>,
+    /// Packages held at their locked version (`update --ignore`), by lowercase name
+    pub locked: BTreeMap<String, String>,
+}
+
+impl ResolveOptions {
+    /// Options for `--until <date>`
+    /// # Errors
+    /// Returns an error if the date cannot be parsed
+    pub fn until(date: &str) -> Result<Self> {
+        let released_before = parse_timestamp(date).ok_or_else(|| {
+            anyhow!("Invalid date '{date}'. Use YYYY-MM-DD or YYYY-MM-DDTHH:MM:SS")
+        })?;
+        Ok(Self {
+            released_before: Some(released_before),
+            ..Self::default()
+        })
+    }
+
+    /// Whether a version may be selected. Versions without a release time (such as
+    /// dev branches) are excluded when a cutoff is set, since their state cannot be pinned.
+    pub fn allows(&self, version: &P2Version) -> bool {
+        let Some(cutoff) = self.released_before else {
+            return true;
+        };
+        version
+            .other
+            .get("time")
+            .and_then(|time| time.as_str())
+            .and_then(parse_timestamp)
+            .is_some_and(|released| released < cutoff)
+    }
+
+    /// The version `name` is held at, if any
+    pub fn locked_version(&self, name: &str) -> Option<&str> {
+        self.locked.get(&name.to_lowercase()).map(String::as_str)
+    }
+
+    /// Like `allows`, also keeping a held package to its locked version
+    pub fn allows_package(&self, name: &str, version: &P2Version) -> bool {
+        let held = self.locked_version(name).is_none_or(|locked| {
+            locked.trim_start_matches('v') == version.version.trim_start_matches('v')
+        });
+        held && self.allows(version)
+    }
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Parse `YYYY-MM-DD`, optionally followed by `THH:MM:SS` (or a space) and a `Z` or
+/// `±HH:MM` offset, into a Unix timestamp. Times without an offset are UTC.
+pub fn parse_timestamp(value: &str) -> Option<i64> {
+    let value = value.trim();
+    let number = |s: &str| -> Option<i64> {
+        (!s.is_empty() && s.chars().all(|c| c.is_ascii_digit()))
+            .then(|| s.parse().ok())
+            .flatten()
+    };
+
+    let date = value.get(..10)?;
+    let mut parts = date.split('-');
+    let (year, month, day) = (
+        number(parts.next()?)?,
+        number(parts.next()?)?,
+        number(parts.next()?)?,
+    );
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let mut seconds = days_from_civil(year, month, day) * 86_400;
+
+    let rest = &value[10..];
+    if rest.is_empty() {
+        return Some(seconds);
+    }
+    let rest = rest.strip_prefix(['T', ' '])?;
+    let time = rest.get(..8)?;
+    let mut clock = time.split(':');
+    let (hour, minute, second) = (
+        number(clock.next()?)?,
+        number(clock.next()?)?,
+        number(clock.next()?)?,
+    );
+    if hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+    seconds += hour * 3600 + minute * 60 + second;
+
+    // Skip fractional seconds
+    let offset = rest[8..].trim_start_matches(|c: char| c == '.' || c.is_ascii_digit());
+    match offset {
+        "" | "Z" | "z" => Some(seconds),
+        _ => {
+            let sign = match offset.chars().next()? {
+                '+' => 1,
+                '-' => -1,
+                _ => return None,
+            };
+            let digits = offset[1..].replace(':', "");
+            if digits.len() != 4 {
+                return None;
+            }
+            let offset_seconds = number(&digits[..2])? * 3600 + number(&digits[2..])? * 60;
+            Some(seconds - sign * offset_seconds)
+        }
+    }
+}
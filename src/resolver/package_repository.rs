@@ -0,0 +1,56 @@
+use crate::models::model::{ComposerJson, Repository};
+use crate::resolver::packagist::P2Version;
+use anyhow::{Context, Result, anyhow};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Versions defined inline by `"type": "package"` repositories, keyed by lowercase
+/// package name. A repository's `package` may hold one definition or a list of them,
+/// e.g. several versions of the same zip.
+/// # Errors
+/// Returns an error if a definition lacks a name or version or cannot be parsed
+pub fn inline_packages(composer: &ComposerJson) -> Result<BTreeMap<String, Vec<P2Version>>> {
+    let mut packages: BTreeMap<String, Vec<P2Version>> = BTreeMap::new();
+    let repositories = composer.repositories.iter().flatten();
+    for repository in repositories {
+        let Repository::Package { package, .. } = repository else {
+            continue;
+        };
+        for (name, versions) in package_definitions(package)? {
+            packages.entry(name).or_default().extend(versions);
+        }
+    }
+    Ok(packages)
+}
+
+/// The versions one `"type": "package"` repository defines, keyed by lowercase name
+/// # Errors
+/// Returns an error if a definition lacks a name or version or cannot be parsed
+pub fn package_definitions(package: &Value) -> Result<BTreeMap<String, Vec<P2Version>>> {
+    let mut packages: BTreeMap<String, Vec<P2Version>> = BTreeMap::new();
+    let definitions = match package {
+        Value::Array(definitions) => definitions.as_slice(),
+        definition => std::slice::from_ref(definition),
+    };
+    for definition in definitions {
+        let name = definition
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow!("Package repository definition is missing a name"))?
+            .to_lowercase();
+        if definition.get("version").and_then(Value::as_str).is_none() {
+            return Err(anyhow!(
+                "Package repository definition for {name} is missing a version"
+            ));
+        }
+        if definition.get("dist").is_none() && definition.get("source").is_none() {
+            return Err(anyhow!(
+                "Package repository definition for {name} needs a dist or source"
+            ));
+        }
+        let version: P2Version = serde_json::from_value(definition.clone())
+            .with_context(|| format!("parse package repository definition for {name}"))?;
+        packages.entry(name).or_default().push(version);
+    }
+    Ok(packages)
+}
@@ -1,47 +1,204 @@
 use crate::cache;
-use crate::resolver::http_client::get_client;
+use crate::core::error::LecternError;
+use crate::core::http;
+use crate::core::throttle::throttle;
+use crate::core::timeouts::RequestKind;
+use crate::models::model::Abandoned;
+use crate::utils::print_warning;
 use anyhow::{Context, Result};
-use futures::stream::{FuturesUnordered, StreamExt};
-use serde::{Deserialize, Serialize};
+use futures::stream::{self, FuturesUnordered, StreamExt};
+use reqwest::StatusCode;
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use serde::de::value::{MapAccessDeserializer, SeqAccessDeserializer};
+use serde::de::{self, IntoDeserializer, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::BTreeMap;
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Packagist marks a field that a version drops with this value
+const UNSET: &str = "__unset";
 
 #[derive(Debug, Deserialize)]
 pub struct P2Envelope {
     pub packages: BTreeMap<String, Vec<P2Version>>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct P2Version {
     pub version: String,
-    #[serde(default)]
     pub version_normalized: String,
-    #[serde(default)]
     pub dist: Option<P2Dist>,
-    #[serde(default)]
     pub source: Option<P2Source>,
-    #[serde(default)]
     pub require: Option<BTreeMap<String, String>>,
-    #[serde(default)]
     pub extra: Option<serde_json::Value>,
     // Everything else as raw JSON to avoid parsing issues
     #[serde(flatten)]
     pub other: serde_json::Map<String, serde_json::Value>,
 }
 
+/// Read field by field rather than through `#[serde(flatten)]`, which buffers each
+/// version in memory first, dropping `"__unset"` fields as they are read
+impl<'de> Deserialize<'de> for P2Version {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(P2VersionVisitor)
+    }
+}
+
+struct P2VersionVisitor;
+
+impl<'de> Visitor<'de> for P2VersionVisitor {
+    type Value = P2Version;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a Packagist version object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<P2Version, A::Error> {
+        let mut version = None;
+        let mut version_normalized = None;
+        let (mut dist, mut source, mut require, mut extra) = (None, None, None, None);
+        let mut other = serde_json::Map::new();
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "version" => version = Some(map.next_value::<String>()?),
+                "version_normalized" => version_normalized = map.next_value::<Unset<_>>()?.0,
+                "dist" => dist = map.next_value::<Unset<_>>()?.0,
+                "source" => source = map.next_value::<Unset<_>>()?.0,
+                "require" => {
+                    require = map.next_value::<Unset<BTreeMap<String, String>>>()?.0.map(
+                        |mut require| {
+                            require.retain(|_, constraint| constraint != UNSET);
+                            require
+                        },
+                    );
+                }
+                "extra" => {
+                    extra = map
+                        .next_value::<Unset<serde_json::Value>>()?
+                        .0
+                        .map(|mut extra| {
+                            clean_unset_values(&mut extra);
+                            extra
+                        });
+                }
+                _ => {
+                    if let Some(mut value) = map.next_value::<Unset<serde_json::Value>>()?.0 {
+                        clean_unset_values(&mut value);
+                        other.insert(key, value);
+                    }
+                }
+            }
+        }
+        Ok(P2Version {
+            version: version.ok_or_else(|| de::Error::missing_field("version"))?,
+            version_normalized: version_normalized.unwrap_or_default(),
+            dist,
+            source,
+            require,
+            extra,
+            other,
+        })
+    }
+}
+
+/// A value that is `None` when missing, `null` or `"__unset"`, read without buffering
+struct Unset<T>(Option<T>);
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Unset<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer
+            .deserialize_any(UnsetVisitor(PhantomData))
+            .map(Unset)
+    }
+}
+
+/// For `deserialize_with` on optional fields
+fn unset_or_none<'de, D: Deserializer<'de>, T: Deserialize<'de>>(
+    deserializer: D,
+) -> Result<Option<T>, D::Error> {
+    Unset::deserialize(deserializer).map(|unset| unset.0)
+}
+
+struct UnsetVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for UnsetVisitor<T> {
+    type Value = Option<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a value or \"__unset\"")
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        if v == UNSET {
+            return Ok(None);
+        }
+        T::deserialize(v.into_deserializer()).map(Some)
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Self::Value, E> {
+        T::deserialize(v.into_deserializer()).map(Some)
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        T::deserialize(v.into_deserializer()).map(Some)
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        T::deserialize(v.into_deserializer()).map(Some)
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+        T::deserialize(v.into_deserializer()).map(Some)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+        T::deserialize(SeqAccessDeserializer::new(seq)).map(Some)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        T::deserialize(MapAccessDeserializer::new(map)).map(Some)
+    }
+}
+
+impl P2Version {
+    /// Whether Packagist marks the package abandoned; it does so on every version
+    pub fn abandonment(&self) -> Option<Abandoned> {
+        Abandoned::from_value(self.other.get("abandoned"))
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct P2Dist {
-    #[serde(rename = "type")]
+    #[serde(rename = "type", default, deserialize_with = "unset_or_none")]
     pub dtype: Option<String>,
+    #[serde(default, deserialize_with = "unset_or_none")]
     pub url: Option<String>,
+    #[serde(default, deserialize_with = "unset_or_none")]
     pub reference: Option<String>,
+    #[serde(default, deserialize_with = "unset_or_none")]
     pub shasum: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct P2Source {
-    #[serde(rename = "type")]
+    #[serde(rename = "type", default, deserialize_with = "unset_or_none")]
     pub stype: Option<String>,
+    #[serde(default, deserialize_with = "unset_or_none")]
     pub url: Option<String>,
+    #[serde(default, deserialize_with = "unset_or_none")]
     pub reference: Option<String>,
 }
 
@@ -110,14 +267,60 @@ pub struct DownloadStats {
     pub daily: Option<u32>,
 }
 
-/// Recursively remove fields with "__unset" values from JSON
+/// Security advisory from the Packagist advisories API
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecurityAdvisory {
+    pub advisory_id: String,
+    pub package_name: String,
+    pub title: String,
+    #[serde(default)]
+    pub link: Option<String>,
+    #[serde(default)]
+    pub cve: Option<String>,
+    /// Composer constraint such as `>=1.0,<1.2.3|>=2.0,<2.0.4`
+    pub affected_versions: String,
+    #[serde(default)]
+    pub reported_at: Option<String>,
+    #[serde(default)]
+    pub severity: Option<String>,
+}
+
+impl SecurityAdvisory {
+    /// Whether `version` falls in the affected ranges. Each `|` alternative is parsed on its
+    /// own, so one unreadable range doesn't hide a match in another; the answer is `None` if
+    /// the version can't be parsed, or it matches no range but some could not be read.
+    pub fn affects(&self, version: &str) -> Option<bool> {
+        let mut ranges = 0;
+        let mut unknown = false;
+        for range in self
+            .affected_versions
+            .split('|')
+            .map(str::trim)
+            .filter(|r| !r.is_empty())
+        {
+            ranges += 1;
+            match crate::resolver::version::Constraint::parse(range)
+                .ok()
+                .and_then(|constraint| constraint.matches(version))
+            {
+                Some(true) => return Some(true),
+                Some(false) => {}
+                None => unknown = true,
+            }
+        }
+        (ranges > 0 && !unknown).then_some(false)
+    }
+}
+
+/// Recursively remove fields with "__unset" values from the free-form parts of a version
 fn clean_unset_values(value: &mut serde_json::Value) {
     match value {
         serde_json::Value::Object(map) => {
             // Remove keys with "__unset" values
             map.retain(|_, v| {
                 if let serde_json::Value::String(s) = v {
-                    s != "__unset"
+                    s != UNSET
                 } else {
                     true
                 }
@@ -136,39 +339,190 @@ fn clean_unset_values(value: &mut serde_json::Value) {
     }
 }
 
-/// Fetch packagist p2 JSON using client, with in-memory cache
-pub async fn fetch_packagist_versions_cached(pkg: &str) -> Result<Vec<P2Version>> {
-    if let Some(cached) = cache::cache_get_meta(&format!("p2:{pkg}")).await {
-        let list: Vec<P2Version> = serde_json::from_value(cached)?;
-        return Ok(list);
+/// One entry of Packagist's `metadata/changes.json` feed
+#[derive(Debug, Clone, Deserialize)]
+pub struct MetadataChange {
+    #[serde(rename = "type")]
+    pub change_type: String,
+    pub package: String,
+    #[serde(default)]
+    pub time: u64,
+}
+
+/// Response of `metadata/changes.json`; `error` is set when `since` is too old to replay
+#[derive(Debug, Clone, Deserialize)]
+pub struct MetadataChanges {
+    pub timestamp: u64,
+    #[serde(default)]
+    pub actions: Vec<MetadataChange>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl MetadataChanges {
+    /// Packages whose metadata changed (`~dev` variants map to the base name)
+    pub fn changed_packages(&self) -> std::collections::BTreeSet<String> {
+        self.actions
+            .iter()
+            .map(|a| {
+                a.package
+                    .strip_suffix("~dev")
+                    .unwrap_or(&a.package)
+                    .to_string()
+            })
+            .collect()
+    }
+}
+
+/// Invalidate cached metadata according to a changes-feed response
+/// # Errors
+/// Returns an error if a full resync is needed and the cache cannot be cleared
+pub async fn apply_metadata_changes(changes: &MetadataChanges) -> Result<()> {
+    if changes.error.is_some() {
+        // Too far behind to replay individual changes
+        cache::cache_invalidate_all_meta().await?;
+    } else {
+        for pkg in changes.changed_packages() {
+            cache::cache_invalidate_meta(&format!("p2:{pkg}")).await;
+            cache::cache_invalidate_meta(&format!("p2:{pkg}~dev")).await;
+        }
     }
-    let url = format!("https://repo.packagist.org/p2/{pkg}.json");
-    let resp = get_client()
-        .get(&url)
+    cache::cache_set_changes_timestamp(changes.timestamp).await
+}
+
+/// Bring the metadata cache in line with Packagist's changes feed. Returns whether the
+/// cache was caught up, which the first sync (with nothing to replay from) cannot do
+/// # Errors
+/// Returns an error if the feed cannot be fetched or applied
+pub async fn sync_metadata_changes() -> Result<bool> {
+    let since = cache::cache_get_changes_timestamp().await;
+    let url = match since {
+        Some(since) => format!("https://packagist.org/metadata/changes.json?since={since}"),
+        None => "https://packagist.org/metadata/changes.json".to_string(),
+    };
+    let resp = http::get(&url, RequestKind::Metadata)
         .send()
         .await
-        .context("packagist request")?
-        .error_for_status()?;
+        .map_err(|e| LecternError::network(&url, e))?;
+    // Packagist answers a missing or stale `since` with an error body carrying the current timestamp
+    let changes: MetadataChanges = resp.json().await.context("parse metadata changes")?;
+    if since.is_none() {
+        // First sync: start tracking from now; what is already cached is still revalidated
+        cache::cache_set_changes_timestamp(changes.timestamp).await?;
+        return Ok(false);
+    }
+    apply_metadata_changes(&changes).await?;
+    Ok(true)
+}
 
-    // Get the raw JSON text
-    let json_text = resp.text().await.context("get response text")?;
+static METADATA_SYNC: tokio::sync::OnceCell<bool> = tokio::sync::OnceCell::const_new();
 
-    // Try to parse as raw JSON first
-    let mut json_value: serde_json::Value =
-        serde_json::from_str(&json_text).context("parse raw json")?;
+/// Sync with the changes feed once per process before the first metadata lookup;
+/// returns whether cached entries are known to be current
+async fn ensure_metadata_synced() -> bool {
+    *METADATA_SYNC
+        .get_or_init(|| async {
+            // Offline or unavailable feed: fall back to revalidating cached entries
+            sync_metadata_changes().await.unwrap_or(false)
+        })
+        .await
+}
 
-    // Clean up "__unset" values that Packagist uses
-    clean_unset_values(&mut json_value);
+/// Fetch packagist p2 JSON using client, with in-memory cache
+pub async fn fetch_packagist_versions_cached(pkg: &str) -> Result<Vec<P2Version>> {
+    fetch_p2_cached(pkg, "").await
+}
 
-    // Try to extract the envelope
-    let env: P2Envelope = serde_json::from_value(json_value)
-        .with_context(|| format!("parse packagist p2 json for package: {pkg}"))?;
+/// The dev branches of `pkg`, which Packagist keeps in a separate `~dev` file
+pub async fn fetch_packagist_dev_versions_cached(pkg: &str) -> Result<Vec<P2Version>> {
+    fetch_p2_cached(pkg, "~dev").await
+}
+
+async fn fetch_p2_cached(pkg: &str, suffix: &str) -> Result<Vec<P2Version>> {
+    let cache_key = format!("p2:{pkg}{suffix}");
+    let lookup = if ensure_metadata_synced().await {
+        cache::cache_lookup_meta_synced(&cache_key).await
+    } else {
+        cache::cache_lookup_meta(&cache_key).await
+    };
+    let stale = match lookup {
+        cache::MetaLookup::Fresh(cached) => return Ok(serde_json::from_value(cached)?),
+        cache::MetaLookup::Revalidate(cached, validators) => Some((cached, validators)),
+        cache::MetaLookup::Missing => None,
+    };
+
+    let url = format!("https://repo.packagist.org/p2/{pkg}{suffix}.json");
+    let mut request = http::get(&url, RequestKind::Metadata);
+    if let Some((_, validators)) = &stale {
+        if let Some(etag) = &validators.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &validators.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+    }
 
-    let list = env.packages.get(pkg).cloned().unwrap_or_default();
-    cache::cache_set_meta(&format!("p2:{pkg}"), serde_json::to_value(&list)?).await;
+    let _host_guard = throttle().acquire(&url).await;
+    let resp = match request.send().await {
+        Ok(resp) => {
+            throttle().report_status(&url, resp.status().as_u16());
+            resp
+        }
+        Err(e) => {
+            // Offline: stale metadata beats no metadata
+            if let Some((cached, _)) = stale {
+                print_warning(&format!(
+                    "Could not revalidate metadata for {pkg}, using cached copy: {e}"
+                ));
+                return Ok(serde_json::from_value(cached)?);
+            }
+            return Err(LecternError::network(&url, e).into());
+        }
+    };
+
+    if resp.status() == StatusCode::NOT_MODIFIED
+        && let Some((cached, validators)) = stale
+    {
+        let list: Vec<P2Version> = serde_json::from_value(cached.clone())?;
+        cache::cache_set_meta_validated(&cache_key, cached, validators).await;
+        return Ok(list);
+    }
+
+    let resp = resp
+        .error_for_status()
+        .map_err(|e| LecternError::network(&url, e))?;
+    let header = |name| {
+        resp.headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+    };
+    let validators = cache::CacheValidators {
+        etag: header(ETAG),
+        last_modified: header(LAST_MODIFIED),
+    };
+
+    let body = resp.bytes().await.context("get response body")?;
+    let list = parse_p2(pkg, &body)?;
+    let value = serde_json::to_value(&list)?;
+    if validators.is_empty() {
+        cache::cache_set_meta(&cache_key, value).await;
+    } else {
+        cache::cache_set_meta_validated(&cache_key, value, validators).await;
+    }
     Ok(list)
 }
 
+/// The versions of `pkg` in a p2 metadata file, parsed in one pass with `"__unset"`
+/// fields dropped
+/// # Errors
+/// Returns an error if the body is not a p2 metadata file
+pub fn parse_p2(pkg: &str, body: &[u8]) -> Result<Vec<P2Version>> {
+    let mut env: P2Envelope = serde_json::from_slice(body)
+        .with_context(|| format!("parse packagist p2 json for package: {pkg}"))?;
+    Ok(env.packages.remove(pkg).unwrap_or_default())
+}
+
 /// Fetch multiple packages concurrently for better performance
 pub async fn fetch_packagist_versions_bulk(
     packages: &[String],
@@ -183,11 +537,11 @@ pub async fn fetch_packagist_versions_bulk(
 
     for pkg in packages {
         let cache_key = format!("p2:{pkg}");
-        if let Some(cached) = cached_results.get(&cache_key) {
-            if let Ok(list) = serde_json::from_value::<Vec<P2Version>>(cached.clone()) {
-                results.insert(pkg.clone(), list);
-                continue;
-            }
+        if let Some(cached) = cached_results.get(&cache_key)
+            && let Ok(list) = serde_json::from_value::<Vec<P2Version>>(cached.clone())
+        {
+            results.insert(pkg.clone(), list);
+            continue;
         }
         packages_to_fetch.push(pkg.clone());
     }
@@ -196,19 +550,17 @@ pub async fn fetch_packagist_versions_bulk(
         return Ok(results);
     }
 
-    // Fetch uncached packages concurrently
-    let mut futures = FuturesUnordered::new();
-
-    for pkg in packages_to_fetch {
-        futures.push(async move {
+    // Fetch uncached packages concurrently, bounded so large trees don't flood Packagist
+    let mut fetches = stream::iter(packages_to_fetch)
+        .map(|pkg| async move {
             match fetch_packagist_versions_cached(&pkg).await {
                 Ok(versions) => Some((pkg, versions)),
                 Err(_) => None,
             }
-        });
-    }
+        })
+        .buffer_unordered(throttle().limits().max_parallel);
 
-    while let Some(result) = futures.next().await {
+    while let Some(result) = fetches.next().await {
         if let Some((pkg, versions)) = result {
             results.insert(pkg, versions);
         }
@@ -242,12 +594,11 @@ pub async fn search_packagist(terms: &[String]) -> Result<Vec<SearchResult>> {
         urlencoding::encode(&query)
     );
 
-    let resp = get_client()
-        .get(&url)
+    let resp = http::get(&url, RequestKind::Metadata)
         .send()
         .await
-        .context("packagist search request")?
-        .error_for_status()?;
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|e| LecternError::network(&url, e))?;
 
     #[derive(Deserialize)]
     struct SearchResponse {
@@ -272,12 +623,11 @@ pub async fn fetch_package_info(package_name: &str) -> Result<PackageInfo> {
 
     let url = format!("https://packagist.org/packages/{package_name}.json");
 
-    let resp = get_client()
-        .get(&url)
+    let resp = http::get(&url, RequestKind::Metadata)
         .send()
         .await
-        .context("packagist package info request")?
-        .error_for_status()?;
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|e| LecternError::network(&url, e))?;
 
     let package_info: PackageInfo = resp.json().await.context("parse package info response")?;
 
@@ -337,10 +687,10 @@ pub async fn fetch_multiple_package_info(
     // Cache the new results
     let mut cache_data = std::collections::HashMap::new();
     for (name, info_opt) in &final_results {
-        if let Some(info) = info_opt {
-            if let Ok(json_value) = serde_json::to_value(info) {
-                cache_data.insert(name.clone(), json_value);
-            }
+        if let Some(info) = info_opt
+            && let Ok(json_value) = serde_json::to_value(info)
+        {
+            cache_data.insert(name.clone(), json_value);
         }
     }
 
@@ -350,3 +700,50 @@ pub async fn fetch_multiple_package_info(
 
     Ok(final_results)
 }
+
+/// Fetch security advisories for the given packages from Packagist
+pub async fn fetch_security_advisories(
+    package_names: &[String],
+) -> Result<BTreeMap<String, Vec<SecurityAdvisory>>> {
+    if package_names.is_empty() {
+        return Ok(BTreeMap::new());
+    }
+
+    let query = package_names
+        .iter()
+        .map(|name| format!("packages[]={}", urlencoding::encode(name)))
+        .collect::<Vec<_>>()
+        .join("&");
+    let url = format!("https://packagist.org/api/security-advisories/?{query}");
+
+    let resp = http::get(&url, RequestKind::Metadata)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|e| LecternError::network(&url, e))?;
+
+    #[derive(Deserialize)]
+    struct AdvisoriesResponse {
+        #[serde(default, deserialize_with = "deserialize_advisories")]
+        advisories: BTreeMap<String, Vec<SecurityAdvisory>>,
+    }
+
+    let body: AdvisoriesResponse = resp
+        .json()
+        .await
+        .context("parse security advisories response")?;
+    Ok(body.advisories)
+}
+
+/// Packagist returns `[]` instead of `{}` when there are no advisories
+fn deserialize_advisories<'de, D>(
+    deserializer: D,
+) -> Result<BTreeMap<String, Vec<SecurityAdvisory>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Array(list) if list.is_empty() => Ok(BTreeMap::new()),
+        value => serde_json::from_value(value).map_err(serde::de::Error::custom),
+    }
+}
@@ -0,0 +1,237 @@
+use crate::cache;
+use crate::core::http;
+use crate::core::throttle::throttle;
+use crate::core::timeouts::RequestKind;
+use crate::models::model::{ComposerJson, Repository};
+use crate::resolver::package_repository::package_definitions;
+use crate::resolver::packagist::P2Version;
+use crate::utils::print_warning;
+use crate::workspace::segment_matches;
+use anyhow::{Context, Result, anyhow};
+use reqwest::StatusCode;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// The repositories composer.json lists, in the order they are consulted, with Packagist
+/// after them unless `"packagist.org": false` turns it off. As in Composer, the first
+/// repository that has a package provides all of its versions; one marked
+/// `"canonical": false` lets later repositories add theirs.
+#[derive(Debug, Clone)]
+pub struct Repositories {
+    sources: Vec<Source>,
+    packagist: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Source {
+    kind: SourceKind,
+    canonical: bool,
+    /// Name patterns (`acme/*`) this repository is limited to, if any
+    only: Vec<String>,
+    exclude: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+enum SourceKind {
+    /// `"type": "package"` definitions, keyed by lowercase name
+    Inline(BTreeMap<String, Vec<P2Version>>),
+    /// A `"type": "composer"` repository at this URL
+    Composer(String),
+}
+
+impl Source {
+    fn serves(&self, name: &str) -> bool {
+        let matches = |patterns: &[String]| {
+            patterns
+                .iter()
+                .any(|pattern| segment_matches(&pattern.to_ascii_lowercase(), name))
+        };
+        (self.only.is_empty() || matches(&self.only)) && !matches(&self.exclude)
+    }
+}
+
+impl Repositories {
+    /// The repositories of `composer`
+    /// # Errors
+    /// Returns an error if an inline package definition is invalid
+    pub fn from_composer(composer: &ComposerJson) -> Result<Self> {
+        let mut sources = Vec::new();
+        let mut packagist = true;
+        for repository in composer.repositories.iter().flatten() {
+            let (kind, canonical, only, exclude) = match repository {
+                Repository::Package {
+                    package,
+                    canonical,
+                    only,
+                    exclude,
+                } => (
+                    SourceKind::Inline(package_definitions(package)?),
+                    canonical,
+                    only,
+                    exclude,
+                ),
+                Repository::Composer {
+                    url,
+                    canonical,
+                    only,
+                    exclude,
+                    ..
+                } => (
+                    SourceKind::Composer(url.trim_end_matches('/').to_string()),
+                    canonical,
+                    only,
+                    exclude,
+                ),
+                Repository::Disabled(_) => {
+                    packagist &= !repository.disables_packagist();
+                    continue;
+                }
+                // Path repositories are read from disk by the resolver; VCS, artifact and
+                // PEAR repositories aren't supported
+                _ => continue,
+            };
+            sources.push(Source {
+                kind,
+                canonical: canonical.unwrap_or(true),
+                only: only.clone().unwrap_or_default(),
+                exclude: exclude.clone().unwrap_or_default(),
+            });
+        }
+        Ok(Self { sources, packagist })
+    }
+
+    /// Whether Packagist is consulted after the listed repositories
+    pub fn packagist(&self) -> bool {
+        self.packagist
+    }
+
+    /// Whether any repository besides Packagist is listed
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty()
+    }
+
+    /// The versions the listed repositories have of `name`, and whether the search ends
+    /// there because a canonical repository had it. Repositories that can't be reached
+    /// are skipped with a warning.
+    pub async fn versions(&self, name: &str) -> (Vec<P2Version>, bool) {
+        let name = name.to_ascii_lowercase();
+        let mut versions = Vec::new();
+        for source in self.sources.iter().filter(|source| source.serves(&name)) {
+            let found = match &source.kind {
+                SourceKind::Inline(packages) => packages.get(&name).cloned().unwrap_or_default(),
+                SourceKind::Composer(url) => match composer_repository_versions(url, &name).await {
+                    Ok(found) => found,
+                    Err(e) => {
+                        print_warning(&format!("⚠️  Could not query repository {url}: {e:#}"));
+                        continue;
+                    }
+                },
+            };
+            if found.is_empty() {
+                continue;
+            }
+            versions.extend(found);
+            if source.canonical {
+                return (versions, true);
+            }
+        }
+        (versions, false)
+    }
+}
+
+/// The versions of `name` in the Composer repository at `url`: read through the p2
+/// `metadata-url` its `packages.json` names, like Packagist's, or else from the packages
+/// listed in `packages.json` itself, as Satis writes them
+async fn composer_repository_versions(url: &str, name: &str) -> Result<Vec<P2Version>> {
+    let cache_key = format!("repo:{url}:{name}");
+    if let Some(cached) = cache::cache_get_meta(&cache_key).await {
+        return Ok(serde_json::from_value(cached)?);
+    }
+
+    let root = repository_root(url).await?;
+    let listed = |key: &str| root.get(key).and_then(Value::as_array);
+    let versions = if listed("available-packages").is_some_and(|available| {
+        !available
+            .iter()
+            .filter_map(Value::as_str)
+            .any(|available| available.eq_ignore_ascii_case(name))
+    }) {
+        Vec::new()
+    } else if let Some(template) = root.get("metadata-url").and_then(Value::as_str) {
+        let mut versions = Vec::new();
+        for file in [name.to_string(), format!("{name}~dev")] {
+            let metadata_url = reqwest::Url::parse(&format!("{url}/"))
+                .and_then(|base| base.join(&template.replace("%package%", &file)))
+                .with_context(|| format!("invalid metadata-url '{template}'"))?;
+            if let Some(envelope) = fetch_json(metadata_url.as_str()).await? {
+                versions.extend(envelope_versions(&envelope, name)?);
+            }
+        }
+        versions
+    } else {
+        envelope_versions(&root, name)?
+    };
+
+    cache::cache_set_meta(&cache_key, serde_json::to_value(&versions)?).await;
+    Ok(versions)
+}
+
+/// The repository's `packages.json`
+async fn repository_root(url: &str) -> Result<Value> {
+    let cache_key = format!("repo:{url}");
+    if let Some(cached) = cache::cache_get_meta(&cache_key).await {
+        return Ok(cached);
+    }
+    let root_url = format!("{url}/packages.json");
+    let root = fetch_json(&root_url)
+        .await?
+        .ok_or_else(|| anyhow!("{root_url} not found"))?;
+    cache::cache_set_meta(&cache_key, root.clone()).await;
+    Ok(root)
+}
+
+/// Fetch a JSON document; `None` if the server doesn't have it
+async fn fetch_json(url: &str) -> Result<Option<Value>> {
+    let _host_guard = throttle().acquire(url).await;
+    let resp = http::get(url, RequestKind::Metadata)
+        .send()
+        .await
+        .with_context(|| format!("request {url}"))?;
+    throttle().report_status(url, resp.status().as_u16());
+    if resp.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    let resp = resp
+        .error_for_status()
+        .with_context(|| format!("request {url}"))?;
+    Ok(Some(
+        resp.json().await.with_context(|| format!("parse {url}"))?,
+    ))
+}
+
+/// The versions of `name` under `packages`: a list in the p2 layout, or a map from
+/// version to definition in the layout `packages.json` has always used
+fn envelope_versions(envelope: &Value, name: &str) -> Result<Vec<P2Version>> {
+    let Some(entry) = envelope.get("packages").and_then(|packages| {
+        packages.as_object().and_then(|packages| {
+            packages
+                .iter()
+                .find(|(listed, _)| listed.eq_ignore_ascii_case(name))
+                .map(|(_, entry)| entry)
+        })
+    }) else {
+        return Ok(Vec::new());
+    };
+    let definitions: Vec<Value> = match entry {
+        Value::Array(list) => list.clone(),
+        Value::Object(by_version) => by_version.values().cloned().collect(),
+        _ => Vec::new(),
+    };
+    definitions
+        .into_iter()
+        .map(|definition| {
+            serde_json::from_value(definition)
+                .with_context(|| format!("parse repository metadata for {name}"))
+        })
+        .collect()
+}
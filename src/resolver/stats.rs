@@ -0,0 +1,43 @@
+use crate::utils::{print_info, print_text};
+use std::time::Duration;
+
+/// Counters and phase timings gathered during one resolution
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResolverStats {
+    /// Distinct packages considered (including path packages)
+    pub packages_in_pool: usize,
+    /// Versions returned by the repositories for those packages
+    pub versions_fetched: usize,
+    /// Versions checked against a constraint while picking candidates
+    pub candidates_evaluated: usize,
+    /// Requirements the already selected version does not satisfy
+    pub conflicts: usize,
+    /// Selections made again because a later requirement ruled the chosen version out
+    pub backtracks: usize,
+    /// Depth of the deepest dependency level
+    pub depth: usize,
+    /// Circular requirement chains among the selected packages
+    pub cycles: usize,
+    pub fetch_time: Duration,
+    pub select_time: Duration,
+    pub total_time: Duration,
+}
+
+/// Print resolver statistics as an info block
+pub fn print_resolver_stats(stats: &ResolverStats) {
+    print_info("📊 Resolver statistics:");
+    let lines = [
+        format!("  Packages in pool:      {}", stats.packages_in_pool),
+        format!("  Versions fetched:      {}", stats.versions_fetched),
+        format!("  Candidates evaluated:  {}", stats.candidates_evaluated),
+        format!("  Conflicts encountered: {}", stats.conflicts),
+        format!("  Backtracks performed:  {}", stats.backtracks),
+        format!("  Dependency depth:      {}", stats.depth),
+        format!("  Dependency cycles:     {}", stats.cycles),
+        format!(
+            "  Time: {:.2?} fetching metadata, {:.2?} selecting versions, {:.2?} total",
+            stats.fetch_time, stats.select_time, stats.total_time
+        ),
+    ];
+    print_text(&lines.join("\n"));
+}
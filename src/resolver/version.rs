@@ -1,198 +1,656 @@
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use semver::{Version, VersionReq};
 
-/// Parse a constraint string into a semver VersionReq
-pub fn parse_constraint(spec: &str) -> Result<VersionReq> {
-    let spec = spec.trim();
+/// A root requirement such as `dev-feature as 1.5.0`: install the version `constraint`
+/// selects, and let it satisfy requirements on `alias` too
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InlineAlias {
+    pub constraint: String,
+    pub alias: String,
+}
 
-    // Handle special cases
-    if spec == "*" || spec.is_empty() {
-        return Ok(VersionReq::STAR);
+/// Split `spec` into the constraint and the alias when it is an inline alias. A `#ref`
+/// pinning a branch to a commit is dropped from the constraint.
+pub fn parse_inline_alias(spec: &str) -> Option<InlineAlias> {
+    let (constraint, alias) = spec.trim().split_once(" as ")?;
+    let constraint = constraint.trim();
+    let alias = alias.trim();
+    let invalid = |part: &str| part.is_empty() || part.contains([',', ' ', '|']);
+    if invalid(constraint) || invalid(alias) {
+        return None;
     }
+    let constraint = constraint.split('#').next().unwrap_or(constraint);
+    Some(InlineAlias {
+        constraint: constraint.to_string(),
+        alias: alias.to_string(),
+    })
+}
 
-    // Handle OR constraints (both | and ||) by selecting the most permissive constraint
-    if spec.contains('|') {
-        let parts: Vec<&str> = if spec.contains("||") {
-            spec.split("||").collect()
-        } else {
-            spec.split('|').collect()
-        };
+/// The constraint `spec` selects versions with: the part before `as` of an inline alias,
+/// without stability flags
+pub fn actual_constraint(spec: &str) -> String {
+    let constraint =
+        parse_inline_alias(spec).map_or_else(|| spec.trim().to_string(), |alias| alias.constraint);
+    split_stability_flag(&constraint).0
+}
+
+/// Composer's stabilities from most to least stable, with the values composer.lock records
+/// in `stability-flags`
+pub const STABILITIES: [(&str, i32); 5] = [
+    ("stable", 0),
+    ("RC", 5),
+    ("beta", 10),
+    ("alpha", 15),
+    ("dev", 20),
+];
+
+/// The value of a stability name such as `beta` or `RC`, ignoring case
+pub fn stability_value(name: &str) -> Option<i32> {
+    STABILITIES
+        .iter()
+        .find(|(stability, _)| stability.eq_ignore_ascii_case(name.trim()))
+        .map(|(_, value)| *value)
+}
+
+/// The stability of a version: `dev` for branches, `alpha`, `beta` or `RC` for
+/// prereleases, `stable` otherwise
+pub fn version_stability(version: &str) -> i32 {
+    let version = version.trim().to_ascii_lowercase();
+    let version = version.strip_prefix('v').unwrap_or(&version);
+    if version.starts_with("dev-") || version.ends_with("-dev") {
+        return 20;
+    }
+    // The suffix after the numbers, e.g. `-beta2`, `RC1` or `-p1` for a patch release
+    let suffix = version.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.');
+    let suffix = suffix.trim_start_matches(['-', '.', '_']);
+    // `a` and `b` are Composer's short forms of alpha and beta
+    if suffix.starts_with('a') {
+        15
+    } else if suffix.starts_with('b') {
+        10
+    } else if suffix.starts_with("rc") {
+        5
+    } else {
+        0
+    }
+}
 
-        // Try to parse each constraint and find the most permissive one
-        let mut best_constraint = None;
-        let mut best_score = 0;
-
-        for part in &parts {
-            let trimmed = part.trim();
-            if !trimmed.is_empty() {
-                if let Ok(constraint) = parse_simple_constraint(trimmed) {
-                    // Score constraints by how permissive they are
-                    let score = score_constraint_permissiveness(trimmed);
-                    if score > best_score {
-                        best_score = score;
-                        best_constraint = Some(constraint);
-                    }
-                }
+/// Split the stability flags off `spec`: `^1.0@beta` is `^1.0` allowing beta releases, and
+/// a bare `@dev` is `*`. When several parts carry a flag the least stable wins, as in
+/// Composer.
+pub fn split_stability_flag(spec: &str) -> (String, Option<i32>) {
+    let mut constraint = String::new();
+    let mut flag: Option<i32> = None;
+    let mut rest = spec;
+    while let Some(at) = rest.find('@') {
+        constraint.push_str(&rest[..at]);
+        let after = &rest[at + 1..];
+        let end = after
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(after.len());
+        match stability_value(&after[..end]) {
+            Some(value) => {
+                flag = Some(flag.map_or(value, |flag| flag.max(value)));
+                rest = &after[end..];
+            }
+            None => {
+                constraint.push('@');
+                rest = after;
             }
         }
+    }
+    constraint.push_str(rest);
+    let constraint = constraint.trim();
+    let constraint = if constraint.is_empty() {
+        "*"
+    } else {
+        constraint
+    };
+    (constraint.to_string(), flag)
+}
 
-        if let Some(constraint) = best_constraint {
-            return Ok(constraint);
+/// Composer's normalized form of an alias: `1.5` is `1.5.0.0` and `2.x-dev` is
+/// `2.9999999.9999999.9999999-dev`
+pub fn normalize_alias(alias: &str) -> String {
+    let alias = alias.trim();
+    let alias = alias.strip_prefix('v').unwrap_or(alias);
+    let (numbers, dev) = match alias.strip_suffix("-dev") {
+        Some(numbers) => (numbers, true),
+        None => (alias, false),
+    };
+    let mut parts: Vec<&str> = numbers
+        .split('.')
+        .map(|part| match part {
+            "x" | "X" | "*" => "9999999",
+            part => part,
+        })
+        .collect();
+    let wildcard = parts.last() == Some(&"9999999");
+    while parts.len() < 4 {
+        parts.push(if wildcard { "9999999" } else { "0" });
+    }
+    let normalized = parts.join(".");
+    if dev {
+        format!("{normalized}-dev")
+    } else {
+        normalized
+    }
+}
+
+/// Whether the aliased version `alias` (`1.5.0`, `2.x-dev`) satisfies `spec`. An alias is
+/// an explicit promise, so a branch alias counts as the newest release of its branch
+/// rather than as a dev version.
+pub fn alias_satisfies(spec: &str, alias: &str) -> bool {
+    let normalized = normalize_alias(alias);
+    let release = normalized.strip_suffix("-dev").unwrap_or(&normalized);
+    let semver: Vec<&str> = release.split('.').take(3).collect();
+    satisfies(&actual_constraint(spec), &semver.join(".")) == Some(true)
+}
+
+/// A version on Composer's scale: the release of up to four numbers (`1.0.0.1`), then its
+/// stability and prerelease number, so `2.0.0-dev < 2.0.0-alpha1 < 2.0.0-RC1 < 2.0.0`.
+/// Numbered branches such as `2.x-dev` sit at the top of their range,
+/// `2.9999999.9999999.9999999-dev`.
+#[derive(Debug, Clone)]
+pub struct ComposerVersion {
+    release: [u64; 4],
+    /// The stability suffix as written, e.g. `beta2`
+    pre: String,
+    /// 0 for dev up to 4 for stable
+    rank: u8,
+    number: u64,
+}
+
+impl ComposerVersion {
+    /// Parse a tagged version or numbered branch; `None` for `dev-*` branches and text that
+    /// isn't a version
+    pub fn parse(version: &str) -> Option<Self> {
+        let version = version.trim();
+        if version.starts_with("dev-") {
+            return None;
         }
+        let normalized;
+        let version = if version.ends_with("-dev") {
+            normalized = normalize_alias(version);
+            &normalized
+        } else {
+            version
+        };
+        let partial = parse_partial(version).ok()?;
+        (!partial.wildcard && !partial.parts.is_empty()).then(|| partial.exact())
+    }
 
-        // Fallback: just use the first valid constraint
-        for part in &parts {
-            let trimmed = part.trim();
-            if !trimmed.is_empty() {
-                if let Ok(constraint) = parse_simple_constraint(trimmed) {
-                    return Ok(constraint);
-                }
-            }
+    /// Whether this is a stable release rather than a prerelease or branch
+    pub fn is_stable(&self) -> bool {
+        self.rank == 4
+    }
+
+    /// The closest semver version; a fourth number is dropped
+    pub fn to_semver(&self) -> Version {
+        let [major, minor, patch, _] = self.release;
+        let mut version = Version::new(major, minor, patch);
+        version.pre = semver::Prerelease::new(&self.pre).unwrap_or_default();
+        version
+    }
+
+    /// The lowest version of a release, below its prereleases: Composer's `1.2.0.0-dev`
+    /// bound for `>=1.2` and `<1.2`
+    fn lowest(release: [u64; 4]) -> Self {
+        Self {
+            release,
+            pre: String::new(),
+            rank: 0,
+            number: 0,
         }
     }
 
-    parse_simple_constraint(spec)
+    fn key(&self) -> ([u64; 4], u8, u64) {
+        (self.release, self.rank, self.number)
+    }
+
+    /// The comparator semver gets for `op` against this version
+    fn comparator(&self, op: Op) -> Option<String> {
+        let symbol = match op {
+            Op::Eq => "=",
+            Op::Lt => "<",
+            Op::Le => "<=",
+            Op::Gt => ">",
+            Op::Ge => ">=",
+            // semver has no `!=`
+            Op::Ne => return None,
+        };
+        Some(format!("{symbol}{}", self.to_semver()))
+    }
 }
 
-/// Score constraint permissiveness (higher = more permissive)
-fn score_constraint_permissiveness(constraint: &str) -> i32 {
-    // Prefer constraints that cover larger version ranges
-    if constraint.starts_with(">=") && !constraint.contains('<') {
-        return 100; // Very permissive (>=X.0.0)
+impl PartialEq for ComposerVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
     }
-    if constraint.starts_with('^') {
-        if let Some(version_part) = constraint.strip_prefix('^') {
-            if let Ok(major) = version_part.split('.').next().unwrap_or("0").parse::<u32>() {
-                return 50 + major as i32; // Higher major versions get higher scores
-            }
+}
+
+impl Eq for ComposerVersion {}
+
+impl PartialOrd for ComposerVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ComposerVersion {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+/// A version as written in a constraint: `1.2` has two parts, `1.2.*` ends in a wildcard
+struct Partial {
+    parts: Vec<u64>,
+    wildcard: bool,
+    /// Rank and number of a stability suffix such as `-beta2`
+    stability: Option<(u8, u64)>,
+    pre: String,
+}
+
+impl Partial {
+    fn release(&self) -> [u64; 4] {
+        let mut release = [0; 4];
+        for (slot, part) in release.iter_mut().zip(&self.parts) {
+            *slot = *part;
         }
-        return 50; // Caret constraints are generally permissive
+        release
     }
-    if constraint.starts_with('~') {
-        return 30; // Tilde constraints are less permissive
+
+    /// The release bumped at index `at`, e.g. `2.0.0.0` for `1.4` at 0
+    fn bumped(&self, at: usize) -> [u64; 4] {
+        let mut release = self.release();
+        let at = at.min(3);
+        release[at] = release[at].saturating_add(1);
+        for part in release.iter_mut().skip(at + 1) {
+            *part = 0;
+        }
+        release
     }
-    if constraint.starts_with('=') {
-        return 10; // Exact constraints are least permissive
+
+    /// Exactly this version, stable unless it has a stability suffix
+    fn exact(&self) -> ComposerVersion {
+        let (rank, number) = self.stability.unwrap_or((4, 0));
+        ComposerVersion {
+            release: self.release(),
+            pre: self.pre.clone(),
+            rank,
+            number,
+        }
     }
-    if constraint.starts_with(">=") && constraint.contains('<') {
-        return 40; // Range constraints
+
+    /// The lower end of a range starting here: below every prerelease of the release,
+    /// unless the constraint names a prerelease itself
+    fn lower_bound(&self) -> ComposerVersion {
+        match self.stability {
+            Some(_) => self.exact(),
+            None => ComposerVersion::lowest(self.release()),
+        }
     }
-    20 // Default score
 }
 
-fn parse_simple_constraint(spec: &str) -> Result<VersionReq> {
-    let spec = spec.trim();
+/// Parse a version of a constraint: up to four numbers with an optional `v` prefix,
+/// wildcard (`*`, `x`) and stability suffix (`-beta2`, `RC1`, `-dev`)
+fn parse_partial(text: &str) -> Result<Partial> {
+    let text = text.trim();
+    let text = text.strip_prefix(['v', 'V']).unwrap_or(text);
+    // Build metadata doesn't order versions
+    let text = text.split('+').next().unwrap_or(text);
+    let end = text
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | '*' | 'x' | 'X')))
+        .unwrap_or(text.len());
+    let (numbers, suffix) = text.split_at(end);
+    let numbers = numbers.strip_suffix('.').unwrap_or(numbers);
 
-    // Handle dev versions
-    if spec.starts_with("dev-") {
-        return Ok(VersionReq::parse(">=999.0.0-dev")?);
+    let mut parts = Vec::new();
+    let mut wildcard = false;
+    for segment in numbers.split('.') {
+        if wildcard {
+            return Err(anyhow!(
+                "Invalid version '{text}': nothing may follow a wildcard"
+            ));
+        }
+        match segment {
+            "*" | "x" | "X" => wildcard = true,
+            _ => parts.push(
+                segment
+                    .parse::<u64>()
+                    .map_err(|_| anyhow!("Invalid version '{text}'"))?,
+            ),
+        }
+    }
+    if parts.len() > 4 {
+        return Err(anyhow!("Invalid version '{text}': too many parts"));
     }
 
-    // Handle caret, tilde, and comparison operators
-    if spec.starts_with('^')
-        || spec.starts_with('~')
-        || spec.starts_with(">=")
-        || spec.starts_with("<=")
-        || spec.starts_with('>')
-        || spec.starts_with('<')
-    {
-        let normalized = normalize_version_in_constraint(spec)?;
-        return Ok(VersionReq::parse(&normalized)?);
-    }
-
-    // Handle ranges like "1.0.0 - 2.0.0"
-    if spec.contains(" - ") {
-        let parts: Vec<&str> = spec.split(" - ").collect();
-        if parts.len() == 2 {
-            let start = normalize_semver_string(parts[0].trim())?;
-            let end = normalize_semver_string(parts[1].trim())?;
-            return Ok(VersionReq::parse(&format!(">={start}, <={end}"))?);
-        }
-    }
-
-    // Handle comma-separated constraints (AND)
-    if spec.contains(',') {
-        return Ok(VersionReq::parse(spec)?);
-    }
-
-    // Treat as exact version
-    let normalized = normalize_semver_string(spec)?;
-    if Version::parse(&normalized).is_ok() {
-        return Ok(VersionReq::parse(&format!("={normalized}"))?);
-    }
-
-    // Last resort
-    Ok(VersionReq::parse(&normalized).unwrap_or(VersionReq::STAR))
-}
-
-fn normalize_version_in_constraint(constraint: &str) -> Result<String> {
-    if let Some(version_part) = constraint.strip_prefix('^') {
-        let normalized = normalize_semver_string(version_part)?;
-        Ok(format!("^{normalized}"))
-    } else if let Some(version_part) = constraint.strip_prefix('~') {
-        let normalized = normalize_semver_string(version_part)?;
-        Ok(format!("~{normalized}"))
-    } else if let Some(version_part) = constraint.strip_prefix(">=") {
-        let normalized = normalize_semver_string(version_part.trim())?;
-        Ok(format!(">={normalized}"))
-    } else if let Some(version_part) = constraint.strip_prefix("<=") {
-        let normalized = normalize_semver_string(version_part.trim())?;
-        Ok(format!("<={normalized}"))
-    } else if let Some(version_part) = constraint.strip_prefix('>') {
-        let normalized = normalize_semver_string(version_part.trim())?;
-        Ok(format!(">{normalized}"))
-    } else if let Some(version_part) = constraint.strip_prefix('<') {
-        let normalized = normalize_semver_string(version_part.trim())?;
-        Ok(format!("<{normalized}"))
+    let pre = suffix.trim_start_matches(['-', '.', '_']);
+    let stability = if pre.is_empty() {
+        None
     } else {
-        Ok(constraint.to_string())
+        let lower = pre.to_ascii_lowercase();
+        let (rank, rest) = [
+            ("alpha", 1),
+            ("beta", 2),
+            ("patch", 4),
+            ("dev", 0),
+            ("rc", 3),
+            ("pl", 4),
+            ("a", 1),
+            ("b", 2),
+            ("p", 4),
+        ]
+        .iter()
+        .find_map(|(name, rank)| lower.strip_prefix(name).map(|rest| (*rank, rest)))
+        .ok_or_else(|| anyhow!("Invalid version '{text}': unknown suffix '{pre}'"))?;
+        let rest = rest.trim_start_matches(['-', '.', '_']);
+        let number = if rest.is_empty() {
+            0
+        } else {
+            rest.parse::<u64>()
+                .map_err(|_| anyhow!("Invalid version '{text}': unknown suffix '{pre}'"))?
+        };
+        Some((rank, number))
+    };
+    Ok(Partial {
+        parts,
+        wildcard,
+        stability,
+        pre: pre.to_string(),
+    })
+}
+
+/// How a condition compares versions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// One condition of a constraint
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Term {
+    Compare(Op, ComposerVersion),
+    /// A `dev-*` branch, matched by name
+    Branch(String),
+}
+
+impl Term {
+    /// Whether the condition holds for the branch `branch` or the version `version`
+    fn holds(&self, branch: Option<&str>, version: Option<&ComposerVersion>) -> bool {
+        match (self, version) {
+            (Term::Branch(name), _) => branch == Some(name.as_str()),
+            (Term::Compare(op, bound), Some(version)) => match op {
+                Op::Eq => version == bound,
+                Op::Ne => version != bound,
+                Op::Lt => version < bound,
+                Op::Le => version <= bound,
+                Op::Gt => version > bound,
+                Op::Ge => version >= bound,
+            },
+            (Term::Compare(..), None) => false,
+        }
     }
 }
 
-/// Normalize a version string to be semver-compatible
-fn normalize_semver_string(s: &str) -> Result<String> {
-    let s = s.trim().strip_prefix('v').unwrap_or(s.trim());
+/// A Composer version constraint: any of its `||` alternatives, each a list of conditions
+/// that must all hold. Ranges follow Composer rather than semver: `~1.2` is `<2.0`,
+/// `1.0 - 2.1` is `<2.2`, `1.0.*` and `2.x` are wildcards, and a bare `1.2` is exact.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Constraint {
+    alternatives: Vec<Vec<Term>>,
+}
+
+impl Constraint {
+    /// Parse `spec`, ignoring inline aliases and stability flags
+    /// # Errors
+    /// Returns an error if any part of the constraint is not a valid condition
+    pub fn parse(spec: &str) -> Result<Self> {
+        let spec = actual_constraint(spec);
+        let mut alternatives = Vec::new();
+        for alternative in spec.split('|').map(str::trim).filter(|a| !a.is_empty()) {
+            let mut terms = Vec::new();
+            for part in constraint_parts(alternative) {
+                terms.extend(parse_term(&part)?);
+            }
+            alternatives.push(terms);
+        }
+        if alternatives.is_empty() {
+            alternatives.push(Vec::new());
+        }
+        Ok(Self { alternatives })
+    }
 
-    // Handle stability suffixes
-    let (version_part, stability_suffix) = if let Some(idx) = s.find('-') {
-        let (v, suffix) = s.split_at(idx);
-        (v, Some(suffix))
-    } else {
-        (s, None)
-    };
+    /// Whether `version` (`1.2.3`, `v2.0.0-RC1`, `2.x-dev`, `dev-main`) satisfies the
+    /// constraint; `None` if it isn't a version
+    pub fn matches(&self, version: &str) -> Option<bool> {
+        let version = version.trim();
+        let branch = version.strip_prefix("dev-");
+        let parsed = ComposerVersion::parse(version);
+        if branch.is_none() && parsed.is_none() {
+            return None;
+        }
+        Some(
+            self.alternatives
+                .iter()
+                .any(|terms| terms.iter().all(|term| term.holds(branch, parsed.as_ref()))),
+        )
+    }
 
-    // Split and validate version parts
-    let parts: Vec<&str> = version_part.split('.').collect();
-    if parts.is_empty() {
-        return Err(anyhow::anyhow!("Invalid version: empty"));
+    /// Whether a parsed version satisfies the constraint
+    pub fn matches_version(&self, version: &ComposerVersion) -> bool {
+        self.alternatives
+            .iter()
+            .any(|terms| terms.iter().all(|term| term.holds(None, Some(version))))
     }
 
-    let major = parts.first().unwrap_or(&"0");
-    let minor = parts.get(1).unwrap_or(&"0");
-    let patch = parts.get(2).unwrap_or(&"0");
+    /// The constraint both `self` and `other` allow, e.g. `^1.0 || ^2.0` and `<1.5` give
+    /// `^1.0, <1.5 || ^2.0, <1.5`
+    pub fn intersect(&self, other: &Constraint) -> Constraint {
+        let alternatives = self
+            .alternatives
+            .iter()
+            .flat_map(|left| {
+                other
+                    .alternatives
+                    .iter()
+                    .map(move |right| left.iter().chain(right).cloned().collect())
+            })
+            .collect();
+        Constraint { alternatives }
+    }
 
-    // Validate and clean each part
-    let clean_part = |part: &str| -> Result<String> {
-        if part.chars().all(char::is_numeric) && !part.is_empty() {
-            Ok(part.parse::<u32>().unwrap_or(0).to_string())
-        } else if part == "*" {
-            Ok("0".to_string())
+    /// The first alternative as a semver requirement. `!=` conditions are dropped, since
+    /// semver cannot express them.
+    fn to_version_req(&self) -> Result<VersionReq> {
+        let terms = self.alternatives.first().map_or(&[][..], Vec::as_slice);
+        let mut comparators = Vec::new();
+        for term in terms {
+            match term {
+                Term::Branch(_) => comparators.push(">=999.0.0-dev".to_string()),
+                Term::Compare(op, bound) => comparators.extend(bound.comparator(*op)),
+            }
+        }
+        if comparators.is_empty() {
+            return Ok(VersionReq::STAR);
+        }
+        Ok(VersionReq::parse(&comparators.join(", "))?)
+    }
+}
+
+/// Parse one condition: an operator with a version, a caret, tilde, wildcard or hyphen
+/// range, an exact version, or a branch
+fn parse_term(text: &str) -> Result<Vec<Term>> {
+    let text = text.trim();
+    if let Some(branch) = text.strip_prefix("dev-") {
+        let branch = branch.split('#').next().unwrap_or(branch);
+        return Ok(vec![Term::Branch(branch.to_string())]);
+    }
+    if let Some((from, to)) = text.split_once(" - ") {
+        let from = parse_partial(from)?;
+        let to = parse_partial(to)?;
+        // A partial upper end covers its whole range: `1.0 - 2.1` is `<2.2`
+        let upper = if to.wildcard || to.parts.len() < 3 {
+            let last = to.parts.len().saturating_sub(1);
+            Term::Compare(Op::Lt, ComposerVersion::lowest(to.bumped(last)))
         } else {
-            Err(anyhow::anyhow!("Invalid version part: {}", part))
+            Term::Compare(Op::Le, to.exact())
+        };
+        return Ok(vec![Term::Compare(Op::Ge, from.lower_bound()), upper]);
+    }
+
+    const OPERATORS: [(&str, Option<Op>); 10] = [
+        (">=", Some(Op::Ge)),
+        ("<=", Some(Op::Le)),
+        ("<>", Some(Op::Ne)),
+        ("!=", Some(Op::Ne)),
+        ("==", Some(Op::Eq)),
+        (">", Some(Op::Gt)),
+        ("<", Some(Op::Lt)),
+        ("=", Some(Op::Eq)),
+        ("^", None),
+        ("~", None),
+    ];
+    let operator = OPERATORS
+        .iter()
+        .find(|(symbol, _)| text.starts_with(symbol));
+    let Some((symbol, op)) = operator else {
+        // Numbered branches are single versions, e.g. `2.x-dev`
+        if text.ends_with("-dev") {
+            let version =
+                ComposerVersion::parse(text).ok_or_else(|| anyhow!("Invalid version '{text}'"))?;
+            return Ok(vec![Term::Compare(Op::Eq, version)]);
         }
+        let version = parse_partial(text)?;
+        if !version.wildcard {
+            if version.parts.is_empty() {
+                return Err(anyhow!("Invalid version '{text}'"));
+            }
+            return Ok(vec![Term::Compare(Op::Eq, version.exact())]);
+        }
+        if version.parts.is_empty() {
+            return Ok(Vec::new());
+        }
+        let last = version.parts.len() - 1;
+        return Ok(vec![
+            Term::Compare(Op::Ge, version.lower_bound()),
+            Term::Compare(Op::Lt, ComposerVersion::lowest(version.bumped(last))),
+        ]);
+    };
+
+    let version = parse_partial(&text[symbol.len()..])?;
+    if version.parts.is_empty() {
+        return Err(anyhow!("Invalid constraint '{text}': missing version"));
+    }
+    let last = version.parts.len() - 1;
+    let Some(op) = op else {
+        let upper = if *symbol == "^" {
+            // `^1.2.3` allows everything up to the next major release, `^0.3` up to the
+            // next minor one and `^0.0.3` only patches
+            let at = version.parts.iter().position(|part| *part != 0);
+            version.bumped(at.unwrap_or(last).min(last))
+        } else {
+            // `~1.2` allows everything up to 2.0, `~1.2.3` up to 1.3
+            version.bumped(last.saturating_sub(1))
+        };
+        return Ok(vec![
+            Term::Compare(Op::Ge, version.lower_bound()),
+            Term::Compare(Op::Lt, ComposerVersion::lowest(upper)),
+        ]);
+    };
+    // `>=1.2` includes the prereleases of 1.2 and `<2.0` excludes those of 2.0
+    let bound = if matches!(op, Op::Ge | Op::Lt) {
+        version.lower_bound()
+    } else {
+        version.exact()
     };
+    Ok(vec![Term::Compare(*op, bound)])
+}
 
-    let major_clean = clean_part(major)?;
-    let minor_clean = clean_part(minor)?;
-    let patch_clean = clean_part(patch)?;
+/// Parse a constraint into a semver VersionReq. semver has no `||`, so the most
+/// permissive alternative is used; `satisfies` and `Constraint` evaluate all of them.
+pub fn parse_constraint(spec: &str) -> Result<VersionReq> {
+    let spec = actual_constraint(spec);
+    let mut best: Option<(i32, VersionReq)> = None;
+    for alternative in spec.split('|').map(str::trim).filter(|a| !a.is_empty()) {
+        let Ok(req) = Constraint::parse(alternative).and_then(|c| c.to_version_req()) else {
+            continue;
+        };
+        let score = score_constraint_permissiveness(alternative);
+        if best.as_ref().is_none_or(|(best, _)| score > *best) {
+            best = Some((score, req));
+        }
+    }
+    match best {
+        Some((_, req)) => Ok(req),
+        None => Constraint::parse(&spec)?.to_version_req(),
+    }
+}
 
-    let normalized = format!("{major_clean}.{minor_clean}.{patch_clean}");
+/// Whether `version` satisfies a full Composer constraint, evaluating every `||` branch
+/// and every `,`/space separated part of a branch. `None` if either cannot be parsed.
+pub fn satisfies(spec: &str, version: &str) -> Option<bool> {
+    Constraint::parse(spec).ok()?.matches(version)
+}
 
-    if let Some(suffix) = stability_suffix {
-        Ok(format!("{normalized}{suffix}"))
-    } else {
-        Ok(normalized)
+/// Split an AND constraint (`>=7.2, <8.0` or `>=7.2 <8.0`) into its parts, keeping
+/// `1.0 - 2.0` ranges and operators written apart from their version together
+fn constraint_parts(branch: &str) -> Vec<String> {
+    if branch.contains(" - ") {
+        return vec![branch.to_string()];
     }
+    let mut parts = Vec::new();
+    let mut pending = String::new();
+    for token in branch
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|t| !t.is_empty())
+    {
+        pending.push_str(token);
+        if !token
+            .chars()
+            .all(|c| matches!(c, '<' | '>' | '=' | '!' | '^' | '~'))
+        {
+            parts.push(std::mem::take(&mut pending));
+        }
+    }
+    // An operator without a version is left for the parser to reject
+    if !pending.is_empty() {
+        parts.push(pending);
+    }
+    parts
+}
+
+/// Score constraint permissiveness (higher = more permissive)
+fn score_constraint_permissiveness(constraint: &str) -> i32 {
+    // Prefer constraints that cover larger version ranges
+    if constraint.starts_with(">=") && !constraint.contains('<') {
+        return 100; // Very permissive (>=X.0.0)
+    }
+    if constraint.starts_with('^') {
+        if let Some(version_part) = constraint.strip_prefix('^')
+            && let Ok(major) = version_part.split('.').next().unwrap_or("0").parse::<u32>()
+        {
+            return 50 + major as i32; // Higher major versions get higher scores
+        }
+        return 50; // Caret constraints are generally permissive
+    }
+    if constraint.starts_with('~') {
+        return 30; // Tilde constraints are less permissive
+    }
+    if constraint.starts_with('=') {
+        return 10; // Exact constraints are least permissive
+    }
+    if constraint.starts_with(">=") && constraint.contains('<') {
+        return 40; // Range constraints
+    }
+    20 // Default score
 }
 
 #[cfg(test)]
@@ -216,10 +674,27 @@ mod tests {
     }
 
     #[test]
-    fn test_normalize_semver_string() {
-        assert_eq!(normalize_semver_string("1.2.3").unwrap(), "1.2.3");
-        assert_eq!(normalize_semver_string("v1.2.3").unwrap(), "1.2.3");
-        assert_eq!(normalize_semver_string("1.2").unwrap(), "1.2.0");
-        assert_eq!(normalize_semver_string("1").unwrap(), "1.0.0");
+    fn test_satisfies() {
+        assert_eq!(satisfies("^7.4 || ^8.0", "8.3"), Some(true));
+        assert_eq!(satisfies("^7.4 || ^8.0", "7.3"), Some(false));
+        assert_eq!(satisfies(">=7.2, <8.3", "8.3"), Some(false));
+        assert_eq!(satisfies(">= 7.2 < 8.3", "8.2"), Some(true));
+        assert_eq!(satisfies("~8.2.0", "8.3"), Some(false));
+        assert_eq!(satisfies("not a constraint", "8.3"), None);
+    }
+
+    #[test]
+    fn test_parse_partial() {
+        assert_eq!(parse_partial("1.2.3").unwrap().release(), [1, 2, 3, 0]);
+        assert_eq!(parse_partial("1.0.0.1").unwrap().release(), [1, 0, 0, 1]);
+        assert_eq!(parse_partial("v1.2").unwrap().parts, vec![1, 2]);
+        assert!(parse_partial("1.0.*").unwrap().wildcard);
+        assert_eq!(parse_partial("2.0.0-RC1").unwrap().stability, Some((3, 1)));
+        assert_eq!(
+            parse_partial("1.0.0beta.2").unwrap().stability,
+            Some((2, 2))
+        );
+        assert!(parse_partial("1.*.3").is_err());
+        assert!(parse_partial("1.0.0-foo").is_err());
     }
 }
@@ -0,0 +1,323 @@
+use lectern::output::{Level, Reporter};
+use lectern::{Lectern, LecternOptions};
+use std::sync::{Arc, Mutex};
+
+/// Collects messages instead of printing them
+#[derive(Default)]
+struct Capture(Mutex<Vec<(Level, String)>>);
+
+impl Reporter for Capture {
+    fn report(&self, level: Level, _package: Option<&str>, message: &str) {
+        self.0.lock().unwrap().push((level, message.to_string()));
+    }
+}
+
+fn project() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("composer.json"),
+        r#"{"name": "test/api", "require": {}}"#,
+    )
+    .unwrap();
+    dir
+}
+
+#[tokio::test]
+async fn test_install_returns_lock_and_reports_through_reporter() {
+    let dir = project();
+    let capture = Arc::new(Capture::default());
+    let report = Lectern::new(dir.path())
+        .with_reporter(capture.clone())
+        .install()
+        .await
+        .unwrap();
+
+    assert!(report.lock.packages.is_empty());
+    assert!(report.installed.is_empty());
+    assert!(dir.path().join("composer.lock").exists());
+    let messages = capture.0.lock().unwrap();
+    assert!(
+        messages
+            .iter()
+            .any(|(level, _)| *level == Level::Success || *level == Level::Step),
+        "{messages:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_dry_run_writes_nothing() {
+    let dir = project();
+    let report = Lectern::new(dir.path())
+        .with_options(LecternOptions {
+            dry_run: true,
+            ..LecternOptions::default()
+        })
+        .update()
+        .await
+        .unwrap();
+
+    assert!(report.lock.packages.is_empty());
+    assert!(!dir.path().join("composer.lock").exists());
+    assert!(!dir.path().join("vendor").exists());
+    assert!(!dir.path().join(".lectern").exists());
+}
+
+#[tokio::test]
+async fn test_outdated_needs_a_lock() {
+    let dir = project();
+    let err = Lectern::new(dir.path()).outdated().await.unwrap_err();
+    assert!(err.to_string().contains("composer.lock"));
+
+    Lectern::new(dir.path()).install().await.unwrap();
+    let outdated = Lectern::new(dir.path()).outdated().await.unwrap();
+    assert!(outdated.is_empty());
+}
+
+#[tokio::test]
+async fn test_reporter_scopes_are_per_task() {
+    use lectern::output::{with_package, with_reporter};
+    let outer = Arc::new(Capture::default());
+    let inner = Arc::new(Capture::default());
+    let other = Arc::new(Capture::default());
+
+    let outer_task = with_reporter(outer.clone(), async {
+        with_reporter(inner.clone(), async {
+            lectern::utils::print_info("api_test inner message");
+        })
+        .await;
+        // Spawned package tasks keep the reporter of the task that started them
+        tokio::spawn(with_package("acme/lib", async {
+            lectern::utils::print_info("api_test spawned message");
+        }))
+        .await
+        .unwrap();
+        lectern::utils::print_info("api_test outer message");
+    });
+    let other_task = with_reporter(other.clone(), async {
+        lectern::utils::print_info("api_test other message");
+    });
+    tokio::join!(outer_task, other_task);
+
+    let texts = |capture: &Capture| -> Vec<String> {
+        capture
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(_, m)| m.clone())
+            .collect()
+    };
+    assert_eq!(texts(&inner), ["api_test inner message"]);
+    assert_eq!(
+        texts(&outer),
+        ["api_test spawned message", "api_test outer message"]
+    );
+    assert_eq!(texts(&other), ["api_test other message"]);
+}
+
+#[tokio::test]
+async fn test_dry_run_reports_changes_against_the_current_lock() {
+    let dir = project();
+    let lock = serde_json::json!({
+        "content-hash": "",
+        "packages": [{"name": "acme/unused", "version": "1.2.0"}]
+    });
+    let lock_text = serde_json::to_string_pretty(&lock).unwrap();
+    std::fs::write(dir.path().join("composer.lock"), &lock_text).unwrap();
+
+    let report = Lectern::new(dir.path())
+        .with_options(LecternOptions {
+            dry_run: true,
+            ..LecternOptions::default()
+        })
+        .update()
+        .await
+        .unwrap();
+
+    assert_eq!(
+        report.changes,
+        vec![lectern::lock_diff::PackageChange::Remove {
+            name: "acme/unused".to_string(),
+            version: "1.2.0".to_string(),
+        }]
+    );
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("composer.lock")).unwrap(),
+        lock_text
+    );
+}
+
+#[tokio::test]
+async fn test_require_dry_run_resolves_but_writes_nothing() {
+    // Metadata is served from the in-memory cache, so no network is needed
+    lectern::cache::cache_set_meta(
+        "p2:lectern-dry-run/lib",
+        serde_json::json!([{"version": "1.2.0"}, {"version": "1.1.0"}]),
+    )
+    .await;
+    let dir = project();
+    let composer = std::fs::read_to_string(dir.path().join("composer.json")).unwrap();
+
+    let report = Lectern::new(dir.path())
+        .with_options(LecternOptions {
+            dry_run: true,
+            ..LecternOptions::default()
+        })
+        .require(&["lectern-dry-run/lib".to_string()])
+        .await
+        .unwrap();
+
+    let install = report.install.unwrap();
+    assert_eq!(
+        install.changes,
+        vec![lectern::lock_diff::PackageChange::Install {
+            name: "lectern-dry-run/lib".to_string(),
+            version: "1.2.0".to_string(),
+        }]
+    );
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("composer.json")).unwrap(),
+        composer
+    );
+    for path in ["composer.lock", "vendor", ".lectern"] {
+        assert!(!dir.path().join(path).exists(), "{path} was written");
+    }
+}
+
+#[tokio::test]
+async fn test_no_install_writes_the_lock_but_not_vendor() {
+    let dir = project();
+    let report = Lectern::new(dir.path())
+        .with_options(LecternOptions {
+            no_install: true,
+            ..LecternOptions::default()
+        })
+        .update()
+        .await
+        .unwrap();
+
+    assert!(report.installed.is_empty());
+    assert!(dir.path().join("composer.lock").exists());
+    assert!(!dir.path().join("vendor").exists());
+}
+
+#[test]
+fn test_update_lock_refreshes_the_hash_and_keeps_locked_versions() {
+    let dir = project();
+    let composer_path = dir.path().join("composer.json");
+    std::fs::write(
+        &composer_path,
+        r#"{"name": "test/api", "description": "Changed", "minimum-stability": "beta", "require": {"php": ">=8.1", "acme/lib": "^1.0"}}"#,
+    )
+    .unwrap();
+    let lock = serde_json::json!({
+        "content-hash": "stale",
+        "packages": [{"name": "acme/lib", "version": "1.2.0"}]
+    });
+    std::fs::write(
+        dir.path().join("composer.lock"),
+        serde_json::to_string(&lock).unwrap(),
+    )
+    .unwrap();
+
+    let lock = Lectern::new(dir.path()).update_lock().unwrap();
+    assert_eq!(
+        lock.content_hash,
+        lectern::io::read_composer_content_hash(&composer_path).unwrap()
+    );
+    assert_eq!(lock.minimum_stability, "beta");
+    assert_eq!(lock.platform.get("php").map(String::as_str), Some(">=8.1"));
+    assert_eq!(lock.packages.len(), 1);
+    assert_eq!(lock.packages[0].version, "1.2.0");
+    let written = lectern::io::read_lock(&dir.path().join("composer.lock")).unwrap();
+    assert_eq!(written.content_hash, lock.content_hash);
+
+    // A requirement the lock doesn't satisfy needs a real update
+    std::fs::write(
+        &composer_path,
+        r#"{"name": "test/api", "require": {"acme/lib": "^2.0"}}"#,
+    )
+    .unwrap();
+    let err = Lectern::new(dir.path()).update_lock().unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("acme/lib (^2.0) is locked at 1.2.0"),
+        "{err}"
+    );
+}
+
+#[tokio::test]
+async fn test_no_abandoned_refuses_abandoned_packages() {
+    lectern::cache::cache_set_meta(
+        "p2:lectern-abandoned/lib",
+        serde_json::json!([{"version": "1.0.0", "abandoned": "lectern-abandoned/successor"}]),
+    )
+    .await;
+    let dir = project();
+    let composer = std::fs::read_to_string(dir.path().join("composer.json")).unwrap();
+
+    let err = Lectern::new(dir.path())
+        .with_options(LecternOptions {
+            no_abandoned: true,
+            ..LecternOptions::default()
+        })
+        .require(&["lectern-abandoned/lib".to_string()])
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("lectern-abandoned/lib"), "{err}");
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("composer.json")).unwrap(),
+        composer
+    );
+    assert!(!dir.path().join("composer.lock").exists());
+
+    // Without it, the package is locked with the abandonment recorded
+    let report = Lectern::new(dir.path())
+        .with_options(LecternOptions {
+            dry_run: true,
+            ..LecternOptions::default()
+        })
+        .require(&["lectern-abandoned/lib".to_string()])
+        .await
+        .unwrap();
+    let lock = report.install.unwrap().lock;
+    assert_eq!(
+        lock.packages[0]
+            .abandonment()
+            .unwrap()
+            .replacement
+            .as_deref(),
+        Some("lectern-abandoned/successor")
+    );
+}
+
+#[tokio::test]
+async fn test_install_notes_suggestions_of_new_packages() {
+    lectern::cache::cache_set_meta(
+        "p2:lectern-suggest/lib",
+        serde_json::json!([{
+            "version": "1.0.0",
+            "suggest": {"lectern-suggest/extra": "For faster parsing"}
+        }]),
+    )
+    .await;
+    let dir = project();
+    let capture = Arc::new(Capture::default());
+    Lectern::new(dir.path())
+        .with_options(LecternOptions {
+            no_install: true,
+            ..LecternOptions::default()
+        })
+        .with_reporter(capture.clone())
+        .require(&["lectern-suggest/lib".to_string()])
+        .await
+        .unwrap();
+
+    let messages = capture.0.lock().unwrap();
+    assert!(
+        messages.iter().any(|(_, message)| message.contains(
+            "1 package suggestion(s) were added by new dependencies, use 'lectern suggests'"
+        )),
+        "{messages:?}"
+    );
+}
@@ -0,0 +1,55 @@
+use lectern::commands::audit::find_vulnerabilities;
+use lectern::models::model::Lock;
+use lectern::resolver::SecurityAdvisory;
+use serde_json::json;
+use std::collections::BTreeMap;
+
+fn advisory(package: &str, affected: &str) -> SecurityAdvisory {
+    serde_json::from_value(json!({
+        "advisoryId": format!("PKSA-{package}"),
+        "packageName": package,
+        "title": "Remote code execution",
+        "affectedVersions": affected
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_find_vulnerabilities_matches_locked_versions() {
+    let lock: Lock = serde_json::from_value(json!({
+        "content-hash": "",
+        "packages": [
+            {"name": "acme/patched", "version": "1.2.3"},
+            {"name": "acme/vulnerable", "version": "2.0.1"}
+        ],
+        "packages-dev": [{"name": "acme/dev-tool", "version": "0.4.0"}]
+    }))
+    .unwrap();
+    let advisories = BTreeMap::from([
+        (
+            "acme/patched".to_string(),
+            vec![advisory("acme/patched", ">=1.0.0,<1.2.3")],
+        ),
+        (
+            "acme/vulnerable".to_string(),
+            vec![advisory("acme/vulnerable", ">=2.0.0,<2.0.4")],
+        ),
+        // Ranges that can't be parsed are reported rather than dropped
+        (
+            "acme/dev-tool".to_string(),
+            vec![advisory("acme/dev-tool", "not a constraint (")],
+        ),
+    ]);
+
+    let found: Vec<(String, String)> = find_vulnerabilities(&lock, &advisories)
+        .into_iter()
+        .map(|found| (found.package, found.version))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            ("acme/dev-tool".to_string(), "0.4.0".to_string()),
+            ("acme/vulnerable".to_string(), "2.0.1".to_string()),
+        ]
+    );
+}
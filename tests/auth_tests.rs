@@ -0,0 +1,110 @@
+use lectern::auth::{AuthConfig, HttpCredentials, url_host};
+use std::fs;
+use tempfile::TempDir;
+
+fn creds(username: &str, password: &str) -> Option<HttpCredentials> {
+    Some(HttpCredentials {
+        username: username.to_string(),
+        password: password.to_string(),
+    })
+}
+
+#[test]
+fn test_url_host() {
+    assert_eq!(
+        url_host("https://github.com/acme/private.git").as_deref(),
+        Some("github.com")
+    );
+    assert_eq!(
+        url_host("https://user:pw@GitLab.example.com:8443/group/repo.git").as_deref(),
+        Some("gitlab.example.com")
+    );
+    assert_eq!(url_host("git@github.com:acme/private.git"), None);
+    assert_eq!(url_host("/srv/git/repo"), None);
+}
+
+#[test]
+fn test_credentials_for_each_provider() {
+    let mut auth = AuthConfig::default();
+    auth.merge_json(&serde_json::json!({
+        "github-oauth": {"github.com": "ghp_token"},
+        "gitlab-oauth": {"gitlab.com": "glo_token"},
+        "gitlab-token": {"gitlab.example.com": {"username": "deployer", "token": "glpat"}},
+        "bearer": {"bitbucket.org": "bb_token"},
+        "http-basic": {"git.example.org": {"username": "me", "password": "secret"}}
+    }));
+
+    assert_eq!(
+        auth.credentials_for("https://github.com/acme/private.git"),
+        creds("ghp_token", "x-oauth-basic")
+    );
+    assert_eq!(
+        auth.credentials_for("https://gitlab.com/acme/private.git"),
+        creds("oauth2", "glo_token")
+    );
+    assert_eq!(
+        auth.credentials_for("https://gitlab.example.com/acme/private.git"),
+        creds("deployer", "glpat")
+    );
+    assert_eq!(
+        auth.credentials_for("https://bitbucket.org/acme/private.git"),
+        creds("x-token-auth", "bb_token")
+    );
+    assert_eq!(
+        auth.credentials_for("https://git.example.org/private.git"),
+        creds("me", "secret")
+    );
+    assert_eq!(auth.credentials_for("https://example.net/repo.git"), None);
+    // Never sent in clear
+    assert_eq!(
+        auth.credentials_for("http://github.com/acme/private.git"),
+        None
+    );
+    // SSH remotes authenticate with keys instead
+    assert_eq!(
+        auth.credentials_for("git@github.com:acme/private.git"),
+        None
+    );
+}
+
+#[test]
+fn test_auth_sources_precedence() {
+    let home = TempDir::new().unwrap();
+    let global = home.path().join(".config/composer");
+    fs::create_dir_all(&global).unwrap();
+    fs::write(
+        global.join("auth.json"),
+        r#"{"github-oauth": {"github.com": "global"}, "gitlab-oauth": {"gitlab.com": "global"}}"#,
+    )
+    .unwrap();
+
+    let project = TempDir::new().unwrap();
+    fs::write(
+        project.path().join("composer.json"),
+        r#"{"config": {"gitlab-oauth": {"gitlab.com": "composer-json"}, "bearer": {"bitbucket.org": "composer-json"}}}"#,
+    )
+    .unwrap();
+    fs::write(
+        project.path().join("auth.json"),
+        r#"{"bearer": {"bitbucket.org": "project"}}"#,
+    )
+    .unwrap();
+
+    let env = |name: &str| match name {
+        "COMPOSER_AUTH" => Some(r#"{"github-oauth": {"github.com": "env"}}"#.to_string()),
+        _ => None,
+    };
+    let auth = AuthConfig::from_env_with(env, Some(home.path()), Some(project.path()));
+    assert_eq!(auth.github_oauth["github.com"], "env");
+    assert_eq!(auth.gitlab_oauth["gitlab.com"], "composer-json");
+    assert_eq!(auth.bearer["bitbucket.org"], "project");
+
+    // COMPOSER_HOME replaces the default global locations
+    let composer_home = TempDir::new().unwrap();
+    let env = |name: &str| match name {
+        "COMPOSER_HOME" => Some(composer_home.path().to_string_lossy().to_string()),
+        _ => None,
+    };
+    let auth = AuthConfig::from_env_with(env, Some(home.path()), None);
+    assert!(auth.github_oauth.is_empty());
+}
@@ -36,6 +36,7 @@ async fn test_write_autoload_files_basic() {
         repositories: None,
         config: None,
         scripts: None,
+        scripts_descriptions: None,
         extra: None,
         minimum_stability: None,
         prefer_stable: None,
@@ -58,7 +59,7 @@ async fn test_write_autoload_files_with_psr4() {
     let temp_path = temp_dir.path();
     
     let mut psr4_map = BTreeMap::new();
-    psr4_map.insert("App\\".to_string(), "src/".to_string());
+    psr4_map.insert("App\\".to_string(), "src/".into());
     
     let composer = ComposerJson {
         name: Some("test/psr4".to_string()),
@@ -66,8 +67,10 @@ async fn test_write_autoload_files_with_psr4() {
         require_dev: BTreeMap::new(),
         autoload: Some(lectern::models::model::Autoload {
             psr4: psr4_map,
+            psr0: BTreeMap::new(),
             classmap: vec![],
             files: vec![],
+            exclude_from_classmap: vec![],
         }),
         autoload_dev: None,
         description: None,
@@ -89,6 +92,7 @@ async fn test_write_autoload_files_with_psr4() {
         repositories: None,
         config: None,
         scripts: None,
+        scripts_descriptions: None,
         extra: None,
         minimum_stability: None,
         prefer_stable: None,
@@ -138,6 +142,7 @@ async fn test_write_autoload_files_with_packages() {
         repositories: None,
         config: None,
         scripts: None,
+        scripts_descriptions: None,
         extra: None,
         minimum_stability: None,
         prefer_stable: None,
@@ -169,15 +174,258 @@ fn test_autoload_structure() {
     use lectern::models::model::Autoload;
     
     let mut psr4 = BTreeMap::new();
-    psr4.insert("Test\\".to_string(), "tests/".to_string());
+    psr4.insert("Test\\".to_string(), "tests/".into());
     
     let autoload = Autoload {
         psr4,
+        psr0: BTreeMap::new(),
         classmap: vec!["src/helpers.php".to_string()],
         files: vec!["src/functions.php".to_string()],
+        exclude_from_classmap: vec![],
     };
     
     assert_eq!(autoload.psr4.len(), 1);
     assert_eq!(autoload.classmap.len(), 1);
     assert_eq!(autoload.files.len(), 1);
 }
+
+#[tokio::test]
+async fn test_classmap_honours_exclusions_and_target_dir() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    for file in [
+        "src/Service.php",
+        "src/Tests/Fixtures/Broken.php",
+        "src/Legacy/Old.php",
+        "lib/Helper.php",
+        "vendor/acme/legacy/Acme/Legacy/Util.php",
+        "vendor/acme/legacy/Acme/Legacy/Tests/UtilTest.php",
+    ] {
+        let path = root.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let class = path.file_stem().unwrap().to_string_lossy().into_owned();
+        fs::write(path, format!("<?php\nclass {class} {{}}\n")).unwrap();
+    }
+    fs::write(
+        root.join("vendor/acme/legacy/composer.json"),
+        r#"{"name": "acme/legacy", "target-dir": "Acme/Legacy",
+            "autoload": {"classmap": [""], "exclude-from-classmap": ["Tests/"]}}"#,
+    )
+    .unwrap();
+
+    // The root's paths include its own target-dir, which isn't on disk
+    let composer: ComposerJson = serde_json::from_str(
+        r#"{"name": "acme/app", "target-dir": "Acme/App",
+            "autoload": {"classmap": ["src/", "Acme/App/lib"],
+                         "exclude-from-classmap": ["src/**/Fixtures", "src/Leg*"]}}"#,
+    )
+    .unwrap();
+    let installed = vec![InstalledPackage {
+        name: "acme/legacy".to_string(),
+        version: "1.0.0".to_string(),
+        path: Utf8PathBuf::from_path_buf(root.join("vendor/acme/legacy")).unwrap(),
+    }];
+    write_autoload_files(root, &composer, &installed)
+        .await
+        .unwrap();
+
+    let classmap = fs::read_to_string(root.join("vendor/composer/autoload_classmap.php")).unwrap();
+    assert!(classmap.contains("src/Service.php"));
+    assert!(classmap.contains("lib/Helper.php"));
+    assert!(classmap.contains("Acme/Legacy/Util.php"));
+    assert!(!classmap.contains("Broken.php"));
+    assert!(!classmap.contains("Old.php"));
+    assert!(!classmap.contains("UtilTest.php"));
+}
+
+#[tokio::test]
+async fn test_static_autoloader_and_apcu_prefix() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    let mut composer: ComposerJson =
+        serde_json::from_str(r#"{"name": "acme/app", "autoload": {"classmap": ["lib/"]}}"#)
+            .unwrap();
+    composer.autoload.as_mut().unwrap().psr4 =
+        BTreeMap::from([("App\\".to_string(), "src/".into())]);
+    fs::create_dir_all(root.join("lib")).unwrap();
+    fs::write(
+        root.join("lib/helpers.php"),
+        "<?php\nnamespace App\\Support;\nfinal class Helpers {}\n",
+    )
+    .unwrap();
+
+    write_autoload_files(root, &composer, &[]).await.unwrap();
+    let static_php = fs::read_to_string(root.join("vendor/composer/autoload_static.php")).unwrap();
+    assert!(static_php.contains("'A' => [\n            'App\\\\' => 4,"));
+    assert!(static_php.contains("'App\\\\' => [__DIR__ . '/../..' . '/src']"));
+    assert!(static_php
+        .contains("'App\\\\Support\\\\Helpers' => __DIR__ . '/../..' . '/lib/helpers.php'"));
+    let autoload = fs::read_to_string(root.join("vendor/autoload.php")).unwrap();
+    assert!(autoload.contains("$apcuPrefix = null;"));
+
+    let options = AutoloadOptions {
+        apcu: true,
+        ..AutoloadOptions::default()
+    };
+    write_autoload_files_with_options(root, &composer, &[], &options)
+        .await
+        .unwrap();
+    let autoload = fs::read_to_string(root.join("vendor/autoload.php")).unwrap();
+    assert!(autoload.contains("$apcuPrefix = 'lectern-"));
+}
+
+#[tokio::test]
+async fn test_autoload_dev_is_dumped_unless_no_dev() {
+    use lectern::autoload::lock_packages;
+
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    let composer: ComposerJson = serde_json::from_str(
+        r#"{"name": "acme/app",
+            "autoload": {"psr-4": {"App\\": ["src/", "lib/"]}},
+            "autoload-dev": {"psr-4": {"App\\Tests\\": "tests/"}}}"#,
+    )
+    .unwrap();
+    let lock: lectern::models::model::Lock = serde_json::from_value(serde_json::json!({
+        "content-hash": "",
+        "packages": [{"name": "acme/http", "version": "v1.2"}],
+        "packages-dev": [{"name": "acme/faker", "version": "2.0.0-RC1"}],
+    }))
+    .unwrap();
+    for (name, namespace) in [("acme/http", "Http"), ("acme/faker", "Faker")] {
+        let dir = root.join("vendor").join(name);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("composer.json"),
+            format!(r#"{{"autoload": {{"psr-4": {{"Acme\\{namespace}\\": "src/"}}}}}}"#),
+        )
+        .unwrap();
+    }
+    let installed: Vec<InstalledPackage> = lock_packages(&lock, false)
+        .into_iter()
+        .map(|mut pkg| {
+            pkg.path = Utf8PathBuf::from_path_buf(root.join(pkg.path.as_std_path())).unwrap();
+            pkg
+        })
+        .collect();
+
+    let options = AutoloadOptions::for_lock(&lock, false);
+    write_autoload_files_with_options(root, &composer, &installed, &options)
+        .await
+        .unwrap();
+    let psr4 = fs::read_to_string(root.join("vendor/composer/autoload_psr4.php")).unwrap();
+    assert!(
+        psr4.contains("'App\\\\' => [__DIR__ . '/../..' . '/src', __DIR__ . '/../..' . '/lib']")
+    );
+    assert!(psr4.contains("'App\\\\Tests\\\\'"));
+    assert!(psr4.contains("'Acme\\\\Faker\\\\'"));
+    let installed_php = fs::read_to_string(root.join("vendor/composer/installed.php")).unwrap();
+    assert!(installed_php.contains("'dev' => true,"));
+    assert!(installed_php.contains("'version' => '1.2.0.0',"));
+    assert!(installed_php.contains("'version' => '2.0.0.0-RC1',"));
+    assert!(installed_php.contains("'dev_requirement' => true,"));
+
+    // --no-dev drops the root's autoload-dev and the dev packages
+    let installed: Vec<InstalledPackage> = installed
+        .into_iter()
+        .filter(|pkg| pkg.name != "acme/faker")
+        .collect();
+    let options = AutoloadOptions::for_lock(&lock, true);
+    write_autoload_files_with_options(root, &composer, &installed, &options)
+        .await
+        .unwrap();
+    let psr4 = fs::read_to_string(root.join("vendor/composer/autoload_psr4.php")).unwrap();
+    assert!(psr4.contains("'Acme\\\\Http\\\\'"));
+    assert!(!psr4.contains("Tests"));
+    assert!(!psr4.contains("Faker"));
+    let installed_php = fs::read_to_string(root.join("vendor/composer/installed.php")).unwrap();
+    assert!(installed_php.contains("'dev' => false,"));
+    assert!(!installed_php.contains("acme/faker"));
+    assert_eq!(lock_packages(&lock, true).len(), 1);
+}
+
+#[tokio::test]
+async fn test_autoload_files_are_only_rewritten_when_inputs_change() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    let mut composer: ComposerJson =
+        serde_json::from_str(r#"{"name": "acme/app", "autoload": {"psr-4": {"App\\": "src/"}}}"#)
+            .unwrap();
+    let psr4_path = root.join("vendor/composer/autoload_psr4.php");
+
+    write_autoload_files(root, &composer, &[]).await.unwrap();
+    assert!(root.join("vendor/composer/autoload.stamp").exists());
+    let composer_dir: Vec<String> = fs::read_dir(root.join("vendor/composer"))
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    assert!(
+        !composer_dir.iter().any(|name| name.ends_with(".tmp")),
+        "{composer_dir:?}"
+    );
+
+    // Unchanged inputs leave the files alone
+    fs::write(&psr4_path, "<?php return [];\n").unwrap();
+    write_autoload_files(root, &composer, &[]).await.unwrap();
+    assert_eq!(
+        fs::read_to_string(&psr4_path).unwrap(),
+        "<?php return [];\n"
+    );
+
+    // Forcing, or a change to the root's autoload rules, regenerates them
+    let options = AutoloadOptions {
+        force: true,
+        ..AutoloadOptions::default()
+    };
+    write_autoload_files_with_options(root, &composer, &[], &options)
+        .await
+        .unwrap();
+    assert!(
+        fs::read_to_string(&psr4_path)
+            .unwrap()
+            .contains("'App\\\\'")
+    );
+
+    composer.autoload.as_mut().unwrap().psr4 =
+        BTreeMap::from([("Acme\\".to_string(), "lib/".into())]);
+    write_autoload_files(root, &composer, &[]).await.unwrap();
+    assert!(
+        fs::read_to_string(&psr4_path)
+            .unwrap()
+            .contains("'Acme\\\\'")
+    );
+
+    // A branch package at a new commit keeps its version, but the lock changed
+    let lock_at = |reference: &str| -> lectern::models::model::Lock {
+        serde_json::from_value(serde_json::json!({
+            "content-hash": "abc",
+            "packages": [{
+                "name": "acme/lib",
+                "version": "dev-main",
+                "source": {"type": "git", "url": "https://example.com/lib.git", "reference": reference}
+            }]
+        }))
+        .unwrap()
+    };
+    let options = AutoloadOptions::for_lock(&lock_at("1111111"), false);
+    write_autoload_files_with_options(root, &composer, &[], &options)
+        .await
+        .unwrap();
+    fs::write(&psr4_path, "<?php return [];\n").unwrap();
+    write_autoload_files_with_options(root, &composer, &[], &options)
+        .await
+        .unwrap();
+    assert_eq!(
+        fs::read_to_string(&psr4_path).unwrap(),
+        "<?php return [];\n"
+    );
+    let options = AutoloadOptions::for_lock(&lock_at("2222222"), false);
+    write_autoload_files_with_options(root, &composer, &[], &options)
+        .await
+        .unwrap();
+    assert!(
+        fs::read_to_string(&psr4_path)
+            .unwrap()
+            .contains("'Acme\\\\'")
+    );
+}
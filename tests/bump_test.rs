@@ -0,0 +1,119 @@
+use lectern::commands::bump::{BumpSections, bump_requirements, bumped_constraint};
+use lectern::models::model::{ComposerJson, Lock};
+use lectern::resolver::dependency_utils::generate_content_hash;
+use serde_json::json;
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+#[path = "common/mod.rs"]
+mod common;
+use common::{ensure_lectern_binary, get_lectern_binary_path};
+
+fn composer() -> ComposerJson {
+    serde_json::from_value(json!({
+        "require": {"php": ">=8.1", "acme/lib": "^1.0", "acme/pinned": "2.0.1", "acme/branch": "dev-main"},
+        "require-dev": {"acme/tool": "*"}
+    }))
+    .unwrap()
+}
+
+fn lock(content_hash: &str) -> Lock {
+    serde_json::from_value(json!({
+        "content-hash": content_hash,
+        "packages": [
+            {"name": "acme/lib", "version": "1.4.2"},
+            {"name": "acme/pinned", "version": "2.0.1"},
+            {"name": "acme/branch", "version": "dev-main"}
+        ],
+        "packages-dev": [{"name": "acme/tool", "version": "v3.1.0"}]
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_bumped_constraint() {
+    assert_eq!(
+        bumped_constraint("^1.0", "1.4.2").as_deref(),
+        Some("^1.4.2")
+    );
+    assert_eq!(bumped_constraint("*", "v3.1.0").as_deref(), Some("^3.1.0"));
+    assert_eq!(bumped_constraint("^1.4.2", "1.4.2"), None);
+    assert_eq!(bumped_constraint("2.0.1", "2.0.1"), None);
+    assert_eq!(bumped_constraint("dev-main", "dev-main"), None);
+    assert_eq!(bumped_constraint("^2.0@beta", "2.0.0-beta1"), None);
+}
+
+#[test]
+fn test_bump_requirements_sections_and_filters() {
+    let mut all = composer();
+    let bumps = bump_requirements(&mut all, &lock(""), &[], BumpSections::All);
+    let names: Vec<&str> = bumps.iter().map(|b| b.name.as_str()).collect();
+    assert_eq!(names, vec!["acme/lib", "acme/tool"]);
+    assert_eq!(all.require["acme/lib"], "^1.4.2");
+    assert_eq!(all.require_dev["acme/tool"], "^3.1.0");
+    assert_eq!(all.require["php"], ">=8.1");
+
+    let mut no_dev = composer();
+    let bumps = bump_requirements(&mut no_dev, &lock(""), &[], BumpSections::NoDevOnly);
+    assert_eq!(bumps.len(), 1);
+    assert_eq!(no_dev.require_dev["acme/tool"], "*");
+
+    let mut dev = composer();
+    let bumps = bump_requirements(&mut dev, &lock(""), &[], BumpSections::DevOnly);
+    assert_eq!(bumps.len(), 1);
+    assert!(bumps[0].dev);
+
+    let mut only = composer();
+    let bumps = bump_requirements(
+        &mut only,
+        &lock(""),
+        &["acme/tool".to_string()],
+        BumpSections::All,
+    );
+    assert_eq!(bumps.len(), 1);
+    assert_eq!(only.require["acme/lib"], "^1.0");
+}
+
+#[test]
+fn test_bump_command_keeps_fresh_lock_in_sync() {
+    ensure_lectern_binary();
+
+    let temp_dir = TempDir::new().unwrap();
+    let temp_path = temp_dir.path();
+    let composer_json = serde_json::to_string_pretty(&composer()).unwrap();
+    fs::write(temp_path.join("composer.json"), &composer_json).unwrap();
+    fs::write(
+        temp_path.join("composer.lock"),
+        serde_json::to_string(&lock(&generate_content_hash(&composer_json))).unwrap(),
+    )
+    .unwrap();
+
+    let output = Command::new(get_lectern_binary_path())
+        .args(["bump", "--dry-run"])
+        .current_dir(temp_path)
+        .output()
+        .expect("Failed to execute lectern bump");
+    assert!(output.status.success());
+    assert_eq!(
+        fs::read_to_string(temp_path.join("composer.json")).unwrap(),
+        composer_json
+    );
+
+    let output = Command::new(get_lectern_binary_path())
+        .arg("bump")
+        .current_dir(temp_path)
+        .output()
+        .expect("Failed to execute lectern bump");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("acme/lib: ^1.0 → ^1.4.2"), "{stdout}");
+
+    let written = fs::read_to_string(temp_path.join("composer.json")).unwrap();
+    let bumped: ComposerJson = serde_json::from_str(&written).unwrap();
+    assert_eq!(bumped.require["acme/lib"], "^1.4.2");
+    let lock: Lock =
+        serde_json::from_str(&fs::read_to_string(temp_path.join("composer.lock")).unwrap())
+            .unwrap();
+    assert_eq!(lock.content_hash, generate_content_hash(&written));
+}
@@ -117,3 +117,122 @@ fn test_cache_file_path_same_key() {
     // Same keys should produce same paths
     assert_eq!(path1, path2);
 }
+
+#[tokio::test]
+async fn test_cache_lookup_meta_revalidates_expired_entry_with_validators() {
+    let key = "test:meta:validated:expired";
+    let path = get_cache_file_path("meta", key);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    let entry = serde_json::json!({
+        "data": [{"version": "1.0.0"}],
+        "timestamp": 0,
+        "ttl": 60,
+        "etag": "\"abc123\"",
+        "last_modified": "Tue, 01 Oct 2024 00:00:00 GMT"
+    });
+    std::fs::write(&path, entry.to_string()).unwrap();
+
+    match cache::cache_lookup_meta(key).await {
+        cache::MetaLookup::Revalidate(data, validators) => {
+            assert_eq!(data, serde_json::json!([{"version": "1.0.0"}]));
+            assert_eq!(validators.etag.as_deref(), Some("\"abc123\""));
+            assert_eq!(
+                validators.last_modified.as_deref(),
+                Some("Tue, 01 Oct 2024 00:00:00 GMT")
+            );
+        }
+        other => panic!("expected revalidation, got {other:?}"),
+    }
+
+    // Expired validated entries stay on disk for the next conditional request
+    assert!(cache::cache_get_meta(key).await.is_none());
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    assert!(path.exists());
+    std::fs::remove_file(&path).ok();
+}
+
+#[tokio::test]
+async fn test_cache_lookup_meta_revalidates_unexpired_entry_unless_synced() {
+    let key = "test:meta:validated:unexpired";
+    let path = get_cache_file_path("meta", key);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let entry = serde_json::json!({
+        "data": [{"version": "1.0.0"}],
+        "timestamp": now,
+        "ttl": 3600,
+        "etag": "\"abc123\""
+    });
+    std::fs::write(&path, entry.to_string()).unwrap();
+
+    // Within the TTL is not enough on its own: a conditional GET confirms the entry
+    assert!(matches!(
+        cache::cache_lookup_meta(key).await,
+        cache::MetaLookup::Revalidate(..)
+    ));
+    // After a changes-feed sync the entry would have been invalidated had it changed
+    assert_eq!(
+        cache::cache_lookup_meta_synced(key).await,
+        cache::MetaLookup::Fresh(serde_json::json!([{"version": "1.0.0"}]))
+    );
+    std::fs::remove_file(&path).ok();
+}
+
+#[tokio::test]
+async fn test_cache_lookup_meta_without_validators() {
+    let key = "test:meta:unvalidated";
+    let path = get_cache_file_path("meta", key);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    // Entries written before validators existed have no etag/last_modified fields
+    std::fs::write(&path, r#"{"data": {"a": 1}, "timestamp": 0, "ttl": 60}"#).unwrap();
+    assert_eq!(cache::cache_lookup_meta(key).await, cache::MetaLookup::Missing);
+
+    let validators = cache::CacheValidators {
+        etag: Some("W/\"v1\"".to_string()),
+        last_modified: None,
+    };
+    let value = serde_json::json!({"a": 2});
+    cache::cache_set_meta_validated(key, value.clone(), validators).await;
+    // Already loaded in this process, so no revalidation is needed
+    assert_eq!(
+        cache::cache_lookup_meta(key).await,
+        cache::MetaLookup::Fresh(value)
+    );
+
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    let stored: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(stored["etag"], "W/\"v1\"");
+    assert!(stored.get("last_modified").is_none());
+    std::fs::remove_file(&path).ok();
+}
+
+#[tokio::test]
+async fn test_cache_invalidate_meta() {
+    let key = "test:meta:invalidate";
+    cache::cache_set_meta(key, serde_json::json!({"v": 1})).await;
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    assert!(get_cache_file_path("meta", key).exists());
+
+    cache::cache_invalidate_meta(key).await;
+    assert!(cache::cache_get_meta(key).await.is_none());
+    assert!(!get_cache_file_path("meta", key).exists());
+}
+
+#[test]
+fn test_cache_usage_in() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let cache_dir = temp_dir.path();
+    std::fs::create_dir_all(cache_dir.join("meta")).unwrap();
+    std::fs::create_dir_all(cache_dir.join("search")).unwrap();
+    std::fs::write(cache_dir.join("meta/a.json"), "1234").unwrap();
+    std::fs::write(cache_dir.join("search/b.json"), "12").unwrap();
+
+    let usage = cache::cache_usage_in(cache_dir);
+    assert_eq!(usage["repo"], cache::CacheUsage { entries: 2, bytes: 6 });
+    assert_eq!(usage["files"], cache::CacheUsage::default());
+    assert_eq!(usage.len(), cache::CACHE_TYPES.len());
+}
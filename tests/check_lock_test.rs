@@ -0,0 +1,89 @@
+use lectern::resolver::dependency_utils::generate_content_hash;
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+#[path = "common/mod.rs"]
+mod common;
+use common::{ensure_lectern_binary, get_lectern_binary_path};
+
+fn write_lock(dir: &std::path::Path, content_hash: &str) {
+    let lock = format!(
+        r#"{{
+"content-hash": "{content_hash}",
+"packages": [
+    {{"name": "monolog/monolog", "version": "3.5.0", "require": {{"psr/log": "^2.0 || ^3.0"}}}},
+    {{"name": "psr/log", "version": "3.0.0"}},
+    {{"name": "old/package", "version": "1.0.0"}}
+],
+"packages-dev": []
+}}"#
+    );
+    fs::write(dir.join("composer.lock"), lock).unwrap();
+}
+
+#[test]
+fn test_check_lock_up_to_date() {
+    ensure_lectern_binary();
+
+    let temp_dir = TempDir::new().unwrap();
+    let temp_path = temp_dir.path();
+
+    let composer_json = r#"{"name": "test/check-lock", "require": {"monolog/monolog": "^3.0"}}"#;
+    fs::write(temp_path.join("composer.json"), composer_json).unwrap();
+
+    write_lock(temp_path, &generate_content_hash(composer_json));
+
+    let output = Command::new(get_lectern_binary_path())
+        .arg("check-lock")
+        .current_dir(temp_path)
+        .output()
+        .expect("Failed to execute lectern check-lock");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "Fresh lock should pass: {stdout}");
+    assert!(stdout.contains("up to date"));
+}
+
+#[test]
+fn test_check_lock_out_of_date() {
+    ensure_lectern_binary();
+
+    let temp_dir = TempDir::new().unwrap();
+    let temp_path = temp_dir.path();
+
+    let composer_json = r#"{"name": "test/check-lock", "require": {"monolog/monolog": "^4.0", "symfony/console": "^7.0"}}"#;
+    fs::write(temp_path.join("composer.json"), composer_json).unwrap();
+    write_lock(temp_path, "stale-hash");
+
+    let output = Command::new(get_lectern_binary_path())
+        .arg("check-lock")
+        .current_dir(temp_path)
+        .output()
+        .expect("Failed to execute lectern check-lock");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(1), "Stale lock should exit 1");
+    assert!(stdout.contains("+ symfony/console"), "{stdout}");
+    assert!(stdout.contains("~ monolog/monolog"), "{stdout}");
+    assert!(stdout.contains("- old/package"), "{stdout}");
+}
+
+#[test]
+fn test_check_lock_missing_lock() {
+    ensure_lectern_binary();
+
+    let temp_dir = TempDir::new().unwrap();
+    let temp_path = temp_dir.path();
+
+    let composer_json = r#"{"name": "test/check-lock", "require": {}}"#;
+    fs::write(temp_path.join("composer.json"), composer_json).unwrap();
+
+    let output = Command::new(get_lectern_binary_path())
+        .arg("check-lock")
+        .current_dir(temp_path)
+        .output()
+        .expect("Failed to execute lectern check-lock");
+
+    assert!(!output.status.success());
+}
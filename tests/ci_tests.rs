@@ -0,0 +1,73 @@
+use lectern::ci::{CiProvider, configured_ci, detect_ci_from, detect_ci_with};
+use std::collections::HashMap;
+
+fn detect(vars: &[(&str, &str)]) -> Option<CiProvider> {
+    let env: HashMap<String, String> = vars
+        .iter()
+        .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+        .collect();
+    detect_ci_from(|name| env.get(name).cloned())
+}
+
+#[test]
+fn test_detect_no_ci() {
+    assert_eq!(detect(&[]), None);
+    assert_eq!(detect(&[("CI", "false")]), None);
+}
+
+#[test]
+fn test_detect_providers() {
+    assert_eq!(
+        detect(&[("CI", "true"), ("GITHUB_ACTIONS", "true")]),
+        Some(CiProvider::GitHubActions)
+    );
+    assert_eq!(
+        detect(&[("CI", "true"), ("GITLAB_CI", "true")]),
+        Some(CiProvider::GitLabCi)
+    );
+    assert_eq!(detect(&[("CI", "1")]), Some(CiProvider::Generic));
+}
+
+#[test]
+fn test_lectern_ci_override() {
+    // Explicitly disabled even though the provider is detected
+    assert_eq!(detect(&[("CI", "true"), ("LECTERN_CI", "0")]), None);
+    // Explicitly enabled outside of a known provider
+    assert_eq!(detect(&[("LECTERN_CI", "true")]), Some(CiProvider::Generic));
+}
+
+#[test]
+fn test_project_ci_setting() {
+    let env: HashMap<String, String> = HashMap::new();
+    let ci_env: HashMap<String, String> = [("CI".to_string(), "true".to_string())].into();
+    let override_env: HashMap<String, String> =
+        [("LECTERN_CI".to_string(), "1".to_string())].into();
+
+    // The project setting forces CI mode on or off
+    assert_eq!(
+        detect_ci_with(|name| env.get(name).cloned(), Some(true)),
+        Some(CiProvider::Generic)
+    );
+    assert_eq!(
+        detect_ci_with(|name| ci_env.get(name).cloned(), Some(false)),
+        None
+    );
+    // LECTERN_CI wins over the project setting
+    assert_eq!(
+        detect_ci_with(|name| override_env.get(name).cloned(), Some(false)),
+        Some(CiProvider::Generic)
+    );
+}
+
+#[test]
+fn test_configured_ci_reads_composer_json() {
+    let dir = tempfile::tempdir().unwrap();
+    assert_eq!(configured_ci(dir.path()), None);
+
+    std::fs::write(
+        dir.path().join("composer.json"),
+        r#"{"extra": {"lectern": {"ci": false}}}"#,
+    )
+    .unwrap();
+    assert_eq!(configured_ci(dir.path()), Some(false));
+}
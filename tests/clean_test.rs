@@ -0,0 +1,169 @@
+use lectern::commands::clean::{CleanTarget, parse_clean_targets, plan_clean};
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+#[path = "common/mod.rs"]
+mod common;
+use common::{ensure_lectern_binary, get_lectern_binary_path};
+
+fn setup_project(dir: &std::path::Path) {
+    fs::write(dir.join("composer.json"), r#"{"name": "test/clean"}"#).unwrap();
+    fs::write(
+        dir.join("composer.lock"),
+        r#"{"content-hash": "x", "packages": []}"#,
+    )
+    .unwrap();
+    fs::create_dir_all(dir.join("vendor/composer")).unwrap();
+    fs::create_dir_all(dir.join("vendor/psr/log")).unwrap();
+    fs::write(dir.join("vendor/autoload.php"), "<?php").unwrap();
+    fs::write(dir.join("vendor/composer/autoload_psr4.php"), "<?php").unwrap();
+}
+
+fn run_lectern(dir: &std::path::Path, args: &[&str]) -> std::process::Output {
+    ensure_lectern_binary();
+    Command::new(get_lectern_binary_path())
+        .args(args)
+        .current_dir(dir)
+        // Keep the real user cache out of reach
+        .env("XDG_CACHE_HOME", dir.join("xdg-cache"))
+        .output()
+        .expect("Failed to execute lectern clean")
+}
+
+#[test]
+fn test_parse_clean_targets() {
+    assert_eq!(
+        parse_clean_targets(&[], false).unwrap(),
+        vec![CleanTarget::Vendor]
+    );
+    assert_eq!(
+        parse_clean_targets(&["lock".to_string(), "lock".to_string()], false).unwrap(),
+        vec![CleanTarget::Lock]
+    );
+    assert!(parse_clean_targets(&["everything".to_string()], false).is_err());
+}
+
+#[test]
+fn test_plan_clean_autoload_only() {
+    let temp_dir = TempDir::new().unwrap();
+    setup_project(temp_dir.path());
+
+    let entries = plan_clean(
+        temp_dir.path(),
+        &temp_dir.path().join("cache"),
+        &[CleanTarget::Autoload],
+    )
+    .unwrap();
+
+    // autoload_classmap.php was never generated, so only two files are planned
+    assert_eq!(entries.len(), 2);
+    assert!(
+        entries
+            .iter()
+            .all(|e| e.path.starts_with(temp_dir.path().join("vendor")))
+    );
+}
+
+#[test]
+fn test_plan_clean_autoload_includes_static_map_and_stamp() {
+    let temp_dir = TempDir::new().unwrap();
+    setup_project(temp_dir.path());
+    let composer_dir = temp_dir.path().join("vendor/composer");
+    for file in ["autoload_static.php", "installed.php", "autoload.stamp"] {
+        fs::write(composer_dir.join(file), "<?php").unwrap();
+    }
+
+    let entries = plan_clean(
+        temp_dir.path(),
+        &temp_dir.path().join("cache"),
+        &[CleanTarget::Autoload],
+    )
+    .unwrap();
+
+    let planned: Vec<_> = entries.iter().map(|e| e.path.clone()).collect();
+    for file in ["autoload_static.php", "installed.php", "autoload.stamp"] {
+        assert!(planned.contains(&composer_dir.join(file)), "{planned:?}");
+    }
+    assert_eq!(entries.len(), 5);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_plan_clean_refuses_paths_outside_project() {
+    let outside = TempDir::new().unwrap();
+    let project = TempDir::new().unwrap();
+    fs::create_dir_all(outside.path().join("vendor")).unwrap();
+    fs::write(outside.path().join("vendor/autoload.php"), "<?php").unwrap();
+
+    // vendor is a symlink to a directory outside the project
+    std::os::unix::fs::symlink(outside.path().join("vendor"), project.path().join("vendor"))
+        .unwrap();
+
+    let result = plan_clean(
+        project.path(),
+        &project.path().join("cache"),
+        &[CleanTarget::Autoload],
+    );
+    assert!(result.is_err());
+    assert!(outside.path().join("vendor/autoload.php").exists());
+}
+
+#[test]
+fn test_clean_dry_run_keeps_files() {
+    let temp_dir = TempDir::new().unwrap();
+    setup_project(temp_dir.path());
+
+    let output = run_lectern(temp_dir.path(), &["clean", "vendor", "lock", "--dry-run"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success());
+    assert!(stdout.contains("vendor"), "{stdout}");
+    assert!(stdout.contains("composer.lock"), "{stdout}");
+    assert!(temp_dir.path().join("vendor").exists());
+    assert!(temp_dir.path().join("composer.lock").exists());
+}
+
+#[test]
+fn test_clean_vendor() {
+    let temp_dir = TempDir::new().unwrap();
+    setup_project(temp_dir.path());
+
+    let output = run_lectern(temp_dir.path(), &["clean"]);
+
+    assert!(output.status.success());
+    assert!(!temp_dir.path().join("vendor").exists());
+    assert!(temp_dir.path().join("composer.lock").exists());
+    assert!(temp_dir.path().join("composer.json").exists());
+}
+
+#[test]
+fn test_clean_lock_requires_confirmation() {
+    let temp_dir = TempDir::new().unwrap();
+    setup_project(temp_dir.path());
+
+    // Non-interactive runs keep the lock unless --force is given
+    let output = run_lectern(temp_dir.path(), &["-n", "clean", "lock"]);
+    assert!(output.status.success());
+    assert!(temp_dir.path().join("composer.lock").exists());
+
+    let output = run_lectern(temp_dir.path(), &["clean", "lock", "--force"]);
+    assert!(output.status.success());
+    assert!(!temp_dir.path().join("composer.lock").exists());
+}
+
+#[test]
+fn test_clean_quiet_prints_nothing() {
+    let temp_dir = TempDir::new().unwrap();
+    setup_project(temp_dir.path());
+
+    let output = run_lectern(temp_dir.path(), &["-q", "clean", "vendor", "--dry-run"]);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+
+    let output = run_lectern(temp_dir.path(), &["-q", "clean", "vendor"]);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(output.stderr.is_empty());
+    assert!(!temp_dir.path().join("vendor").exists());
+}
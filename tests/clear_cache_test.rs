@@ -1,4 +1,7 @@
+use lectern::commands::clear_cache::{format_megabytes, parse_cache_type};
+use std::fs;
 use std::process::Command;
+use tempfile::TempDir;
 
 #[path = "common/mod.rs"]
 mod common;
@@ -73,3 +76,60 @@ fn test_clear_cache_invalid_type() {
         !output.status.success() || combined.contains("Unknown") || combined.contains("invalid")
     );
 }
+
+fn seed_cache(cache_home: &std::path::Path) -> std::path::PathBuf {
+    let cache_dir = cache_home.join("lectern");
+    fs::create_dir_all(cache_dir.join("meta")).unwrap();
+    fs::create_dir_all(cache_dir.join("packages")).unwrap();
+    fs::write(cache_dir.join("meta/a.json"), "{}").unwrap();
+    fs::write(cache_dir.join("packages/b.zip"), vec![0u8; 2048]).unwrap();
+    cache_dir
+}
+
+fn run_clear_cache(cache_home: &std::path::Path, args: &[&str]) -> std::process::Output {
+    ensure_lectern_binary();
+    Command::new(get_lectern_binary_path())
+        .arg("clear-cache")
+        .args(args)
+        .env("XDG_CACHE_HOME", cache_home)
+        .output()
+        .expect("Failed to execute lectern clear-cache")
+}
+
+#[test]
+fn test_parse_cache_type() {
+    assert_eq!(parse_cache_type("files").unwrap(), vec!["files"]);
+    assert_eq!(
+        parse_cache_type("all").unwrap(),
+        vec!["repo", "files", "vcs", "dependency_resolution"]
+    );
+    assert!(parse_cache_type("everything").is_err());
+    assert_eq!(format_megabytes(1024 * 1024 * 3 / 2), "1.50 MB");
+}
+
+#[test]
+fn test_clear_cache_dry_run_keeps_entries() {
+    let temp_dir = TempDir::new().unwrap();
+    let cache_dir = seed_cache(temp_dir.path());
+
+    let output = run_clear_cache(temp_dir.path(), &["--dry-run"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("repo"));
+    assert!(stdout.contains("dependency_resolution"));
+    assert!(cache_dir.join("meta/a.json").exists());
+    assert!(cache_dir.join("packages/b.zip").exists());
+}
+
+#[test]
+fn test_clear_cache_files_keeps_repo_metadata() {
+    let temp_dir = TempDir::new().unwrap();
+    let cache_dir = seed_cache(temp_dir.path());
+
+    let output = run_clear_cache(temp_dir.path(), &["files"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Removed 1 entries"));
+    assert!(!cache_dir.join("packages").exists());
+    assert!(cache_dir.join("meta/a.json").exists());
+}
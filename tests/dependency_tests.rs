@@ -163,8 +163,8 @@ fn test_generate_content_hash_consistency() {
     // Different content should produce different hash
     assert_ne!(hash1, hash3);
 
-    // Hash should be hex string of expected length (SHA-256 = 64 chars)
-    assert_eq!(hash1.len(), 64);
+    // Hash should be hex string of expected length (md5 = 32 chars)
+    assert_eq!(hash1.len(), 32);
     assert!(hash1.chars().all(|c| c.is_ascii_hexdigit()));
 }
 
@@ -173,10 +173,35 @@ fn test_generate_content_hash_empty() {
     let hash = generate_content_hash("");
 
     // Should still generate a valid hash for empty content
-    assert_eq!(hash.len(), 64);
+    assert_eq!(hash.len(), 32);
     assert!(hash.chars().all(|c| c.is_ascii_hexdigit()));
 }
 
+#[test]
+fn test_generate_content_hash_matches_composer() {
+    // md5 of {"name":"test\/package","require":{"php":">=7.4"}}, as Composer encodes it
+    let content = r#"{
+        "require": {"php": ">=7.4"},
+        "description": "Ignored by the hash",
+        "name": "test/package"
+    }"#;
+    assert_eq!(
+        generate_content_hash(content),
+        "709ead827733f99e1123e418e0a49617"
+    );
+
+    // Only config.platform counts, empty objects become [] and non-ASCII is \u-escaped
+    let content = r#"{
+        "require": {"monolog/monolog": "^3.0"},
+        "config": {"platform": {"php": "8.1.0"}, "sort-packages": true},
+        "extra": {"branch-alias": {}, "note": "café"}
+    }"#;
+    assert_eq!(
+        generate_content_hash(content),
+        "d51e64381f8662dd8f026869f69c8e60"
+    );
+}
+
 #[test]
 fn test_version_selection_with_prereleases() {
     let versions = vec![
@@ -193,6 +218,48 @@ fn test_version_selection_with_prereleases() {
     assert_eq!(best.version, "1.1.0");
 }
 
+#[test]
+fn test_find_best_version_four_number_versions() {
+    let versions = vec![
+        create_test_version("1.0.0", Some("1.0.0.0")),
+        create_test_version("1.0.0.1", Some("1.0.0.1")),
+        create_test_version("1.0.0.12", Some("1.0.0.12")),
+        create_test_version("1.0.0.3", Some("1.0.0.3")),
+    ];
+
+    let constraint = parse_constraint("^1.0").unwrap();
+    let best = find_best_version(&versions, &constraint).unwrap();
+    assert_eq!(best.version, "1.0.0.12");
+}
+
+#[test]
+fn test_find_best_version_with_stability() {
+    use lectern::resolver::Constraint;
+    use lectern::resolver::dependency_utils::find_best_version_with_stability;
+
+    let versions = vec![
+        create_test_version("1.0.0", Some("1.0.0.0")),
+        create_test_version("1.1.0-beta1", Some("1.1.0.0-beta1")),
+        create_test_version("1.1.0-RC1", Some("1.1.0.0-RC1")),
+        create_test_version("1.2.0-alpha1", Some("1.2.0.0-alpha1")),
+        create_test_version("dev-main", Some("dev-main")),
+    ];
+    let constraint = Constraint::parse("^1.0").unwrap();
+    let best = |stability: i32, prefer_stable: bool| {
+        &find_best_version_with_stability(&versions, &constraint, stability, prefer_stable)
+            .unwrap()
+            .version
+    };
+
+    assert_eq!(best(0, false), "1.0.0");
+    // RC is preferred over beta of the same release
+    assert_eq!(best(10, false), "1.1.0-RC1");
+    assert_eq!(best(15, false), "1.2.0-alpha1");
+    assert_eq!(best(15, true), "1.0.0");
+    // Branches are never picked for a range
+    assert_eq!(best(20, false), "1.2.0-alpha1");
+}
+
 #[test]
 fn test_version_with_v_prefix() {
     let versions = vec![
@@ -207,3 +274,777 @@ fn test_version_with_v_prefix() {
     // Should handle versions with 'v' prefix correctly
     assert_eq!(best.version, "v1.2.0");
 }
+
+async fn seed_metadata(name: &str, version: &str, requires: &[(&str, &str)]) {
+    let mut p2 = create_test_version(version, None);
+    p2.require = Some(
+        requires
+            .iter()
+            .map(|(n, c)| (n.to_string(), c.to_string()))
+            .collect(),
+    );
+    lectern::cache::cache_set_meta(
+        &format!("p2:{name}"),
+        serde_json::to_value(vec![p2]).unwrap(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_solve_resolves_transitive_levels() {
+    // Metadata is served from the in-memory cache, so no network is needed
+    seed_metadata(
+        "lectern-test/app-a",
+        "1.0.0",
+        &[("lectern-test/shared", "^2.0"), ("php", ">=8.1")],
+    )
+    .await;
+    seed_metadata(
+        "lectern-test/app-b",
+        "1.1.0",
+        &[("lectern-test/shared", "^2.1")],
+    )
+    .await;
+    seed_metadata(
+        "lectern-test/shared",
+        "2.3.0",
+        &[("lectern-test/leaf", "^1.0")],
+    )
+    .await;
+    seed_metadata("lectern-test/leaf", "1.0.4", &[]).await;
+    seed_metadata(
+        "lectern-test/dev-tool",
+        "0.5.0",
+        &[("lectern-test/dev-leaf", "*")],
+    )
+    .await;
+    seed_metadata("lectern-test/dev-leaf", "3.0.0", &[]).await;
+
+    let composer: lectern::models::model::ComposerJson = serde_json::from_str(
+        r#"{
+            "require": {"lectern-test/app-a": "^1.0", "lectern-test/app-b": "^1.0", "php": ">=8.1"},
+            "require-dev": {"lectern-test/dev-tool": "^0.5"}
+        }"#,
+    )
+    .unwrap();
+
+    let lock = lectern::resolver::solve(&composer).await.unwrap();
+
+    let names: Vec<&str> = lock.packages.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec![
+            "lectern-test/app-a",
+            "lectern-test/app-b",
+            "lectern-test/leaf",
+            "lectern-test/shared",
+        ]
+    );
+    let dev_names: Vec<&str> = lock.packages_dev.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(
+        dev_names,
+        vec!["lectern-test/dev-leaf", "lectern-test/dev-tool"]
+    );
+    assert_eq!(lock.platform.get("php").map(String::as_str), Some(">=8.1"));
+}
+
+#[tokio::test]
+async fn test_solve_with_stats_counts_conflicts() {
+    seed_metadata(
+        "lectern-stats/a",
+        "1.0.0",
+        &[("lectern-stats/shared", "<2.2")],
+    )
+    .await;
+    let shared = |version: &str, requires: &[(&str, &str)]| {
+        let mut p2 = create_test_version(version, None);
+        p2.require = Some(
+            requires
+                .iter()
+                .map(|(n, c)| (n.to_string(), c.to_string()))
+                .collect(),
+        );
+        p2
+    };
+    lectern::cache::cache_set_meta(
+        "p2:lectern-stats/shared",
+        serde_json::to_value(vec![
+            shared("2.3.0", &[("lectern-stats/helper", "^1.0")]),
+            shared("2.1.0", &[]),
+        ])
+        .unwrap(),
+    )
+    .await;
+    seed_metadata("lectern-stats/helper", "1.0.0", &[]).await;
+
+    // The root's ^2.0 picks 2.3.0 before a's <2.2 is known, so shared is chosen again
+    let composer: lectern::models::model::ComposerJson = serde_json::from_str(
+        r#"{"require": {"lectern-stats/a": "^1.0", "lectern-stats/shared": "^2.0"}}"#,
+    )
+    .unwrap();
+
+    let (lock, stats) = lectern::resolver::solve_with_stats(&composer)
+        .await
+        .unwrap();
+    let locked: Vec<(&str, &str)> = lock
+        .packages
+        .iter()
+        .map(|p| (p.name.as_str(), p.version.as_str()))
+        .collect();
+    // 2.3.0's helper is no longer required by anything
+    assert_eq!(
+        locked,
+        vec![
+            ("lectern-stats/a", "1.0.0"),
+            ("lectern-stats/shared", "2.1.0")
+        ]
+    );
+    assert_eq!(stats.packages_in_pool, 3);
+    assert_eq!(stats.versions_fetched, 4);
+    assert_eq!(stats.conflicts, 1);
+    assert_eq!(stats.backtracks, 1);
+    assert!(stats.total_time >= stats.fetch_time);
+}
+
+#[tokio::test]
+async fn test_solve_intersects_requirements_from_every_requirer() {
+    let versions = |versions: &[&str]| {
+        let versions: Vec<P2Version> = versions
+            .iter()
+            .map(|version| {
+                let mut p2 = create_test_version(version, None);
+                p2.require = None;
+                p2
+            })
+            .collect();
+        serde_json::to_value(versions).unwrap()
+    };
+    lectern::cache::cache_set_meta(
+        "p2:lectern-intersect/shared",
+        versions(&["3.0.0", "2.5.0", "2.3.0", "2.1.0"]),
+    )
+    .await;
+    seed_metadata(
+        "lectern-intersect/a",
+        "1.0.0",
+        &[("lectern-intersect/shared", "^2.0")],
+    )
+    .await;
+    seed_metadata(
+        "lectern-intersect/b",
+        "1.0.0",
+        &[("lectern-intersect/shared", "<2.4 || ^3.0")],
+    )
+    .await;
+    seed_metadata(
+        "lectern-intersect/c",
+        "1.0.0",
+        &[("lectern-intersect/shared", "~3.0")],
+    )
+    .await;
+
+    // Both requirers meet shared at the same depth: the newest version allowed by both
+    let composer: lectern::models::model::ComposerJson = serde_json::from_str(
+        r#"{"require": {"lectern-intersect/a": "^1.0", "lectern-intersect/b": "^1.0"}}"#,
+    )
+    .unwrap();
+    let (lock, stats) = lectern::resolver::solve_with_stats(&composer)
+        .await
+        .unwrap();
+    let shared = lock
+        .packages
+        .iter()
+        .find(|p| p.name == "lectern-intersect/shared")
+        .unwrap();
+    assert_eq!(shared.version, "2.3.0");
+    assert_eq!(stats.conflicts, 0);
+    assert_eq!(stats.backtracks, 0);
+
+    // ^2.0 and ~3.0 share no version: the error names both requirers
+    let composer: lectern::models::model::ComposerJson = serde_json::from_str(
+        r#"{"name": "acme/app", "require": {
+            "lectern-intersect/a": "^1.0",
+            "lectern-intersect/c": "^1.0"
+        }}"#,
+    )
+    .unwrap();
+    let err = lectern::resolver::solve(&composer).await.unwrap_err();
+    let message = err.to_string();
+    assert!(
+        message.contains(
+            "Root composer.json requires lectern-intersect/a ^1.0 -> lectern-intersect/a 1.0.0 requires lectern-intersect/shared ^2.0"
+        ),
+        "{message}"
+    );
+    assert!(
+        message.contains(
+            "Root composer.json requires lectern-intersect/c ^1.0 -> lectern-intersect/c 1.0.0 requires lectern-intersect/shared ~3.0"
+        ),
+        "{message}"
+    );
+    assert_eq!(
+        lectern::error::find_lectern_error(&err).map(lectern::LecternError::exit_code),
+        Some(2)
+    );
+}
+
+#[test]
+fn test_parse_timestamp() {
+    use lectern::resolver::options::parse_timestamp;
+
+    assert_eq!(parse_timestamp("1970-01-01"), Some(0));
+    assert_eq!(parse_timestamp("2024-01-01"), Some(1_704_067_200));
+    assert_eq!(
+        parse_timestamp("2024-01-01T12:30:00+00:00"),
+        Some(1_704_067_200 + 45_000)
+    );
+    // Offsets are converted to UTC
+    assert_eq!(
+        parse_timestamp("2024-01-01T02:00:00+02:00"),
+        parse_timestamp("2024-01-01")
+    );
+    assert_eq!(
+        parse_timestamp("2023-12-31 22:00:00.123Z"),
+        parse_timestamp("2023-12-31T22:00:00")
+    );
+    assert_eq!(parse_timestamp("2024-13-01"), None);
+    assert_eq!(parse_timestamp("yesterday"), None);
+    assert_eq!(parse_timestamp("2024-01-01T25:00:00"), None);
+}
+
+#[tokio::test]
+async fn test_solve_until_ignores_later_releases() {
+    use lectern::resolver::{ResolveOptions, solve_with_options};
+
+    let release = |version: &str, time: Option<&str>| {
+        let mut p2 = create_test_version(version, Some(&format!("{version}.0")));
+        p2.require = None;
+        if let Some(time) = time {
+            p2.other
+                .insert("time".to_string(), serde_json::Value::from(time));
+        }
+        p2
+    };
+    lectern::cache::cache_set_meta(
+        "p2:lectern-until/lib",
+        serde_json::to_value(vec![
+            release("1.0.0", Some("2023-06-01T10:00:00+00:00")),
+            release("1.1.0", Some("2023-12-31T23:59:59+00:00")),
+            release("1.2.0", Some("2024-02-15T08:00:00+00:00")),
+            release("1.3.0", None),
+        ])
+        .unwrap(),
+    )
+    .await;
+
+    let composer: lectern::models::model::ComposerJson =
+        serde_json::from_str(r#"{"require": {"lectern-until/lib": "^1.0"}}"#).unwrap();
+
+    let options = ResolveOptions::until("2024-01-01").unwrap();
+    let lock = solve_with_options(&composer, &options).await.unwrap();
+    assert_eq!(lock.packages[0].version, "1.1.0");
+
+    let options = ResolveOptions::until("2023-01-01").unwrap();
+    assert!(solve_with_options(&composer, &options).await.is_err());
+    assert!(ResolveOptions::until("last tuesday").is_err());
+}
+
+#[tokio::test]
+async fn test_solve_uses_inline_package_repositories() {
+    seed_metadata("lectern-inline/dep", "1.2.0", &[]).await;
+
+    let composer: lectern::models::model::ComposerJson = serde_json::from_str(
+        r#"{
+            "repositories": [{"type": "package", "package": [
+                {"name": "Acme/Widget", "version": "1.0.0",
+                 "dist": {"type": "tar", "url": "https://files.example.com/widget-1.0.0.tar"},
+                 "require": {"lectern-inline/dep": "^1.0"},
+                 "autoload": {"psr-4": {"Acme\\Widget\\": "src/"}}},
+                {"name": "acme/widget", "version": "2.0.0",
+                 "dist": {"type": "zip", "url": "https://files.example.com/widget-2.0.0.zip"}}
+            ]}],
+            "require": {"acme/widget": "^1.0"}
+        }"#,
+    )
+    .unwrap();
+
+    let lock = lectern::resolver::solve(&composer).await.unwrap();
+    let names: Vec<&str> = lock.packages.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, vec!["acme/widget", "lectern-inline/dep"]);
+
+    let widget = &lock.packages[0];
+    assert_eq!(widget.version, "1.0.0");
+    let dist = widget.dist.as_ref().unwrap();
+    assert_eq!(dist.dist_type, "tar");
+    assert_eq!(dist.url, "https://files.example.com/widget-1.0.0.tar");
+    assert!(widget.autoload.is_some());
+    assert_eq!(widget.notification_url, None);
+}
+
+#[test]
+fn test_inline_packages_require_name_version_and_archive() {
+    use lectern::resolver::inline_packages;
+
+    let parse = |package: &str| -> lectern::models::model::ComposerJson {
+        serde_json::from_str(&format!(
+            r#"{{"repositories": [{{"type": "package", "package": {package}}}]}}"#
+        ))
+        .unwrap()
+    };
+
+    let single = parse(
+        r#"{"name": "acme/one", "version": "1.0.0", "source": {"type": "git", "url": "https://example.com/one.git", "reference": "main"}}"#,
+    );
+    assert_eq!(inline_packages(&single).unwrap()["acme/one"].len(), 1);
+
+    let no_version =
+        parse(r#"{"name": "acme/one", "dist": {"url": "https://example.com/one.zip"}}"#);
+    assert!(inline_packages(&no_version).is_err());
+    let no_archive = parse(r#"{"name": "acme/one", "version": "1.0.0"}"#);
+    assert!(inline_packages(&no_archive).is_err());
+}
+
+#[tokio::test]
+async fn test_solve_consults_repositories_before_packagist() {
+    // Packagist has the public package, the private repository an internal fork of it
+    seed_metadata("lectern-repo/fork", "2.0.0", &[]).await;
+    seed_metadata("lectern-repo/public", "1.0.0", &[]).await;
+    let fork = create_test_version("1.5.0", None);
+    lectern::cache::cache_set_meta(
+        "repo:https://repo.example.com:lectern-repo/fork",
+        serde_json::to_value(vec![fork]).unwrap(),
+    )
+    .await;
+    lectern::cache::cache_set_meta(
+        "repo:https://repo.example.com:lectern-repo/public",
+        serde_json::json!([]),
+    )
+    .await;
+
+    let composer: lectern::models::model::ComposerJson = serde_json::from_str(
+        r#"{
+            "require": {"lectern-repo/fork": "^1.0 || ^2.0", "lectern-repo/public": "^1.0"},
+            "repositories": [{"type": "composer", "url": "https://repo.example.com/"}]
+        }"#,
+    )
+    .unwrap();
+    let lock = lectern::resolver::solve(&composer).await.unwrap();
+    let versions: Vec<(&str, &str)> = lock
+        .packages
+        .iter()
+        .map(|p| (p.name.as_str(), p.version.as_str()))
+        .collect();
+    assert_eq!(
+        versions,
+        vec![
+            ("lectern-repo/fork", "1.5.0"),
+            ("lectern-repo/public", "1.0.0")
+        ]
+    );
+    assert_eq!(lock.packages[0].notification_url, None);
+
+    // Without Packagist a package no repository has can't be found
+    let composer: lectern::models::model::ComposerJson = serde_json::from_str(
+        r#"{
+            "require": {"lectern-repo/public": "^1.0"},
+            "repositories": {
+                "private": {"type": "composer", "url": "https://repo.example.com"},
+                "packagist.org": false
+            }
+        }"#,
+    )
+    .unwrap();
+    let err = lectern::resolver::solve(&composer).await.unwrap_err();
+    assert!(matches!(
+        lectern::error::find_lectern_error(&err),
+        Some(lectern::error::LecternError::PackageNotFound { .. })
+    ));
+}
+
+#[test]
+fn test_repositories_keep_their_order_in_either_form() {
+    use lectern::models::model::Repository;
+
+    let composer: lectern::models::model::ComposerJson = serde_json::from_str(
+        r#"{"repositories": {
+            "private": {"type": "composer", "url": "https://repo.example.com"},
+            "packagist.org": false,
+            "local": {"type": "path", "url": "../lib"}
+        }}"#,
+    )
+    .unwrap();
+    let repositories = composer.repositories.unwrap();
+    assert!(matches!(repositories[0], Repository::Composer { .. }));
+    assert!(repositories[1].disables_packagist());
+    assert!(matches!(repositories[2], Repository::Path { .. }));
+
+    let composer: lectern::models::model::ComposerJson = serde_json::from_str(
+        r#"{"repositories": [{"packagist": false}, {"type": "vcs", "url": "https://git.example.com/x.git"}]}"#,
+    )
+    .unwrap();
+    let repositories = composer.repositories.unwrap();
+    assert!(repositories[0].disables_packagist());
+    assert!(!repositories[1].disables_packagist());
+}
+
+#[tokio::test]
+async fn test_solve_inline_and_branch_aliases() {
+    let branch = |version: &str, extra: Option<serde_json::Value>| {
+        let mut p2 = create_test_version(version, None);
+        p2.require = None;
+        p2.extra = extra;
+        // Commits, so the resolver doesn't look up the branch heads
+        if let Some(source) = &mut p2.source {
+            source.reference = Some("a".repeat(40));
+        }
+        p2
+    };
+    seed_metadata("lectern-alias/framework", "1.0.0", &[]).await;
+    lectern::cache::cache_set_meta(
+        "p2:lectern-alias/framework~dev",
+        serde_json::to_value(vec![
+            branch(
+                "dev-main",
+                Some(serde_json::json!({"branch-alias": {"dev-main": "2.x-dev"}})),
+            ),
+            branch("dev-feature", None),
+        ])
+        .unwrap(),
+    )
+    .await;
+    seed_metadata(
+        "lectern-alias/plugin",
+        "1.0.0",
+        &[("lectern-alias/framework", "^1.5")],
+    )
+    .await;
+
+    let composer: lectern::models::model::ComposerJson = serde_json::from_str(
+        r#"{"require": {
+            "lectern-alias/framework": "dev-feature as 1.5.0",
+            "lectern-alias/plugin": "^1.0"
+        }}"#,
+    )
+    .unwrap();
+    let (lock, stats) = lectern::resolver::solve_with_stats(&composer)
+        .await
+        .unwrap();
+    assert_eq!(lock.packages[0].version, "dev-feature");
+    assert_eq!(
+        lock.aliases,
+        vec![serde_json::json!({
+            "package": "lectern-alias/framework",
+            "version": "dev-feature",
+            "alias": "1.5.0",
+            "alias_normalized": "1.5.0.0"
+        })]
+    );
+    // The plugin's ^1.5 is satisfied by the alias
+    assert_eq!(stats.conflicts, 0);
+
+    let composer: lectern::models::model::ComposerJson =
+        serde_json::from_str(r#"{"require": {"lectern-alias/framework": "2.x-dev"}}"#).unwrap();
+    let lock = lectern::resolver::solve(&composer).await.unwrap();
+    assert_eq!(lock.packages[0].version, "dev-main");
+    assert!(lock.aliases.is_empty());
+    assert_eq!(
+        lectern::resolver::dependency_utils::locked_aliases(&lock, &lock.packages[0]),
+        vec!["2.x-dev"]
+    );
+
+    // A commit pinned in the requirement is what gets locked
+    let pinned = "0123456789abcdef0123456789abcdef01234567";
+    let composer: lectern::models::model::ComposerJson = serde_json::from_str(&format!(
+        r#"{{"require": {{"lectern-alias/framework": "dev-feature#{pinned}"}}}}"#
+    ))
+    .unwrap();
+    let lock = lectern::resolver::solve(&composer).await.unwrap();
+    assert_eq!(lock.packages[0].version, "dev-feature");
+    assert_eq!(lock.packages[0].source.as_ref().unwrap().reference, pinned);
+    assert_eq!(lock.packages[0].dist.as_ref().unwrap().reference, pinned);
+}
+
+#[tokio::test]
+async fn test_solve_with_stability_flags() {
+    let version = |version: &str| {
+        let mut p2 = create_test_version(version, None);
+        p2.require = None;
+        p2
+    };
+    lectern::cache::cache_set_meta(
+        "p2:lectern-stability/flex",
+        serde_json::to_value(vec![version("1.0.0"), version("1.1.0-beta2")]).unwrap(),
+    )
+    .await;
+    seed_metadata("lectern-stability/other", "1.0.0", &[]).await;
+
+    let composer: lectern::models::model::ComposerJson = serde_json::from_str(
+        r#"{"require": {
+            "lectern-stability/flex": "^1.0@beta",
+            "lectern-stability/other": "^1.0"
+        }}"#,
+    )
+    .unwrap();
+    let lock = lectern::resolver::solve(&composer).await.unwrap();
+    assert_eq!(lock.packages[0].version, "1.1.0-beta2");
+    assert_eq!(
+        lock.stability_flags,
+        BTreeMap::from([("lectern-stability/flex".to_string(), 10)])
+    );
+
+    // Without the flag minimum-stability applies
+    let composer: lectern::models::model::ComposerJson =
+        serde_json::from_str(r#"{"require": {"lectern-stability/flex": "^1.0"}}"#).unwrap();
+    let lock = lectern::resolver::solve(&composer).await.unwrap();
+    assert_eq!(lock.packages[0].version, "1.0.0");
+    assert!(lock.stability_flags.is_empty());
+
+    // Branches and prereleases named in the constraint are recorded too
+    let composer: lectern::models::model::ComposerJson = serde_json::from_str(
+        r#"{"require": {"a/branch": "dev-main", "b/rc": "2.0.0-RC1", "c/stable": "^1.0"}}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        lectern::resolver::dependency_utils::root_stability_flags(&composer),
+        BTreeMap::from([("a/branch".to_string(), 20), ("b/rc".to_string(), 5)])
+    );
+}
+
+#[test]
+fn test_partition_dev_packages_matches_composer() {
+    use lectern::io::{read_composer_json, read_lock};
+    use lectern::resolver::dependency_utils::partition_dev_packages;
+
+    // A lock written by Composer for the fixture's composer.json
+    let fixture =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/dev-partition");
+    let composer = read_composer_json(&fixture.join("composer.json")).unwrap();
+    let lock = read_lock(&fixture.join("composer.lock")).unwrap();
+    let names = |packages: &[lectern::models::model::LockedPackage]| {
+        packages.iter().map(|p| p.name.clone()).collect::<Vec<_>>()
+    };
+
+    // The resolver hands packages over sorted by name, prod and dev mixed
+    let mut resolved: Vec<_> = lock
+        .packages
+        .iter()
+        .chain(&lock.packages_dev)
+        .cloned()
+        .collect();
+    resolved.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let (regular, dev) = partition_dev_packages(&composer, resolved);
+    // psr/log stays a production package although the dev-only debug bar needs it, and
+    // guzzle and symfony/polyfill are reached through provide and replace
+    assert_eq!(names(&regular), names(&lock.packages));
+    assert_eq!(names(&dev), names(&lock.packages_dev));
+}
+
+#[tokio::test]
+async fn test_solve_skips_packages_the_root_replaces_or_provides() {
+    seed_metadata(
+        "lectern-root/app",
+        "1.0.0",
+        &[
+            ("lectern-root/support", "^1.2"),
+            ("lectern-root/log-implementation", "^1.0"),
+            ("lectern-root/monorepo", "^1.0"),
+        ],
+    )
+    .await;
+    // Would be locked if the root didn't stand in for them
+    seed_metadata("lectern-root/support", "1.9.0", &[]).await;
+    seed_metadata("lectern-root/log-implementation", "1.0.0", &[]).await;
+
+    let composer: lectern::models::model::ComposerJson = serde_json::from_str(
+        r#"{
+            "name": "lectern-root/monorepo",
+            "version": "1.4.0",
+            "require": {"lectern-root/app": "^1.0"},
+            "replace": {"lectern-root/support": "self.version"},
+            "provide": {"lectern-root/log-implementation": "1.0.0"}
+        }"#,
+    )
+    .unwrap();
+    let lock = lectern::resolver::solve(&composer).await.unwrap();
+    let names: Vec<&str> = lock.packages.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, vec!["lectern-root/app"]);
+
+    // A replaced version outside the requirement is a conflict
+    let composer: lectern::models::model::ComposerJson = serde_json::from_str(
+        r#"{
+            "name": "lectern-root/monorepo",
+            "version": "1.1.0",
+            "require": {"lectern-root/app": "^1.0"},
+            "replace": {"lectern-root/support": "self.version"},
+            "provide": {"lectern-root/log-implementation": "1.0.0"}
+        }"#,
+    )
+    .unwrap();
+    let err = lectern::resolver::solve(&composer).await.unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("lectern-root/app 1.0.0 requires lectern-root/support ^1.2 -> but the root package replaces it"),
+        "{err}"
+    );
+}
+
+#[tokio::test]
+async fn test_solve_keeps_ignored_packages_at_their_locked_version() {
+    use lectern::resolver::{ResolveOptions, solve_with_options};
+
+    let release = |version: &str| {
+        let normalized = format!("{}.0", version.trim_start_matches('v'));
+        let mut p2 = create_test_version(version, Some(&normalized));
+        p2.require = None;
+        p2
+    };
+    lectern::cache::cache_set_meta(
+        "p2:lectern-held/lib",
+        serde_json::to_value(vec![
+            release("v1.0.0"),
+            release("v1.1.0"),
+            release("v1.2.0"),
+        ])
+        .unwrap(),
+    )
+    .await;
+
+    let composer: lectern::models::model::ComposerJson =
+        serde_json::from_str(r#"{"require": {"lectern-held/lib": "^1.0"}}"#).unwrap();
+    let lock = solve_with_options(&composer, &ResolveOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(lock.packages[0].version, "v1.2.0");
+
+    let mut options = ResolveOptions::default();
+    options
+        .locked
+        .insert("lectern-held/lib".to_string(), "1.1.0".to_string());
+    let lock = solve_with_options(&composer, &options).await.unwrap();
+    assert_eq!(lock.packages[0].version, "v1.1.0");
+
+    options
+        .locked
+        .insert("lectern-held/lib".to_string(), "0.9.0".to_string());
+    let err = solve_with_options(&composer, &options).await.unwrap_err();
+    assert!(err.to_string().contains("held at 0.9.0"));
+}
+
+#[tokio::test]
+async fn test_resolution_failures_explain_the_requirement_chain() {
+    use lectern::resolver::{ResolveOptions, solve_with_options};
+
+    seed_metadata(
+        "lectern-explain/foo",
+        "2.1.0",
+        &[("lectern-explain/bar", "^3.0")],
+    )
+    .await;
+    seed_metadata(
+        "lectern-explain/baz",
+        "1.4.0",
+        &[("lectern-explain/bar", "~2.0")],
+    )
+    .await;
+    let bar = |version: &str| {
+        let mut p2 = create_test_version(version, None);
+        p2.require = None;
+        p2
+    };
+    lectern::cache::cache_set_meta(
+        "p2:lectern-explain/bar",
+        serde_json::to_value(vec![bar("3.0.0"), bar("2.4.0")]).unwrap(),
+    )
+    .await;
+
+    let composer: lectern::models::model::ComposerJson = serde_json::from_str(
+        r#"{"require": {"lectern-explain/foo": "^2.0", "lectern-explain/baz": "^1.0"}}"#,
+    )
+    .unwrap();
+    let message = lectern::resolver::solve(&composer)
+        .await
+        .unwrap_err()
+        .to_string();
+    assert_eq!(
+        message,
+        "No version of lectern-explain/bar satisfies every requirement:\n  \
+         Root composer.json requires lectern-explain/baz ^1.0 -> lectern-explain/baz 1.4.0 requires lectern-explain/bar ~2.0\n  \
+         Root composer.json requires lectern-explain/foo ^2.0 -> lectern-explain/foo 2.1.0 requires lectern-explain/bar ^3.0"
+    );
+
+    // A package held at its locked version says so
+    let composer: lectern::models::model::ComposerJson =
+        serde_json::from_str(r#"{"require": {"lectern-explain/foo": "^2.0"}}"#).unwrap();
+    let mut options = ResolveOptions::default();
+    options
+        .locked
+        .insert("lectern-explain/bar".to_string(), "2.4.0".to_string());
+    let message = solve_with_options(&composer, &options)
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(
+        message.ends_with(
+            "Root composer.json requires lectern-explain/foo ^2.0 -> lectern-explain/foo 2.1.0 requires lectern-explain/bar ^3.0 -> but lectern-explain/bar is locked at 2.4.0"
+        ),
+        "{message}"
+    );
+}
+
+#[test]
+fn test_explainer_stops_at_cycles() {
+    use lectern::resolver::Explainer;
+
+    let requirements: BTreeMap<String, Vec<(String, String)>> = [
+        ("acme/a", vec![("__root__", "^1.0"), ("acme/b", "*")]),
+        ("acme/b", vec![("acme/a", "^2.0")]),
+    ]
+    .into_iter()
+    .map(|(name, by)| {
+        (
+            name.to_string(),
+            by.into_iter()
+                .map(|(r, c)| (r.to_string(), c.to_string()))
+                .collect(),
+        )
+    })
+    .collect();
+    let selected = BTreeMap::new();
+    let explainer = Explainer {
+        root: "__root__",
+        requirements: &requirements,
+        selected: &selected,
+    };
+    assert_eq!(
+        explainer.chain("acme/c", "acme/b", "^1.0"),
+        "Root composer.json requires acme/a ^1.0 -> acme/a requires acme/b ^2.0 -> acme/b requires acme/c ^1.0"
+    );
+}
+
+#[tokio::test]
+async fn test_solve_fails_on_unavailable_root_requirements() {
+    use lectern::LecternError;
+    use lectern::error::{exit_code, find_lectern_error};
+
+    lectern::cache::cache_set_meta("p2:lectern-missing/empty", serde_json::json!([])).await;
+    let composer: lectern::models::model::ComposerJson =
+        serde_json::from_str(r#"{"require": {"lectern-missing/empty": "^1.0"}}"#).unwrap();
+    let err = lectern::resolver::solve(&composer).await.unwrap_err();
+    assert!(matches!(
+        find_lectern_error(&err),
+        Some(LecternError::PackageNotFound { name, .. }) if name == "lectern-missing/empty"
+    ));
+    assert_eq!(exit_code(&err), 2);
+
+    seed_metadata("lectern-missing/lib", "1.0.0", &[]).await;
+    let composer: lectern::models::model::ComposerJson =
+        serde_json::from_str(r#"{"require": {"lectern-missing/lib": "^^1.0"}}"#).unwrap();
+    let err = lectern::resolver::solve(&composer).await.unwrap_err();
+    assert!(matches!(
+        find_lectern_error(&err),
+        Some(LecternError::ResolutionConflict(message))
+            if message.contains("Invalid constraint '^^1.0'")
+    ));
+    assert_eq!(exit_code(&err), 2);
+}
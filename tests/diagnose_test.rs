@@ -76,3 +76,87 @@ fn test_diagnose_detects_missing_dependencies() {
         stdout.contains("vendor") || stdout.contains("install") || stdout.contains("Issue")
     );
 }
+
+#[test]
+fn test_parse_rate_limit() {
+    use lectern::commands::diagnose::{RateLimit, parse_rate_limit};
+
+    let body = serde_json::json!({
+        "resources": {"core": {"limit": 5000, "remaining": 4990, "reset": 1700000000}},
+        "rate": {"limit": 1, "remaining": 1, "reset": 1}
+    });
+    assert_eq!(
+        parse_rate_limit(&body),
+        Some(RateLimit {
+            limit: 5000,
+            remaining: 4990,
+            reset: 1_700_000_000
+        })
+    );
+    let body = serde_json::json!({"rate": {"limit": 60, "remaining": 0, "reset": 5}});
+    assert_eq!(parse_rate_limit(&body).map(|rate| rate.remaining), Some(0));
+    assert_eq!(parse_rate_limit(&serde_json::json!({"message": "x"})), None);
+}
+
+#[test]
+fn test_rate_limit_and_disk_space_issues() {
+    use lectern::commands::diagnose::{RateLimit, disk_space_issue, rate_limit_issue};
+
+    let rate = |remaining| RateLimit {
+        limit: 60,
+        remaining,
+        reset: 0,
+    };
+    assert_eq!(rate_limit_issue(&rate(50), false), None);
+    let low = rate_limit_issue(&rate(3), false).unwrap();
+    assert!(
+        low.contains("Only 3 of 60") && low.contains("github-oauth"),
+        "{low}"
+    );
+    assert!(
+        !rate_limit_issue(&rate(3), true)
+            .unwrap()
+            .contains("github-oauth")
+    );
+    assert!(
+        rate_limit_issue(&rate(0), true)
+            .unwrap()
+            .contains("exhausted")
+    );
+
+    const MB: u64 = 1024 * 1024;
+    assert_eq!(disk_space_issue(500 * MB, 100 * MB), None);
+    let issue = disk_space_issue(50 * MB, 100 * MB).unwrap();
+    assert!(
+        issue.contains("50 MB free") && issue.contains("100 MB"),
+        "{issue}"
+    );
+}
+
+#[test]
+fn test_diagnose_checks_tools_cache_and_network() {
+    ensure_lectern_binary();
+
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("composer.json"), "{}").unwrap();
+
+    let output = Command::new(get_lectern_binary_path())
+        .arg("diagnose")
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute lectern diagnose");
+
+    // Network problems are reported as issues, not as a failed run
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    for check in [
+        "Checking git availability",
+        "Checking cache writability",
+        "Checking disk space",
+        "Checking network connectivity",
+        "Checking authentication and GitHub rate limit",
+        "Checking lectern version",
+    ] {
+        assert!(stdout.contains(check), "missing {check}: {stdout}");
+    }
+}
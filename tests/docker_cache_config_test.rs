@@ -0,0 +1,69 @@
+use lectern::commands::docker::{CacheLayout, devcontainer_snippet, dockerfile_snippet};
+use std::path::PathBuf;
+use std::process::Command;
+use tempfile::TempDir;
+
+#[path = "common/mod.rs"]
+mod common;
+use common::{ensure_lectern_binary, get_lectern_binary_path};
+
+fn layout(has_lock: bool) -> CacheLayout {
+    CacheLayout {
+        cache_dir: PathBuf::from("/home/dev/.cache/lectern"),
+        packages_dir: PathBuf::from("/home/dev/.cache/lectern/packages"),
+        vendor_dir: PathBuf::from("/work/app/vendor"),
+        has_lock,
+    }
+}
+
+#[test]
+fn test_dockerfile_snippet_orders_layers() {
+    let snippet = dockerfile_snippet(&layout(true));
+    let copy_manifests = snippet.find("COPY composer.json composer.lock ./").unwrap();
+    let install = snippet
+        .find("RUN --mount=type=cache,target=/var/cache/lectern lectern install")
+        .unwrap();
+    let copy_app = snippet.find("COPY . .").unwrap();
+    assert!(copy_manifests < install && install < copy_app, "{snippet}");
+    assert!(snippet.contains("ENV XDG_CACHE_HOME=/var/cache"));
+
+    let without_lock = dockerfile_snippet(&layout(false));
+    assert!(
+        without_lock.contains("COPY composer.json ./"),
+        "{without_lock}"
+    );
+}
+
+#[test]
+fn test_devcontainer_snippet_mounts_host_cache() {
+    let snippet: serde_json::Value =
+        serde_json::from_str(&devcontainer_snippet(&layout(true))).unwrap();
+    assert_eq!(snippet["containerEnv"]["XDG_CACHE_HOME"], "/var/cache");
+    let mounts = snippet["mounts"].as_array().unwrap();
+    assert_eq!(
+        mounts[0],
+        "source=/home/dev/.cache/lectern,target=/var/cache/lectern,type=bind"
+    );
+    assert!(mounts[1].as_str().unwrap().contains("/vendor,type=volume"));
+}
+
+#[test]
+fn test_docker_cache_config_prints_cache_dir() {
+    ensure_lectern_binary();
+
+    let temp_dir = TempDir::new().unwrap();
+    let cache_home = temp_dir.path().join("xdg");
+    let output = Command::new(get_lectern_binary_path())
+        .args(["docker-cache-config", "--cache-dir-print"])
+        .env("XDG_CACHE_HOME", &cache_home)
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute lectern docker-cache-config");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        stdout.trim(),
+        cache_home.join("lectern").to_string_lossy().as_ref()
+    );
+}
@@ -0,0 +1,89 @@
+use lectern::environments::{
+    apply_environment, check_environments, environment_lock_path, environments,
+};
+use lectern::models::model::ComposerJson;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+const COMPOSER: &str = r#"{
+    "require": {"php": ">=7.4", "lectern-env/lib": "^2.0"},
+    "require-dev": {"lectern-env/tool": "^1.0"},
+    "config": {"platform": {"ext-intl": "1.0"}},
+    "extra": {"lectern": {"platform-overrides": {
+        "php74": {"platform": {"php": "7.4.33"}, "require": {"lectern-env/lib": "^1.0"}},
+        "php83": {"platform": {"php": "8.3.0"}, "require-dev": {"lectern-env/tool": "^3.0"}}
+    }}}
+}"#;
+
+fn composer() -> ComposerJson {
+    serde_json::from_str(COMPOSER).unwrap()
+}
+
+#[test]
+fn test_environments_are_read_from_extra() {
+    let declared = environments(&composer()).unwrap();
+    assert_eq!(
+        declared.keys().map(String::as_str).collect::<Vec<_>>(),
+        vec!["php74", "php83"]
+    );
+    assert_eq!(declared["php74"].require["lectern-env/lib"], "^1.0");
+
+    let plain: ComposerJson = serde_json::from_str(r#"{"require": {}}"#).unwrap();
+    assert!(environments(&plain).unwrap().is_empty());
+}
+
+#[test]
+fn test_apply_environment_overrides_constraints_and_platform() {
+    let legacy = apply_environment(composer(), "php74").unwrap();
+    assert_eq!(legacy.require["lectern-env/lib"], "^1.0");
+    assert_eq!(legacy.require_dev["lectern-env/tool"], "^1.0");
+    let platform = legacy.config.unwrap().platform.unwrap();
+    assert_eq!(platform["php"], "7.4.33");
+    // Existing platform settings are kept
+    assert_eq!(platform["ext-intl"], "1.0");
+
+    let modern = apply_environment(composer(), "php83").unwrap();
+    assert_eq!(modern.require["lectern-env/lib"], "^2.0");
+    assert_eq!(modern.require_dev["lectern-env/tool"], "^3.0");
+
+    let err = apply_environment(composer(), "php56").unwrap_err();
+    assert!(err.to_string().contains("php74, php83"));
+}
+
+#[test]
+fn test_environment_lock_path() {
+    let dir = Path::new("project");
+    assert_eq!(environment_lock_path(dir, None), dir.join("composer.lock"));
+    assert_eq!(
+        environment_lock_path(dir, Some("php74")),
+        dir.join("composer.php74.lock")
+    );
+}
+
+async fn seed_metadata(name: &str, versions: &[&str]) {
+    let versions: Vec<serde_json::Value> = versions
+        .iter()
+        .map(|v| serde_json::json!({"version": v, "version_normalized": format!("{v}.0")}))
+        .collect();
+    lectern::cache::cache_set_meta(&format!("p2:{name}"), serde_json::Value::Array(versions)).await;
+}
+
+#[tokio::test]
+async fn test_check_environments_reports_unsolvable_ones() {
+    // Metadata is served from the in-memory cache, so no network is needed
+    seed_metadata("lectern-env/lib", &["1.5.0", "2.1.0"]).await;
+    seed_metadata("lectern-env/tool", &["1.2.0"]).await;
+
+    let temp_dir = TempDir::new().unwrap();
+    let composer_path = temp_dir.path().join("composer.json");
+    fs::write(&composer_path, COMPOSER).unwrap();
+
+    let results = check_environments(&composer_path).await.unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].0, "php74");
+    assert!(results[0].1.is_ok());
+    // No lectern-env/tool release satisfies ^3.0
+    assert_eq!(results[1].0, "php83");
+    assert!(results[1].1.is_err());
+}
@@ -0,0 +1,90 @@
+use anyhow::Context;
+use lectern::LecternError;
+use lectern::error::{exit_code, find_lectern_error};
+
+#[path = "common/mod.rs"]
+mod common;
+use common::{ensure_lectern_binary, get_lectern_binary_path};
+
+#[test]
+fn test_exit_codes_match_composer() {
+    let conflict = LecternError::ResolutionConflict("no match".into());
+    assert_eq!(conflict.exit_code(), 2);
+    let missing = LecternError::PackageNotFound {
+        name: "vendor/missing".into(),
+        hint: String::new(),
+    };
+    assert_eq!(missing.exit_code(), 2);
+    assert_eq!(missing.to_string(), "Package vendor/missing not found");
+    let checksum = LecternError::ChecksumMismatch {
+        expected: "aa".into(),
+        actual: "bb".into(),
+    };
+    assert_eq!(checksum.exit_code(), 1);
+
+    // Anything that isn't a LecternError is a generic failure
+    assert_eq!(exit_code(&anyhow::anyhow!("boom")), 1);
+}
+
+#[test]
+fn test_lectern_error_found_through_context() {
+    let err = Err::<(), _>(LecternError::ResolutionConflict("no match".into()))
+        .context("resolve dependencies")
+        .context("install")
+        .unwrap_err();
+    assert!(matches!(
+        find_lectern_error(&err),
+        Some(LecternError::ResolutionConflict(_))
+    ));
+    assert_eq!(exit_code(&err), 2);
+}
+
+#[test]
+fn test_invalid_composer_json_is_typed() {
+    let err = lectern::io::parse_composer_json("{ not json").unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<LecternError>(),
+        Some(LecternError::InvalidComposerJson(_))
+    ));
+
+    let err = lectern::commands::self_update::verify_checksum(b"data", "0000").unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<LecternError>(),
+        Some(LecternError::ChecksumMismatch { .. })
+    ));
+}
+
+#[test]
+fn test_invalid_composer_json_exit_code() {
+    ensure_lectern_binary();
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("composer.json"), "{ not json").unwrap();
+
+    let output = std::process::Command::new(get_lectern_binary_path())
+        .arg("--working-dir")
+        .arg(dir.path())
+        .arg("install")
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1), "stderr: {stderr}");
+    assert!(stderr.contains("Invalid composer.json"), "stderr: {stderr}");
+}
+
+#[test]
+fn test_reported_failures_exit_non_zero() {
+    ensure_lectern_binary();
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("composer.json"), r#"{"require": {}}"#).unwrap();
+
+    for args in [&["dump-autoload"][..], &["init"], &["suggests"]] {
+        let output = std::process::Command::new(get_lectern_binary_path())
+            .args(args)
+            .current_dir(dir.path())
+            .output()
+            .unwrap();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert_eq!(output.status.code(), Some(1), "{args:?}: {stderr}");
+        assert!(stderr.contains("[ERROR]"), "{args:?}: {stderr}");
+    }
+}
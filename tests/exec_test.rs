@@ -0,0 +1,96 @@
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+#[path = "common/mod.rs"]
+mod common;
+use common::{ensure_lectern_binary, get_lectern_binary_path};
+
+fn project() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("composer.json"), "{}").unwrap();
+    temp_dir
+}
+
+fn lectern_exec(dir: &std::path::Path, args: &[&str]) -> std::process::Output {
+    ensure_lectern_binary();
+    Command::new(get_lectern_binary_path())
+        .arg("exec")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .expect("Failed to execute lectern exec")
+}
+
+#[test]
+fn test_available_binaries_from_bin_dir_and_package_bins() {
+    use lectern::commands::exec::available_binaries;
+
+    let temp_dir = project();
+    let bin = temp_dir.path().join("vendor").join("bin");
+    fs::create_dir_all(&bin).unwrap();
+    fs::write(bin.join("phpunit"), "#!/usr/bin/env php\n").unwrap();
+    fs::write(bin.join("phpunit.bat"), "@ECHO OFF\r\n").unwrap();
+    let pkg = temp_dir.path().join("vendor").join("acme").join("tool");
+    fs::create_dir_all(pkg.join("bin")).unwrap();
+    fs::write(pkg.join("bin").join("acme"), "#!/bin/sh\n").unwrap();
+    fs::write(
+        temp_dir.path().join("composer.lock"),
+        r#"{"content-hash": "x", "packages": [
+            {"name": "acme/tool", "version": "1.0.0", "bin": ["bin/acme", "bin/missing"]}
+        ]}"#,
+    )
+    .unwrap();
+
+    let composer = serde_json::from_str("{}").unwrap();
+    let binaries = available_binaries(&composer, temp_dir.path());
+    assert_eq!(binaries.keys().collect::<Vec<_>>(), vec!["acme", "phpunit"]);
+    assert_eq!(binaries["acme"], pkg.join("bin").join("acme"));
+    let expected = if cfg!(windows) {
+        "phpunit.bat"
+    } else {
+        "phpunit"
+    };
+    assert_eq!(binaries["phpunit"], bin.join(expected));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_exec_runs_binaries_with_arguments_and_exit_code() {
+    let temp_dir = project();
+    let bin = temp_dir.path().join("vendor").join("bin");
+    fs::create_dir_all(&bin).unwrap();
+    // Not executable, so it is run through sh
+    fs::write(
+        bin.join("check"),
+        "echo \"check $* dev=$COMPOSER_DEV_MODE\"\nexit \"${EXIT:-0}\"\n",
+    )
+    .unwrap();
+
+    let output = lectern_exec(temp_dir.path(), &["check", "--", "--filter", "Foo"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("check --filter Foo dev=1"), "{stdout}");
+
+    ensure_lectern_binary();
+    let output = Command::new(get_lectern_binary_path())
+        .args(["exec", "check"])
+        .env("EXIT", "3")
+        .current_dir(temp_dir.path())
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+
+    let output = lectern_exec(temp_dir.path(), &["--list"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("- check"));
+}
+
+#[test]
+fn test_exec_unknown_binary_fails() {
+    let temp_dir = project();
+    let output = lectern_exec(temp_dir.path(), &["phpunit"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Binary 'phpunit' not found"), "{stderr}");
+}
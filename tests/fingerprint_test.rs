@@ -0,0 +1,105 @@
+use lectern::commands::fingerprint::{Fingerprint, lock_hash};
+use lectern::models::model::Lock;
+use serde_json::{Value, json};
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+#[path = "common/mod.rs"]
+mod common;
+use common::{ensure_lectern_binary, get_lectern_binary_path};
+
+fn lock(content_hash: &str, monolog: &str) -> Lock {
+    serde_json::from_value(json!({
+        "content-hash": content_hash,
+        "packages": [
+            {"name": "psr/log", "version": "3.0.0",
+             "dist": {"type": "zip", "url": "https://example.com/log.zip", "reference": "abc"}},
+            {"name": "monolog/monolog", "version": monolog}
+        ],
+        "packages-dev": [{"name": "phpunit/phpunit", "version": "10.5.0"}],
+        "platform-overrides": {"php": "8.2.10"}
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_lock_hash_tracks_installed_packages_only() {
+    let base = lock("aaa", "3.5.0");
+    // content-hash and package order don't matter
+    let mut reordered = lock("bbb", "3.5.0");
+    reordered.packages.reverse();
+    assert_eq!(lock_hash(&base, true), lock_hash(&reordered, true));
+
+    assert_ne!(
+        lock_hash(&base, true),
+        lock_hash(&lock("aaa", "3.6.0"), true)
+    );
+    assert_ne!(lock_hash(&base, true), lock_hash(&base, false));
+}
+
+#[test]
+fn test_fingerprint_components() {
+    let base = lock("aaa", "3.5.0");
+    let fingerprint = Fingerprint::compute(&base, true, Some("8.2"), "linux-x86_64");
+    assert_eq!(fingerprint.fingerprint.len(), 64);
+    assert!(fingerprint.fingerprint.starts_with(&fingerprint.short));
+    assert_eq!(fingerprint.short.len(), 12);
+    assert_eq!(fingerprint.components.lock, lock_hash(&base, true));
+
+    let other_php = Fingerprint::compute(&base, true, Some("8.3"), "linux-x86_64");
+    assert_eq!(other_php.components.lock, fingerprint.components.lock);
+    assert_ne!(other_php.fingerprint, fingerprint.fingerprint);
+
+    let other_platform = Fingerprint::compute(&base, true, Some("8.2"), "macos-aarch64");
+    assert_ne!(other_platform.fingerprint, fingerprint.fingerprint);
+}
+
+#[test]
+fn test_fingerprint_command() {
+    ensure_lectern_binary();
+
+    let temp_dir = TempDir::new().unwrap();
+    let temp_path = temp_dir.path();
+    fs::write(
+        temp_path.join("composer.lock"),
+        serde_json::to_string(&lock("aaa", "3.5.0")).unwrap(),
+    )
+    .unwrap();
+
+    let run = |args: &[&str]| {
+        let output = Command::new(get_lectern_binary_path())
+            .arg("fingerprint")
+            .args(args)
+            .current_dir(temp_path)
+            .output()
+            .expect("Failed to execute lectern fingerprint");
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+
+    let full = run(&[]);
+    assert_eq!(full.len(), 64, "{full}");
+    let short = run(&["--short"]);
+    assert!(full.starts_with(&short), "{short}");
+
+    let json: Value = serde_json::from_str(&run(&["--format", "json"])).unwrap();
+    assert_eq!(json["fingerprint"], full);
+    assert_eq!(json["short"], short);
+    for component in ["lock", "php", "platform"] {
+        assert_eq!(json["components"][component].as_str().unwrap().len(), 64);
+    }
+}
+
+#[test]
+fn test_fingerprint_command_without_lock() {
+    ensure_lectern_binary();
+
+    let temp_dir = TempDir::new().unwrap();
+    let output = Command::new(get_lectern_binary_path())
+        .arg("fingerprint")
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute lectern fingerprint");
+    assert!(!output.status.success());
+}
@@ -1,3 +1,9 @@
+use lectern::commands::funding::{
+    FundingLink, FundingReport, funding_for_version, funding_platform,
+};
+use lectern::resolver::packagist::P2Version;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
 use std::fs;
 use std::process::Command;
 use tempfile::TempDir;
@@ -45,6 +51,116 @@ fn test_fund_no_lock() {
         .output()
         .expect("Failed to execute lectern fund");
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    assert!(stdout.contains("composer.lock") || output.status.success());
+    // A missing lock is a failure, reported on stderr
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stderr.contains("composer.lock"));
+}
+#[test]
+fn test_funding_report_groups_by_platform() {
+    let entries: BTreeMap<String, Vec<Value>> = BTreeMap::from([
+        (
+            "symfony/console".to_string(),
+            vec![
+                json!({"type": "github", "url": "https://github.com/fabpot"}),
+                json!({"type": "tidelift", "url": "https://tidelift.com/funding/github/packagist/symfony/symfony"}),
+            ],
+        ),
+        (
+            "symfony/process".to_string(),
+            vec![json!({"type": "github", "url": "https://github.com/fabpot"})],
+        ),
+        (
+            "acme/lib".to_string(),
+            vec![json!({"type": "custom", "url": "https://opencollective.com/acme"})],
+        ),
+        ("acme/plain".to_string(), vec![]),
+    ]);
+
+    let report = FundingReport::from_entries(&entries);
+    assert_eq!(
+        report.platforms.keys().collect::<Vec<_>>(),
+        vec!["GitHub Sponsors", "OpenCollective", "Tidelift"]
+    );
+    assert_eq!(
+        report.platforms["GitHub Sponsors"],
+        vec![FundingLink {
+            url: "https://github.com/fabpot".to_string(),
+            packages: vec!["symfony/console".to_string(), "symfony/process".to_string()],
+        }]
+    );
+    assert_eq!(
+        report.platforms["OpenCollective"][0].packages,
+        vec!["acme/lib"]
+    );
+    assert_eq!(report.unfunded, vec!["acme/plain"]);
+    assert_eq!(
+        funding_platform("patreon", "https://patreon.com/x"),
+        "Patreon"
+    );
+    assert_eq!(
+        funding_platform("liberapay", "https://liberapay.com/x"),
+        "liberapay"
+    );
+}
+
+#[test]
+fn test_funding_for_version_follows_minified_metadata() {
+    let versions: Vec<P2Version> = serde_json::from_value(json!([
+        {"version": "v2.0.0", "funding": [{"type": "github", "url": "https://github.com/new"}]},
+        {"version": "v1.1.0"},
+        {"version": "v1.0.0", "funding": [{"type": "github", "url": "https://github.com/old"}]}
+    ]))
+    .unwrap();
+
+    let url = |version: &str| {
+        funding_for_version(&versions, version).map(|funding| funding[0]["url"].clone())
+    };
+    assert_eq!(url("1.1.0"), Some(json!("https://github.com/new")));
+    assert_eq!(url("v1.0.0"), Some(json!("https://github.com/old")));
+    assert_eq!(url("3.0.0"), None);
+}
+
+#[test]
+fn test_fund_json_uses_lock_and_vendor_funding() {
+    ensure_lectern_binary();
+
+    let temp_dir = TempDir::new().unwrap();
+    let temp_path = temp_dir.path();
+    fs::write(temp_path.join("composer.json"), r#"{"name": "test/fund"}"#).unwrap();
+    let lock = json!({
+        "content-hash": "",
+        "packages": [{
+            "name": "acme/locked",
+            "version": "1.0.0",
+            "funding": [{"type": "github", "url": "https://github.com/acme"}]
+        }],
+        "packages-dev": [{"name": "acme/installed", "version": "1.0.0"}]
+    });
+    fs::write(temp_path.join("composer.lock"), lock.to_string()).unwrap();
+    let installed = temp_path.join("vendor/acme/installed");
+    fs::create_dir_all(&installed).unwrap();
+    fs::write(
+        installed.join("composer.json"),
+        r#"{"funding": [{"type": "tidelift", "url": "https://tidelift.com/acme"}]}"#,
+    )
+    .unwrap();
+
+    let output = Command::new(get_lectern_binary_path())
+        .args(["fund", "--format", "json"])
+        .current_dir(temp_path)
+        .output()
+        .expect("Failed to execute lectern fund");
+
+    assert!(output.status.success());
+    let report: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        report["platforms"]["GitHub Sponsors"][0]["packages"],
+        json!(["acme/locked"])
+    );
+    assert_eq!(
+        report["platforms"]["Tidelift"][0]["url"],
+        json!("https://tidelift.com/acme")
+    );
+    assert_eq!(report["unfunded"], json!([]));
 }
@@ -0,0 +1,90 @@
+use lectern::commands::global::{link_binaries, path_guidance};
+use std::ffi::OsString;
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+#[path = "common/mod.rs"]
+mod common;
+use common::{ensure_lectern_binary, get_lectern_binary_path};
+
+#[test]
+fn test_link_binaries() {
+    let temp_dir = TempDir::new().unwrap();
+    let project = temp_dir.path().join("global");
+    let bin_dir = temp_dir.path().join("bin");
+    fs::create_dir_all(&project).unwrap();
+    fs::write(
+        project.join("composer.lock"),
+        r#"{
+            "content-hash": "",
+            "packages": [
+                {"name": "acme/tool", "version": "1.0.0", "bin": ["bin/tool"]},
+                {"name": "acme/other", "version": "1.0.0", "bin": ["bin/tool", "scripts/other", "bin/missing"]},
+                {"name": "acme/lib", "version": "1.0.0"}
+            ]
+        }"#,
+    )
+    .unwrap();
+    for file in [
+        "acme/tool/bin/tool",
+        "acme/other/bin/tool",
+        "acme/other/scripts/other",
+    ] {
+        let path = project.join("vendor").join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "#!/usr/bin/env php\n<?php\n").unwrap();
+    }
+    fs::create_dir_all(&bin_dir).unwrap();
+    fs::write(bin_dir.join("stale"), "").unwrap();
+
+    let linked = link_binaries(&project, &bin_dir).unwrap();
+    assert_eq!(linked, vec!["other", "tool"]);
+    assert!(!bin_dir.join("stale").exists());
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let tool = project.join("vendor/acme/tool/bin/tool");
+        assert_eq!(fs::read_link(bin_dir.join("tool")).unwrap(), tool);
+        assert_ne!(fs::metadata(&tool).unwrap().permissions().mode() & 0o111, 0);
+    }
+
+    // Without a lock nothing stays linked
+    fs::remove_file(project.join("composer.lock")).unwrap();
+    assert!(link_binaries(&project, &bin_dir).unwrap().is_empty());
+    assert_eq!(fs::read_dir(&bin_dir).unwrap().count(), 0);
+}
+
+#[test]
+fn test_path_guidance() {
+    let temp_dir = TempDir::new().unwrap();
+    let bin_dir = temp_dir.path().join("bin");
+    let path = std::env::join_paths([temp_dir.path().join("other"), bin_dir.clone()]).unwrap();
+    assert_eq!(path_guidance(&bin_dir, Some(&path)), None);
+
+    let path = OsString::from(temp_dir.path().join("other"));
+    let guidance = path_guidance(&bin_dir, Some(&path)).unwrap();
+    assert!(guidance.contains(&bin_dir.display().to_string()));
+    assert!(path_guidance(&bin_dir, None).is_some());
+}
+
+#[test]
+fn test_global_show_creates_global_project() {
+    ensure_lectern_binary();
+    let config_home = TempDir::new().unwrap();
+
+    let output = Command::new(get_lectern_binary_path())
+        .args(["global", "show"])
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .output()
+        .expect("Failed to execute lectern global show");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let global = config_home.path().join("lectern/global");
+    assert!(stdout.contains(&format!(
+        "Changed current directory to {}",
+        global.display()
+    )));
+    assert!(global.join("composer.json").exists());
+}
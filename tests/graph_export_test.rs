@@ -0,0 +1,143 @@
+use lectern::commands::graph::GraphExport;
+use lectern::models::model::{ComposerJson, Lock};
+use serde_json::json;
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+#[path = "common/mod.rs"]
+mod common;
+use common::{ensure_lectern_binary, get_lectern_binary_path};
+
+fn lock() -> Lock {
+    serde_json::from_value(json!({
+        "content-hash": "",
+        "packages": [
+            {"name": "acme/app-kit", "version": "1.2.0", "require": {"acme/util": "^2.0 || ^3.0", "php": ">=8.1"}},
+            {"name": "acme/util", "version": "3.0.1"}
+        ],
+        "packages-dev": [
+            {"name": "acme/test-kit", "version": "0.9.0", "require": {"acme/util": "^3.0"}}
+        ]
+    }))
+    .unwrap()
+}
+
+fn composer() -> ComposerJson {
+    serde_json::from_value(json!({
+        "name": "acme/project",
+        "require": {"acme/app-kit": "^1.0", "php": ">=8.1"},
+        "require-dev": {"acme/test-kit": "^0.9"}
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_graph_export_nodes_and_edges() {
+    let export = GraphExport::from_lock(&lock(), Some(&composer()), true);
+    let names: Vec<_> = export.nodes.iter().map(|node| node.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec!["acme/project", "acme/app-kit", "acme/util", "acme/test-kit"]
+    );
+    assert!(export.nodes[0].root);
+    assert!(export.nodes[3].dev);
+    let edges: Vec<_> = export
+        .edges
+        .iter()
+        .map(|edge| (edge.from.as_str(), edge.to.as_str(), edge.dev))
+        .collect();
+    // Platform requirements are not part of the graph
+    assert_eq!(
+        edges,
+        vec![
+            ("acme/project", "acme/app-kit", false),
+            ("acme/project", "acme/test-kit", true),
+            ("acme/app-kit", "acme/util", false),
+            ("acme/test-kit", "acme/util", true),
+        ]
+    );
+
+    let export = GraphExport::from_lock(&lock(), Some(&composer()), false);
+    assert_eq!(export.nodes.len(), 3);
+    assert!(export.edges.iter().all(|edge| !edge.dev));
+}
+
+#[test]
+fn test_graph_export_formats() {
+    let export = GraphExport::from_lock(&lock(), Some(&composer()), true);
+
+    let dot = export.to_dot();
+    assert!(dot.starts_with("digraph dependencies {"));
+    assert!(dot.contains(r#""acme/project" [label="acme/project", style=bold];"#));
+    assert!(dot.contains(r#""acme/util" [label="acme/util\n3.0.1"];"#));
+    assert!(dot.contains(r#""acme/app-kit" -> "acme/util" [label="^2.0 || ^3.0"];"#));
+    assert!(dot.contains(r#""acme/project" -> "acme/test-kit" [label="^0.9", style=dashed];"#));
+
+    let mermaid = export.to_mermaid();
+    assert!(mermaid.starts_with("graph LR"));
+    assert!(mermaid.contains(r#"n1["acme/app-kit 1.2.0"]"#));
+    // `|` would end the edge label
+    assert!(mermaid.contains(r#"n1 -->|"^2.0 #124;#124; ^3.0"| n2"#));
+    assert!(mermaid.contains(r#"n0 -.->|"^0.9"| n3"#));
+    assert!(mermaid.contains("class n3 dev"));
+
+    let graph = export.to_json();
+    assert_eq!(graph["root"], "acme/project");
+    assert_eq!(
+        graph["packages"]["acme/project"]["require-dev"],
+        json!({"acme/test-kit": "^0.9"})
+    );
+    assert_eq!(
+        graph["packages"]["acme/test-kit"]["require"],
+        json!({"acme/util": "^3.0"})
+    );
+    assert_eq!(graph["packages"]["acme/util"]["version"], "3.0.1");
+}
+
+#[test]
+fn test_graph_command() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    fs::write(
+        dir.join("composer.json"),
+        serde_json::to_string(&json!({"name": "acme/project", "require": {"acme/util": "^3.0"}}))
+            .unwrap(),
+    )
+    .unwrap();
+    fs::write(
+        dir.join("composer.lock"),
+        r#"{"content-hash": "", "packages": [{"name": "acme/util", "version": "3.0.1"}]}"#,
+    )
+    .unwrap();
+    ensure_lectern_binary();
+    let lectern = |args: &[&str]| {
+        Command::new(get_lectern_binary_path())
+            .arg("graph")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .expect("Failed to execute lectern graph")
+    };
+
+    let output = lectern(&[]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains(r#""acme/project" -> "acme/util""#),
+        "{stdout}"
+    );
+
+    let output = lectern(&["--format", "json", "-o", "graph.json"]);
+    assert!(output.status.success());
+    let graph: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.join("graph.json")).unwrap()).unwrap();
+    assert_eq!(
+        graph["packages"]["acme/project"]["require"],
+        json!({"acme/util": "^3.0"})
+    );
+
+    let output = lectern(&["--format", "svg"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unknown format: svg"));
+}
@@ -0,0 +1,80 @@
+use lectern::graph::DependencyGraph;
+use lectern::models::model::Lock;
+use serde_json::json;
+use std::collections::BTreeSet;
+
+fn lock(packages: serde_json::Value) -> Lock {
+    serde_json::from_value(json!({
+        "content-hash": "",
+        "packages": packages,
+        "packages-dev": []
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_cycles() {
+    let graph = DependencyGraph::from_lock(&lock(json!([
+        {"name": "acme/a", "version": "1.0.0", "require": {"acme/b": "^1.0", "php": ">=8.1"}},
+        {"name": "acme/b", "version": "1.0.0", "require": {"acme/c": "^1.0"}},
+        {"name": "acme/c", "version": "1.0.0", "require": {"acme/a": "^1.0"}},
+        {"name": "acme/self", "version": "2.0.0", "require": {"acme/self": "*"}},
+        {"name": "acme/leaf", "version": "1.0.0", "require": {"acme/missing": "^1.0"}}
+    ])));
+
+    assert_eq!(
+        graph.cycles(),
+        vec![
+            vec!["acme/a", "acme/b", "acme/c", "acme/a"],
+            vec!["acme/self", "acme/self"]
+        ]
+    );
+    // Platform and unlocked requirements are not edges
+    assert_eq!(
+        graph.dependencies("acme/a"),
+        &[("acme/b".to_string(), "^1.0".to_string())]
+    );
+    assert!(graph.dependencies("acme/leaf").is_empty());
+}
+
+#[test]
+fn test_components_within_orders_dependencies_first() {
+    let graph = DependencyGraph::from_lock(&lock(json!([
+        {"name": "acme/app", "version": "1.0.0", "require": {"acme/a": "^1.0"}},
+        {"name": "acme/a", "version": "1.0.0", "require": {"acme/b": "^1.0"}},
+        {"name": "acme/b", "version": "1.0.0", "require": {"acme/a": "^1.0", "acme/base": "^1.0"}},
+        {"name": "acme/base", "version": "1.0.0"}
+    ])));
+    let names: BTreeSet<&str> = ["acme/app", "acme/a", "acme/b", "acme/base"].into();
+
+    assert_eq!(
+        graph.components_within(&names),
+        vec![
+            vec!["acme/base".to_string()],
+            vec!["acme/a".to_string(), "acme/b".to_string()],
+            vec!["acme/app".to_string()]
+        ]
+    );
+}
+
+#[test]
+fn test_tree_lines_abort_on_cycles() {
+    let graph = DependencyGraph::from_lock(&lock(json!([
+        {"name": "acme/app", "version": "1.2.0",
+         "require": {"acme/a": "^1.0", "acme/log": "^2.0"}},
+        {"name": "acme/a", "version": "1.0.0", "require": {"acme/b": "^1.0"}},
+        {"name": "acme/b", "version": "1.0.0", "require": {"acme/a": "^1.0"}},
+        {"name": "acme/log", "version": "2.1.0"}
+    ])));
+
+    assert_eq!(
+        graph.tree_lines("acme/app"),
+        vec![
+            "acme/app 1.2.0",
+            "├──acme/a ^1.0",
+            "│  └──acme/b ^1.0",
+            "│     └──acme/a ^1.0 (circular dependency aborted here)",
+            "└──acme/log ^2.0"
+        ]
+    );
+}
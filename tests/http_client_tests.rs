@@ -1,4 +1,4 @@
-use lectern::resolver::http_client::get_client;
+use lectern::http::get_client;
 
 #[test]
 fn test_http_client_initialization() {
@@ -51,3 +51,65 @@ fn test_http_client_has_compression() {
     // We're just checking it doesn't panic and is valid
     assert!(std::ptr::addr_of!(client) as usize != 0);
 }
+
+fn auth_headers(auth: &lectern::auth::AuthConfig, url: &str) -> reqwest::header::HeaderMap {
+    lectern::http::authenticate_with(auth, reqwest::Client::new().get(url), url)
+        .build()
+        .unwrap()
+        .headers()
+        .clone()
+}
+
+#[test]
+fn test_authenticate_with_sends_credentials_by_host() {
+    let mut auth = lectern::auth::AuthConfig::default();
+    auth.github_oauth.insert("github.com".into(), "gh".into());
+    auth.gitlab_token
+        .insert("gitlab.example.com".into(), (None, "glpat".into()));
+    auth.bearer.insert("bitbucket.org".into(), "bb".into());
+    auth.http_basic.insert(
+        "repo.example.com".into(),
+        lectern::auth::HttpCredentials {
+            username: "user".into(),
+            password: "pass".into(),
+        },
+    );
+
+    let github = auth_headers(&auth, "https://github.com/vendor/pkg/zipball/1.0");
+    assert_eq!(github["authorization"], "token gh");
+    let gitlab = auth_headers(&auth, "https://gitlab.example.com/api/v4/projects/1");
+    assert_eq!(gitlab["private-token"], "glpat");
+    let bitbucket = auth_headers(&auth, "https://bitbucket.org/vendor/pkg.zip");
+    assert_eq!(bitbucket["authorization"], "Bearer bb");
+    let basic = auth_headers(&auth, "https://repo.example.com/packages.json");
+    assert_eq!(basic["authorization"], "Basic dXNlcjpwYXNz");
+
+    let anonymous = auth_headers(&auth, "https://repo.packagist.org/p2/vendor/pkg.json");
+    assert!(!anonymous.contains_key("authorization"));
+}
+
+#[test]
+fn test_tls_settings_from_config() {
+    use lectern::http::TlsSettings;
+    use serde_json::json;
+
+    let dir = tempfile::tempdir().unwrap();
+    let none = TlsSettings::from_config(None, dir.path()).unwrap();
+    assert!(none.certificates.is_empty());
+    let unset = TlsSettings::from_config(Some(&json!({"cafile": ""})), dir.path()).unwrap();
+    assert!(unset.certificates.is_empty());
+
+    let missing = TlsSettings::from_config(Some(&json!({"cafile": "missing.pem"})), dir.path());
+    assert!(missing.is_err());
+
+    std::fs::write(dir.path().join("empty.pem"), "not a certificate\n").unwrap();
+    let empty = TlsSettings::from_config(Some(&json!({"cafile": "empty.pem"})), dir.path());
+    assert!(empty.is_err());
+
+    // capath only picks up certificate files
+    let capath = dir.path().join("certs");
+    std::fs::create_dir(&capath).unwrap();
+    std::fs::write(capath.join("README"), "not a certificate\n").unwrap();
+    let certs = TlsSettings::from_config(Some(&json!({"capath": "certs"})), dir.path()).unwrap();
+    assert!(certs.certificates.is_empty());
+}
@@ -15,9 +15,9 @@ fn test_get_cached_package_path() {
     let name = "vendor/package";
     let version = "1.0.0";
     let url = "https://example.com/package.zip";
-    
+
     let path = get_cached_package_path(name, version, url);
-    
+
     // Should be a valid path
     assert!(path.to_string_lossy().ends_with(".zip"));
     assert!(path.to_string_lossy().contains("packages"));
@@ -28,10 +28,10 @@ fn test_get_cached_package_path_consistency() {
     let name = "vendor/package";
     let version = "1.0.0";
     let url = "https://example.com/package.zip";
-    
+
     let path1 = get_cached_package_path(name, version, url);
     let path2 = get_cached_package_path(name, version, url);
-    
+
     // Same inputs should produce same path
     assert_eq!(path1, path2);
 }
@@ -40,7 +40,7 @@ fn test_get_cached_package_path_consistency() {
 fn test_get_cached_package_path_different_inputs() {
     let path1 = get_cached_package_path("vendor/package1", "1.0.0", "https://example.com/1.zip");
     let path2 = get_cached_package_path("vendor/package2", "1.0.0", "https://example.com/2.zip");
-    
+
     // Different inputs should produce different paths
     assert_ne!(path1, path2);
 }
@@ -50,7 +50,7 @@ fn test_extract_archive_with_invalid_path() {
     let temp_dir = TempDir::new().unwrap();
     let dest = temp_dir.path();
     let nonexistent = Path::new("/nonexistent/file.zip");
-    
+
     let result = extract_archive_ultra_fast(nonexistent, dest);
     assert!(result.is_err(), "Should fail with nonexistent archive");
 }
@@ -59,11 +59,11 @@ fn test_extract_archive_with_invalid_path() {
 fn test_extract_zip_with_invalid_archive() {
     let temp_dir = TempDir::new().unwrap();
     let dest = temp_dir.path();
-    
+
     // Create an invalid zip file
     let invalid_zip = temp_dir.path().join("invalid.zip");
     fs::write(&invalid_zip, b"not a zip file").unwrap();
-    
+
     let result = extract_zip_ultra_fast(&invalid_zip, dest);
     assert!(result.is_err(), "Should fail with invalid zip");
 }
@@ -72,11 +72,11 @@ fn test_extract_zip_with_invalid_archive() {
 fn test_extract_tar_gz_with_invalid_archive() {
     let temp_dir = TempDir::new().unwrap();
     let dest = temp_dir.path();
-    
+
     // Create an invalid tar.gz file
     let invalid_tar = temp_dir.path().join("invalid.tar.gz");
     fs::write(&invalid_tar, b"not a tar.gz file").unwrap();
-    
+
     let result = extract_tar_gz_ultra_fast(&invalid_tar, dest);
     assert!(result.is_err(), "Should fail with invalid tar.gz");
 }
@@ -85,7 +85,7 @@ fn test_extract_tar_gz_with_invalid_archive() {
 async fn test_copy_local_path_nonexistent() {
     let temp_dir = TempDir::new().unwrap();
     let dest = temp_dir.path().join("dest");
-    
+
     let result = copy_local_path_optimized("/nonexistent/path", &dest).await;
     assert!(result.is_err(), "Should fail with nonexistent source");
 }
@@ -95,9 +95,1502 @@ async fn test_copy_local_path_file_not_dir() {
     let temp_dir = TempDir::new().unwrap();
     let temp_file = temp_dir.path().join("file.txt");
     fs::write(&temp_file, "test").unwrap();
-    
+
     let dest = temp_dir.path().join("dest");
-    
+
     let result = copy_local_path_optimized(temp_file.to_str().unwrap(), &dest).await;
-    assert!(result.is_err(), "Should fail when source is not a directory");
+    assert!(
+        result.is_err(),
+        "Should fail when source is not a directory"
+    );
+}
+
+fn installed(name: &str) -> lectern::installer::InstalledPackage {
+    lectern::installer::InstalledPackage {
+        name: name.to_string(),
+        version: "1.0.0".to_string(),
+        path: format!("vendor/{name}").into(),
+    }
+}
+
+#[test]
+fn test_group_by_vendor() {
+    use lectern::installer::installer_summary::group_by_vendor;
+
+    let packages = vec![
+        installed("symfony/console"),
+        installed("monolog/monolog"),
+        installed("symfony/process"),
+        installed("symfony/finder"),
+    ];
+
+    let groups = group_by_vendor(&packages);
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups["monolog"].len(), 1);
+
+    let symfony: Vec<&str> = groups["symfony"].iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(
+        symfony,
+        vec!["symfony/console", "symfony/finder", "symfony/process"]
+    );
+}
+
+#[test]
+fn test_summary_collapses_large_installs() {
+    use lectern::installer::installer_summary::{
+        SUMMARY_COLLAPSE_THRESHOLD, should_expand_summary,
+    };
+
+    assert!(should_expand_summary(5, 0));
+    assert!(!should_expand_summary(SUMMARY_COLLAPSE_THRESHOLD + 1, 0));
+    // --verbose always expands the full list
+    assert!(should_expand_summary(300, 1));
+}
+
+fn locked(name: &str, version: &str, reference: &str) -> lectern::models::model::LockedPackage {
+    lectern::models::model::LockedPackage {
+        name: name.to_string(),
+        version: version.to_string(),
+        dist: Some(lectern::models::model::DistInfo {
+            dist_type: "zip".to_string(),
+            url: format!("https://example.com/{name}.zip"),
+            reference: reference.to_string(),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_install_state_detects_current_packages() {
+    use lectern::installer::InstallState;
+
+    let temp_dir = TempDir::new().unwrap();
+    let vendor = temp_dir.path().join("vendor");
+    fs::create_dir_all(vendor.join("psr/log")).unwrap();
+
+    let pkg = locked("psr/log", "3.0.0", "abc123");
+    let mut state = InstallState::default();
+    assert!(!state.is_current(&pkg, &vendor));
+
+    state.record(&pkg);
+    state.record(&locked("acme/gone", "1.0.0", "def456"));
+    state.save(&vendor).unwrap();
+
+    let loaded = InstallState::load(&vendor);
+    assert!(loaded.is_current(&pkg, &vendor));
+    // Records for packages no longer in vendor are dropped on save
+    assert!(!loaded.packages.contains_key("acme/gone"));
+
+    // Same version with a new reference (e.g. a moved dev branch) must reinstall
+    assert!(!loaded.is_current(&locked("psr/log", "3.0.0", "fff999"), &vendor));
+    assert!(!loaded.is_current(&locked("psr/log", "3.0.1", "abc123"), &vendor));
+
+    fs::remove_dir_all(vendor.join("psr/log")).unwrap();
+    assert!(!loaded.is_current(&pkg, &vendor));
+}
+
+#[test]
+fn test_install_state_requires_reference() {
+    use lectern::installer::InstallState;
+
+    let temp_dir = TempDir::new().unwrap();
+    let vendor = temp_dir.path().join("vendor");
+    fs::create_dir_all(vendor.join("acme/local")).unwrap();
+
+    let pkg = locked("acme/local", "dev-main", "");
+    let mut state = InstallState::default();
+    state.record(&pkg);
+    assert!(!state.is_current(&pkg, &vendor));
+    assert_eq!(InstallState::load(&vendor), InstallState::default());
+}
+
+#[test]
+fn test_prefers_source_for_branches() {
+    use lectern::installer::prefers_source;
+
+    let with_source = |name: &str, version: &str| {
+        let mut pkg = locked(name, version, "abc123");
+        pkg.source = Some(lectern::models::model::SourceInfo {
+            source_type: "git".to_string(),
+            url: format!("https://github.com/{name}.git"),
+            reference: "abc123".to_string(),
+            mirrors: None,
+        });
+        pkg
+    };
+    let branch = with_source("acme/framework", "dev-main");
+    let release = with_source("psr/log", "3.0.0");
+
+    assert!(prefers_source(&branch, false, None));
+    assert!(!prefers_source(&release, false, None));
+    assert!(prefers_source(&release, true, None));
+    // Without a git source there is nothing to clone
+    assert!(!prefers_source(
+        &locked("acme/framework", "dev-main", "abc123"),
+        true,
+        None
+    ));
+
+    let dist = serde_json::json!("dist");
+    assert!(!prefers_source(&branch, false, Some(&dist)));
+    let patterns = serde_json::json!({"acme/*": "dist", "*": "source"});
+    assert!(!prefers_source(&branch, false, Some(&patterns)));
+    assert!(prefers_source(&release, false, Some(&patterns)));
+}
+
+#[test]
+fn test_is_ssh_url() {
+    use lectern::installer::installer_ssh::is_ssh_url;
+
+    assert!(is_ssh_url("git@github.com:vendor/repo.git"));
+    assert!(is_ssh_url("ssh://git@gitlab.com/vendor/repo.git"));
+    assert!(!is_ssh_url("https://github.com/vendor/repo.git"));
+    assert!(!is_ssh_url("/srv/git/repo.git"));
+    assert!(!is_ssh_url("../packages/local"));
+}
+
+#[test]
+fn test_ssh_options_from_env() {
+    use lectern::installer::installer_ssh::{HostKeyPolicy, SshOptions};
+
+    let home = TempDir::new().unwrap();
+    let ssh_dir = home.path().join(".ssh");
+    fs::create_dir_all(&ssh_dir).unwrap();
+    fs::write(ssh_dir.join("id_rsa"), "key").unwrap();
+    fs::write(ssh_dir.join("id_ed25519"), "key").unwrap();
+
+    let env = |name: &str| match name {
+        "LECTERN_SSH_KEY" => Some("/keys/deploy".to_string()),
+        "LECTERN_SSH_HOST_KEY_CHECK" => Some("off".to_string()),
+        "SSH_AUTH_SOCK" => Some("/tmp/agent.sock".to_string()),
+        _ => None,
+    };
+    let options = SshOptions::from_env_with(env, Some(home.path()));
+    assert_eq!(
+        options.key_paths,
+        vec![
+            Path::new("/keys/deploy").to_path_buf(),
+            ssh_dir.join("id_ed25519"),
+            ssh_dir.join("id_rsa"),
+        ]
+    );
+    assert!(options.use_agent);
+    assert_eq!(options.host_key_policy, HostKeyPolicy::AcceptAll);
+
+    let command = options.git_ssh_command().unwrap();
+    assert!(command.starts_with("ssh -i /keys/deploy -i "));
+    assert!(command.contains("StrictHostKeyChecking=no"));
+
+    let quoted = SshOptions::from_env_with(
+        |name| (name == "LECTERN_SSH_KEY").then(|| "/keys/o'brien key".to_string()),
+        None,
+    );
+    assert_eq!(
+        shlex::split(&quoted.git_ssh_command().unwrap()).unwrap()[..3],
+        ["ssh", "-i", "/keys/o'brien key"]
+    );
+
+    let defaults = SshOptions::from_env_with(|_| None, None);
+    assert_eq!(defaults.host_key_policy, HostKeyPolicy::Strict);
+    assert!(!defaults.use_agent);
+    assert_eq!(defaults.git_ssh_command(), None);
+}
+
+#[test]
+fn test_host_key_policy_leaves_x509_certificates_to_libgit2() {
+    use git2::CertificateCheckStatus::{CertificateOk, CertificatePassthrough};
+    use lectern::installer::installer_ssh::HostKeyPolicy;
+
+    assert!(matches!(
+        HostKeyPolicy::AcceptAll.certificate_status(true),
+        CertificateOk
+    ));
+    // An x509 certificate of an HTTPS remote is never accepted blindly
+    assert!(matches!(
+        HostKeyPolicy::AcceptAll.certificate_status(false),
+        CertificatePassthrough
+    ));
+    assert!(matches!(
+        HostKeyPolicy::Strict.certificate_status(true),
+        CertificatePassthrough
+    ));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_git_credential_helper_answers_only_its_host() {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let fill = |request: &str| {
+        let mut child = Command::new("git")
+            .args(["-c", "credential.helper=", "-c", GIT_CREDENTIAL_HELPER])
+            .args(["credential", "fill"])
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .env("LECTERN_GIT_HOST", "git.example.com")
+            .env("LECTERN_GIT_USERNAME", "deployer")
+            .env("LECTERN_GIT_PASSWORD", "s3cret")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(request.as_bytes())
+            .unwrap();
+        String::from_utf8(child.wait_with_output().unwrap().stdout).unwrap()
+    };
+
+    assert!(fill("protocol=https\nhost=Git.Example.com:443\n\n").contains("password=s3cret"));
+    assert!(!fill("protocol=https\nhost=evil.example.net\n\n").contains("s3cret"));
+    assert!(!fill("protocol=http\nhost=git.example.com\n\n").contains("s3cret"));
+}
+
+#[test]
+fn test_is_git_reference() {
+    assert!(is_git_reference("0123456789abcdef0123456789abcdef01234567"));
+    assert!(is_git_reference("v1.2.3"));
+    assert!(is_git_reference("feature/login-form"));
+    assert!(!is_git_reference("--upload-pack=touch /tmp/pwned"));
+    assert!(!is_git_reference("-b"));
+    assert!(!is_git_reference("main branch"));
+    assert!(!is_git_reference("main..evil"));
+    assert!(!is_git_reference("HEAD~1"));
+    assert!(!is_git_reference(""));
+}
+
+#[test]
+fn test_parse_cache_size() {
+    use lectern::installer::installer_cache::parse_cache_size;
+
+    assert_eq!(parse_cache_size("300MiB"), Some(300 * 1024 * 1024));
+    assert_eq!(parse_cache_size("2G"), Some(2 * 1024 * 1024 * 1024));
+    assert_eq!(parse_cache_size("512 kb"), Some(512 * 1024));
+    assert_eq!(parse_cache_size("1048576"), Some(1048576));
+    assert_eq!(parse_cache_size("1.5m"), Some(1572864));
+    assert_eq!(parse_cache_size("lots"), None);
+    assert_eq!(parse_cache_size("10T"), None);
+}
+
+#[test]
+fn test_cache_files_maxsize_from_composer_json() {
+    use lectern::installer::installer_cache::{DEFAULT_CACHE_FILES_MAXSIZE, cache_files_maxsize};
+
+    let temp_dir = TempDir::new().unwrap();
+    assert_eq!(
+        cache_files_maxsize(temp_dir.path()),
+        DEFAULT_CACHE_FILES_MAXSIZE
+    );
+
+    fs::write(
+        temp_dir.path().join("composer.json"),
+        r#"{"config": {"cache-files-maxsize": "300MiB"}}"#,
+    )
+    .unwrap();
+    assert_eq!(cache_files_maxsize(temp_dir.path()), 300 * 1024 * 1024);
+}
+
+#[test]
+fn test_evict_archives_removes_least_recently_used() {
+    use lectern::installer::installer_cache::evict_archives;
+    use std::time::{Duration, SystemTime};
+
+    let temp_dir = TempDir::new().unwrap();
+    let cache = temp_dir.path();
+    let now = SystemTime::now();
+    for (name, age) in [("old.zip", 300), ("mid.zip", 200), ("new.zip", 100)] {
+        let path = cache.join(name);
+        fs::write(&path, vec![0u8; 100]).unwrap();
+        let file = fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(now - Duration::from_secs(age)).unwrap();
+    }
+    // In-flight downloads are never evicted
+    fs::write(cache.join("partial.tmp"), vec![0u8; 500]).unwrap();
+
+    let report = evict_archives(cache, 150).unwrap();
+    assert_eq!(report.freed_bytes, 200);
+    assert_eq!(
+        report.removed,
+        vec![cache.join("old.zip"), cache.join("mid.zip")]
+    );
+    assert!(cache.join("new.zip").exists());
+    assert!(cache.join("partial.tmp").exists());
+
+    assert_eq!(evict_archives(cache, 1000).unwrap().removed.len(), 0);
+}
+
+#[test]
+fn test_extract_archive_from_bytes_zip_and_tar_gz() {
+    use std::io::Write;
+
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    zip.start_file(
+        "psr-log-abc123/src/LoggerInterface.php",
+        zip::write::SimpleFileOptions::default(),
+    )
+    .unwrap();
+    zip.write_all(b"<?php interface LoggerInterface {}")
+        .unwrap();
+    let zip_bytes = zip.finish().unwrap().into_inner();
+
+    let temp_dir = TempDir::new().unwrap();
+    let dest = temp_dir.path().join("zip");
+    extract_archive_from_bytes(&zip_bytes, &dest).unwrap();
+    assert_eq!(
+        fs::read_to_string(dest.join("src/LoggerInterface.php")).unwrap(),
+        "<?php interface LoggerInterface {}"
+    );
+
+    let mut tar = tar::Builder::new(flate2::write::GzEncoder::new(
+        Vec::new(),
+        flate2::Compression::default(),
+    ));
+    let content = b"<?php";
+    let mut header = tar::Header::new_gnu();
+    header.set_size(content.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    tar.append_data(&mut header, "package/index.php", &content[..])
+        .unwrap();
+    let tar_gz = tar.into_inner().unwrap().finish().unwrap();
+
+    let dest = temp_dir.path().join("tar");
+    extract_archive_from_bytes(&tar_gz, &dest).unwrap();
+    assert_eq!(fs::read_to_string(dest.join("index.php")).unwrap(), "<?php");
+
+    assert!(extract_archive_from_bytes(b"not an archive", &temp_dir.path().join("bad")).is_err());
+}
+
+#[test]
+fn test_extract_plain_tar() {
+    let mut tar = tar::Builder::new(Vec::new());
+    let content = b"<?php";
+    let mut header = tar::Header::new_ustar();
+    header.set_size(content.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    tar.append_data(&mut header, "widget-1.0.0/index.php", &content[..])
+        .unwrap();
+    let tar_bytes = tar.into_inner().unwrap();
+
+    let temp_dir = TempDir::new().unwrap();
+    let dest = temp_dir.path().join("bytes");
+    extract_archive_from_bytes(&tar_bytes, &dest).unwrap();
+    assert_eq!(fs::read_to_string(dest.join("index.php")).unwrap(), "<?php");
+
+    let archive = temp_dir.path().join("widget.tar");
+    fs::write(&archive, &tar_bytes).unwrap();
+    let dest = temp_dir.path().join("file");
+    extract_archive_ultra_fast(&archive, &dest).unwrap();
+    assert_eq!(fs::read_to_string(dest.join("index.php")).unwrap(), "<?php");
+}
+
+#[test]
+fn test_sanitize_entry_path() {
+    assert_eq!(sanitize_entry_path("pkg-1.0/").unwrap(), None);
+    assert_eq!(
+        sanitize_entry_path("pkg-1.0/./src/A.php").unwrap(),
+        Some(std::path::PathBuf::from("src/A.php"))
+    );
+    assert!(sanitize_entry_path("pkg/../../evil.php").is_err());
+    assert!(sanitize_entry_path("pkg\\..\\..\\evil.php").is_err());
+    assert!(sanitize_entry_path("/etc/passwd").is_err());
+    assert!(sanitize_entry_path("C:/Windows/evil.dll").is_err());
+
+    let link = Path::new("bin/tool");
+    assert!(symlink_stays_inside(link, Path::new("../src/tool.php")));
+    assert!(!symlink_stays_inside(link, Path::new("../../outside")));
+    assert!(!symlink_stays_inside(link, Path::new("/etc/passwd")));
+}
+
+/// Build a tar.gz with raw entry and link names, bypassing the builder's own path checks
+fn raw_tar_gz(entries: &[(&str, tar::EntryType, &str, &[u8])]) -> Vec<u8> {
+    let mut tar = tar::Builder::new(flate2::write::GzEncoder::new(
+        Vec::new(),
+        flate2::Compression::default(),
+    ));
+    for (name, entry_type, link, data) in entries {
+        let mut header = tar::Header::new_old();
+        header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+        header.as_old_mut().linkname[..link.len()].copy_from_slice(link.as_bytes());
+        header.set_entry_type(*entry_type);
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        tar.append(&header, *data).unwrap();
+    }
+    tar.into_inner().unwrap().finish().unwrap()
+}
+
+#[test]
+fn test_extract_rejects_path_traversal() {
+    use std::io::Write;
+
+    let temp_dir = TempDir::new().unwrap();
+    let dest = temp_dir.path().join("vendor/acme/pkg");
+
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    zip.start_file(
+        "pkg/../../../evil.php",
+        zip::write::SimpleFileOptions::default(),
+    )
+    .unwrap();
+    zip.write_all(b"<?php evil();").unwrap();
+    let zip_bytes = zip.finish().unwrap().into_inner();
+    assert!(extract_archive_from_bytes(&zip_bytes, &dest).is_err());
+
+    let tar_gz = raw_tar_gz(&[("pkg/../../../evil.php", tar::EntryType::Regular, "", b"x")]);
+    assert!(extract_archive_from_bytes(&tar_gz, &dest).is_err());
+
+    assert!(!temp_dir.path().join("vendor/evil.php").exists());
+    assert!(!temp_dir.path().join("evil.php").exists());
+}
+
+#[test]
+fn test_extract_rejects_symlink_escapes() {
+    let temp_dir = TempDir::new().unwrap();
+    let dest = temp_dir.path().join("pkg");
+
+    for target in ["../../outside", "/etc"] {
+        let tar_gz = raw_tar_gz(&[
+            ("pkg/link", tar::EntryType::Symlink, target, b""),
+            ("pkg/link/owned.txt", tar::EntryType::Regular, "", b"x"),
+        ]);
+        assert!(extract_archive_from_bytes(&tar_gz, &dest).is_err());
+    }
+
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    zip.add_symlink(
+        "pkg/link",
+        "../../outside",
+        zip::write::SimpleFileOptions::default(),
+    )
+    .unwrap();
+    let zip_bytes = zip.finish().unwrap().into_inner();
+    assert!(extract_archive_from_bytes(&zip_bytes, &temp_dir.path().join("zip")).is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_extract_rejects_chained_symlink_escapes() {
+    let temp_dir = TempDir::new().unwrap();
+    let victim = temp_dir.path().join("victim");
+    fs::create_dir_all(&victim).unwrap();
+
+    // `d/l1` points back at the package root, so `d/l1/l2` really sits there
+    let through_parent = raw_tar_gz(&[
+        ("top/d/l1", tar::EntryType::Symlink, "..", b""),
+        ("top/d/l1/l2", tar::EntryType::Symlink, "../victim", b""),
+    ]);
+    let dest = temp_dir.path().join("pkg");
+    assert!(extract_archive_from_bytes(&through_parent, &dest).is_err());
+    assert!(fs::symlink_metadata(dest.join("l2")).is_err());
+
+    // The target climbs through a link that resolves above where its name suggests
+    let through_target = raw_tar_gz(&[
+        ("top/d/l1", tar::EntryType::Symlink, "..", b""),
+        ("top/x", tar::EntryType::Symlink, "d/l1/../victim", b""),
+    ]);
+    let dest = temp_dir.path().join("pkg2");
+    assert!(extract_archive_from_bytes(&through_target, &dest).is_err());
+    assert!(fs::symlink_metadata(dest.join("x")).is_err());
+
+    // A link made to escape by one extracted after it
+    let redirected_later = raw_tar_gz(&[
+        ("top/d/l1", tar::EntryType::Symlink, "..", b""),
+        ("top/a", tar::EntryType::Symlink, "m/../victim", b""),
+        ("top/m", tar::EntryType::Symlink, "d/l1", b""),
+    ]);
+    let dest = temp_dir.path().join("pkg3");
+    assert!(extract_archive_from_bytes(&redirected_later, &dest).is_err());
+    assert!(fs::symlink_metadata(dest.join("a")).is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_extract_keeps_internal_symlinks() {
+    let temp_dir = TempDir::new().unwrap();
+    let dest = temp_dir.path().join("pkg");
+    let tar_gz = raw_tar_gz(&[
+        ("pkg/src/tool.php", tar::EntryType::Regular, "", b"<?php"),
+        (
+            "pkg/bin/tool",
+            tar::EntryType::Symlink,
+            "../src/tool.php",
+            b"",
+        ),
+    ]);
+    extract_archive_from_bytes(&tar_gz, &dest).unwrap();
+    assert_eq!(fs::read_to_string(dest.join("bin/tool")).unwrap(), "<?php");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_extract_rejects_writes_through_existing_symlink() {
+    let temp_dir = TempDir::new().unwrap();
+    let outside = temp_dir.path().join("outside");
+    let dest = temp_dir.path().join("pkg");
+    fs::create_dir_all(&outside).unwrap();
+    fs::create_dir_all(&dest).unwrap();
+    std::os::unix::fs::symlink(&outside, dest.join("cache")).unwrap();
+
+    let tar_gz = raw_tar_gz(&[("pkg/cache/owned.txt", tar::EntryType::Regular, "", b"x")]);
+    assert!(extract_archive_from_bytes(&tar_gz, &dest).is_err());
+    assert!(!outside.join("owned.txt").exists());
+}
+
+/// A local git repository standing in for a package source; returns the commit to lock
+fn git_source_repo(source_dir: &Path) -> String {
+    let repo = git2::Repository::init(source_dir).unwrap();
+    fs::write(source_dir.join("composer.json"), r#"{"name": "acme/pkg"}"#).unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("composer.json")).unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+    repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+        .unwrap()
+        .to_string()
+}
+
+fn locked_with_source(
+    name: &str,
+    source_dir: &Path,
+    commit: &str,
+) -> lectern::models::model::LockedPackage {
+    let mut pkg = locked(name, "1.0.0", commit);
+    // Nothing listens on the discard port, so a dist download fails immediately
+    pkg.dist.as_mut().unwrap().url = format!("http://127.0.0.1:9/{name}.zip");
+    pkg.source = Some(lectern::models::model::SourceInfo {
+        source_type: "git".to_string(),
+        url: source_dir.to_string_lossy().to_string(),
+        reference: commit.to_string(),
+        mirrors: None,
+    });
+    pkg
+}
+
+#[tokio::test]
+async fn test_install_falls_back_to_source_when_dist_fails() {
+    let temp_dir = TempDir::new().unwrap();
+    let source_dir = temp_dir.path().join("source");
+    let commit = git_source_repo(&source_dir);
+    let pkg = locked_with_source("acme/fallback", &source_dir, &commit);
+
+    let project = temp_dir.path().join("project");
+    fs::create_dir_all(&project).unwrap();
+    let installed = lectern::installer::install_packages(&[pkg], &project)
+        .await
+        .unwrap();
+
+    assert_eq!(installed.len(), 1);
+    assert!(project.join("vendor/acme/fallback/composer.json").exists());
+}
+
+#[tokio::test]
+async fn test_prefer_source_clones_instead_of_downloading() {
+    use lectern::installer::{InstallOptions, InstallState, install_packages_with_options};
+
+    let temp_dir = TempDir::new().unwrap();
+    let source_dir = temp_dir.path().join("source");
+    let commit = git_source_repo(&source_dir);
+    let pkg = locked_with_source("acme/patched", &source_dir, &commit);
+
+    // Pretend the package was already installed from its dist archive
+    let project = temp_dir.path().join("project");
+    let vendor = project.join("vendor");
+    fs::create_dir_all(vendor.join("acme/patched")).unwrap();
+    let mut state = InstallState::default();
+    state.record(&pkg);
+    state.save(&vendor).unwrap();
+
+    let options = InstallOptions {
+        prefer_source: true,
+    };
+    let installed = install_packages_with_options(&[pkg], &project, options)
+        .await
+        .unwrap();
+
+    assert_eq!(installed.len(), 1);
+    let checkout = vendor.join("acme/patched");
+    assert!(checkout.join(".git").exists());
+    let head = git2::Repository::open(&checkout)
+        .unwrap()
+        .head()
+        .unwrap()
+        .peel_to_commit()
+        .unwrap()
+        .id()
+        .to_string();
+    assert_eq!(head, commit);
+}
+
+#[test]
+fn test_reference_refspecs() {
+    let sha = "0123456789abcdef0123456789abcdef01234567";
+    assert!(is_commit_sha(sha));
+    assert!(!is_commit_sha("v1.2.3"));
+    assert!(!is_commit_sha("0123456"));
+    assert_eq!(reference_refspecs(sha), vec![sha.to_string()]);
+    assert_eq!(
+        reference_refspecs("v1.2.3"),
+        vec![
+            "+refs/tags/v1.2.3:refs/tags/v1.2.3".to_string(),
+            "+refs/heads/v1.2.3:refs/remotes/origin/v1.2.3".to_string(),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_clone_checks_out_locked_reference() {
+    let temp_dir = TempDir::new().unwrap();
+    let source_dir = temp_dir.path().join("source");
+    let first = git_source_repo(&source_dir);
+
+    // A tag on the first commit and a branch one commit ahead of the default branch
+    let repo = git2::Repository::open(&source_dir).unwrap();
+    let first_commit = repo
+        .find_commit(git2::Oid::from_str(&first).unwrap())
+        .unwrap();
+    repo.tag_lightweight("v1.0.0", first_commit.as_object(), false)
+        .unwrap();
+    repo.branch("feature", &first_commit, false).unwrap();
+    let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+    let second = repo
+        .commit(
+            Some("refs/heads/feature"),
+            &signature,
+            &signature,
+            "feature work",
+            &first_commit.tree().unwrap(),
+            &[&first_commit],
+        )
+        .unwrap()
+        .to_string();
+
+    let url = source_dir.to_string_lossy().to_string();
+    let sem = std::sync::Arc::new(tokio::sync::Semaphore::new(1));
+    for (name, reference, expected) in [
+        ("sha", first.as_str(), first.as_str()),
+        ("tag", "v1.0.0", first.as_str()),
+        ("branch", "feature", second.as_str()),
+    ] {
+        let target = temp_dir.path().join(name);
+        clone_git_optimized(&url, Some(reference), &target, sem.clone())
+            .await
+            .unwrap();
+
+        let checkout = git2::Repository::open(&target).unwrap();
+        assert!(checkout.head_detached().unwrap(), "{name} is not detached");
+        let head = checkout.head().unwrap().peel_to_commit().unwrap().id();
+        assert_eq!(
+            head.to_string(),
+            expected,
+            "{name} checked out wrong commit"
+        );
+        assert!(target.join("composer.json").exists());
+    }
+}
+
+#[test]
+fn test_submodule_config() {
+    use lectern::installer::SubmoduleConfig;
+    use serde_json::json;
+
+    let off = SubmoduleConfig::default();
+    assert_eq!(off.depth_for("acme/pkg"), None);
+
+    let all = SubmoduleConfig::from_value(&json!(true));
+    assert_eq!(all.depth_for("acme/pkg"), Some(usize::MAX));
+
+    let selected = SubmoduleConfig::from_value(&json!({
+        "packages": ["acme/*"],
+        "depth": 1
+    }));
+    assert_eq!(selected.depth_for("acme/pkg"), Some(1));
+    assert_eq!(selected.depth_for("other/pkg"), None);
+
+    let disabled = SubmoduleConfig::from_value(&json!({"enabled": false, "depth": 2}));
+    assert_eq!(disabled.depth_for("acme/pkg"), None);
+}
+
+#[tokio::test]
+async fn test_source_install_initializes_submodules() {
+    use lectern::installer::{InstallOptions, install_packages_with_options};
+
+    let temp_dir = TempDir::new().unwrap();
+    let child_dir = temp_dir.path().join("child");
+    git_source_repo(&child_dir);
+    fs::write(child_dir.join("composer.json"), "{}").unwrap();
+
+    // A parent package that vendors the child as a submodule under lib/
+    let parent_dir = temp_dir.path().join("parent");
+    git_source_repo(&parent_dir);
+    let parent = git2::Repository::open(&parent_dir).unwrap();
+    let mut submodule = parent
+        .submodule(&child_dir.to_string_lossy(), Path::new("lib"), true)
+        .unwrap();
+    submodule.clone(None).unwrap();
+    submodule.add_finalize().unwrap();
+    let mut index = parent.index().unwrap();
+    let tree = parent.find_tree(index.write_tree().unwrap()).unwrap();
+    let head = parent.head().unwrap().peel_to_commit().unwrap();
+    let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+    let commit = parent
+        .commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "add submodule",
+            &tree,
+            &[&head],
+        )
+        .unwrap()
+        .to_string();
+
+    let project = temp_dir.path().join("project");
+    fs::create_dir_all(&project).unwrap();
+    fs::write(
+        project.join("composer.json"),
+        r#"{"extra": {"lectern": {"submodules": {"packages": ["acme/*"]}}}}"#,
+    )
+    .unwrap();
+
+    let pkg = locked_with_source("acme/parent", &parent_dir, &commit);
+    let options = InstallOptions {
+        prefer_source: true,
+    };
+    install_packages_with_options(&[pkg], &project, options)
+        .await
+        .unwrap();
+
+    assert!(
+        project
+            .join("vendor/acme/parent/lib/composer.json")
+            .exists()
+    );
+}
+
+#[test]
+fn test_github_download_urls() {
+    use lectern::installer::installer_github::{api_zipball_url, is_github_host};
+
+    assert!(is_github_host(
+        "https://api.github.com/repos/acme/pkg/zipball/abc"
+    ));
+    assert!(is_github_host(
+        "https://codeload.github.com/acme/pkg/legacy.zip/abc"
+    ));
+    assert!(!is_github_host("https://gitlab.com/acme/pkg.zip"));
+    assert_eq!(
+        api_zipball_url("https://codeload.github.com/acme/pkg/legacy.zip/abc123").as_deref(),
+        Some("https://api.github.com/repos/acme/pkg/zipball/abc123")
+    );
+    assert_eq!(
+        api_zipball_url("https://api.github.com/repos/acme/pkg/zipball/abc"),
+        None
+    );
+}
+
+#[test]
+fn test_dist_url_rewrites() {
+    use lectern::installer::installer_urls::{DistUrlRewrite, DistUrlRewrites};
+    use serde_json::json;
+
+    let defaults = DistUrlRewrites::default();
+    // GitHub API archives go to codeload, which has no anonymous API quota
+    assert_eq!(
+        defaults
+            .rewrite("https://api.github.com/repos/acme/pkg/zipball/0123abcd")
+            .as_deref(),
+        Some("https://codeload.github.com/acme/pkg/legacy.zip/0123abcd")
+    );
+    assert_eq!(
+        defaults
+            .rewrite("https://api.github.com/repos/acme/pkg/tarball/v1.2.0")
+            .as_deref(),
+        Some("https://codeload.github.com/acme/pkg/legacy.tar.gz/v1.2.0")
+    );
+    // Already direct: codeload, GitLab and Bitbucket archives
+    for url in [
+        "https://codeload.github.com/acme/pkg/legacy.zip/0123abcd",
+        "https://api.github.com/repos/acme/pkg/commits/0123abcd",
+        "https://gitlab.com/api/v4/projects/acme%2Fpkg/repository/archive.zip?sha=0123abcd",
+        "https://bitbucket.org/acme/pkg/get/0123abcd.zip",
+    ] {
+        assert_eq!(defaults.rewrite(url), None, "{url}");
+    }
+
+    // Configured rules come first; captures are percent-decoded
+    let rewrites = DistUrlRewrites::from_config(Some(&json!([
+        {
+            "from": "https://gitlab.com/api/v4/projects/{project}/repository/archive.zip?sha={ref}",
+            "to": "https://gitlab.com/{project}/-/archive/{ref}/archive.zip"
+        },
+        {"from": "https://api.github.com/repos/{owner}/{repo}/zipball/{ref}",
+         "to": "https://mirror.example.com/github/{owner}/{repo}/{ref}.zip"},
+        {"from": "https://incomplete.example.com/{x}"}
+    ])));
+    assert_eq!(rewrites.rules.len(), 4);
+    assert_eq!(
+        rewrites
+            .rewrite(
+                "https://gitlab.com/api/v4/projects/acme%2Fpkg/repository/archive.zip?sha=0123abcd"
+            )
+            .as_deref(),
+        Some("https://gitlab.com/acme/pkg/-/archive/0123abcd/archive.zip")
+    );
+    assert_eq!(
+        rewrites
+            .rewrite("https://api.github.com/repos/acme/pkg/zipball/0123abcd")
+            .as_deref(),
+        Some("https://mirror.example.com/github/acme/pkg/0123abcd.zip")
+    );
+
+    // Inner captures are single segments; the last one takes the rest
+    let rule = DistUrlRewrite::new(
+        "https://example.com/{owner}/dist/{file}",
+        "https://x/{owner}/{file}",
+    );
+    assert_eq!(
+        rule.apply("https://example.com/acme/extra/dist/a.zip"),
+        None
+    );
+    assert_eq!(
+        rule.apply("https://example.com/acme/dist/v1/a.zip")
+            .as_deref(),
+        Some("https://x/acme/v1/a.zip")
+    );
+    // Placeholders missing from `from` don't match
+    assert_eq!(
+        DistUrlRewrite::new("https://example.com/{file}", "https://x/{other}")
+            .apply("https://example.com/a.zip"),
+        None
+    );
+}
+
+#[test]
+fn test_rate_limit_wait() {
+    use lectern::installer::installer_github::rate_limit_wait;
+    use reqwest::header::HeaderMap;
+    use std::time::Duration;
+
+    let headers = |pairs: &[(&'static str, &str)]| {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.insert(*name, value.parse().unwrap());
+        }
+        map
+    };
+
+    let exhausted = headers(&[
+        ("x-ratelimit-remaining", "0"),
+        ("x-ratelimit-reset", "1030"),
+    ]);
+    assert_eq!(
+        rate_limit_wait(403, &exhausted, 1000),
+        Some(Duration::from_secs(30))
+    );
+    assert_eq!(
+        rate_limit_wait(403, &headers(&[("retry-after", "5")]), 1000),
+        Some(Duration::from_secs(5))
+    );
+    assert_eq!(
+        rate_limit_wait(429, &HeaderMap::new(), 1000),
+        Some(Duration::from_secs(60))
+    );
+    // A plain 403 (e.g. a private repository) is not a rate limit
+    assert_eq!(rate_limit_wait(403, &HeaderMap::new(), 1000), None);
+    assert_eq!(rate_limit_wait(200, &exhausted, 1000), None);
+}
+
+#[tokio::test]
+async fn test_download_waits_out_rate_limit() {
+    use lectern::installer::installer_github::send_download;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/archive.zip", listener.local_addr().unwrap());
+    let server = tokio::spawn(async move {
+        for response in [
+            "HTTP/1.1 429 Too Many Requests\r\nretry-after: 0\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+            "HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\nok",
+        ] {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 1024];
+            let _ = socket.read(&mut request).await.unwrap();
+            socket.write_all(response.as_bytes()).await.unwrap();
+        }
+    });
+
+    let response = send_download(&reqwest::Client::new(), &url).await.unwrap();
+    assert_eq!(response.text().await.unwrap(), "ok");
+    server.await.unwrap();
+}
+
+#[test]
+fn test_content_range_start() {
+    use lectern::installer::content_range_start;
+
+    assert_eq!(content_range_start("bytes 1024-2047/2048"), Some(1024));
+    assert_eq!(content_range_start("bytes */2048"), None);
+    assert_eq!(content_range_start("items 0-1/2"), None);
+}
+
+#[tokio::test]
+async fn test_download_resumes_partial_archive() {
+    use std::io::Write;
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::sync::Semaphore;
+
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    zip.start_file(
+        "pkg-abc/src/Big.php",
+        zip::write::SimpleFileOptions::default(),
+    )
+    .unwrap();
+    zip.write_all(b"<?php class Big {}").unwrap();
+    let archive = zip.finish().unwrap().into_inner();
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/big.zip", listener.local_addr().unwrap());
+
+    // An earlier run stopped half way through the archive
+    let half = archive.len() / 2;
+    let cache_path = get_cached_package_path("acme/big", "1.0.0", &url);
+    fs::create_dir_all(cache_path.parent().unwrap()).unwrap();
+    fs::write(cache_path.with_extension("tmp"), &archive[..half]).unwrap();
+    fs::write(cache_path.with_extension("validator"), "\"v1\"").unwrap();
+
+    let body = archive.clone();
+    let server = tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut request = vec![0u8; 4096];
+        let read = socket.read(&mut request).await.unwrap();
+        let request = String::from_utf8_lossy(&request[..read]).to_ascii_lowercase();
+        let rest = &body[half..];
+        let head = format!(
+            "HTTP/1.1 206 Partial Content\r\ncontent-range: bytes {half}-{}/{}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+            body.len() - 1,
+            body.len(),
+            rest.len()
+        );
+        socket.write_all(head.as_bytes()).await.unwrap();
+        socket.write_all(rest).await.unwrap();
+        request
+    });
+
+    let temp_dir = TempDir::new().unwrap();
+    let target = temp_dir.path().join("big");
+    lectern::installer::download_and_extract_streaming(
+        &dist(&url, ""),
+        &target,
+        reqwest::Client::new(),
+        Arc::new(Semaphore::new(1)),
+        Arc::new(Semaphore::new(1)),
+        "acme/big",
+        "1.0.0",
+    )
+    .await
+    .unwrap();
+
+    let request = server.await.unwrap();
+    assert!(
+        request.contains(&format!("range: bytes={half}-")),
+        "{request}"
+    );
+    assert!(request.contains("if-range: \"v1\""), "{request}");
+    assert_eq!(
+        fs::read_to_string(target.join("src/Big.php")).unwrap(),
+        "<?php class Big {}"
+    );
+    assert_eq!(fs::read(&cache_path).unwrap(), archive);
+    assert!(!cache_path.with_extension("validator").exists());
+    let _ = fs::remove_file(&cache_path);
+}
+
+#[tokio::test]
+async fn test_download_restarts_when_archive_changed() {
+    use std::io::Write;
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::sync::Semaphore;
+
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    zip.start_file(
+        "pkg-abc/src/Changed.php",
+        zip::write::SimpleFileOptions::default(),
+    )
+    .unwrap();
+    zip.write_all(b"<?php class Changed {}").unwrap();
+    let archive = zip.finish().unwrap().into_inner();
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/changed.zip", listener.local_addr().unwrap());
+
+    // The partial file belongs to an older build of the archive
+    let cache_path = get_cached_package_path("acme/changed", "1.0.0", &url);
+    fs::create_dir_all(cache_path.parent().unwrap()).unwrap();
+    fs::write(cache_path.with_extension("tmp"), b"stale bytes").unwrap();
+    fs::write(cache_path.with_extension("validator"), "\"old\"").unwrap();
+
+    let body = archive.clone();
+    let server = tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut request = vec![0u8; 4096];
+        let _ = socket.read(&mut request).await.unwrap();
+        // If-Range didn't match, so the whole new archive comes back
+        let head = format!(
+            "HTTP/1.1 200 OK\r\netag: \"new\"\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+            body.len()
+        );
+        socket.write_all(head.as_bytes()).await.unwrap();
+        socket.write_all(&body).await.unwrap();
+    });
+
+    let temp_dir = TempDir::new().unwrap();
+    let target = temp_dir.path().join("changed");
+    lectern::installer::download_and_extract_streaming(
+        &dist(&url, ""),
+        &target,
+        reqwest::Client::new(),
+        Arc::new(Semaphore::new(1)),
+        Arc::new(Semaphore::new(1)),
+        "acme/changed",
+        "1.0.0",
+    )
+    .await
+    .unwrap();
+    server.await.unwrap();
+
+    assert_eq!(
+        fs::read_to_string(target.join("src/Changed.php")).unwrap(),
+        "<?php class Changed {}"
+    );
+    assert_eq!(fs::read(&cache_path).unwrap(), archive);
+    assert!(!cache_path.with_extension("validator").exists());
+    let _ = fs::remove_file(&cache_path);
+}
+
+#[tokio::test]
+async fn test_verify_archive_shasum() {
+    use lectern::installer::verify_archive_shasum;
+
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("pkg.zip");
+    fs::write(&archive, b"abc").unwrap();
+
+    // SHA-1 of "abc"
+    let shasum = "A9993E364706816ABA3E25717850C26C9CD0D89D";
+    assert!(verify_archive_shasum(&archive, shasum).await.is_ok());
+    assert!(verify_archive_shasum(&archive, "").await.is_ok());
+    let err = verify_archive_shasum(&archive, &"0".repeat(40))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(
+            err.downcast_ref::<lectern::LecternError>(),
+            Some(lectern::LecternError::ChecksumMismatch { .. })
+        ),
+        "{err}"
+    );
+}
+
+fn dist(url: &str, shasum: &str) -> lectern::models::model::DistInfo {
+    lectern::models::model::DistInfo {
+        dist_type: "zip".to_string(),
+        url: url.to_string(),
+        shasum: shasum.to_string(),
+        ..Default::default()
+    }
+}
+
+/// Serve one response with `head` (status line and headers) and `body`, then hang up
+async fn serve_once(head: String, body: Vec<u8>) -> String {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut request = vec![0u8; 4096];
+        let _ = socket.read(&mut request).await.unwrap();
+        socket.write_all(head.as_bytes()).await.unwrap();
+        socket.write_all(&body).await.unwrap();
+    });
+    format!("http://{addr}")
+}
+
+#[tokio::test]
+async fn test_download_with_wrong_shasum_extracts_nothing() {
+    use std::io::Write;
+    use std::sync::Arc;
+    use tokio::sync::Semaphore;
+
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    zip.start_file(
+        "pkg-abc/src/Bad.php",
+        zip::write::SimpleFileOptions::default(),
+    )
+    .unwrap();
+    zip.write_all(b"<?php class Bad {}").unwrap();
+    let archive = zip.finish().unwrap().into_inner();
+    let head = format!(
+        "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+        archive.len()
+    );
+    let url = format!("{}/bad.zip", serve_once(head, archive).await);
+
+    let temp_dir = TempDir::new().unwrap();
+    let target = temp_dir.path().join("bad");
+    fs::create_dir_all(&target).unwrap();
+    let err = lectern::installer::download_and_extract_streaming(
+        &dist(&url, &"0".repeat(40)),
+        &target,
+        reqwest::Client::new(),
+        Arc::new(Semaphore::new(1)),
+        Arc::new(Semaphore::new(1)),
+        "acme/bad",
+        "1.0.0",
+    )
+    .await
+    .unwrap_err();
+
+    assert!(
+        matches!(
+            err.downcast_ref::<lectern::LecternError>(),
+            Some(lectern::LecternError::ChecksumMismatch { .. })
+        ),
+        "{err}"
+    );
+    assert!(!target.exists());
+    let cache_path = get_cached_package_path("acme/bad", "1.0.0", &url);
+    assert!(!cache_path.exists());
+    assert!(!cache_path.with_extension("lock").exists());
+}
+
+#[tokio::test]
+async fn test_cached_archive_with_wrong_shasum_is_dropped() {
+    use std::sync::Arc;
+    use tokio::sync::Semaphore;
+
+    // Nothing listens here, so only the cached copy can be used
+    let url = "http://127.0.0.1:9/lectern-shasum-test/lib.zip";
+    let cache_path = get_cached_package_path("acme/cached", "1.0.0", url);
+    fs::create_dir_all(cache_path.parent().unwrap()).unwrap();
+    fs::write(&cache_path, b"not the locked archive").unwrap();
+
+    let temp_dir = TempDir::new().unwrap();
+    let target = temp_dir.path().join("cached");
+    fs::create_dir_all(&target).unwrap();
+    let err = lectern::installer::download_and_extract_streaming(
+        &dist(url, &"0".repeat(40)),
+        &target,
+        reqwest::Client::new(),
+        Arc::new(Semaphore::new(1)),
+        Arc::new(Semaphore::new(1)),
+        "acme/cached",
+        "1.0.0",
+    )
+    .await
+    .unwrap_err();
+
+    assert!(
+        matches!(
+            err.downcast_ref::<lectern::LecternError>(),
+            Some(lectern::LecternError::ChecksumMismatch { .. })
+        ),
+        "{err}"
+    );
+    assert!(!target.exists());
+    assert!(!cache_path.exists());
+}
+
+#[tokio::test]
+async fn test_interrupted_download_releases_lock() {
+    use std::sync::Arc;
+    use tokio::sync::Semaphore;
+
+    // The connection closes long before the announced length arrives
+    let head = "HTTP/1.1 200 OK\r\ncontent-length: 1000\r\nconnection: close\r\n\r\n".to_string();
+    let url = format!("{}/cut.zip", serve_once(head, b"PK".to_vec()).await);
+
+    let temp_dir = TempDir::new().unwrap();
+    let result = lectern::installer::download_and_extract_streaming(
+        &dist(&url, ""),
+        &temp_dir.path().join("cut"),
+        reqwest::Client::new(),
+        Arc::new(Semaphore::new(1)),
+        Arc::new(Semaphore::new(1)),
+        "acme/cut",
+        "1.0.0",
+    )
+    .await;
+
+    assert!(result.is_err());
+    let cache_path = get_cached_package_path("acme/cut", "1.0.0", &url);
+    assert!(!cache_path.exists());
+    assert!(!cache_path.with_extension("lock").exists());
+    let _ = fs::remove_file(cache_path.with_extension("validator"));
+}
+
+#[test]
+fn test_entity_validator() {
+    use lectern::installer::entity_validator;
+    use reqwest::header::{ETAG, HeaderMap, LAST_MODIFIED};
+
+    let mut headers = HeaderMap::new();
+    assert_eq!(entity_validator(&headers), None);
+    headers.insert(
+        LAST_MODIFIED,
+        "Tue, 01 Oct 2024 00:00:00 GMT".parse().unwrap(),
+    );
+    headers.insert(ETAG, "W/\"weak\"".parse().unwrap());
+    // Weak ETags can't guard a range request
+    assert_eq!(
+        entity_validator(&headers).as_deref(),
+        Some("Tue, 01 Oct 2024 00:00:00 GMT")
+    );
+    headers.insert(ETAG, "\"strong\"".parse().unwrap());
+    assert_eq!(entity_validator(&headers).as_deref(), Some("\"strong\""));
+}
+
+#[test]
+fn test_install_plan_puts_script_and_bin_packages_first() {
+    use lectern::installer::install_plan;
+    use lectern::models::model::{ComposerJson, LockedPackage};
+    use serde_json::json;
+
+    let pkgs: Vec<LockedPackage> = serde_json::from_value(json!([
+        {"name": "acme/app-lib", "version": "1.0.0", "require": {"acme/http": "^1.0"}},
+        {"name": "acme/http", "version": "1.0.0", "require": {"php": ">=8.1"}},
+        {"name": "acme/installer", "version": "1.0.0",
+         "require": {"acme/console": "^1.0"},
+         "autoload": {"psr-4": {"Acme\\Installer\\": "src/"}}},
+        {"name": "acme/console", "version": "1.0.0", "require": {"acme/polyfill": "^1.0"}},
+        {"name": "acme/polyfill", "version": "1.0.0"},
+        {"name": "acme/linter", "version": "1.0.0", "bin": ["bin/lint"]}
+    ]))
+    .unwrap();
+    let composer: ComposerJson = serde_json::from_value(json!({
+        "require": {"acme/app-lib": "^1.0"},
+        "scripts": {"post-install-cmd": ["Acme\\Installer\\Hooks::postInstall"]}
+    }))
+    .unwrap();
+
+    let plan = install_plan(&pkgs, Some(&composer));
+    // Dependencies come before the packages that need them
+    assert_eq!(
+        plan.critical,
+        vec![
+            "acme/linter",
+            "acme/polyfill",
+            "acme/console",
+            "acme/installer"
+        ]
+    );
+    assert_eq!(plan.deferred, vec!["acme/http", "acme/app-lib"]);
+
+    // Without scripts only bin providers are prioritised
+    let plan = install_plan(&pkgs, None);
+    assert_eq!(plan.critical, vec!["acme/linter"]);
+    assert_eq!(plan.ranks()["acme/linter"], 0);
+}
+
+#[test]
+fn test_install_plan_queues_direct_requirements_early() {
+    use lectern::installer::install_plan;
+    use lectern::models::model::{ComposerJson, LockedPackage};
+    use serde_json::json;
+
+    let pkgs: Vec<LockedPackage> = serde_json::from_value(json!([
+        {"name": "acme/alpha", "version": "1.0.0", "require": {"acme/mid": "^1.0"}},
+        {"name": "acme/mid", "version": "1.0.0", "require": {"acme/base2": "^1.0"}},
+        {"name": "acme/base2", "version": "1.0.0"},
+        {"name": "acme/zeta", "version": "1.0.0", "require": {"acme/base": "^1.0"}},
+        {"name": "acme/base", "version": "1.0.0"}
+    ]))
+    .unwrap();
+    let composer: ComposerJson = serde_json::from_value(json!({
+        "require": {"acme/alpha": "^1.0", "acme/zeta": "^1.0"}
+    }))
+    .unwrap();
+
+    // By layer alone alpha would come last; it follows its own dependencies instead
+    let plan = install_plan(&pkgs, Some(&composer));
+    assert_eq!(
+        plan.deferred,
+        vec![
+            "acme/base2",
+            "acme/mid",
+            "acme/alpha",
+            "acme/base",
+            "acme/zeta"
+        ]
+    );
+    let plan = install_plan(&pkgs, None);
+    assert_eq!(plan.deferred.last().unwrap(), "acme/alpha");
+}
+
+#[test]
+fn test_install_plan_keeps_cycles_together() {
+    use lectern::installer::install_plan;
+    use lectern::models::model::LockedPackage;
+    use serde_json::json;
+
+    let pkgs: Vec<LockedPackage> = serde_json::from_value(json!([
+        {"name": "acme/app", "version": "1.0.0", "require": {"acme/a": "^1.0"}},
+        {"name": "acme/a", "version": "1.0.0", "require": {"acme/b": "^1.0"}},
+        {"name": "acme/b", "version": "1.0.0",
+         "require": {"acme/a": "^1.0", "acme/base": "^1.0"}},
+        {"name": "acme/base", "version": "1.0.0"}
+    ]))
+    .unwrap();
+
+    // The cycle is one batch: after what it needs, before what needs it
+    let plan = install_plan(&pkgs, None);
+    assert!(plan.critical.is_empty());
+    assert_eq!(
+        plan.deferred,
+        vec!["acme/base", "acme/a", "acme/b", "acme/app"]
+    );
+}
+
+#[test]
+fn test_verbatim_path_prefixes_drive_and_unc_paths() {
+    use lectern::installer::installer_links::verbatim_path;
+
+    assert_eq!(
+        verbatim_path(r"C:\project\vendor").as_deref(),
+        Some(r"\\?\C:\project\vendor")
+    );
+    assert_eq!(
+        verbatim_path("C:/project/vendor").as_deref(),
+        Some(r"\\?\C:\project\vendor")
+    );
+    assert_eq!(
+        verbatim_path(r"\\server\share\vendor").as_deref(),
+        Some(r"\\?\UNC\server\share\vendor")
+    );
+    // Already verbatim, device and relative paths are left alone
+    assert_eq!(verbatim_path(r"\\?\C:\project"), None);
+    assert_eq!(verbatim_path(r"\\.\pipe\x"), None);
+    assert_eq!(verbatim_path(r"vendor\acme"), None);
+}
+
+#[test]
+fn test_package_dir_handles_deep_vendor_paths() {
+    use lectern::installer::installer_links::{package_dir, remove_dir_all};
+
+    let temp = TempDir::new().unwrap();
+    let mut vendor = temp.path().to_path_buf();
+    while vendor.as_os_str().len() < 300 {
+        vendor.push("nested-directory-name");
+    }
+    let target = package_dir(&vendor, "acme/widget");
+    assert!(target.ends_with(Path::new("acme").join("widget")));
+
+    fs::create_dir_all(target.join("src")).unwrap();
+    fs::write(target.join("src").join("Widget.php"), "<?php").unwrap();
+    assert!(target.join("src").join("Widget.php").is_file());
+
+    remove_dir_all(&target).unwrap();
+    assert!(!target.exists());
+}
+
+#[test]
+fn test_remove_dir_all_clears_read_only_files_and_tolerates_missing_paths() {
+    use lectern::installer::installer_links::remove_dir_all;
+
+    let temp = TempDir::new().unwrap();
+    let pack = temp.path().join("pkg").join(".git").join("objects");
+    fs::create_dir_all(&pack).unwrap();
+    let object = pack.join("pack-1.idx");
+    fs::write(&object, "x").unwrap();
+    let mut permissions = fs::metadata(&object).unwrap().permissions();
+    permissions.set_readonly(true);
+    fs::set_permissions(&object, permissions).unwrap();
+
+    remove_dir_all(&temp.path().join("pkg")).unwrap();
+    assert!(!temp.path().join("pkg").exists());
+    remove_dir_all(&temp.path().join("pkg")).unwrap();
+}
+
+#[test]
+fn test_remove_dir_all_keeps_link_targets() {
+    use lectern::installer::installer_links::{link_dir, remove_dir_all};
+
+    let temp = TempDir::new().unwrap();
+    let source = temp.path().join("packages").join("local");
+    fs::create_dir_all(&source).unwrap();
+    fs::write(source.join("composer.json"), "{}").unwrap();
+
+    let link = temp.path().join("vendor-local");
+    link_dir(&source, &link).unwrap();
+    assert!(link.join("composer.json").is_file());
+
+    remove_dir_all(&link).unwrap();
+    assert!(!link.exists());
+    assert!(source.join("composer.json").is_file());
+}
+
+#[test]
+fn test_extracted_symlinks_resolve_on_every_platform() {
+    let temp = TempDir::new().unwrap();
+    let archive = temp.path().join("pkg.tar.gz");
+    let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+        fs::File::create(&archive).unwrap(),
+        flate2::Compression::default(),
+    ));
+    let mut add_file = |path: &str, content: &[u8]| {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, path, content).unwrap();
+    };
+    add_file("pkg-1.0/bin/tool.php", b"<?php echo 1;");
+    let mut header = tar::Header::new_gnu();
+    header.set_entry_type(tar::EntryType::Symlink);
+    header.set_size(0);
+    header.set_cksum();
+    builder
+        .append_link(&mut header, "pkg-1.0/tool", "bin/tool.php")
+        .unwrap();
+    builder.into_inner().unwrap().finish().unwrap();
+
+    let dest = temp.path().join("vendor").join("acme").join("pkg");
+    extract_tar_gz_ultra_fast(&archive, &dest).unwrap();
+    // A symlink where the platform allows one, otherwise a copy of the target
+    assert_eq!(
+        fs::read_to_string(dest.join("tool")).unwrap(),
+        "<?php echo 1;"
+    );
+}
+
+#[test]
+fn test_bin_proxies_run_php_scripts_with_php() {
+    use lectern::installer::installer_links::{bat_proxy, is_php_script, sh_proxy};
+
+    let temp = TempDir::new().unwrap();
+    let script = temp.path().join("phpunit");
+    fs::write(&script, "#!/usr/bin/env php\n<?php\n").unwrap();
+    let native = temp.path().join("tool.sh");
+    fs::write(&native, "#!/bin/sh\necho hi\n").unwrap();
+    assert!(is_php_script(&script));
+    assert!(!is_php_script(&native));
+
+    let target = Path::new(r"C:\Users\me\lectern\global\vendor\bin\phpunit");
+    let bat = bat_proxy(target, true);
+    assert!(bat.starts_with("@ECHO OFF\r\n"));
+    assert!(bat.contains(r"SET BIN_TARGET=C:\Users\me\lectern\global\vendor\bin\phpunit"));
+    assert!(bat.contains("php \"%BIN_TARGET%\" %*"));
+    assert!(bat_proxy(target, false).contains("\r\n\"%BIN_TARGET%\" %*"));
+
+    let sh = sh_proxy(target, true);
+    assert!(sh.contains("exec php \"C:/Users/me/lectern/global/vendor/bin/phpunit\" \"$@\""));
 }
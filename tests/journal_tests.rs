@@ -0,0 +1,83 @@
+use lectern::journal::{
+    EntryStatus, JOURNAL_RETENTION, Snapshot, begin_install_operation, begin_operation,
+    commit_operation, list_entries,
+};
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn test_journal_records_before_and_after() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    fs::write(dir.join("composer.json"), r#"{"require": {}}"#).unwrap();
+
+    let mut entry = begin_operation(dir, "require").unwrap();
+    assert_eq!(entry.status, EntryStatus::Pending);
+    assert_eq!(list_entries(dir).unwrap().len(), 1);
+
+    fs::write(
+        dir.join("composer.json"),
+        r#"{"require": {"psr/log": "^3.0"}}"#,
+    )
+    .unwrap();
+    fs::write(dir.join("composer.lock"), "{}").unwrap();
+    commit_operation(dir, &mut entry).unwrap();
+
+    let entries = list_entries(dir).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].status, EntryStatus::Committed);
+    assert_eq!(entries[0].before.composer_lock, None);
+    assert_eq!(
+        entries[0].after.as_ref().unwrap().composer_lock.as_deref(),
+        Some("{}")
+    );
+}
+
+#[test]
+fn test_snapshot_restore_removes_new_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    fs::write(dir.join("composer.json"), "original").unwrap();
+    let snapshot = Snapshot::capture(dir).unwrap();
+
+    fs::write(dir.join("composer.json"), "changed").unwrap();
+    fs::write(dir.join("composer.lock"), "new lock").unwrap();
+    snapshot.restore(dir).unwrap();
+
+    assert_eq!(
+        fs::read_to_string(dir.join("composer.json")).unwrap(),
+        "original"
+    );
+    assert!(!dir.join("composer.lock").exists());
+}
+
+#[test]
+fn test_journal_retention() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+
+    for _ in 0..JOURNAL_RETENTION + 5 {
+        let mut entry = begin_operation(dir, "update").unwrap();
+        commit_operation(dir, &mut entry).unwrap();
+    }
+
+    let entries = list_entries(dir).unwrap();
+    assert_eq!(entries.len(), JOURNAL_RETENTION);
+    assert_eq!(entries.last().unwrap().id, (JOURNAL_RETENTION + 5) as u64);
+}
+
+#[test]
+fn test_journal_records_no_dev() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+
+    let entry = begin_operation(dir, "require").unwrap();
+    let content =
+        fs::read_to_string(dir.join(format!(".lectern/journal/{:08}.json", entry.id))).unwrap();
+    assert!(!content.contains("no_dev"), "{content}");
+
+    begin_install_operation(dir, "install", true).unwrap();
+    let entries = list_entries(dir).unwrap();
+    assert!(!entries[0].no_dev);
+    assert!(entries[1].no_dev);
+}
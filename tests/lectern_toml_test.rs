@@ -0,0 +1,160 @@
+use lectern::cli::ConvertArgs;
+use lectern::commands::convert;
+use lectern::io::{manifest_path, read_composer_content_hash, read_composer_json};
+use lectern::models::lectern_toml::{ManifestFormat, json_to_toml, toml_to_json};
+use lectern::resolver::dependency_utils::generate_content_hash;
+use serde_json::json;
+use std::fs;
+use tempfile::TempDir;
+
+const COMPOSER: &str = r#"{
+    "name": "acme/app",
+    "description": null,
+    "require": {
+        "php": ">=8.1",
+        "monolog/monolog": "^3.0"
+    },
+    "require-dev": {
+        "phpunit/phpunit": "^11.0"
+    },
+    "autoload": {
+        "psr-4": {
+            "Acme\\App\\": "src/"
+        }
+    },
+    "minimum-stability": "dev",
+    "prefer-stable": true,
+    "config": {
+        "platform": {
+            "php": "8.2.0"
+        }
+    }
+}
+"#;
+
+fn args(to: Option<&str>, keep: bool) -> ConvertArgs {
+    ConvertArgs {
+        to: to.map(str::to_string),
+        keep,
+        force: false,
+    }
+}
+
+#[test]
+fn test_toml_round_trip() {
+    let value: serde_json::Value = serde_json::from_str(COMPOSER).unwrap();
+    let toml = json_to_toml(&value).unwrap();
+    assert!(toml.contains("[require]"));
+    assert!(toml.contains(r#""monolog/monolog" = "^3.0""#));
+    assert!(!toml.contains("description"));
+
+    let back = toml_to_json(&toml).unwrap();
+    assert_eq!(back["autoload"]["psr-4"]["Acme\\App\\"], json!("src/"));
+    assert_eq!(back["prefer-stable"], json!(true));
+    assert_eq!(
+        generate_content_hash(&back.to_string()),
+        generate_content_hash(COMPOSER)
+    );
+}
+
+#[test]
+fn test_toml_round_trip_keeps_hashed_key_order() {
+    let composer = r#"{
+        "name": "acme/app",
+        "require": {"php": ">=8.1"},
+        "extra": {
+            "branch-alias": {"dev-main": "1.x-dev"},
+            "foo": "bar"
+        },
+        "repositories": [
+            {"type": "vcs", "options": {"ssh2": {"username": "git"}}, "url": "https://example.com/repo.git"}
+        ],
+        "autoload": {"psr-4": {"Acme\\": "src/"}, "files": ["helpers.php"]}
+    }"#;
+    let value: serde_json::Value = serde_json::from_str(composer).unwrap();
+    let toml = json_to_toml(&value).unwrap();
+    // Keys outside the hash still get tables
+    assert!(toml.contains("[autoload.psr-4]"));
+
+    let back = toml_to_json(&toml).unwrap();
+    let extra_keys: Vec<&String> = back["extra"].as_object().unwrap().keys().collect();
+    assert_eq!(extra_keys, ["branch-alias", "foo"]);
+    assert_eq!(
+        generate_content_hash(&back.to_string()),
+        generate_content_hash(composer)
+    );
+}
+
+#[test]
+fn test_json_to_toml_rejects_non_objects() {
+    assert!(json_to_toml(&json!(["acme/app"])).is_err());
+}
+
+#[test]
+fn test_detect_format() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    assert_eq!(ManifestFormat::detect(dir), ManifestFormat::ComposerJson);
+
+    fs::write(dir.join("Lectern.toml"), "name = \"acme/app\"\n").unwrap();
+    assert_eq!(ManifestFormat::detect(dir), ManifestFormat::LecternToml);
+    assert_eq!(manifest_path(dir), dir.join("Lectern.toml"));
+
+    fs::write(dir.join("composer.json"), "{}").unwrap();
+    assert_eq!(ManifestFormat::detect(dir), ManifestFormat::ComposerJson);
+}
+
+#[test]
+fn test_read_composer_json_from_toml() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("Lectern.toml");
+    fs::write(
+        &path,
+        "name = \"acme/app\"\n\n[require]\n\"monolog/monolog\" = \"^3.0\"\n",
+    )
+    .unwrap();
+
+    let composer = read_composer_json(&path).unwrap();
+    assert_eq!(composer.name.as_deref(), Some("acme/app"));
+    assert_eq!(
+        composer.require.get("monolog/monolog").map(String::as_str),
+        Some("^3.0")
+    );
+}
+
+#[test]
+fn test_convert_keeps_content_hash() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    fs::write(dir.join("composer.json"), COMPOSER).unwrap();
+    let hash = read_composer_content_hash(&dir.join("composer.json")).unwrap();
+
+    convert(&args(None, false), dir).unwrap();
+    assert!(!dir.join("composer.json").exists());
+    assert_eq!(
+        read_composer_content_hash(&dir.join("Lectern.toml")).unwrap(),
+        hash
+    );
+
+    convert(&args(Some("json"), true), dir).unwrap();
+    assert!(dir.join("Lectern.toml").exists());
+    assert_eq!(
+        read_composer_content_hash(&dir.join("composer.json")).unwrap(),
+        hash
+    );
+}
+
+#[test]
+fn test_convert_refuses_to_overwrite() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    fs::write(dir.join("composer.json"), COMPOSER).unwrap();
+    fs::write(dir.join("Lectern.toml"), "name = \"other/app\"\n").unwrap();
+
+    let err = convert(&args(Some("toml"), false), dir).unwrap_err();
+    assert!(err.to_string().contains("already exists"));
+    assert!(dir.join("composer.json").exists());
+
+    assert!(convert(&args(Some("yaml"), false), dir).is_err());
+    assert!(convert(&args(None, false), &dir.join("missing")).is_err());
+}
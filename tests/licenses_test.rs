@@ -49,3 +49,105 @@ fn test_licenses_with_quiet_flag() {
     // Quiet mode should run without crashing (may fail without lock file)
     assert!(output.status.code().is_some());
 }
+
+fn licensed_lock() -> serde_json::Value {
+    serde_json::json!({
+        "content-hash": "",
+        "packages": [
+            {"name": "acme/mit", "version": "1.0.0", "license": ["MIT"]},
+            {"name": "acme/dual", "version": "1.0.0", "license": ["GPL-3.0-only", "MIT"]},
+            {"name": "acme/gpl", "version": "2.0.0", "license": ["GPL-3.0-only"]},
+            {"name": "acme/none", "version": "1.0.0"}
+        ],
+        "packages-dev": [{"name": "acme/tool", "version": "3.0.0", "license": ["BSD-3-Clause"]}]
+    })
+}
+
+#[test]
+fn test_license_report_and_policy() {
+    use lectern::commands::licenses::{LicensePolicy, LicenseReport};
+
+    let lock: lectern::models::model::Lock = serde_json::from_value(licensed_lock()).unwrap();
+    let report = LicenseReport::from_lock(&lock, true, &LicensePolicy::default());
+    assert!(report.violations.is_empty());
+    assert_eq!(
+        report.summary_by_count(),
+        vec![
+            ("GPL-3.0-only", 2),
+            ("MIT", 2),
+            ("BSD-3-Clause", 1),
+            ("Unknown", 1)
+        ]
+    );
+    assert!(report.dependencies["acme/tool"].dev);
+
+    // Dual-licensed packages pass when either license is acceptable
+    let denied = LicensePolicy {
+        allowed: vec![],
+        denied: vec!["gpl-3.0-only".to_string()],
+    };
+    let report = LicenseReport::from_lock(&lock, false, &denied);
+    assert_eq!(report.violations, vec!["acme/gpl"]);
+    assert!(!report.dependencies.contains_key("acme/tool"));
+
+    // An allow-list also rejects packages without a license
+    let allowed = LicensePolicy {
+        allowed: vec!["MIT".to_string(), "BSD-3-Clause".to_string()],
+        denied: vec![],
+    };
+    let report = LicenseReport::from_lock(&lock, true, &allowed);
+    assert_eq!(report.violations, vec!["acme/gpl", "acme/none"]);
+}
+
+#[test]
+fn test_licenses_policy_fails_the_command() {
+    ensure_lectern_binary();
+
+    let temp_dir = TempDir::new().unwrap();
+    let temp_path = temp_dir.path();
+    fs::write(temp_path.join("composer.lock"), licensed_lock().to_string()).unwrap();
+    let composer = |policy: serde_json::Value| {
+        serde_json::json!({"name": "test/licenses", "extra": {"lectern": policy}}).to_string()
+    };
+
+    fs::write(
+        temp_path.join("composer.json"),
+        composer(serde_json::json!({"denied-licenses": ["GPL-3.0-only"]})),
+    )
+    .unwrap();
+    let output = Command::new(get_lectern_binary_path())
+        .args(["licenses", "--format", "json", "--no-dev"])
+        .current_dir(temp_path)
+        .output()
+        .expect("Failed to execute lectern licenses");
+    assert_eq!(output.status.code(), Some(1));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["violations"], serde_json::json!(["acme/gpl"]));
+    assert_eq!(report["summary"]["MIT"], 2);
+    assert!(report["dependencies"].get("acme/tool").is_none());
+
+    fs::write(
+        temp_path.join("composer.json"),
+        composer(
+            serde_json::json!({"allowed-licenses": ["MIT", "GPL-3.0-only"],
+                                    "denied-licenses": []}),
+        ),
+    )
+    .unwrap();
+    fs::write(
+        temp_path.join("composer.lock"),
+        serde_json::json!({"content-hash": "", "packages": [
+            {"name": "acme/mit", "version": "1.0.0", "license": ["MIT"]}
+        ]})
+        .to_string(),
+    )
+    .unwrap();
+    let output = Command::new(get_lectern_binary_path())
+        .arg("licenses")
+        .current_dir(temp_path)
+        .output()
+        .expect("Failed to execute lectern licenses");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Summary by license"));
+}
@@ -0,0 +1,71 @@
+use lectern::lock_diff::{PackageChange, diff_locks};
+use lectern::models::model::Lock;
+use serde_json::json;
+
+fn lock(packages: serde_json::Value, packages_dev: serde_json::Value) -> Lock {
+    serde_json::from_value(json!({
+        "content-hash": "",
+        "packages": packages,
+        "packages-dev": packages_dev
+    }))
+    .unwrap()
+}
+
+fn package(name: &str, version: &str, reference: &str) -> serde_json::Value {
+    json!({
+        "name": name,
+        "version": version,
+        "source": {"type": "git", "url": format!("https://example.com/{name}.git"), "reference": reference}
+    })
+}
+
+#[test]
+fn test_diff_locks_lists_operations_by_kind() {
+    let old = lock(
+        json!([
+            package("acme/kept", "1.0.0", "a1"),
+            package("acme/newer", "1.0.0", "a1"),
+            package("acme/older", "2.1.0", "a1"),
+            package("acme/gone", "3.0.0", "a1"),
+            package("acme/branch", "dev-main", "1111111111"),
+        ]),
+        json!([package("acme/moved", "1.0.0", "a1")]),
+    );
+    let new = lock(
+        json!([
+            package("acme/moved", "1.0.0", "a1"),
+            package("acme/older", "2.0.5", "b2"),
+            package("acme/newer", "1.1.0", "b2"),
+            package("acme/kept", "1.0.0", "a1"),
+            package("acme/branch", "dev-main", "2222222222"),
+        ]),
+        json!([package("acme/added", "0.1.0", "c3")]),
+    );
+
+    let changes = diff_locks(Some(&old), &new);
+    let lines: Vec<String> = changes.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        lines,
+        vec![
+            "Installing acme/added (0.1.0)",
+            "Upgrading acme/branch (dev-main 1111111 => dev-main 2222222)",
+            "Upgrading acme/newer (1.0.0 => 1.1.0)",
+            "Downgrading acme/older (2.1.0 => 2.0.5)",
+            "Removing acme/gone (3.0.0)",
+        ]
+    );
+    assert_eq!(changes[0].name(), "acme/added");
+}
+
+#[test]
+fn test_diff_locks_without_a_previous_lock_installs_everything() {
+    let new = lock(json!([package("acme/one", "1.0.0", "a1")]), json!([]));
+    assert_eq!(
+        diff_locks(None, &new),
+        vec![PackageChange::Install {
+            name: "acme/one".to_string(),
+            version: "1.0.0".to_string(),
+        }]
+    );
+    assert!(diff_locks(Some(&new), &new).is_empty());
+}
@@ -1,3 +1,6 @@
+// Command test files are also built standalone, each pulling in `common` via #[path]
+#![allow(clippy::duplicate_mod)]
+
 // Core test modules
 mod dependency_tests;
 mod integration_tests;
@@ -8,30 +11,60 @@ mod version_tests;
 
 // Component test modules
 mod cache_tests;
+mod ci_tests;
 mod http_client_tests;
+mod journal_tests;
 mod utils_tests;
 mod installer_tests;
 mod autoload_tests;
+mod auth_tests;
+mod environments_test;
+mod placeholders_test;
+mod throttle_test;
+mod graph_test;
+mod timeouts_test;
+mod proxy_test;
+mod error_test;
+mod api_test;
+mod reporter_test;
+mod platform_test;
 
 // Shared test utilities (available to all test files)
 pub mod common;
 
 // Individual command test modules (one per command)
 mod browse_test;
+mod bump_test;
+mod check_lock_test;
+mod clean_test;
 mod clear_cache_test;
 mod depends_test;
 mod diagnose_test;
+mod docker_cache_config_test;
+mod exec_test;
+mod fingerprint_test;
 mod fund_test;
+mod global_test;
+mod graph_export_test;
 mod init_test;
 mod licenses_test;
 mod outdated_test;
+mod php_compat_test;
 mod prohibits_test;
+mod reinstall_test;
+mod require_test;
 mod project_test;
+mod rollback_test;
+mod sbom_test;
 mod script_test;
 mod search_test;
+mod self_update_test;
+mod shell_test;
 mod show_test;
 mod status_test;
 mod suggests_test;
+mod sync_test;
+mod third_party_test;
 mod validate_test;
 
 // Re-export strict testing utilities for use in other test modules
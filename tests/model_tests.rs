@@ -1,3 +1,4 @@
+use lectern::io::{lock_to_string, read_lock, write_file_atomic, write_lock};
 use lectern::models::model::{ComposerJson, DistInfo, Lock, LockedPackage, SourceInfo};
 use std::collections::BTreeMap;
 
@@ -31,6 +32,7 @@ fn test_composer_json_serialization() {
         repositories: None,
         config: None,
         scripts: None,
+        scripts_descriptions: None,
         extra: None,
         minimum_stability: Some("stable".to_string()),
         prefer_stable: Some(true),
@@ -80,6 +82,7 @@ fn test_composer_json_with_dev_dependencies() {
         repositories: None,
         config: None,
         scripts: None,
+        scripts_descriptions: None,
         extra: None,
         minimum_stability: None,
         prefer_stable: None,
@@ -101,6 +104,7 @@ fn test_locked_package_creation() {
         source_type: "git".to_string(),
         url: "https://github.com/example/package.git".to_string(),
         reference: "abc123".to_string(),
+        mirrors: None,
     };
 
     let dist_info = DistInfo {
@@ -108,6 +112,7 @@ fn test_locked_package_creation() {
         url: "https://api.github.com/repos/example/package/zipball/abc123".to_string(),
         reference: "abc123".to_string(),
         shasum: "".to_string(),
+        mirrors: None,
     };
 
     let locked_package = LockedPackage {
@@ -136,6 +141,11 @@ fn test_locked_package_creation() {
         time: None,
         bin: None,
         include_path: None,
+        version_normalized: None,
+        default_branch: None,
+        scripts: None,
+        abandoned: None,
+        other: serde_json::Map::new(),
     };
 
     assert_eq!(locked_package.name, "example/package");
@@ -173,6 +183,11 @@ fn test_lock_file_structure() {
         time: None,
         bin: None,
         include_path: None,
+        version_normalized: None,
+        default_branch: None,
+        scripts: None,
+        abandoned: None,
+        other: serde_json::Map::new(),
     }];
 
     let lock = Lock {
@@ -191,7 +206,10 @@ fn test_lock_file_structure() {
         prefer_lowest: false,
         platform: BTreeMap::new(),
         platform_dev: BTreeMap::new(),
+        platform_overrides: None,
         plugin_api_version: Some("2.3.0".to_string()),
+        other: serde_json::Map::new(),
+        empty_list_keys: Vec::new(),
     };
 
     let json = serde_json::to_string_pretty(&lock).unwrap();
@@ -207,6 +225,7 @@ fn test_source_and_dist_info() {
         source_type: "git".to_string(),
         url: "https://github.com/test/repo.git".to_string(),
         reference: "main".to_string(),
+        mirrors: None,
     };
 
     let dist = DistInfo {
@@ -214,6 +233,7 @@ fn test_source_and_dist_info() {
         url: "https://github.com/test/repo/archive/main.zip".to_string(),
         reference: "abc123def456".to_string(),
         shasum: "sha256:abcdef123456".to_string(),
+        mirrors: None,
     };
 
     assert_eq!(source.source_type, "git");
@@ -254,3 +274,93 @@ fn test_composer_json_with_repositories() {
     assert!(composer.repositories.is_some());
     assert_eq!(composer.repositories.unwrap().len(), 1);
 }
+
+fn fixture_path(name: &str) -> std::path::PathBuf {
+    std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(name)
+}
+
+#[test]
+fn test_composer_lock_round_trip_is_lossless() {
+    let fixture = fixture_path("composer-2.7.lock");
+    let original = std::fs::read_to_string(&fixture).unwrap();
+
+    let lock = read_lock(&fixture).unwrap();
+    assert_eq!(lock.plugin_api_version.as_deref(), Some("2.6.0"));
+    assert_eq!(lock.packages[0].default_branch, Some(true));
+    assert!(lock.packages[0].dist.as_ref().unwrap().mirrors.is_some());
+    assert!(lock.packages[0].other.contains_key("transport-options"));
+    let monolog = &lock.packages[1];
+    assert_eq!(monolog.authors.as_ref().unwrap()[0].name, "Jordi Boggiano");
+    assert_eq!(
+        monolog.support.as_ref().unwrap().issues.as_deref(),
+        Some("https://github.com/Seldaek/monolog/issues")
+    );
+    assert!(
+        monolog
+            .autoload
+            .as_ref()
+            .unwrap()
+            .psr4
+            .contains_key("Monolog\\")
+    );
+    assert_eq!(
+        lock.packages_dev[0].abandoned,
+        Some(serde_json::json!(true))
+    );
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let written_path = temp_dir.path().join("composer.lock");
+    write_lock(&written_path, &lock).unwrap();
+    let written = std::fs::read_to_string(&written_path).unwrap();
+
+    // Byte-for-byte identical to the input, including key order and formatting
+    assert_eq!(written, original);
+}
+
+#[test]
+fn test_composer_lock_legacy_empty_maps() {
+    // Composer 2.2 writes empty maps as [] and has no platform-overrides
+    let fixture = fixture_path("composer-2.2.lock");
+    let original = std::fs::read_to_string(&fixture).unwrap();
+
+    let lock = read_lock(&fixture).unwrap();
+    assert!(lock.stability_flags.is_empty());
+    assert!(lock.platform.is_empty());
+    assert!(lock.platform_overrides.is_none());
+
+    // Byte-for-byte identical to the input, with the empty maps still written as []
+    assert_eq!(lock_to_string(&lock).unwrap(), original);
+}
+
+#[test]
+fn test_write_file_atomic_keeps_permissions_and_line_endings() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let path = temp_dir.path().join("composer.json");
+
+    write_file_atomic(&path, "{\n}").unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "{\n}");
+
+    // A file checked out with CRLF endings stays CRLF
+    std::fs::write(&path, "{\r\n}").unwrap();
+    write_file_atomic(&path, "{\n    \"name\": \"acme/app\"\n}").unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "{\r\n    \"name\": \"acme/app\"\r\n}"
+    );
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o640)).unwrap();
+        write_file_atomic(&path, "{}").unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o640);
+    }
+
+    // No temp files are left behind
+    let entries: Vec<_> = std::fs::read_dir(temp_dir.path()).unwrap().collect();
+    assert_eq!(entries.len(), 1);
+}
@@ -25,7 +25,10 @@ fn test_outdated_command() {
     let stdout = String::from_utf8_lossy(&output.stdout);
     // Should either show outdated packages or indicate no lock file
     assert!(
-        stdout.contains("outdated") || stdout.contains("composer.lock") || stdout.contains("up to date") || output.status.success()
+        stdout.contains("outdated")
+            || stdout.contains("composer.lock")
+            || stdout.contains("up to date")
+            || output.status.success()
     );
 }
 
@@ -49,3 +52,220 @@ fn test_outdated_with_quiet_flag() {
     // Quiet mode should run without crashing (may fail without lock file)
     assert!(output.status.code().is_some());
 }
+
+fn lock_with(packages: &[(&str, &str)]) -> lectern::models::model::Lock {
+    let packages: Vec<serde_json::Value> = packages
+        .iter()
+        .map(|(name, version)| serde_json::json!({"name": name, "version": version}))
+        .collect();
+    serde_json::from_value(serde_json::json!({
+        "content-hash": "",
+        "packages": packages,
+    }))
+    .unwrap()
+}
+
+fn versions(list: &[&str]) -> Vec<lectern::resolver::packagist::P2Version> {
+    list.iter()
+        .map(|v| serde_json::from_value(serde_json::json!({"version": v})).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_workspace_segment_matches() {
+    use lectern::workspace::segment_matches;
+
+    assert!(segment_matches("*", "api"));
+    assert!(segment_matches("lib-*", "lib-http"));
+    assert!(segment_matches("*-bundle", "auth-bundle"));
+    assert!(segment_matches("a*b*c", "axxbyyc"));
+    assert!(!segment_matches("lib-*", "app"));
+    assert!(!segment_matches("a*bc", "abc-no"));
+    assert!(segment_matches("exact", "exact"));
+}
+
+#[test]
+fn test_workspace_members_expands_globs() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    fs::write(
+        root.join("composer.json"),
+        r#"{"extra": {"lectern": {"workspace": {"members": ["packages/*", "tools/cli"]}}}}"#,
+    )
+    .unwrap();
+    for member in ["packages/b", "packages/a", "tools/cli"] {
+        fs::create_dir_all(root.join(member)).unwrap();
+        fs::write(root.join(member).join("composer.json"), "{}").unwrap();
+    }
+    // Directories without composer.json are not members
+    fs::create_dir_all(root.join("packages/docs")).unwrap();
+
+    let members = lectern::workspace::workspace_members(root).unwrap();
+    let labels: Vec<String> = members
+        .iter()
+        .map(|m| lectern::workspace::member_label(root, m))
+        .collect();
+    assert_eq!(labels, vec!["packages/a", "packages/b", "tools/cli"]);
+
+    // A root with its own lock file is checked too
+    fs::write(root.join("composer.lock"), "{}").unwrap();
+    let members = lectern::workspace::workspace_members(root).unwrap();
+    assert_eq!(lectern::workspace::member_label(root, &members[0]), ".");
+}
+
+#[test]
+fn test_workspace_members_requires_configuration() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("composer.json"), r#"{"name": "a/b"}"#).unwrap();
+    assert!(lectern::workspace::workspace_members(temp_dir.path()).is_err());
+}
+
+#[test]
+fn test_find_outdated_uses_latest_stable() {
+    use lectern::commands::outdated::find_outdated;
+
+    let lock = lock_with(&[
+        ("acme/old", "1.0.0"),
+        ("acme/current", "v2.1.0"),
+        ("php", "8.2.0"),
+    ]);
+    let mut versions_map = std::collections::BTreeMap::new();
+    versions_map.insert(
+        "acme/old".to_string(),
+        versions(&["1.0.0", "1.4.2", "2.0.0-beta1"]),
+    );
+    versions_map.insert("acme/current".to_string(), versions(&["v2.0.0", "v2.1.0"]));
+
+    let outdated = find_outdated(&lock, &versions_map);
+    assert_eq!(outdated.len(), 1);
+    assert_eq!(outdated[0].name, "acme/old");
+    assert_eq!(outdated[0].current, "1.0.0");
+    assert_eq!(outdated[0].latest, "1.4.2");
+}
+
+#[test]
+fn test_find_outdated_with_four_number_versions() {
+    use lectern::commands::outdated::{find_outdated, is_behind};
+
+    assert!(is_behind("1.0.0", "1.0.0.1"));
+    assert!(!is_behind("1.0.0.1", "1.0.0"));
+    assert!(is_behind("2.1", "2.1.1"));
+
+    let lock = lock_with(&[("acme/patched", "1.0.0")]);
+    let mut versions_map = std::collections::BTreeMap::new();
+    versions_map.insert(
+        "acme/patched".to_string(),
+        versions(&["1.0.0", "1.0.0.2", "1.0.0.10", "1.0.1-RC1"]),
+    );
+
+    let outdated = find_outdated(&lock, &versions_map);
+    assert_eq!(outdated.len(), 1);
+    assert_eq!(outdated[0].latest, "1.0.0.10");
+}
+
+#[test]
+fn test_latest_stable_follows_composer_ordering() {
+    use lectern::commands::outdated::{is_behind, latest_stable_version};
+
+    // Patch releases are stable and sort above their release; suffixes ignore case
+    let list = versions(&["1.1.0-rc2", "1.0.0-P1", "1.0.0", "1.1.0-BETA1", "dev-main"]);
+    assert_eq!(latest_stable_version(&list).unwrap().version, "1.0.0-P1");
+    let list = versions(&["2.0.0", "1.9.0", "10.0.0-RC1", "2.0.0-patch2"]);
+    assert_eq!(
+        latest_stable_version(&list).unwrap().version,
+        "2.0.0-patch2"
+    );
+    assert!(latest_stable_version(&versions(&["1.0.0-RC1", "dev-main"])).is_none());
+
+    assert!(is_behind("1.0.0-RC1", "1.0.0"));
+    assert!(is_behind("1.0.0", "1.0.0-p1"));
+    assert!(!is_behind("1.0.0-pl1", "1.0.0"));
+}
+
+#[test]
+fn test_find_abandoned_prefers_current_metadata() {
+    use lectern::commands::outdated::find_abandoned;
+    use lectern::models::model::Abandoned;
+
+    let mut lock = lock_with(&[
+        ("acme/moved", "1.0.0"),
+        ("acme/dropped", "2.0.0"),
+        ("acme/revived", "3.0.0"),
+        ("acme/active", "1.0.0"),
+    ]);
+    // Only the lock knows about acme/dropped; acme/revived was un-abandoned since
+    lock.packages[1].abandoned = Some(serde_json::json!(true));
+    lock.packages[2].abandoned = Some(serde_json::json!(true));
+    let abandoned = |value: serde_json::Value| {
+        vec![
+            serde_json::from_value(serde_json::json!({"version": "1.0.0", "abandoned": value}))
+                .unwrap(),
+        ]
+    };
+    let mut versions_map = std::collections::BTreeMap::new();
+    versions_map.insert(
+        "acme/moved".to_string(),
+        abandoned(serde_json::json!("acme/new")),
+    );
+    versions_map.insert("acme/revived".to_string(), versions(&["3.0.0"]));
+    versions_map.insert(
+        "acme/active".to_string(),
+        abandoned(serde_json::json!(false)),
+    );
+
+    assert_eq!(
+        find_abandoned(&lock, &versions_map),
+        vec![
+            ("acme/dropped".to_string(), Abandoned { replacement: None }),
+            (
+                "acme/moved".to_string(),
+                Abandoned {
+                    replacement: Some("acme/new".to_string())
+                }
+            ),
+        ]
+    );
+    assert_eq!(
+        Abandoned {
+            replacement: Some("acme/new".to_string())
+        }
+        .to_string(),
+        "Use acme/new instead."
+    );
+}
+
+#[test]
+fn test_ignore_list_from_manifest_and_flags() {
+    use lectern::commands::outdated::{OutdatedPackage, remove_ignored};
+    use lectern::ignore_list::IgnoreList;
+
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("composer.json"),
+        r#"{"extra": {"lectern": {"ignore": ["symfony/*"]}}}"#,
+    )
+    .unwrap();
+    let ignore = IgnoreList::for_project(temp_dir.path(), &["Acme/Legacy".to_string()]).unwrap();
+    assert_eq!(ignore.patterns(), ["symfony/*", "acme/legacy"]);
+    assert!(ignore.matches("symfony/console"));
+    assert!(ignore.matches("acme/legacy"));
+    assert!(!ignore.matches("acme/legacy-bridge"));
+    assert!(!ignore.matches("monolog/monolog"));
+
+    let outdated = |name: &str| OutdatedPackage {
+        name: name.to_string(),
+        current: "1.0.0".to_string(),
+        latest: "2.0.0".to_string(),
+        description: String::new(),
+    };
+    let mut report = vec![
+        outdated("symfony/yaml"),
+        outdated("monolog/monolog"),
+        outdated("acme/legacy"),
+    ];
+    assert_eq!(remove_ignored(&mut report, &ignore), 2);
+    assert_eq!(report, vec![outdated("monolog/monolog")]);
+
+    let none = IgnoreList::for_project(&temp_dir.path().join("missing"), &[]).unwrap();
+    assert!(none.is_empty());
+}
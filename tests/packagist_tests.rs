@@ -1,5 +1,6 @@
 use lectern::resolver::packagist::{
-    P2Dist, P2Source, P2Version, fetch_packagist_versions_cached, search_packagist,
+    P2Dist, P2Source, P2Version, SecurityAdvisory, fetch_packagist_versions_cached,
+    search_packagist,
 };
 use std::collections::BTreeMap;
 
@@ -241,3 +242,119 @@ fn test_p2_version_metadata_extraction() {
     assert!(license.is_some());
     assert_eq!(license.unwrap()[0].as_str(), Some("MIT"));
 }
+
+#[test]
+fn test_security_advisory_affects() {
+    let advisory: SecurityAdvisory = serde_json::from_value(serde_json::json!({
+        "advisoryId": "PKSA-test",
+        "packageName": "test/package",
+        "title": "Remote code execution",
+        "cve": "CVE-2024-0001",
+        "affectedVersions": ">=1.0.0,<1.2.3|>=2.0.0,<2.0.4",
+        "reportedAt": "2024-01-01 00:00:00"
+    }))
+    .unwrap();
+
+    assert_eq!(advisory.affects("1.2.2"), Some(true));
+    assert_eq!(advisory.affects("v2.0.3"), Some(true));
+    assert_eq!(advisory.affects("1.2.3"), Some(false));
+    assert_eq!(advisory.affects("2.1.0"), Some(false));
+    assert_eq!(advisory.affects("not-a-version"), None);
+
+    // A range that can't be parsed only leaves versions outside the others undecided
+    let partly_readable = SecurityAdvisory {
+        affected_versions: "not a range|>=2.0.0,<2.0.4".to_string(),
+        ..advisory.clone()
+    };
+    assert_eq!(partly_readable.affects("2.0.1"), Some(true));
+    assert_eq!(partly_readable.affects("2.1.0"), None);
+
+    let empty = SecurityAdvisory {
+        affected_versions: String::new(),
+        ..advisory
+    };
+    assert_eq!(empty.affects("1.0.0"), None);
+}
+
+#[test]
+fn test_metadata_changes_parsing() {
+    use lectern::resolver::packagist::MetadataChanges;
+
+    let json = r#"{
+        "actions": [
+            {"type": "update", "package": "monolog/monolog", "time": 16945000000000},
+            {"type": "update", "package": "monolog/monolog~dev", "time": 16945000000001},
+            {"type": "delete", "package": "acme/abandoned", "time": 16945000000002}
+        ],
+        "timestamp": 16945000000003
+    }"#;
+    let changes: MetadataChanges = serde_json::from_str(json).unwrap();
+    assert_eq!(changes.timestamp, 16945000000003);
+    assert!(changes.error.is_none());
+    let changed: Vec<String> = changes.changed_packages().into_iter().collect();
+    assert_eq!(changed, vec!["acme/abandoned", "monolog/monolog"]);
+
+    let resync: MetadataChanges =
+        serde_json::from_str(r#"{"error": "Please resync", "timestamp": 16945000000100}"#).unwrap();
+    assert!(resync.error.is_some());
+    assert!(resync.actions.is_empty());
+}
+
+#[test]
+fn test_parse_p2_drops_unset_fields() {
+    use lectern::resolver::packagist::parse_p2;
+
+    let body = br#"{
+        "minified": "composer/2.0",
+        "packages": {
+            "acme/lib": [
+                {
+                    "name": "acme/lib",
+                    "version": "2.0.0",
+                    "version_normalized": "2.0.0.0",
+                    "dist": {"type": "zip", "url": "https://example.com/2.zip", "reference": "b", "shasum": "__unset"},
+                    "require": {"php": ">=8.1", "psr/log": "__unset"},
+                    "extra": {"branch-alias": "__unset", "laravel": {"providers": ["A"]}},
+                    "license": ["MIT"],
+                    "abandoned": "__unset"
+                },
+                {
+                    "version": "1.0.0",
+                    "source": "__unset",
+                    "require": "__unset",
+                    "extra": null
+                }
+            ]
+        }
+    }"#;
+
+    let versions = parse_p2("acme/lib", body).unwrap();
+    assert_eq!(versions.len(), 2);
+    let latest = &versions[0];
+    assert_eq!(latest.version_normalized, "2.0.0.0");
+    let dist = latest.dist.as_ref().unwrap();
+    assert_eq!(dist.url.as_deref(), Some("https://example.com/2.zip"));
+    assert!(dist.shasum.is_none());
+    assert_eq!(
+        latest.require.as_ref().unwrap().keys().collect::<Vec<_>>(),
+        vec!["php"]
+    );
+    assert_eq!(
+        latest.extra,
+        Some(serde_json::json!({"laravel": {"providers": ["A"]}}))
+    );
+    assert!(latest.abandonment().is_none());
+    assert_eq!(latest.other["license"], serde_json::json!(["MIT"]));
+
+    let oldest = &versions[1];
+    assert_eq!(oldest.version_normalized, "");
+    assert!(oldest.source.is_none() && oldest.require.is_none() && oldest.extra.is_none());
+
+    // Cached metadata is stored as serialized versions and reads back the same
+    let cached: Vec<P2Version> =
+        serde_json::from_value(serde_json::to_value(&versions).unwrap()).unwrap();
+    assert_eq!(cached[0].other, latest.other);
+
+    assert!(parse_p2("acme/other", body).unwrap().is_empty());
+    assert!(parse_p2("acme/lib", br#"{"packages": {"acme/lib": [{}]}}"#).is_err());
+}
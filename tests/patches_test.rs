@@ -0,0 +1,231 @@
+use lectern::installer::InstallState;
+use lectern::installer::install_packages;
+use lectern::installer::installer_patches::{Patch, apply_patch, patches_from_extra};
+use lectern::models::model::LockedPackage;
+use serde_json::json;
+use std::fs;
+use tempfile::TempDir;
+
+const FIX_GREETING: &str = "\
+--- a/src/Greeter.php
++++ b/src/Greeter.php
+@@ -3,3 +3,3 @@
+ function greet()
+ {
+-    return 'Helo';
++    return 'Hello';
+";
+
+fn greeter(dir: &std::path::Path, lines_before: usize) {
+    fs::create_dir_all(dir.join("src")).unwrap();
+    let padding = "// padding\n".repeat(lines_before);
+    fs::write(
+        dir.join("src/Greeter.php"),
+        format!("<?php\n\n{padding}function greet()\n{{\n    return 'Helo';\n}}\n"),
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_apply_patch() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    greeter(dir, 0);
+
+    apply_patch(dir, FIX_GREETING).unwrap();
+    assert_eq!(
+        fs::read_to_string(dir.join("src/Greeter.php")).unwrap(),
+        "<?php\n\nfunction greet()\n{\n    return 'Hello';\n}\n"
+    );
+    // Applied twice, the old lines are gone
+    assert!(apply_patch(dir, FIX_GREETING).is_err());
+}
+
+#[test]
+fn test_apply_patch_finds_moved_lines() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    greeter(dir, 5);
+
+    apply_patch(dir, FIX_GREETING).unwrap();
+    let patched = fs::read_to_string(dir.join("src/Greeter.php")).unwrap();
+    assert!(patched.contains("return 'Hello';"));
+    assert_eq!(patched.matches("// padding").count(), 5);
+}
+
+#[test]
+fn test_apply_patch_adds_and_removes_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    fs::write(dir.join("OLD.md"), "old\n").unwrap();
+
+    let patch = "\
+diff --git a/NEW.md b/NEW.md
+new file mode 100644
+--- /dev/null
++++ b/docs/NEW.md
+@@ -0,0 +1,2 @@
++first
++second
+\\ No newline at end of file
+--- a/OLD.md
++++ /dev/null
+@@ -1 +0,0 @@
+-old
+";
+    apply_patch(dir, patch).unwrap();
+    assert_eq!(
+        fs::read_to_string(dir.join("docs/NEW.md")).unwrap(),
+        "first\nsecond"
+    );
+    assert!(!dir.join("OLD.md").exists());
+}
+
+#[test]
+fn test_apply_patch_without_prefix_and_crlf() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    fs::write(dir.join("a.txt"), "one\r\ntwo\r\nthree\r\n").unwrap();
+
+    let patch = "--- a.txt\n+++ a.txt\n@@ -1,3 +1,3 @@\n one\n-two\n+2\n three\n";
+    apply_patch(dir, patch).unwrap();
+    assert_eq!(
+        fs::read_to_string(dir.join("a.txt")).unwrap(),
+        "one\r\n2\r\nthree\r\n"
+    );
+}
+
+#[test]
+fn test_failed_patch_writes_nothing() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    fs::write(dir.join("a.txt"), "one\n").unwrap();
+    fs::write(dir.join("b.txt"), "two\n").unwrap();
+
+    let patch = "\
+--- a/a.txt
++++ b/a.txt
+@@ -1 +1 @@
+-one
++1
+--- a/b.txt
++++ b/b.txt
+@@ -1 +1 @@
+-three
++3
+";
+    let err = apply_patch(dir, patch).unwrap_err();
+    assert!(format!("{err:#}").contains("hunk 1 does not apply"));
+    assert_eq!(fs::read_to_string(dir.join("a.txt")).unwrap(), "one\n");
+    assert!(apply_patch(dir, "not a diff").is_err());
+}
+
+#[test]
+fn test_patches_from_extra() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("patches.json"),
+        r#"{"patches": {"drupal/core": {"Fix menus": "patches/menus.patch"}}}"#,
+    )
+    .unwrap();
+    let extra = json!({
+        "patches": {
+            "Acme/Lib": {"Fix greeting": "patches/greeting.patch"},
+            "drupal/core": [{"description": "Upstream fix", "url": "https://example.com/1.patch"}]
+        },
+        "patches-file": "patches.json"
+    });
+
+    let patches = patches_from_extra(Some(&extra), temp_dir.path()).unwrap();
+    assert_eq!(
+        patches["acme/lib"],
+        vec![Patch {
+            description: "Fix greeting".to_string(),
+            url: "patches/greeting.patch".to_string()
+        }]
+    );
+    let core: Vec<&str> = patches["drupal/core"]
+        .iter()
+        .map(|p| p.description.as_str())
+        .collect();
+    assert_eq!(core, vec!["Upstream fix", "Fix menus"]);
+
+    assert!(
+        patches_from_extra(None, temp_dir.path())
+            .unwrap()
+            .is_empty()
+    );
+    assert!(patches_from_extra(Some(&json!({"patches": ["x"]})), temp_dir.path()).is_err());
+}
+
+#[tokio::test]
+async fn test_install_applies_and_records_patches() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("lib");
+    greeter(&source, 0);
+    let project = temp_dir.path().join("project");
+    fs::create_dir_all(project.join("patches")).unwrap();
+    fs::write(project.join("patches/greeting.patch"), FIX_GREETING).unwrap();
+    fs::write(
+        project.join("composer.json"),
+        json!({"extra": {"patches": {"acme/lib": {"Fix greeting": "patches/greeting.patch"}}}})
+            .to_string(),
+    )
+    .unwrap();
+    let package: LockedPackage = serde_json::from_value(json!({
+        "name": "acme/lib",
+        "version": "dev-main",
+        "source": {"type": "path", "url": source.to_string_lossy(), "reference": ""}
+    }))
+    .unwrap();
+
+    // Installing again starts from the pristine files rather than patching twice
+    for _ in 0..2 {
+        install_packages(std::slice::from_ref(&package), &project)
+            .await
+            .unwrap();
+        let installed =
+            fs::read_to_string(project.join("vendor/acme/lib/src/Greeter.php")).unwrap();
+        assert!(installed.contains("return 'Hello';"));
+    }
+    let state = InstallState::load(&project.join("vendor"));
+    assert_eq!(state.patches_of("acme/lib").len(), 1);
+    assert_eq!(state.patches_of("acme/lib")[0].len(), 64);
+
+    // installed.json lists what was applied, as composer-patches does
+    let installed: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(project.join("vendor/composer/installed.json")).unwrap(),
+    )
+    .unwrap();
+    let lib = &installed["packages"][0];
+    assert_eq!(lib["name"], "acme/lib");
+    assert_eq!(lib["install-path"], "../acme/lib");
+    assert_eq!(
+        lib["extra"]["patches_applied"],
+        json!([{
+            "description": "Fix greeting",
+            "url": "patches/greeting.patch",
+            "sha256": state.patches_of("acme/lib")[0]
+        }])
+    );
+}
+
+#[tokio::test]
+async fn test_patch_urls_are_read_from_cache() {
+    use lectern::installer::installer_patches::{cached_patch_path, load_patches};
+
+    // Nothing listens on the discard port, so only the cached copy can be used
+    let url = "http://127.0.0.1:9/lectern-test/greeting.patch";
+    let cached = cached_patch_path(url);
+    fs::create_dir_all(cached.parent().unwrap()).unwrap();
+    fs::write(&cached, FIX_GREETING).unwrap();
+
+    let patches = vec![Patch {
+        description: "Fix greeting".to_string(),
+        url: url.to_string(),
+    }];
+    let temp_dir = TempDir::new().unwrap();
+    let loaded = load_patches(&patches, temp_dir.path()).await.unwrap();
+    assert_eq!(loaded[0].contents, FIX_GREETING);
+    let _ = fs::remove_file(&cached);
+}
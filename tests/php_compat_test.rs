@@ -0,0 +1,116 @@
+use lectern::commands::php_compat::{find_php_blockers, minimal_supporting_version, resolve_fixes};
+use lectern::models::model::{ComposerJson, Lock};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+#[path = "common/mod.rs"]
+mod common;
+use common::{ensure_lectern_binary, get_lectern_binary_path};
+
+fn composer() -> ComposerJson {
+    serde_json::from_value(json!({
+        "name": "acme/app",
+        "require": {"php": ">=8.1", "acme/framework": "^2.0", "acme/modern": "^1.0"}
+    }))
+    .unwrap()
+}
+
+fn lock() -> Lock {
+    serde_json::from_value(json!({
+        "content-hash": "",
+        "packages": [
+            {"name": "acme/framework", "version": "2.1.0",
+             "require": {"php": "^8.1", "acme/legacy-util": "~1.2.0"}},
+            {"name": "acme/legacy-util", "version": "1.2.3", "require": {"php": ">=7.4,<8.3"}},
+            {"name": "acme/modern", "version": "1.0.0", "require": {"php": "^8.0"}}
+        ]
+    }))
+    .unwrap()
+}
+
+fn versions(list: &[(&str, &str)]) -> Vec<lectern::resolver::packagist::P2Version> {
+    list.iter()
+        .map(|(version, php)| {
+            serde_json::from_value(json!({"version": version, "require": {"php": php}})).unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn test_find_php_blockers_with_chain() {
+    let blockers = find_php_blockers(&composer(), &lock(), "8.3");
+    assert_eq!(blockers.len(), 1);
+    let blocker = &blockers[0];
+    assert_eq!(blocker.name, "acme/legacy-util");
+    assert_eq!(blocker.constraint, ">=7.4,<8.3");
+    assert_eq!(
+        blocker.chain,
+        vec!["acme/app", "acme/framework", "acme/legacy-util"]
+    );
+
+    assert!(find_php_blockers(&composer(), &lock(), "8.2").is_empty());
+}
+
+#[test]
+fn test_minimal_supporting_version() {
+    let list = versions(&[
+        ("1.2.3", ">=7.4,<8.3"),
+        ("1.2.4", ">=7.4,<8.3"),
+        ("1.3.0", ">=8.1"),
+        ("2.0.0", ">=8.2"),
+        ("2.1.0-beta1", ">=8.2"),
+    ]);
+    let fix = minimal_supporting_version(&list, "1.2.3", "8.3").unwrap();
+    assert_eq!(fix.version, "1.3.0");
+    assert!(minimal_supporting_version(&list, "1.2.3", "9.0").is_some());
+    assert!(minimal_supporting_version(&versions(&[("1.2.4", "<8.3")]), "1.2.3", "8.3").is_none());
+}
+
+#[test]
+fn test_resolve_fixes_reports_constraints_holding_back() {
+    let mut blockers = find_php_blockers(&composer(), &lock(), "8.3");
+    let mut versions_map = BTreeMap::new();
+    versions_map.insert(
+        "acme/legacy-util".to_string(),
+        versions(&[("1.2.3", "<8.3"), ("1.3.0", ">=8.1")]),
+    );
+    resolve_fixes(&mut blockers, &composer(), &lock(), &versions_map, "8.3");
+
+    assert_eq!(blockers[0].fix.as_deref(), Some("1.3.0"));
+    // acme/framework pins ~1.2.0, which excludes the fix
+    assert_eq!(
+        blockers[0].held_back_by,
+        vec![("acme/framework".to_string(), "~1.2.0".to_string())]
+    );
+}
+
+#[test]
+fn test_php_compat_command_all_compatible() {
+    ensure_lectern_binary();
+
+    let temp_dir = TempDir::new().unwrap();
+    let temp_path = temp_dir.path();
+    fs::write(
+        temp_path.join("composer.json"),
+        serde_json::to_string(&composer()).unwrap(),
+    )
+    .unwrap();
+    fs::write(
+        temp_path.join("composer.lock"),
+        serde_json::to_string(&lock()).unwrap(),
+    )
+    .unwrap();
+
+    let output = Command::new(get_lectern_binary_path())
+        .args(["php-compat", "8.2"])
+        .current_dir(temp_path)
+        .output()
+        .expect("Failed to execute lectern php-compat");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("support PHP 8.2"), "{stdout}");
+}
@@ -0,0 +1,95 @@
+use lectern::php_scanner::find_classes;
+
+#[test]
+fn test_find_classes_with_namespaces() {
+    let source = br#"<?php
+namespace Acme\Http;
+
+use Other\Thing;
+
+abstract class Request {}
+final readonly class Response extends Message implements \Stringable {}
+interface Middleware {}
+trait Macroable {}
+enum Method: string { case Get = 'GET'; }
+enum Status { case Ok; }
+"#;
+    assert_eq!(
+        find_classes(source),
+        vec![
+            "Acme\\Http\\Request",
+            "Acme\\Http\\Response",
+            "Acme\\Http\\Middleware",
+            "Acme\\Http\\Macroable",
+            "Acme\\Http\\Method",
+            "Acme\\Http\\Status",
+        ]
+    );
+
+    // Braced namespaces, including the global one
+    let source = br#"<?php
+namespace Acme { class First {} }
+namespace { class Second {} }
+"#;
+    assert_eq!(find_classes(source), vec!["Acme\\First", "Second"]);
+}
+
+#[test]
+fn test_find_classes_ignores_what_only_looks_like_a_declaration() {
+    let source = br#"<?php
+namespace App;
+
+// class InComment {}
+# class InHashComment {}
+/* class InBlock {} */
+#[Attribute(flags: Attribute::TARGET_CLASS)]
+class Real
+{
+    public function class() { return static::class . $this->class . $this?->class; }
+    public function enum() { $x = new class extends Base {}; return $x; }
+    const ENUM = 'class Fake {}';
+    private $sql = "SELECT * FROM class WHERE \"class Quoted {}\"";
+    private $doc = <<<EOT
+        class InHeredoc {}
+        EOT;
+    private $now = <<<'EOT'
+class InNowdoc {}
+EOT;
+    public function make() { return enum_exists(Status::class) ? makeEnum(class: 1) : null; }
+}
+"#;
+    assert_eq!(find_classes(source), vec!["App\\Real"]);
+}
+
+#[test]
+fn test_find_classes_handles_conditional_and_half_open_files() {
+    // Declarations inside conditions count, as in Composer's classmap
+    let source = br#"<?php
+if (!class_exists('Polyfill', false)) {
+    class Polyfill {}
+}
+"#;
+    assert_eq!(find_classes(source), vec!["Polyfill"]);
+
+    // Inline HTML, short tags and a closing tag inside a line comment
+    let source = br#"<html><?= $title ?><p>class NotCode {}</p>
+<?php // class Commented ?> class AlsoNotCode {}
+<?php class Template {}"#;
+    assert_eq!(find_classes(source), vec!["Template"]);
+
+    // A file cut off in the middle of a string or comment keeps what came before
+    assert_eq!(
+        find_classes(b"<?php class Kept {} $x = 'unterminated"),
+        vec!["Kept"]
+    );
+    assert_eq!(
+        find_classes(b"<?php class Kept {} /* unterminated"),
+        vec!["Kept"]
+    );
+    assert_eq!(
+        find_classes(b"<?php class Kept {} $x = <<<EOT\nclass Lost {}"),
+        vec!["Kept"]
+    );
+    assert!(find_classes(b"no php here").is_empty());
+    assert!(find_classes(b"<?php\nclass").is_empty());
+}
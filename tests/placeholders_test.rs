@@ -0,0 +1,72 @@
+use lectern::placeholders::{expand_composer_with, expand_str};
+use serde_json::json;
+use std::fs;
+use tempfile::TempDir;
+
+fn env(name: &str) -> Option<String> {
+    match name {
+        "REPO_HOST" => Some("git.example.com".to_string()),
+        "DEPLOY_TOKEN" => Some("s3cr3t".to_string()),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_expand_str() {
+    let mut missing = Vec::new();
+    assert_eq!(
+        expand_str("https://${REPO_HOST}/acme.git", &env, &mut missing),
+        "https://git.example.com/acme.git"
+    );
+    // Escaped placeholders and bare `$` are left for the shell
+    assert_eq!(
+        expand_str("echo $${HOME} $PATH ${not-a-var}", &env, &mut missing),
+        "echo ${HOME} $PATH ${not-a-var}"
+    );
+    assert!(missing.is_empty());
+
+    expand_str("${UNSET_ONE}/${UNSET_ONE}/${UNSET_TWO}", &env, &mut missing);
+    assert_eq!(missing, vec!["UNSET_ONE", "UNSET_TWO"]);
+}
+
+#[test]
+fn test_expand_composer_sections() {
+    let mut composer = json!({
+        "description": "Costs ${DEPLOY_TOKEN}",
+        "repositories": [{"type": "vcs", "url": "https://${REPO_HOST}/acme/lib.git"}],
+        "config": {"gitlab-token": {"${REPO_HOST}": "${DEPLOY_TOKEN}"}},
+        "scripts": {"deploy": ["deploy --token=${DEPLOY_TOKEN}"]}
+    });
+    expand_composer_with(&mut composer, env).unwrap();
+
+    assert_eq!(
+        composer["repositories"][0]["url"],
+        "https://git.example.com/acme/lib.git"
+    );
+    assert_eq!(composer["config"]["gitlab-token"]["${REPO_HOST}"], "s3cr3t");
+    assert_eq!(composer["scripts"]["deploy"][0], "deploy --token=s3cr3t");
+    // Only repositories, config and scripts are expanded
+    assert_eq!(composer["description"], "Costs ${DEPLOY_TOKEN}");
+}
+
+#[test]
+fn test_read_composer_json_reports_missing_variables() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("composer.json");
+    fs::write(
+        &path,
+        r#"{"repositories": [{"type": "vcs", "url": "${LECTERN_TEST_UNSET_REPO_URL}"}]}"#,
+    )
+    .unwrap();
+
+    let err = lectern::io::read_composer_json(&path).unwrap_err();
+    assert!(
+        err.to_string().contains("LECTERN_TEST_UNSET_REPO_URL"),
+        "{err}"
+    );
+
+    // Commands that rewrite composer.json keep the placeholder
+    let raw = lectern::io::read_composer_json_raw(&path).unwrap();
+    let written = serde_json::to_string(&raw).unwrap();
+    assert!(written.contains("${LECTERN_TEST_UNSET_REPO_URL}"));
+}
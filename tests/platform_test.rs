@@ -0,0 +1,67 @@
+use lectern::platform::{
+    PlatformPackage, apply_platform_overrides, platform_packages_from, platform_version,
+};
+use serde_json::json;
+use std::collections::BTreeMap;
+
+#[test]
+fn test_platform_version() {
+    assert_eq!(platform_version("8.3.1-1ubuntu22.04"), "8.3.1");
+    assert_eq!(platform_version("OpenSSL 3.0.2 15 Mar 2022"), "3.0.2");
+    assert_eq!(platform_version("10.42 2022-12-11"), "10.42");
+    assert_eq!(platform_version(""), "0");
+}
+
+#[test]
+fn test_platform_packages_from_probe() {
+    let packages = platform_packages_from(&json!({
+        "php": "8.3.1-1ubuntu22.04",
+        "bits": 64,
+        "extensions": {"Core": "8.3.1", "Zend OPcache": "8.3.1", "json": "8.3.1", "mbstring": ""},
+        "libs": {"openssl": "OpenSSL 3.0.2 15 Mar 2022"}
+    }));
+    let names: Vec<_> = packages
+        .iter()
+        .map(|package| (package.name.as_str(), package.version.as_str()))
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            ("ext-core", "8.3.1"),
+            ("ext-json", "8.3.1"),
+            ("ext-mbstring", "0"),
+            ("ext-zend-opcache", "8.3.1"),
+            ("lib-openssl", "3.0.2"),
+            ("php", "8.3.1"),
+            ("php-64bit", "8.3.1"),
+        ]
+    );
+    assert_eq!(packages[1].description, "The json PHP extension");
+}
+
+#[test]
+fn test_platform_overrides() {
+    let mut packages = vec![
+        PlatformPackage {
+            name: "ext-intl".to_string(),
+            version: "8.3.1".to_string(),
+            description: "The intl PHP extension".to_string(),
+        },
+        PlatformPackage {
+            name: "php".to_string(),
+            version: "8.3.1".to_string(),
+            description: "The PHP interpreter".to_string(),
+        },
+    ];
+    let overrides = BTreeMap::from([
+        ("php".to_string(), "8.1.0".to_string()),
+        ("ext-intl".to_string(), "false".to_string()),
+        ("ext-redis".to_string(), "6.0.0".to_string()),
+    ]);
+    apply_platform_overrides(&mut packages, &overrides);
+    let names: Vec<_> = packages
+        .iter()
+        .map(|package| (package.name.as_str(), package.version.as_str()))
+        .collect();
+    assert_eq!(names, vec![("ext-redis", "6.0.0"), ("php", "8.1.0")]);
+}
@@ -0,0 +1,62 @@
+use lectern::profiler::{Phase, Profile, record_package, recorded_profile};
+use std::time::Duration;
+
+#[test]
+fn test_profile_sums_phases_and_ranks_packages() {
+    let mut profile = Profile::new();
+    profile.record(Phase::Resolution, Duration::from_millis(40), None);
+    profile.record(
+        Phase::Download,
+        Duration::from_millis(300),
+        Some("acme/big"),
+    );
+    profile.record(
+        Phase::Extraction,
+        Duration::from_millis(200),
+        Some("acme/big"),
+    );
+    profile.record(
+        Phase::Download,
+        Duration::from_millis(350),
+        Some("acme/slow"),
+    );
+    profile.record(
+        Phase::Download,
+        Duration::from_millis(10),
+        Some("acme/small"),
+    );
+
+    let download = &profile.phases[&Phase::Download];
+    assert_eq!(download.time, Duration::from_millis(660));
+    assert_eq!(download.count, 3);
+    assert_eq!(
+        profile.slowest_packages(2),
+        vec![
+            ("acme/big", Duration::from_millis(500)),
+            ("acme/slow", Duration::from_millis(350))
+        ]
+    );
+
+    let lines = profile.report_lines();
+    // Phases are listed in the order a run goes through them
+    assert!(lines[0].trim_start().starts_with("Resolution"));
+    assert!(lines[1].contains("over 3 package(s)"));
+    assert!(lines.iter().any(|line| line.contains("Slowest packages")));
+    let big = lines.iter().find(|line| line.contains("acme/big")).unwrap();
+    assert!(
+        big.contains("download 300.00ms, extraction 200.00ms"),
+        "{big}"
+    );
+}
+
+#[test]
+fn test_nothing_is_recorded_without_profile() {
+    record_package("acme/unprofiled", Phase::Download, Duration::from_secs(1));
+    assert!(!recorded_profile().packages.contains_key("acme/unprofiled"));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_peak_memory_is_reported_on_linux() {
+    assert!(lectern::profiler::peak_memory().is_some_and(|bytes| bytes > 0));
+}
@@ -23,10 +23,10 @@ fn test_prohibits_command() {
         .output()
         .expect("Failed to execute lectern prohibits");
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
     assert!(
-        stdout.contains("composer.lock") || stdout.contains("No packages") || output.status.success(),
-        "Should handle missing lock file"
+        !output.status.success() && stderr.contains("composer.lock"),
+        "Should fail on a missing lock file"
     );
 }
 
@@ -0,0 +1,123 @@
+use lectern::commands::reinstall::select_locked_packages;
+use lectern::models::model::Lock;
+use serde_json::json;
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+#[path = "common/mod.rs"]
+mod common;
+use common::{ensure_lectern_binary, get_lectern_binary_path};
+
+#[test]
+fn test_select_locked_packages() {
+    let lock: Lock = serde_json::from_value(json!({
+        "content-hash": "",
+        "packages": [
+            {"name": "symfony/console", "version": "7.0.0"},
+            {"name": "symfony/string", "version": "7.0.0"},
+            {"name": "psr/log", "version": "3.0.0"}
+        ],
+        "packages-dev": [{"name": "phpunit/phpunit", "version": "10.5.0"}]
+    }))
+    .unwrap();
+
+    let names = |patterns: &[&str]| -> Vec<String> {
+        let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+        select_locked_packages(&lock, &patterns)
+            .unwrap()
+            .iter()
+            .map(|pkg| pkg.name.clone())
+            .collect()
+    };
+    assert_eq!(names(&["psr/log"]), vec!["psr/log"]);
+    assert_eq!(
+        names(&["symfony/*", "Symfony/Console"]),
+        vec!["symfony/console", "symfony/string"]
+    );
+    assert_eq!(names(&["phpunit/phpunit"]), vec!["phpunit/phpunit"]);
+    assert!(select_locked_packages(&lock, &["acme/missing".to_string()]).is_err());
+}
+
+#[tokio::test]
+async fn test_reinstall_restores_modified_package() {
+    use lectern::cli::ReinstallArgs;
+    use lectern::installer::installer_utils::get_cached_package_path;
+    use std::io::Write;
+
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    zip.start_file(
+        "acme-lib-abc/src/Lib.php",
+        zip::write::SimpleFileOptions::default(),
+    )
+    .unwrap();
+    zip.write_all(b"<?php class Lib {}").unwrap();
+    let archive = zip.finish().unwrap().into_inner();
+
+    // Nothing listens here; the package is installed from the archive cache
+    let url = "http://127.0.0.1:9/lectern-reinstall-test/lib.zip";
+    let cached = get_cached_package_path("acme/reinstall-lib", "1.0.0", url);
+    fs::create_dir_all(cached.parent().unwrap()).unwrap();
+    fs::write(&cached, &archive).unwrap();
+
+    let temp_dir = TempDir::new().unwrap();
+    let project = temp_dir.path();
+    let installed = project.join("vendor/acme/reinstall-lib");
+    fs::create_dir_all(installed.join("src")).unwrap();
+    fs::write(installed.join("src/Lib.php"), "corrupted").unwrap();
+    fs::write(installed.join("stray.txt"), "local edit").unwrap();
+    let lock = json!({
+        "content-hash": "",
+        "packages": [{
+            "name": "acme/reinstall-lib",
+            "version": "1.0.0",
+            "dist": {"type": "zip", "url": url, "reference": "abc", "shasum": ""}
+        }]
+    });
+    fs::write(project.join("composer.lock"), lock.to_string()).unwrap();
+
+    let args = |no_cache| ReinstallArgs {
+        packages: vec!["acme/reinstall-lib".to_string()],
+        no_cache,
+        prefer_source: false,
+    };
+    lectern::commands::reinstall(&args(false), project)
+        .await
+        .unwrap();
+    assert_eq!(
+        fs::read_to_string(installed.join("src/Lib.php")).unwrap(),
+        "<?php class Lib {}"
+    );
+    assert!(!installed.join("stray.txt").exists());
+    // The lock is left alone
+    assert_eq!(
+        fs::read_to_string(project.join("composer.lock")).unwrap(),
+        lock.to_string()
+    );
+
+    // --no-cache drops the archive, so it has to be downloaded again (and can't be here)
+    assert!(
+        lectern::commands::reinstall(&args(true), project)
+            .await
+            .is_err()
+    );
+    assert!(!cached.exists());
+}
+
+#[test]
+fn test_reinstall_command_rejects_unknown_package() {
+    ensure_lectern_binary();
+
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("composer.lock"),
+        json!({"content-hash": "", "packages": []}).to_string(),
+    )
+    .unwrap();
+    let output = Command::new(get_lectern_binary_path())
+        .args(["reinstall", "acme/unknown"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute lectern reinstall");
+    assert!(!output.status.success());
+}
@@ -0,0 +1,75 @@
+use lectern::output::{Level, strip_symbols};
+
+#[path = "common/mod.rs"]
+mod common;
+use common::{ensure_lectern_binary, get_lectern_binary_path};
+
+#[test]
+fn test_levels_follow_quiet_and_verbosity() {
+    assert!(Level::Info.shown(false, 0));
+    assert!(!Level::Info.shown(true, 0));
+    assert!(!Level::Warning.shown(true, 3));
+    assert!(Level::Error.shown(true, 0));
+    assert!(!Level::Debug.shown(false, 1));
+    assert!(Level::Debug.shown(false, 2));
+    assert!(!Level::Debug.shown(true, 3));
+}
+
+#[test]
+fn test_strip_symbols() {
+    assert_eq!(
+        strip_symbols("✅ Resolved 3 packages"),
+        "Resolved 3 packages"
+    );
+    assert_eq!(strip_symbols("⚠️  Moving a/b"), "Moving a/b");
+    assert_eq!(strip_symbols("Élan 1.0"), "Élan 1.0");
+    assert_eq!(strip_symbols("plain"), "plain");
+}
+
+fn empty_project() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("composer.json"), r#"{"require": {}}"#).unwrap();
+    dir
+}
+
+#[test]
+fn test_json_message_format() {
+    ensure_lectern_binary();
+    let dir = empty_project();
+    let output = std::process::Command::new(get_lectern_binary_path())
+        .args(["--message-format", "json", "install"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let messages: Vec<serde_json::Value> = stdout
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("every line is JSON"))
+        .collect();
+    assert!(
+        messages
+            .iter()
+            .any(|m| m["level"] == "success" && m["message"] == "Resolved 0 packages"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn test_quiet_install_prints_nothing() {
+    ensure_lectern_binary();
+    let dir = empty_project();
+    let output = std::process::Command::new(get_lectern_binary_path())
+        .args(["-q", "install"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(
+        output.stdout.is_empty(),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+    assert!(dir.path().join("composer.lock").exists());
+}
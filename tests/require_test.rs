@@ -0,0 +1,252 @@
+use lectern::commands::require::{
+    UpdateWith, add_requirement, parse_package_spec, recommended_constraint, remove_requirement,
+    resolve_prospective, resolve_required, resolve_requirement,
+};
+use lectern::models::model::{ComposerJson, Lock};
+use lectern::resolver::dependency_utils::generate_content_hash;
+use serde_json::json;
+
+#[test]
+fn test_parse_package_spec() {
+    assert_eq!(
+        parse_package_spec("monolog/monolog:^3.0"),
+        ("monolog/monolog".to_string(), Some("^3.0".to_string()))
+    );
+    assert_eq!(
+        parse_package_spec("monolog/monolog=3.5.0"),
+        ("monolog/monolog".to_string(), Some("3.5.0".to_string()))
+    );
+    assert_eq!(
+        parse_package_spec("monolog/monolog"),
+        ("monolog/monolog".to_string(), None)
+    );
+    assert_eq!(
+        parse_package_spec("monolog/monolog:"),
+        ("monolog/monolog".to_string(), None)
+    );
+}
+
+#[test]
+fn test_recommended_constraint() {
+    assert_eq!(recommended_constraint("3.5.2").as_deref(), Some("^3.5"));
+    assert_eq!(recommended_constraint("v2.0.0").as_deref(), Some("^2.0"));
+    assert_eq!(recommended_constraint("0.4.1").as_deref(), Some("^0.4.1"));
+    assert_eq!(recommended_constraint("dev-main"), None);
+}
+
+#[tokio::test]
+async fn test_require_defaults_to_latest_stable() {
+    // Metadata is served from the in-memory cache, so no network is needed
+    lectern::cache::cache_set_meta(
+        "p2:lectern-require/lib",
+        json!([{"version": "1.4.2"}, {"version": "2.1.0"}, {"version": "3.0.0-beta1"}]),
+    )
+    .await;
+
+    let (name, constraint) = resolve_requirement("lectern-require/lib", false)
+        .await
+        .unwrap();
+    assert_eq!(name, "lectern-require/lib");
+    assert_eq!(constraint, "^2.1");
+
+    // An explicit constraint is kept
+    let (_, constraint) = resolve_requirement("lectern-require/lib:~1.4", false)
+        .await
+        .unwrap();
+    assert_eq!(constraint, "~1.4");
+
+    // Platform packages aren't looked up
+    let (_, constraint) = resolve_requirement("ext-intl", false).await.unwrap();
+    assert_eq!(constraint, "*");
+}
+
+#[tokio::test]
+async fn test_require_suggests_similar_packages() {
+    lectern::cache::cache_set_meta("p2:lectern-require/mongolog", json!([])).await;
+    lectern::cache::cache_set_search(
+        "search:lectern-require/mongolog",
+        json!([{"name": "lectern-require/monolog"}, {"name": "lectern-require/mongodb"}]),
+    )
+    .await;
+
+    let err = resolve_requirement("lectern-require/mongolog", false)
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("not found"), "{err}");
+    assert!(err.contains("Did you mean"), "{err}");
+    assert!(err.contains("lectern-require/monolog"), "{err}");
+
+    // With custom repositories the package may live elsewhere
+    let (_, constraint) = resolve_requirement("lectern-require/mongolog", true)
+        .await
+        .unwrap();
+    assert_eq!(constraint, "*");
+}
+
+#[tokio::test]
+async fn test_resolve_prospective_before_writing() {
+    lectern::cache::cache_set_meta(
+        "p2:lectern-require/resolvable",
+        json!([{"version": "1.0.0"}, {"version": "2.1.0"}]),
+    )
+    .await;
+
+    let mut composer: ComposerJson =
+        serde_json::from_value(json!({"require": {"lectern-require/resolvable": "^2.0"}})).unwrap();
+    let (contents, lock) = resolve_prospective(&composer).await.unwrap();
+    assert_eq!(lock.packages[0].version, "2.1.0");
+    assert_eq!(lock.content_hash, generate_content_hash(&contents));
+
+    // A constraint nothing satisfies fails before anything could be saved
+    composer
+        .require
+        .insert("lectern-require/resolvable".to_string(), "^9.0".to_string());
+    let err = resolve_prospective(&composer)
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("No version satisfies"), "{err}");
+}
+
+#[tokio::test]
+async fn test_resolve_required_updates_only_what_it_may() {
+    lectern::cache::cache_set_meta(
+        "p2:lectern-rw/framework",
+        json!([
+            {"version": "1.0.0", "require": {"lectern-rw/log": "^1.0 || ^2.0"}},
+            {"version": "1.1.0", "require": {"lectern-rw/log": "^1.0 || ^2.0"}}
+        ]),
+    )
+    .await;
+    lectern::cache::cache_set_meta(
+        "p2:lectern-rw/log",
+        json!([{"version": "1.0.0"}, {"version": "2.0.0"}]),
+    )
+    .await;
+    lectern::cache::cache_set_meta(
+        "p2:lectern-rw/new",
+        json!([{"version": "1.0.0", "require": {"lectern-rw/log": "^2.0"}}]),
+    )
+    .await;
+    let lock: Lock = serde_json::from_value(json!({
+        "content-hash": "",
+        "packages": [
+            {"name": "lectern-rw/framework", "version": "1.0.0", "require": {"lectern-rw/log": "^1.0 || ^2.0"}},
+            {"name": "lectern-rw/log", "version": "1.0.0"}
+        ]
+    }))
+    .unwrap();
+    let required = vec!["lectern-rw/new".to_string()];
+    let version = |lock: &Lock, name: &str| {
+        lock.packages
+            .iter()
+            .find(|p| p.name == name)
+            .map(|p| p.version.clone())
+    };
+
+    let composer: ComposerJson = serde_json::from_value(json!({"require": {
+        "lectern-rw/framework": "^1.0",
+        "lectern-rw/new": "^1.0"
+    }}))
+    .unwrap();
+    // The locked log 1.0.0 doesn't fit the new package
+    assert!(
+        resolve_required(&composer, Some(&lock), &required, UpdateWith::Nothing)
+            .await
+            .is_err()
+    );
+    let (_, updated) =
+        resolve_required(&composer, Some(&lock), &required, UpdateWith::Dependencies)
+            .await
+            .unwrap();
+    assert_eq!(
+        version(&updated, "lectern-rw/log").as_deref(),
+        Some("2.0.0")
+    );
+    assert_eq!(
+        version(&updated, "lectern-rw/framework").as_deref(),
+        Some("1.0.0")
+    );
+
+    // Once log is a root requirement, only -W may move it
+    let mut composer = composer;
+    composer
+        .require
+        .insert("lectern-rw/log".to_string(), "^1.0 || ^2.0".to_string());
+    assert!(
+        resolve_required(&composer, Some(&lock), &required, UpdateWith::Dependencies)
+            .await
+            .is_err()
+    );
+    let (_, updated) = resolve_required(
+        &composer,
+        Some(&lock),
+        &required,
+        UpdateWith::AllDependencies,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        version(&updated, "lectern-rw/log").as_deref(),
+        Some("2.0.0")
+    );
+    assert_eq!(
+        version(&updated, "lectern-rw/framework").as_deref(),
+        Some("1.0.0")
+    );
+}
+
+#[test]
+fn test_add_requirement_moves_between_sections() {
+    let mut composer: ComposerJson = serde_json::from_value(json!({
+        "require": {"acme/tool": "^1.0"},
+        "require-dev": {"acme/debug": "^2.0"}
+    }))
+    .unwrap();
+
+    assert_eq!(
+        add_requirement(&mut composer, "acme/tool", "^1.1", true).as_deref(),
+        Some("^1.0")
+    );
+    assert!(composer.require.is_empty());
+    assert_eq!(composer.require_dev["acme/tool"], "^1.1");
+
+    assert_eq!(
+        add_requirement(&mut composer, "acme/debug", "^2.0", false).as_deref(),
+        Some("^2.0")
+    );
+    assert!(!composer.require_dev.contains_key("acme/debug"));
+
+    // Updating a constraint in the same section is not a move
+    assert_eq!(
+        add_requirement(&mut composer, "acme/debug", "^2.1", false),
+        None
+    );
+    assert_eq!(composer.require["acme/debug"], "^2.1");
+}
+
+#[test]
+fn test_remove_requirement_from_either_section() {
+    let mut composer: ComposerJson = serde_json::from_value(json!({
+        "require": {"acme/lib": "^1.0", "acme/both": "^1.0"},
+        "require-dev": {"acme/tool": "^1.0", "acme/both": "^1.0"}
+    }))
+    .unwrap();
+
+    assert_eq!(
+        remove_requirement(&mut composer, "acme/tool", false),
+        vec!["require-dev"]
+    );
+    // --dev leaves require alone
+    assert!(remove_requirement(&mut composer, "acme/lib", true).is_empty());
+    assert_eq!(
+        remove_requirement(&mut composer, "acme/both", false),
+        vec!["require", "require-dev"]
+    );
+    assert!(remove_requirement(&mut composer, "acme/missing", false).is_empty());
+    assert_eq!(
+        composer.require.keys().collect::<Vec<_>>(),
+        vec!["acme/lib"]
+    );
+}
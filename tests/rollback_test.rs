@@ -0,0 +1,152 @@
+use lectern::journal::{begin_install_operation, begin_operation, commit_operation, list_entries};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::TempDir;
+
+#[path = "common/mod.rs"]
+mod common;
+use common::{ensure_lectern_binary, get_lectern_binary_path};
+
+const EMPTY_LOCK: &str = r#"{"content-hash": "before", "packages": []}"#;
+const UPDATED_LOCK: &str = r#"{
+    "content-hash": "after",
+    "packages": [{"name": "psr/log", "version": "3.0.0"}]
+}"#;
+
+#[test]
+fn test_rollback_restores_previous_state() {
+    ensure_lectern_binary();
+
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    fs::write(dir.join("composer.json"), r#"{"require": {}}"#).unwrap();
+    fs::write(dir.join("composer.lock"), EMPTY_LOCK).unwrap();
+
+    // Simulate a completed `lectern require psr/log`
+    let mut entry = begin_operation(dir, "require").unwrap();
+    fs::write(
+        dir.join("composer.json"),
+        r#"{"require": {"psr/log": "^3.0"}}"#,
+    )
+    .unwrap();
+    fs::write(dir.join("composer.lock"), UPDATED_LOCK).unwrap();
+    fs::create_dir_all(dir.join("vendor/psr/log")).unwrap();
+    commit_operation(dir, &mut entry).unwrap();
+
+    let output = Command::new(get_lectern_binary_path())
+        .arg("rollback")
+        .current_dir(dir)
+        .output()
+        .expect("Failed to execute lectern rollback");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("require"), "{stdout}");
+    assert_eq!(
+        fs::read_to_string(dir.join("composer.json")).unwrap(),
+        r#"{"require": {}}"#
+    );
+    assert_eq!(
+        fs::read_to_string(dir.join("composer.lock")).unwrap(),
+        EMPTY_LOCK
+    );
+    assert!(!dir.join("vendor/psr/log").exists());
+    assert!(list_entries(dir).unwrap().is_empty());
+}
+
+#[test]
+fn test_rollback_without_journal() {
+    ensure_lectern_binary();
+
+    let temp_dir = TempDir::new().unwrap();
+    let output = Command::new(get_lectern_binary_path())
+        .arg("rollback")
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute lectern rollback");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success());
+    assert!(stdout.contains("Nothing to roll back"), "{stdout}");
+}
+
+/// Simulate an `update` that dropped the `dev/tool` path package, then roll it back
+fn roll_back_dropped_dev_package(dir: &Path, no_dev: bool) {
+    let tool = dir.join("tool-src");
+    fs::create_dir_all(&tool).unwrap();
+    fs::write(tool.join("Tool.php"), "<?php").unwrap();
+    let lock = serde_json::json!({
+        "content-hash": "before",
+        "packages": [],
+        "packages-dev": [{
+            "name": "dev/tool",
+            "version": "1.0.0",
+            "source": {"type": "path", "url": tool.to_str().unwrap(), "reference": ""}
+        }]
+    });
+    fs::write(
+        dir.join("composer.json"),
+        r#"{"require-dev": {"dev/tool": "*"}}"#,
+    )
+    .unwrap();
+    fs::write(dir.join("composer.lock"), lock.to_string()).unwrap();
+
+    let mut entry = begin_install_operation(dir, "update", no_dev).unwrap();
+    fs::write(dir.join("composer.json"), r#"{"require-dev": {}}"#).unwrap();
+    fs::write(dir.join("composer.lock"), EMPTY_LOCK).unwrap();
+    commit_operation(dir, &mut entry).unwrap();
+
+    let output = Command::new(get_lectern_binary_path())
+        .arg("rollback")
+        .current_dir(dir)
+        .output()
+        .expect("Failed to execute lectern rollback");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stdout}{stderr}");
+}
+
+#[test]
+fn test_rollback_reinstalls_dev_packages() {
+    ensure_lectern_binary();
+
+    let temp_dir = TempDir::new().unwrap();
+    roll_back_dropped_dev_package(temp_dir.path(), false);
+    assert!(temp_dir.path().join("vendor/dev/tool/Tool.php").exists());
+}
+
+#[test]
+fn test_rollback_of_no_dev_operation_skips_dev_packages() {
+    ensure_lectern_binary();
+
+    let temp_dir = TempDir::new().unwrap();
+    roll_back_dropped_dev_package(temp_dir.path(), true);
+    assert!(!temp_dir.path().join("vendor/dev/tool").exists());
+}
+
+#[test]
+fn test_failed_remove_restores_composer_json() {
+    ensure_lectern_binary();
+
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    // The package left behind can't be resolved, so the update after the removal fails
+    let composer_json =
+        r#"{"require": {"psr/log": "^3.0", "lectern-test/does-not-exist": "^1.0"}}"#;
+    fs::write(dir.join("composer.json"), composer_json).unwrap();
+
+    let output = Command::new(get_lectern_binary_path())
+        .args(["remove", "psr/log"])
+        .current_dir(dir)
+        .output()
+        .expect("Failed to execute lectern remove");
+
+    assert!(!output.status.success());
+    assert_eq!(
+        fs::read_to_string(dir.join("composer.json")).unwrap(),
+        composer_json
+    );
+    assert!(!dir.join("composer.lock").exists());
+    assert!(list_entries(dir).unwrap().is_empty());
+}
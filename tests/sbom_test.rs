@@ -0,0 +1,173 @@
+use lectern::commands::sbom::{cyclonedx, purl, rfc3339_utc, spdx};
+use lectern::models::model::{ComposerJson, Lock};
+use serde_json::{Value, json};
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+#[path = "common/mod.rs"]
+mod common;
+use common::{ensure_lectern_binary, get_lectern_binary_path};
+
+fn lock() -> Value {
+    json!({
+        "content-hash": "",
+        "packages": [
+            {"name": "monolog/monolog", "version": "3.5.0", "license": ["MIT"],
+             "require": {"php": ">=8.1", "psr/log": "^3.0"},
+             "dist": {"type": "zip", "url": "https://example.com/monolog.zip",
+                      "reference": "abc", "shasum": "0123456789abcdef0123456789abcdef01234567"},
+             "source": {"type": "git", "url": "https://github.com/Seldaek/monolog.git", "reference": "abc"}},
+            {"name": "psr/log", "version": "3.0.0", "license": ["MIT", "Apache-2.0"]}
+        ],
+        "packages-dev": [{"name": "phpunit/phpunit", "version": "10.5.0", "license": ["BSD-3-Clause"]}]
+    })
+}
+
+fn composer() -> ComposerJson {
+    serde_json::from_value(json!({
+        "name": "acme/app",
+        "license": ["proprietary"],
+        "require": {"php": "^8.2", "monolog/monolog": "^3.5"},
+        "require-dev": {"phpunit/phpunit": "^10.5"}
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_purl_and_timestamps() {
+    assert_eq!(purl("psr/log", "3.0.0"), "pkg:composer/psr/log@3.0.0");
+    assert_eq!(
+        purl("acme/app", "dev-main#abc"),
+        "pkg:composer/acme/app@dev-main%23abc"
+    );
+    assert_eq!(rfc3339_utc(0), "1970-01-01T00:00:00Z");
+    assert_eq!(rfc3339_utc(951_782_400), "2000-02-29T00:00:00Z");
+    assert_eq!(rfc3339_utc(1_700_000_000), "2023-11-14T22:13:20Z");
+}
+
+#[test]
+fn test_cyclonedx_document() {
+    let lock: Lock = serde_json::from_value(lock()).unwrap();
+    let bom = cyclonedx(&lock, Some(&composer()), true, "2024-01-01T00:00:00Z");
+
+    assert_eq!(bom["bomFormat"], "CycloneDX");
+    assert_eq!(bom["metadata"]["component"]["name"], "acme/app");
+    assert_eq!(
+        bom["metadata"]["component"]["licenses"],
+        json!([{"license": {"name": "proprietary"}}])
+    );
+    let components = bom["components"].as_array().unwrap();
+    assert_eq!(components.len(), 3);
+    let monolog = &components[0];
+    assert_eq!(monolog["group"], "monolog");
+    assert_eq!(monolog["purl"], "pkg:composer/monolog/monolog@3.5.0");
+    assert_eq!(
+        monolog["hashes"],
+        json!([{"alg": "SHA-1", "content": "0123456789abcdef0123456789abcdef01234567"}])
+    );
+    assert_eq!(monolog["externalReferences"][1]["type"], "vcs");
+    assert_eq!(
+        components[1]["licenses"],
+        json!([{"expression": "MIT OR Apache-2.0"}])
+    );
+    assert_eq!(components[2]["scope"], "optional");
+
+    // Platform requirements are not dependencies
+    assert_eq!(
+        bom["dependencies"][0]["dependsOn"],
+        json!([
+            "pkg:composer/monolog/monolog@3.5.0",
+            "pkg:composer/phpunit/phpunit@10.5.0"
+        ])
+    );
+    assert_eq!(
+        bom["dependencies"][1]["dependsOn"],
+        json!(["pkg:composer/psr/log@3.0.0"])
+    );
+
+    // The same lock always gets the same serial number
+    let again = cyclonedx(&lock, Some(&composer()), true, "2025-01-01T00:00:00Z");
+    assert_eq!(bom["serialNumber"], again["serialNumber"]);
+    let no_dev = cyclonedx(&lock, Some(&composer()), false, "2024-01-01T00:00:00Z");
+    assert_eq!(no_dev["components"].as_array().unwrap().len(), 2);
+    assert_ne!(bom["serialNumber"], no_dev["serialNumber"]);
+}
+
+#[test]
+fn test_spdx_document() {
+    let lock: Lock = serde_json::from_value(lock()).unwrap();
+    let doc = spdx(&lock, Some(&composer()), true, "2024-01-01T00:00:00Z");
+
+    assert_eq!(doc["spdxVersion"], "SPDX-2.3");
+    assert_eq!(doc["creationInfo"]["created"], "2024-01-01T00:00:00Z");
+    let packages = doc["packages"].as_array().unwrap();
+    assert_eq!(packages.len(), 4);
+    assert_eq!(packages[0]["licenseDeclared"], "LicenseRef-proprietary");
+    let monolog = &packages[1];
+    assert_eq!(monolog["SPDXID"], "SPDXRef-Package-monolog-monolog");
+    assert_eq!(
+        monolog["downloadLocation"],
+        "https://example.com/monolog.zip"
+    );
+    assert_eq!(monolog["checksums"][0]["algorithm"], "SHA1");
+    assert_eq!(
+        monolog["externalRefs"][0]["referenceLocator"],
+        "pkg:composer/monolog/monolog@3.5.0"
+    );
+    assert_eq!(packages[2]["licenseDeclared"], "(MIT OR Apache-2.0)");
+    assert_eq!(packages[2]["downloadLocation"], "NOASSERTION");
+
+    let relationships = doc["relationships"].as_array().unwrap();
+    assert!(relationships.contains(&json!({
+        "spdxElementId": "SPDXRef-Package-phpunit-phpunit",
+        "relationshipType": "DEV_DEPENDENCY_OF",
+        "relatedSpdxElement": "SPDXRef-RootPackage"
+    })));
+    assert!(relationships.contains(&json!({
+        "spdxElementId": "SPDXRef-Package-monolog-monolog",
+        "relationshipType": "DEPENDS_ON",
+        "relatedSpdxElement": "SPDXRef-Package-psr-log"
+    })));
+}
+
+#[test]
+fn test_sbom_command_writes_file() {
+    ensure_lectern_binary();
+
+    let temp_dir = TempDir::new().unwrap();
+    let temp_path = temp_dir.path();
+    fs::write(temp_path.join("composer.lock"), lock().to_string()).unwrap();
+    fs::write(
+        temp_path.join("composer.json"),
+        r#"{"name": "acme/app", "require": {"monolog/monolog": "^3.5"}}"#,
+    )
+    .unwrap();
+
+    let output = Command::new(get_lectern_binary_path())
+        .args([
+            "sbom",
+            "--format",
+            "spdx-json",
+            "--no-dev",
+            "-o",
+            "sbom.spdx.json",
+        ])
+        .env("SOURCE_DATE_EPOCH", "1700000000")
+        .current_dir(temp_path)
+        .output()
+        .expect("Failed to execute lectern sbom");
+    assert!(output.status.success());
+    let doc: Value =
+        serde_json::from_str(&fs::read_to_string(temp_path.join("sbom.spdx.json")).unwrap())
+            .unwrap();
+    assert_eq!(doc["creationInfo"]["created"], "2023-11-14T22:13:20Z");
+    assert_eq!(doc["packages"].as_array().unwrap().len(), 3);
+
+    let output = Command::new(get_lectern_binary_path())
+        .args(["sbom", "--format", "xml"])
+        .current_dir(temp_path)
+        .output()
+        .expect("Failed to execute lectern sbom");
+    assert!(!output.status.success());
+}
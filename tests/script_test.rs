@@ -120,3 +120,205 @@ fn test_run_script_no_scripts() {
     // Should run without crashing (may fail or show message)
     assert!(output.status.code().is_some());
 }
+
+fn run_script_in(dir: &std::path::Path, args: &[&str]) -> std::process::Output {
+    ensure_lectern_binary();
+    Command::new(get_lectern_binary_path())
+        .arg("run-script")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .expect("Failed to execute lectern run-script")
+}
+
+#[test]
+fn test_parse_script_command_prefixes() {
+    use lectern::commands::script::{ScriptCommand, parse_script_command};
+
+    let args = vec!["--filter".to_string(), "a b".to_string()];
+    assert_eq!(
+        parse_script_command("phpunit", &args, "lectern"),
+        ScriptCommand::Shell("phpunit --filter 'a b'".into())
+    );
+    assert_eq!(
+        parse_script_command("phpunit @additional_args --stop", &args, "lectern"),
+        ScriptCommand::Shell("phpunit --filter 'a b' --stop".into())
+    );
+    assert_eq!(
+        parse_script_command("@composer install --no-dev", &[], "/bin/lectern"),
+        ScriptCommand::Shell("/bin/lectern install --no-dev".into())
+    );
+    assert_eq!(
+        parse_script_command("@test", &args, "lectern"),
+        ScriptCommand::Script("test".into())
+    );
+    assert_eq!(
+        parse_script_command("@putenv APP_ENV=test", &[], "lectern"),
+        ScriptCommand::PutEnv("APP_ENV".into(), "test".into())
+    );
+    if std::env::var_os("PHP_BINARY").is_none() {
+        assert_eq!(
+            parse_script_command("@php bin/console cache:clear", &[], "lectern"),
+            ScriptCommand::Shell("php bin/console cache:clear".into())
+        );
+    }
+}
+
+#[test]
+fn test_process_timeout_precedence() {
+    use lectern::commands::script::process_timeout;
+    use std::time::Duration;
+
+    let composer: lectern::models::model::ComposerJson =
+        serde_json::from_str(r#"{"config": {"process-timeout": 60}}"#).unwrap();
+    assert_eq!(
+        process_timeout(&composer, None, None),
+        Some(Duration::from_secs(60))
+    );
+    assert_eq!(
+        process_timeout(&composer, None, Some("10")),
+        Some(Duration::from_secs(10))
+    );
+    assert_eq!(
+        process_timeout(&composer, Some(5), Some("10")),
+        Some(Duration::from_secs(5))
+    );
+    assert_eq!(process_timeout(&composer, Some(0), None), None);
+
+    let composer: lectern::models::model::ComposerJson = serde_json::from_str("{}").unwrap();
+    assert_eq!(
+        process_timeout(&composer, None, None),
+        Some(Duration::from_secs(300))
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_script_exports_composer_env_and_bin_path() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let bin = temp_dir.path().join("vendor").join("bin");
+    fs::create_dir_all(&bin).unwrap();
+    fs::write(bin.join("greet"), "#!/bin/sh\necho \"greetings $1\"\n").unwrap();
+    fs::set_permissions(bin.join("greet"), fs::Permissions::from_mode(0o755)).unwrap();
+    fs::write(
+        temp_dir.path().join("composer.json"),
+        r#"{"scripts": {
+            "env": "echo dev=$COMPOSER_DEV_MODE root=$COMPOSER_ROOT_DIR",
+            "greet": "greet"
+        }}"#,
+    )
+    .unwrap();
+
+    let output = run_script_in(temp_dir.path(), &["env"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("dev=1"), "{stdout}");
+    assert!(
+        stdout.contains(&format!("root={}", temp_dir.path().display())),
+        "{stdout}"
+    );
+
+    let output = run_script_in(temp_dir.path(), &["--no-dev", "env"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("dev=0"));
+
+    // Arguments after `--` reach the command, found through vendor/bin
+    let output = run_script_in(temp_dir.path(), &["greet", "--", "--loudly"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("greetings --loudly"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_script_chains_references_and_stops_at_failures() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("composer.json"),
+        r#"{"scripts": {
+            "lint": "echo linting",
+            "check": ["@lint", "@putenv STAGE=tests", "echo stage=$STAGE", "false", "echo unreachable"],
+            "loop": "@loop"
+        }}"#,
+    )
+    .unwrap();
+
+    let output = run_script_in(temp_dir.path(), &["check"]);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("linting"), "{stdout}");
+    assert!(stdout.contains("stage=tests"), "{stdout}");
+    assert!(!stdout.contains("unreachable"), "{stdout}");
+
+    let output = run_script_in(temp_dir.path(), &["loop"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("references itself"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_script_composer_prefix_and_timeout() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("composer.json"),
+        r#"{
+            "config": {"process-timeout": 1},
+            "scripts": {"version": "@composer --version", "slow": "sleep 5"}
+        }"#,
+    )
+    .unwrap();
+
+    let output = run_script_in(temp_dir.path(), &["version"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains(env!("CARGO_PKG_VERSION")));
+
+    let started = std::time::Instant::now();
+    let output = run_script_in(temp_dir.path(), &["slow"]);
+    assert!(!output.status.success());
+    assert!(started.elapsed() < std::time::Duration::from_secs(4));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("timed out"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_scripts_run_as_top_level_commands() {
+    ensure_lectern_binary();
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("composer.json"),
+        r#"{
+            "scripts": {"test": "echo testing", "lint": "echo linting"},
+            "scripts-descriptions": {"test": "Run the test suite"}
+        }"#,
+    )
+    .unwrap();
+    let lectern = |args: &[&str]| {
+        Command::new(get_lectern_binary_path())
+            .args(args)
+            .current_dir(temp_dir.path())
+            .output()
+            .unwrap()
+    };
+
+    let output = lectern(&["test", "--", "--filter", "UserTest"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("testing --filter UserTest"));
+
+    // Unknown names suggest close commands and scripts
+    let output = lectern(&["instal"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Command 'instal' is not defined"), "{stderr}");
+    assert!(stderr.contains("install"), "{stderr}");
+    let output = lectern(&["lnit"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("lint"));
+
+    let output = lectern(&["list"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Available commands"), "{stdout}");
+    assert!(stdout.contains("require"), "{stdout}");
+    assert!(stdout.contains("Available scripts"), "{stdout}");
+    assert!(stdout.contains("Run the test suite"), "{stdout}");
+    assert!(stdout.contains("Runs the lint script as defined in composer.json"));
+}
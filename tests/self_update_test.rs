@@ -0,0 +1,182 @@
+use lectern::commands::self_update::{
+    asset_name, install_update, restore_backup, verify_checksum, verify_signature,
+};
+use ring::signature::{Ed25519KeyPair, KeyPair};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::process::Command;
+use tempfile::TempDir;
+
+#[path = "common/mod.rs"]
+mod common;
+use common::{ensure_lectern_binary, get_lectern_binary_path};
+
+#[test]
+fn test_verify_checksum() {
+    let digest = hex::encode(Sha256::digest(b"binary"));
+    assert!(verify_checksum(b"binary", &digest).is_ok());
+    assert!(
+        verify_checksum(
+            b"binary",
+            &format!("{}  lectern-linux-x86_64\n", digest.to_uppercase())
+        )
+        .is_ok()
+    );
+    let err = verify_checksum(b"tampered", &digest).unwrap_err();
+    assert!(err.to_string().contains("Checksum mismatch"));
+    assert!(verify_checksum(b"binary", "  ").is_err());
+}
+
+#[test]
+fn test_verify_signature() {
+    let rng = ring::rand::SystemRandom::new();
+    let pkcs8 = Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+    let key_pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+    let public_key = hex::encode(key_pair.public_key().as_ref());
+    let signature = hex::encode(key_pair.sign(b"binary").as_ref());
+
+    assert!(verify_signature(b"binary", &format!("{signature}\n"), &public_key).is_ok());
+    assert!(verify_signature(b"tampered", &signature, &public_key).is_err());
+    assert!(verify_signature(b"binary", "not hex", &public_key).is_err());
+}
+
+#[test]
+fn test_install_update_and_rollback() {
+    let temp_dir = TempDir::new().unwrap();
+    let exe = temp_dir.path().join("lectern");
+    let backups = temp_dir.path().join("backups");
+    fs::write(&exe, "v1").unwrap();
+
+    let backup = install_update(&exe, b"v2", &backups, "1.0.0").unwrap();
+    assert_eq!(fs::read_to_string(&exe).unwrap(), "v2");
+    assert_eq!(fs::read_to_string(&backup).unwrap(), "v1");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(
+            fs::metadata(&exe).unwrap().permissions().mode() & 0o777,
+            0o755
+        );
+    }
+
+    assert_eq!(restore_backup(&exe, &backups).unwrap(), "1.0.0");
+    assert_eq!(fs::read_to_string(&exe).unwrap(), "v1");
+    assert!(!backup.exists());
+    let err = restore_backup(&exe, &backups).unwrap_err();
+    assert!(err.to_string().contains("No previous version"));
+}
+
+/// Serve fixed responses by path over HTTP/1.1 until the test process exits. `routes`
+/// receives the server's base URL so responses can link back to it.
+fn serve(routes: impl FnOnce(&str) -> HashMap<String, Vec<u8>>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let routes = routes(&base);
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let mut reader = BufReader::new(&stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut header = String::new();
+            while reader.read_line(&mut header).unwrap() > 2 {
+                header.clear();
+            }
+            let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+            let (status, body) = match routes.get(path) {
+                Some(body) => ("200 OK", body.as_slice()),
+                None => ("404 Not Found", &[][..]),
+            };
+            let mut stream = &stream;
+            write!(
+                stream,
+                "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            )
+            .unwrap();
+            stream.write_all(body).unwrap();
+        }
+    });
+    base
+}
+
+#[cfg(unix)]
+#[test]
+fn test_self_update_command() {
+    ensure_lectern_binary();
+    let temp_dir = TempDir::new().unwrap();
+    let exe = temp_dir.path().join("lectern");
+    fs::copy(get_lectern_binary_path(), &exe).unwrap();
+    let original = fs::read(&exe).unwrap();
+
+    let name = asset_name();
+    let new_binary = b"#!/bin/sh\necho updated\n".to_vec();
+    let checksum = hex::encode(Sha256::digest(&new_binary));
+    let base = serve(|base| {
+        let release = |tag: &str, checksum: &str| {
+            serde_json::json!({
+                "tag_name": tag,
+                "assets": [
+                    {"name": name, "browser_download_url": format!("{base}/{name}")},
+                    {"name": format!("{name}.sha256"), "browser_download_url": format!("{base}/{checksum}")}
+                ]
+            })
+            .to_string()
+            .into_bytes()
+        };
+        HashMap::from([
+            (format!("/{name}"), new_binary.clone()),
+            (
+                "/good.sha256".to_string(),
+                format!("{checksum}  {name}").into_bytes(),
+            ),
+            ("/bad.sha256".to_string(), b"0000".to_vec()),
+            (
+                "/current".to_string(),
+                release(env!("CARGO_PKG_VERSION"), "good.sha256"),
+            ),
+            ("/tampered".to_string(), release("v99.0.0", "bad.sha256")),
+            ("/latest".to_string(), release("v99.0.0", "good.sha256")),
+        ])
+    });
+    let run_with = |release: &str, args: &[&str]| {
+        Command::new(&exe)
+            .arg("self-update")
+            .args(args)
+            .env("LECTERN_SELF_UPDATE_URL", format!("{base}/{release}"))
+            .env("XDG_CONFIG_HOME", temp_dir.path().join("config"))
+            .output()
+            .expect("Failed to execute lectern self-update")
+    };
+    // Test builds embed no signing key, so only --insecure installs anything
+    let run = |release: &str| run_with(release, &["--insecure"]);
+
+    let output = run_with("latest", &[]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("no release signing key"));
+    assert_eq!(fs::read(&exe).unwrap(), original);
+
+    let output = run("current");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("is the latest version"));
+
+    let output = run("tampered");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Checksum mismatch"));
+    assert_eq!(fs::read(&exe).unwrap(), original);
+
+    let output = run("latest");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(fs::read(&exe).unwrap(), new_binary);
+    let backup = temp_dir.path().join(format!(
+        "config/lectern/self-update/lectern-{}",
+        env!("CARGO_PKG_VERSION")
+    ));
+    assert_eq!(fs::read(backup).unwrap(), original);
+}
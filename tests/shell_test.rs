@@ -0,0 +1,89 @@
+use lectern::commands::shell::{Completions, project_packages};
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use tempfile::TempDir;
+
+#[path = "common/mod.rs"]
+mod common;
+use common::{ensure_lectern_binary, get_lectern_binary_path};
+
+fn project() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("composer.json"),
+        r#"{"name": "acme/app", "require": {"php": ">=8.1", "symfony/console": "^7.0"}}"#,
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("composer.lock"),
+        r#"{
+            "content-hash": "",
+            "packages": [
+                {"name": "symfony/console", "version": "7.0.0", "require": {"symfony/string": "^7.0"}},
+                {"name": "symfony/string", "version": "7.0.0"}
+            ],
+            "packages-dev": [{"name": "phpunit/phpunit", "version": "10.0.0"}]
+        }"#,
+    )
+    .unwrap();
+    temp_dir
+}
+
+#[test]
+fn test_shell_completions() {
+    let temp_dir = project();
+    assert_eq!(
+        project_packages(temp_dir.path()),
+        vec!["phpunit/phpunit", "symfony/console", "symfony/string"]
+    );
+
+    let completions = Completions::load(temp_dir.path());
+    let (start, candidates) = completions.complete("sh");
+    assert_eq!(start, 0);
+    assert_eq!(candidates, vec!["shell", "show"]);
+    assert_eq!(completions.complete("wh").1, vec!["why", "why-not"]);
+    assert!(completions.complete("ex").1.contains(&"exit".to_string()));
+
+    let (start, candidates) = completions.complete("why symfony/");
+    assert_eq!(start, 4);
+    assert_eq!(candidates, vec!["symfony/console", "symfony/string"]);
+    assert!(completions.complete("show --fu").1.is_empty());
+    assert!(completions.complete("require monolog/").1.is_empty());
+}
+
+#[test]
+fn test_shell_runs_commands_until_exit() {
+    ensure_lectern_binary();
+    let temp_dir = project();
+
+    let mut child = Command::new(get_lectern_binary_path())
+        .arg("shell")
+        .current_dir(temp_dir.path())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to execute lectern shell");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(
+            b"why symfony/string\nnot-a-command\n\"unbalanced\nshow --tree\nexit\nshow --tree\n",
+        )
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stdout.contains("symfony/console"), "{stdout}");
+    assert!(
+        stderr.contains("Command 'not-a-command' is not defined"),
+        "{stderr}"
+    );
+    assert!(stdout.contains("Unbalanced quotes") || stderr.contains("Unbalanced quotes"));
+    // Nothing after `exit` runs
+    assert_eq!(stdout.matches("└──symfony/string").count(), 1, "{stdout}");
+}
@@ -1,3 +1,5 @@
+use lectern::commands::show::collect_package_report;
+use lectern::io::{read_composer_json, read_lock};
 use std::process::Command;
 
 #[path = "common/mod.rs"]
@@ -57,3 +59,155 @@ fn test_show_package_details() {
         stdout.contains("symfony") || stdout.contains("Description") || stdout.contains("Version") || output.status.success()
     );
 }
+
+#[test]
+fn test_collect_package_report() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    let fixtures = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    std::fs::copy(fixtures.join("composer-2.7.lock"), dir.join("composer.lock")).unwrap();
+    std::fs::write(
+        dir.join("composer.json"),
+        r#"{"name": "acme/app", "require": {"psr/log": "^3.0"}}"#,
+    )
+    .unwrap();
+    std::fs::create_dir_all(dir.join("vendor/psr/log/src")).unwrap();
+    std::fs::write(dir.join("vendor/psr/log/src/LoggerInterface.php"), "<?php").unwrap();
+    std::fs::write(dir.join("vendor/psr/log/composer.json"), "{}").unwrap();
+
+    let composer = read_composer_json(&dir.join("composer.json")).unwrap();
+    let lock = read_lock(&dir.join("composer.lock")).unwrap();
+    let report = collect_package_report("psr/log", Some(&composer), Some(&lock), dir);
+
+    assert_eq!(report.locked.as_ref().unwrap().version, "3.0.0");
+    assert!(!report.is_dev);
+    assert_eq!(report.installed_files, Some(2));
+    assert_eq!(report.autoload_roots, vec!["psr-4: Psr\\Log\\".to_string()]);
+
+    let requirers: Vec<&str> = report.required_by.iter().map(|(n, _)| n.as_str()).collect();
+    assert_eq!(
+        requirers,
+        vec!["acme/app", "acme/internal-tools", "monolog/monolog"]
+    );
+
+    let dev = collect_package_report("fzaninotto/faker", None, Some(&lock), dir);
+    assert!(dev.is_dev);
+    assert_eq!(dev.installed_files, None);
+}
+
+#[test]
+fn test_show_tree_marks_cycles() {
+    ensure_lectern_binary();
+    let temp = tempfile::TempDir::new().unwrap();
+    let dir = temp.path();
+    std::fs::write(
+        dir.join("composer.json"),
+        r#"{"name": "acme/app", "require": {"acme/a": "^1.0"}}"#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("composer.lock"),
+        serde_json::json!({
+            "content-hash": "",
+            "packages": [
+                {"name": "acme/a", "version": "1.0.0", "require": {"acme/b": "^1.0"}},
+                {"name": "acme/b", "version": "1.0.0", "require": {"acme/a": "^1.0"}}
+            ],
+            "packages-dev": []
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    let output = Command::new(get_lectern_binary_path())
+        .args(["show", "--tree"])
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(
+        "acme/a 1.0.0\n└──acme/b ^1.0\n   └──acme/a ^1.0 (circular dependency aborted here)"
+    ));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Circular dependency: acme/a → acme/b → acme/a"));
+}
+
+#[test]
+fn test_remote_versions_grouped_and_described() {
+    use lectern::commands::show::{describe_version, find_version, group_versions};
+    use lectern::resolver::packagist::P2Version;
+
+    let versions: Vec<P2Version> = serde_json::from_value(serde_json::json!([
+        {"version": "v2.1.0-RC1", "version_normalized": "2.1.0.0-RC1"},
+        {
+            "version": "v2.0.0",
+            "version_normalized": "2.0.0.0",
+            "description": "A tool",
+            "license": ["MIT"],
+            "authors": [{"name": "Jane Doe", "email": "jane@example.com"}],
+            "require": {"php": ">=8.1"},
+            "support": {"issues": "https://example.com/issues"},
+            "suggest": {"acme/extra": "For extra features"}
+        },
+        {"version": "dev-main", "version_normalized": "dev-main"},
+        {"version": "v1.0.0", "version_normalized": "1.0.0.0"},
+        {"version": "v1.10.0", "version_normalized": "1.10.0.0"},
+        {"version": "v2.1.0-rc2", "version_normalized": "2.1.0.0-RC2"}
+    ]))
+    .unwrap();
+
+    let (stable, prerelease) = group_versions(&versions);
+    assert_eq!(stable, vec!["v2.0.0", "v1.10.0", "v1.0.0"]);
+    assert_eq!(prerelease, vec!["v2.1.0-rc2", "v2.1.0-RC1", "dev-main"]);
+
+    let found = find_version(&versions, "2.0.0").unwrap();
+    assert_eq!(found.version, "v2.0.0");
+    assert!(find_version(&versions, "3.0.0").is_none());
+
+    let lines = describe_version("acme/tool", found);
+    for expected in [
+        "📦 Package: acme/tool v2.0.0",
+        "⚖️  License: MIT",
+        "   • Jane Doe <jane@example.com>",
+        "   • php >=8.1",
+        "   • issues: https://example.com/issues",
+        "   • acme/extra: For extra features",
+    ] {
+        assert!(lines.iter().any(|line| line == expected), "{lines:?}");
+    }
+}
+
+#[test]
+fn test_sort_newest_first_compares_versions_not_strings() {
+    use lectern::commands::show::sort_newest_first;
+
+    let mut versions = vec!["1.9.0", "dev-main", "1.10.0", "2.0.0-beta1", "v2.0.0"];
+    sort_newest_first(&mut versions);
+    assert_eq!(
+        versions,
+        ["v2.0.0", "2.0.0-beta1", "1.10.0", "1.9.0", "dev-main"]
+    );
+}
+
+#[test]
+fn test_show_platform_applies_config_platform() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    std::fs::write(
+        temp_dir.path().join("composer.json"),
+        r#"{"config": {"platform": {"php": "8.1.99", "ext-redis": "6.0.0"}}}"#,
+    )
+    .unwrap();
+    ensure_lectern_binary();
+    let output = Command::new(get_lectern_binary_path())
+        .args(["show", "--platform"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute lectern show --platform");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("php"), "{stdout}");
+    assert!(stdout.contains("8.1.99"), "{stdout}");
+    assert!(stdout.contains("ext-redis"), "{stdout}");
+}
@@ -1,4 +1,5 @@
 use std::fs;
+use std::path::Path;
 use std::process::Command;
 use tempfile::TempDir;
 
@@ -45,8 +46,10 @@ fn test_status_no_lock() {
         .output()
         .expect("Failed to execute lectern status");
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    assert!(stdout.contains("composer.lock") || output.status.success());
+    // A missing lock is a failure, reported on stderr
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stderr.contains("composer.lock"));
 }
 
 #[test]
@@ -71,3 +74,145 @@ fn test_status_with_empty_lock() {
     // Should run without crashing (may show empty or succeed)
     assert!(output.status.code().is_some());
 }
+
+fn dist_package(name: &str, url: &str, shasum: &str) -> lectern::models::model::LockedPackage {
+    serde_json::from_value(serde_json::json!({
+        "name": name,
+        "version": "1.0.0",
+        "dist": {"type": "zip", "url": url, "reference": "abc", "shasum": shasum}
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_package_status_against_dist_archive() {
+    use lectern::commands::status::{LocalChanges, PackageStatus, package_status};
+    use lectern::installer::installer_utils::{
+        extract_archive_from_bytes, get_cached_package_path,
+    };
+    use sha1::Digest;
+    use std::io::Write;
+
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    for (path, contents) in [
+        ("acme-widget-abc/src/Widget.php", "<?php class Widget {}"),
+        ("acme-widget-abc/README.md", "# Widget"),
+    ] {
+        zip.start_file(path, zip::write::SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(contents.as_bytes()).unwrap();
+    }
+    let archive = zip.finish().unwrap().into_inner();
+    let shasum = hex::encode(sha1::Sha1::digest(&archive));
+
+    let url = "http://127.0.0.1:9/lectern-status-test/widget.zip";
+    let cached = get_cached_package_path("acme/status-widget", "1.0.0", url);
+    fs::create_dir_all(cached.parent().unwrap()).unwrap();
+    fs::write(&cached, &archive).unwrap();
+
+    let temp_dir = TempDir::new().unwrap();
+    let vendor = temp_dir.path().join("vendor");
+    let installed = vendor.join("acme/status-widget");
+    extract_archive_from_bytes(&archive, &installed).unwrap();
+
+    let pkg = dist_package("acme/status-widget", url, &shasum);
+    assert_eq!(
+        package_status(&pkg, &vendor).unwrap(),
+        PackageStatus::Unchanged
+    );
+
+    fs::write(installed.join("src/Widget.php"), "<?php class Patched {}").unwrap();
+    fs::remove_file(installed.join("README.md")).unwrap();
+    fs::write(installed.join("src/Debug.php"), "<?php").unwrap();
+    assert_eq!(
+        package_status(&pkg, &vendor).unwrap(),
+        PackageStatus::Modified(LocalChanges {
+            modified: vec!["src/Widget.php".to_string()],
+            added: vec!["src/Debug.php".to_string()],
+            deleted: vec!["README.md".to_string()],
+            moved_to: None,
+        })
+    );
+
+    // An archive that fails the checksum is no baseline
+    let tampered = dist_package("acme/status-widget", url, &"0".repeat(40));
+    assert!(matches!(
+        package_status(&tampered, &vendor).unwrap(),
+        PackageStatus::Unknown(_)
+    ));
+    let uncached = dist_package("acme/status-widget", "http://127.0.0.1:9/none.zip", "");
+    assert!(matches!(
+        package_status(&uncached, &vendor).unwrap(),
+        PackageStatus::Unknown(_)
+    ));
+    let missing = dist_package("acme/not-installed", url, "");
+    assert_eq!(
+        package_status(&missing, &vendor).unwrap(),
+        PackageStatus::NotInstalled
+    );
+
+    // `status -v` lists the changed files
+    ensure_lectern_binary();
+    let lock = serde_json::json!({"content-hash": "", "packages": [pkg], "packages-dev": []});
+    fs::write(temp_dir.path().join("composer.lock"), lock.to_string()).unwrap();
+    let output = Command::new(get_lectern_binary_path())
+        .args(["-v", "status"])
+        .current_dir(temp_dir.path())
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("acme/status-widget (1 modified, 1 added, 1 deleted)"));
+    assert!(stdout.contains("    M src/Widget.php"));
+    assert!(stdout.contains("    D README.md"));
+
+    let _ = fs::remove_file(&cached);
+}
+
+#[test]
+fn test_package_status_against_git_reference() {
+    use lectern::commands::status::{PackageStatus, package_status};
+
+    let temp_dir = TempDir::new().unwrap();
+    let vendor = temp_dir.path().join("vendor");
+    let checkout = vendor.join("acme/status-git");
+    fs::create_dir_all(&checkout).unwrap();
+    fs::write(checkout.join("Lib.php"), "<?php").unwrap();
+
+    let repo = git2::Repository::init(&checkout).unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("Lib.php")).unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+    let commit = repo
+        .commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+        .unwrap();
+    index.write().unwrap();
+
+    let locked = |reference: &str| -> lectern::models::model::LockedPackage {
+        serde_json::from_value(serde_json::json!({
+            "name": "acme/status-git",
+            "version": "dev-main",
+            "source": {"type": "git", "url": "https://example.com/status-git.git", "reference": reference}
+        }))
+        .unwrap()
+    };
+    let pkg = locked(&commit.to_string());
+    assert_eq!(
+        package_status(&pkg, &vendor).unwrap(),
+        PackageStatus::Unchanged
+    );
+
+    fs::write(checkout.join("Lib.php"), "<?php // patched").unwrap();
+    let PackageStatus::Modified(changes) = package_status(&pkg, &vendor).unwrap() else {
+        panic!("expected local modifications");
+    };
+    assert_eq!(changes.modified, vec!["Lib.php"]);
+    assert_eq!(changes.moved_to, None);
+
+    let PackageStatus::Modified(changes) =
+        package_status(&locked(&"1".repeat(40)), &vendor).unwrap()
+    else {
+        panic!("expected a moved checkout");
+    };
+    assert_eq!(changes.moved_to, Some(commit.to_string()));
+}
@@ -45,6 +45,107 @@ fn test_suggests_no_lock() {
         .output()
         .expect("Failed to execute lectern suggests");
 
+    // A missing lock is a failure, reported on stderr
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stderr.contains("composer.lock"));
+}
+
+fn suggesting_lock() -> lectern::models::model::Lock {
+    serde_json::from_value(serde_json::json!({
+        "content-hash": "",
+        "packages": [
+            {"name": "acme/http", "version": "1.0.0", "suggest": {
+                "acme/cache": "For response caching",
+                "acme/log": "To log requests",
+                "ext-curl": "For the curl transport"
+            }},
+            {"name": "acme/log", "version": "1.0.0", "provide": {"psr/log-implementation": "1.0"}},
+            {"name": "acme/mail", "version": "1.0.0", "suggest": {
+                "acme/cache": "To cache templates",
+                "psr/log-implementation": "To log deliveries"
+            }}
+        ],
+        "packages-dev": [
+            {"name": "acme/test", "version": "1.0.0", "suggest": {"acme/mock": "For mocks"}}
+        ]
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_collect_suggestions_hides_installed() {
+    use lectern::commands::suggests::collect_suggestions;
+
+    let lock = suggesting_lock();
+    let platform = std::collections::BTreeSet::from(["ext-curl".to_string()]);
+    let suggestions = collect_suggestions(&lock, None, false, &platform);
+    let names: Vec<_> = suggestions
+        .iter()
+        .map(|s| (s.from.as_str(), s.package.as_str()))
+        .collect();
+    assert_eq!(
+        names,
+        vec![("acme/http", "acme/cache"), ("acme/mail", "acme/cache")]
+    );
+
+    let suggestions = collect_suggestions(&lock, None, true, &Default::default());
+    let names: Vec<_> = suggestions.iter().map(|s| s.package.as_str()).collect();
+    assert_eq!(
+        names,
+        vec!["acme/cache", "ext-curl", "acme/cache", "acme/mock"]
+    );
+}
+
+#[test]
+fn test_suggestion_lines_grouping() {
+    use lectern::commands::suggests::{collect_suggestions, suggestion_lines};
+
+    let lock = suggesting_lock();
+    let suggestions = collect_suggestions(&lock, None, true, &Default::default());
+
+    let by_package = suggestion_lines(&suggestions, false, false, false);
+    assert_eq!(by_package[0], "📦 acme/http suggests:");
+    assert!(by_package.contains(&"    • acme/cache: To cache templates".to_string()));
+
+    let by_suggestion = suggestion_lines(&suggestions, false, true, false);
+    assert_eq!(by_suggestion[0], "💡 acme/cache is suggested by:");
+    assert_eq!(by_suggestion[1], "    • acme/http: For response caching");
+    assert_eq!(by_suggestion[2], "    • acme/mail: To cache templates");
+    assert!(!by_suggestion.iter().any(|line| line.contains("suggests:")));
+
+    let both = suggestion_lines(&suggestions, true, true, false);
+    assert_eq!(both.len(), by_package.len() + by_suggestion.len());
+
+    let list = suggestion_lines(&suggestions, false, false, true);
+    assert_eq!(list, vec!["acme/cache", "acme/mock", "ext-curl"]);
+}
+
+#[test]
+fn test_suggests_list_output() {
+    ensure_lectern_binary();
+
+    let temp_dir = TempDir::new().unwrap();
+    let temp_path = temp_dir.path();
+    fs::write(
+        temp_path.join("composer.json"),
+        r#"{"require": {"acme/http": "^1.0"}}"#,
+    )
+    .unwrap();
+    fs::write(
+        temp_path.join("composer.lock"),
+        serde_json::to_string(&suggesting_lock()).unwrap(),
+    )
+    .unwrap();
+
+    let output = Command::new(get_lectern_binary_path())
+        .args(["suggests", "--list"])
+        .current_dir(temp_path)
+        .output()
+        .expect("Failed to execute lectern suggests");
+
+    assert!(output.status.success());
     let stdout = String::from_utf8_lossy(&output.stdout);
-    assert!(stdout.contains("composer.lock") || output.status.success());
+    assert!(stdout.lines().any(|line| line == "acme/cache"), "{stdout}");
+    assert!(!stdout.contains("acme/mock"), "{stdout}");
 }
@@ -0,0 +1,163 @@
+use lectern::commands::sync::{SyncReason, plan_sync};
+use lectern::installer::InstallState;
+use lectern::installer::installer_utils::{extract_archive_from_bytes, get_cached_package_path};
+use lectern::models::model::{Lock, LockedPackage};
+use serde_json::json;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::Command;
+use tempfile::TempDir;
+
+#[path = "common/mod.rs"]
+mod common;
+use common::{ensure_lectern_binary, get_lectern_binary_path};
+
+fn archive() -> Vec<u8> {
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    zip.start_file(
+        "acme-lib-abc/src/Lib.php",
+        zip::write::SimpleFileOptions::default(),
+    )
+    .unwrap();
+    zip.write_all(b"<?php class Lib {}").unwrap();
+    zip.finish().unwrap().into_inner()
+}
+
+fn url(name: &str) -> String {
+    // Nothing listens here; packages are installed from the archive cache
+    format!("http://127.0.0.1:9/lectern-sync-test/{name}.zip")
+}
+
+fn package(name: &str, version: &str) -> LockedPackage {
+    serde_json::from_value(json!({
+        "name": name,
+        "version": version,
+        "dist": {"type": "zip", "url": url(name), "reference": "abc", "shasum": ""}
+    }))
+    .unwrap()
+}
+
+/// A project where `acme/<tag>-edited` was modified, `acme/<tag>-gone` deleted,
+/// `acme/<tag>-old` installed at another version and `acme/<tag>-stray` is not locked.
+/// Each test uses its own tag, so they don't share cached archives.
+fn project(tag: &str) -> (TempDir, Lock, Vec<PathBuf>) {
+    let name = |suffix: &str| format!("acme/{tag}-{suffix}");
+    let archive = archive();
+    let mut cached = Vec::new();
+    for name in [name("edited"), name("gone"), name("old")] {
+        let path = get_cached_package_path(&name, "1.0.0", &url(&name));
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, &archive).unwrap();
+        cached.push(path);
+    }
+
+    let temp_dir = TempDir::new().unwrap();
+    let project = temp_dir.path();
+    let vendor = project.join("vendor");
+    let lock: Lock = serde_json::from_value(json!({
+        "content-hash": "",
+        "packages": [package(&name("edited"), "1.0.0"), package(&name("gone"), "1.0.0")],
+        "packages-dev": [package(&name("old"), "1.0.0")]
+    }))
+    .unwrap();
+    fs::write(
+        project.join("composer.lock"),
+        serde_json::to_string(&lock).unwrap(),
+    )
+    .unwrap();
+    fs::write(project.join("composer.json"), r#"{"name": "acme/app"}"#).unwrap();
+
+    let mut state = InstallState::default();
+    for (name, version) in [(name("edited"), "1.0.0"), (name("old"), "0.9.0")] {
+        extract_archive_from_bytes(&archive, &vendor.join(&name)).unwrap();
+        state.record(&package(&name, version));
+    }
+    fs::write(
+        vendor.join(name("edited")).join("src/Lib.php"),
+        "<?php // patched",
+    )
+    .unwrap();
+    fs::create_dir_all(vendor.join(name("stray")).join("src")).unwrap();
+    fs::create_dir_all(vendor.join("bin")).unwrap();
+    state.save(&vendor).unwrap();
+    (temp_dir, lock, cached)
+}
+
+#[test]
+fn test_plan_sync() {
+    let (temp_dir, lock, cached) = project("plan");
+    let project = temp_dir.path();
+
+    let plan = plan_sync(&lock, project, true).unwrap();
+    assert_eq!(
+        plan.reinstall,
+        vec![
+            ("acme/plan-edited".to_string(), SyncReason::Modified),
+            ("acme/plan-gone".to_string(), SyncReason::Missing),
+            ("acme/plan-old".to_string(), SyncReason::Outdated)
+        ]
+    );
+    assert_eq!(plan.remove, vec![project.join("vendor/acme/plan-stray")]);
+    assert!(plan.regenerate_autoload);
+
+    // Without dev packages an installed dev package is extraneous
+    let plan = plan_sync(&lock, project, false).unwrap();
+    assert_eq!(plan.reinstall.len(), 2);
+    assert_eq!(
+        plan.remove,
+        vec![
+            project.join("vendor/acme/plan-old"),
+            project.join("vendor/acme/plan-stray")
+        ]
+    );
+
+    for path in cached {
+        let _ = fs::remove_file(path);
+    }
+}
+
+#[test]
+fn test_sync_command_reconciles_vendor() {
+    ensure_lectern_binary();
+    let (temp_dir, lock, cached) = project("sync");
+    let project = temp_dir.path();
+    let vendor = project.join("vendor");
+
+    let run = |args: &[&str]| {
+        Command::new(get_lectern_binary_path())
+            .args(args)
+            .current_dir(project)
+            .output()
+            .expect("Failed to execute lectern sync")
+    };
+
+    let output = run(&["sync", "--dry-run"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("reinstall acme/sync-edited (locally modified)"));
+    assert!(stdout.contains("remove vendor/acme/sync-stray (not in composer.lock)"));
+    assert!(vendor.join("acme/sync-stray").exists());
+
+    let output = run(&["sync"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        fs::read_to_string(vendor.join("acme/sync-edited/src/Lib.php")).unwrap(),
+        "<?php class Lib {}"
+    );
+    assert!(vendor.join("acme/sync-gone/src/Lib.php").exists());
+    assert!(!vendor.join("acme/sync-stray").exists());
+    assert!(vendor.join("autoload.php").exists());
+    assert!(plan_sync(&lock, project, true).unwrap().is_empty());
+
+    let output = run(&["sync"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("already matches"));
+
+    for path in cached {
+        let _ = fs::remove_file(path);
+    }
+}
@@ -0,0 +1,120 @@
+use lectern::commands::third_party::{
+    NoticeFormat, collect_notice_entries, find_license_files, render_entry, render_notices,
+};
+use lectern::io::read_lock;
+use std::fs;
+use std::process::Command;
+use tempfile::TempDir;
+
+#[path = "common/mod.rs"]
+mod common;
+use common::{ensure_lectern_binary, get_lectern_binary_path};
+
+fn setup_project(dir: &std::path::Path) {
+    fs::write(dir.join("composer.json"), r#"{"name": "acme/app"}"#).unwrap();
+    fs::write(
+        dir.join("composer.lock"),
+        r#"{
+            "content-hash": "x",
+            "packages": [
+                {
+                    "name": "psr/log",
+                    "version": "3.0.0",
+                    "license": ["MIT"],
+                    "authors": [{"name": "PHP-FIG", "homepage": "https://www.php-fig.org/"}],
+                    "homepage": "https://github.com/php-fig/log"
+                },
+                {
+                    "name": "acme/no-license",
+                    "version": "1.0.0",
+                    "authors": [{"name": "Jo Doe", "email": "jo@example.com"}]
+                }
+            ],
+            "packages-dev": [
+                {"name": "phpunit/phpunit", "version": "10.0.0", "license": ["BSD-3-Clause"]}
+            ]
+        }"#,
+    )
+    .unwrap();
+    fs::create_dir_all(dir.join("vendor/psr/log")).unwrap();
+    fs::write(
+        dir.join("vendor/psr/log/LICENSE"),
+        "Copyright (c) 2012 PHP Framework Interoperability Group\n",
+    )
+    .unwrap();
+    fs::write(dir.join("vendor/psr/log/README.md"), "# log").unwrap();
+    fs::create_dir_all(dir.join("vendor/acme/no-license")).unwrap();
+}
+
+#[test]
+fn test_find_license_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let pkg = temp_dir.path();
+    for name in ["LICENSE.md", "COPYING", "notice.txt", "README.md", "src"] {
+        fs::write(pkg.join(name), "x").unwrap();
+    }
+
+    let names: Vec<String> = find_license_files(pkg)
+        .iter()
+        .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+        .collect();
+    assert_eq!(names, vec!["COPYING", "LICENSE.md", "notice.txt"]);
+    assert!(find_license_files(&pkg.join("missing")).is_empty());
+}
+
+#[test]
+fn test_collect_and_render_notices() {
+    let temp_dir = TempDir::new().unwrap();
+    setup_project(temp_dir.path());
+    let lock = read_lock(&temp_dir.path().join("composer.lock")).unwrap();
+
+    let entries = collect_notice_entries(&lock, temp_dir.path(), false);
+    let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, vec!["acme/no-license", "psr/log"]);
+    assert_eq!(entries[0].authors, vec!["Jo Doe <jo@example.com>"]);
+    assert_eq!(entries[1].license_texts.len(), 1);
+
+    let with_dev = collect_notice_entries(&lock, temp_dir.path(), true);
+    assert_eq!(with_dev.len(), 3);
+
+    let rendered = render_entry(
+        &entries[1],
+        "{name}@{version} [{license}] by {authors} ({homepage}): {license_text}",
+    );
+    assert_eq!(
+        rendered,
+        "psr/log@3.0.0 [MIT] by PHP-FIG (https://github.com/php-fig/log): \
+         Copyright (c) 2012 PHP Framework Interoperability Group"
+    );
+
+    let markdown = render_notices(&entries, "acme/app", NoticeFormat::Markdown, None);
+    assert!(markdown.starts_with("# Third-party notices\n\nacme/app includes"));
+    assert!(markdown.contains("## psr/log 3.0.0"));
+    assert!(markdown.contains("(no license file found in package)"));
+}
+
+#[test]
+fn test_third_party_file_command() {
+    ensure_lectern_binary();
+    let temp_dir = TempDir::new().unwrap();
+    setup_project(temp_dir.path());
+
+    let output = Command::new(get_lectern_binary_path())
+        .args(["third-party-file", "--output", "NOTICES.txt"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute lectern third-party-file");
+    assert!(output.status.success());
+
+    let notices = fs::read_to_string(temp_dir.path().join("NOTICES.txt")).unwrap();
+    assert!(notices.starts_with("THIRD-PARTY NOTICES"));
+    assert!(notices.contains("psr/log 3.0.0\nLicense: MIT"));
+    assert!(!notices.contains("phpunit/phpunit"));
+
+    let stderr_and_out = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(stderr_and_out.contains("acme/no-license"));
+}
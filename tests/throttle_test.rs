@@ -0,0 +1,63 @@
+use lectern::throttle::{HttpLimits, HttpThrottle};
+use serde_json::json;
+use std::time::Duration;
+
+#[test]
+fn test_http_limits_sources() {
+    assert_eq!(
+        HttpLimits::from_env_with(|_| None, None),
+        HttpLimits {
+            max_parallel: 12,
+            max_per_host: 8
+        }
+    );
+
+    let config = json!({"max-parallel": 20, "max-per-host": 4});
+    assert_eq!(
+        HttpLimits::from_env_with(|_| None, Some(&config)),
+        HttpLimits {
+            max_parallel: 20,
+            max_per_host: 4
+        }
+    );
+
+    // The Composer variable wins over composer.json; invalid values are ignored
+    let env = |name: &str| match name {
+        "COMPOSER_MAX_PARALLEL_HTTP" => Some("3".to_string()),
+        "LECTERN_MAX_HTTP_PER_HOST" => Some("zero".to_string()),
+        _ => None,
+    };
+    assert_eq!(
+        HttpLimits::from_env_with(env, Some(&config)),
+        HttpLimits {
+            max_parallel: 3,
+            max_per_host: 3
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_throttled_host_gets_fewer_connections() {
+    let throttle = HttpThrottle::new(HttpLimits {
+        max_parallel: 12,
+        max_per_host: 4,
+    });
+    let url = "https://repo.packagist.org/p2/acme/pkg.json";
+
+    assert!(!throttle.report_status(url, 200));
+    assert!(!throttle.report_status(url, 404));
+    assert!(throttle.report_status(url, 429));
+    assert_eq!(throttle.host_limit(url), 2);
+    // Other hosts keep their own budget
+    assert_eq!(throttle.host_limit("https://api.github.com/repos"), 4);
+
+    let first = throttle.acquire(url).await;
+    let second = throttle.acquire(url).await;
+    assert!(first.is_some() && second.is_some());
+    let third = tokio::time::timeout(Duration::from_millis(50), throttle.acquire(url)).await;
+    assert!(third.is_err(), "a third connection was allowed");
+
+    assert!(throttle.report_status(url, 503));
+    assert_eq!(throttle.host_limit(url), 1);
+    assert!(!throttle.report_status(url, 503), "limit went below one");
+}
@@ -0,0 +1,82 @@
+use lectern::timeouts::{HttpTimeouts, RequestKind, Timeouts};
+use serde_json::json;
+use std::time::Duration;
+
+#[test]
+fn test_timeouts_default_per_kind() {
+    let timeouts = HttpTimeouts::from_env_with(|_| None, None);
+    assert_eq!(timeouts, HttpTimeouts::default());
+    assert!(timeouts.download.read > timeouts.metadata.read);
+    assert_eq!(
+        timeouts.for_url(
+            "https://repo.packagist.org/p2/acme/pkg.json",
+            RequestKind::Metadata
+        ),
+        Timeouts::METADATA
+    );
+    assert_eq!(
+        timeouts.for_url(
+            "https://codeload.github.com/acme/pkg",
+            RequestKind::Download
+        ),
+        Timeouts::DOWNLOAD
+    );
+}
+
+#[test]
+fn test_timeouts_config_env_and_host_overrides() {
+    let config = json!({
+        "metadata": {"connect": 2},
+        "download": {"connect": 20, "read": 600},
+        "hosts": {
+            "Slow.Example.com": {"download": {"read": 1800}},
+            "repo.internal": {"metadata": {"connect": 1, "read": 0}}
+        }
+    });
+    let env = |name: &str| (name == "LECTERN_METADATA_TIMEOUT").then(|| "45".to_string());
+    let timeouts = HttpTimeouts::from_env_with(env, Some(&config));
+
+    // The environment overrides the configured read timeout; zero is ignored
+    assert_eq!(
+        timeouts.metadata,
+        Timeouts {
+            connect: Duration::from_secs(2),
+            read: Duration::from_secs(45)
+        }
+    );
+    assert_eq!(
+        timeouts.download,
+        Timeouts {
+            connect: Duration::from_secs(20),
+            read: Duration::from_secs(600)
+        }
+    );
+
+    // Host entries only replace what they set, and only for that host
+    let slow = "https://slow.example.com/dist/acme-pkg.zip";
+    assert_eq!(
+        timeouts.for_url(slow, RequestKind::Download),
+        Timeouts {
+            connect: Duration::from_secs(20),
+            read: Duration::from_secs(1800)
+        }
+    );
+    assert_eq!(
+        timeouts.for_url(slow, RequestKind::Metadata),
+        timeouts.metadata
+    );
+    assert_eq!(
+        timeouts.for_url(
+            "http://repo.internal:8080/packages.json",
+            RequestKind::Metadata
+        ),
+        Timeouts {
+            connect: Duration::from_secs(1),
+            read: Duration::from_secs(45)
+        }
+    );
+    assert_eq!(
+        timeouts.for_url("https://fast.example.com/a.zip", RequestKind::Download),
+        timeouts.download
+    );
+}
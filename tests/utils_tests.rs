@@ -12,7 +12,10 @@ fn test_normalize_path_absolute() {
 fn test_normalize_path_relative() {
     let relative = "src/lib";
     let result = normalize_path(relative).unwrap();
-    assert!(result.is_absolute(), "Relative path should be converted to absolute");
+    assert!(
+        result.is_absolute(),
+        "Relative path should be converted to absolute"
+    );
 }
 
 #[test]
@@ -57,29 +60,109 @@ fn test_print_functions_dont_panic() {
 #[test]
 fn test_is_prerelease_version() {
     use lectern::utils::is_prerelease_version;
-    
+
     assert!(is_prerelease_version("1.0.0-alpha"));
     assert!(is_prerelease_version("2.0.0-beta.1"));
     assert!(is_prerelease_version("3.0.0-rc"));
     assert!(is_prerelease_version("v1.0.0-dev"));
-    
+
     assert!(!is_prerelease_version("1.0.0"));
     assert!(!is_prerelease_version("2.5.3"));
     assert!(!is_prerelease_version("v3.0.0"));
+    assert!(!is_prerelease_version("1.0.0-p1"));
+    assert!(!is_prerelease_version("1.0.0-patch2"));
+    assert!(is_prerelease_version("1.0.0-RC1"));
+    assert!(is_prerelease_version("2.0.0b2"));
 }
 
 #[test]
 fn test_version_comparison() {
     use lectern::utils::is_prerelease_version;
-    
+
     let stable_versions = vec!["1.0.0", "2.0.0", "3.5.7"];
     let prerelease_versions = vec!["1.0.0-alpha", "2.0.0-beta", "3.0.0-rc1"];
-    
+
     for version in stable_versions {
-        assert!(!is_prerelease_version(version), "{} should be stable", version);
+        assert!(
+            !is_prerelease_version(version),
+            "{} should be stable",
+            version
+        );
     }
-    
+
     for version in prerelease_versions {
-        assert!(is_prerelease_version(version), "{} should be prerelease", version);
+        assert!(
+            is_prerelease_version(version),
+            "{} should be prerelease",
+            version
+        );
     }
 }
+
+#[tokio::test]
+async fn test_output_is_tagged_with_package() {
+    use lectern::output::{package_prefix, with_package};
+
+    assert_eq!(package_prefix(), "");
+    let prefix = with_package("psr/log", async { package_prefix() }).await;
+    assert_eq!(prefix, "[psr/log] ");
+
+    // Tags follow the task, so concurrent installs keep their own
+    let handles: Vec<_> = ["a/one", "b/two"]
+        .into_iter()
+        .map(|name| tokio::spawn(with_package(name, async { package_prefix() })))
+        .collect();
+    let mut prefixes = Vec::new();
+    for handle in handles {
+        prefixes.push(handle.await.unwrap());
+    }
+    assert_eq!(prefixes, vec!["[a/one] ", "[b/two] "]);
+}
+
+#[test]
+fn test_output_group_markers() {
+    use lectern::ci::CiProvider;
+    use lectern::output::{group_end_marker, group_start_marker};
+
+    assert_eq!(
+        group_start_marker(Some(CiProvider::GitHubActions), "g", "Installing", 1),
+        Some("::group::Installing".to_string())
+    );
+    assert_eq!(
+        group_end_marker(Some(CiProvider::GitHubActions), "g", 2),
+        Some("::endgroup::".to_string())
+    );
+    assert_eq!(
+        group_start_marker(Some(CiProvider::GitLabCi), "lectern_0", "Installing", 10),
+        Some("\x1b[0Ksection_start:10:lectern_0[collapsed=true]\r\x1b[0KInstalling".to_string())
+    );
+    assert_eq!(
+        group_end_marker(Some(CiProvider::GitLabCi), "lectern_0", 12),
+        Some("\x1b[0Ksection_end:12:lectern_0\r\x1b[0K".to_string())
+    );
+    assert_eq!(
+        group_start_marker(Some(CiProvider::Generic), "g", "x", 0),
+        None
+    );
+    assert_eq!(group_end_marker(None, "g", 0), None);
+}
+
+#[test]
+fn test_print_from_many_threads() {
+    // Every message is written whole by the output writer
+    let threads: Vec<_> = (0..8)
+        .map(|i| std::thread::spawn(move || print_info(&format!("line {i}"))))
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+}
+
+#[test]
+fn test_similar_names() {
+    let commands = ["install", "update", "require", "status", "test"];
+    assert_eq!(similar_names("instal", commands), vec!["install"]);
+    assert_eq!(similar_names("tets", commands), vec!["test"]);
+    assert_eq!(similar_names("stat", commands), vec!["status"]);
+    assert!(similar_names("deploy", commands).is_empty());
+}
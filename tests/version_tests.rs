@@ -313,8 +313,8 @@ mod version_constraint_tests {
                 // Test with multiple versions
                 let test_versions = vec!["1.0.0", "1.2.0", "1.2.3", "1.2.5", "1.3.0"];
                 for version_str in test_versions {
-                    if let Ok(version) = Version::parse(version_str) {
-                        if restrictive.matches(&version) {
+                    if let Ok(version) = Version::parse(version_str)
+                        && restrictive.matches(&version) {
                             // Note: This property doesn't always hold due to our OR constraint behavior
                             // but we test it as documentation of the current behavior
                             let permissive_matches = permissive.matches(&version);
@@ -325,7 +325,6 @@ mod version_constraint_tests {
                                 "Constraint '{permissive_str}' matches '{version_str}': {permissive_matches}"
                             );
                         }
-                    }
                 }
             }
         }
@@ -502,4 +501,243 @@ mod version_constraint_tests {
             handle.join().expect("Thread should not panic");
         }
     }
+
+    #[test]
+    fn test_inline_aliases() {
+        use lectern::resolver::version::{
+            InlineAlias, alias_satisfies, normalize_alias, parse_inline_alias,
+        };
+
+        assert_eq!(
+            parse_inline_alias("dev-feature#abc123 as 1.5.0"),
+            Some(InlineAlias {
+                constraint: "dev-feature".to_string(),
+                alias: "1.5.0".to_string(),
+            })
+        );
+        assert_eq!(parse_inline_alias("^1.0"), None);
+        assert_eq!(parse_inline_alias(">=1.0, <2.0 as 1.5.0"), None);
+
+        // The alias doesn't change which versions the constraint selects
+        let constraint = parse_constraint("dev-main as 2.x-dev").unwrap();
+        assert!(constraint.matches(&Version::parse("999.0.0-dev").unwrap()));
+
+        assert_eq!(normalize_alias("1.5"), "1.5.0.0");
+        assert_eq!(normalize_alias("2.x-dev"), "2.9999999.9999999.9999999-dev");
+        assert_eq!(normalize_alias("2.1.x-dev"), "2.1.9999999.9999999-dev");
+
+        assert!(alias_satisfies("^1.5", "1.5.0"));
+        assert!(!alias_satisfies("^2.0", "1.5.0"));
+        assert!(alias_satisfies("^2.3", "2.x-dev"));
+        assert!(!alias_satisfies("^3.0", "2.x-dev"));
+    }
+
+    #[test]
+    fn test_stability_flags() {
+        use lectern::resolver::version::{satisfies, split_stability_flag, version_stability};
+
+        assert_eq!(
+            split_stability_flag("^1.0@beta"),
+            ("^1.0".to_string(), Some(10))
+        );
+        assert_eq!(split_stability_flag("@dev"), ("*".to_string(), Some(20)));
+        // The least stable flag wins
+        assert_eq!(
+            split_stability_flag("^1.0@RC || ^2.0@alpha"),
+            ("^1.0 || ^2.0".to_string(), Some(15))
+        );
+        assert_eq!(split_stability_flag("^1.0"), ("^1.0".to_string(), None));
+        assert!(parse_constraint("^2@dev").is_ok());
+
+        assert_eq!(version_stability("1.0.0"), 0);
+        assert_eq!(version_stability("v2.0.0-RC1"), 5);
+        assert_eq!(version_stability("2.0.0-beta.2"), 10);
+        assert_eq!(version_stability("1.0.0a1"), 15);
+        assert_eq!(version_stability("1.0.0-p1"), 0);
+        assert_eq!(version_stability("dev-main"), 20);
+        assert_eq!(version_stability("2.x-dev"), 20);
+
+        // Prereleases satisfy the constraints of their release
+        assert_eq!(satisfies("^1.0@beta", "1.1.0-beta1"), Some(true));
+        assert_eq!(satisfies("^1.0", "2.0.0-beta1"), Some(false));
+    }
+
+    /// The examples of Composer's "Writing Version Constraints" documentation, plus the
+    /// edges Composer's VersionParser defines for them
+    #[test]
+    fn test_composer_constraint_semantics() {
+        use lectern::resolver::Constraint;
+
+        let cases: &[(&str, &str, bool)] = &[
+            // Exact versions, including partial ones
+            ("1.0.2", "1.0.2", true),
+            ("1.0.2", "1.0.3", false),
+            ("1.0", "1.0.0", true),
+            ("1.0", "1.0.1", false),
+            ("v1.0.0", "1.0.0", true),
+            ("==1.0.0", "1.0.0", true),
+            ("!=1.0.1", "1.0.0", true),
+            ("!=1.0.1", "1.0.1", false),
+            ("<>1.0.1", "1.0.1", false),
+            // Ranges, with `,` or a space for AND and `||` for OR
+            (">=1.0", "1.0.0", true),
+            (">=1.0", "0.9.9", false),
+            (">=1.0 <1.1 || >=1.2", "1.0.5", true),
+            (">=1.0 <1.1 || >=1.2", "1.1.0", false),
+            (">=1.0 <1.1 || >=1.2", "3.0.0", true),
+            (">=1.2 <2.0", "1.9.9", true),
+            (">=1.2 <2.0", "2.0.0", false),
+            (">=1.2, <2.0", "1.1.9", false),
+            (">=1.0,!=1.0.1", "1.0.1", false),
+            (">1.0.0", "1.0.0", false),
+            ("<=1.2", "1.2.0", true),
+            // Hyphen ranges: a partial upper end covers its whole range
+            ("1.0 - 2.0", "1.0.0", true),
+            ("1.0 - 2.0", "2.0.5", true),
+            ("1.0 - 2.0", "2.1.0", false),
+            ("1.0 - 2.1", "2.1.9", true),
+            ("1.0 - 2.1", "2.2.0", false),
+            ("1.0.0 - 2.1.0", "2.1.0", true),
+            ("1.0.0 - 2.1.0", "2.1.1", false),
+            ("1.0.0 - 2.1.0", "0.9.9", false),
+            // Wildcards
+            ("*", "0.0.1", true),
+            ("1.0.*", "1.0.0", true),
+            ("1.0.*", "1.0.9", true),
+            ("1.0.*", "1.1.0", false),
+            ("2.*", "2.9.1", true),
+            ("2.*", "3.0.0", false),
+            ("2.*", "1.9.0", false),
+            ("1.x", "1.5.0", true),
+            ("1.x", "2.0.0", false),
+            ("1.* || 2.*", "2.3.0", true),
+            // Tilde: the last given part may increase
+            ("~1.2", "1.2.0", true),
+            ("~1.2", "1.9.0", true),
+            ("~1.2", "2.0.0", false),
+            ("~1.2", "1.1.0", false),
+            ("~1.2.3", "1.2.9", true),
+            ("~1.2.3", "1.3.0", false),
+            ("~1", "1.9.0", true),
+            ("~1", "2.0.0", false),
+            // Caret: up to the next significant release
+            ("^1.2.3", "1.2.3", true),
+            ("^1.2.3", "1.9.0", true),
+            ("^1.2.3", "1.2.2", false),
+            ("^1.2.3", "2.0.0", false),
+            ("^0.3", "0.3.9", true),
+            ("^0.3", "0.4.0", false),
+            ("^0.0.3", "0.0.3", true),
+            ("^0.0.3", "0.0.4", false),
+            ("^0", "0.9.0", true),
+            ("^0", "1.0.0", false),
+            // Prereleases sit below their release
+            (">=2.0", "2.0.0-beta1", true),
+            ("<2.0", "2.0.0-beta1", false),
+            ("<=2.0", "2.0.0-RC1", true),
+            (">2.0", "2.0.0-RC1", false),
+            ("^1.0", "1.1.0-alpha2", true),
+            (">=2.0.0-beta2", "2.0.0-beta1", false),
+            (">=2.0.0-beta2", "2.0.0-RC1", true),
+            ("2.0.0-RC1", "2.0.0-rc1", true),
+            // Branches
+            ("dev-main", "dev-main", true),
+            ("dev-main", "1.0.0", false),
+            ("^1.0", "dev-main", false),
+            ("*", "dev-main", true),
+            ("^2.0", "2.x-dev", true),
+            ("2.x-dev", "2.x-dev", true),
+            ("2.x-dev", "2.1.0", false),
+        ];
+        for (constraint, version, expected) in cases {
+            let parsed = Constraint::parse(constraint)
+                .unwrap_or_else(|e| panic!("'{constraint}' should parse: {e}"));
+            assert_eq!(
+                parsed.matches(version),
+                Some(*expected),
+                "'{constraint}' on {version}"
+            );
+        }
+
+        for invalid in ["^", ">=", "1.*.3", "1.0.0-foo", "x.y.z", "1.0.0.0.0"] {
+            assert!(
+                Constraint::parse(invalid).is_err(),
+                "'{invalid}' should not parse"
+            );
+        }
+        assert_eq!(
+            Constraint::parse("*").unwrap().matches("not a version"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_composer_version_ordering() {
+        use lectern::resolver::version::ComposerVersion;
+
+        let parse = |version: &str| ComposerVersion::parse(version).unwrap();
+        let ordered = [
+            "1.0.0-dev",
+            "1.0.0-alpha1",
+            "1.0.0-beta1",
+            "1.0.0-beta2",
+            "1.0.0-RC1",
+            "1.0.0",
+            "1.0.0.1",
+            "1.0.0.10",
+            "v1.0.1",
+            "1.10",
+            "1.x-dev",
+        ];
+        for pair in ordered.windows(2) {
+            assert!(parse(pair[0]) < parse(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+        assert_eq!(parse("1.2.3.0"), parse("v1.2.3"));
+        assert!(parse("1.0.0.1").is_stable());
+        assert!(!parse("1.0.0-RC1").is_stable());
+        assert_eq!(parse("1.0.0.1").to_semver(), Version::new(1, 0, 0));
+
+        for invalid in ["dev-main", "1.0.*", "latest", "1.2.3.4.5"] {
+            assert!(ComposerVersion::parse(invalid).is_none(), "{invalid}");
+        }
+        assert_eq!(lectern::resolver::satisfies("^1.0", "1.0.0.1"), Some(true));
+    }
+
+    #[test]
+    fn test_constraint_intersection() {
+        use lectern::resolver::Constraint;
+
+        let parse = |spec: &str| Constraint::parse(spec).unwrap();
+        let both = parse("^1.0 || ^2.0").intersect(&parse("<1.5 || >=2.3"));
+        for (version, expected) in [
+            ("1.4.9", true),
+            ("1.5.0", false),
+            ("2.2.0", false),
+            ("2.3.0", true),
+            ("3.0.0", false),
+        ] {
+            assert_eq!(both.matches(version), Some(expected), "{version}");
+        }
+        let disjoint = parse("^2.0").intersect(&parse("~3.0"));
+        assert_eq!(disjoint.matches("2.5.0"), Some(false));
+        assert_eq!(disjoint.matches("3.0.0"), Some(false));
+    }
+
+    #[test]
+    fn test_semver_translation_follows_composer() {
+        let matches = |constraint: &str, version: &str| {
+            parse_constraint(constraint)
+                .unwrap()
+                .matches(&Version::parse(version).unwrap())
+        };
+        assert!(matches("~1.2", "1.9.0"));
+        assert!(!matches("~1.2", "2.0.0"));
+        assert!(matches("1.0.*", "1.0.7"));
+        assert!(!matches("1.0.*", "1.1.0"));
+        assert!(matches("2.*", "2.4.0"));
+        assert!(matches("1.0 - 2.1", "2.1.5"));
+        assert!(!matches("1.0 - 2.1", "2.2.0"));
+        assert!(matches(">=1.2 <2.0", "1.5.0"));
+        assert!(!matches(">=1.2 <2.0", "2.0.0"));
+    }
 }
@@ -0,0 +1,183 @@
+use lectern::cli::WorkspaceInstallArgs;
+use lectern::commands::workspace::{LockState, workspace_statuses};
+use lectern::commands::{workspace_install, workspace_status};
+use lectern::models::model::Lock;
+use lectern::workspace::{combined_manifest, load_members, member_lock, relative_path};
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+fn write_json(path: &Path, value: &serde_json::Value) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, serde_json::to_string_pretty(value).unwrap()).unwrap();
+}
+
+/// A workspace with `packages/*` members, each given as (directory, composer.json)
+fn workspace(members: &[(&str, serde_json::Value)]) -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    write_json(
+        &temp_dir.path().join("composer.json"),
+        &json!({"extra": {"lectern": {"workspace": {"members": ["packages/*"]}}}}),
+    );
+    for (dir, composer) in members {
+        write_json(
+            &temp_dir
+                .path()
+                .join("packages")
+                .join(dir)
+                .join("composer.json"),
+            composer,
+        );
+    }
+    temp_dir
+}
+
+fn root_manifest(root: &Path) -> lectern::models::model::ComposerJson {
+    lectern::io::read_composer_json(&root.join("composer.json")).unwrap()
+}
+
+#[test]
+fn test_combined_manifest_merges_member_requirements() {
+    let temp_dir = workspace(&[
+        (
+            "a",
+            json!({"name": "acme/a", "require": {"acme/b": "*", "monolog/monolog": "^3.0"}}),
+        ),
+        (
+            "b",
+            json!({
+                "name": "acme/b",
+                "require": {"psr/log": "^3.0"},
+                "require-dev": {"monolog/monolog": "^3.2", "phpunit/phpunit": "^11.0"}
+            }),
+        ),
+    ]);
+    let root = temp_dir.path();
+    let members = load_members(root).unwrap();
+    assert_eq!(
+        members.iter().map(|m| m.label.as_str()).collect::<Vec<_>>(),
+        vec!["packages/a", "packages/b"]
+    );
+
+    let combined = combined_manifest(root_manifest(root), &members).unwrap();
+    assert_eq!(
+        combined.require.get("monolog/monolog").map(String::as_str),
+        Some("^3.0, ^3.2")
+    );
+    assert!(combined.require.contains_key("psr/log"));
+    assert!(!combined.require.contains_key("acme/b"));
+    assert_eq!(
+        combined.require_dev.keys().collect::<Vec<_>>(),
+        vec!["phpunit/phpunit"]
+    );
+}
+
+#[test]
+fn test_combined_manifest_rejects_alternatives_that_differ() {
+    let temp_dir = workspace(&[
+        (
+            "a",
+            json!({"name": "acme/a", "require": {"psr/log": "^1.0 || ^2.0"}}),
+        ),
+        (
+            "b",
+            json!({"name": "acme/b", "require": {"psr/log": "^3.0"}}),
+        ),
+    ]);
+    let root = temp_dir.path();
+    let members = load_members(root).unwrap();
+    let err = combined_manifest(root_manifest(root), &members).unwrap_err();
+    assert!(err.to_string().contains("^1.0 || ^2.0 by packages/a"));
+}
+
+#[test]
+fn test_relative_path() {
+    assert_eq!(
+        relative_path(Path::new("/ws/packages/a"), Path::new("/ws/packages/b")),
+        PathBuf::from("../b")
+    );
+    assert_eq!(
+        relative_path(Path::new("/ws/apps/web"), Path::new("/ws/packages/b")),
+        PathBuf::from("../../packages/b")
+    );
+}
+
+#[test]
+fn test_member_lock_keeps_what_the_member_reaches() {
+    let temp_dir = workspace(&[
+        ("a", json!({"name": "acme/a", "require": {"acme/b": "*"}})),
+        (
+            "b",
+            json!({
+                "name": "acme/b",
+                "require": {"psr/log": "^3.0"},
+                "require-dev": {"phpunit/phpunit": "^11.0"}
+            }),
+        ),
+    ]);
+    let members = load_members(temp_dir.path()).unwrap();
+    let lock: Lock = serde_json::from_value(json!({
+        "content-hash": "workspace",
+        "packages": [{"name": "psr/log", "version": "3.0.2"}],
+        "packages-dev": [{"name": "phpunit/phpunit", "version": "11.0.0"}]
+    }))
+    .unwrap();
+
+    let a = member_lock(&lock, &members, &members[0]);
+    let names: Vec<&str> = a.packages.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, vec!["psr/log", "acme/b"]);
+    assert!(a.packages_dev.is_empty());
+    let linked = &a.packages[1];
+    assert_eq!(linked.source.as_ref().unwrap().source_type, "path");
+    assert_eq!(linked.source.as_ref().unwrap().url, "../b");
+
+    let b = member_lock(&lock, &members, &members[1]);
+    assert_eq!(b.packages.len(), 1);
+    assert_eq!(b.packages_dev[0].name, "phpunit/phpunit");
+}
+
+#[tokio::test]
+async fn test_workspace_install_links_members() {
+    let temp_dir = workspace(&[
+        ("a", json!({"name": "acme/a", "require": {"acme/b": "*"}})),
+        (
+            "b",
+            json!({"name": "acme/b", "autoload": {"psr-4": {"Acme\\B\\": "src/"}}}),
+        ),
+    ]);
+    let root = temp_dir.path();
+    let args = WorkspaceInstallArgs {
+        dry_run: false,
+        prefer_source: false,
+    };
+
+    let (workspace, _) = workspace_statuses(root).unwrap();
+    assert_eq!(workspace, LockState::Missing);
+    assert!(!workspace_status(root).unwrap());
+
+    workspace_install(&args, root, false).await.unwrap();
+    let a = root.join("packages/a");
+    assert!(root.join("composer.workspace.lock").exists());
+    assert!(a.join("composer.lock").exists());
+    let linked = a.join("vendor/acme/b");
+    assert!(linked.join("composer.json").exists());
+    #[cfg(unix)]
+    assert!(fs::symlink_metadata(&linked).unwrap().is_symlink());
+    let psr4 = fs::read_to_string(a.join("vendor/composer/autoload_psr4.php")).unwrap();
+    assert!(psr4.contains("Acme\\\\B\\\\"));
+
+    let (workspace, statuses) = workspace_statuses(root).unwrap();
+    assert_eq!(workspace, LockState::Current);
+    assert_eq!(statuses[0].links, vec!["acme/b"]);
+    assert!(
+        statuses
+            .iter()
+            .all(|s| s.lock == LockState::Current && s.installed)
+    );
+    assert!(workspace_status(root).unwrap());
+
+    // A second install reuses the workspace lock and links again
+    workspace_install(&args, root, false).await.unwrap();
+    assert!(linked.join("composer.json").exists());
+}